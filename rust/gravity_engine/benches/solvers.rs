@@ -0,0 +1,36 @@
+use criterion::{BenchmarkId, Criterion, Throughput, criterion_group, criterion_main};
+use gravity_engine::SimulationEngine;
+use gravity_engine::bench_utils::{available_solvers, benchmark_config, generate_orbital_system};
+
+const TICKS_PER_ITERATION: u32 = 10;
+
+/// Throughput is body-steps (bodies x ticks) per second.
+fn solvers(c: &mut Criterion) {
+    let mut group = c.benchmark_group("step");
+    group.sample_size(10);
+    for body_count in [128, 512, 2000] {
+        group.throughput(Throughput::Elements(
+            body_count as u64 * u64::from(TICKS_PER_ITERATION),
+        ));
+        for solver in available_solvers() {
+            let config = benchmark_config(solver);
+            let bodies = generate_orbital_system(body_count, config.gravity_constant);
+            let mut engine = SimulationEngine::with_bodies(config, bodies)
+                .expect("benchmark engine should initialize");
+            // Warm-up to avoid including first-step initialization effects.
+            engine.step(200).expect("warm-up step should succeed");
+
+            group.bench_function(BenchmarkId::new(format!("{solver:?}"), body_count), |b| {
+                b.iter(|| {
+                    engine
+                        .step(TICKS_PER_ITERATION)
+                        .expect("benchmark stepping should succeed")
+                })
+            });
+        }
+    }
+    group.finish();
+}
+
+criterion_group!(benches, solvers);
+criterion_main!(benches);
@@ -0,0 +1,276 @@
+#ifndef GRAVITY_ENGINE_H
+#define GRAVITY_ENGINE_H
+
+/* Generated with cbindgen from rust/gravity_engine; regenerate with
+   `cbindgen --config cbindgen.toml --output include/gravity_engine.h`. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+// Machine-readable error codes shipped as `errorCode` in FFI responses. The
+// discriminants are part of the C ABI: codes are only ever appended.
+enum ErrorCode
+#ifdef __cplusplus
+  : uint32_t
+#endif  // __cplusplus
+  {
+  ErrorCode_InvalidConfig = 1,
+  ErrorCode_InvalidBody = 2,
+  ErrorCode_DuplicateBodyId = 3,
+  ErrorCode_BodyNotFound = 4,
+  ErrorCode_CheckpointNotFound = 5,
+  ErrorCode_NumericalInstability = 6,
+  ErrorCode_SchemaValidationFailed = 7,
+  ErrorCode_ChecksumMismatch = 8,
+  ErrorCode_EventQueueFull = 9,
+  ErrorCode_UnsupportedFeature = 10,
+  ErrorCode_WorkerFailed = 11,
+  ErrorCode_ExportFailed = 12,
+  // The engine is stepping on a background worker.
+  ErrorCode_Busy = 13,
+  // A null pointer, malformed JSON or otherwise unusable FFI argument.
+  ErrorCode_InvalidArgument = 100,
+  // No engine is registered under the handle.
+  ErrorCode_EngineNotFound = 101,
+  // A poisoned lock or another fault inside the FFI layer.
+  ErrorCode_Internal = 102,
+};
+#ifndef __cplusplus
+typedef uint32_t ErrorCode;
+#endif  // __cplusplus
+
+// Called by `gs_step_observed` after each tick with the tick, a flat
+// `[x0, y0, x1, y1, ...]` position array valid only for the duration of the call, the
+// body count and the host's `user_data`. Returning non-zero stops the batch.
+typedef int32_t (*TickCallback)(uint64_t, const double *, uintptr_t, void *);
+
+#ifdef __cplusplus
+extern "C" {
+#endif  // __cplusplus
+
+char *gs_initialize(const char *config_json, const char *bodies_json);
+
+char *gs_dispose(uint64_t handle);
+
+char *gs_set_config(uint64_t handle, const char *config_json);
+
+char *gs_update_config(uint64_t handle, const char *patch_json);
+
+char *gs_apply_edit(uint64_t handle, const char *edit_json);
+
+// Applies an array of edits atomically, returning the state once rather than per edit.
+char *gs_apply_edits(uint64_t handle, const char *edits_json);
+
+// `SimulationEngine::predict`: the engine behind `handle` is left untouched.
+char *gs_predict(uint64_t handle, const char *edits_json, uint32_t ticks, uint32_t sample_every);
+
+char *gs_step(uint64_t handle, uint32_t ticks);
+
+// Runs as many ticks as fit in `max_micros` of wall time, capped at `max_ticks`; see
+// `SimulationEngine::step_budget`.
+char *gs_step_budget(uint64_t handle, uint64_t max_micros, uint32_t max_ticks);
+
+// Queues `ticks` on a background worker and returns at once with the job id. The
+// engine stays on the worker, and other `gs_*` calls on the handle fail with `Busy`,
+// until `gs_poll_result` has returned every queued job.
+char *gs_step_async(uint64_t handle, uint32_t ticks);
+
+// Next finished `gs_step_async` job, shaped like a `gs_step` response plus `job`
+// and `pending`, or `{"job": null}` while it is still running.
+char *gs_poll_result(uint64_t handle);
+
+// `gs_step` that reports every `every`-th tick (and the last one) to `callback`
+// without returning to the host. The engine stays locked during the callback, so it
+// must not call back into `gs_*` functions on the same handle.
+char *gs_step_observed(uint64_t handle,
+                       uint32_t ticks,
+                       uint32_t every,
+                       TickCallback callback,
+                       void *user_data);
+
+// Render-loop variant of `gs_step`: flat `[x0, y0, x1, y1, ...]` position and velocity
+// arrays plus alive flags, in the engine's body order, without ids or config.
+char *gs_step_delta(uint64_t handle, uint32_t ticks);
+
+// Number of bodies (alive or not) in engine order, or -1 for an unknown handle.
+int64_t gs_body_count(uint64_t handle);
+
+char *gs_merge_history(uint64_t handle);
+
+char *gs_body_index_map(uint64_t handle);
+
+// Writes `[x0, y0, x1, y1, ...]` for every body, in engine order, into a caller-owned
+// buffer of `capacity` f64 values and returns the body count. Returns -1 for an
+// unknown handle or null buffer and -2 if `capacity` is below twice the body count.
+int64_t gs_get_positions(uint64_t handle, double *out_ptr, uintptr_t capacity);
+
+// Samples `SimulationEngine::sample_field` over `grid_json` and writes
+// `[phi0, ax0, ay0, phi1, ...]` per cell, in `GridSpec` order, into a caller-owned
+// buffer of `capacity` f64 values. Returns the cell count, -1 for an unknown handle,
+// null buffer or invalid grid, and -2 if `capacity` is below three per cell.
+int64_t gs_sample_field(uint64_t handle,
+                        const char *grid_json,
+                        double *out_ptr,
+                        uintptr_t capacity);
+
+char *gs_step_back(uint64_t handle, uint32_t ticks);
+
+char *gs_run_until(uint64_t handle, const char *condition_json, uint32_t max_ticks);
+
+// Sets whether mutating calls on this handle echo the full state (`"full"`, the
+// default) or only their acknowledgement and summary (`"summaryOnly"`).
+char *gs_set_response_mode(uint64_t handle, const char *mode_json);
+
+// Sets the events `gs_step` and `gs_run_until` ship for this handle; JSON `null`
+// clears the filter. The engine's own event log is unaffected.
+char *gs_set_event_filter(uint64_t handle, const char *filter_json);
+
+char *gs_json_schema(const char *name_json);
+
+char *gs_generate_cloud(uint64_t seed, const char *spec_json);
+
+char *gs_search_stable(const char *config_json, const char *search_json);
+
+char *gs_verify_replay(const char *scenario_json, const char *checkpoints_json);
+
+char *gs_state_hash(uint64_t handle);
+
+char *gs_set_paused(uint64_t handle, bool paused);
+
+char *gs_export_journal(uint64_t handle);
+
+// Replays into a new engine and returns its handle, like `gs_initialize`.
+char *gs_replay_journal(const char *journal_json);
+
+char *gs_get_state(uint64_t handle);
+
+char *gs_query_bodies(uint64_t handle, const char *query_json);
+
+char *gs_state_in_region(uint64_t handle, const char *region_json);
+
+char *gs_diagnostics(uint64_t handle);
+
+char *gs_jacobi_constants(uint64_t handle,
+                          const char *primary_id_json,
+                          const char *secondary_id_json);
+
+// Lagrange points of the pair, plus the effective potential over `grid_json` unless
+// it is the JSON `null`.
+char *gs_lagrange_map(uint64_t handle,
+                      const char *primary_id_json,
+                      const char *secondary_id_json,
+                      const char *grid_json);
+
+char *gs_lyapunov_exponent(uint64_t handle, const char *query_json);
+
+char *gs_barnes_hut_error(uint64_t handle, const char *sampling_json);
+
+char *gs_estimate_tick_cost(uint64_t handle);
+
+char *gs_mass_distribution(uint64_t handle, const char *options_json);
+
+char *gs_density_grid(uint64_t handle, const char *grid_json);
+
+char *gs_kinematics_grid(uint64_t handle, const char *grid_json);
+
+char *gs_group_diagnostics(uint64_t handle);
+
+char *gs_binaries(uint64_t handle);
+
+char *gs_collision_rate(uint64_t handle, const char *query_json);
+
+char *gs_body_derivatives(uint64_t handle, const char *ids_json);
+
+char *gs_sample_orbit(uint64_t handle,
+                      const char *primary_id_json,
+                      const char *body_id_json,
+                      uint32_t n_points);
+
+char *gs_create_checkpoint(uint64_t handle, const char *name_json);
+
+char *gs_add_marker(uint64_t handle, const char *name_json);
+
+char *gs_list_markers(uint64_t handle);
+
+char *gs_last_instability(uint64_t handle);
+
+char *gs_list_checkpoints(uint64_t handle);
+
+char *gs_restore_checkpoint(uint64_t handle, const char *name_json);
+
+char *gs_delete_checkpoint(uint64_t handle, const char *name_json);
+
+char *gs_configure_event_log(uint64_t handle, uint32_t capacity, const char *overflow_json);
+
+char *gs_set_checkpoint_capacity(uint64_t handle, uint32_t capacity);
+
+char *gs_softening_radius(uint64_t handle, double tolerance);
+
+char *gs_excursions(uint64_t handle);
+
+char *gs_load_scenario(uint64_t handle, const char *scenario_json);
+
+char *gs_save_scenario(uint64_t handle);
+
+char *gs_snapshot(uint64_t handle);
+
+char *gs_restore_snapshot(uint64_t handle, const char *snapshot_json);
+
+// Writes an owned buffer to `out_ptr`/`out_len`; release it with `gs_bytes_free`.
+char *gs_snapshot_binary(uint64_t handle, uint8_t **out_ptr, uintptr_t *out_len);
+
+char *gs_restore_snapshot_binary(uint64_t handle, const uint8_t *bytes_ptr, uintptr_t bytes_len);
+
+// Writes an owned buffer to `out_ptr`/`out_len`; release it with `gs_bytes_free`.
+char *gs_save_scenario_binary(uint64_t handle, uint8_t **out_ptr, uintptr_t *out_len);
+
+char *gs_load_scenario_binary(uint64_t handle, const uint8_t *bytes_ptr, uintptr_t bytes_len);
+
+char *gs_dt_schedule(uint64_t handle);
+
+char *gs_replay_dt_schedule(uint64_t handle, const char *schedule_json);
+
+char *gs_add_zone(uint64_t handle, const char *zone_json);
+
+char *gs_remove_zone(uint64_t handle, const char *name_json);
+
+char *gs_schedule_maneuver(uint64_t handle, const char *body_id_json, const char *maneuver_json);
+
+char *gs_list_maneuvers(uint64_t handle);
+
+char *gs_cancel_maneuver(uint64_t handle, uint64_t maneuver_id);
+
+char *gs_add_poincare_section(uint64_t handle, const char *section_json);
+
+char *gs_remove_poincare_section(uint64_t handle, const char *name_json);
+
+// Samples recorded so far by one section; `clear` empties it after reading.
+char *gs_poincare_samples(uint64_t handle, const char *name_json, bool clear);
+
+char *gs_watch_alignment(uint64_t handle, const char *watch_json);
+
+char *gs_predict_alignment(uint64_t handle, const char *watch_json, uint32_t max_ticks);
+
+char *gs_catalog_add(const char *scenario_json);
+
+char *gs_catalog_remove(uint64_t id);
+
+char *gs_catalog_get(uint64_t id);
+
+char *gs_catalog_search(const char *query_json);
+
+// Counts of FFI strings and byte buffers handed out and freed. Needs the
+// `ffi-audit` feature; the returned string is allocated after the counts are read.
+char *gs_debug_alloc_stats(void);
+
+void gs_string_free(char *ptr);
+
+void gs_bytes_free(uint8_t *ptr, uintptr_t len);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* GRAVITY_ENGINE_H */
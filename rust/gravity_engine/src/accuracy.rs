@@ -0,0 +1,171 @@
+//! Time-step convergence: the same run at `dt`, `dt/2` and `dt/4`, Richardson
+//! extrapolated to estimate the integrator's observed order and each body's error.
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::{DtPolicy, EngineConfig};
+use crate::engine::SimulationEngine;
+use crate::errors::{EngineError, Result};
+use crate::math::Vec2;
+use crate::types::Body;
+
+/// Position error of one run against the extrapolated reference.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LevelError {
+    pub dt: f64,
+    pub ticks: u64,
+    pub rms_position_error: f64,
+    pub max_position_error: f64,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BodyAccuracy {
+    pub id: String,
+    /// Richardson-extrapolated position at the end of the run.
+    pub reference_position: Vec2,
+    /// Estimated position error of the run at the requested `dt`.
+    pub position_error: f64,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccuracyReport {
+    pub dt: f64,
+    pub duration: f64,
+    /// Observed convergence order, `log2(|x(dt) - x(dt/2)| / |x(dt/2) - x(dt/4)|)`
+    /// over the RMS of the differences. `None` when the runs agree too closely (or
+    /// not at all) to measure it.
+    pub order: Option<f64>,
+    /// `dt`, `dt/2` and `dt/4`, coarsest first.
+    pub levels: Vec<LevelError>,
+    /// Bodies alive at the end of every run, in engine order.
+    pub bodies: Vec<BodyAccuracy>,
+}
+
+impl AccuracyReport {
+    /// Largest step expected to keep the RMS position error below `tolerance`,
+    /// assuming the error scales as `dt^order`.
+    pub fn dt_for_tolerance(&self, tolerance: f64) -> Option<f64> {
+        let order = self.order?;
+        let error = self.levels.first()?.rms_position_error;
+        let dt = self.dt * (tolerance / error).powf(1.0 / order);
+        (tolerance > 0.0 && error > 0.0 && dt.is_finite()).then_some(dt)
+    }
+}
+
+/// Runs `bodies` for `ticks` ticks of `config.dt`, then twice and four times as many
+/// ticks of half and a quarter the step, and compares the end states. Needs the
+/// `Fixed` dt policy so every run covers the same simulated time.
+pub fn accuracy_report(
+    config: &EngineConfig,
+    bodies: &[Body],
+    ticks: u32,
+) -> Result<AccuracyReport> {
+    if !matches!(config.dt_policy, DtPolicy::Fixed) {
+        return Err(EngineError::InvalidConfig(
+            "accuracy reports need the fixed dt policy".to_string(),
+        ));
+    }
+    if ticks == 0 {
+        return Err(EngineError::InvalidConfig(
+            "accuracy reports need at least one tick".to_string(),
+        ));
+    }
+    let mut runs = Vec::with_capacity(3);
+    for level in 0..3u32 {
+        let scale = 1u32 << level;
+        let config = EngineConfig {
+            dt: config.dt / f64::from(scale),
+            time_quantum: None,
+            ..config.clone()
+        };
+        let level_ticks = ticks.checked_mul(scale).ok_or_else(|| {
+            EngineError::InvalidConfig(format!("{ticks} ticks overflow at dt/{scale}"))
+        })?;
+        let mut engine = SimulationEngine::with_bodies(config, bodies.to_vec())?;
+        engine.step(level_ticks)?;
+        runs.push(engine);
+    }
+
+    let positions = runs
+        .iter()
+        .map(|engine| {
+            engine
+                .bodies()
+                .iter()
+                .filter(|body| body.alive)
+                .map(|body| (body.id.as_str(), body.position))
+                .collect::<HashMap<_, _>>()
+        })
+        .collect::<Vec<_>>();
+    let shared = runs[2]
+        .bodies()
+        .iter()
+        .filter(|body| {
+            body.alive
+                && positions
+                    .iter()
+                    .all(|run| run.contains_key(body.id.as_str()))
+        })
+        .map(|body| body.id.as_str())
+        .collect::<Vec<_>>();
+    let at = |level: usize, id: &str| positions[level][id];
+
+    let rms_difference = |coarse: usize, fine: usize| {
+        let sum = shared
+            .iter()
+            .map(|id| (at(coarse, id) - at(fine, id)).norm_squared())
+            .sum::<f64>();
+        (sum / shared.len().max(1) as f64).sqrt()
+    };
+    let (coarse, fine) = (rms_difference(0, 1), rms_difference(1, 2));
+    let order = (coarse / fine).log2();
+    let order = (coarse > 0.0 && fine > 0.0 && order.is_finite() && order > 0.0).then_some(order);
+
+    // x_ref = x(dt/4) + (x(dt/4) - x(dt/2)) / (2^p - 1); without a measured order the
+    // finest run stands in for the reference.
+    let correction = order.map_or(0.0, |order| 1.0 / (2f64.powf(order) - 1.0));
+    let reference = shared
+        .iter()
+        .map(|id| (*id, at(2, id) + (at(2, id) - at(1, id)) * correction))
+        .collect::<Vec<_>>();
+
+    let levels = runs
+        .iter()
+        .enumerate()
+        .map(|(level, engine)| {
+            let errors = reference
+                .iter()
+                .map(|(id, position)| (at(level, id) - *position).norm())
+                .collect::<Vec<_>>();
+            LevelError {
+                dt: engine.config().dt,
+                ticks: engine.tick(),
+                rms_position_error: (errors.iter().map(|error| error * error).sum::<f64>()
+                    / errors.len().max(1) as f64)
+                    .sqrt(),
+                max_position_error: errors.iter().copied().fold(0.0, f64::max),
+            }
+        })
+        .collect();
+    let bodies = reference
+        .iter()
+        .map(|(id, position)| BodyAccuracy {
+            id: id.to_string(),
+            reference_position: *position,
+            position_error: (at(0, id) - *position).norm(),
+        })
+        .collect();
+
+    Ok(AccuracyReport {
+        dt: config.dt,
+        duration: config.dt * f64::from(ticks),
+        order,
+        levels,
+        bodies,
+    })
+}
@@ -0,0 +1,74 @@
+use std::f64::consts::PI;
+
+use serde::{Deserialize, Serialize};
+
+use crate::errors::{EngineError, Result};
+use crate::math::Vec2;
+use crate::types::Body;
+
+/// Watches three bodies for collinearity; the middle id is the vertex body.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AlignmentWatch {
+    pub body_ids: [String; 3],
+    /// Maximum alignment error in radians.
+    pub tolerance: f64,
+}
+
+impl AlignmentWatch {
+    pub fn new(
+        first: impl Into<String>,
+        middle: impl Into<String>,
+        last: impl Into<String>,
+        tolerance: f64,
+    ) -> Self {
+        Self {
+            body_ids: [first.into(), middle.into(), last.into()],
+            tolerance,
+        }
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        if !self.tolerance.is_finite() || self.tolerance <= 0.0 || self.tolerance >= PI / 2.0 {
+            return Err(EngineError::InvalidConfig(
+                "alignment tolerance must be finite and in (0, pi/2)".to_string(),
+            ));
+        }
+        let [a, b, c] = &self.body_ids;
+        if a == b || b == c || a == c {
+            return Err(EngineError::InvalidConfig(
+                "alignment watch needs three distinct body ids".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    pub(crate) fn measure(&self, bodies: &[Body]) -> Option<(f64, f64)> {
+        let mut positions = [Vec2::ZERO; 3];
+        for (slot, id) in positions.iter_mut().zip(&self.body_ids) {
+            *slot = bodies
+                .iter()
+                .find(|body| body.alive && body.id == *id)?
+                .position;
+        }
+        let [a, b, c] = positions;
+        let angle = separation_angle(a, b, c)?;
+        Some((angle.min(PI - angle), angle))
+    }
+}
+
+/// Angle at `b` between the directions towards `a` and `c`.
+pub fn separation_angle(a: Vec2, b: Vec2, c: Vec2) -> Option<f64> {
+    let to_a = a - b;
+    let to_c = c - b;
+    if to_a.norm_squared() == 0.0 || to_c.norm_squared() == 0.0 {
+        return None;
+    }
+    Some(to_a.cross(to_c).abs().atan2(to_a.dot(to_c)))
+}
+
+#[derive(Clone, Debug)]
+pub(crate) struct AlignmentTracker {
+    pub watch: AlignmentWatch,
+    pub aligned: bool,
+}
@@ -0,0 +1,446 @@
+use std::collections::BTreeSet;
+use std::f64::consts::TAU;
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::EngineConfig;
+use crate::errors::{EngineError, Result};
+use crate::forces::pair_softening_squared;
+use crate::history::History;
+use crate::math::Vec2;
+use crate::query::BodyQuery;
+use crate::types::Body;
+use crate::zones::Region;
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BinaryElements {
+    pub semi_major_axis: f64,
+    pub eccentricity: f64,
+    pub period: f64,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BinaryRecord {
+    pub primary_id: String,
+    pub secondary_id: String,
+    pub formed_tick: u64,
+    /// Pair energy in the pair's centre-of-mass frame (negative when bound).
+    pub binding_energy: f64,
+    pub elements: BinaryElements,
+}
+
+/// Finds mutually nearest neighbours whose pair energy is negative.
+///
+/// `formed_tick` is left at zero; the engine stamps it when maintaining its catalog.
+pub fn detect_binaries(bodies: &[Body], config: &EngineConfig) -> Vec<BinaryRecord> {
+    let alive = bodies.iter().filter(|body| body.alive).collect::<Vec<_>>();
+    let mut nearest = vec![None::<(usize, f64)>; alive.len()];
+    for i in 0..alive.len() {
+        for j in (i + 1)..alive.len() {
+            let dist_sq = (alive[j].position - alive[i].position).norm_squared();
+            if nearest[i].is_none_or(|(_, best)| dist_sq < best) {
+                nearest[i] = Some((j, dist_sq));
+            }
+            if nearest[j].is_none_or(|(_, best)| dist_sq < best) {
+                nearest[j] = Some((i, dist_sq));
+            }
+        }
+    }
+
+    let mut binaries = Vec::new();
+    for (i, entry) in nearest.iter().enumerate() {
+        let Some((j, _)) = *entry else {
+            continue;
+        };
+        if j < i || nearest[j].map(|(k, _)| k) != Some(i) {
+            continue;
+        }
+
+        let (primary, secondary) = if alive[j].mass > alive[i].mass {
+            (alive[j], alive[i])
+        } else {
+            (alive[i], alive[j])
+        };
+        let offset = secondary.position - primary.position;
+        let relative_velocity = secondary.velocity - primary.velocity;
+        let reduced_mass = primary.mass * secondary.mass / (primary.mass + secondary.mass);
+        let binding_energy = 0.5 * reduced_mass * relative_velocity.norm_squared()
+            - config.gravity_constant * primary.mass * secondary.mass
+                / (offset.norm_squared()
+                    + pair_softening_squared(primary, secondary, config.softening_epsilon))
+                .sqrt();
+        if binding_energy >= 0.0 {
+            continue;
+        }
+
+        let mu = config.gravity_constant * (primary.mass + secondary.mass);
+        let Some(elements) = two_body_elements(offset, relative_velocity, mu) else {
+            continue;
+        };
+        binaries.push(BinaryRecord {
+            primary_id: primary.id.clone(),
+            secondary_id: secondary.id.clone(),
+            formed_tick: 0,
+            binding_energy,
+            elements,
+        });
+    }
+    binaries
+}
+
+/// Points along `body`'s osculating conic about `primary`, in world coordinates and in
+/// the direction of motion; ellipses start at periapsis.
+///
+/// Ellipses are sampled over a full turn; open orbits stop once the branch reaches
+/// ten times the larger of the current separation and the periapsis distance. Returns
+/// an empty path for radial or coincident configurations and when `n_points < 2`.
+pub fn sample_kepler_orbit(
+    primary: &Body,
+    body: &Body,
+    n_points: usize,
+    gravity_constant: f64,
+) -> Vec<Vec2> {
+    let offset = body.position - primary.position;
+    let relative_velocity = body.velocity - primary.velocity;
+    let mu = gravity_constant * (primary.mass + body.mass);
+    let distance = offset.norm();
+    let angular_momentum = offset.cross(relative_velocity);
+    if n_points < 2 || distance <= 0.0 || mu <= 0.0 || angular_momentum == 0.0 {
+        return Vec::new();
+    }
+
+    let eccentricity_vector = (offset * (relative_velocity.norm_squared() - mu / distance)
+        - relative_velocity * offset.dot(relative_velocity))
+        / mu;
+    let eccentricity = eccentricity_vector.norm();
+    let periapsis_angle = if eccentricity > 1e-12 {
+        eccentricity_vector.angle()
+    } else {
+        offset.angle()
+    };
+    let semi_latus_rectum = angular_momentum * angular_momentum / mu;
+    let direction = angular_momentum.signum();
+
+    let (start, span) = if eccentricity < 1.0 {
+        (0.0, TAU * (1.0 - 1.0 / n_points as f64))
+    } else {
+        let periapsis = semi_latus_rectum / (1.0 + eccentricity);
+        let max_radius = 10.0 * distance.max(periapsis);
+        let limit = ((semi_latus_rectum / max_radius - 1.0) / eccentricity)
+            .clamp(-1.0, 1.0)
+            .acos();
+        (-limit, 2.0 * limit)
+    };
+
+    (0..n_points)
+        .map(|index| {
+            let anomaly = start + span * index as f64 / (n_points - 1) as f64;
+            let radius = semi_latus_rectum / (1.0 + eccentricity * anomaly.cos());
+            primary.position + Vec2::from_angle(periapsis_angle + direction * anomaly) * radius
+        })
+        .collect()
+}
+
+/// Two populations, selected among the alive bodies inside `region`, whose mutual
+/// collision rate is estimated. Identical queries count each pair once.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CollisionRateQuery {
+    pub first: BodyQuery,
+    pub second: BodyQuery,
+    pub region: Region,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CollisionRateEstimate {
+    pub first_count: usize,
+    pub second_count: usize,
+    /// RMS relative speed between members of the two populations.
+    pub relative_speed: f64,
+    /// Mutual escape speed at contact for a pair of average members.
+    pub escape_speed: f64,
+    /// `1 + v_esc² / v_rel²`: how much gravity enlarges the squared cross-section;
+    /// infinite for a cold population.
+    pub focusing_factor: f64,
+    /// Expected collisions per unit sim time, ignoring focusing.
+    pub geometric_rate: f64,
+    /// Expected collisions per unit sim time.
+    pub expected_rate: f64,
+}
+
+/// Particle-in-a-box collision rate with gravitational focusing.
+///
+/// In the plane the cross-section is a width, `2 s sqrt(1 + v_esc² / v_rel²)` for
+/// contact distance `s`, so the rate is `pairs / area * 2 s sqrt(v_rel² + v_esc²)`.
+/// Velocities are taken about each population's mean plus the difference of the
+/// means; keep the region small enough that shear across it is negligible.
+pub fn estimate_collision_rate(
+    bodies: &[Body],
+    gravity_constant: f64,
+    query: &CollisionRateQuery,
+) -> Result<CollisionRateEstimate> {
+    query.first.validate()?;
+    query.second.validate()?;
+    query.region.validate()?;
+    let select = |population: &BodyQuery| {
+        bodies
+            .iter()
+            .filter(|body| {
+                body.alive && query.region.contains(body.position) && population.matches(body)
+            })
+            .collect::<Vec<_>>()
+    };
+    let first = Moments::of(&select(&query.first));
+    let second = Moments::of(&select(&query.second));
+    let pairs = if query.first == query.second {
+        first.count * first.count.saturating_sub(1) / 2
+    } else {
+        first.count * second.count
+    };
+
+    let relative_speed = (first.dispersion
+        + second.dispersion
+        + (first.mean_velocity - second.mean_velocity).norm_squared())
+    .sqrt();
+    let contact = first.mean_radius + second.mean_radius;
+    let escape_speed = if contact > 0.0 {
+        (2.0 * gravity_constant * (first.mean_mass + second.mean_mass) / contact).sqrt()
+    } else {
+        0.0
+    };
+    let focusing_factor = if relative_speed > 0.0 {
+        1.0 + (escape_speed / relative_speed).powi(2)
+    } else {
+        f64::INFINITY
+    };
+    let rate = |speed: f64| pairs as f64 / query.region.area() * 2.0 * contact * speed;
+    Ok(CollisionRateEstimate {
+        first_count: first.count,
+        second_count: second.count,
+        relative_speed,
+        escape_speed,
+        focusing_factor,
+        geometric_rate: rate(relative_speed),
+        expected_rate: rate(relative_speed.hypot(escape_speed)),
+    })
+}
+
+#[derive(Default)]
+struct Moments {
+    count: usize,
+    mean_mass: f64,
+    mean_radius: f64,
+    mean_velocity: Vec2,
+    /// Mean squared speed about `mean_velocity`.
+    dispersion: f64,
+}
+
+impl Moments {
+    fn of(bodies: &[&Body]) -> Self {
+        if bodies.is_empty() {
+            return Self::default();
+        }
+        let count = bodies.len() as f64;
+        let mean_velocity = bodies.iter().map(|body| body.velocity).sum::<Vec2>() / count;
+        Self {
+            count: bodies.len(),
+            mean_mass: bodies.iter().map(|body| body.mass).sum::<f64>() / count,
+            mean_radius: bodies.iter().map(|body| body.radius).sum::<f64>() / count,
+            mean_velocity,
+            dispersion: bodies
+                .iter()
+                .map(|body| (body.velocity - mean_velocity).norm_squared())
+                .sum::<f64>()
+                / count,
+        }
+    }
+}
+
+fn two_body_elements(offset: Vec2, relative_velocity: Vec2, mu: f64) -> Option<BinaryElements> {
+    let distance = offset.norm();
+    if distance <= 0.0 || mu <= 0.0 {
+        return None;
+    }
+    let specific_energy = 0.5 * relative_velocity.norm_squared() - mu / distance;
+    if specific_energy >= 0.0 {
+        return None;
+    }
+
+    let semi_major_axis = -mu / (2.0 * specific_energy);
+    let angular_momentum = offset.cross(relative_velocity);
+    let eccentricity = (1.0
+        + 2.0 * specific_energy * angular_momentum * angular_momentum / (mu * mu))
+        .max(0.0)
+        .sqrt();
+    Some(BinaryElements {
+        semi_major_axis,
+        eccentricity,
+        period: TAU * (semi_major_axis.powi(3) / mu).sqrt(),
+    })
+}
+
+/// Close approaches of `body_id` to bodies at least `min_mass_ratio` times heavier.
+/// An encounter spans the consecutive frames within `max_distance` of one primary.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FlybyQuery {
+    pub body_id: String,
+    pub max_distance: f64,
+    #[serde(default = "default_flyby_mass_ratio")]
+    pub min_mass_ratio: f64,
+}
+
+fn default_flyby_mass_ratio() -> f64 {
+    10.0
+}
+
+impl FlybyQuery {
+    pub fn validate(&self) -> Result<()> {
+        if self.body_id.is_empty() {
+            return Err(EngineError::InvalidConfig(
+                "flyby body_id must not be empty".to_string(),
+            ));
+        }
+        if !(self.max_distance.is_finite() && self.max_distance > 0.0) {
+            return Err(EngineError::InvalidConfig(
+                "flyby max_distance must be finite and > 0".to_string(),
+            ));
+        }
+        if !(self.min_mass_ratio.is_finite() && self.min_mass_ratio >= 0.0) {
+            return Err(EngineError::InvalidConfig(
+                "flyby min_mass_ratio must be finite and >= 0".to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// One encounter. Velocities are relative to the primary, taken at the first and
+/// last frame inside `max_distance`.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FlybyReport {
+    pub primary_id: String,
+    pub periapsis_time: f64,
+    pub periapsis_distance: f64,
+    pub velocity_in: Vec2,
+    pub velocity_out: Vec2,
+    pub speed_in: f64,
+    pub speed_out: f64,
+    /// Turn from `velocity_in` to `velocity_out` in radians, counter-clockwise positive.
+    pub deflection_angle: f64,
+    /// False when the recording starts or ends mid-encounter, or either body
+    /// disappears during it.
+    pub complete: bool,
+}
+
+/// Scans a recorded trajectory for flybys, ordered by periapsis time. Periapsis is
+/// refined with a parabola through the closest frame and its neighbours.
+pub fn analyze_flybys(history: &History, query: &FlybyQuery) -> Result<Vec<FlybyReport>> {
+    query.validate()?;
+    let primaries = history
+        .frames()
+        .filter_map(|frame| Some((frame, frame.body(&query.body_id)?)))
+        .flat_map(|(frame, body)| {
+            frame.bodies.iter().filter(move |other| {
+                other.id != body.id && other.mass >= query.min_mass_ratio * body.mass
+            })
+        })
+        .map(|primary| primary.id.clone())
+        .collect::<BTreeSet<_>>();
+
+    let mut reports = Vec::new();
+    for primary_id in &primaries {
+        let mut encounter = Vec::new();
+        let mut entered_cleanly = false;
+        let mut outside = false;
+        for frame in history.frames() {
+            let relative = match (frame.body(&query.body_id), frame.body(primary_id)) {
+                (Some(body), Some(primary)) if primary.mass >= query.min_mass_ratio * body.mass => {
+                    Some(FlybySample {
+                        sim_time: frame.sim_time,
+                        position: body.position - primary.position,
+                        velocity: body.velocity - primary.velocity,
+                    })
+                }
+                _ => None,
+            };
+            match relative {
+                Some(sample) if sample.position.norm() <= query.max_distance => {
+                    if encounter.is_empty() {
+                        entered_cleanly = outside;
+                    }
+                    encounter.push(sample);
+                }
+                _ => {
+                    if !encounter.is_empty() {
+                        let complete = entered_cleanly && relative.is_some();
+                        reports.push(flyby_report(primary_id, &encounter, complete));
+                        encounter.clear();
+                    }
+                    outside = relative.is_some();
+                }
+            }
+        }
+        if !encounter.is_empty() {
+            reports.push(flyby_report(primary_id, &encounter, false));
+        }
+    }
+    reports.sort_by(|a, b| a.periapsis_time.total_cmp(&b.periapsis_time));
+    Ok(reports)
+}
+
+struct FlybySample {
+    sim_time: f64,
+    position: Vec2,
+    velocity: Vec2,
+}
+
+fn flyby_report(primary_id: &str, encounter: &[FlybySample], complete: bool) -> FlybyReport {
+    let closest = (0..encounter.len())
+        .min_by(|&a, &b| {
+            encounter[a]
+                .position
+                .norm()
+                .total_cmp(&encounter[b].position.norm())
+        })
+        .unwrap_or(0);
+    let mut periapsis_time = encounter[closest].sim_time;
+    let mut periapsis_distance = encounter[closest].position.norm();
+    if closest > 0 && closest + 1 < encounter.len() {
+        let [t0, t1, t2] = [closest - 1, closest, closest + 1].map(|i| encounter[i].sim_time);
+        let [d0, d1, d2] =
+            [closest - 1, closest, closest + 1].map(|i| encounter[i].position.norm());
+        // Divided differences of the quadratic d(t) through the three frames.
+        let slope_01 = (d1 - d0) / (t1 - t0);
+        let slope_12 = (d2 - d1) / (t2 - t1);
+        let curvature = (slope_12 - slope_01) / (t2 - t0);
+        if curvature > 0.0 {
+            let vertex = 0.5 * (t0 + t1) - slope_01 / (2.0 * curvature);
+            if (t0..=t2).contains(&vertex) {
+                periapsis_time = vertex;
+                periapsis_distance =
+                    d0 + slope_01 * (vertex - t0) + curvature * (vertex - t0) * (vertex - t1);
+            }
+        }
+    }
+
+    let velocity_in = encounter[0].velocity;
+    let velocity_out = encounter[encounter.len() - 1].velocity;
+    FlybyReport {
+        primary_id: primary_id.to_string(),
+        periapsis_time,
+        periapsis_distance,
+        velocity_in,
+        velocity_out,
+        speed_in: velocity_in.norm(),
+        speed_out: velocity_out.norm(),
+        deflection_angle: velocity_in
+            .cross(velocity_out)
+            .atan2(velocity_in.dot(velocity_out)),
+        complete,
+    }
+}
@@ -0,0 +1,70 @@
+//! Workloads shared by the benchmark suite, public so hosts can benchmark the engine
+//! against the same systems.
+
+use crate::config::{CollisionMode, DtPolicy, EngineConfig, GravitySolver, IntegratorKind};
+use crate::math::Vec2;
+use crate::types::Body;
+
+/// A heavy star at the origin and `body_count - 1` light bodies on circular orbits in
+/// bands from radius 20 outwards, spread by the golden angle. Deterministic.
+pub fn generate_orbital_system(body_count: usize, gravity_constant: f64) -> Vec<Body> {
+    let mut bodies = Vec::with_capacity(body_count);
+
+    let central_mass = 5000.0;
+    bodies.push(Body::new("star", central_mass, 3.0, Vec2::ZERO, Vec2::ZERO));
+
+    let orbiters = body_count.saturating_sub(1);
+    for i in 0..orbiters {
+        let idx = i as f64;
+        let angle = (idx * 2.399963229728653) % std::f64::consts::TAU;
+        let band = (i % 64) as f64;
+        let radius = 20.0 + band * 1.2 + (idx / 256.0);
+
+        let position = Vec2::new(radius * angle.cos(), radius * angle.sin());
+        let tangent = Vec2::new(-angle.sin(), angle.cos());
+
+        let mass = 0.2 + ((i % 11) as f64) * 0.05;
+        let speed = (gravity_constant * central_mass / radius).sqrt();
+        let velocity = tangent * speed;
+
+        bodies.push(Body::new(
+            format!("body_{i}"),
+            mass,
+            0.25,
+            position,
+            velocity,
+        ));
+    }
+
+    bodies
+}
+
+/// Fixed-step Velocity Verlet without collisions, so timings measure the solver.
+pub fn benchmark_config(gravity_solver: GravitySolver) -> EngineConfig {
+    EngineConfig {
+        gravity_constant: 1.0,
+        softening_epsilon: 1e-4,
+        dt: 0.002,
+        dt_policy: DtPolicy::Fixed,
+        integrator: IntegratorKind::VelocityVerlet,
+        collision_mode: CollisionMode::Ignore,
+        deterministic: true,
+        gravity_solver,
+        barnes_hut_theta: 0.6,
+        barnes_hut_threshold: 256,
+        ..EngineConfig::default()
+    }
+}
+
+/// Every solver this build can run.
+pub fn available_solvers() -> Vec<GravitySolver> {
+    let mut solvers = vec![
+        GravitySolver::Pairwise,
+        GravitySolver::BarnesHut,
+        GravitySolver::Auto,
+    ];
+    if cfg!(feature = "simd") {
+        solvers.push(GravitySolver::PairwiseSimd);
+    }
+    solvers
+}
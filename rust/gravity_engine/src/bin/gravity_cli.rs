@@ -0,0 +1,49 @@
+use std::env;
+use std::fs;
+use std::path::Path;
+use std::process::ExitCode;
+
+use gravity_engine::schema::{SCHEMA_NAMES, all_json_schemas, json_schema};
+
+const USAGE: &str = "usage: gravity_cli schema [<name> | --out <dir>]";
+
+fn main() -> ExitCode {
+    let args = env::args().skip(1).collect::<Vec<_>>();
+    let result = match args
+        .iter()
+        .map(String::as_str)
+        .collect::<Vec<_>>()
+        .as_slice()
+    {
+        ["schema"] => print_json(&serde_json::to_value(all_json_schemas()).unwrap()),
+        ["schema", "--out", dir] => write_schemas(Path::new(dir)),
+        ["schema", name] => json_schema(name)
+            .map_err(|error| error.to_string())
+            .and_then(|schema| print_json(&schema)),
+        _ => Err(format!("{USAGE}\nschemas: {}", SCHEMA_NAMES.join(", "))),
+    };
+
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(message) => {
+            eprintln!("{message}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn print_json(value: &serde_json::Value) -> Result<(), String> {
+    let text = serde_json::to_string_pretty(value).map_err(|error| error.to_string())?;
+    println!("{text}");
+    Ok(())
+}
+
+fn write_schemas(dir: &Path) -> Result<(), String> {
+    fs::create_dir_all(dir).map_err(|error| error.to_string())?;
+    for (name, schema) in all_json_schemas() {
+        let path = dir.join(format!("{name}.schema.json"));
+        let text = serde_json::to_string_pretty(&schema).map_err(|error| error.to_string())?;
+        fs::write(&path, text + "\n").map_err(|error| format!("{}: {error}", path.display()))?;
+    }
+    Ok(())
+}
@@ -0,0 +1,77 @@
+//! Compact binary encoding for snapshots and scenarios.
+//!
+//! Each buffer is a four-byte magic tag followed by the bincode (varint, little-endian)
+//! encoding of the same serde model the JSON interface uses, so both formats round-trip
+//! the exact same data. The same encoding doubles as the canonical form hashed for
+//! scenario and snapshot checksums.
+
+use std::io::{self, Write};
+
+use bincode::Options;
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+
+use crate::errors::{EngineError, Result};
+use crate::types::{Scenario, Snapshot};
+
+const SNAPSHOT_MAGIC: &[u8; 4] = b"GSS2";
+const SCENARIO_MAGIC: &[u8; 4] = b"GSC2";
+
+pub fn encode_snapshot(snapshot: &Snapshot) -> Vec<u8> {
+    encode(SNAPSHOT_MAGIC, snapshot)
+}
+
+pub fn decode_snapshot(bytes: &[u8]) -> Result<Snapshot> {
+    decode(SNAPSHOT_MAGIC, bytes, "snapshot")
+}
+
+pub fn encode_scenario(scenario: &Scenario) -> Vec<u8> {
+    encode(SCENARIO_MAGIC, scenario)
+}
+
+pub fn decode_scenario(bytes: &[u8]) -> Result<Scenario> {
+    decode(SCENARIO_MAGIC, bytes, "scenario")
+}
+
+fn encode<T: Serialize>(magic: &[u8; 4], value: &T) -> Vec<u8> {
+    let mut bytes = magic.to_vec();
+    bincode::DefaultOptions::new()
+        .serialize_into(&mut bytes, value)
+        .expect("in-memory bincode encoding cannot fail");
+    bytes
+}
+
+fn decode<T: DeserializeOwned>(magic: &[u8; 4], bytes: &[u8], name: &str) -> Result<T> {
+    let payload = bytes.strip_prefix(magic.as_slice()).ok_or_else(|| {
+        EngineError::SchemaValidationFailed(format!("not a binary {name} buffer"))
+    })?;
+    bincode::DefaultOptions::new()
+        .deserialize(payload)
+        .map_err(|error| {
+            EngineError::SchemaValidationFailed(format!("failed to decode binary {name}: {error}"))
+        })
+}
+
+/// FNV-1a (64-bit) over the canonical binary encoding of `value`.
+pub(crate) fn content_checksum<T: Serialize + ?Sized>(value: &T) -> String {
+    let mut hasher = Fnv1a(0xcbf2_9ce4_8422_2325);
+    bincode::DefaultOptions::new()
+        .serialize_into(&mut hasher, value)
+        .expect("in-memory bincode encoding cannot fail");
+    format!("{:016x}", hasher.0)
+}
+
+struct Fnv1a(u64);
+
+impl Write for Fnv1a {
+    fn write(&mut self, bytes: &[u8]) -> io::Result<usize> {
+        for &byte in bytes {
+            self.0 = (self.0 ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3);
+        }
+        Ok(bytes.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
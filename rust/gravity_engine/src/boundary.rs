@@ -0,0 +1,72 @@
+//! World boundary conditions, applied to positions after every tick.
+
+use crate::config::{BoundaryMode, WorldBoundary};
+use crate::math::Vec2;
+use crate::types::Body;
+
+/// `to - from`, replaced by its nearest periodic image when `period` is set.
+pub(crate) fn separation(from: Vec2, to: Vec2, period: Option<Vec2>) -> Vec2 {
+    let delta = to - from;
+    match period {
+        Some(period) => Vec2::new(
+            delta.x - period.x * (delta.x / period.x).round(),
+            delta.y - period.y * (delta.y / period.y).round(),
+        ),
+        None => delta,
+    }
+}
+
+/// Enforces `boundary` on the alive bodies and returns the indices it absorbed.
+pub(crate) fn apply_boundary(bodies: &mut [Body], boundary: &WorldBoundary) -> Vec<usize> {
+    let mut absorbed = Vec::new();
+    for (index, body) in bodies.iter_mut().enumerate() {
+        if !body.alive {
+            continue;
+        }
+        match boundary.mode {
+            BoundaryMode::Reflect => {
+                (body.position.x, body.velocity.x) = reflect(
+                    body.position.x,
+                    body.velocity.x,
+                    boundary.min.x,
+                    boundary.max.x,
+                );
+                (body.position.y, body.velocity.y) = reflect(
+                    body.position.y,
+                    body.velocity.y,
+                    boundary.min.y,
+                    boundary.max.y,
+                );
+            }
+            BoundaryMode::Periodic => {
+                body.position.x = wrap(body.position.x, boundary.min.x, boundary.max.x);
+                body.position.y = wrap(body.position.y, boundary.min.y, boundary.max.y);
+            }
+            BoundaryMode::Absorb => {
+                let inside = (boundary.min.x..=boundary.max.x).contains(&body.position.x)
+                    && (boundary.min.y..=boundary.max.y).contains(&body.position.y);
+                if !inside {
+                    body.alive = false;
+                    absorbed.push(index);
+                }
+            }
+        }
+    }
+    absorbed
+}
+
+/// Mirrors an overshoot back inside and points the velocity inwards. Overshoots larger
+/// than the world are clamped to the wall.
+fn reflect(position: f64, velocity: f64, min: f64, max: f64) -> (f64, f64) {
+    if position < min {
+        ((2.0 * min - position).min(max), velocity.abs())
+    } else if position > max {
+        ((2.0 * max - position).max(min), -velocity.abs())
+    } else {
+        (position, velocity)
+    }
+}
+
+fn wrap(position: f64, min: f64, max: f64) -> f64 {
+    min + (position - min).rem_euclid(max - min)
+}
@@ -0,0 +1,259 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use serde::{Deserialize, Serialize};
+
+use crate::events::{CollisionEvent, CollisionKind};
+use crate::history::{BodySample, History, sample_trajectory};
+use crate::math::Vec2;
+
+type Trajectories = BTreeMap<String, Vec<(f64, BodySample)>>;
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum TourStop {
+    Body {
+        id: String,
+    },
+    Location {
+        sim_time: f64,
+        position: Vec2,
+        view_radius: f64,
+    },
+    /// Every collision recorded in the history, each held where and when it
+    /// happened; `merges_only` skips elastic bounces.
+    Collisions {
+        #[serde(default)]
+        merges_only: bool,
+    },
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TourOptions {
+    /// Share of each stop's time slot spent holding on the target; the rest is travel.
+    pub dwell_fraction: f64,
+    /// Body stops frame `radius * body_padding` of world space around the body.
+    pub body_padding: f64,
+    pub min_view_radius: f64,
+    pub transition_samples: usize,
+}
+
+impl Default for TourOptions {
+    fn default() -> Self {
+        Self {
+            dwell_fraction: 0.6,
+            body_padding: 12.0,
+            min_view_radius: 1.0,
+            transition_samples: 8,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CameraKeyframe {
+    pub sim_time: f64,
+    pub position: Vec2,
+    /// Radius of world space kept in view.
+    pub view_radius: f64,
+}
+
+/// Builds a camera path over the recorded time range that visits `stops`.
+///
+/// Body stops are spread evenly over the recording in the given order and
+/// track the body while holding; location and collision stops are held at their
+/// own time.
+pub fn grand_tour(
+    history: &History,
+    stops: &[TourStop],
+    options: &TourOptions,
+) -> Vec<CameraKeyframe> {
+    let Some((start, end)) = history.time_span() else {
+        return Vec::new();
+    };
+    let span = (end - start).max(0.0);
+
+    let collisions = if stops
+        .iter()
+        .any(|stop| matches!(stop, TourStop::Collisions { .. }))
+    {
+        history.collisions().collect::<Vec<_>>()
+    } else {
+        Vec::new()
+    };
+    let mut ids = BTreeSet::new();
+    for stop in stops {
+        if let TourStop::Body { id } = stop {
+            ids.insert(id.as_str());
+        }
+    }
+    for event in &collisions {
+        ids.insert(event.body_a.as_str());
+        ids.insert(event.body_b.as_str());
+    }
+    let trajectories = history.trajectories(&ids);
+
+    let mut resolved = Vec::with_capacity(stops.len());
+    for stop in stops {
+        match stop {
+            TourStop::Collisions { merges_only } => resolved.extend(
+                collisions
+                    .iter()
+                    .filter(|event| !merges_only || event.kind == CollisionKind::Merge)
+                    .filter_map(|event| collision_stop(event, &trajectories, options)),
+            ),
+            _ => resolved.push(stop.clone()),
+        }
+    }
+
+    let body_stop_count = resolved
+        .iter()
+        .filter(|stop| matches!(stop, TourStop::Body { .. }))
+        .count();
+    let mut scheduled = Vec::with_capacity(resolved.len());
+    let mut body_slot = 0;
+    for stop in &resolved {
+        let time = match stop {
+            TourStop::Body { .. } => {
+                body_slot += 1;
+                start + span * (body_slot as f64 - 0.5) / body_stop_count as f64
+            }
+            TourStop::Location { sim_time, .. } => sim_time.clamp(start, end),
+            TourStop::Collisions { .. } => continue,
+        };
+        scheduled.push((time, stop));
+    }
+    scheduled.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+    let hold = span * options.dwell_fraction.clamp(0.0, 1.0) / scheduled.len().max(1) as f64;
+    let mut holds: Vec<Vec<CameraKeyframe>> = Vec::with_capacity(scheduled.len());
+    for (time, stop) in scheduled {
+        let window_start = (time - 0.5 * hold).max(start);
+        let window_end = (time + 0.5 * hold).min(end);
+        let keyframes = hold_keyframes(&trajectories, stop, window_start, window_end, options);
+        if !keyframes.is_empty() {
+            holds.push(keyframes);
+        }
+    }
+
+    let mut path: Vec<CameraKeyframe> = Vec::new();
+    for hold in &holds {
+        if let (Some(previous), Some(next)) = (path.last().copied(), hold.first()) {
+            push_transition(&mut path, previous, *next, options.transition_samples);
+        }
+        for keyframe in hold {
+            // Overlapping holds (stops close in time) must not run the path backwards.
+            if path
+                .last()
+                .is_none_or(|last| keyframe.sim_time >= last.sim_time)
+            {
+                path.push(*keyframe);
+            }
+        }
+    }
+    path
+}
+
+/// Frames the midpoint of the two bodies at the time of the collision.
+fn collision_stop(
+    event: &CollisionEvent,
+    trajectories: &Trajectories,
+    options: &TourOptions,
+) -> Option<TourStop> {
+    let sample = |id: &str| {
+        trajectories
+            .get(id)
+            .and_then(|trajectory| sample_trajectory(trajectory, event.sim_time))
+    };
+    let (position, radius) = match (sample(&event.body_a), sample(&event.body_b)) {
+        (Some(a), Some(b)) => ((a.position + b.position) * 0.5, a.radius + b.radius),
+        (Some(only), None) | (None, Some(only)) => (only.position, only.radius),
+        (None, None) => return None,
+    };
+    Some(TourStop::Location {
+        sim_time: event.sim_time,
+        position,
+        view_radius: radius * options.body_padding,
+    })
+}
+
+fn hold_keyframes(
+    trajectories: &Trajectories,
+    stop: &TourStop,
+    window_start: f64,
+    window_end: f64,
+    options: &TourOptions,
+) -> Vec<CameraKeyframe> {
+    match stop {
+        TourStop::Location {
+            position,
+            view_radius,
+            ..
+        } => vec![
+            CameraKeyframe {
+                sim_time: window_start,
+                position: *position,
+                view_radius: view_radius.max(options.min_view_radius),
+            },
+            CameraKeyframe {
+                sim_time: window_end,
+                position: *position,
+                view_radius: view_radius.max(options.min_view_radius),
+            },
+        ],
+        TourStop::Body { id } => {
+            let Some(trajectory) = trajectories.get(id) else {
+                return Vec::new();
+            };
+            let mut times = vec![window_start];
+            times.extend(
+                trajectory
+                    .iter()
+                    .map(|(time, _)| *time)
+                    .filter(|time| *time > window_start && *time < window_end),
+            );
+            times.push(window_end);
+
+            times
+                .into_iter()
+                .filter_map(|time| {
+                    let sample = sample_trajectory(trajectory, time)?;
+                    Some(CameraKeyframe {
+                        sim_time: time,
+                        position: sample.position,
+                        view_radius: (sample.radius * options.body_padding)
+                            .max(options.min_view_radius),
+                    })
+                })
+                .collect()
+        }
+        // Expanded into location stops before scheduling.
+        TourStop::Collisions { .. } => Vec::new(),
+    }
+}
+
+fn push_transition(
+    path: &mut Vec<CameraKeyframe>,
+    from: CameraKeyframe,
+    to: CameraKeyframe,
+    samples: usize,
+) {
+    let duration = to.sim_time - from.sim_time;
+    if samples == 0 || duration <= 0.0 {
+        return;
+    }
+
+    // Pull back mid-flight so both endpoints share the frame while travelling.
+    let travel_radius = 0.6 * (to.position - from.position).norm();
+    for step in 1..=samples {
+        let t = step as f64 / (samples + 1) as f64;
+        let eased = t * t * (3.0 - 2.0 * t);
+        let base_radius = from.view_radius + (to.view_radius - from.view_radius) * eased;
+        let bulge = 4.0 * t * (1.0 - t);
+        path.push(CameraKeyframe {
+            sim_time: from.sim_time + duration * t,
+            position: from.position + (to.position - from.position) * eased,
+            view_radius: base_radius.max(travel_radius * bulge),
+        });
+    }
+}
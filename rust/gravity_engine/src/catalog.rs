@@ -0,0 +1,142 @@
+//! Local scenario catalog with metadata filtering and free-text search.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::errors::{EngineError, Result};
+use crate::types::{Scenario, ScenarioMetadata};
+
+const MAX_PAGE_SIZE: usize = 500;
+
+/// Every populated field must match; string comparisons ignore case.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct CatalogQuery {
+    /// Whitespace-separated terms that must all appear in the name or description.
+    pub text: Option<String>,
+    /// Scenarios must carry every listed tag.
+    pub tags: Vec<String>,
+    pub author: Option<String>,
+    /// Inclusive bounds on `created_at`, compared as ISO-8601 strings.
+    pub created_after: Option<String>,
+    pub created_before: Option<String>,
+    pub offset: usize,
+    /// Page size; `None` means 20, and at most 500 are returned.
+    pub limit: Option<usize>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CatalogEntry {
+    pub id: u64,
+    pub metadata: ScenarioMetadata,
+    pub body_count: usize,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CatalogPage {
+    /// Matches before paging.
+    pub total: usize,
+    pub offset: usize,
+    pub entries: Vec<CatalogEntry>,
+}
+
+/// Scenarios keyed by insertion id; search results come back in id order.
+#[derive(Clone, Debug, Default)]
+pub struct ScenarioCatalog {
+    scenarios: BTreeMap<u64, Scenario>,
+    next_id: u64,
+}
+
+impl ScenarioCatalog {
+    pub fn insert(&mut self, scenario: Scenario) -> Result<u64> {
+        if !scenario.schema_version.starts_with('1') {
+            return Err(EngineError::SchemaValidationFailed(
+                "only scenario schema v1.x is supported".to_string(),
+            ));
+        }
+        scenario.verify_checksum()?;
+        scenario.engine_config.validate()?;
+        self.next_id += 1;
+        self.scenarios.insert(self.next_id, scenario);
+        Ok(self.next_id)
+    }
+
+    pub fn remove(&mut self, id: u64) -> Option<Scenario> {
+        self.scenarios.remove(&id)
+    }
+
+    pub fn get(&self, id: u64) -> Option<&Scenario> {
+        self.scenarios.get(&id)
+    }
+
+    pub fn len(&self) -> usize {
+        self.scenarios.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.scenarios.is_empty()
+    }
+
+    pub fn search(&self, query: &CatalogQuery) -> CatalogPage {
+        let terms = query
+            .text
+            .as_deref()
+            .unwrap_or_default()
+            .split_whitespace()
+            .map(str::to_lowercase)
+            .collect::<Vec<_>>();
+        let matches = self
+            .scenarios
+            .iter()
+            .filter(|(_, scenario)| matches(&scenario.metadata, query, &terms))
+            .collect::<Vec<_>>();
+        let limit = query.limit.unwrap_or(20).min(MAX_PAGE_SIZE);
+        CatalogPage {
+            total: matches.len(),
+            offset: query.offset,
+            entries: matches
+                .into_iter()
+                .skip(query.offset)
+                .take(limit)
+                .map(|(&id, scenario)| CatalogEntry {
+                    id,
+                    metadata: scenario.metadata.clone(),
+                    body_count: scenario.bodies.len(),
+                })
+                .collect(),
+        }
+    }
+}
+
+fn matches(metadata: &ScenarioMetadata, query: &CatalogQuery, terms: &[String]) -> bool {
+    let haystack = format!(
+        "{}\n{}",
+        metadata.name,
+        metadata.description.as_deref().unwrap_or_default()
+    )
+    .to_lowercase();
+    terms.iter().all(|term| haystack.contains(term.as_str()))
+        && query.tags.iter().all(|wanted| {
+            metadata
+                .tags
+                .iter()
+                .any(|tag| tag.eq_ignore_ascii_case(wanted))
+        })
+        && query.author.as_ref().is_none_or(|wanted| {
+            metadata
+                .author
+                .as_ref()
+                .is_some_and(|author| author.eq_ignore_ascii_case(wanted))
+        })
+        && query
+            .created_after
+            .as_ref()
+            .is_none_or(|after| metadata.created_at.as_str() >= after.as_str())
+        && query
+            .created_before
+            .as_ref()
+            .is_none_or(|before| metadata.created_at.as_str() <= before.as_str())
+}
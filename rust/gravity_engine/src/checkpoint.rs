@@ -0,0 +1,105 @@
+//! Named in-memory checkpoints kept by the engine.
+
+use std::collections::VecDeque;
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::EngineConfig;
+use crate::types::Snapshot;
+
+pub const DEFAULT_CHECKPOINT_CAPACITY: usize = 8;
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CheckpointInfo {
+    pub name: String,
+    pub tick: u64,
+    pub sim_time: f64,
+    pub body_count: usize,
+}
+
+/// How `SimulationEngine::step_back` reached the earlier tick.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum RewindMethod {
+    /// Integrated backwards with negative dt; exact up to rounding.
+    Reversed,
+    /// Restored the nearest earlier checkpoint and stepped forward from it.
+    Replayed,
+}
+
+#[derive(Clone, Debug)]
+pub(crate) struct Checkpoint {
+    pub(crate) name: String,
+    pub(crate) config: EngineConfig,
+    pub(crate) snapshot: Snapshot,
+}
+
+/// Checkpoints in creation order; past `capacity` the oldest is evicted. Reusing a
+/// name replaces the earlier checkpoint and moves it to the newest position.
+#[derive(Clone, Debug)]
+pub(crate) struct CheckpointStore {
+    capacity: usize,
+    checkpoints: VecDeque<Checkpoint>,
+}
+
+impl Default for CheckpointStore {
+    fn default() -> Self {
+        Self {
+            capacity: DEFAULT_CHECKPOINT_CAPACITY,
+            checkpoints: VecDeque::new(),
+        }
+    }
+}
+
+impl CheckpointStore {
+    pub(crate) fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = capacity.max(1);
+        self.evict();
+    }
+
+    pub(crate) fn insert(&mut self, checkpoint: Checkpoint) {
+        self.remove(&checkpoint.name);
+        self.checkpoints.push_back(checkpoint);
+        self.evict();
+    }
+
+    pub(crate) fn get(&self, name: &str) -> Option<&Checkpoint> {
+        self.checkpoints
+            .iter()
+            .find(|checkpoint| checkpoint.name == name)
+    }
+
+    /// The checkpoint with the highest tick not after `tick`.
+    pub(crate) fn latest_at_or_before(&self, tick: u64) -> Option<&Checkpoint> {
+        self.checkpoints
+            .iter()
+            .filter(|checkpoint| checkpoint.snapshot.tick <= tick)
+            .max_by_key(|checkpoint| checkpoint.snapshot.tick)
+    }
+
+    pub(crate) fn remove(&mut self, name: &str) -> bool {
+        let before = self.checkpoints.len();
+        self.checkpoints
+            .retain(|checkpoint| checkpoint.name != name);
+        self.checkpoints.len() != before
+    }
+
+    pub(crate) fn list(&self) -> Vec<CheckpointInfo> {
+        self.checkpoints
+            .iter()
+            .map(|checkpoint| CheckpointInfo {
+                name: checkpoint.name.clone(),
+                tick: checkpoint.snapshot.tick,
+                sim_time: checkpoint.snapshot.sim_time,
+                body_count: checkpoint.snapshot.bodies.len(),
+            })
+            .collect()
+    }
+
+    fn evict(&mut self) {
+        while self.checkpoints.len() > self.capacity {
+            self.checkpoints.pop_front();
+        }
+    }
+}
@@ -0,0 +1,49 @@
+//! Quantized simulation time.
+//!
+//! With `EngineConfig::time_quantum` set, elapsed time is an integer count of quanta
+//! plus a residual below one quantum. Steps whose dt is a whole number of quanta only
+//! touch the integer, so runs with different dt reach bit-identical `sim_time` values
+//! at the same instant instead of drifting apart through float accumulation.
+
+use serde::{Deserialize, Serialize};
+
+/// How close `dt / quantum` must be to an integer to count as a whole number of quanta.
+const WHOLE_QUANTA_TOLERANCE: f64 = 1e-9;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct SimClock {
+    pub quanta: i64,
+    /// Always in `[0, quantum)`.
+    pub residual: f64,
+}
+
+impl SimClock {
+    pub fn from_seconds(seconds: f64, quantum: f64) -> Self {
+        let mut clock = Self::default();
+        clock.advance(seconds, quantum);
+        clock
+    }
+
+    pub fn seconds(&self, quantum: f64) -> f64 {
+        self.quanta as f64 * quantum + self.residual
+    }
+
+    pub fn advance(&mut self, dt: f64, quantum: f64) {
+        let steps = dt / quantum;
+        let whole = steps.round();
+        if (steps - whole).abs() <= WHOLE_QUANTA_TOLERANCE * whole.abs().max(1.0) {
+            self.quanta += whole as i64;
+            return;
+        }
+        self.residual += dt;
+        let carry = (self.residual / quantum).floor();
+        self.quanta += carry as i64;
+        self.residual = (self.residual - carry * quantum).clamp(0.0, quantum);
+        if self.residual >= quantum {
+            self.quanta += 1;
+            self.residual = 0.0;
+        }
+    }
+}
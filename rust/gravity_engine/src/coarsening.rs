@@ -0,0 +1,110 @@
+//! Far-field coarse-graining: light bodies outside the region of interest that
+//! share a grid cell are merged into one super-particle carrying their total mass,
+//! momentum and centre of mass.
+
+use std::collections::BTreeMap;
+
+use crate::collision::merged_angular_velocity;
+use crate::config::CoarseGraining;
+use crate::math::Vec2;
+use crate::types::Body;
+
+pub(crate) struct Aggregate {
+    pub(crate) survivor: String,
+    pub(crate) absorbed: Vec<String>,
+    /// Masses of every member before the merge, survivor included, in body order.
+    pub(crate) member_masses: Vec<f64>,
+    pub(crate) mass: f64,
+    pub(crate) position: Vec2,
+}
+
+/// Merges each qualifying cell into its heaviest member and drops the rest. Cells
+/// are keyed by interaction group too, so group rules still hold afterwards.
+pub(crate) fn coarse_grain(bodies: &mut Vec<Body>, settings: &CoarseGraining) -> Vec<Aggregate> {
+    let mut cells = BTreeMap::<(i64, i64, u32), Vec<usize>>::new();
+    for (index, body) in bodies.iter().enumerate() {
+        let far = (body.position - settings.focus).norm() > settings.focus_radius;
+        if body.alive && !body.fixed && body.mass < settings.max_body_mass && far {
+            let cell = body.position / settings.cell_size;
+            cells
+                .entry((
+                    cell.x.floor() as i64,
+                    cell.y.floor() as i64,
+                    body.interaction_group(),
+                ))
+                .or_default()
+                .push(index);
+        }
+    }
+
+    let mut aggregates = Vec::new();
+    for members in cells.into_values() {
+        if members.len() < settings.min_members.max(2) {
+            continue;
+        }
+        let survivor = members
+            .iter()
+            .copied()
+            .reduce(|best, index| {
+                if bodies[index].mass > bodies[best].mass {
+                    index
+                } else {
+                    best
+                }
+            })
+            .expect("cells hold at least two members");
+        let mass = members.iter().map(|&index| bodies[index].mass).sum::<f64>();
+        let position = members
+            .iter()
+            .map(|&index| bodies[index].position * bodies[index].mass)
+            .sum::<Vec2>()
+            / mass;
+        let velocity = members
+            .iter()
+            .map(|&index| bodies[index].velocity * bodies[index].mass)
+            .sum::<Vec2>()
+            / mass;
+        // Area-preserving, like collision merges.
+        let radius = members
+            .iter()
+            .map(|&index| bodies[index].radius * bodies[index].radius)
+            .sum::<f64>()
+            .sqrt();
+
+        let angular_velocity = merged_angular_velocity(
+            members.iter().map(|&index| &bodies[index]),
+            position,
+            velocity,
+            mass,
+            radius,
+        );
+
+        let member_masses = members.iter().map(|&index| bodies[index].mass).collect();
+        let absorbed = members
+            .iter()
+            .filter(|&&index| index != survivor)
+            .map(|&index| {
+                bodies[index].alive = false;
+                bodies[index].id.clone()
+            })
+            .collect();
+        let merged = &mut bodies[survivor];
+        merged.mass = mass;
+        merged.position = position;
+        merged.velocity = velocity;
+        merged.radius = radius;
+        merged.angular_velocity = angular_velocity;
+        aggregates.push(Aggregate {
+            survivor: merged.id.clone(),
+            absorbed,
+            member_masses,
+            mass,
+            position,
+        });
+    }
+
+    if !aggregates.is_empty() {
+        bodies.retain(|body| body.alive);
+    }
+    aggregates
+}
@@ -1,29 +1,50 @@
-use crate::config::CollisionMode;
+use crate::config::{CollisionMode, EngineConfig};
+use crate::events::ImpactReport;
 use crate::math::Vec2;
-use crate::types::Body;
+use crate::types::{Body, BodyMetadata};
 
-#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq)]
+pub struct CollisionContact {
+    pub body_a: String,
+    pub body_b: String,
+    /// Id of the surviving body when the pair merged.
+    pub merged_into: Option<String>,
+    pub impact: Option<ImpactReport>,
+    /// Survivor's mass right after the merge.
+    pub merged_mass: Option<f64>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
 pub struct CollisionStats {
     pub collisions: u64,
     pub merges: u64,
+    pub contacts: Vec<CollisionContact>,
 }
 
-pub(crate) fn resolve_collisions(bodies: &mut Vec<Body>, mode: CollisionMode) -> CollisionStats {
+pub(crate) fn resolve_collisions(bodies: &mut Vec<Body>, config: &EngineConfig) -> CollisionStats {
+    let mode = config.collision_mode;
     if matches!(mode, CollisionMode::Ignore) {
         return CollisionStats::default();
     }
 
     let mut stats = CollisionStats::default();
-    let count = bodies.len();
+    let candidates = (0..bodies.len())
+        .filter(|&index| bodies[index].collidable)
+        .collect::<Vec<_>>();
 
-    for i in 0..count {
+    for (slot, &i) in candidates.iter().enumerate() {
         if !bodies[i].alive {
             continue;
         }
-        for j in (i + 1)..count {
+        for &j in &candidates[(slot + 1)..] {
             if !bodies[j].alive {
                 continue;
             }
+            if config.interaction_groups.as_ref().is_some_and(|groups| {
+                !groups.collides(bodies[i].interaction_group(), bodies[j].interaction_group())
+            }) {
+                continue;
+            }
 
             let delta = bodies[j].position - bodies[i].position;
             let distance = delta.norm();
@@ -34,17 +55,35 @@ pub(crate) fn resolve_collisions(bodies: &mut Vec<Body>, mode: CollisionMode) ->
             }
 
             stats.collisions += 1;
+            let mut contact = CollisionContact {
+                body_a: bodies[i].id.clone(),
+                body_b: bodies[j].id.clone(),
+                merged_into: None,
+                impact: None,
+                merged_mass: None,
+            };
 
             match mode {
                 CollisionMode::Elastic => {
-                    apply_elastic_collision(bodies, i, j, delta, distance, collision_distance);
+                    apply_elastic_collision(
+                        bodies,
+                        (i, j),
+                        delta,
+                        distance,
+                        collision_distance,
+                        config,
+                    );
                 }
                 CollisionMode::InelasticMerge => {
-                    apply_inelastic_merge(bodies, i, j);
+                    contact.impact = Some(impact_report(&bodies[i], &bodies[j]));
+                    apply_inelastic_merge(bodies, i, j, config.count_impacts);
                     stats.merges += 1;
+                    contact.merged_into = Some(bodies[i].id.clone());
+                    contact.merged_mass = Some(bodies[i].mass);
                 }
                 CollisionMode::Ignore => {}
             }
+            stats.contacts.push(contact);
         }
     }
 
@@ -55,7 +94,29 @@ pub(crate) fn resolve_collisions(bodies: &mut Vec<Body>, mode: CollisionMode) ->
     stats
 }
 
-fn apply_inelastic_merge(bodies: &mut [Body], i: usize, j: usize) {
+fn impact_report(first: &Body, second: &Body) -> ImpactReport {
+    let relative_velocity = second.velocity - first.velocity;
+    let separation = second.position - first.position;
+    let speed = relative_velocity.norm();
+    let reduced_mass = first.mass * second.mass / (first.mass + second.mass);
+    let lengths = speed * separation.norm();
+    let angle = if lengths > 0.0 {
+        (relative_velocity.dot(separation).abs() / lengths)
+            .min(1.0)
+            .acos()
+    } else {
+        0.0
+    };
+    ImpactReport {
+        energy: 0.5 * reduced_mass * speed * speed,
+        speed,
+        angle,
+    }
+}
+
+/// With `count_impacts`, the survivor's `impacts_absorbed` grows by one plus the
+/// count the absorbed body carried.
+fn apply_inelastic_merge(bodies: &mut [Body], i: usize, j: usize, count_impacts: bool) {
     let (first, second) = get_pair_mut(bodies, i, j);
     if !first.alive || !second.alive {
         return;
@@ -66,29 +127,57 @@ fn apply_inelastic_merge(bodies: &mut [Body], i: usize, j: usize) {
         return;
     }
 
-    let merged_position =
-        (first.position * first.mass + second.position * second.mass) / total_mass;
-    let merged_velocity =
-        (first.velocity * first.mass + second.velocity * second.mass) / total_mass;
+    // An anchor absorbs whatever hits it and stays put.
+    let (merged_position, merged_velocity) = match (first.fixed, second.fixed) {
+        (true, _) => (first.position, first.velocity),
+        (false, true) => (second.position, second.velocity),
+        (false, false) => (
+            (first.position * first.mass + second.position * second.mass) / total_mass,
+            (first.velocity * first.mass + second.velocity * second.mass) / total_mass,
+        ),
+    };
     let merged_radius = (first.radius * first.radius + second.radius * second.radius).sqrt();
+    let merged_spin = merged_angular_velocity(
+        [&*first, &*second],
+        merged_position,
+        merged_velocity,
+        total_mass,
+        merged_radius,
+    );
+
+    // The merged body keeps the origin tag of whichever side dominated the mass.
+    if second.mass > first.mass {
+        first.origin_group = second.origin_group;
+    }
 
     first.mass = total_mass;
     first.position = merged_position;
     first.velocity = merged_velocity;
     first.radius = merged_radius;
+    first.angular_velocity = merged_spin;
+    first.fixed |= second.fixed;
+    if count_impacts {
+        let absorbed = second
+            .metadata
+            .as_ref()
+            .map_or(0, |metadata| metadata.impacts_absorbed);
+        let metadata = first.metadata.get_or_insert_with(BodyMetadata::default);
+        metadata.impacts_absorbed += 1 + absorbed;
+    }
 
     second.alive = false;
 }
 
 fn apply_elastic_collision(
     bodies: &mut [Body],
-    i: usize,
-    j: usize,
+    (i, j): (usize, usize),
     delta: Vec2,
     distance: f64,
     collision_distance: f64,
+    config: &EngineConfig,
 ) {
     let (first, second) = get_pair_mut(bodies, i, j);
+    let (restitution, friction) = (config.restitution, config.collision_friction);
     if !first.alive || !second.alive {
         return;
     }
@@ -99,24 +188,82 @@ fn apply_elastic_collision(
         Vec2::new(1.0, 0.0)
     };
 
+    // Fixed bodies behave as infinitely massive: zero inverse mass, no share of the
+    // overlap correction.
+    let inverse_mass = |body: &Body| if body.fixed { 0.0 } else { 1.0 / body.mass };
+    let (first_inverse, second_inverse) = (inverse_mass(first), inverse_mass(second));
     let relative_velocity = second.velocity - first.velocity;
     let vel_along_normal = relative_velocity.dot(normal);
     if vel_along_normal <= 0.0 {
-        let restitution = 1.0;
-        let inverse_mass_sum = (1.0 / first.mass) + (1.0 / second.mass);
+        let inverse_mass_sum = first_inverse + second_inverse;
         if inverse_mass_sum > 0.0 {
             let impulse_scalar = -((1.0 + restitution) * vel_along_normal) / inverse_mass_sum;
             let impulse = normal * impulse_scalar;
-            first.velocity -= impulse / first.mass;
-            second.velocity += impulse / second.mass;
+            first.velocity -= impulse * first_inverse;
+            second.velocity += impulse * second_inverse;
+            if friction > 0.0 {
+                let limit = friction * impulse_scalar;
+                apply_contact_friction(first, second, normal, limit, first_inverse, second_inverse);
+            }
         }
     }
 
     let overlap = (collision_distance - distance).max(0.0);
-    if overlap > 0.0 {
-        let correction = normal * (0.5 * overlap + 1e-9);
-        first.position -= correction;
-        second.position += correction;
+    let movable_sum = first_inverse + second_inverse;
+    if overlap > 0.0 && movable_sum > 0.0 {
+        let correction = normal * (overlap + 2e-9);
+        first.position -= correction * (first_inverse / movable_sum);
+        second.position += correction * (second_inverse / movable_sum);
+    }
+}
+
+/// Tangential impulse that stops the contact points slipping, capped at `limit`.
+/// Both bodies are uniform disks, so `r^2 / I = 2 / m` and the tangential effective
+/// inverse mass is `3 / m1 + 3 / m2`.
+fn apply_contact_friction(
+    first: &mut Body,
+    second: &mut Body,
+    normal: Vec2,
+    limit: f64,
+    first_inverse: f64,
+    second_inverse: f64,
+) {
+    let tangent = normal.perp();
+    let slip = (second.velocity - first.velocity).dot(tangent)
+        - second.angular_velocity * second.radius
+        - first.angular_velocity * first.radius;
+    let inverse_mass = 3.0 * (first_inverse + second_inverse);
+    if inverse_mass <= 0.0 {
+        return;
+    }
+    let impulse = (-slip / inverse_mass).clamp(-limit, limit);
+    first.velocity -= tangent * (impulse * first_inverse);
+    second.velocity += tangent * (impulse * second_inverse);
+    first.angular_velocity -= 2.0 * impulse * first_inverse / first.radius;
+    second.angular_velocity -= 2.0 * impulse * second_inverse / second.radius;
+}
+
+/// Spin of a body of `mass` and `radius` formed at `position`, `velocity` from
+/// `members`, carrying their spin and orbital angular momentum about that point.
+pub(crate) fn merged_angular_velocity<'a>(
+    members: impl IntoIterator<Item = &'a Body>,
+    position: Vec2,
+    velocity: Vec2,
+    mass: f64,
+    radius: f64,
+) -> f64 {
+    let angular_momentum = members
+        .into_iter()
+        .map(|body| {
+            body.moment_of_inertia() * body.angular_velocity
+                + (body.position - position).cross(body.velocity - velocity) * body.mass
+        })
+        .sum::<f64>();
+    let inertia = 0.5 * mass * radius * radius;
+    if inertia > 0.0 {
+        angular_momentum / inertia
+    } else {
+        0.0
     }
 }
 
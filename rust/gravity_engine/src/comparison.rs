@@ -0,0 +1,194 @@
+//! Side-by-side runs of one initial system under several configs, for comparing
+//! integrators, solvers or time steps without hand-written harnesses.
+
+use std::collections::{BTreeSet, HashMap};
+use std::thread;
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::EngineConfig;
+use crate::engine::SimulationEngine;
+use crate::errors::{EngineError, Result};
+use crate::types::Body;
+
+/// One engine per labelled config, all started from the same bodies. `step` advances
+/// every engine by the same number of ticks, one worker thread per engine, so the
+/// variants stay in lockstep.
+pub struct MultiEngineRunner {
+    labels: Vec<String>,
+    engines: Vec<SimulationEngine>,
+    initial_energy: Vec<f64>,
+}
+
+/// Where one variant stands after a `step`.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VariantReport {
+    pub label: String,
+    pub tick: u64,
+    pub sim_time: f64,
+    pub total_energy: f64,
+    /// `(E - E0) / |E0|` since construction. Merges and absorptions change it too.
+    pub energy_drift: f64,
+    pub alive_bodies: usize,
+    /// Wall time this variant spent in the last `step`.
+    pub step_wall_time_micros: u64,
+}
+
+/// How far a variant's bodies have moved away from the reference (first) variant's,
+/// over the ids alive in both.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Divergence {
+    pub label: String,
+    pub compared_bodies: usize,
+    pub max_position_delta: f64,
+    pub rms_position_delta: f64,
+    pub max_velocity_delta: f64,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ComparisonReport {
+    pub variants: Vec<VariantReport>,
+    /// One entry per variant after the first.
+    pub divergence: Vec<Divergence>,
+}
+
+impl MultiEngineRunner {
+    /// Fails on an empty or duplicate label, or a config the engine rejects.
+    pub fn new(bodies: Vec<Body>, variants: Vec<(String, EngineConfig)>) -> Result<Self> {
+        if variants.is_empty() {
+            return Err(EngineError::InvalidConfig(
+                "comparison needs at least one variant".to_string(),
+            ));
+        }
+        let mut seen = BTreeSet::new();
+        let mut labels = Vec::with_capacity(variants.len());
+        let mut engines = Vec::with_capacity(variants.len());
+        for (label, config) in variants {
+            if label.is_empty() || !seen.insert(label.clone()) {
+                return Err(EngineError::InvalidConfig(format!(
+                    "variant labels must be unique and non-empty: '{label}'"
+                )));
+            }
+            engines.push(SimulationEngine::with_bodies(config, bodies.clone())?);
+            labels.push(label);
+        }
+        let initial_energy = engines
+            .iter()
+            .map(|engine| engine.diagnostics().total_energy)
+            .collect();
+        Ok(Self {
+            labels,
+            engines,
+            initial_energy,
+        })
+    }
+
+    pub fn labels(&self) -> &[String] {
+        &self.labels
+    }
+
+    pub fn engine(&self, label: &str) -> Option<&SimulationEngine> {
+        let index = self.labels.iter().position(|known| known == label)?;
+        Some(&self.engines[index])
+    }
+
+    /// Steps every variant by `ticks` and compares the results. The first error, in
+    /// variant order, is returned once all workers have finished.
+    pub fn step(&mut self, ticks: u32) -> Result<ComparisonReport> {
+        let results = thread::scope(|scope| {
+            let workers = self
+                .engines
+                .iter_mut()
+                .map(|engine| {
+                    scope.spawn(move || {
+                        engine
+                            .step(ticks)
+                            .map(|summary| summary.step_wall_time_micros)
+                    })
+                })
+                .collect::<Vec<_>>();
+            workers
+                .into_iter()
+                .map(|worker| {
+                    worker.join().unwrap_or_else(|_| {
+                        Err(EngineError::WorkerFailed(
+                            "comparison worker panicked".to_string(),
+                        ))
+                    })
+                })
+                .collect::<Vec<_>>()
+        });
+        let wall_times = results.into_iter().collect::<Result<Vec<_>>>()?;
+        Ok(self.compare(&wall_times))
+    }
+
+    /// The comparison as of now, without stepping.
+    pub fn report(&self) -> ComparisonReport {
+        self.compare(&vec![0; self.engines.len()])
+    }
+
+    fn compare(&self, wall_times: &[u64]) -> ComparisonReport {
+        let variants = self
+            .engines
+            .iter()
+            .enumerate()
+            .map(|(index, engine)| {
+                let total_energy = engine.diagnostics().total_energy;
+                let initial = self.initial_energy[index];
+                VariantReport {
+                    label: self.labels[index].clone(),
+                    tick: engine.tick(),
+                    sim_time: engine.sim_time(),
+                    total_energy,
+                    energy_drift: (total_energy - initial) / initial.abs().max(f64::MIN_POSITIVE),
+                    alive_bodies: engine.bodies().iter().filter(|body| body.alive).count(),
+                    step_wall_time_micros: wall_times[index],
+                }
+            })
+            .collect();
+        let reference = self.engines[0]
+            .bodies()
+            .iter()
+            .filter(|body| body.alive)
+            .map(|body| (body.id.as_str(), body))
+            .collect::<HashMap<_, _>>();
+        let divergence = self.engines[1..]
+            .iter()
+            .zip(&self.labels[1..])
+            .map(|(engine, label)| {
+                let mut divergence = Divergence {
+                    label: label.clone(),
+                    compared_bodies: 0,
+                    max_position_delta: 0.0,
+                    rms_position_delta: 0.0,
+                    max_velocity_delta: 0.0,
+                };
+                let mut sum_squares = 0.0;
+                for body in engine.bodies().iter().filter(|body| body.alive) {
+                    let Some(other) = reference.get(body.id.as_str()) else {
+                        continue;
+                    };
+                    let delta = (body.position - other.position).norm();
+                    divergence.compared_bodies += 1;
+                    divergence.max_position_delta = divergence.max_position_delta.max(delta);
+                    divergence.max_velocity_delta = divergence
+                        .max_velocity_delta
+                        .max((body.velocity - other.velocity).norm());
+                    sum_squares += delta * delta;
+                }
+                if divergence.compared_bodies > 0 {
+                    divergence.rms_position_delta =
+                        (sum_squares / divergence.compared_bodies as f64).sqrt();
+                }
+                divergence
+            })
+            .collect();
+        ComparisonReport {
+            variants,
+            divergence,
+        }
+    }
+}
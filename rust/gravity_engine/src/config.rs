@@ -1,11 +1,18 @@
-use serde::{Deserialize, Serialize};
+use serde::de::Error as _;
+use serde::ser::Error as _;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::{Map, Value};
 use std::collections::hash_map::DefaultHasher;
 use std::hash::{Hash, Hasher};
 
 use crate::errors::{EngineError, Result};
+use crate::events::SimulationEvent;
+use crate::math::Vec2;
+use crate::types::Frame;
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub enum IntegratorKind {
     SemiImplicitEuler,
     VelocityVerlet,
@@ -14,6 +21,7 @@ pub enum IntegratorKind {
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub enum CollisionMode {
     Elastic,
     InelasticMerge,
@@ -22,17 +30,136 @@ pub enum CollisionMode {
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub enum DtPolicy {
     Fixed,
     Adaptive,
+    /// Keeps the tick length at `dt` but splits each tick into power-of-two substeps
+    /// chosen by step doubling against `dt_tolerance`.
+    ErrorControlled,
+    /// Ticks of `dt`, but each body steps by its own power-of-two fraction of `dt`
+    /// (at most `2^max_substep_level` steps per tick), chosen from its acceleration
+    /// and jerk against `block_timestep_eta`. Bodies synchronize at every tick.
+    BlockTimesteps,
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub enum GravitySolver {
     Pairwise,
     BarnesHut,
+    /// Auto uses the vectorized pairwise kernel below the Barnes-Hut threshold when
+    /// the `simd` feature is enabled.
     Auto,
+    /// Vectorized pairwise kernel; requires the `simd` feature. Sums in a different
+    /// order than `Pairwise`, so results agree only to rounding.
+    PairwiseSimd,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct BinaryDetection {
+    pub interval_ticks: u32,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct ExcursionTracking {
+    /// Bodies farther than this from the system centre of mass count as outside.
+    pub system_radius: f64,
+}
+
+/// Kills bodies that have left the system for good, so ejected bodies stop inflating
+/// the Barnes-Hut bounding box. A body escapes once it is beyond `radius` from the
+/// centre of mass of the other bodies, receding from it, and faster than both
+/// `min_speed` and the escape speed from their total mass.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct EscapePolicy {
+    pub radius: f64,
+    #[serde(default)]
+    pub min_speed: f64,
+}
+
+/// Watches for bodies passing inside the Roche limit (see `tidal::roche_limit`) of a
+/// primary at least `min_mass_ratio` times heavier. Each entry is logged once as a
+/// `tidalDisruption` event; with `fragments >= 2` the body is also replaced by that
+/// many equal fragments.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct TidalDisruption {
+    /// 2.44 for a fluid satellite, about 1.26 for a rigid one.
+    #[serde(default = "default_roche_coefficient")]
+    pub roche_coefficient: f64,
+    #[serde(default = "default_tidal_mass_ratio")]
+    pub min_mass_ratio: f64,
+    /// 0 only flags the crossing.
+    #[serde(default)]
+    pub fragments: u32,
+}
+
+impl Default for TidalDisruption {
+    fn default() -> Self {
+        Self {
+            roche_coefficient: default_roche_coefficient(),
+            min_mass_ratio: default_tidal_mass_ratio(),
+            fragments: 0,
+        }
+    }
+}
+
+fn default_roche_coefficient() -> f64 {
+    2.44
+}
+
+fn default_tidal_mass_ratio() -> f64 {
+    10.0
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct ForceCaching {
+    /// Bodies that moved less than this since their cached gravity was computed are
+    /// treated as stationary. Zero keeps only exact reuse, which is lossless.
+    #[serde(default)]
+    pub displacement_threshold: f64,
+}
+
+/// Keeps the pre-tick state of the last `history_ticks` ticks so a
+/// `NumericalInstability` leaves an `InstabilityReport` behind. Costs a copy of the
+/// bodies per tick.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct InstabilityCapture {
+    #[serde(default = "default_history_ticks")]
+    pub history_ticks: usize,
+}
+
+fn default_history_ticks() -> usize {
+    16
+}
+
+fn default_restitution() -> f64 {
+    1.0
+}
+
+fn default_dt_tolerance() -> f64 {
+    1e-6
+}
+
+fn default_block_timestep_eta() -> f64 {
+    0.02
+}
+
+fn default_max_substep_level() -> u8 {
+    8
 }
 
 fn default_gravity_solver() -> GravitySolver {
@@ -43,19 +170,342 @@ fn default_barnes_hut_theta() -> f64 {
     0.6
 }
 
+/// One entry of the interaction matrix. `None` on either side matches every group,
+/// and bodies without a `group` belong to group 0.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct GroupRule {
+    #[serde(default)]
+    pub source: Option<u32>,
+    #[serde(default)]
+    pub target: Option<u32>,
+    /// Whether `source` bodies pull `target` bodies; unset leaves it to earlier rules.
+    #[serde(default)]
+    pub gravity: Option<bool>,
+    /// Whether the two groups collide, in either order; unset leaves it to earlier
+    /// rules.
+    #[serde(default)]
+    pub collisions: Option<bool>,
+}
+
+impl GroupRule {
+    fn matches(&self, source: u32, target: u32) -> bool {
+        self.source.is_none_or(|group| group == source)
+            && self.target.is_none_or(|group| group == target)
+    }
+}
+
+/// Every pair of groups attracts and collides unless a rule says otherwise; later
+/// rules override earlier ones.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct InteractionGroups {
+    pub rules: Vec<GroupRule>,
+}
+
+impl InteractionGroups {
+    pub fn attracts(&self, source: u32, target: u32) -> bool {
+        self.rules
+            .iter()
+            .rev()
+            .filter(|rule| rule.matches(source, target))
+            .find_map(|rule| rule.gravity)
+            .unwrap_or(true)
+    }
+
+    pub fn collides(&self, first: u32, second: u32) -> bool {
+        self.rules
+            .iter()
+            .rev()
+            .filter(|rule| rule.matches(first, second) || rule.matches(second, first))
+            .find_map(|rule| rule.collisions)
+            .unwrap_or(true)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub enum BoundaryMode {
+    /// Bodies bounce off the walls with their normal velocity reversed.
+    Reflect,
+    /// Bodies leaving one side re-enter on the opposite one, and gravity acts through
+    /// the nearest periodic image of each body.
+    Periodic,
+    /// Bodies leaving the world die.
+    Absorb,
+}
+
+/// Axis-aligned world rectangle, enforced after every tick. Periodic worlds always use
+/// the pairwise solver; collisions and diagnostics ignore periodic images.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct WorldBoundary {
+    pub min: Vec2,
+    pub max: Vec2,
+    pub mode: BoundaryMode,
+}
+
+/// Far-field aggregation: every `interval_ticks`, bodies lighter than
+/// `max_body_mass` and farther than `focus_radius` from `focus` that share a grid
+/// cell merge into one super-particle.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct CoarseGraining {
+    pub cell_size: f64,
+    pub max_body_mass: f64,
+    #[serde(default)]
+    pub focus: Vec2,
+    pub focus_radius: f64,
+    #[serde(default = "default_coarse_interval_ticks")]
+    pub interval_ticks: u32,
+    /// Cells with fewer candidates are left alone.
+    #[serde(default = "default_coarse_min_members")]
+    pub min_members: usize,
+}
+
+/// Watches total angular momentum about the origin against the value at the start
+/// of the run. With `correct`, drift beyond `tolerance` is removed by adding a
+/// tiny rigid rotation about the centre of mass of the movable bodies: linear
+/// momentum is untouched and kinetic energy shifts at second order. This trades
+/// strict fidelity for long-run exactness and is meant for demonstrations.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct AngularMomentumGuard {
+    #[serde(default = "default_guard_interval_ticks")]
+    pub interval_ticks: u32,
+    /// Relative drift `(L - L0) / |L0|` tolerated before warning or correcting.
+    #[serde(default)]
+    pub tolerance: f64,
+    #[serde(default)]
+    pub correct: bool,
+}
+
+/// Checks total-energy drift `(E - E0) / |E0|` every `interval_ticks`, an early sign
+/// that dt is too large. Each check is an O(n^2) energy evaluation; merges and edits
+/// re-baseline `E0`. External forces that do work show up as drift too.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct EnergyWatchdog {
+    #[serde(default = "default_watchdog_interval_ticks")]
+    pub interval_ticks: u32,
+    /// Drift magnitude that adds a `StepSummary` warning.
+    #[serde(default = "default_watchdog_warn_drift")]
+    pub warn_drift: f64,
+    /// Drift magnitude that fails the step with `NumericalInstability`.
+    #[serde(default)]
+    pub hard_limit: Option<f64>,
+}
+
+impl Default for EnergyWatchdog {
+    fn default() -> Self {
+        Self {
+            interval_ticks: default_watchdog_interval_ticks(),
+            warn_drift: default_watchdog_warn_drift(),
+            hard_limit: None,
+        }
+    }
+}
+
+impl EnergyWatchdog {
+    pub fn validate(&self) -> Result<()> {
+        let valid_limit = |limit: f64| limit.is_finite() && limit >= 0.0;
+        if self.interval_ticks == 0
+            || !valid_limit(self.warn_drift)
+            || self
+                .hard_limit
+                .is_some_and(|limit| !valid_limit(limit) || limit < self.warn_drift)
+        {
+            return Err(EngineError::InvalidConfig(
+                "energy_watchdog needs interval_ticks >= 1, a finite warn_drift >= 0 and a \
+                 finite hard_limit >= warn_drift"
+                    .to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Retunes `barnes_hut_theta` every `interval_ticks` ticks, in steps of 10%, to hold
+/// `target` while staying within `[min_theta, max_theta]`. Wall-time targets make
+/// runs depend on the machine.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct ThetaTuning {
+    pub target: ThetaTarget,
+    #[serde(default = "default_theta_tuning_interval_ticks")]
+    pub interval_ticks: u32,
+    #[serde(default = "default_min_theta")]
+    pub min_theta: f64,
+    #[serde(default = "default_max_theta")]
+    pub max_theta: f64,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub enum ThetaTarget {
+    /// Average wall time of the Barnes-Hut ticks in each interval. Timing differs
+    /// between runs, so this target is rejected in deterministic mode.
+    #[serde(rename_all = "camelCase")]
+    TickMicros { micros: u64 },
+    /// RMS relative force error of a seeded sample, as in `force_error_sampling`.
+    #[serde(rename_all = "camelCase")]
+    ForceError {
+        rms: f64,
+        #[serde(default = "default_force_error_sample_size")]
+        sample_size: usize,
+    },
+}
+
+impl ThetaTuning {
+    pub fn validate(&self) -> Result<()> {
+        let valid_target = match self.target {
+            ThetaTarget::TickMicros { micros } => micros > 0,
+            ThetaTarget::ForceError { rms, sample_size } => {
+                rms.is_finite() && rms > 0.0 && sample_size > 0
+            }
+        };
+        let valid_range = self.min_theta.is_finite()
+            && self.max_theta.is_finite()
+            && 0.0 < self.min_theta
+            && self.min_theta <= self.max_theta
+            && self.max_theta <= 2.0;
+        if self.interval_ticks == 0 || !valid_target || !valid_range {
+            return Err(EngineError::InvalidConfig(
+                "theta_tuning needs interval_ticks >= 1, a positive target and \
+                 0 < min_theta <= max_theta <= 2"
+                    .to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+fn default_theta_tuning_interval_ticks() -> u32 {
+    10
+}
+
+fn default_min_theta() -> f64 {
+    0.2
+}
+
+fn default_max_theta() -> f64 {
+    1.2
+}
+
+/// Samples bodies and compares their Barnes-Hut accelerations against exact pairwise
+/// sums, ignoring interaction groups. Costs `sample_size` pairwise rows plus one tree
+/// build per sample.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct ForceErrorSampling {
+    /// Ticks between samples while stepping.
+    #[serde(default = "default_force_error_interval_ticks")]
+    pub interval_ticks: u32,
+    #[serde(default = "default_force_error_sample_size")]
+    pub sample_size: usize,
+    /// Periodic samples add the tick so each draws different bodies.
+    #[serde(default)]
+    pub seed: u64,
+    /// Opening angle to evaluate; `None` uses `barnes_hut_theta`.
+    #[serde(default)]
+    pub theta: Option<f64>,
+}
+
+impl Default for ForceErrorSampling {
+    fn default() -> Self {
+        Self {
+            interval_ticks: default_force_error_interval_ticks(),
+            sample_size: default_force_error_sample_size(),
+            seed: 0,
+            theta: None,
+        }
+    }
+}
+
+impl ForceErrorSampling {
+    pub fn validate(&self) -> Result<()> {
+        if self.interval_ticks == 0 || self.sample_size == 0 {
+            return Err(EngineError::InvalidConfig(
+                "force_error_sampling needs interval_ticks and sample_size >= 1".to_string(),
+            ));
+        }
+        if let Some(theta) = self.theta
+            && !(theta.is_finite() && theta > 0.0 && theta <= 2.0)
+        {
+            return Err(EngineError::InvalidConfig(
+                "force_error_sampling theta must be finite and in (0, 2]".to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+fn default_force_error_interval_ticks() -> u32 {
+    100
+}
+
+fn default_force_error_sample_size() -> usize {
+    64
+}
+
+fn default_guard_interval_ticks() -> u32 {
+    64
+}
+
+fn default_watchdog_interval_ticks() -> u32 {
+    100
+}
+
+fn default_watchdog_warn_drift() -> f64 {
+    1e-3
+}
+
+fn default_coarse_interval_ticks() -> u32 {
+    16
+}
+
+fn default_coarse_min_members() -> usize {
+    2
+}
+
 fn default_barnes_hut_threshold() -> usize {
     256
 }
 
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct EngineConfig {
     pub gravity_constant: f64,
     pub softening_epsilon: f64,
     pub dt: f64,
     pub dt_policy: DtPolicy,
+    #[serde(default = "default_dt_tolerance")]
+    pub dt_tolerance: f64,
+    #[serde(default = "default_max_substep_level")]
+    pub max_substep_level: u8,
+    #[serde(default = "default_block_timestep_eta")]
+    pub block_timestep_eta: f64,
     pub integrator: IntegratorKind,
     pub collision_mode: CollisionMode,
+    /// Coefficient of restitution for `Elastic` collisions: 1 is perfectly elastic,
+    /// 0 removes the whole approach velocity along the contact normal.
+    #[serde(default = "default_restitution")]
+    pub restitution: f64,
+    /// Coulomb friction between the surfaces of `Elastic` colliders, trading linear
+    /// momentum for spin at the contact. 0 leaves spins untouched.
+    #[serde(default)]
+    pub collision_friction: f64,
     pub deterministic: bool,
     #[serde(default = "default_gravity_solver")]
     pub gravity_solver: GravitySolver,
@@ -63,6 +513,59 @@ pub struct EngineConfig {
     pub barnes_hut_theta: f64,
     #[serde(default = "default_barnes_hut_threshold")]
     pub barnes_hut_threshold: usize,
+    #[serde(default)]
+    pub binary_detection: Option<BinaryDetection>,
+    #[serde(default)]
+    pub include_diagnostics: bool,
+    #[serde(default)]
+    pub excursion_tracking: Option<ExcursionTracking>,
+    /// Velocity Verlet only. Ticks solved with Barnes-Hut bypass the cache.
+    #[serde(default)]
+    pub force_caching: Option<ForceCaching>,
+    /// Event kinds (see `SimulationEvent::kind`) that end `step` early after the tick
+    /// that emitted them.
+    #[serde(default)]
+    pub pause_on_events: Vec<String>,
+    /// When set, `sim_time` is tracked as whole multiples of this quantum plus a
+    /// residual (see `SimClock`).
+    #[serde(default)]
+    pub time_quantum: Option<f64>,
+    /// Restricts which body groups attract and collide with which.
+    #[serde(default)]
+    pub interaction_groups: Option<InteractionGroups>,
+    /// Stamp a running merge count into the survivor's `metadata.impacts_absorbed`.
+    #[serde(default)]
+    pub count_impacts: bool,
+    #[serde(default)]
+    pub instability_capture: Option<InstabilityCapture>,
+    #[serde(default)]
+    pub coarse_graining: Option<CoarseGraining>,
+    #[serde(default)]
+    pub angular_momentum_guard: Option<AngularMomentumGuard>,
+    #[serde(default)]
+    pub force_error_sampling: Option<ForceErrorSampling>,
+    #[serde(default)]
+    pub energy_watchdog: Option<EnergyWatchdog>,
+    /// No boundary when `None`.
+    #[serde(default)]
+    pub boundary: Option<WorldBoundary>,
+    #[serde(default)]
+    pub remove_escapers: Option<EscapePolicy>,
+    /// Lets the engine move `barnes_hut_theta` at runtime; `None` keeps it fixed.
+    #[serde(default)]
+    pub theta_tuning: Option<ThetaTuning>,
+    /// Roche-limit detection and fragmentation; off when `None`.
+    #[serde(default)]
+    pub tidal_disruption: Option<TidalDisruption>,
+    /// Id of a dominant mass whose pull on every other body is integrated exactly
+    /// along Kepler conics, leaving only perturber-perturber forces to the numerical
+    /// kicks. Ticks run the plain integrator while the body is missing or dead.
+    #[serde(default)]
+    pub central_body: Option<String>,
+    /// Moves the bodies into this frame after every tick, keeping long runs centred
+    /// on the origin. Ticks where the frame's body is missing are left alone.
+    #[serde(default)]
+    pub recenter: Option<Frame>,
 }
 
 impl Default for EngineConfig {
@@ -72,12 +575,36 @@ impl Default for EngineConfig {
             softening_epsilon: 1e-3,
             dt: 1.0,
             dt_policy: DtPolicy::Fixed,
+            dt_tolerance: default_dt_tolerance(),
+            max_substep_level: default_max_substep_level(),
+            block_timestep_eta: default_block_timestep_eta(),
             integrator: IntegratorKind::VelocityVerlet,
             collision_mode: CollisionMode::InelasticMerge,
+            restitution: default_restitution(),
+            collision_friction: 0.0,
             deterministic: true,
             gravity_solver: default_gravity_solver(),
             barnes_hut_theta: default_barnes_hut_theta(),
             barnes_hut_threshold: default_barnes_hut_threshold(),
+            binary_detection: None,
+            include_diagnostics: false,
+            excursion_tracking: None,
+            force_caching: None,
+            pause_on_events: Vec::new(),
+            time_quantum: None,
+            interaction_groups: None,
+            count_impacts: false,
+            instability_capture: None,
+            coarse_graining: None,
+            angular_momentum_guard: None,
+            force_error_sampling: None,
+            energy_watchdog: None,
+            boundary: None,
+            remove_escapers: None,
+            theta_tuning: None,
+            tidal_disruption: None,
+            central_body: None,
+            recenter: None,
         }
     }
 }
@@ -99,11 +626,41 @@ impl EngineConfig {
                 "dt must be finite and > 0".to_string(),
             ));
         }
+        if matches!(self.gravity_solver, GravitySolver::PairwiseSimd) && !cfg!(feature = "simd") {
+            return Err(EngineError::UnsupportedFeature(
+                "pairwiseSimd gravity solver requires the simd feature".to_string(),
+            ));
+        }
         if self.deterministic && matches!(self.dt_policy, DtPolicy::Adaptive) {
             return Err(EngineError::InvalidConfig(
                 "adaptive dt is not allowed in deterministic mode".to_string(),
             ));
         }
+        if !self.collision_friction.is_finite() || self.collision_friction < 0.0 {
+            return Err(EngineError::InvalidConfig(
+                "collision_friction must be finite and >= 0".to_string(),
+            ));
+        }
+        if !(0.0..=1.0).contains(&self.restitution) {
+            return Err(EngineError::InvalidConfig(
+                "restitution must be in [0, 1]".to_string(),
+            ));
+        }
+        if !self.dt_tolerance.is_finite() || self.dt_tolerance <= 0.0 {
+            return Err(EngineError::InvalidConfig(
+                "dt_tolerance must be finite and > 0".to_string(),
+            ));
+        }
+        if !self.block_timestep_eta.is_finite() || self.block_timestep_eta <= 0.0 {
+            return Err(EngineError::InvalidConfig(
+                "block_timestep_eta must be finite and > 0".to_string(),
+            ));
+        }
+        if self.max_substep_level > 16 {
+            return Err(EngineError::InvalidConfig(
+                "max_substep_level must be <= 16".to_string(),
+            ));
+        }
         if !self.barnes_hut_theta.is_finite()
             || self.barnes_hut_theta <= 0.0
             || self.barnes_hut_theta > 2.0
@@ -117,9 +674,163 @@ impl EngineConfig {
                 "barnes_hut_threshold must be >= 1".to_string(),
             ));
         }
+        if let Some(detection) = &self.binary_detection
+            && detection.interval_ticks == 0
+        {
+            return Err(EngineError::InvalidConfig(
+                "binary_detection.interval_ticks must be >= 1".to_string(),
+            ));
+        }
+        if let Some(tracking) = &self.excursion_tracking
+            && (!tracking.system_radius.is_finite() || tracking.system_radius <= 0.0)
+        {
+            return Err(EngineError::InvalidConfig(
+                "excursion_tracking.system_radius must be finite and > 0".to_string(),
+            ));
+        }
+        if let Some(caching) = &self.force_caching
+            && (!caching.displacement_threshold.is_finite() || caching.displacement_threshold < 0.0)
+        {
+            return Err(EngineError::InvalidConfig(
+                "force_caching.displacement_threshold must be finite and >= 0".to_string(),
+            ));
+        }
+        if self
+            .time_quantum
+            .is_some_and(|quantum| !quantum.is_finite() || quantum <= 0.0)
+        {
+            return Err(EngineError::InvalidConfig(
+                "time_quantum must be finite and > 0".to_string(),
+            ));
+        }
+        if self
+            .instability_capture
+            .as_ref()
+            .is_some_and(|capture| capture.history_ticks == 0)
+        {
+            return Err(EngineError::InvalidConfig(
+                "instability_capture.history_ticks must be > 0".to_string(),
+            ));
+        }
+        if let Some(coarse) = &self.coarse_graining {
+            let positive = |value: f64| value.is_finite() && value > 0.0;
+            if !positive(coarse.cell_size)
+                || !positive(coarse.max_body_mass)
+                || !coarse.focus_radius.is_finite()
+                || coarse.focus_radius < 0.0
+                || !coarse.focus.is_finite()
+                || coarse.interval_ticks == 0
+            {
+                return Err(EngineError::InvalidConfig(
+                    "coarse_graining needs positive cell_size, max_body_mass and interval_ticks, \
+                     and a finite focus with focus_radius >= 0"
+                        .to_string(),
+                ));
+            }
+        }
+        if let Some(guard) = &self.angular_momentum_guard
+            && (guard.interval_ticks == 0 || !guard.tolerance.is_finite() || guard.tolerance < 0.0)
+        {
+            return Err(EngineError::InvalidConfig(
+                "angular_momentum_guard needs interval_ticks >= 1 and a finite tolerance >= 0"
+                    .to_string(),
+            ));
+        }
+        if let Some(sampling) = &self.force_error_sampling {
+            sampling.validate()?;
+        }
+        if let Some(watchdog) = &self.energy_watchdog {
+            watchdog.validate()?;
+        }
+        if let Some(tuning) = &self.theta_tuning {
+            tuning.validate()?;
+            if self.deterministic && matches!(tuning.target, ThetaTarget::TickMicros { .. }) {
+                return Err(EngineError::InvalidConfig(
+                    "theta_tuning by tick time is not allowed in deterministic mode".to_string(),
+                ));
+            }
+        }
+        if let Some(central) = &self.central_body {
+            if central.trim().is_empty() {
+                return Err(EngineError::InvalidConfig(
+                    "central_body must name a body".to_string(),
+                ));
+            }
+            if !matches!(self.dt_policy, DtPolicy::Fixed | DtPolicy::Adaptive)
+                || self.interaction_groups.is_some()
+                || self.periodic_extent().is_some()
+            {
+                return Err(EngineError::InvalidConfig(
+                    "central_body needs a fixed or adaptive dt and no interaction groups or periodic boundary"
+                        .to_string(),
+                ));
+            }
+        }
+        if let Some(frame) = &self.recenter {
+            if matches!(frame, Frame::Body(id) if id.trim().is_empty()) {
+                return Err(EngineError::InvalidConfig(
+                    "recenter frame must name a body".to_string(),
+                ));
+            }
+            if self.boundary.is_some() {
+                return Err(EngineError::InvalidConfig(
+                    "recenter would move bodies relative to the boundary walls".to_string(),
+                ));
+            }
+        }
+        if let Some(tidal) = &self.tidal_disruption
+            && !(tidal.roche_coefficient.is_finite()
+                && tidal.roche_coefficient > 0.0
+                && tidal.min_mass_ratio.is_finite()
+                && tidal.min_mass_ratio >= 1.0
+                && tidal.fragments != 1
+                && tidal.fragments <= 64)
+        {
+            return Err(EngineError::InvalidConfig(
+                "tidal_disruption needs roche_coefficient > 0, min_mass_ratio >= 1 and 0 or 2..=64 fragments"
+                    .to_string(),
+            ));
+        }
+        if let Some(boundary) = &self.boundary
+            && !(boundary.min.is_finite()
+                && boundary.max.is_finite()
+                && boundary.min.x < boundary.max.x
+                && boundary.min.y < boundary.max.y)
+        {
+            return Err(EngineError::InvalidConfig(
+                "boundary needs finite corners with min < max on both axes".to_string(),
+            ));
+        }
+        if let Some(policy) = &self.remove_escapers
+            && !(policy.radius.is_finite()
+                && policy.radius > 0.0
+                && policy.min_speed.is_finite()
+                && policy.min_speed >= 0.0)
+        {
+            return Err(EngineError::InvalidConfig(
+                "remove_escapers needs a finite radius > 0 and a finite min_speed >= 0".to_string(),
+            ));
+        }
+        if let Some(kind) = self
+            .pause_on_events
+            .iter()
+            .find(|kind| !SimulationEvent::KINDS.contains(&kind.as_str()))
+        {
+            return Err(EngineError::InvalidConfig(format!(
+                "pause_on_events contains unknown event kind '{kind}'"
+            )));
+        }
         Ok(())
     }
 
+    /// World size when the boundary is periodic.
+    pub fn periodic_extent(&self) -> Option<Vec2> {
+        self.boundary
+            .as_ref()
+            .filter(|boundary| boundary.mode == BoundaryMode::Periodic)
+            .map(|boundary| boundary.max - boundary.min)
+    }
+
     pub fn stable_hash(&self) -> String {
         let mut hasher = DefaultHasher::new();
         self.integrator.hash(&mut hasher);
@@ -132,6 +843,216 @@ impl EngineConfig {
         self.softening_epsilon.to_bits().hash(&mut hasher);
         self.dt.to_bits().hash(&mut hasher);
         self.barnes_hut_theta.to_bits().hash(&mut hasher);
+        if self.restitution != 1.0 {
+            self.restitution.to_bits().hash(&mut hasher);
+        }
+        if self.collision_friction != 0.0 {
+            self.collision_friction.to_bits().hash(&mut hasher);
+        }
+        if matches!(self.dt_policy, DtPolicy::ErrorControlled) {
+            self.dt_tolerance.to_bits().hash(&mut hasher);
+            self.max_substep_level.hash(&mut hasher);
+        }
+        if matches!(self.dt_policy, DtPolicy::BlockTimesteps) {
+            self.block_timestep_eta.to_bits().hash(&mut hasher);
+            self.max_substep_level.hash(&mut hasher);
+        }
+        if let Some(quantum) = self.time_quantum {
+            quantum.to_bits().hash(&mut hasher);
+        }
+        if let Some(groups) = &self.interaction_groups {
+            for rule in &groups.rules {
+                (rule.source, rule.target, rule.gravity, rule.collisions).hash(&mut hasher);
+            }
+        }
+        if let Some(coarse) = &self.coarse_graining {
+            for value in [
+                coarse.cell_size,
+                coarse.max_body_mass,
+                coarse.focus.x,
+                coarse.focus.y,
+                coarse.focus_radius,
+            ] {
+                value.to_bits().hash(&mut hasher);
+            }
+            (coarse.interval_ticks, coarse.min_members).hash(&mut hasher);
+        }
+        if let Some(guard) = &self.angular_momentum_guard
+            && guard.correct
+        {
+            (guard.interval_ticks, guard.tolerance.to_bits()).hash(&mut hasher);
+        }
+        if let Some(boundary) = &self.boundary {
+            boundary.mode.hash(&mut hasher);
+            for value in [
+                boundary.min.x,
+                boundary.min.y,
+                boundary.max.x,
+                boundary.max.y,
+            ] {
+                value.to_bits().hash(&mut hasher);
+            }
+        }
+        if let Some(policy) = &self.remove_escapers {
+            (policy.radius.to_bits(), policy.min_speed.to_bits()).hash(&mut hasher);
+        }
+        if let Some(tuning) = &self.theta_tuning {
+            let target = match tuning.target {
+                ThetaTarget::TickMicros { micros } => (0, micros),
+                ThetaTarget::ForceError { rms, sample_size } => (rms.to_bits(), sample_size as u64),
+            };
+            (
+                target,
+                tuning.interval_ticks,
+                tuning.min_theta.to_bits(),
+                tuning.max_theta.to_bits(),
+            )
+                .hash(&mut hasher);
+        }
+        if let Some(central) = &self.central_body {
+            central.hash(&mut hasher);
+        }
+        if let Some(frame) = &self.recenter {
+            frame.hash(&mut hasher);
+        }
+        if let Some(caching) = &self.force_caching {
+            caching.displacement_threshold.to_bits().hash(&mut hasher);
+        }
+        if let Some(tidal) = &self.tidal_disruption {
+            (
+                tidal.roche_coefficient.to_bits(),
+                tidal.min_mass_ratio.to_bits(),
+                tidal.fragments,
+            )
+                .hash(&mut hasher);
+        }
         format!("{:016x}", hasher.finish())
     }
 }
+
+/// Partial config update: a camelCase object holding only the fields to change, as
+/// in the `EngineConfig` JSON. `null` clears an optional setting.
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(
+    feature = "schema",
+    derive(schemars::JsonSchema),
+    schemars(transparent)
+)]
+pub struct ConfigPatch(pub Map<String, Value>);
+
+// Binary formats can't carry free-form JSON values, so they get the patch as a JSON
+// string instead.
+impl Serialize for ConfigPatch {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            self.0.serialize(serializer)
+        } else {
+            serde_json::to_string(&self.0)
+                .map_err(S::Error::custom)?
+                .serialize(serializer)
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for ConfigPatch {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        if deserializer.is_human_readable() {
+            Map::deserialize(deserializer).map(Self)
+        } else {
+            let json = String::deserialize(deserializer)?;
+            serde_json::from_str(&json)
+                .map(Self)
+                .map_err(D::Error::custom)
+        }
+    }
+}
+
+impl ConfigPatch {
+    pub fn set(mut self, field: &str, value: impl Serialize) -> Self {
+        // Config values are plain data, so serializing them cannot fail.
+        let value = serde_json::to_value(value).expect("config values serialize to JSON");
+        self.0.insert(field.to_string(), value);
+        self
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigChange {
+    pub field: String,
+    pub before: Value,
+    pub after: Value,
+    /// The change alters `stable_hash`, so runs replayed across it diverge.
+    pub affects_replay: bool,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigDiff {
+    pub changes: Vec<ConfigChange>,
+    pub warnings: Vec<String>,
+}
+
+impl ConfigDiff {
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    pub fn changed(&self, field: &str) -> bool {
+        self.changes.iter().any(|change| change.field == field)
+    }
+}
+
+impl EngineConfig {
+    /// Validated copy of `self` with `patch` applied, plus the fields that changed, sorted by
+    /// name. Unknown fields are rejected rather than ignored.
+    pub fn apply_patch(&self, patch: &ConfigPatch) -> Result<(EngineConfig, ConfigDiff)> {
+        let Value::Object(current) = to_json(self)? else {
+            unreachable!("EngineConfig serializes to an object");
+        };
+        if let Some(field) = patch.0.keys().find(|field| !current.contains_key(*field)) {
+            return Err(EngineError::InvalidConfig(format!(
+                "unknown config field '{field}'"
+            )));
+        }
+        let mut merged = current.clone();
+        merged.extend(patch.0.clone());
+        let config = from_json(Value::Object(merged))?;
+        config.validate()?;
+
+        let Value::Object(updated) = to_json(&config)? else {
+            unreachable!("EngineConfig serializes to an object");
+        };
+        let hash = self.stable_hash();
+        let mut changes = Vec::new();
+        for (field, before) in &current {
+            let after = &updated[field];
+            if before == after {
+                continue;
+            }
+            let mut single = current.clone();
+            single.insert(field.clone(), after.clone());
+            changes.push(ConfigChange {
+                field: field.clone(),
+                before: before.clone(),
+                after: after.clone(),
+                affects_replay: from_json(Value::Object(single))?.stable_hash() != hash,
+            });
+        }
+        Ok((
+            config,
+            ConfigDiff {
+                changes,
+                warnings: Vec::new(),
+            },
+        ))
+    }
+}
+
+fn to_json(config: &EngineConfig) -> Result<Value> {
+    serde_json::to_value(config).map_err(|error| EngineError::InvalidConfig(error.to_string()))
+}
+
+fn from_json(value: Value) -> Result<EngineConfig> {
+    serde_json::from_value(value).map_err(|error| EngineError::InvalidConfig(error.to_string()))
+}
@@ -0,0 +1,390 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::EngineConfig;
+use crate::forces::pair_softening_squared;
+use crate::math::Vec2;
+use crate::types::Body;
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Diagnostics {
+    /// Translational plus spin kinetic energy.
+    pub kinetic_energy: f64,
+    /// Softened exactly like the force law, so it pairs with the simulated dynamics.
+    pub potential_energy: f64,
+    pub total_energy: f64,
+    pub linear_momentum: Vec2,
+    /// Angular momentum about the origin (the out-of-plane component), spin included.
+    pub angular_momentum: f64,
+    pub center_of_mass: Vec2,
+    pub total_mass: f64,
+}
+
+/// Relative Barnes-Hut acceleration error `|a_bh - a_exact| / |a_exact|` over a
+/// sample of bodies.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ForceErrorStats {
+    pub theta: f64,
+    /// Bodies compared; those feeling no exact force are skipped.
+    pub sampled: usize,
+    pub mean: f64,
+    /// Root mean square of the relative errors.
+    #[serde(default)]
+    pub rms: f64,
+    /// Nearest-rank 95th percentile.
+    pub p95: f64,
+    pub max: f64,
+}
+
+impl ForceErrorStats {
+    pub(crate) fn from_errors(theta: f64, mut errors: Vec<f64>) -> Self {
+        if errors.is_empty() {
+            return Self {
+                theta,
+                ..Self::default()
+            };
+        }
+        errors.sort_by(f64::total_cmp);
+        let rank = (0.95 * errors.len() as f64).ceil() as usize;
+        Self {
+            theta,
+            sampled: errors.len(),
+            mean: errors.iter().sum::<f64>() / errors.len() as f64,
+            rms: (errors.iter().map(|error| error * error).sum::<f64>() / errors.len() as f64)
+                .sqrt(),
+            p95: errors[rank.clamp(1, errors.len()) - 1],
+            max: errors[errors.len() - 1],
+        }
+    }
+}
+
+pub fn compute_diagnostics(bodies: &[Body], config: &EngineConfig) -> Diagnostics {
+    let alive = bodies.iter().filter(|body| body.alive).collect::<Vec<_>>();
+    let mut diagnostics = Diagnostics::default();
+    let mut weighted_position = Vec2::ZERO;
+
+    for body in &alive {
+        let momentum = body.velocity * body.mass;
+        diagnostics.kinetic_energy += 0.5 * body.mass * body.velocity.norm_squared()
+            + 0.5 * body.moment_of_inertia() * body.angular_velocity * body.angular_velocity;
+        diagnostics.linear_momentum += momentum;
+        diagnostics.angular_momentum +=
+            body.position.cross(momentum) + body.moment_of_inertia() * body.angular_velocity;
+        diagnostics.total_mass += body.mass;
+        weighted_position += body.position * body.mass;
+    }
+
+    for (i, first) in alive.iter().enumerate() {
+        for second in &alive[(i + 1)..] {
+            let dist_sq = (second.position - first.position).norm_squared()
+                + pair_softening_squared(first, second, config.softening_epsilon);
+            if dist_sq > 0.0 {
+                diagnostics.potential_energy -=
+                    config.gravity_constant * first.mass * second.mass / dist_sq.sqrt();
+            }
+        }
+    }
+
+    diagnostics.total_energy = diagnostics.kinetic_energy + diagnostics.potential_energy;
+    if diagnostics.total_mass > 0.0 {
+        diagnostics.center_of_mass = weighted_position / diagnostics.total_mass;
+    }
+    diagnostics
+}
+
+/// Total angular momentum of the alive bodies about the origin, spin included.
+pub(crate) fn angular_momentum(bodies: &[Body]) -> f64 {
+    bodies
+        .iter()
+        .filter(|body| body.alive)
+        .map(|body| {
+            body.position.cross(body.velocity * body.mass)
+                + body.moment_of_inertia() * body.angular_velocity
+        })
+        .sum()
+}
+
+/// Adds `delta` to the angular momentum with the rigid rotation `omega x (r - R)`
+/// about the centre of mass `R` of the alive, non-fixed bodies, which leaves their
+/// linear momentum unchanged. Returns the change actually applied.
+pub(crate) fn add_angular_momentum(bodies: &mut [Body], delta: f64) -> f64 {
+    let movable = |body: &Body| body.alive && !body.fixed;
+    let (moment, mass) = bodies
+        .iter()
+        .filter(|body| movable(body))
+        .fold((Vec2::ZERO, 0.0), |(moment, mass), body| {
+            (moment + body.position * body.mass, mass + body.mass)
+        });
+    if mass <= 0.0 {
+        return 0.0;
+    }
+    let center = moment / mass;
+    let inertia = bodies
+        .iter()
+        .filter(|body| movable(body))
+        .map(|body| body.mass * (body.position - center).norm_squared())
+        .sum::<f64>();
+    if inertia <= 0.0 {
+        return 0.0;
+    }
+    let omega = delta / inertia;
+    for body in bodies.iter_mut().filter(|body| movable(body)) {
+        body.velocity += (body.position - center).perp() * omega;
+    }
+    delta
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JacobiSample {
+    pub body_id: String,
+    pub jacobi_constant: f64,
+}
+
+/// Restricted three-body Jacobi constant `C = w^2 r^2 + 2 G m1 / r1 + 2 G m2 / r2 - v_rot^2`
+/// for every alive body other than the two primaries.
+///
+/// The rotating frame is centred on the primaries' barycentre and turns at their
+/// instantaneous angular rate, so it matches the classic definition for circular
+/// primaries. Distances are unsoftened.
+pub fn jacobi_constants(
+    bodies: &[Body],
+    primary: usize,
+    secondary: usize,
+    gravity_constant: f64,
+) -> Vec<JacobiSample> {
+    let (first, second) = (&bodies[primary], &bodies[secondary]);
+    let pair_mass = first.mass + second.mass;
+    if pair_mass <= 0.0 {
+        return Vec::new();
+    }
+    let barycenter = (first.position * first.mass + second.position * second.mass) / pair_mass;
+    let barycenter_velocity =
+        (first.velocity * first.mass + second.velocity * second.mass) / pair_mass;
+    let separation = second.position - first.position;
+    let separation_sq = separation.norm_squared();
+    if separation_sq <= 0.0 {
+        return Vec::new();
+    }
+    let angular_rate = separation.cross(second.velocity - first.velocity) / separation_sq;
+
+    bodies
+        .iter()
+        .enumerate()
+        .filter(|(index, body)| body.alive && *index != primary && *index != secondary)
+        .map(|(_, body)| {
+            let offset = body.position - barycenter;
+            let rotating_velocity =
+                body.velocity - barycenter_velocity - offset.perp() * angular_rate;
+            let potential = gravity_constant
+                * (first.mass / body.position.distance(first.position)
+                    + second.mass / body.position.distance(second.position));
+            JacobiSample {
+                body_id: body.id.clone(),
+                jacobi_constant: angular_rate * angular_rate * offset.norm_squared()
+                    + 2.0 * potential
+                    - rotating_velocity.norm_squared(),
+            }
+        })
+        .collect()
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GroupDiagnostics {
+    pub origin_group: u32,
+    pub body_count: usize,
+    pub total_mass: f64,
+    /// Mass fraction with negative specific energy relative to the group itself.
+    pub bound_fraction: f64,
+    pub center_of_mass: Vec2,
+    pub mean_velocity: Vec2,
+    /// Mass-weighted RMS speed about the group's mean velocity.
+    pub velocity_dispersion: f64,
+    /// `1 - b/a` for the axes of the mass-weighted second-moment ellipse: 0 for a
+    /// round group, approaching 1 as it is stretched into a line.
+    #[serde(default)]
+    pub ellipticity: f64,
+}
+
+pub fn group_diagnostics(bodies: &[Body], config: &EngineConfig) -> Vec<GroupDiagnostics> {
+    let mut groups: BTreeMap<u32, Vec<&Body>> = BTreeMap::new();
+    for body in bodies.iter().filter(|body| body.alive) {
+        if let Some(group) = body.origin_group {
+            groups.entry(group).or_default().push(body);
+        }
+    }
+
+    groups
+        .into_iter()
+        .map(|(origin_group, members)| {
+            let total_mass = members.iter().map(|body| body.mass).sum::<f64>();
+            let center_of_mass = members
+                .iter()
+                .fold(Vec2::ZERO, |acc, body| acc + body.position * body.mass)
+                / total_mass;
+            let mean_velocity = members
+                .iter()
+                .fold(Vec2::ZERO, |acc, body| acc + body.velocity * body.mass)
+                / total_mass;
+
+            let mut bound_mass = 0.0;
+            let mut dispersion_sum = 0.0;
+            for (i, body) in members.iter().enumerate() {
+                let relative_speed_sq = (body.velocity - mean_velocity).norm_squared();
+                dispersion_sum += body.mass * relative_speed_sq;
+
+                let potential = members
+                    .iter()
+                    .enumerate()
+                    .filter(|(j, _)| *j != i)
+                    .map(|(_, other)| {
+                        let dist_sq = (other.position - body.position).norm_squared()
+                            + pair_softening_squared(body, other, config.softening_epsilon);
+                        -config.gravity_constant * other.mass / dist_sq.sqrt()
+                    })
+                    .sum::<f64>();
+                if 0.5 * relative_speed_sq + potential < 0.0 {
+                    bound_mass += body.mass;
+                }
+            }
+
+            GroupDiagnostics {
+                origin_group,
+                body_count: members.len(),
+                total_mass,
+                bound_fraction: bound_mass / total_mass,
+                center_of_mass,
+                mean_velocity,
+                velocity_dispersion: (dispersion_sum / total_mass).sqrt(),
+                ellipticity: ellipticity(&members, center_of_mass),
+            }
+        })
+        .collect()
+}
+
+fn ellipticity(members: &[&Body], center_of_mass: Vec2) -> f64 {
+    let (mut xx, mut xy, mut yy) = (0.0, 0.0, 0.0);
+    for body in members {
+        let offset = body.position - center_of_mass;
+        xx += body.mass * offset.x * offset.x;
+        xy += body.mass * offset.x * offset.y;
+        yy += body.mass * offset.y * offset.y;
+    }
+    let half_trace = 0.5 * (xx + yy);
+    let spread = (0.25 * (xx - yy) * (xx - yy) + xy * xy).sqrt();
+    let major = half_trace + spread;
+    if major <= 0.0 {
+        return 0.0;
+    }
+    let minor = (half_trace - spread).max(0.0);
+    1.0 - (minor / major).sqrt()
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MassHistogramOptions {
+    pub bins: usize,
+    /// Log-spaced bin edges, which suit the power-law tails of accretion runs.
+    #[serde(default)]
+    pub logarithmic: bool,
+}
+
+impl Default for MassHistogramOptions {
+    fn default() -> Self {
+        Self {
+            bins: 16,
+            logarithmic: true,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MassBin {
+    pub lower: f64,
+    pub upper: f64,
+    pub count: usize,
+    pub mass: f64,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MassDistribution {
+    pub body_count: usize,
+    pub total_mass: f64,
+    pub min_mass: f64,
+    pub max_mass: f64,
+    pub mean_mass: f64,
+    pub median_mass: f64,
+    /// Share of the total mass held by the heaviest body.
+    pub largest_fraction: f64,
+    pub bins: Vec<MassBin>,
+}
+
+pub fn mass_distribution(bodies: &[Body], options: &MassHistogramOptions) -> MassDistribution {
+    let mut masses = bodies
+        .iter()
+        .filter(|body| body.alive)
+        .map(|body| body.mass)
+        .collect::<Vec<_>>();
+    if masses.is_empty() || options.bins == 0 {
+        return MassDistribution::default();
+    }
+    masses.sort_by(f64::total_cmp);
+
+    let count = masses.len();
+    let total_mass = masses.iter().sum::<f64>();
+    let min_mass = masses[0];
+    let max_mass = masses[count - 1];
+    let median_mass = if count.is_multiple_of(2) {
+        0.5 * (masses[count / 2 - 1] + masses[count / 2])
+    } else {
+        masses[count / 2]
+    };
+
+    let logarithmic = options.logarithmic && min_mass > 0.0;
+    let scale = |mass: f64| if logarithmic { mass.ln() } else { mass };
+    let (low, high) = (scale(min_mass), scale(max_mass));
+    let width = (high - low) / options.bins as f64;
+    let edge = |index: usize| {
+        let value = low + width * index as f64;
+        if logarithmic { value.exp() } else { value }
+    };
+
+    let mut bins = (0..options.bins)
+        .map(|index| MassBin {
+            lower: edge(index),
+            upper: if index + 1 == options.bins {
+                max_mass
+            } else {
+                edge(index + 1)
+            },
+            count: 0,
+            mass: 0.0,
+        })
+        .collect::<Vec<_>>();
+    for &mass in &masses {
+        let index = if width > 0.0 {
+            (((scale(mass) - low) / width) as usize).min(options.bins - 1)
+        } else {
+            0
+        };
+        bins[index].count += 1;
+        bins[index].mass += mass;
+    }
+
+    MassDistribution {
+        body_count: count,
+        total_mass,
+        min_mass,
+        max_mass,
+        mean_mass: total_mass / count as f64,
+        median_mass,
+        largest_fraction: max_mass / total_mass,
+        bins,
+    }
+}
@@ -1,14 +1,65 @@
-use std::collections::HashSet;
-use std::time::Instant;
+use std::collections::{BTreeMap, HashSet, VecDeque};
+use std::ops::ControlFlow;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 
-use crate::collision::resolve_collisions;
-use crate::config::EngineConfig;
+use crate::alignment::{AlignmentTracker, AlignmentWatch};
+use crate::analysis::{
+    BinaryRecord, CollisionRateEstimate, CollisionRateQuery, detect_binaries,
+    estimate_collision_rate, sample_kepler_orbit,
+};
+use crate::binary::{self, content_checksum};
+use crate::boundary::apply_boundary;
+use crate::checkpoint::{Checkpoint, CheckpointInfo, CheckpointStore, RewindMethod};
+use crate::clock::SimClock;
+use crate::coarsening::coarse_grain;
+use crate::collision::{CollisionContact, resolve_collisions};
+use crate::config::{
+    CollisionMode, ConfigDiff, ConfigPatch, DtPolicy, EngineConfig, ForceErrorSampling,
+    IntegratorKind, ThetaTarget,
+};
+use crate::diagnostics::{
+    Diagnostics, ForceErrorStats, GroupDiagnostics, JacobiSample, MassDistribution,
+    MassHistogramOptions, add_angular_momentum, angular_momentum, compute_diagnostics,
+    group_diagnostics, jacobi_constants, mass_distribution,
+};
+use crate::epochs::{Epoch, EpochSchedule, validate_epochs};
 use crate::errors::{EngineError, Result};
-use crate::integrator::integrate_step;
+use crate::events::{
+    AggregationEvent, AlignmentEvent, BinaryEvent, BoundaryEvent, CollisionEvent, CollisionKind,
+    EpochEvent, EscapeEvent, EventLog, EventOverflow, ExcursionEvent, MergeCause, MergeRecord,
+    PushOutcome, SimulationEvent, TidalDisruptionEvent, ZoneEvent, push_bounded,
+};
+use crate::excursions::{Crossing, ExcursionSummary, ExcursionTracker, find_escapers};
+use crate::force_cache::ForceCache;
+use crate::forces::{BodyDerivatives, ForceProvider, ForceProviders, gravity_derivatives};
+use crate::grid::{CellKinematics, FieldGrid, GridSpec, density_grid, kinematics_grid};
+use crate::hooks::{StageHook, StageHooks};
+use crate::integrator::{StepExtensions, advance_spin, block_levels, effective_dt, integrate_step};
+use crate::journal::{Journal, JournalCommand, JournalRecorder};
+use crate::lagrange::{LagrangeMap, lagrange_map};
+use crate::lyapunov::{LyapunovEstimate, LyapunovQuery, phase_separation, rescale};
+use crate::maneuvers::{Maneuver, ScheduledManeuver, tick_thrust};
+use crate::math::{Transform2, Vec2};
+use crate::perf::{TickCostEstimate, TickCostModel};
+use crate::poincare::{PoincareSample, PoincareSection, PoincareTracker};
+use crate::postmortem::{HistoryFrame, InstabilityReport, StateHistory};
+use crate::query::{BodyQuery, BodyQueryResult};
+use crate::random::{PerturbSpec, Xoshiro256};
+use crate::slots::BodySlots;
+use crate::softbody::{SoftBodySpec, build_soft_body};
+use crate::solver::{
+    SolverRuntimeMode, barnes_hut_relative_errors, bodies_in_region, choose_runtime_mode,
+    sample_field,
+};
+use crate::stopping::{RunOutcome, StopCondition};
+use crate::tidal::{find_roche_crossings, fragment};
 use crate::types::{
-    Body, BodyEdit, BodyUpdate, Scenario, ScenarioMetadata, SimulationState, Snapshot, StepSummary,
-    deterministic_timestamp_iso8601,
+    Body, BodyEdit, BodyUpdate, DtSchedule, Frame, Scenario, ScenarioMetadata, SimulationState,
+    Snapshot, StepSummary, TimeMarker, TrajectorySample, deterministic_timestamp_iso8601,
 };
+use crate::units::UnitSystem;
+use crate::zones::{Region, Zone, ZoneTracker};
 
 #[derive(Clone, Debug)]
 pub struct SimulationEngine {
@@ -16,17 +67,59 @@ pub struct SimulationEngine {
     bodies: Vec<Body>,
     tick: u64,
     sim_time: f64,
+    clock: SimClock,
+    events: EventLog,
+    alignment_trackers: Vec<AlignmentTracker>,
+    binaries: Vec<BinaryRecord>,
+    excursions: ExcursionTracker,
+    /// Bodies inside a Roche limit as of the last tick, so each entry is logged once.
+    inside_roche: HashSet<String>,
+    /// Fragments not yet seen inside a Roche limit: their first entry is still part of
+    /// the disruption that made them.
+    roche_fragments: HashSet<String>,
+    dt_schedule: DtSchedule,
+    markers: Vec<TimeMarker>,
+    journal: JournalRecorder,
+    epochs: EpochSchedule,
+    /// Scenario `units` block, written back on save.
+    units: Option<UnitSystem>,
+    body_slots: BodySlots,
+    merge_history: Vec<MergeRecord>,
+    /// Baseline for `angular_momentum_guard`; cleared whenever bodies are edited.
+    angular_momentum_reference: Option<f64>,
+    /// Baseline for `energy_watchdog`; cleared alongside `angular_momentum_reference`.
+    energy_reference: Option<f64>,
+    /// Barnes-Hut ticks and their wall time since the last `theta_tuning` update.
+    theta_window: (u32, u64),
+    dt_replay: VecDeque<u8>,
+    force_cache: ForceCache,
+    zones: Vec<ZoneTracker>,
+    poincare_sections: Vec<PoincareTracker>,
+    maneuvers: Vec<ScheduledManeuver>,
+    next_maneuver_id: u64,
+    /// Last tick that delivered thrust or retired a maneuver; reversing past it would
+    /// lose the burn.
+    last_maneuver_tick: Option<u64>,
+    /// Maneuvers pruned so far, with the tick that retired them, so rewinds can bring
+    /// them back.
+    retired_maneuvers: Vec<(u64, ScheduledManeuver)>,
+    force_providers: ForceProviders,
+    stage_hooks: StageHooks,
+    tick_costs: TickCostModel,
+    checkpoints: CheckpointStore,
+    state_history: StateHistory,
+    last_instability: Option<InstabilityReport>,
+    /// First `pause_on_events` kind emitted in the current tick.
+    pending_pause: Option<&'static str>,
+    paused: bool,
+    /// Set when the log refused an event under `EventOverflow::Error`.
+    event_rejected: bool,
 }
 
 impl SimulationEngine {
     pub fn initialize(config: EngineConfig) -> Result<Self> {
         config.validate()?;
-        Ok(Self {
-            config,
-            bodies: Vec::new(),
-            tick: 0,
-            sim_time: 0.0,
-        })
+        Ok(Self::from_parts(config, Vec::new()))
     }
 
     pub fn with_bodies(config: EngineConfig, bodies: Vec<Body>) -> Result<Self> {
@@ -35,12 +128,62 @@ impl SimulationEngine {
         for body in &bodies {
             body.validate()?;
         }
-        Ok(Self {
+        Ok(Self::from_parts(config, bodies))
+    }
+
+    fn from_parts(config: EngineConfig, bodies: Vec<Body>) -> Self {
+        let body_slots = BodySlots::from_bodies(&bodies);
+        let journal = JournalRecorder {
+            config: config.clone(),
+            start: HistoryFrame {
+                tick: 0,
+                sim_time: 0.0,
+                clock: config.time_quantum.map(|_| SimClock::default()),
+                bodies: bodies.clone(),
+            },
+            markers: Vec::new(),
+            entries: Vec::new(),
+        };
+        Self {
             config,
             bodies,
             tick: 0,
             sim_time: 0.0,
-        })
+            clock: SimClock::default(),
+            events: EventLog::default(),
+            alignment_trackers: Vec::new(),
+            binaries: Vec::new(),
+            excursions: ExcursionTracker::default(),
+            inside_roche: HashSet::new(),
+            roche_fragments: HashSet::new(),
+            dt_schedule: DtSchedule::default(),
+            markers: Vec::new(),
+            journal,
+            epochs: EpochSchedule::default(),
+            units: None,
+            body_slots,
+            merge_history: Vec::new(),
+            angular_momentum_reference: None,
+            energy_reference: None,
+            theta_window: (0, 0),
+            dt_replay: VecDeque::new(),
+            force_cache: ForceCache::default(),
+            zones: Vec::new(),
+            poincare_sections: Vec::new(),
+            maneuvers: Vec::new(),
+            next_maneuver_id: 1,
+            last_maneuver_tick: None,
+            retired_maneuvers: Vec::new(),
+            force_providers: ForceProviders::default(),
+            stage_hooks: StageHooks::default(),
+            tick_costs: TickCostModel::default(),
+            checkpoints: CheckpointStore::default(),
+            state_history: StateHistory::default(),
+            last_instability: None,
+            pending_pause: None,
+            paused: false,
+            event_rejected: false,
+        }
     }
 
     pub fn config(&self) -> &EngineConfig {
@@ -51,35 +194,325 @@ impl SimulationEngine {
         &self.bodies
     }
 
+    /// Stable slot of every body by id. Slots survive merges and deletions, which
+    /// reorder `bodies()`; a freed slot is reused by the next body added.
+    pub fn body_index_map(&self) -> &BTreeMap<String, usize> {
+        self.body_slots.map()
+    }
+
+    /// Every merge since the scenario was loaded, oldest first.
+    pub fn merge_history(&self) -> &[MergeRecord] {
+        &self.merge_history
+    }
+
+    pub fn query_bodies(&self, query: &BodyQuery) -> Result<BodyQueryResult> {
+        query.run(&self.bodies)
+    }
+
+    pub fn tick(&self) -> u64 {
+        self.tick
+    }
+
+    pub fn sim_time(&self) -> f64 {
+        self.sim_time
+    }
+
+    /// Quantized form of `sim_time`, when the config sets a `time_quantum`.
+    pub fn clock(&self) -> Option<SimClock> {
+        self.config.time_quantum.map(|_| self.clock)
+    }
+
+    /// With scenario epochs this replaces the base config; the active epoch's overlay
+    /// still applies on top.
     pub fn set_config(&mut self, config: EngineConfig) -> Result<()> {
         config.validate()?;
-        self.config = config;
+        let command = JournalCommand::SetConfig {
+            config: Box::new(config.clone()),
+        };
+        let config = if self.epochs.base.is_some() {
+            validate_epochs(&self.epochs.epochs, &config)?;
+            self.epochs.base = Some(config);
+            self.epochs
+                .config_for(self.epochs.active)?
+                .expect("epoch base config is set")
+        } else {
+            config
+        };
+        self.apply_config(config);
+        self.journal.record(self.tick, command);
         Ok(())
     }
 
+    fn apply_config(&mut self, config: EngineConfig) {
+        if let Some(quantum) = config.time_quantum
+            && self.config.time_quantum != config.time_quantum
+        {
+            self.clock = SimClock::from_seconds(self.sim_time, quantum);
+        }
+        self.config = config;
+        self.angular_momentum_reference = None;
+        self.energy_reference = None;
+    }
+
+    /// The loaded scenario's `units` block, if it had one.
+    pub fn units(&self) -> Option<&UnitSystem> {
+        self.units.as_ref()
+    }
+
+    pub fn epochs(&self) -> &[Epoch] {
+        &self.epochs.epochs
+    }
+
+    pub fn active_epoch(&self) -> Option<&Epoch> {
+        self.epochs.active.map(|index| &self.epochs.epochs[index])
+    }
+
+    /// Applies a partial update on top of the current config. Past tick 0, changes that
+    /// alter the config hash come back with a warning since earlier snapshots no longer
+    /// replay to the same state.
+    pub fn update_config(&mut self, patch: &ConfigPatch) -> Result<ConfigDiff> {
+        let (config, mut diff) = self.config.apply_patch(patch)?;
+        if diff.is_empty() {
+            return Ok(diff);
+        }
+        if self.tick > 0 {
+            for change in diff.changes.iter().filter(|change| change.affects_replay) {
+                diff.warnings.push(format!(
+                    "changing {} at tick {} breaks determinism with earlier snapshots",
+                    change.field, self.tick
+                ));
+            }
+        }
+        self.set_config(config)?;
+        Ok(diff)
+    }
+
     pub fn apply_edit(&mut self, edit: BodyEdit) -> Result<()> {
-        match edit {
+        self.apply_edit_unjournaled(edit.clone())?;
+        self.journal.record(
+            self.tick,
+            JournalCommand::ApplyEdit {
+                edit: Box::new(edit),
+            },
+        );
+        Ok(())
+    }
+
+    fn apply_edit_unjournaled(&mut self, edit: BodyEdit) -> Result<()> {
+        self.angular_momentum_reference = None;
+        self.energy_reference = None;
+        let result = match edit {
             BodyEdit::Create(body) => self.create_body(body),
             BodyEdit::Update(update) => self.update_body(update),
             BodyEdit::Delete { id } => self.delete_body(&id),
+            BodyEdit::Transform { ids, transform } => self.transform_bodies(&ids, &transform),
+            BodyEdit::Perturb { ids, spec } => self.perturb_bodies(&ids, &spec),
+            BodyEdit::Recenter { frame } => self.recenter_bodies(&frame),
+        };
+        self.body_slots.sync(&self.bodies);
+        result
+    }
+
+    /// Applies `edits` in order, all or nothing: the first failing edit rolls every
+    /// earlier one back and its error is returned.
+    pub fn apply_edits(&mut self, edits: Vec<BodyEdit>) -> Result<()> {
+        let bodies = self.bodies.clone();
+        let slots = self.body_slots.clone();
+        for edit in edits.iter().cloned() {
+            if let Err(error) = self.apply_edit_unjournaled(edit) {
+                self.bodies = bodies;
+                self.body_slots = slots;
+                return Err(error);
+            }
+        }
+        self.journal
+            .record(self.tick, JournalCommand::ApplyEdits { edits });
+        Ok(())
+    }
+
+    /// Applies `spec` to the listed bodies (every body when `ids` is empty) in engine
+    /// order, so equal seeds reproduce the same kick.
+    pub fn perturb(&mut self, ids: &[String], spec: &PerturbSpec) -> Result<()> {
+        self.apply_edit(BodyEdit::Perturb {
+            ids: ids.to_vec(),
+            spec: *spec,
+        })
+    }
+
+    /// Shifts every body so `frame`'s origin sits at rest at (0, 0). Goes through
+    /// `apply_edit`, so it is journaled. A system with no mass is left as is.
+    pub fn recenter(&mut self, frame: &Frame) -> Result<()> {
+        self.apply_edit(BodyEdit::Recenter {
+            frame: frame.clone(),
+        })
+    }
+
+    fn recenter_bodies(&mut self, frame: &Frame) -> Result<()> {
+        match frame.origin(&self.bodies) {
+            Some(origin) => {
+                shift_bodies(&mut self.bodies, origin);
+                Ok(())
+            }
+            None => match frame {
+                Frame::Body(id) => Err(EngineError::BodyNotFound(id.clone())),
+                Frame::CenterOfMass => Ok(()),
+            },
         }
     }
 
+    fn perturb_bodies(&mut self, ids: &[String], spec: &PerturbSpec) -> Result<()> {
+        spec.validate()?;
+        let targets = self.resolve_edit_targets(ids)?;
+        self.angular_momentum_reference = None;
+        self.energy_reference = None;
+        let bodies = &mut self.bodies;
+        spec.apply(
+            bodies
+                .iter_mut()
+                .enumerate()
+                .filter(|(index, _)| targets.binary_search(index).is_ok())
+                .map(|(_, body)| body),
+        );
+        Ok(())
+    }
+
+    /// While paused, `step` applies no ticks and reports `stop_reason` "paused"; edits,
+    /// config changes and `step_back` still go through.
+    pub fn set_paused(&mut self, paused: bool) {
+        self.paused = paused;
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
     pub fn step(&mut self, ticks: u32) -> Result<StepSummary> {
-        let mut summary = StepSummary::default();
-        summary.max_body_count = self.bodies.len();
+        if self.paused {
+            let mut summary = self.run_ticks(0)?;
+            summary.stop_reason = Some("paused".to_string());
+            return Ok(summary);
+        }
+        self.step_unpaused(ticks)
+    }
+
+    fn step_unpaused(&mut self, ticks: u32) -> Result<StepSummary> {
+        let tick = self.tick;
+        match self.run_ticks(ticks) {
+            Ok(summary) => {
+                if summary.ticks_applied > 0 {
+                    self.journal.record(
+                        tick,
+                        JournalCommand::Step {
+                            ticks: summary.ticks_applied,
+                        },
+                    );
+                }
+                Ok(summary)
+            }
+            Err(error) => {
+                // A failed tick can leave the state half-advanced, which no replay
+                // reproduces.
+                self.restart_journal();
+                Err(error)
+            }
+        }
+    }
+
+    fn run_ticks(&mut self, ticks: u32) -> Result<StepSummary> {
+        let mut summary = StepSummary {
+            max_body_count: self.bodies.len(),
+            ..StepSummary::default()
+        };
 
         if ticks == 0 {
             summary.final_tick = self.tick;
             summary.sim_time = self.sim_time;
+            if self.config.include_diagnostics {
+                summary.diagnostics = Some(self.diagnostics());
+            }
             return Ok(summary);
         }
 
         let wall_start = Instant::now();
 
+        let (cache_hits, cache_partials) =
+            (self.force_cache.hits, self.force_cache.partial_updates);
         for _ in 0..ticks {
-            let integration_stats = integrate_step(&mut self.bodies, &self.config)?;
-            let collision_stats = resolve_collisions(&mut self.bodies, self.config.collision_mode);
+            self.pending_pause = None;
+            self.enter_epoch(&mut summary)?;
+            let error_controlled = matches!(self.config.dt_policy, DtPolicy::ErrorControlled);
+            if self.config.angular_momentum_guard.is_some()
+                && self.angular_momentum_reference.is_none()
+            {
+                self.angular_momentum_reference = Some(angular_momentum(&self.bodies));
+            }
+            if self.config.energy_watchdog.is_some() && self.energy_reference.is_none() {
+                self.energy_reference = Some(self.diagnostics().total_energy);
+            }
+            if let Some(capture) = &self.config.instability_capture {
+                let frame = HistoryFrame {
+                    tick: self.tick,
+                    sim_time: self.sim_time,
+                    clock: self.clock(),
+                    bodies: self.bodies.clone(),
+                };
+                self.state_history.record(frame, capture.history_ticks);
+            }
+            let forced_level = if error_controlled {
+                self.dt_replay.pop_front()
+            } else {
+                None
+            };
+            let thrust = tick_thrust(
+                &self.maneuvers,
+                &self.bodies,
+                self.sim_time,
+                effective_dt(&self.bodies, &self.config),
+            );
+            if !thrust.is_empty() {
+                self.last_maneuver_tick = Some(self.tick + 1);
+            }
+            let tick_start = Instant::now();
+            let integration_stats = integrate_step(
+                &mut self.bodies,
+                &self.config,
+                forced_level,
+                &mut self.force_cache,
+                StepExtensions {
+                    providers: &self.force_providers,
+                    hooks: &self.stage_hooks,
+                    thrust: &thrust,
+                },
+            )
+            .map_err(|error| self.capture_instability(error))?;
+            advance_spin(&mut self.bodies, integration_stats.dt_used);
+            if integration_stats.used_barnes_hut {
+                self.theta_window.0 += 1;
+                self.theta_window.1 += tick_start.elapsed().as_micros() as u64;
+            }
+            if error_controlled && self.config.deterministic {
+                self.dt_schedule
+                    .levels
+                    .push(integration_stats.substep_level);
+            }
+            summary.substeps += 1_u64 << integration_stats.substep_level;
+            // Merges drop dead bodies, so take absorbed ids before resolving collisions.
+            let absorbed = match &self.config.boundary {
+                Some(boundary) => apply_boundary(&mut self.bodies, boundary)
+                    .into_iter()
+                    .map(|index| (self.bodies[index].id.clone(), self.bodies[index].position))
+                    .collect(),
+                None => Vec::new(),
+            };
+            let collision_stats = resolve_collisions(&mut self.bodies, &self.config);
+            if let Some(origin) = self
+                .config
+                .recenter
+                .as_ref()
+                .and_then(|frame| frame.origin(&self.bodies))
+            {
+                shift_bodies(&mut self.bodies, origin);
+            }
 
             summary.collision_events += collision_stats.collisions;
             summary.merged_events += collision_stats.merges;
@@ -95,29 +528,903 @@ impl SimulationEngine {
             }
 
             self.tick += 1;
-            self.sim_time += integration_stats.dt_used;
+            self.advance_time(integration_stats.dt_used);
+            self.prune_maneuvers();
+            for (body_id, position) in absorbed {
+                let event = SimulationEvent::BodyAbsorbed(BoundaryEvent {
+                    tick: self.tick,
+                    sim_time: self.sim_time,
+                    body_id,
+                    position,
+                });
+                self.emit(&mut summary, event);
+            }
+            for contact in collision_stats.contacts {
+                self.record_collision(&mut summary, contact);
+            }
+            let bodies_before_coarsening = self.bodies.len();
+            if let Some(coarse) = &self.config.coarse_graining
+                && self.tick.is_multiple_of(u64::from(coarse.interval_ticks))
+            {
+                self.coarse_grain(&mut summary);
+            }
+            let escaped = self.remove_escapers(&mut summary);
+            let fragmented = self.detect_tidal_disruptions(&mut summary);
+            let bodies_merged = collision_stats.merges > 0
+                || escaped
+                || fragmented
+                || self.bodies.len() < bodies_before_coarsening;
+            if self.bodies.len() != self.body_slots.len() {
+                summary
+                    .removed_bodies
+                    .extend(self.body_slots.sync(&self.bodies));
+            }
+            self.guard_angular_momentum(&mut summary, bodies_merged);
+            self.watch_energy(&mut summary, bodies_merged)
+                .map_err(|error| self.capture_instability(error))?;
+            if let Some(sampling) = &self.config.force_error_sampling
+                && self.tick.is_multiple_of(u64::from(sampling.interval_ticks))
+            {
+                let sampling = ForceErrorSampling {
+                    seed: sampling.seed.wrapping_add(self.tick),
+                    ..sampling.clone()
+                };
+                summary.force_error = Some(self.barnes_hut_error(&sampling)?);
+            }
+            self.tune_theta(&mut summary)?;
+            self.detect_alignments(&mut summary);
+            if let Some(detection) = &self.config.binary_detection
+                && self
+                    .tick
+                    .is_multiple_of(u64::from(detection.interval_ticks))
+            {
+                self.refresh_binaries(&mut summary);
+            }
+            self.track_excursions(&mut summary);
+            self.track_zones(&mut summary);
+            for tracker in &mut self.poincare_sections {
+                tracker.update(&self.bodies, self.tick, self.sim_time);
+            }
+
+            if std::mem::take(&mut self.event_rejected) {
+                return Err(EngineError::EventQueueFull(format!(
+                    "event log holds {} events; drain it or raise its capacity",
+                    self.events.capacity()
+                )));
+            }
+            if let Some(kind) = self.pending_pause.take() {
+                summary.stop_reason = Some(kind.to_string());
+                break;
+            }
         }
 
-        summary.step_wall_time_micros = wall_start.elapsed().as_micros() as u64;
+        summary.force_cache_hits = self.force_cache.hits - cache_hits;
+        summary.force_cache_partial_updates = self.force_cache.partial_updates - cache_partials;
+        let elapsed = wall_start.elapsed();
+        summary.step_wall_time_micros = elapsed.as_micros() as u64;
+        if summary.ticks_applied > 0
+            && (summary.pairwise_ticks == 0 || summary.barnes_hut_ticks == 0)
+        {
+            let mode = if summary.barnes_hut_ticks > 0 {
+                SolverRuntimeMode::BarnesHut
+            } else {
+                SolverRuntimeMode::Pairwise
+            };
+            self.tick_costs.record(
+                mode,
+                self.alive_count(),
+                elapsed.as_secs_f64() * 1e6 / f64::from(summary.ticks_applied),
+            );
+        }
         if summary.ticks_applied > 0 {
             summary.average_tick_micros =
                 summary.step_wall_time_micros / (summary.ticks_applied as u64);
         }
 
-        for body in &self.bodies {
-            if !body.position.is_finite() || !body.velocity.is_finite() {
-                return Err(EngineError::NumericalInstability(format!(
-                    "body '{}' produced non-finite values after stepping",
-                    body.id
-                )));
-            }
+        if let Some(body) = self.bodies.iter().find(|body| !body.is_finite()) {
+            let error = EngineError::NumericalInstability(format!(
+                "body '{}' produced non-finite values after stepping",
+                body.id
+            ));
+            return Err(self.capture_instability(error));
         }
 
         summary.final_tick = self.tick;
         summary.sim_time = self.sim_time;
+        if self.config.include_diagnostics {
+            summary.diagnostics = Some(self.diagnostics());
+        }
         Ok(summary)
     }
 
+    /// Steps up to `ticks` ticks, calling `observer` after each one with the tick, the
+    /// bodies and the summary so far. Stops early when the observer breaks or a
+    /// `pause_on_events` event fires.
+    pub fn step_with_observer<F>(&mut self, ticks: u32, mut observer: F) -> Result<StepSummary>
+    where
+        F: FnMut(u64, &[Body], &StepSummary) -> ControlFlow<()>,
+    {
+        let mut summary = self.step(0)?;
+        for _ in 0..ticks {
+            let step = self.step(1)?;
+            let paused = step.stop_reason.is_some();
+            summary.absorb(step);
+            if observer(self.tick, &self.bodies, &summary).is_break() || paused {
+                break;
+            }
+        }
+        Ok(summary)
+    }
+
+    /// Steps until the next tick, at the average cost so far, would overrun `budget` of
+    /// wall time, or `max_ticks` have run. The first tick always runs, so frame loops
+    /// make progress even when one tick costs more than a frame.
+    pub fn step_budget(&mut self, budget: Duration, max_ticks: u32) -> Result<StepSummary> {
+        let start = Instant::now();
+        self.step_with_observer(max_ticks, |_, _, summary| {
+            let elapsed = start.elapsed();
+            if elapsed + elapsed / summary.ticks_applied.max(1) > budget {
+                ControlFlow::Break(())
+            } else {
+                ControlFlow::Continue(())
+            }
+        })
+    }
+
+    /// Steps one tick at a time until `condition` holds or `max_ticks` have run.
+    /// A condition that already holds returns without stepping.
+    pub fn run_until(&mut self, condition: &StopCondition, max_ticks: u32) -> Result<RunOutcome> {
+        condition.validate(&self.bodies)?;
+        let mut summary = self.step(0)?;
+        let mut stop_reason = condition
+            .check(&self.bodies, self.sim_time, 0, None)
+            .map(str::to_string);
+
+        let mut ticks_run = 0_u64;
+        while stop_reason.is_none() && ticks_run < u64::from(max_ticks) {
+            let step = self.step(1)?;
+            ticks_run += 1;
+            stop_reason = condition
+                .check(&self.bodies, self.sim_time, ticks_run, Some(&step))
+                .map(str::to_string)
+                .or_else(|| step.stop_reason.clone());
+            summary.absorb(step);
+        }
+
+        Ok(RunOutcome {
+            summary,
+            stop_reason,
+        })
+    }
+
+    /// Rewinds `ticks` ticks. Velocity Verlet with fixed dt and collisions ignored is
+    /// time-reversible, so it integrates backwards with negative dt (velocity-dependent
+    /// force providers break this symmetry). Boundaries, escaper removal, tidal
+    /// disruption, coarse graining, per-tick recentering and a correcting angular
+    /// momentum guard edit bodies after integrating, so they never reverse; neither
+    /// does a span that crosses an epoch boundary. Otherwise, or when a maneuver burned in the
+    /// rewound ticks, the nearest checkpoint at or before the target tick is restored,
+    /// maneuvers retired since are rescheduled, and the ticks are replayed forward with
+    /// the current config, re-emitting the replayed events. No events are emitted while
+    /// integrating backwards.
+    pub fn step_back(&mut self, ticks: u32) -> Result<RewindMethod> {
+        let Some(target) = self.tick.checked_sub(u64::from(ticks)) else {
+            return Err(EngineError::InvalidConfig(format!(
+                "cannot step back {ticks} ticks from tick {}",
+                self.tick
+            )));
+        };
+        if ticks == 0 {
+            return Ok(RewindMethod::Reversed);
+        }
+        // Markers past the target belong to the discarded future.
+        let markers = self
+            .markers
+            .iter()
+            .filter(|marker| marker.tick <= target)
+            .cloned()
+            .collect::<Vec<_>>();
+
+        let reversible = matches!(self.config.integrator, IntegratorKind::VelocityVerlet)
+            && matches!(self.config.dt_policy, DtPolicy::Fixed)
+            && matches!(self.config.collision_mode, CollisionMode::Ignore)
+            && self.config.boundary.is_none()
+            && self.config.remove_escapers.is_none()
+            && self.config.tidal_disruption.is_none()
+            && self.config.coarse_graining.is_none()
+            && self.config.recenter.is_none()
+            && !self
+                .config
+                .angular_momentum_guard
+                .as_ref()
+                .is_some_and(|guard| guard.correct)
+            && !self.epochs.changes_between(target, self.tick)
+            && self.last_maneuver_tick.is_none_or(|tick| tick <= target);
+        if reversible {
+            let reversed = EngineConfig {
+                dt: -self.config.dt,
+                force_caching: None,
+                ..self.config.clone()
+            };
+            let mut cache = ForceCache::default();
+            for _ in 0..ticks {
+                let stats = integrate_step(
+                    &mut self.bodies,
+                    &reversed,
+                    None,
+                    &mut cache,
+                    StepExtensions {
+                        providers: &self.force_providers,
+                        hooks: &self.stage_hooks,
+                        thrust: &[],
+                    },
+                )?;
+                advance_spin(&mut self.bodies, stats.dt_used);
+                self.tick -= 1;
+                self.advance_time(stats.dt_used);
+            }
+            self.markers = markers;
+            self.sync_epoch()?;
+            self.reset_replay_state();
+            return Ok(RewindMethod::Reversed);
+        }
+
+        let Some(checkpoint) = self.checkpoints.latest_at_or_before(target) else {
+            return Err(EngineError::UnsupportedFeature(format!(
+                "step_back needs velocity Verlet with fixed dt, collisions ignored, no body \
+                 removal or fragmentation and no maneuver in the rewound ticks, or a \
+                 checkpoint at or before tick {target}"
+            )));
+        };
+        let snapshot = checkpoint.snapshot.clone();
+        self.restore_snapshot(snapshot)?;
+        self.unretire_maneuvers();
+        // `step` may stop early on `pause_on_events`, so keep going until the target.
+        while self.tick < target {
+            self.step_unpaused(u32::try_from(target - self.tick).unwrap_or(u32::MAX))?;
+        }
+        self.markers = markers;
+        Ok(RewindMethod::Replayed)
+    }
+
+    /// Predicted wall time of one tick for the current alive body count and solver,
+    /// calibrated from earlier `step` calls in this engine.
+    pub fn estimate_tick_cost(&self) -> TickCostEstimate {
+        let alive_count = self.alive_count();
+        self.tick_costs
+            .estimate(choose_runtime_mode(alive_count, &self.config), alive_count)
+    }
+
+    fn alive_count(&self) -> usize {
+        self.bodies.iter().filter(|body| body.alive).count()
+    }
+
+    /// Conserved quantities of the alive bodies; O(n^2) because of the potential term.
+    pub fn diagnostics(&self) -> Diagnostics {
+        compute_diagnostics(&self.bodies, &self.config)
+    }
+
+    /// Jacobi constant of every other alive body in the frame co-rotating with the
+    /// `primary_id`/`secondary_id` pair.
+    pub fn jacobi_constants(
+        &self,
+        primary_id: &str,
+        secondary_id: &str,
+    ) -> Result<Vec<JacobiSample>> {
+        let find = |id: &str| {
+            self.bodies
+                .iter()
+                .position(|body| body.alive && body.id == id)
+                .ok_or_else(|| EngineError::BodyNotFound(id.to_string()))
+        };
+        let primary = find(primary_id)?;
+        let secondary = find(secondary_id)?;
+        if primary == secondary {
+            return Err(EngineError::InvalidConfig(
+                "jacobi primaries must be two different bodies".to_string(),
+            ));
+        }
+        Ok(jacobi_constants(
+            &self.bodies,
+            primary,
+            secondary,
+            self.config.gravity_constant,
+        ))
+    }
+
+    pub fn group_diagnostics(&self) -> Vec<GroupDiagnostics> {
+        group_diagnostics(&self.bodies, &self.config)
+    }
+
+    pub fn mass_distribution(&self, options: &MassHistogramOptions) -> Result<MassDistribution> {
+        if options.bins == 0 || options.bins > 4096 {
+            return Err(EngineError::InvalidConfig(
+                "mass histogram bins must be in 1..=4096".to_string(),
+            ));
+        }
+        Ok(mass_distribution(&self.bodies, options))
+    }
+
+    pub fn density_grid(&self, spec: &GridSpec) -> Result<Vec<f64>> {
+        spec.validate()?;
+        Ok(density_grid(&self.bodies, spec))
+    }
+
+    pub fn kinematics_grid(&self, spec: &GridSpec) -> Result<Vec<CellKinematics>> {
+        spec.validate()?;
+        Ok(kinematics_grid(&self.bodies, spec))
+    }
+
+    /// Field a massless probe would feel at each cell centre, from a Barnes-Hut walk
+    /// at `barnes_hut_theta` whatever the configured solver. Force providers,
+    /// interaction groups and periodic images are not included.
+    pub fn sample_field(&self, grid: GridSpec) -> Result<FieldGrid> {
+        grid.validate()?;
+        let points = (0..grid.cell_count())
+            .map(|index| grid.cell_center(index))
+            .collect::<Vec<_>>();
+        let (potential, acceleration) = sample_field(&self.bodies, &self.config, &points)
+            .into_iter()
+            .unzip();
+        Ok(FieldGrid {
+            grid,
+            potential,
+            acceleration,
+        })
+    }
+
+    /// Lagrange points of the pair and, with `grid`, its effective potential map; see
+    /// [`lagrange_map`].
+    pub fn lagrange_map(
+        &self,
+        primary_id: &str,
+        secondary_id: &str,
+        grid: Option<&GridSpec>,
+    ) -> Result<LagrangeMap> {
+        let find = |id: &str| {
+            self.bodies
+                .iter()
+                .find(|body| body.alive && body.id == id)
+                .ok_or_else(|| EngineError::BodyNotFound(id.to_string()))
+        };
+        if primary_id == secondary_id {
+            return Err(EngineError::InvalidConfig(
+                "lagrange primaries must be two different bodies".to_string(),
+            ));
+        }
+        lagrange_map(
+            find(primary_id)?,
+            find(secondary_id)?,
+            self.config.gravity_constant,
+            self.config.softening_epsilon,
+            grid,
+        )
+    }
+
+    /// Closed-form path of `body_id`'s current orbit about `primary_id`; see
+    /// [`sample_kepler_orbit`].
+    pub fn sample_orbit(
+        &self,
+        primary_id: &str,
+        body_id: &str,
+        n_points: usize,
+    ) -> Result<Vec<Vec2>> {
+        let find = |id: &str| {
+            self.bodies
+                .iter()
+                .find(|body| body.alive && body.id == id)
+                .ok_or_else(|| EngineError::BodyNotFound(id.to_string()))
+        };
+        Ok(sample_kepler_orbit(
+            find(primary_id)?,
+            find(body_id)?,
+            n_points,
+            self.config.gravity_constant,
+        ))
+    }
+
+    /// Gravitational acceleration, jerk and snap of the alive bodies in `ids` (all alive
+    /// bodies when empty), evaluated analytically from the current state.
+    pub fn body_derivatives(&self, ids: &[String]) -> Result<Vec<BodyDerivatives>> {
+        let targets = self
+            .resolve_edit_targets(ids)?
+            .into_iter()
+            .filter(|&index| self.bodies[index].alive)
+            .collect::<Vec<_>>();
+        if let Some(id) = ids
+            .iter()
+            .find(|id| !targets.iter().any(|&index| self.bodies[index].id == **id))
+        {
+            return Err(EngineError::BodyNotFound(id.clone()));
+        }
+        Ok(gravity_derivatives(&self.bodies, &self.config, &targets))
+    }
+
+    /// Timestep level each body would get on the next tick under `BlockTimesteps`, in
+    /// engine order: body `i` steps by `dt / 2^level`. Dead and fixed bodies report 0.
+    pub fn timestep_levels(&self) -> Vec<u8> {
+        block_levels(&self.bodies, &self.config)
+    }
+
+    /// Binary catalog as of the last detection pass (see `EngineConfig::binary_detection`).
+    pub fn binaries(&self) -> &[BinaryRecord] {
+        &self.binaries
+    }
+
+    /// Expected merge rate between two populations right now, to compare against the
+    /// `merged_events` actually reported by `step`.
+    pub fn collision_rate(&self, query: &CollisionRateQuery) -> Result<CollisionRateEstimate> {
+        estimate_collision_rate(&self.bodies, self.config.gravity_constant, query)
+    }
+
+    /// Labels the current tick; snapshots and checkpoints carry the markers up to
+    /// their tick, so restoring one rewinds the timeline with the state.
+    pub fn add_marker(&mut self, name: impl Into<String>) -> Result<&TimeMarker> {
+        let name = name.into();
+        if name.trim().is_empty() {
+            return Err(EngineError::InvalidConfig(
+                "marker name must not be empty".to_string(),
+            ));
+        }
+        self.markers.push(TimeMarker {
+            name,
+            tick: self.tick,
+            sim_time: self.sim_time,
+        });
+        Ok(self.markers.last().expect("marker was just pushed"))
+    }
+
+    /// Markers in the order they were added.
+    pub fn markers(&self) -> &[TimeMarker] {
+        &self.markers
+    }
+
+    pub fn markers_between(
+        &self,
+        from_tick: u64,
+        to_tick: u64,
+    ) -> impl Iterator<Item = &TimeMarker> {
+        self.markers
+            .iter()
+            .filter(move |marker| (from_tick..=to_tick).contains(&marker.tick))
+    }
+
+    pub fn remove_markers(&mut self, name: &str) -> usize {
+        let before = self.markers.len();
+        self.markers.retain(|marker| marker.name != name);
+        before - self.markers.len()
+    }
+
+    /// Substep levels recorded while stepping with error-controlled dt in deterministic mode.
+    pub fn dt_schedule(&self) -> &DtSchedule {
+        &self.dt_schedule
+    }
+
+    /// Queues recorded substep levels so the next ticks reproduce them exactly
+    /// instead of re-running the error estimate.
+    pub fn replay_dt_schedule(&mut self, schedule: DtSchedule) -> Result<()> {
+        if schedule.start_tick != self.tick {
+            return Err(EngineError::InvalidConfig(format!(
+                "dt schedule starts at tick {} but the engine is at tick {}",
+                schedule.start_tick, self.tick
+            )));
+        }
+        if schedule.levels.iter().any(|&level| level > 16) {
+            return Err(EngineError::InvalidConfig(
+                "dt schedule levels must be <= 16".to_string(),
+            ));
+        }
+        self.dt_replay = schedule.levels.into();
+        Ok(())
+    }
+
+    pub fn excursion_summary(&self) -> ExcursionSummary {
+        self.excursions.summary()
+    }
+
+    pub fn reset_excursions(&mut self) {
+        self.excursions.clear();
+    }
+
+    pub fn collision_events(&self) -> impl Iterator<Item = &CollisionEvent> {
+        self.events.iter().filter_map(|event| match event {
+            SimulationEvent::Collision(collision) => Some(collision),
+            _ => None,
+        })
+    }
+
+    pub fn events(&self) -> impl Iterator<Item = &SimulationEvent> {
+        self.events.iter()
+    }
+
+    pub fn drain_events(&mut self) -> Vec<SimulationEvent> {
+        self.events.drain()
+    }
+
+    pub fn set_event_log_capacity(&mut self, capacity: usize) {
+        self.events.set_capacity(capacity);
+    }
+
+    pub fn set_event_overflow(&mut self, overflow: EventOverflow) {
+        self.events.set_overflow(overflow);
+    }
+
+    pub fn watch_alignment(&mut self, watch: AlignmentWatch) -> Result<()> {
+        watch.validate()?;
+        // Only alignments that start after registration are reported.
+        let aligned = watch
+            .measure(&self.bodies)
+            .is_some_and(|(error, _)| error <= watch.tolerance);
+        self.alignment_trackers
+            .push(AlignmentTracker { watch, aligned });
+        Ok(())
+    }
+
+    /// Providers run after the built-in forces, in registration order.
+    pub fn add_force_provider(
+        &mut self,
+        name: impl Into<String>,
+        provider: Arc<dyn ForceProvider>,
+    ) -> Result<()> {
+        let name = name.into();
+        if !self.force_providers.insert(name.clone(), provider) {
+            return Err(EngineError::InvalidConfig(format!(
+                "force provider '{name}' is already registered"
+            )));
+        }
+        Ok(())
+    }
+
+    /// Adds the particles of a soft body and registers its springs as force
+    /// provider `name`; removing that provider releases the springs.
+    pub fn add_soft_body(&mut self, name: impl Into<String>, spec: &SoftBodySpec) -> Result<()> {
+        let name = name.into();
+        if self
+            .force_providers
+            .names()
+            .any(|existing| existing == name)
+        {
+            return Err(EngineError::InvalidConfig(format!(
+                "force provider '{name}' is already registered"
+            )));
+        }
+        let soft_body = build_soft_body(spec)?;
+        if let Some(body) = soft_body
+            .bodies
+            .iter()
+            .find(|body| self.bodies.iter().any(|existing| existing.id == body.id))
+        {
+            return Err(EngineError::DuplicateBodyId(body.id.clone()));
+        }
+        for body in soft_body.bodies {
+            self.apply_edit(BodyEdit::Create(body))?;
+        }
+        self.add_force_provider(name, Arc::new(soft_body.springs))
+    }
+
+    pub fn remove_force_provider(&mut self, name: &str) -> bool {
+        self.force_providers.remove(name)
+    }
+
+    pub fn force_provider_names(&self) -> impl Iterator<Item = &str> {
+        self.force_providers.names()
+    }
+
+    /// Hooks run around every integrator stage, in registration order; see `StageHook`.
+    pub fn add_stage_hook(
+        &mut self,
+        name: impl Into<String>,
+        hook: Arc<dyn StageHook>,
+    ) -> Result<()> {
+        let name = name.into();
+        if !self.stage_hooks.insert(name.clone(), hook) {
+            return Err(EngineError::InvalidConfig(format!(
+                "stage hook '{name}' is already registered"
+            )));
+        }
+        Ok(())
+    }
+
+    pub fn remove_stage_hook(&mut self, name: &str) -> bool {
+        self.stage_hooks.remove(name)
+    }
+
+    pub fn stage_hook_names(&self) -> impl Iterator<Item = &str> {
+        self.stage_hooks.names()
+    }
+
+    /// Bodies already inside the zone when it is added do not produce an enter event.
+    pub fn add_zone(&mut self, zone: Zone) -> Result<()> {
+        zone.validate()?;
+        if self
+            .zones
+            .iter()
+            .any(|tracker| tracker.zone.name == zone.name)
+        {
+            return Err(EngineError::InvalidConfig(format!(
+                "zone '{}' already exists",
+                zone.name
+            )));
+        }
+        self.zones.push(ZoneTracker::new(zone, &self.bodies));
+        Ok(())
+    }
+
+    pub fn remove_zone(&mut self, name: &str) -> bool {
+        let before = self.zones.len();
+        self.zones.retain(|tracker| tracker.zone.name != name);
+        self.zones.len() != before
+    }
+
+    pub fn zones(&self) -> impl Iterator<Item = &Zone> {
+        self.zones.iter().map(|tracker| &tracker.zone)
+    }
+
+    /// Starts recording crossings of `section`. Bodies already on the far side of the
+    /// line when it is added only count once they cross it again.
+    pub fn add_poincare_section(&mut self, section: PoincareSection) -> Result<()> {
+        section.validate()?;
+        if self
+            .poincare_sections
+            .iter()
+            .any(|tracker| tracker.section.name == section.name)
+        {
+            return Err(EngineError::InvalidConfig(format!(
+                "section '{}' already exists",
+                section.name
+            )));
+        }
+        self.poincare_sections
+            .push(PoincareTracker::new(section, &self.bodies, self.sim_time));
+        Ok(())
+    }
+
+    pub fn remove_poincare_section(&mut self, name: &str) -> bool {
+        let before = self.poincare_sections.len();
+        self.poincare_sections
+            .retain(|tracker| tracker.section.name != name);
+        self.poincare_sections.len() != before
+    }
+
+    pub fn poincare_sections(&self) -> impl Iterator<Item = &PoincareSection> {
+        self.poincare_sections
+            .iter()
+            .map(|tracker| &tracker.section)
+    }
+
+    /// Crossings recorded by section `name`, oldest first.
+    pub fn poincare_samples(&self, name: &str) -> Option<impl Iterator<Item = &PoincareSample>> {
+        self.poincare_sections
+            .iter()
+            .find(|tracker| tracker.section.name == name)
+            .map(|tracker| tracker.samples.iter())
+    }
+
+    pub fn clear_poincare_samples(&mut self, name: &str) -> bool {
+        let tracker = self
+            .poincare_sections
+            .iter_mut()
+            .find(|tracker| tracker.section.name == name);
+        tracker.map(|tracker| tracker.samples.clear()).is_some()
+    }
+
+    /// Queues a burn on `body_id` and returns its id. The burn is dropped once it
+    /// completes or the body leaves the simulation.
+    pub fn schedule_maneuver(&mut self, body_id: &str, maneuver: Maneuver) -> Result<u64> {
+        maneuver.validate()?;
+        let body = self
+            .bodies
+            .iter()
+            .find(|body| body.alive && body.id == body_id)
+            .ok_or_else(|| EngineError::BodyNotFound(body_id.to_string()))?;
+        if body.fixed {
+            return Err(EngineError::InvalidBody(format!(
+                "body '{body_id}' is fixed and cannot maneuver"
+            )));
+        }
+        if maneuver.finished(self.sim_time) {
+            return Err(EngineError::InvalidConfig(format!(
+                "maneuver ends before the current time {}",
+                self.sim_time
+            )));
+        }
+        let id = self.next_maneuver_id;
+        self.next_maneuver_id += 1;
+        self.maneuvers.push(ScheduledManeuver {
+            id,
+            body_id: body_id.to_string(),
+            maneuver,
+        });
+        Ok(id)
+    }
+
+    /// Pending and in-progress maneuvers, in scheduling order.
+    pub fn maneuvers(&self) -> &[ScheduledManeuver] {
+        &self.maneuvers
+    }
+
+    pub fn cancel_maneuver(&mut self, id: u64) -> bool {
+        let before = self.maneuvers.len();
+        self.maneuvers.retain(|entry| entry.id != id);
+        self.maneuvers.len() != before
+    }
+
+    fn prune_maneuvers(&mut self) {
+        let (sim_time, tick) = (self.sim_time, self.tick);
+        let bodies = &self.bodies;
+        let retired = &mut self.retired_maneuvers;
+        self.maneuvers.retain(|entry| {
+            let active = !entry.maneuver.finished(sim_time)
+                && bodies
+                    .iter()
+                    .any(|body| body.alive && body.id == entry.body_id);
+            if !active {
+                retired.push((tick, entry.clone()));
+            }
+            active
+        });
+        if retired.last().is_some_and(|(at, _)| *at == tick) {
+            self.last_maneuver_tick = Some(tick);
+        }
+    }
+
+    /// Reschedules the maneuvers retired after the current tick, which a rewind has
+    /// put back in the future.
+    fn unretire_maneuvers(&mut self) {
+        let tick = self.tick;
+        let (revived, kept): (Vec<_>, Vec<_>) = self
+            .retired_maneuvers
+            .drain(..)
+            .partition(|(at, _)| *at > tick);
+        self.retired_maneuvers = kept;
+        self.maneuvers
+            .extend(revived.into_iter().map(|(_, entry)| entry));
+        self.maneuvers.sort_by_key(|entry| entry.id);
+    }
+
+    pub fn clear_alignment_watches(&mut self) {
+        self.alignment_trackers.clear();
+    }
+
+    /// Applies `edits` to a copy of the engine and steps it `ticks` ticks, sampling every
+    /// alive body at the start, every `sample_every` ticks and at the end. Samples are
+    /// ordered by tick, then engine order. `pause_on_events` and `set_paused` are ignored.
+    /// Unpaused copy of everything a step reads, for look-ahead queries. The journal,
+    /// checkpoints, event log and trackers start empty rather than being cloned.
+    fn preview_copy(&self) -> Self {
+        let mut config = self.config.clone();
+        config.pause_on_events.clear();
+        Self {
+            bodies: self.bodies.clone(),
+            tick: self.tick,
+            sim_time: self.sim_time,
+            clock: self.clock,
+            inside_roche: self.inside_roche.clone(),
+            roche_fragments: self.roche_fragments.clone(),
+            epochs: self.epochs.clone(),
+            body_slots: self.body_slots.clone(),
+            angular_momentum_reference: self.angular_momentum_reference,
+            energy_reference: self.energy_reference,
+            theta_window: self.theta_window,
+            dt_replay: self.dt_replay.clone(),
+            force_cache: self.force_cache.clone(),
+            maneuvers: self.maneuvers.clone(),
+            next_maneuver_id: self.next_maneuver_id,
+            force_providers: self.force_providers.clone(),
+            stage_hooks: self.stage_hooks.clone(),
+            ..Self::from_parts(config, Vec::new())
+        }
+    }
+
+    pub fn predict(
+        &self,
+        edits: Vec<BodyEdit>,
+        ticks: u32,
+        sample_every: u32,
+    ) -> Result<Vec<TrajectorySample>> {
+        if sample_every == 0 {
+            return Err(EngineError::InvalidConfig(
+                "sample_every must be at least 1".to_string(),
+            ));
+        }
+        let mut preview = self.preview_copy();
+        preview.apply_edits(edits)?;
+
+        let mut samples = Vec::new();
+        let mut record = |engine: &Self| {
+            samples.extend(engine.bodies.iter().filter(|body| body.alive).map(|body| {
+                TrajectorySample {
+                    tick: engine.tick,
+                    sim_time: engine.sim_time,
+                    body_id: body.id.clone(),
+                    position: body.position,
+                    velocity: body.velocity,
+                }
+            }));
+        };
+        record(&preview);
+        let mut remaining = ticks;
+        while remaining > 0 {
+            let chunk = remaining.min(sample_every);
+            preview.step(chunk)?;
+            remaining -= chunk;
+            record(&preview);
+        }
+        Ok(samples)
+    }
+
+    /// Steps two copies of the engine, one with `body_id` nudged, and measures how fast
+    /// they separate. The engine itself is left untouched; `pause_on_events` and
+    /// `set_paused` are ignored.
+    pub fn lyapunov_exponent(&self, query: &LyapunovQuery) -> Result<LyapunovEstimate> {
+        query.validate()?;
+        let mut reference = self.preview_copy();
+        let mut shadow = reference.clone();
+        let body = shadow
+            .bodies
+            .iter_mut()
+            .find(|body| body.alive && body.id == query.body_id)
+            .ok_or_else(|| EngineError::BodyNotFound(query.body_id.clone()))?;
+        body.position.x += query.perturbation;
+
+        let mut estimate = LyapunovEstimate {
+            exponent: 0.0,
+            elapsed_time: 0.0,
+            renormalizations: 0,
+            history: Vec::new(),
+            complete: true,
+        };
+        let mut log_growth = 0.0;
+        let mut remaining = query.ticks;
+        while remaining > 0 {
+            let chunk = remaining.min(query.renormalize_every);
+            reference.step(chunk)?;
+            shadow.step(chunk)?;
+            remaining -= chunk;
+            let separation = phase_separation(&reference.bodies, &shadow.bodies);
+            let Some(separation) = separation.filter(|separation| *separation > 0.0) else {
+                estimate.complete = false;
+                break;
+            };
+            log_growth += (separation / query.perturbation).ln();
+            rescale(
+                &reference.bodies,
+                &mut shadow.bodies,
+                query.perturbation / separation,
+            );
+            estimate.elapsed_time = reference.sim_time - self.sim_time;
+            estimate.renormalizations += 1;
+            if estimate.elapsed_time > 0.0 {
+                estimate.exponent = log_growth / estimate.elapsed_time;
+            }
+            estimate.history.push(estimate.exponent);
+        }
+        Ok(estimate)
+    }
+
+    /// Steps a copy of the engine until `watch` next aligns, up to `max_ticks`.
+    pub fn predict_alignment(
+        &self,
+        watch: AlignmentWatch,
+        max_ticks: u32,
+    ) -> Result<Option<AlignmentEvent>> {
+        let mut preview = self.clone();
+        preview.alignment_trackers.clear();
+        preview.watch_alignment(watch)?;
+
+        for _ in 0..max_ticks {
+            let summary = preview.step(1)?;
+            let found = summary
+                .events
+                .into_iter()
+                .find(|event| matches!(event, SimulationEvent::Alignment(_)));
+            if let Some(SimulationEvent::Alignment(alignment)) = found {
+                return Ok(Some(alignment));
+            }
+        }
+        Ok(None)
+    }
+
     pub fn get_state(&self) -> SimulationState {
         SimulationState {
             tick: self.tick,
@@ -127,6 +1434,21 @@ impl SimulationEngine {
         }
     }
 
+    /// `get_state` restricted to the alive bodies inside `region`, for viewports that
+    /// show a small part of a large system.
+    pub fn state_in_region(&self, region: &Region) -> Result<SimulationState> {
+        region.validate()?;
+        Ok(SimulationState {
+            tick: self.tick,
+            sim_time: self.sim_time,
+            config: self.config.clone(),
+            bodies: bodies_in_region(&self.bodies, region)
+                .into_iter()
+                .map(|index| self.bodies[index].clone())
+                .collect(),
+        })
+    }
+
     pub fn load_scenario(&mut self, scenario: Scenario) -> Result<()> {
         if !scenario.schema_version.starts_with('1') {
             return Err(EngineError::SchemaValidationFailed(
@@ -134,21 +1456,42 @@ impl SimulationEngine {
             ));
         }
 
+        scenario.verify_checksum()?;
         scenario.engine_config.validate()?;
         validate_unique_body_ids(&scenario.bodies)?;
         for body in &scenario.bodies {
             body.validate()?;
         }
+        let epochs = EpochSchedule::new(scenario.epochs, &scenario.engine_config)?;
+        if let Some(units) = &scenario.units {
+            units.validate()?;
+        }
 
         self.config = scenario.engine_config;
+        self.epochs = epochs;
+        self.units = scenario.units;
         self.bodies = scenario.bodies;
+        self.body_slots = BodySlots::from_bodies(&self.bodies);
         self.tick = 0;
         self.sim_time = 0.0;
+        self.clock = SimClock::default();
+        self.excursions.clear();
+        self.inside_roche.clear();
+        self.roche_fragments.clear();
+        self.markers.clear();
+        self.maneuvers.clear();
+        self.last_maneuver_tick = None;
+        self.retired_maneuvers.clear();
+        for tracker in &mut self.poincare_sections {
+            tracker.reseed(&self.bodies, self.sim_time);
+        }
+        self.merge_history.clear();
+        self.reset_replay_state();
         Ok(())
     }
 
     pub fn save_scenario(&self) -> Scenario {
-        Scenario {
+        let mut scenario = Scenario {
             schema_version: "1.0".to_string(),
             metadata: ScenarioMetadata {
                 name: "Untitled".to_string(),
@@ -157,20 +1500,136 @@ impl SimulationEngine {
                 created_at: deterministic_timestamp_iso8601(),
                 tags: Vec::new(),
             },
-            engine_config: self.config.clone(),
+            engine_config: self.epochs.base.as_ref().unwrap_or(&self.config).clone(),
             bodies: self.bodies.clone(),
-        }
+            epochs: self.epochs.epochs.clone(),
+            units: self.units.clone(),
+            checksum: None,
+        };
+        scenario.checksum = Some(scenario.compute_checksum());
+        scenario
+    }
+
+    pub fn save_scenario_binary(&self) -> Vec<u8> {
+        binary::encode_scenario(&self.save_scenario())
+    }
+
+    pub fn load_scenario_binary(&mut self, bytes: &[u8]) -> Result<()> {
+        self.load_scenario(binary::decode_scenario(bytes)?)
+    }
+
+    /// FNV-1a over the tick, sim time, every body (alive or not) and the config's
+    /// `stable_hash`. Equal hashes mean bit-identical simulations, so lockstep peers
+    /// can compare them each tick.
+    pub fn state_hash(&self) -> String {
+        content_checksum(&(
+            self.tick,
+            self.sim_time,
+            &self.bodies,
+            self.config.stable_hash(),
+        ))
     }
 
     pub fn snapshot(&self) -> Snapshot {
-        Snapshot {
-            schema_version: "1.0".to_string(),
-            created_at: deterministic_timestamp_iso8601(),
+        self.snapshot_of(HistoryFrame {
             tick: self.tick,
             sim_time: self.sim_time,
-            config_hash: self.config.stable_hash(),
+            clock: self.clock(),
             bodies: self.bodies.clone(),
+        })
+    }
+
+    fn snapshot_of(&self, frame: HistoryFrame) -> Snapshot {
+        build_snapshot(&self.config, &self.markers, frame)
+    }
+
+    /// Every `step`, `apply_edit(s)`, `perturb` and `set_config` call since the engine
+    /// was created or its state last replaced.
+    pub fn export_journal(&self) -> Journal {
+        Journal {
+            config: self.journal.config.clone(),
+            start: build_snapshot(
+                &self.journal.config,
+                &self.journal.markers,
+                self.journal.start.clone(),
+            ),
+            entries: self.journal.entries.clone(),
+        }
+    }
+
+    /// Rebuilds the engine a journal was exported from by restoring its start and
+    /// re-issuing every command.
+    pub fn replay_journal(journal: &Journal) -> Result<SimulationEngine> {
+        let mut engine = Self::initialize(journal.config.clone())?;
+        engine.restore_snapshot(journal.start.clone())?;
+        for entry in &journal.entries {
+            if entry.tick != engine.tick {
+                return Err(EngineError::InvalidConfig(format!(
+                    "journal entry for tick {} reached at tick {}",
+                    entry.tick, engine.tick
+                )));
+            }
+            match &entry.command {
+                JournalCommand::Step { ticks } => {
+                    engine.step(*ticks)?;
+                }
+                JournalCommand::ApplyEdit { edit } => engine.apply_edit(edit.as_ref().clone())?,
+                JournalCommand::ApplyEdits { edits } => engine.apply_edits(edits.clone())?,
+                JournalCommand::SetConfig { config } => {
+                    engine.set_config(config.as_ref().clone())?
+                }
+            }
         }
+        Ok(engine)
+    }
+
+    fn restart_journal(&mut self) {
+        self.journal = JournalRecorder {
+            config: self.config.clone(),
+            start: HistoryFrame {
+                tick: self.tick,
+                sim_time: self.sim_time,
+                clock: self.clock(),
+                bodies: self.bodies.clone(),
+            },
+            markers: self.markers.clone(),
+            entries: Vec::new(),
+        };
+    }
+
+    /// Post-mortem of the last `NumericalInstability` raised while
+    /// `instability_capture` was on.
+    pub fn last_instability(&self) -> Option<&InstabilityReport> {
+        self.last_instability.as_ref()
+    }
+
+    fn capture_instability(&mut self, error: EngineError) -> EngineError {
+        let EngineError::NumericalInstability(message) = &error else {
+            return error;
+        };
+        let Some(frame) = self
+            .state_history
+            .latest()
+            .filter(|_| self.config.instability_capture.is_some())
+        else {
+            return error;
+        };
+        let body_id = self
+            .bodies
+            .iter()
+            .find(|body| !body.is_finite())
+            .map(|body| body.id.clone());
+        let report = InstabilityReport {
+            message: message.clone(),
+            history: body_id
+                .as_deref()
+                .map(|id| self.state_history.body_history(id))
+                .unwrap_or_default(),
+            body_id,
+            last_good: self.snapshot_of(frame.clone()),
+        };
+        self.last_instability = Some(report);
+        error
     }
 
     pub fn restore_snapshot(&mut self, snapshot: Snapshot) -> Result<()> {
@@ -179,6 +1638,7 @@ impl SimulationEngine {
                 "only snapshot schema v1.x is supported".to_string(),
             ));
         }
+        snapshot.verify_checksum()?;
 
         validate_unique_body_ids(&snapshot.bodies)?;
         for body in &snapshot.bodies {
@@ -187,10 +1647,587 @@ impl SimulationEngine {
 
         self.tick = snapshot.tick;
         self.sim_time = snapshot.sim_time;
+        self.sync_epoch()?;
+        if let Some(quantum) = self.config.time_quantum {
+            self.clock = snapshot
+                .clock
+                .unwrap_or_else(|| SimClock::from_seconds(snapshot.sim_time, quantum));
+            self.sim_time = self.clock.seconds(quantum);
+        }
         self.bodies = snapshot.bodies;
+        self.body_slots.sync(&self.bodies);
+        self.markers = snapshot.markers;
+        self.merge_history
+            .retain(|record| record.tick <= snapshot.tick);
+        self.reset_replay_state();
+        Ok(())
+    }
+
+    pub fn snapshot_binary(&self) -> Vec<u8> {
+        binary::encode_snapshot(&self.snapshot())
+    }
+
+    pub fn restore_snapshot_binary(&mut self, bytes: &[u8]) -> Result<()> {
+        self.restore_snapshot(binary::decode_snapshot(bytes)?)
+    }
+
+    fn advance_time(&mut self, dt: f64) {
+        match self.config.time_quantum {
+            Some(quantum) => {
+                self.clock.advance(dt, quantum);
+                self.sim_time = self.clock.seconds(quantum);
+            }
+            None => self.sim_time += dt,
+        }
+    }
+
+    /// Saves the current state and config under `name`, replacing any checkpoint of
+    /// the same name.
+    pub fn create_checkpoint(&mut self, name: &str) -> Result<()> {
+        if name.trim().is_empty() {
+            return Err(EngineError::InvalidConfig(
+                "checkpoint name must not be empty".to_string(),
+            ));
+        }
+        self.checkpoints.insert(Checkpoint {
+            name: name.to_string(),
+            config: self.config.clone(),
+            snapshot: self.snapshot(),
+        });
+        Ok(())
+    }
+
+    pub fn list_checkpoints(&self) -> Vec<CheckpointInfo> {
+        self.checkpoints.list()
+    }
+
+    /// Rewinds to the named checkpoint, including the config it was taken with. The
+    /// checkpoint stays available for later restores.
+    pub fn restore_checkpoint(&mut self, name: &str) -> Result<()> {
+        let checkpoint = self
+            .checkpoints
+            .get(name)
+            .ok_or_else(|| EngineError::CheckpointNotFound(name.to_string()))?;
+        let (config, snapshot) = (checkpoint.config.clone(), checkpoint.snapshot.clone());
+        let previous = std::mem::replace(&mut self.config, config);
+        if let Err(error) = self.restore_snapshot(snapshot) {
+            self.config = previous;
+            return Err(error);
+        }
+        self.unretire_maneuvers();
+        Ok(())
+    }
+
+    pub fn delete_checkpoint(&mut self, name: &str) -> bool {
+        self.checkpoints.remove(name)
+    }
+
+    /// Oldest checkpoints are evicted once more than `capacity` (at least 1) exist.
+    pub fn set_checkpoint_capacity(&mut self, capacity: usize) {
+        self.checkpoints.set_capacity(capacity);
+    }
+
+    /// State restored from outside must not inherit the cached forces or the dt
+    /// schedule of the timeline it replaced.
+    fn reset_replay_state(&mut self) {
+        self.force_cache = ForceCache::default();
+        self.angular_momentum_reference = None;
+        self.energy_reference = None;
+        self.state_history.clear();
+        self.dt_schedule = DtSchedule {
+            start_tick: self.tick,
+            levels: Vec::new(),
+        };
+        self.dt_replay.clear();
+        self.restart_journal();
+    }
+
+    /// Switches to the epoch covering the current tick, emitting `EpochChanged`.
+    fn enter_epoch(&mut self, summary: &mut StepSummary) -> Result<()> {
+        let target = self.epochs.epoch_at(self.tick);
+        if target == self.epochs.active {
+            return Ok(());
+        }
+        let Some(config) = self.epochs.config_for(target)? else {
+            return Ok(());
+        };
+        let from = self.epochs.name(self.epochs.active);
+        self.epochs.active = target;
+        self.apply_config(config);
+        let event = SimulationEvent::EpochChanged(EpochEvent {
+            tick: self.tick,
+            sim_time: self.sim_time,
+            from,
+            to: self.epochs.name(target),
+        });
+        self.emit(summary, event);
+        Ok(())
+    }
+
+    /// Puts the epoch for the current tick in effect without an event, after a jump in
+    /// time.
+    fn sync_epoch(&mut self) -> Result<()> {
+        let active = self.epochs.epoch_at(self.tick);
+        if let Some(config) = self.epochs.config_for(active)? {
+            self.epochs.active = active;
+            self.apply_config(config);
+        }
+        Ok(())
+    }
+
+    fn emit(&mut self, summary: &mut StepSummary, event: SimulationEvent) {
+        let kind = event.kind();
+        if self.pending_pause.is_none() && self.config.pause_on_events.iter().any(|k| k == kind) {
+            self.pending_pause = Some(kind);
+        }
+        match self.events.push(event.clone()) {
+            PushOutcome::Queued => {}
+            PushOutcome::DroppedOldest => summary.events_dropped += 1,
+            PushOutcome::Coalesced => summary.events_coalesced += 1,
+            PushOutcome::Rejected => {
+                summary.events_dropped += 1;
+                self.event_rejected = true;
+            }
+        }
+        push_bounded(
+            &mut summary.events,
+            self.events.capacity(),
+            self.events.overflow(),
+            event,
+        );
+    }
+
+    /// Logs an event raised by a driver around the engine, such as a playlist.
+    pub(crate) fn record_event(&mut self, summary: &mut StepSummary, event: SimulationEvent) {
+        self.emit(summary, event);
+    }
+
+    fn detect_alignments(&mut self, summary: &mut StepSummary) {
+        let mut emitted = Vec::new();
+        for tracker in &mut self.alignment_trackers {
+            let Some((error, angle)) = tracker.watch.measure(&self.bodies) else {
+                tracker.aligned = false;
+                continue;
+            };
+            let aligned = error <= tracker.watch.tolerance;
+            if aligned && !tracker.aligned {
+                emitted.push(SimulationEvent::Alignment(AlignmentEvent {
+                    tick: self.tick,
+                    sim_time: self.sim_time,
+                    body_ids: tracker.watch.body_ids.to_vec(),
+                    alignment_error: error,
+                    separation_angle: angle,
+                }));
+            }
+            tracker.aligned = aligned;
+        }
+        for event in emitted {
+            self.emit(summary, event);
+        }
+    }
+
+    fn refresh_binaries(&mut self, summary: &mut StepSummary) {
+        let mut detected = detect_binaries(&self.bodies, &self.config);
+        let mut emitted = Vec::new();
+
+        for binary in &mut detected {
+            let existing = self.binaries.iter().find(|known| {
+                known.primary_id == binary.primary_id && known.secondary_id == binary.secondary_id
+            });
+            match existing {
+                Some(known) => binary.formed_tick = known.formed_tick,
+                None => {
+                    binary.formed_tick = self.tick;
+                    emitted.push(SimulationEvent::BinaryFormed(self.binary_event(binary)));
+                }
+            }
+        }
+        for known in &self.binaries {
+            let survives = detected.iter().any(|binary| {
+                binary.primary_id == known.primary_id && binary.secondary_id == known.secondary_id
+            });
+            if !survives {
+                emitted.push(SimulationEvent::BinaryDisrupted(self.binary_event(known)));
+            }
+        }
+
+        self.binaries = detected;
+        for event in emitted {
+            self.emit(summary, event);
+        }
+    }
+
+    /// Returns whether any body escaped.
+    fn remove_escapers(&mut self, summary: &mut StepSummary) -> bool {
+        let Some(policy) = &self.config.remove_escapers else {
+            return false;
+        };
+        let escapers = find_escapers(&self.bodies, policy, self.config.gravity_constant);
+        for escaper in &escapers {
+            let body = &mut self.bodies[escaper.index];
+            body.alive = false;
+            let event = SimulationEvent::BodyEscaped(EscapeEvent {
+                tick: self.tick,
+                sim_time: self.sim_time,
+                body_id: body.id.clone(),
+                distance: escaper.distance,
+                speed: escaper.speed,
+            });
+            self.emit(summary, event);
+        }
+        !escapers.is_empty()
+    }
+
+    /// Returns whether any body was broken into fragments.
+    fn detect_tidal_disruptions(&mut self, summary: &mut StepSummary) -> bool {
+        let Some(policy) = &self.config.tidal_disruption else {
+            self.inside_roche.clear();
+            self.roche_fragments.clear();
+            return false;
+        };
+        let mut taken = HashSet::new();
+        if policy.fragments >= 2 {
+            taken.extend(self.bodies.iter().map(|body| body.id.clone()));
+        }
+        let mut inside = HashSet::new();
+        let mut replacements = BTreeMap::new();
+        let mut emitted = Vec::new();
+        for crossing in find_roche_crossings(&self.bodies, policy) {
+            let body = &self.bodies[crossing.index];
+            inside.insert(body.id.clone());
+            if self.inside_roche.contains(&body.id) || self.roche_fragments.remove(&body.id) {
+                continue;
+            }
+            let fragments = if policy.fragments >= 2 {
+                fragment(
+                    body,
+                    &self.bodies[crossing.primary],
+                    policy.fragments,
+                    &mut taken,
+                )
+            } else {
+                Vec::new()
+            };
+            self.roche_fragments
+                .extend(fragments.iter().map(|piece| piece.id.clone()));
+            emitted.push(SimulationEvent::TidalDisruption(TidalDisruptionEvent {
+                tick: self.tick,
+                sim_time: self.sim_time,
+                body_id: body.id.clone(),
+                primary_id: self.bodies[crossing.primary].id.clone(),
+                distance: crossing.distance,
+                roche_limit: crossing.roche_limit,
+                fragment_ids: fragments.iter().map(|piece| piece.id.clone()).collect(),
+            }));
+            if !fragments.is_empty() {
+                replacements.insert(crossing.index, fragments);
+            }
+        }
+        self.inside_roche = inside;
+
+        let fragmented = !replacements.is_empty();
+        if fragmented {
+            self.bodies = std::mem::take(&mut self.bodies)
+                .into_iter()
+                .enumerate()
+                .flat_map(|(index, body)| replacements.remove(&index).unwrap_or(vec![body]))
+                .collect();
+        }
+        if !self.roche_fragments.is_empty() {
+            let present = self
+                .bodies
+                .iter()
+                .filter(|body| body.alive)
+                .map(|body| body.id.as_str())
+                .collect::<HashSet<_>>();
+            self.roche_fragments
+                .retain(|id| present.contains(id.as_str()));
+        }
+        for event in emitted {
+            self.emit(summary, event);
+        }
+        fragmented
+    }
+
+    fn track_excursions(&mut self, summary: &mut StepSummary) {
+        let Some(tracking) = &self.config.excursion_tracking else {
+            return;
+        };
+        let crossings = self
+            .excursions
+            .update(&self.bodies, tracking.system_radius, self.sim_time);
+        for (body_id, crossing) in crossings {
+            let event = match crossing {
+                Crossing::Exited { distance } => SimulationEvent::BodyExited(ExcursionEvent {
+                    tick: self.tick,
+                    sim_time: self.sim_time,
+                    body_id,
+                    distance,
+                    duration: None,
+                }),
+                Crossing::Returned { distance, duration } => {
+                    SimulationEvent::BodyReturned(ExcursionEvent {
+                        tick: self.tick,
+                        sim_time: self.sim_time,
+                        body_id,
+                        distance,
+                        duration: Some(duration),
+                    })
+                }
+            };
+            self.emit(summary, event);
+        }
+    }
+
+    /// Compares Barnes-Hut against exact accelerations for a seeded sample of alive
+    /// bodies, whatever solver the config selects.
+    pub fn barnes_hut_error(&self, sampling: &ForceErrorSampling) -> Result<ForceErrorStats> {
+        sampling.validate()?;
+        let mut sample = self
+            .bodies
+            .iter()
+            .enumerate()
+            .filter_map(|(index, body)| body.alive.then_some(index))
+            .collect::<Vec<_>>();
+        let take = sampling.sample_size.min(sample.len());
+        let mut rng = Xoshiro256::new(sampling.seed);
+        for slot in 0..take {
+            let pick = slot + (rng.next_u64() % (sample.len() - slot) as u64) as usize;
+            sample.swap(slot, pick);
+        }
+        sample.truncate(take);
+        sample.sort_unstable();
+        let theta = sampling.theta.unwrap_or(self.config.barnes_hut_theta);
+        Ok(ForceErrorStats::from_errors(
+            theta,
+            barnes_hut_relative_errors(&self.bodies, &self.config, theta, &sample),
+        ))
+    }
+
+    /// Moves `barnes_hut_theta` one 10% step towards the `theta_tuning` target at the
+    /// end of each interval. A wider angle is faster and coarser.
+    fn tune_theta(&mut self, summary: &mut StepSummary) -> Result<()> {
+        let Some(tuning) = self.config.theta_tuning.clone() else {
+            return Ok(());
+        };
+        if self.tick.is_multiple_of(u64::from(tuning.interval_ticks)) {
+            let (ticks, micros) = std::mem::take(&mut self.theta_window);
+            let widen = match tuning.target {
+                ThetaTarget::TickMicros { micros: target } => {
+                    // Pairwise ticks do not depend on theta, so they say nothing.
+                    let average = (ticks > 0).then(|| micros as f64 / f64::from(ticks));
+                    let target = target as f64;
+                    match average {
+                        Some(average) if average > 1.1 * target => Some(true),
+                        Some(average) if average < 0.9 * target => Some(false),
+                        _ => None,
+                    }
+                }
+                ThetaTarget::ForceError { rms, sample_size } => {
+                    let stats = self.barnes_hut_error(&ForceErrorSampling {
+                        sample_size,
+                        seed: self.tick,
+                        ..ForceErrorSampling::default()
+                    })?;
+                    if stats.sampled == 0 {
+                        None
+                    } else if stats.rms > rms {
+                        Some(false)
+                    } else if stats.rms < 0.5 * rms {
+                        Some(true)
+                    } else {
+                        None
+                    }
+                }
+            };
+            let theta = match widen {
+                Some(true) => self.config.barnes_hut_theta * 1.1,
+                Some(false) => self.config.barnes_hut_theta / 1.1,
+                None => self.config.barnes_hut_theta,
+            };
+            self.config.barnes_hut_theta = theta.clamp(tuning.min_theta, tuning.max_theta);
+        }
+        summary.barnes_hut_theta = Some(self.config.barnes_hut_theta);
         Ok(())
     }
 
+    /// Merges legitimately shed angular momentum, so they re-baseline the guard.
+    fn guard_angular_momentum(&mut self, summary: &mut StepSummary, bodies_merged: bool) {
+        let Some(guard) = &self.config.angular_momentum_guard else {
+            return;
+        };
+        let current = angular_momentum(&self.bodies);
+        if bodies_merged {
+            self.angular_momentum_reference = Some(current);
+            return;
+        }
+        let reference = *self.angular_momentum_reference.get_or_insert(current);
+        if !self.tick.is_multiple_of(u64::from(guard.interval_ticks)) {
+            return;
+        }
+        let drift = (current - reference) / reference.abs().max(f64::MIN_POSITIVE);
+        summary.angular_momentum_drift = Some(drift);
+        if drift.abs() <= guard.tolerance {
+            return;
+        }
+        if guard.correct {
+            summary.angular_momentum_correction +=
+                add_angular_momentum(&mut self.bodies, reference - current);
+        } else {
+            summary.warnings.push(format!(
+                "angular momentum drifted by {drift:e} (relative) at tick {}",
+                self.tick
+            ));
+        }
+    }
+
+    /// Like the angular momentum guard, merges re-baseline the reference energy.
+    fn watch_energy(&mut self, summary: &mut StepSummary, bodies_merged: bool) -> Result<()> {
+        let Some(watchdog) = &self.config.energy_watchdog else {
+            return Ok(());
+        };
+        if bodies_merged {
+            self.energy_reference = Some(self.diagnostics().total_energy);
+            return Ok(());
+        }
+        if !self.tick.is_multiple_of(u64::from(watchdog.interval_ticks)) {
+            return Ok(());
+        }
+        let current = self.diagnostics().total_energy;
+        let reference = *self.energy_reference.get_or_insert(current);
+        let drift = (current - reference) / reference.abs().max(f64::MIN_POSITIVE);
+        summary.energy_drift = Some(drift);
+        if watchdog.hard_limit.is_some_and(|limit| drift.abs() > limit) {
+            return Err(EngineError::NumericalInstability(format!(
+                "total energy drifted by {drift:e} (relative) at tick {}; dt {} is likely too \
+                 large",
+                self.tick, self.config.dt
+            )));
+        }
+        if drift.abs() > watchdog.warn_drift {
+            summary.warnings.push(format!(
+                "total energy drifted by {drift:e} (relative) at tick {}; consider a smaller dt",
+                self.tick
+            ));
+        }
+        Ok(())
+    }
+
+    fn record_merge(
+        &mut self,
+        summary: &mut StepSummary,
+        survivor: String,
+        absorbed: Vec<String>,
+        mass: f64,
+        cause: MergeCause,
+    ) {
+        let record = MergeRecord {
+            tick: self.tick,
+            sim_time: self.sim_time,
+            survivor,
+            absorbed,
+            mass,
+            cause,
+        };
+        summary.merges.push(record.clone());
+        self.merge_history.push(record);
+    }
+
+    fn coarse_grain(&mut self, summary: &mut StepSummary) {
+        let Some(settings) = &self.config.coarse_graining else {
+            return;
+        };
+        for aggregate in coarse_grain(&mut self.bodies, settings) {
+            summary.aggregated_bodies += aggregate.absorbed.len() as u64;
+            self.record_merge(
+                summary,
+                aggregate.survivor.clone(),
+                aggregate.absorbed.clone(),
+                aggregate.mass,
+                MergeCause::CoarseGraining,
+            );
+            let event = SimulationEvent::Aggregated(AggregationEvent {
+                tick: self.tick,
+                sim_time: self.sim_time,
+                body_id: aggregate.survivor,
+                absorbed_ids: aggregate.absorbed,
+                member_masses: aggregate.member_masses,
+                mass: aggregate.mass,
+                position: aggregate.position,
+            });
+            self.emit(summary, event);
+        }
+    }
+
+    fn record_collision(&mut self, summary: &mut StepSummary, contact: CollisionContact) {
+        if let (Some(survivor), Some(mass)) = (&contact.merged_into, contact.merged_mass) {
+            let absorbed = if *survivor == contact.body_a {
+                &contact.body_b
+            } else {
+                &contact.body_a
+            };
+            self.record_merge(
+                summary,
+                survivor.clone(),
+                vec![absorbed.clone()],
+                mass,
+                MergeCause::Collision,
+            );
+        }
+        let kind = if contact.merged_into.is_some() {
+            CollisionKind::Merge
+        } else {
+            CollisionKind::Elastic
+        };
+        let event = SimulationEvent::Collision(CollisionEvent {
+            tick: self.tick,
+            sim_time: self.sim_time,
+            body_a: contact.body_a,
+            body_b: contact.body_b,
+            kind,
+            merged_into: contact.merged_into,
+            impact: contact.impact,
+        });
+        self.emit(summary, event);
+    }
+
+    fn track_zones(&mut self, summary: &mut StepSummary) {
+        let mut emitted = Vec::new();
+        for tracker in &mut self.zones {
+            let (entered, exited) = tracker.update(&self.bodies);
+            let event = |body_id: String| ZoneEvent {
+                tick: self.tick,
+                sim_time: self.sim_time,
+                zone: tracker.zone.name.clone(),
+                body_id,
+            };
+            emitted.extend(
+                entered
+                    .into_iter()
+                    .map(|id| SimulationEvent::ZoneEntered(event(id))),
+            );
+            emitted.extend(
+                exited
+                    .into_iter()
+                    .map(|id| SimulationEvent::ZoneExited(event(id))),
+            );
+        }
+        for event in emitted {
+            self.emit(summary, event);
+        }
+    }
+
+    fn binary_event(&self, binary: &BinaryRecord) -> BinaryEvent {
+        BinaryEvent {
+            tick: self.tick,
+            sim_time: self.sim_time,
+            primary_id: binary.primary_id.clone(),
+            secondary_id: binary.secondary_id.clone(),
+            semi_major_axis: binary.elements.semi_major_axis,
+            eccentricity: binary.elements.eccentricity,
+        }
+    }
+
     fn create_body(&mut self, body: Body) -> Result<()> {
         body.validate()?;
         if self.bodies.iter().any(|existing| existing.id == body.id) {
@@ -225,10 +2262,60 @@ impl SimulationEngine {
         if let Some(metadata) = update.metadata {
             body.metadata = Some(metadata);
         }
+        if let Some(collidable) = update.collidable {
+            body.collidable = collidable;
+        }
+        if let Some(fixed) = update.fixed {
+            body.fixed = fixed;
+        }
+        if let Some(group) = update.group {
+            body.group = Some(group);
+        }
+        if let Some(softening) = update.softening {
+            body.softening = Some(softening);
+        }
+        if let Some(angle) = update.angle {
+            body.angle = angle;
+        }
+        if let Some(angular_velocity) = update.angular_velocity {
+            body.angular_velocity = angular_velocity;
+        }
 
         body.validate()
     }
 
+    fn transform_bodies(&mut self, ids: &[String], transform: &Transform2) -> Result<()> {
+        if !transform.is_valid() {
+            return Err(EngineError::InvalidBody(
+                "transform must be finite with scale > 0".to_string(),
+            ));
+        }
+        for index in self.resolve_edit_targets(ids)? {
+            transform.apply_to_body(&mut self.bodies[index]);
+        }
+        Ok(())
+    }
+
+    /// Sorted, deduplicated indices for `ids`, or every index when `ids` is empty.
+    /// Every id is resolved up front so a typo leaves the system untouched.
+    fn resolve_edit_targets(&self, ids: &[String]) -> Result<Vec<usize>> {
+        let mut targets = Vec::with_capacity(ids.len());
+        for id in ids {
+            let index = self
+                .bodies
+                .iter()
+                .position(|body| body.id == *id)
+                .ok_or_else(|| EngineError::BodyNotFound(id.clone()))?;
+            targets.push(index);
+        }
+        if ids.is_empty() {
+            targets.extend(0..self.bodies.len());
+        }
+        targets.sort_unstable();
+        targets.dedup();
+        Ok(targets)
+    }
+
     fn delete_body(&mut self, id: &str) -> Result<()> {
         let initial_count = self.bodies.len();
         self.bodies.retain(|body| body.id != id);
@@ -239,6 +2326,13 @@ impl SimulationEngine {
     }
 }
 
+fn shift_bodies(bodies: &mut [Body], (position, velocity): (Vec2, Vec2)) {
+    for body in bodies.iter_mut().filter(|body| body.alive) {
+        body.position -= position;
+        body.velocity -= velocity;
+    }
+}
+
 fn validate_unique_body_ids(bodies: &[Body]) -> Result<()> {
     let mut ids = HashSet::new();
     for body in bodies {
@@ -248,3 +2342,23 @@ fn validate_unique_body_ids(bodies: &[Body]) -> Result<()> {
     }
     Ok(())
 }
+
+fn build_snapshot(config: &EngineConfig, markers: &[TimeMarker], frame: HistoryFrame) -> Snapshot {
+    let mut snapshot = Snapshot {
+        schema_version: "1.0".to_string(),
+        created_at: deterministic_timestamp_iso8601(),
+        tick: frame.tick,
+        sim_time: frame.sim_time,
+        config_hash: config.stable_hash(),
+        bodies: frame.bodies,
+        clock: frame.clock,
+        markers: markers
+            .iter()
+            .filter(|marker| marker.tick <= frame.tick)
+            .cloned()
+            .collect(),
+        checksum: None,
+    };
+    snapshot.checksum = Some(snapshot.compute_checksum());
+    snapshot
+}
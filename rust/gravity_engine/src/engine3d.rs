@@ -0,0 +1,425 @@
+use std::collections::HashSet;
+use std::time::Instant;
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::{CollisionMode, DtPolicy, EngineConfig, IntegratorKind};
+use crate::errors::{EngineError, Result};
+use crate::math::Vec3;
+use crate::octree::compute_accelerations_3d;
+use crate::solver::SolverRuntimeMode;
+use crate::types::{Body, BodyMetadata, StepSummary};
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Body3 {
+    pub id: String,
+    pub mass: f64,
+    pub radius: f64,
+    pub position: Vec3,
+    pub velocity: Vec3,
+    pub alive: bool,
+    pub metadata: Option<BodyMetadata>,
+}
+
+impl Body3 {
+    pub fn new(
+        id: impl Into<String>,
+        mass: f64,
+        radius: f64,
+        position: Vec3,
+        velocity: Vec3,
+    ) -> Self {
+        Self {
+            id: id.into(),
+            mass,
+            radius,
+            position,
+            velocity,
+            alive: true,
+            metadata: None,
+        }
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        if self.id.trim().is_empty() {
+            return Err(EngineError::InvalidBody("id must not be empty".to_string()));
+        }
+        if !self.mass.is_finite() || self.mass <= 0.0 {
+            return Err(EngineError::InvalidBody(format!(
+                "body '{}' mass must be finite and > 0",
+                self.id
+            )));
+        }
+        if !self.radius.is_finite() || self.radius <= 0.0 {
+            return Err(EngineError::InvalidBody(format!(
+                "body '{}' radius must be finite and > 0",
+                self.id
+            )));
+        }
+        if !self.position.is_finite() || !self.velocity.is_finite() {
+            return Err(EngineError::InvalidBody(format!(
+                "body '{}' position and velocity must be finite",
+                self.id
+            )));
+        }
+        Ok(())
+    }
+}
+
+impl From<&Body> for Body3 {
+    fn from(body: &Body) -> Self {
+        Self {
+            id: body.id.clone(),
+            mass: body.mass,
+            radius: body.radius,
+            position: body.position.into(),
+            velocity: body.velocity.into(),
+            alive: body.alive,
+            metadata: body.metadata.clone(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SimulationState3 {
+    pub tick: u64,
+    pub sim_time: f64,
+    pub config: EngineConfig,
+    pub bodies: Vec<Body3>,
+}
+
+/// Three-dimensional counterpart of [`crate::SimulationEngine`].
+///
+/// Shares `EngineConfig` with the planar engine; Barnes-Hut runs on an octree.
+/// Per-body force extensions of the planar engine are not available here.
+#[derive(Clone, Debug)]
+pub struct SimulationEngine3 {
+    config: EngineConfig,
+    bodies: Vec<Body3>,
+    tick: u64,
+    sim_time: f64,
+}
+
+impl SimulationEngine3 {
+    pub fn initialize(config: EngineConfig) -> Result<Self> {
+        Self::with_bodies(config, Vec::new())
+    }
+
+    pub fn with_bodies(config: EngineConfig, bodies: Vec<Body3>) -> Result<Self> {
+        validate_config_3d(&config)?;
+        let mut ids = HashSet::new();
+        for body in &bodies {
+            body.validate()?;
+            if !ids.insert(body.id.as_str()) {
+                return Err(EngineError::DuplicateBodyId(body.id.clone()));
+            }
+        }
+        Ok(Self {
+            config,
+            bodies,
+            tick: 0,
+            sim_time: 0.0,
+        })
+    }
+
+    pub fn config(&self) -> &EngineConfig {
+        &self.config
+    }
+
+    pub fn bodies(&self) -> &[Body3] {
+        &self.bodies
+    }
+
+    pub fn set_config(&mut self, config: EngineConfig) -> Result<()> {
+        validate_config_3d(&config)?;
+        self.config = config;
+        Ok(())
+    }
+
+    pub fn add_body(&mut self, body: Body3) -> Result<()> {
+        body.validate()?;
+        if self.bodies.iter().any(|existing| existing.id == body.id) {
+            return Err(EngineError::DuplicateBodyId(body.id));
+        }
+        self.bodies.push(body);
+        Ok(())
+    }
+
+    pub fn remove_body(&mut self, id: &str) -> Result<()> {
+        let initial_count = self.bodies.len();
+        self.bodies.retain(|body| body.id != id);
+        if self.bodies.len() == initial_count {
+            return Err(EngineError::BodyNotFound(id.to_string()));
+        }
+        Ok(())
+    }
+
+    pub fn step(&mut self, ticks: u32) -> Result<StepSummary> {
+        let mut summary = StepSummary {
+            max_body_count: self.bodies.len(),
+            ..StepSummary::default()
+        };
+        let wall_start = Instant::now();
+
+        for _ in 0..ticks {
+            let dt = effective_dt(&self.bodies, &self.config);
+            let mode = match self.config.integrator {
+                IntegratorKind::SemiImplicitEuler => {
+                    semi_implicit_euler_step(&mut self.bodies, &self.config, dt)
+                }
+                IntegratorKind::VelocityVerlet => {
+                    velocity_verlet_step(&mut self.bodies, &self.config, dt)
+                }
+                IntegratorKind::Rk4 => rk4_step(&mut self.bodies, &self.config, dt),
+            };
+            if let Some(body) = self
+                .bodies
+                .iter()
+                .find(|body| !body.position.is_finite() || !body.velocity.is_finite())
+            {
+                return Err(EngineError::NumericalInstability(format!(
+                    "body '{}' produced non-finite state",
+                    body.id
+                )));
+            }
+
+            let (collisions, merges) = resolve_collisions(&mut self.bodies, &self.config);
+            summary.collision_events += collisions;
+            summary.merged_events += merges;
+            summary.ticks_applied += 1;
+            summary.substeps += 1;
+            summary.max_body_count = summary.max_body_count.max(self.bodies.len());
+            if mode == SolverRuntimeMode::BarnesHut {
+                summary.barnes_hut_ticks += 1;
+                summary.last_solver_mode = "barnesHut".to_string();
+            } else {
+                summary.pairwise_ticks += 1;
+                summary.last_solver_mode = "pairwise".to_string();
+            }
+
+            self.tick += 1;
+            self.sim_time += dt;
+        }
+
+        summary.step_wall_time_micros = wall_start.elapsed().as_micros() as u64;
+        if summary.ticks_applied > 0 {
+            summary.average_tick_micros =
+                summary.step_wall_time_micros / (summary.ticks_applied as u64);
+        }
+        summary.final_tick = self.tick;
+        summary.sim_time = self.sim_time;
+        Ok(summary)
+    }
+
+    pub fn get_state(&self) -> SimulationState3 {
+        SimulationState3 {
+            tick: self.tick,
+            sim_time: self.sim_time,
+            config: self.config.clone(),
+            bodies: self.bodies.clone(),
+        }
+    }
+}
+
+fn validate_config_3d(config: &EngineConfig) -> Result<()> {
+    config.validate()?;
+    if matches!(config.dt_policy, DtPolicy::ErrorControlled) {
+        return Err(EngineError::UnsupportedFeature(
+            "error-controlled dt is not available in the 3D engine".to_string(),
+        ));
+    }
+    if matches!(config.dt_policy, DtPolicy::BlockTimesteps) {
+        return Err(EngineError::UnsupportedFeature(
+            "block timesteps are not available in the 3D engine".to_string(),
+        ));
+    }
+    // Planar-only options the 3D step would otherwise silently ignore.
+    let unsupported = [
+        ("boundary", config.boundary.is_some()),
+        ("central_body", config.central_body.is_some()),
+        ("remove_escapers", config.remove_escapers.is_some()),
+        ("tidal_disruption", config.tidal_disruption.is_some()),
+        ("coarse_graining", config.coarse_graining.is_some()),
+        ("interaction_groups", config.interaction_groups.is_some()),
+        ("collision_friction", config.collision_friction != 0.0),
+        ("recenter", config.recenter.is_some()),
+        ("pause_on_events", !config.pause_on_events.is_empty()),
+    ];
+    if let Some((option, _)) = unsupported.iter().find(|(_, set)| *set) {
+        return Err(EngineError::UnsupportedFeature(format!(
+            "{option} is not available in the 3D engine"
+        )));
+    }
+    Ok(())
+}
+
+fn effective_dt(bodies: &[Body3], config: &EngineConfig) -> f64 {
+    if !matches!(config.dt_policy, DtPolicy::Adaptive) {
+        return config.dt;
+    }
+
+    let alive = bodies.iter().filter(|body| body.alive).collect::<Vec<_>>();
+    let max_speed = alive
+        .iter()
+        .map(|body| body.velocity.norm())
+        .fold(0.0, f64::max);
+    let mut min_distance = f64::INFINITY;
+    for (i, first) in alive.iter().enumerate() {
+        for second in &alive[(i + 1)..] {
+            let distance = (second.position - first.position).norm();
+            if distance > 0.0 {
+                min_distance = min_distance.min(distance);
+            }
+        }
+    }
+
+    if !min_distance.is_finite() || max_speed == 0.0 {
+        return config.dt;
+    }
+    (0.05 * min_distance / max_speed).clamp(config.dt * 0.05, config.dt)
+}
+
+fn semi_implicit_euler_step(
+    bodies: &mut [Body3],
+    config: &EngineConfig,
+    dt: f64,
+) -> SolverRuntimeMode {
+    let positions = bodies.iter().map(|body| body.position).collect::<Vec<_>>();
+    let (accelerations, mode) = compute_accelerations_3d(bodies, &positions, config);
+    for (body, acceleration) in bodies.iter_mut().zip(accelerations) {
+        if body.alive {
+            body.velocity += acceleration * dt;
+            body.position += body.velocity * dt;
+        }
+    }
+    mode
+}
+
+fn velocity_verlet_step(bodies: &mut [Body3], config: &EngineConfig, dt: f64) -> SolverRuntimeMode {
+    let positions = bodies.iter().map(|body| body.position).collect::<Vec<_>>();
+    let (accelerations_0, mode) = compute_accelerations_3d(bodies, &positions, config);
+    let predicted = bodies
+        .iter()
+        .zip(&accelerations_0)
+        .map(|(body, acceleration)| {
+            if body.alive {
+                body.position + body.velocity * dt + *acceleration * (0.5 * dt * dt)
+            } else {
+                body.position
+            }
+        })
+        .collect::<Vec<_>>();
+    let (accelerations_1, _) = compute_accelerations_3d(bodies, &predicted, config);
+
+    for (index, body) in bodies.iter_mut().enumerate() {
+        if body.alive {
+            body.position = predicted[index];
+            body.velocity += (accelerations_0[index] + accelerations_1[index]) * (0.5 * dt);
+        }
+    }
+    mode
+}
+
+fn rk4_step(bodies: &mut [Body3], config: &EngineConfig, dt: f64) -> SolverRuntimeMode {
+    let count = bodies.len();
+    let p0 = bodies.iter().map(|body| body.position).collect::<Vec<_>>();
+    let v0 = bodies.iter().map(|body| body.velocity).collect::<Vec<_>>();
+
+    let (k1v, mode) = compute_accelerations_3d(bodies, &p0, config);
+    let k1p = v0.clone();
+
+    let p2 = (0..count)
+        .map(|i| p0[i] + k1p[i] * (0.5 * dt))
+        .collect::<Vec<_>>();
+    let k2p = (0..count)
+        .map(|i| v0[i] + k1v[i] * (0.5 * dt))
+        .collect::<Vec<_>>();
+    let (k2v, _) = compute_accelerations_3d(bodies, &p2, config);
+
+    let p3 = (0..count)
+        .map(|i| p0[i] + k2p[i] * (0.5 * dt))
+        .collect::<Vec<_>>();
+    let k3p = (0..count)
+        .map(|i| v0[i] + k2v[i] * (0.5 * dt))
+        .collect::<Vec<_>>();
+    let (k3v, _) = compute_accelerations_3d(bodies, &p3, config);
+
+    let p4 = (0..count).map(|i| p0[i] + k3p[i] * dt).collect::<Vec<_>>();
+    let k4p = (0..count).map(|i| v0[i] + k3v[i] * dt).collect::<Vec<_>>();
+    let (k4v, _) = compute_accelerations_3d(bodies, &p4, config);
+
+    for (i, body) in bodies.iter_mut().enumerate() {
+        if body.alive {
+            body.position += (k1p[i] + k2p[i] * 2.0 + k3p[i] * 2.0 + k4p[i]) * (dt / 6.0);
+            body.velocity += (k1v[i] + k2v[i] * 2.0 + k3v[i] * 2.0 + k4v[i]) * (dt / 6.0);
+        }
+    }
+    mode
+}
+
+fn resolve_collisions(bodies: &mut Vec<Body3>, config: &EngineConfig) -> (u64, u64) {
+    if matches!(config.collision_mode, CollisionMode::Ignore) {
+        return (0, 0);
+    }
+
+    let mut collisions = 0;
+    let mut merges = 0;
+    let count = bodies.len();
+    for i in 0..count {
+        for j in (i + 1)..count {
+            let (left, right) = bodies.split_at_mut(j);
+            let (first, second) = (&mut left[i], &mut right[0]);
+            if !first.alive || !second.alive {
+                continue;
+            }
+
+            let delta = second.position - first.position;
+            let distance = delta.norm();
+            let collision_distance = first.radius + second.radius;
+            if distance > collision_distance {
+                continue;
+            }
+            collisions += 1;
+
+            match config.collision_mode {
+                CollisionMode::Elastic => {
+                    let normal = delta.normalized_or(Vec3::new(1.0, 0.0, 0.0));
+                    let vel_along_normal = (second.velocity - first.velocity).dot(normal);
+                    if vel_along_normal <= 0.0 {
+                        let inverse_mass_sum = first.mass.recip() + second.mass.recip();
+                        let impulse = normal
+                            * (-(1.0 + config.restitution) * vel_along_normal / inverse_mass_sum);
+                        first.velocity -= impulse / first.mass;
+                        second.velocity += impulse / second.mass;
+                    }
+                    let overlap = (collision_distance - distance).max(0.0);
+                    if overlap > 0.0 {
+                        let correction = normal * (0.5 * overlap + 1e-9);
+                        first.position -= correction;
+                        second.position += correction;
+                    }
+                }
+                CollisionMode::InelasticMerge => {
+                    let total_mass = first.mass + second.mass;
+                    first.position =
+                        (first.position * first.mass + second.position * second.mass) / total_mass;
+                    first.velocity =
+                        (first.velocity * first.mass + second.velocity * second.mass) / total_mass;
+                    // Volume-preserving in 3D, unlike the area-preserving planar merge.
+                    first.radius = (first.radius.powi(3) + second.radius.powi(3)).cbrt();
+                    first.mass = total_mass;
+                    second.alive = false;
+                    merges += 1;
+                }
+                CollisionMode::Ignore => {}
+            }
+        }
+    }
+
+    if matches!(config.collision_mode, CollisionMode::InelasticMerge) {
+        bodies.retain(|body| body.alive);
+    }
+    (collisions, merges)
+}
@@ -0,0 +1,105 @@
+//! Scenario epochs: tick ranges that run with a config overlay on top of the
+//! scenario's base config, switched by the engine at the start of a tick.
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::{ConfigPatch, EngineConfig};
+use crate::errors::{EngineError, Result};
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct Epoch {
+    pub name: String,
+    pub start_tick: u64,
+    /// Exclusive; `None` runs until the next epoch starts, or forever.
+    #[serde(default)]
+    pub end_tick: Option<u64>,
+    pub overlay: ConfigPatch,
+}
+
+/// Epochs must be ordered by `start_tick` without overlapping, and every overlay must
+/// apply cleanly to `base`.
+pub fn validate_epochs(epochs: &[Epoch], base: &EngineConfig) -> Result<()> {
+    for (index, epoch) in epochs.iter().enumerate() {
+        if epoch.name.trim().is_empty() {
+            return Err(EngineError::InvalidConfig(
+                "epoch name must not be empty".to_string(),
+            ));
+        }
+        if epoch.end_tick.is_some_and(|end| end <= epoch.start_tick) {
+            return Err(EngineError::InvalidConfig(format!(
+                "epoch '{}' must end after it starts",
+                epoch.name
+            )));
+        }
+        if let Some(next) = epochs.get(index + 1)
+            && (next.start_tick <= epoch.start_tick
+                || epoch.end_tick.is_some_and(|end| next.start_tick < end))
+        {
+            return Err(EngineError::InvalidConfig(format!(
+                "epoch '{}' must start after epoch '{}' ends",
+                next.name, epoch.name
+            )));
+        }
+        base.apply_patch(&epoch.overlay).map_err(|error| {
+            EngineError::InvalidConfig(format!("epoch '{}': {error}", epoch.name))
+        })?;
+    }
+    Ok(())
+}
+
+/// The engine's epoch state. `active` is the epoch whose overlay is in effect.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct EpochSchedule {
+    pub epochs: Vec<Epoch>,
+    pub base: Option<EngineConfig>,
+    pub active: Option<usize>,
+}
+
+impl EpochSchedule {
+    pub(crate) fn new(epochs: Vec<Epoch>, base: &EngineConfig) -> Result<Self> {
+        validate_epochs(&epochs, base)?;
+        Ok(Self {
+            base: (!epochs.is_empty()).then(|| base.clone()),
+            epochs,
+            active: None,
+        })
+    }
+
+    pub(crate) fn epoch_at(&self, tick: u64) -> Option<usize> {
+        self.epochs.iter().enumerate().find_map(|(index, epoch)| {
+            let end = epoch
+                .end_tick
+                .or_else(|| self.epochs.get(index + 1).map(|next| next.start_tick))
+                .unwrap_or(u64::MAX);
+            (epoch.start_tick..end).contains(&tick).then_some(index)
+        })
+    }
+
+    /// True when an epoch starts or ends at a tick in `(from, to)`, so ticks `from`
+    /// to `to - 1` do not all run with the same config.
+    pub(crate) fn changes_between(&self, from: u64, to: u64) -> bool {
+        self.epochs.iter().any(|epoch| {
+            [Some(epoch.start_tick), epoch.end_tick]
+                .into_iter()
+                .flatten()
+                .any(|tick| from < tick && tick < to)
+        })
+    }
+
+    pub(crate) fn name(&self, index: Option<usize>) -> Option<String> {
+        index.map(|index| self.epochs[index].name.clone())
+    }
+
+    /// Base config with the overlay of epoch `index` applied; `None` without epochs.
+    pub(crate) fn config_for(&self, index: Option<usize>) -> Result<Option<EngineConfig>> {
+        let Some(base) = &self.base else {
+            return Ok(None);
+        };
+        match index {
+            Some(index) => Ok(Some(base.apply_patch(&self.epochs[index].overlay)?.0)),
+            None => Ok(Some(base.clone())),
+        }
+    }
+}
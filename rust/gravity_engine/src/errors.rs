@@ -1,3 +1,4 @@
+use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
 pub type Result<T> = std::result::Result<T, EngineError>;
@@ -12,10 +13,69 @@ pub enum EngineError {
     DuplicateBodyId(String),
     #[error("body not found: {0}")]
     BodyNotFound(String),
+    #[error("checkpoint not found: {0}")]
+    CheckpointNotFound(String),
     #[error("numerical instability: {0}")]
     NumericalInstability(String),
     #[error("schema validation failed: {0}")]
     SchemaValidationFailed(String),
+    #[error("checksum mismatch: {0}")]
+    ChecksumMismatch(String),
+    #[error("event queue full: {0}")]
+    EventQueueFull(String),
     #[error("unsupported feature: {0}")]
     UnsupportedFeature(String),
+    #[error("background worker failed: {0}")]
+    WorkerFailed(String),
+    #[error("export failed: {0}")]
+    ExportFailed(String),
+    #[error("engine busy: {0}")]
+    Busy(String),
+}
+
+/// Machine-readable error codes shipped as `errorCode` in FFI responses. The
+/// discriminants are part of the C ABI: codes are only ever appended.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[repr(u32)]
+pub enum ErrorCode {
+    InvalidConfig = 1,
+    InvalidBody = 2,
+    DuplicateBodyId = 3,
+    BodyNotFound = 4,
+    CheckpointNotFound = 5,
+    NumericalInstability = 6,
+    SchemaValidationFailed = 7,
+    ChecksumMismatch = 8,
+    EventQueueFull = 9,
+    UnsupportedFeature = 10,
+    WorkerFailed = 11,
+    ExportFailed = 12,
+    /// The engine is stepping on a background worker.
+    Busy = 13,
+    /// A null pointer, malformed JSON or otherwise unusable FFI argument.
+    InvalidArgument = 100,
+    /// No engine is registered under the handle.
+    EngineNotFound = 101,
+    /// A poisoned lock or another fault inside the FFI layer.
+    Internal = 102,
+}
+
+impl EngineError {
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            Self::InvalidConfig(_) => ErrorCode::InvalidConfig,
+            Self::InvalidBody(_) => ErrorCode::InvalidBody,
+            Self::DuplicateBodyId(_) => ErrorCode::DuplicateBodyId,
+            Self::BodyNotFound(_) => ErrorCode::BodyNotFound,
+            Self::CheckpointNotFound(_) => ErrorCode::CheckpointNotFound,
+            Self::NumericalInstability(_) => ErrorCode::NumericalInstability,
+            Self::SchemaValidationFailed(_) => ErrorCode::SchemaValidationFailed,
+            Self::ChecksumMismatch(_) => ErrorCode::ChecksumMismatch,
+            Self::EventQueueFull(_) => ErrorCode::EventQueueFull,
+            Self::UnsupportedFeature(_) => ErrorCode::UnsupportedFeature,
+            Self::WorkerFailed(_) => ErrorCode::WorkerFailed,
+            Self::ExportFailed(_) => ErrorCode::ExportFailed,
+            Self::Busy(_) => ErrorCode::Busy,
+        }
+    }
 }
@@ -0,0 +1,516 @@
+use std::collections::VecDeque;
+
+use serde::{Deserialize, Serialize};
+
+use crate::errors::{EngineError, Result};
+use crate::math::Vec2;
+use crate::playlist::PlaylistTransition;
+use crate::types::Body;
+use crate::zones::Region;
+
+pub const DEFAULT_EVENT_LOG_CAPACITY: usize = 4096;
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AlignmentEvent {
+    pub tick: u64,
+    pub sim_time: f64,
+    pub body_ids: Vec<String>,
+    /// Angular distance from perfect collinearity, in radians.
+    pub alignment_error: f64,
+    /// Angle at the middle body: near 0 for a conjunction, near pi for an opposition.
+    pub separation_angle: f64,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BinaryEvent {
+    pub tick: u64,
+    pub sim_time: f64,
+    pub primary_id: String,
+    pub secondary_id: String,
+    pub semi_major_axis: f64,
+    pub eccentricity: f64,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExcursionEvent {
+    pub tick: u64,
+    pub sim_time: f64,
+    pub body_id: String,
+    /// Distance from the system centre of mass when the crossing was detected.
+    pub distance: f64,
+    /// Time spent outside; only set on return.
+    #[serde(default)]
+    pub duration: Option<f64>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ZoneEvent {
+    pub tick: u64,
+    pub sim_time: f64,
+    pub zone: String,
+    pub body_id: String,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum CollisionKind {
+    Elastic,
+    Merge,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CollisionEvent {
+    pub tick: u64,
+    pub sim_time: f64,
+    pub body_a: String,
+    pub body_b: String,
+    pub kind: CollisionKind,
+    #[serde(default)]
+    pub merged_into: Option<String>,
+    /// Set for merges.
+    #[serde(default)]
+    pub impact: Option<ImpactReport>,
+}
+
+/// The approach of two bodies just before they merged.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImpactReport {
+    /// Kinetic energy of the relative motion in the centre-of-mass frame,
+    /// `0.5 * mu * speed^2`, all of which the merge dissipates. In SI units this is
+    /// joules.
+    pub energy: f64,
+    /// Relative speed of the two bodies.
+    pub speed: f64,
+    /// Between the relative velocity and the line of centres, in radians: 0 is
+    /// head-on, pi/2 grazing.
+    pub angle: f64,
+}
+
+/// Far-field bodies merged into a super-particle by coarse-graining.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AggregationEvent {
+    pub tick: u64,
+    pub sim_time: f64,
+    /// The member that now carries the aggregate.
+    pub body_id: String,
+    pub absorbed_ids: Vec<String>,
+    /// Every member's mass before the merge, survivor included, so the original mass
+    /// spectrum can be reconstructed.
+    pub member_masses: Vec<f64>,
+    pub mass: f64,
+    pub position: Vec2,
+}
+
+/// A body removed by `EngineConfig::remove_escapers`, with its distance and speed
+/// relative to the rest of the system.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EscapeEvent {
+    pub tick: u64,
+    pub sim_time: f64,
+    pub body_id: String,
+    pub distance: f64,
+    pub speed: f64,
+}
+
+/// A body that left an absorbing world boundary and died.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BoundaryEvent {
+    pub tick: u64,
+    pub sim_time: f64,
+    pub body_id: String,
+    pub position: Vec2,
+}
+
+/// A body that passed inside a primary's Roche limit.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TidalDisruptionEvent {
+    pub tick: u64,
+    pub sim_time: f64,
+    pub body_id: String,
+    pub primary_id: String,
+    pub distance: f64,
+    pub roche_limit: f64,
+    /// Ids of the pieces that replaced the body; empty when only flagging.
+    #[serde(default)]
+    pub fragment_ids: Vec<String>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum MergeCause {
+    Collision,
+    CoarseGraining,
+}
+
+/// One link in the merge genealogy: `absorbed` ended at `tick` and `survivor` carries
+/// their mass on. Follow survivors through later records to trace accretion chains.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MergeRecord {
+    pub tick: u64,
+    pub sim_time: f64,
+    pub survivor: String,
+    pub absorbed: Vec<String>,
+    /// Survivor's mass right after the merge.
+    pub mass: f64,
+    pub cause: MergeCause,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PlaylistEvent {
+    pub tick: u64,
+    pub sim_time: f64,
+    pub from_index: usize,
+    pub to_index: usize,
+    pub scenario_name: String,
+    pub transition: PlaylistTransition,
+}
+
+/// The engine switched scenario epochs at the start of `tick`.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EpochEvent {
+    pub tick: u64,
+    pub sim_time: f64,
+    /// `None` outside any epoch, running the base config.
+    pub from: Option<String>,
+    pub to: Option<String>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum SimulationEvent {
+    Alignment(AlignmentEvent),
+    BinaryFormed(BinaryEvent),
+    BinaryDisrupted(BinaryEvent),
+    BodyExited(ExcursionEvent),
+    BodyReturned(ExcursionEvent),
+    ZoneEntered(ZoneEvent),
+    ZoneExited(ZoneEvent),
+    Collision(CollisionEvent),
+    PlaylistAdvanced(PlaylistEvent),
+    Aggregated(AggregationEvent),
+    EpochChanged(EpochEvent),
+    BodyAbsorbed(BoundaryEvent),
+    BodyEscaped(EscapeEvent),
+    TidalDisruption(TidalDisruptionEvent),
+}
+
+impl SimulationEvent {
+    pub const KINDS: [&'static str; 14] = [
+        "alignment",
+        "binaryFormed",
+        "binaryDisrupted",
+        "bodyExited",
+        "bodyReturned",
+        "zoneEntered",
+        "zoneExited",
+        "collision",
+        "playlistAdvanced",
+        "aggregated",
+        "epochChanged",
+        "bodyAbsorbed",
+        "bodyEscaped",
+        "tidalDisruption",
+    ];
+
+    pub fn kind(&self) -> &'static str {
+        match self {
+            SimulationEvent::Alignment(_) => "alignment",
+            SimulationEvent::BinaryFormed(_) => "binaryFormed",
+            SimulationEvent::BinaryDisrupted(_) => "binaryDisrupted",
+            SimulationEvent::BodyExited(_) => "bodyExited",
+            SimulationEvent::BodyReturned(_) => "bodyReturned",
+            SimulationEvent::ZoneEntered(_) => "zoneEntered",
+            SimulationEvent::ZoneExited(_) => "zoneExited",
+            SimulationEvent::Collision(_) => "collision",
+            SimulationEvent::PlaylistAdvanced(_) => "playlistAdvanced",
+            SimulationEvent::Aggregated(_) => "aggregated",
+            SimulationEvent::EpochChanged(_) => "epochChanged",
+            SimulationEvent::BodyAbsorbed(_) => "bodyAbsorbed",
+            SimulationEvent::BodyEscaped(_) => "bodyEscaped",
+            SimulationEvent::TidalDisruption(_) => "tidalDisruption",
+        }
+    }
+
+    /// Ids of the bodies the event is about.
+    pub fn body_ids(&self) -> Vec<&str> {
+        match self {
+            SimulationEvent::Alignment(event) => {
+                event.body_ids.iter().map(String::as_str).collect()
+            }
+            SimulationEvent::BinaryFormed(event) | SimulationEvent::BinaryDisrupted(event) => {
+                vec![&event.primary_id, &event.secondary_id]
+            }
+            SimulationEvent::BodyExited(event) | SimulationEvent::BodyReturned(event) => {
+                vec![&event.body_id]
+            }
+            SimulationEvent::ZoneEntered(event) | SimulationEvent::ZoneExited(event) => {
+                vec![&event.body_id]
+            }
+            SimulationEvent::Collision(event) => vec![&event.body_a, &event.body_b],
+            SimulationEvent::BodyAbsorbed(event) => vec![&event.body_id],
+            SimulationEvent::BodyEscaped(event) => vec![&event.body_id],
+            SimulationEvent::TidalDisruption(event) => [&event.body_id, &event.primary_id]
+                .into_iter()
+                .chain(&event.fragment_ids)
+                .map(String::as_str)
+                .collect(),
+            SimulationEvent::PlaylistAdvanced(_) | SimulationEvent::EpochChanged(_) => Vec::new(),
+            SimulationEvent::Aggregated(event) => std::iter::once(&event.body_id)
+                .chain(&event.absorbed_ids)
+                .map(String::as_str)
+                .collect(),
+        }
+    }
+
+    pub fn tick(&self) -> u64 {
+        match self {
+            SimulationEvent::Alignment(event) => event.tick,
+            SimulationEvent::BinaryFormed(event) | SimulationEvent::BinaryDisrupted(event) => {
+                event.tick
+            }
+            SimulationEvent::BodyExited(event) | SimulationEvent::BodyReturned(event) => event.tick,
+            SimulationEvent::ZoneEntered(event) | SimulationEvent::ZoneExited(event) => event.tick,
+            SimulationEvent::Collision(event) => event.tick,
+            SimulationEvent::PlaylistAdvanced(event) => event.tick,
+            SimulationEvent::Aggregated(event) => event.tick,
+            SimulationEvent::EpochChanged(event) => event.tick,
+            SimulationEvent::BodyAbsorbed(event) => event.tick,
+            SimulationEvent::BodyEscaped(event) => event.tick,
+            SimulationEvent::TidalDisruption(event) => event.tick,
+        }
+    }
+}
+
+/// Interest filter for events shipped to a host. An event passes when it passes
+/// every criterion that is set.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EventFilter {
+    /// Event kinds (see `SimulationEvent::kind`) to keep; empty keeps every kind.
+    #[serde(default)]
+    pub kinds: Vec<String>,
+    /// Keep events involving at least one of these bodies; empty keeps all.
+    #[serde(default)]
+    pub body_ids: Vec<String>,
+    /// Keep events involving a body that is currently inside the region.
+    #[serde(default)]
+    pub region: Option<Region>,
+}
+
+impl EventFilter {
+    pub fn validate(&self) -> Result<()> {
+        if let Some(kind) = self
+            .kinds
+            .iter()
+            .find(|kind| !SimulationEvent::KINDS.contains(&kind.as_str()))
+        {
+            return Err(EngineError::InvalidConfig(format!(
+                "event filter names unknown event kind '{kind}'"
+            )));
+        }
+        match &self.region {
+            Some(region) => region.validate(),
+            None => Ok(()),
+        }
+    }
+
+    /// Body criteria do not apply to events without bodies, such as
+    /// `playlistAdvanced`. Bodies that no longer exist are never inside the region.
+    pub fn matches(&self, event: &SimulationEvent, bodies: &[Body]) -> bool {
+        if !self.kinds.is_empty() && !self.kinds.iter().any(|kind| kind == event.kind()) {
+            return false;
+        }
+        let involved = event.body_ids();
+        if involved.is_empty() {
+            return true;
+        }
+        if !self.body_ids.is_empty()
+            && !involved
+                .iter()
+                .any(|id| self.body_ids.iter().any(|wanted| wanted == id))
+        {
+            return false;
+        }
+        self.region.as_ref().is_none_or(|region| {
+            bodies
+                .iter()
+                .filter(|body| body.alive && involved.contains(&body.id.as_str()))
+                .any(|body| region.contains(body.position))
+        })
+    }
+}
+
+/// What a full event queue does with an incoming event.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum EventOverflow {
+    /// Evict the oldest queued event.
+    #[default]
+    DropOldest,
+    /// Drop the newest queued event of the same kind and append the incoming one, so a
+    /// burst collapses to its latest member. Evicts the oldest event when no event of
+    /// that kind is queued.
+    CoalesceByKind,
+    /// Refuse the event; `step` fails with `EventQueueFull` after the tick.
+    Error,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PushOutcome {
+    Queued,
+    DroppedOldest,
+    Coalesced,
+    Rejected,
+}
+
+/// Queues that `push_bounded` can manage.
+pub(crate) trait EventBuffer {
+    fn len(&self) -> usize;
+    fn newest_of_kind(&self, kind: &str) -> Option<usize>;
+    fn remove(&mut self, index: usize);
+    fn push(&mut self, event: SimulationEvent);
+}
+
+impl EventBuffer for VecDeque<SimulationEvent> {
+    fn len(&self) -> usize {
+        VecDeque::len(self)
+    }
+
+    fn newest_of_kind(&self, kind: &str) -> Option<usize> {
+        self.iter().rposition(|event| event.kind() == kind)
+    }
+
+    fn remove(&mut self, index: usize) {
+        VecDeque::remove(self, index);
+    }
+
+    fn push(&mut self, event: SimulationEvent) {
+        self.push_back(event);
+    }
+}
+
+impl EventBuffer for Vec<SimulationEvent> {
+    fn len(&self) -> usize {
+        Vec::len(self)
+    }
+
+    fn newest_of_kind(&self, kind: &str) -> Option<usize> {
+        self.iter().rposition(|event| event.kind() == kind)
+    }
+
+    fn remove(&mut self, index: usize) {
+        Vec::remove(self, index);
+    }
+
+    fn push(&mut self, event: SimulationEvent) {
+        Vec::push(self, event);
+    }
+}
+
+pub(crate) fn push_bounded(
+    events: &mut impl EventBuffer,
+    capacity: usize,
+    overflow: EventOverflow,
+    event: SimulationEvent,
+) -> PushOutcome {
+    if events.len() < capacity {
+        events.push(event);
+        return PushOutcome::Queued;
+    }
+    let outcome = match overflow {
+        EventOverflow::Error => return PushOutcome::Rejected,
+        EventOverflow::CoalesceByKind => match events.newest_of_kind(event.kind()) {
+            Some(index) => {
+                events.remove(index);
+                PushOutcome::Coalesced
+            }
+            None => {
+                events.remove(0);
+                PushOutcome::DroppedOldest
+            }
+        },
+        EventOverflow::DropOldest => {
+            events.remove(0);
+            PushOutcome::DroppedOldest
+        }
+    };
+    events.push(event);
+    outcome
+}
+
+/// Bounded log of recent events, held until the host drains it; `overflow` decides
+/// what happens when it is full.
+#[derive(Clone, Debug)]
+pub struct EventLog {
+    capacity: usize,
+    overflow: EventOverflow,
+    events: VecDeque<SimulationEvent>,
+}
+
+impl Default for EventLog {
+    fn default() -> Self {
+        Self::new(DEFAULT_EVENT_LOG_CAPACITY)
+    }
+}
+
+impl EventLog {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            overflow: EventOverflow::default(),
+            events: VecDeque::new(),
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = capacity.max(1);
+        while self.events.len() > self.capacity {
+            self.events.pop_front();
+        }
+    }
+
+    pub fn overflow(&self) -> EventOverflow {
+        self.overflow
+    }
+
+    pub fn set_overflow(&mut self, overflow: EventOverflow) {
+        self.overflow = overflow;
+    }
+
+    pub fn push(&mut self, event: SimulationEvent) -> PushOutcome {
+        push_bounded(&mut self.events, self.capacity, self.overflow, event)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &SimulationEvent> {
+        self.events.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.events.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+
+    pub fn drain(&mut self) -> Vec<SimulationEvent> {
+        self.events.drain(..).collect()
+    }
+}
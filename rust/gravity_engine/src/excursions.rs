@@ -0,0 +1,163 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::EscapePolicy;
+use crate::math::Vec2;
+use crate::types::Body;
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExcursionRecord {
+    pub body_id: String,
+    pub exits: u32,
+    pub returns: u32,
+    /// Set while the body is beyond the system radius.
+    pub outside_since: Option<f64>,
+    /// Sum of completed excursion durations.
+    pub time_outside: f64,
+    pub longest_excursion: f64,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExcursionSummary {
+    pub total_exits: u32,
+    pub total_returns: u32,
+    pub currently_outside: u32,
+    pub bodies: Vec<ExcursionRecord>,
+}
+
+pub(crate) enum Crossing {
+    Exited { distance: f64 },
+    Returned { distance: f64, duration: f64 },
+}
+
+/// Per-body inside/outside state, measured from the system centre of mass.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct ExcursionTracker {
+    records: BTreeMap<String, ExcursionRecord>,
+}
+
+impl ExcursionTracker {
+    pub(crate) fn clear(&mut self) {
+        self.records.clear();
+    }
+
+    /// Bodies seen for the first time only establish their state, so bodies that
+    /// start far out do not produce a spurious exit.
+    pub(crate) fn update(
+        &mut self,
+        bodies: &[Body],
+        system_radius: f64,
+        sim_time: f64,
+    ) -> Vec<(String, Crossing)> {
+        let center = center_of_mass(bodies);
+        let mut crossings = Vec::new();
+
+        for body in bodies.iter().filter(|body| body.alive) {
+            let distance = (body.position - center).norm();
+            let outside = distance > system_radius;
+            let Some(record) = self.records.get_mut(&body.id) else {
+                self.records.insert(
+                    body.id.clone(),
+                    ExcursionRecord {
+                        body_id: body.id.clone(),
+                        outside_since: outside.then_some(sim_time),
+                        ..ExcursionRecord::default()
+                    },
+                );
+                continue;
+            };
+
+            match (record.outside_since, outside) {
+                (None, true) => {
+                    record.exits += 1;
+                    record.outside_since = Some(sim_time);
+                    crossings.push((body.id.clone(), Crossing::Exited { distance }));
+                }
+                (Some(since), false) => {
+                    let duration = sim_time - since;
+                    record.returns += 1;
+                    record.outside_since = None;
+                    record.time_outside += duration;
+                    record.longest_excursion = record.longest_excursion.max(duration);
+                    crossings.push((body.id.clone(), Crossing::Returned { distance, duration }));
+                }
+                _ => {}
+            }
+        }
+
+        crossings
+    }
+
+    pub(crate) fn summary(&self) -> ExcursionSummary {
+        let bodies = self.records.values().cloned().collect::<Vec<_>>();
+        ExcursionSummary {
+            total_exits: bodies.iter().map(|record| record.exits).sum(),
+            total_returns: bodies.iter().map(|record| record.returns).sum(),
+            currently_outside: bodies
+                .iter()
+                .filter(|record| record.outside_since.is_some())
+                .count() as u32,
+            bodies,
+        }
+    }
+}
+
+pub(crate) struct Escaper {
+    pub(crate) index: usize,
+    pub(crate) distance: f64,
+    pub(crate) speed: f64,
+}
+
+/// Alive, unpinned bodies that meet `policy`. Each is measured against the rest of the
+/// system lumped at its centre of mass.
+pub(crate) fn find_escapers(bodies: &[Body], policy: &EscapePolicy, g: f64) -> Vec<Escaper> {
+    let mut total_mass = 0.0;
+    let mut weighted_position = Vec2::ZERO;
+    let mut weighted_velocity = Vec2::ZERO;
+    for body in bodies.iter().filter(|body| body.alive) {
+        total_mass += body.mass;
+        weighted_position += body.position * body.mass;
+        weighted_velocity += body.velocity * body.mass;
+    }
+
+    let mut escapers = Vec::new();
+    for (index, body) in bodies.iter().enumerate() {
+        let rest_mass = total_mass - body.mass;
+        if !body.alive || body.fixed || rest_mass <= 0.0 {
+            continue;
+        }
+        let offset = body.position - (weighted_position - body.position * body.mass) / rest_mass;
+        let velocity = body.velocity - (weighted_velocity - body.velocity * body.mass) / rest_mass;
+        let distance = offset.norm();
+        if distance <= policy.radius || offset.dot(velocity) <= 0.0 {
+            continue;
+        }
+        let speed = velocity.norm();
+        let escape_speed = (2.0 * g * rest_mass / distance).sqrt();
+        if speed > escape_speed.max(policy.min_speed) {
+            escapers.push(Escaper {
+                index,
+                distance,
+                speed,
+            });
+        }
+    }
+    escapers
+}
+
+fn center_of_mass(bodies: &[Body]) -> Vec2 {
+    let mut total_mass = 0.0;
+    let mut weighted = Vec2::ZERO;
+    for body in bodies.iter().filter(|body| body.alive) {
+        total_mass += body.mass;
+        weighted += body.position * body.mass;
+    }
+    if total_mass > 0.0 {
+        weighted / total_mass
+    } else {
+        Vec2::ZERO
+    }
+}
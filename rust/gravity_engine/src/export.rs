@@ -0,0 +1,356 @@
+//! Tabular export of recorded trajectories and per-tick diagnostics.
+//!
+//! Trajectories come from a `History` (one row per body per frame); diagnostics
+//! from `DiagnosticsRow`s the host captures as it steps. Both write CSV with the
+//! selected columns in the given order, and Parquet with the `parquet` feature.
+
+use std::io::Write;
+
+use serde::{Deserialize, Serialize};
+
+use crate::diagnostics::Diagnostics;
+use crate::engine::SimulationEngine;
+use crate::errors::{EngineError, Result};
+use crate::history::{BodySample, History, HistoryFrame};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum TrajectoryColumn {
+    Tick,
+    SimTime,
+    Id,
+    Mass,
+    Radius,
+    X,
+    Y,
+    Vx,
+    Vy,
+}
+
+impl TrajectoryColumn {
+    pub const ALL: [Self; 9] = [
+        Self::Tick,
+        Self::SimTime,
+        Self::Id,
+        Self::Mass,
+        Self::Radius,
+        Self::X,
+        Self::Y,
+        Self::Vx,
+        Self::Vy,
+    ];
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::Tick => "tick",
+            Self::SimTime => "sim_time",
+            Self::Id => "id",
+            Self::Mass => "mass",
+            Self::Radius => "radius",
+            Self::X => "x",
+            Self::Y => "y",
+            Self::Vx => "vx",
+            Self::Vy => "vy",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum DiagnosticsColumn {
+    Tick,
+    SimTime,
+    KineticEnergy,
+    PotentialEnergy,
+    TotalEnergy,
+    MomentumX,
+    MomentumY,
+    AngularMomentum,
+    CenterOfMassX,
+    CenterOfMassY,
+    TotalMass,
+}
+
+impl DiagnosticsColumn {
+    pub const ALL: [Self; 11] = [
+        Self::Tick,
+        Self::SimTime,
+        Self::KineticEnergy,
+        Self::PotentialEnergy,
+        Self::TotalEnergy,
+        Self::MomentumX,
+        Self::MomentumY,
+        Self::AngularMomentum,
+        Self::CenterOfMassX,
+        Self::CenterOfMassY,
+        Self::TotalMass,
+    ];
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::Tick => "tick",
+            Self::SimTime => "sim_time",
+            Self::KineticEnergy => "kinetic_energy",
+            Self::PotentialEnergy => "potential_energy",
+            Self::TotalEnergy => "total_energy",
+            Self::MomentumX => "momentum_x",
+            Self::MomentumY => "momentum_y",
+            Self::AngularMomentum => "angular_momentum",
+            Self::CenterOfMassX => "center_of_mass_x",
+            Self::CenterOfMassY => "center_of_mass_y",
+            Self::TotalMass => "total_mass",
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiagnosticsRow {
+    pub tick: u64,
+    pub sim_time: f64,
+    pub diagnostics: Diagnostics,
+}
+
+impl DiagnosticsRow {
+    pub fn capture(engine: &SimulationEngine) -> Self {
+        Self {
+            tick: engine.tick(),
+            sim_time: engine.sim_time(),
+            diagnostics: engine.diagnostics(),
+        }
+    }
+}
+
+pub fn write_trajectory_csv(
+    history: &History,
+    columns: &[TrajectoryColumn],
+    writer: impl Write,
+) -> Result<()> {
+    trajectory_table(history, columns)?.write_csv(writer)
+}
+
+pub fn write_diagnostics_csv(
+    rows: &[DiagnosticsRow],
+    columns: &[DiagnosticsColumn],
+    writer: impl Write,
+) -> Result<()> {
+    diagnostics_table(rows, columns)?.write_csv(writer)
+}
+
+#[cfg(feature = "parquet")]
+pub fn write_trajectory_parquet(
+    history: &History,
+    columns: &[TrajectoryColumn],
+    writer: impl Write + Send,
+) -> Result<()> {
+    trajectory_table(history, columns)?.write_parquet("trajectory", writer)
+}
+
+#[cfg(feature = "parquet")]
+pub fn write_diagnostics_parquet(
+    rows: &[DiagnosticsRow],
+    columns: &[DiagnosticsColumn],
+    writer: impl Write + Send,
+) -> Result<()> {
+    diagnostics_table(rows, columns)?.write_parquet("diagnostics", writer)
+}
+
+enum ColumnData {
+    Int(Vec<i64>),
+    Float(Vec<f64>),
+    Text(Vec<String>),
+}
+
+/// Columnar staging shared by both formats.
+struct Table {
+    columns: Vec<(&'static str, ColumnData)>,
+    rows: usize,
+}
+
+fn trajectory_table(history: &History, columns: &[TrajectoryColumn]) -> Result<Table> {
+    check_columns(columns.len())?;
+    let samples = history
+        .frames()
+        .flat_map(|frame| frame.bodies.iter().map(move |body| (frame, body)))
+        .collect::<Vec<_>>();
+    let columns = columns
+        .iter()
+        .map(|&column| {
+            let floats = |value: fn(&HistoryFrame, &BodySample) -> f64| {
+                ColumnData::Float(
+                    samples
+                        .iter()
+                        .map(|(frame, body)| value(frame, body))
+                        .collect(),
+                )
+            };
+            let data = match column {
+                TrajectoryColumn::Tick => {
+                    ColumnData::Int(samples.iter().map(|(frame, _)| frame.tick as i64).collect())
+                }
+                TrajectoryColumn::Id => {
+                    ColumnData::Text(samples.iter().map(|(_, body)| body.id.clone()).collect())
+                }
+                TrajectoryColumn::SimTime => floats(|frame, _| frame.sim_time),
+                TrajectoryColumn::Mass => floats(|_, body| body.mass),
+                TrajectoryColumn::Radius => floats(|_, body| body.radius),
+                TrajectoryColumn::X => floats(|_, body| body.position.x),
+                TrajectoryColumn::Y => floats(|_, body| body.position.y),
+                TrajectoryColumn::Vx => floats(|_, body| body.velocity.x),
+                TrajectoryColumn::Vy => floats(|_, body| body.velocity.y),
+            };
+            (column.name(), data)
+        })
+        .collect();
+    Ok(Table {
+        columns,
+        rows: samples.len(),
+    })
+}
+
+fn diagnostics_table(rows: &[DiagnosticsRow], columns: &[DiagnosticsColumn]) -> Result<Table> {
+    check_columns(columns.len())?;
+    let columns = columns
+        .iter()
+        .map(|&column| {
+            let floats = |value: fn(&Diagnostics) -> f64| {
+                ColumnData::Float(rows.iter().map(|row| value(&row.diagnostics)).collect())
+            };
+            let data = match column {
+                DiagnosticsColumn::Tick => {
+                    ColumnData::Int(rows.iter().map(|row| row.tick as i64).collect())
+                }
+                DiagnosticsColumn::SimTime => {
+                    ColumnData::Float(rows.iter().map(|row| row.sim_time).collect())
+                }
+                DiagnosticsColumn::KineticEnergy => floats(|d| d.kinetic_energy),
+                DiagnosticsColumn::PotentialEnergy => floats(|d| d.potential_energy),
+                DiagnosticsColumn::TotalEnergy => floats(|d| d.total_energy),
+                DiagnosticsColumn::MomentumX => floats(|d| d.linear_momentum.x),
+                DiagnosticsColumn::MomentumY => floats(|d| d.linear_momentum.y),
+                DiagnosticsColumn::AngularMomentum => floats(|d| d.angular_momentum),
+                DiagnosticsColumn::CenterOfMassX => floats(|d| d.center_of_mass.x),
+                DiagnosticsColumn::CenterOfMassY => floats(|d| d.center_of_mass.y),
+                DiagnosticsColumn::TotalMass => floats(|d| d.total_mass),
+            };
+            (column.name(), data)
+        })
+        .collect();
+    Ok(Table {
+        columns,
+        rows: rows.len(),
+    })
+}
+
+fn check_columns(count: usize) -> Result<()> {
+    if count == 0 {
+        return Err(EngineError::InvalidConfig(
+            "export needs at least one column".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+fn export_error(error: impl std::fmt::Display) -> EngineError {
+    EngineError::ExportFailed(error.to_string())
+}
+
+impl Table {
+    fn write_csv(&self, mut writer: impl Write) -> Result<()> {
+        let header = self
+            .columns
+            .iter()
+            .map(|(name, _)| *name)
+            .collect::<Vec<_>>()
+            .join(",");
+        writeln!(writer, "{header}").map_err(export_error)?;
+        let mut line = String::new();
+        for row in 0..self.rows {
+            line.clear();
+            for (index, (_, data)) in self.columns.iter().enumerate() {
+                if index > 0 {
+                    line.push(',');
+                }
+                match data {
+                    ColumnData::Int(values) => line.push_str(&values[row].to_string()),
+                    // `{:?}` keeps the shortest round-tripping representation.
+                    ColumnData::Float(values) => line.push_str(&format!("{:?}", values[row])),
+                    ColumnData::Text(values) => push_csv_field(&mut line, &values[row]),
+                }
+            }
+            writeln!(writer, "{line}").map_err(export_error)?;
+        }
+        writer.flush().map_err(export_error)
+    }
+
+    #[cfg(feature = "parquet")]
+    fn write_parquet(&self, message: &str, writer: impl Write + Send) -> Result<()> {
+        use std::sync::Arc;
+
+        use parquet::data_type::{ByteArray, ByteArrayType, DoubleType, Int64Type};
+        use parquet::file::properties::WriterProperties;
+        use parquet::file::writer::SerializedFileWriter;
+        use parquet::schema::parser::parse_message_type;
+
+        let fields = self
+            .columns
+            .iter()
+            .map(|(name, data)| match data {
+                ColumnData::Int(_) => format!("REQUIRED INT64 {name};"),
+                ColumnData::Float(_) => format!("REQUIRED DOUBLE {name};"),
+                ColumnData::Text(_) => format!("REQUIRED BYTE_ARRAY {name} (UTF8);"),
+            })
+            .collect::<Vec<_>>()
+            .join(" ");
+        let schema = parse_message_type(&format!("message {message} {{ {fields} }}"))
+            .map_err(export_error)?;
+        let mut file = SerializedFileWriter::new(
+            writer,
+            Arc::new(schema),
+            Arc::new(WriterProperties::builder().build()),
+        )
+        .map_err(export_error)?;
+        let mut row_group = file.next_row_group().map_err(export_error)?;
+        for (_, data) in &self.columns {
+            let mut column = row_group
+                .next_column()
+                .map_err(export_error)?
+                .ok_or_else(|| export_error("parquet schema has fewer columns than the table"))?;
+            match data {
+                ColumnData::Int(values) => column
+                    .typed::<Int64Type>()
+                    .write_batch(values, None, None)
+                    .map(drop),
+                ColumnData::Float(values) => column
+                    .typed::<DoubleType>()
+                    .write_batch(values, None, None)
+                    .map(drop),
+                ColumnData::Text(values) => {
+                    let values = values
+                        .iter()
+                        .map(|value| ByteArray::from(value.as_str()))
+                        .collect::<Vec<_>>();
+                    column
+                        .typed::<ByteArrayType>()
+                        .write_batch(&values, None, None)
+                        .map(drop)
+                }
+            }
+            .map_err(export_error)?;
+            column.close().map_err(export_error)?;
+        }
+        row_group.close().map_err(export_error)?;
+        file.close().map_err(export_error)?;
+        Ok(())
+    }
+}
+
+fn push_csv_field(line: &mut String, value: &str) {
+    if value.contains([',', '"', '\n', '\r']) {
+        line.push('"');
+        line.push_str(&value.replace('"', "\"\""));
+        line.push('"');
+    } else {
+        line.push_str(value);
+    }
+}
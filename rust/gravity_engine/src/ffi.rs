@@ -1,114 +1,976 @@
 use std::collections::HashMap;
+use std::collections::hash_map::Entry;
 use std::ffi::{CStr, CString};
-use std::os::raw::c_char;
-use std::sync::Mutex;
+use std::ops::ControlFlow;
+use std::os::raw::{c_char, c_void};
 use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
 use once_cell::sync::Lazy;
 use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
 use serde_json::{Value, json};
 
-use crate::config::EngineConfig;
+use crate::alignment::AlignmentWatch;
+use crate::analysis::CollisionRateQuery;
+use crate::catalog::{CatalogQuery, ScenarioCatalog};
+use crate::config::{ConfigPatch, EngineConfig, ForceErrorSampling};
+use crate::diagnostics::MassHistogramOptions;
 use crate::engine::SimulationEngine;
-use crate::types::{Body, BodyEdit, Scenario, Snapshot};
+use crate::errors::{EngineError, ErrorCode};
+use crate::events::{EventFilter, EventOverflow, SimulationEvent};
+use crate::forces::softening_radius;
+use crate::grid::GridSpec;
+use crate::journal::Journal;
+use crate::lyapunov::LyapunovQuery;
+use crate::maneuvers::Maneuver;
+use crate::netcode::{ReplayCheckpoint, verify_replay};
+use crate::poincare::PoincareSection;
+use crate::query::BodyQuery;
+use crate::random::{CloudSpec, Xoshiro256, generate_cloud};
+use crate::runner::{EngineRunner, RunnerCommand, RunnerOutput};
+use crate::search::{StableSearch, search_stable};
+use crate::stopping::StopCondition;
+use crate::types::{Body, BodyEdit, DtSchedule, Scenario, Snapshot};
+use crate::zones::{Region, Zone};
 
-static ENGINES: Lazy<Mutex<HashMap<u64, SimulationEngine>>> =
-    Lazy::new(|| Mutex::new(HashMap::new()));
+/// A failed call: the message hosts show and the code they branch on.
+#[derive(Debug)]
+struct FfiError {
+    code: ErrorCode,
+    message: String,
+}
+
+/// Engine errors keep their variant's code.
+impl From<EngineError> for FfiError {
+    fn from(error: EngineError) -> Self {
+        Self {
+            code: error.code(),
+            message: error.to_string(),
+        }
+    }
+}
+
+/// Bare messages come from argument checks in this module.
+impl From<String> for FfiError {
+    fn from(message: String) -> Self {
+        Self {
+            code: ErrorCode::InvalidArgument,
+            message,
+        }
+    }
+}
+
+type FfiResult<T = Value> = std::result::Result<T, FfiError>;
+
+/// One engine behind its own lock; `None` once `gs_step_async` has moved it onto a
+/// worker.
+type EngineSlot = Arc<Mutex<Option<SimulationEngine>>>;
+
+/// Live engines by handle. The registry lock is only held to look a slot up, never
+/// while an engine runs, so separate handles step concurrently.
+static ENGINES: Lazy<Mutex<HashMap<u64, EngineSlot>>> = Lazy::new(|| Mutex::new(HashMap::new()));
 static NEXT_HANDLE: AtomicU64 = AtomicU64::new(1);
+/// Per-handle interest filters set with `gs_set_event_filter`. Lock after the engine.
+static EVENT_FILTERS: Lazy<Mutex<HashMap<u64, EventFilter>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+/// Engines moved onto a worker by `gs_step_async`, returned to `ENGINES` once
+/// `gs_poll_result` has drained every job. Lock after `ENGINES` and the engine.
+static RUNNERS: Lazy<Mutex<HashMap<u64, EngineRunner>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+/// Per-handle response modes set with `gs_set_response_mode`. Lock after the engine.
+static RESPONSE_MODES: Lazy<Mutex<HashMap<u64, ResponseMode>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+static CATALOG: Lazy<Mutex<ScenarioCatalog>> = Lazy::new(|| Mutex::new(ScenarioCatalog::default()));
+
+#[unsafe(no_mangle)]
+pub extern "C" fn gs_initialize(
+    config_json: *const c_char,
+    bodies_json: *const c_char,
+) -> *mut c_char {
+    let result = (|| {
+        let config: EngineConfig = parse_json_arg(config_json, "config")?;
+        let bodies: Vec<Body> = parse_json_arg(bodies_json, "bodies")?;
+
+        let engine = SimulationEngine::with_bodies(config, bodies)?;
+
+        let handle = NEXT_HANDLE.fetch_add(1, Ordering::Relaxed);
+        let state = engine.get_state();
+
+        let mut engines = ENGINES.lock().map_err(|_| poisoned("engine registry"))?;
+        engines.insert(handle, Arc::new(Mutex::new(Some(engine))));
+
+        Ok(json!({
+            "handle": handle,
+            "state": state,
+        }))
+    })();
+
+    response_to_ptr(result)
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn gs_dispose(handle: u64) -> *mut c_char {
+    let result = (|| {
+        let mut engines = ENGINES.lock().map_err(|_| poisoned("engine registry"))?;
+        let mut removed = engines.remove(&handle).is_some();
+        if let Ok(mut runners) = RUNNERS.lock() {
+            removed |= runners.remove(&handle).is_some();
+        }
+        if let Ok(mut filters) = EVENT_FILTERS.lock() {
+            filters.remove(&handle);
+        }
+        if let Ok(mut modes) = RESPONSE_MODES.lock() {
+            modes.remove(&handle);
+        }
+        Ok(json!({ "removed": removed }))
+    })();
+
+    response_to_ptr(result)
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn gs_set_config(handle: u64, config_json: *const c_char) -> *mut c_char {
+    let result = with_engine_mut(handle, |engine| {
+        let config: EngineConfig = parse_json_arg(config_json, "config")?;
+        engine.set_config(config)?;
+        Ok(json!({ "state": state_echo(handle, engine)? }))
+    });
+
+    response_to_ptr(result)
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn gs_update_config(handle: u64, patch_json: *const c_char) -> *mut c_char {
+    let result = with_engine_mut(handle, |engine| {
+        let patch: ConfigPatch = parse_json_arg(patch_json, "config patch")?;
+        let diff = engine.update_config(&patch)?;
+        Ok(json!({ "diff": diff, "state": state_echo(handle, engine)? }))
+    });
+
+    response_to_ptr(result)
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn gs_apply_edit(handle: u64, edit_json: *const c_char) -> *mut c_char {
+    let result = with_engine_mut(handle, |engine| {
+        let edit: BodyEdit = parse_json_arg(edit_json, "edit")?;
+        engine.apply_edit(edit)?;
+        Ok(json!({ "state": state_echo(handle, engine)? }))
+    });
+
+    response_to_ptr(result)
+}
+
+/// Applies an array of edits atomically, returning the state once rather than per edit.
+#[unsafe(no_mangle)]
+pub extern "C" fn gs_apply_edits(handle: u64, edits_json: *const c_char) -> *mut c_char {
+    let result = with_engine_mut(handle, |engine| {
+        let edits: Vec<BodyEdit> = parse_json_arg(edits_json, "edits")?;
+        engine.apply_edits(edits)?;
+        Ok(json!({ "state": state_echo(handle, engine)? }))
+    });
+
+    response_to_ptr(result)
+}
+
+/// `SimulationEngine::predict`: the engine behind `handle` is left untouched.
+#[unsafe(no_mangle)]
+pub extern "C" fn gs_predict(
+    handle: u64,
+    edits_json: *const c_char,
+    ticks: u32,
+    sample_every: u32,
+) -> *mut c_char {
+    let result = with_engine(handle, |engine| {
+        let edits: Vec<BodyEdit> = parse_json_arg(edits_json, "edits")?;
+        let samples = engine.predict(edits, ticks, sample_every)?;
+        Ok(json!({ "samples": samples }))
+    });
+
+    response_to_ptr(result)
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn gs_step(handle: u64, ticks: u32) -> *mut c_char {
+    let result = with_engine_mut(handle, |engine| {
+        let mut summary = engine.step(ticks)?;
+        filter_events(handle, engine.bodies(), &mut summary.events)?;
+        let collisions = summary
+            .events
+            .iter()
+            .filter_map(|event| match event {
+                SimulationEvent::Collision(collision) => Some(collision),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        Ok(json!({
+            "summary": summary,
+            "collisions": collisions,
+            "state": state_echo(handle, engine)?,
+        }))
+    });
+
+    response_to_ptr(result)
+}
+
+/// Runs as many ticks as fit in `max_micros` of wall time, capped at `max_ticks`; see
+/// `SimulationEngine::step_budget`.
+#[unsafe(no_mangle)]
+pub extern "C" fn gs_step_budget(handle: u64, max_micros: u64, max_ticks: u32) -> *mut c_char {
+    let result = with_engine_mut(handle, |engine| {
+        let mut summary = engine.step_budget(Duration::from_micros(max_micros), max_ticks)?;
+        filter_events(handle, engine.bodies(), &mut summary.events)?;
+        Ok(json!({
+            "summary": summary,
+            "state": state_echo(handle, engine)?,
+        }))
+    });
+
+    response_to_ptr(result)
+}
+
+/// Queues `ticks` on a background worker and returns at once with the job id. The
+/// engine stays on the worker, and other `gs_*` calls on the handle fail with `Busy`,
+/// until `gs_poll_result` has returned every queued job.
+#[unsafe(no_mangle)]
+pub extern "C" fn gs_step_async(handle: u64, ticks: u32) -> *mut c_char {
+    let result = (|| {
+        let mut engines = ENGINES.lock().map_err(|_| poisoned("engine registry"))?;
+        let mut runners = RUNNERS.lock().map_err(|_| poisoned("runner registry"))?;
+        let runner = match runners.entry(handle) {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => {
+                let slot = engines.remove(&handle).ok_or_else(|| FfiError {
+                    code: ErrorCode::EngineNotFound,
+                    message: format!("engine handle not found: {handle}"),
+                })?;
+                // Later lookups miss the registry; calls already holding the slot
+                // finish first and whoever locks it next finds it empty.
+                drop(engines);
+                let engine = slot
+                    .lock()
+                    .map_err(|_| poisoned("engine"))?
+                    .take()
+                    .ok_or_else(|| missing_engine(handle))?;
+                entry.insert(EngineRunner::spawn(engine))
+            }
+        };
+        let job = runner.submit(RunnerCommand::Step { ticks })?;
+        Ok(json!({ "job": job, "pending": runner.pending() }))
+    })();
+
+    response_to_ptr(result)
+}
+
+/// Next finished `gs_step_async` job, shaped like a `gs_step` response plus `job`
+/// and `pending`, or `{"job": null}` while it is still running.
+#[unsafe(no_mangle)]
+pub extern "C" fn gs_poll_result(handle: u64) -> *mut c_char {
+    let result = (|| {
+        let mut engines = ENGINES.lock().map_err(|_| poisoned("engine registry"))?;
+        let mut runners = RUNNERS.lock().map_err(|_| poisoned("runner registry"))?;
+        let runner = runners
+            .get(&handle)
+            .ok_or_else(|| format!("no background work for engine handle: {handle}"))?;
+        let Some(reply) = runner.try_recv() else {
+            return Ok(json!({ "job": null, "pending": runner.pending() }));
+        };
+        let pending = runner.pending();
+        if pending == 0 {
+            let runner = runners.remove(&handle).expect("runner was found above");
+            let engine = runner.shutdown()?;
+            engines.insert(handle, Arc::new(Mutex::new(Some(engine))));
+        }
+        drop(runners);
+        drop(engines);
+
+        let job = reply.job;
+        match reply.result.map_err(|error| FfiError {
+            code: error.code(),
+            message: format!("job {job} failed: {error}"),
+        })? {
+            RunnerOutput::Stepped { mut summary, state } => {
+                filter_events(handle, &state.bodies, &mut summary.events)?;
+                Ok(json!({
+                    "job": job,
+                    "pending": pending,
+                    "summary": summary,
+                    "state": state,
+                }))
+            }
+            other => Ok(json!({ "job": job, "pending": pending, "output": other })),
+        }
+    })();
+
+    response_to_ptr(result)
+}
+
+/// Called by `gs_step_observed` after each tick with the tick, a flat
+/// `[x0, y0, x1, y1, ...]` position array valid only for the duration of the call, the
+/// body count and the host's `user_data`. Returning non-zero stops the batch.
+pub type TickCallback = extern "C" fn(u64, *const f64, usize, *mut c_void) -> i32;
+
+/// `gs_step` that reports every `every`-th tick (and the last one) to `callback`
+/// without returning to the host. The engine stays locked during the callback, so it
+/// must not call back into `gs_*` functions on the same handle.
+#[unsafe(no_mangle)]
+pub extern "C" fn gs_step_observed(
+    handle: u64,
+    ticks: u32,
+    every: u32,
+    callback: Option<TickCallback>,
+    user_data: *mut c_void,
+) -> *mut c_char {
+    let result = with_engine_mut(handle, |engine| {
+        let callback = callback.ok_or_else(|| "received null callback".to_string())?;
+        let every = u64::from(every.max(1));
+        let start = engine.tick();
+        let end = start + u64::from(ticks);
+        let mut positions = Vec::new();
+        let mut summary = engine.step_with_observer(ticks, |tick, bodies, _| {
+            if !(tick - start).is_multiple_of(every) && tick != end {
+                return ControlFlow::Continue(());
+            }
+            positions.clear();
+            positions.extend(
+                bodies
+                    .iter()
+                    .flat_map(|body| [body.position.x, body.position.y]),
+            );
+            if callback(tick, positions.as_ptr(), bodies.len(), user_data) == 0 {
+                ControlFlow::Continue(())
+            } else {
+                ControlFlow::Break(())
+            }
+        })?;
+        filter_events(handle, engine.bodies(), &mut summary.events)?;
+        Ok(json!({
+            "summary": summary,
+            "state": state_echo(handle, engine)?,
+        }))
+    });
+
+    response_to_ptr(result)
+}
+
+/// Render-loop variant of `gs_step`: flat `[x0, y0, x1, y1, ...]` position and velocity
+/// arrays plus alive flags, in the engine's body order, without ids or config.
+#[unsafe(no_mangle)]
+pub extern "C" fn gs_step_delta(handle: u64, ticks: u32) -> *mut c_char {
+    let result = with_engine_mut(handle, |engine| {
+        engine.step(ticks)?;
+        let bodies = engine.bodies();
+        let mut positions = Vec::with_capacity(bodies.len() * 2);
+        let mut velocities = Vec::with_capacity(bodies.len() * 2);
+        for body in bodies {
+            positions.extend([body.position.x, body.position.y]);
+            velocities.extend([body.velocity.x, body.velocity.y]);
+        }
+        let alive = bodies.iter().map(|body| body.alive).collect::<Vec<_>>();
+        Ok(json!({
+            "tick": engine.tick(),
+            "simTime": engine.sim_time(),
+            "positions": positions,
+            "velocities": velocities,
+            "alive": alive,
+        }))
+    });
+
+    response_to_ptr(result)
+}
+
+/// Number of bodies (alive or not) in engine order, or -1 for an unknown handle.
+#[unsafe(no_mangle)]
+pub extern "C" fn gs_body_count(handle: u64) -> i64 {
+    with_engine(handle, |engine| Ok(json!(engine.bodies().len())))
+        .ok()
+        .and_then(|count| count.as_i64())
+        .unwrap_or(-1)
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn gs_merge_history(handle: u64) -> *mut c_char {
+    let result = with_engine(handle, |engine| {
+        Ok(json!({ "mergeHistory": engine.merge_history() }))
+    });
+    response_to_ptr(result)
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn gs_body_index_map(handle: u64) -> *mut c_char {
+    let result = with_engine(handle, |engine| {
+        Ok(json!({ "bodyIndexMap": engine.body_index_map() }))
+    });
+    response_to_ptr(result)
+}
+
+/// Writes `[x0, y0, x1, y1, ...]` for every body, in engine order, into a caller-owned
+/// buffer of `capacity` f64 values and returns the body count. Returns -1 for an
+/// unknown handle or null buffer and -2 if `capacity` is below twice the body count.
+#[unsafe(no_mangle)]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn gs_get_positions(handle: u64, out_ptr: *mut f64, capacity: usize) -> i64 {
+    if out_ptr.is_null() {
+        return -1;
+    }
+    let Ok(slot) = engine_slot(handle) else {
+        return -1;
+    };
+    let Ok(engine) = slot.lock() else {
+        return -1;
+    };
+    let Some(engine) = engine.as_ref() else {
+        return -1;
+    };
+    let bodies = engine.bodies();
+    if capacity < bodies.len() * 2 {
+        return -2;
+    }
+
+    // SAFETY: caller guarantees `out_ptr` points to `capacity` writable f64 values.
+    let out = unsafe { std::slice::from_raw_parts_mut(out_ptr, capacity) };
+    for (pair, body) in out.chunks_exact_mut(2).zip(bodies) {
+        pair[0] = body.position.x;
+        pair[1] = body.position.y;
+    }
+    bodies.len() as i64
+}
+
+/// Samples `SimulationEngine::sample_field` over `grid_json` and writes
+/// `[phi0, ax0, ay0, phi1, ...]` per cell, in `GridSpec` order, into a caller-owned
+/// buffer of `capacity` f64 values. Returns the cell count, -1 for an unknown handle,
+/// null buffer or invalid grid, and -2 if `capacity` is below three per cell.
+#[unsafe(no_mangle)]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn gs_sample_field(
+    handle: u64,
+    grid_json: *const c_char,
+    out_ptr: *mut f64,
+    capacity: usize,
+) -> i64 {
+    if out_ptr.is_null() {
+        return -1;
+    }
+    let Ok(spec) = parse_json_arg::<GridSpec>(grid_json, "grid") else {
+        return -1;
+    };
+    if spec.validate().is_err() {
+        return -1;
+    }
+    if capacity < spec.cell_count() * 3 {
+        return -2;
+    }
+    let Ok(slot) = engine_slot(handle) else {
+        return -1;
+    };
+    let Ok(engine) = slot.lock() else {
+        return -1;
+    };
+    let Some(engine) = engine.as_ref() else {
+        return -1;
+    };
+    let Ok(field) = engine.sample_field(spec) else {
+        return -1;
+    };
+
+    // SAFETY: caller guarantees `out_ptr` points to `capacity` writable f64 values.
+    let out = unsafe { std::slice::from_raw_parts_mut(out_ptr, capacity) };
+    for (cell, (potential, acceleration)) in out
+        .chunks_exact_mut(3)
+        .zip(field.potential.iter().zip(&field.acceleration))
+    {
+        cell[0] = *potential;
+        cell[1] = acceleration.x;
+        cell[2] = acceleration.y;
+    }
+    field.potential.len() as i64
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn gs_step_back(handle: u64, ticks: u32) -> *mut c_char {
+    let result = with_engine_mut(handle, |engine| {
+        let method = engine.step_back(ticks)?;
+        Ok(json!({
+            "method": method,
+            "tick": engine.tick(),
+            "simTime": engine.sim_time(),
+        }))
+    });
+
+    response_to_ptr(result)
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn gs_run_until(
+    handle: u64,
+    condition_json: *const c_char,
+    max_ticks: u32,
+) -> *mut c_char {
+    let result = with_engine_mut(handle, |engine| {
+        let condition: StopCondition = parse_json_arg(condition_json, "condition")?;
+        let mut outcome = engine.run_until(&condition, max_ticks)?;
+        filter_events(handle, engine.bodies(), &mut outcome.summary.events)?;
+        Ok(json!({
+            "summary": outcome.summary,
+            "stopReason": outcome.stop_reason,
+            "state": state_echo(handle, engine)?,
+        }))
+    });
+
+    response_to_ptr(result)
+}
+
+/// How much a mutating call echoes back.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+enum ResponseMode {
+    /// Every mutating call returns the whole `state`.
+    #[default]
+    Full,
+    /// `state` is `null`; hosts fetch it with `gs_get_state` when they need it.
+    SummaryOnly,
+}
+
+/// Sets whether mutating calls on this handle echo the full state (`"full"`, the
+/// default) or only their acknowledgement and summary (`"summaryOnly"`).
+#[unsafe(no_mangle)]
+pub extern "C" fn gs_set_response_mode(handle: u64, mode_json: *const c_char) -> *mut c_char {
+    let result = with_engine(handle, |_| {
+        let mode: ResponseMode = parse_json_arg(mode_json, "response mode")?;
+        let mut modes = RESPONSE_MODES
+            .lock()
+            .map_err(|_| poisoned("response mode"))?;
+        match mode {
+            ResponseMode::Full => modes.remove(&handle),
+            ResponseMode::SummaryOnly => modes.insert(handle, mode),
+        };
+        Ok(json!({ "responseMode": mode }))
+    });
+
+    response_to_ptr(result)
+}
+
+/// Sets the events `gs_step` and `gs_run_until` ship for this handle; JSON `null`
+/// clears the filter. The engine's own event log is unaffected.
+#[unsafe(no_mangle)]
+pub extern "C" fn gs_set_event_filter(handle: u64, filter_json: *const c_char) -> *mut c_char {
+    let result = with_engine(handle, |_| {
+        let filter: Option<EventFilter> = parse_json_arg(filter_json, "filter")?;
+        if let Some(filter) = &filter {
+            filter.validate()?;
+        }
+        let mut filters = EVENT_FILTERS.lock().map_err(|_| poisoned("event filter"))?;
+        match filter.clone() {
+            Some(filter) => filters.insert(handle, filter),
+            None => filters.remove(&handle),
+        };
+        Ok(json!({ "filter": filter }))
+    });
+
+    response_to_ptr(result)
+}
+
+#[cfg(feature = "schema")]
+#[unsafe(no_mangle)]
+pub extern "C" fn gs_json_schema(name_json: *const c_char) -> *mut c_char {
+    let result = (|| {
+        let name: String = parse_json_arg(name_json, "name")?;
+        let schema = crate::schema::json_schema(&name)?;
+        Ok(json!({ "schema": schema }))
+    })();
+
+    response_to_ptr(result)
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn gs_generate_cloud(seed: u64, spec_json: *const c_char) -> *mut c_char {
+    let result = (|| {
+        let spec: CloudSpec = parse_json_arg(spec_json, "spec")?;
+        let bodies = generate_cloud(&spec, &mut Xoshiro256::new(seed))?;
+        Ok(json!({ "bodies": bodies }))
+    })();
+
+    response_to_ptr(result)
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn gs_search_stable(
+    config_json: *const c_char,
+    search_json: *const c_char,
+) -> *mut c_char {
+    let result = (|| {
+        let config: EngineConfig = parse_json_arg(config_json, "config")?;
+        let search: StableSearch = parse_json_arg(search_json, "search")?;
+        let candidates = search_stable(&config, &search)?;
+        Ok(json!({ "candidates": candidates }))
+    })();
+
+    response_to_ptr(result)
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn gs_verify_replay(
+    scenario_json: *const c_char,
+    checkpoints_json: *const c_char,
+) -> *mut c_char {
+    let result = (|| {
+        let scenario: Scenario = parse_json_arg(scenario_json, "scenario")?;
+        let checkpoints: Vec<ReplayCheckpoint> = parse_json_arg(checkpoints_json, "checkpoints")?;
+        let verification = verify_replay(scenario, &checkpoints)?;
+        Ok(json!({ "passed": verification.passed(), "verification": verification }))
+    })();
+
+    response_to_ptr(result)
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn gs_state_hash(handle: u64) -> *mut c_char {
+    let result = with_engine(handle, |engine| {
+        Ok(json!({ "tick": engine.tick(), "hash": engine.state_hash() }))
+    });
+    response_to_ptr(result)
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn gs_set_paused(handle: u64, paused: bool) -> *mut c_char {
+    let result = with_engine_mut(handle, |engine| {
+        engine.set_paused(paused);
+        Ok(json!({ "paused": engine.is_paused() }))
+    });
+    response_to_ptr(result)
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn gs_export_journal(handle: u64) -> *mut c_char {
+    let result = with_engine(handle, |engine| {
+        Ok(json!({ "journal": engine.export_journal() }))
+    });
+    response_to_ptr(result)
+}
+
+/// Replays into a new engine and returns its handle, like `gs_initialize`.
+#[unsafe(no_mangle)]
+pub extern "C" fn gs_replay_journal(journal_json: *const c_char) -> *mut c_char {
+    let result = (|| {
+        let journal: Journal = parse_json_arg(journal_json, "journal")?;
+        let engine = SimulationEngine::replay_journal(&journal)?;
+
+        let handle = NEXT_HANDLE.fetch_add(1, Ordering::Relaxed);
+        let state = engine.get_state();
+
+        let mut engines = ENGINES.lock().map_err(|_| poisoned("engine registry"))?;
+        engines.insert(handle, Arc::new(Mutex::new(Some(engine))));
+
+        Ok(json!({
+            "handle": handle,
+            "state": state,
+        }))
+    })();
+    response_to_ptr(result)
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn gs_get_state(handle: u64) -> *mut c_char {
+    let result = with_engine(handle, |engine| Ok(json!({ "state": engine.get_state() })));
+    response_to_ptr(result)
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn gs_query_bodies(handle: u64, query_json: *const c_char) -> *mut c_char {
+    let result = with_engine(handle, |engine| {
+        let query: BodyQuery = parse_json_arg(query_json, "query")?;
+        let matches = engine.query_bodies(&query)?;
+        Ok(json!({ "result": matches }))
+    });
+
+    response_to_ptr(result)
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn gs_state_in_region(handle: u64, region_json: *const c_char) -> *mut c_char {
+    let result = with_engine(handle, |engine| {
+        let region: Region = parse_json_arg(region_json, "region")?;
+        let state = engine.state_in_region(&region)?;
+        Ok(json!({ "state": state }))
+    });
+    response_to_ptr(result)
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn gs_diagnostics(handle: u64) -> *mut c_char {
+    let result = with_engine(handle, |engine| {
+        Ok(json!({ "diagnostics": engine.diagnostics() }))
+    });
+    response_to_ptr(result)
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn gs_jacobi_constants(
+    handle: u64,
+    primary_id_json: *const c_char,
+    secondary_id_json: *const c_char,
+) -> *mut c_char {
+    let result = with_engine(handle, |engine| {
+        let primary_id: String = parse_json_arg(primary_id_json, "primary id")?;
+        let secondary_id: String = parse_json_arg(secondary_id_json, "secondary id")?;
+        let samples = engine.jacobi_constants(&primary_id, &secondary_id)?;
+        Ok(json!({ "jacobi": samples }))
+    });
+
+    response_to_ptr(result)
+}
+
+/// Lagrange points of the pair, plus the effective potential over `grid_json` unless
+/// it is the JSON `null`.
+#[unsafe(no_mangle)]
+pub extern "C" fn gs_lagrange_map(
+    handle: u64,
+    primary_id_json: *const c_char,
+    secondary_id_json: *const c_char,
+    grid_json: *const c_char,
+) -> *mut c_char {
+    let result = with_engine(handle, |engine| {
+        let primary_id: String = parse_json_arg(primary_id_json, "primary id")?;
+        let secondary_id: String = parse_json_arg(secondary_id_json, "secondary id")?;
+        let grid: Option<GridSpec> = parse_json_arg(grid_json, "grid")?;
+        let map = engine.lagrange_map(&primary_id, &secondary_id, grid.as_ref())?;
+        Ok(json!({ "grid": grid, "lagrange": map }))
+    });
+
+    response_to_ptr(result)
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn gs_lyapunov_exponent(handle: u64, query_json: *const c_char) -> *mut c_char {
+    let result = with_engine(handle, |engine| {
+        let query: LyapunovQuery = parse_json_arg(query_json, "query")?;
+        Ok(json!({ "lyapunov": engine.lyapunov_exponent(&query)? }))
+    });
+
+    response_to_ptr(result)
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn gs_barnes_hut_error(handle: u64, sampling_json: *const c_char) -> *mut c_char {
+    let result = with_engine(handle, |engine| {
+        let sampling: ForceErrorSampling = parse_json_arg(sampling_json, "sampling")?;
+        let stats = engine.barnes_hut_error(&sampling)?;
+        Ok(json!({ "forceError": stats }))
+    });
+    response_to_ptr(result)
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn gs_estimate_tick_cost(handle: u64) -> *mut c_char {
+    let result = with_engine(handle, |engine| {
+        Ok(json!({ "estimate": engine.estimate_tick_cost() }))
+    });
+    response_to_ptr(result)
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn gs_mass_distribution(handle: u64, options_json: *const c_char) -> *mut c_char {
+    let result = with_engine(handle, |engine| {
+        let options: MassHistogramOptions = parse_json_arg(options_json, "options")?;
+        let distribution = engine.mass_distribution(&options)?;
+        Ok(json!({ "massDistribution": distribution }))
+    });
+    response_to_ptr(result)
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn gs_density_grid(handle: u64, grid_json: *const c_char) -> *mut c_char {
+    let result = with_engine(handle, |engine| {
+        let spec: GridSpec = parse_json_arg(grid_json, "grid")?;
+        let density = engine.density_grid(&spec)?;
+        Ok(json!({ "grid": spec, "density": density }))
+    });
+    response_to_ptr(result)
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn gs_kinematics_grid(handle: u64, grid_json: *const c_char) -> *mut c_char {
+    let result = with_engine(handle, |engine| {
+        let spec: GridSpec = parse_json_arg(grid_json, "grid")?;
+        let cells = engine.kinematics_grid(&spec)?;
+        Ok(json!({ "grid": spec, "cells": cells }))
+    });
+    response_to_ptr(result)
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn gs_group_diagnostics(handle: u64) -> *mut c_char {
+    let result = with_engine(handle, |engine| {
+        Ok(json!({ "groups": engine.group_diagnostics() }))
+    });
+    response_to_ptr(result)
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn gs_binaries(handle: u64) -> *mut c_char {
+    let result = with_engine(handle, |engine| {
+        Ok(json!({ "binaries": engine.binaries() }))
+    });
+    response_to_ptr(result)
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn gs_collision_rate(handle: u64, query_json: *const c_char) -> *mut c_char {
+    let result = with_engine(handle, |engine| {
+        let query: CollisionRateQuery = parse_json_arg(query_json, "query")?;
+        let estimate = engine.collision_rate(&query)?;
+        Ok(json!({ "estimate": estimate }))
+    });
+    response_to_ptr(result)
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn gs_body_derivatives(handle: u64, ids_json: *const c_char) -> *mut c_char {
+    let result = with_engine(handle, |engine| {
+        let ids: Vec<String> = parse_json_arg(ids_json, "ids")?;
+        let derivatives = engine.body_derivatives(&ids)?;
+        Ok(json!({ "derivatives": derivatives }))
+    });
+    response_to_ptr(result)
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn gs_sample_orbit(
+    handle: u64,
+    primary_id_json: *const c_char,
+    body_id_json: *const c_char,
+    n_points: u32,
+) -> *mut c_char {
+    let result = with_engine(handle, |engine| {
+        let primary_id: String = parse_json_arg(primary_id_json, "primary id")?;
+        let body_id: String = parse_json_arg(body_id_json, "body id")?;
+        let points = engine.sample_orbit(&primary_id, &body_id, n_points as usize)?;
+        Ok(json!({ "points": points }))
+    });
+
+    response_to_ptr(result)
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn gs_create_checkpoint(handle: u64, name_json: *const c_char) -> *mut c_char {
+    let result = with_engine_mut(handle, |engine| {
+        let name: String = parse_json_arg(name_json, "checkpoint name")?;
+        engine.create_checkpoint(&name)?;
+        Ok(json!({ "checkpoints": engine.list_checkpoints() }))
+    });
+
+    response_to_ptr(result)
+}
 
 #[unsafe(no_mangle)]
-pub extern "C" fn gs_initialize(
-    config_json: *const c_char,
-    bodies_json: *const c_char,
-) -> *mut c_char {
-    let result = (|| {
-        let config: EngineConfig = parse_json_arg(config_json, "config")?;
-        let bodies: Vec<Body> = parse_json_arg(bodies_json, "bodies")?;
+pub extern "C" fn gs_add_marker(handle: u64, name_json: *const c_char) -> *mut c_char {
+    let result = with_engine_mut(handle, |engine| {
+        let name: String = parse_json_arg(name_json, "marker name")?;
+        engine.add_marker(name)?;
+        Ok(json!({ "markers": engine.markers() }))
+    });
 
-        let engine =
-            SimulationEngine::with_bodies(config, bodies).map_err(|error| error.to_string())?;
+    response_to_ptr(result)
+}
 
-        let handle = NEXT_HANDLE.fetch_add(1, Ordering::Relaxed);
-        let state = engine.get_state();
+#[unsafe(no_mangle)]
+pub extern "C" fn gs_list_markers(handle: u64) -> *mut c_char {
+    let result = with_engine(handle, |engine| Ok(json!({ "markers": engine.markers() })));
 
-        let mut engines = ENGINES
-            .lock()
-            .map_err(|_| "engine registry lock poisoned".to_string())?;
-        engines.insert(handle, engine);
+    response_to_ptr(result)
+}
 
-        Ok(json!({
-            "handle": handle,
-            "state": state,
-        }))
-    })();
+#[unsafe(no_mangle)]
+pub extern "C" fn gs_last_instability(handle: u64) -> *mut c_char {
+    let result = with_engine(handle, |engine| {
+        Ok(json!({ "report": engine.last_instability() }))
+    });
 
     response_to_ptr(result)
 }
 
 #[unsafe(no_mangle)]
-pub extern "C" fn gs_dispose(handle: u64) -> *mut c_char {
-    let result = (|| {
-        let mut engines = ENGINES
-            .lock()
-            .map_err(|_| "engine registry lock poisoned".to_string())?;
-        let removed = engines.remove(&handle).is_some();
-        Ok(json!({ "removed": removed }))
-    })();
+pub extern "C" fn gs_list_checkpoints(handle: u64) -> *mut c_char {
+    let result = with_engine(handle, |engine| {
+        Ok(json!({ "checkpoints": engine.list_checkpoints() }))
+    });
 
     response_to_ptr(result)
 }
 
 #[unsafe(no_mangle)]
-pub extern "C" fn gs_set_config(handle: u64, config_json: *const c_char) -> *mut c_char {
+pub extern "C" fn gs_restore_checkpoint(handle: u64, name_json: *const c_char) -> *mut c_char {
     let result = with_engine_mut(handle, |engine| {
-        let config: EngineConfig = parse_json_arg(config_json, "config")?;
-        engine
-            .set_config(config)
-            .map_err(|error| error.to_string())?;
-        Ok(json!({ "state": engine.get_state() }))
+        let name: String = parse_json_arg(name_json, "checkpoint name")?;
+        engine.restore_checkpoint(&name)?;
+        Ok(json!({ "tick": engine.tick(), "simTime": engine.sim_time() }))
     });
 
     response_to_ptr(result)
 }
 
 #[unsafe(no_mangle)]
-pub extern "C" fn gs_apply_edit(handle: u64, edit_json: *const c_char) -> *mut c_char {
+pub extern "C" fn gs_delete_checkpoint(handle: u64, name_json: *const c_char) -> *mut c_char {
     let result = with_engine_mut(handle, |engine| {
-        let edit: BodyEdit = parse_json_arg(edit_json, "edit")?;
-        engine.apply_edit(edit).map_err(|error| error.to_string())?;
-        Ok(json!({ "state": engine.get_state() }))
+        let name: String = parse_json_arg(name_json, "checkpoint name")?;
+        Ok(json!({ "deleted": engine.delete_checkpoint(&name) }))
     });
 
     response_to_ptr(result)
 }
 
 #[unsafe(no_mangle)]
-pub extern "C" fn gs_step(handle: u64, ticks: u32) -> *mut c_char {
+pub extern "C" fn gs_configure_event_log(
+    handle: u64,
+    capacity: u32,
+    overflow_json: *const c_char,
+) -> *mut c_char {
     let result = with_engine_mut(handle, |engine| {
-        let summary = engine.step(ticks).map_err(|error| error.to_string())?;
-        Ok(json!({
-            "summary": summary,
-            "state": engine.get_state(),
-        }))
+        let overflow: EventOverflow = parse_json_arg(overflow_json, "overflow")?;
+        engine.set_event_log_capacity(capacity as usize);
+        engine.set_event_overflow(overflow);
+        Ok(json!({ "capacity": capacity.max(1), "overflow": overflow }))
     });
 
     response_to_ptr(result)
 }
 
 #[unsafe(no_mangle)]
-pub extern "C" fn gs_get_state(handle: u64) -> *mut c_char {
-    let result = with_engine(handle, |engine| Ok(json!({ "state": engine.get_state() })));
+pub extern "C" fn gs_set_checkpoint_capacity(handle: u64, capacity: u32) -> *mut c_char {
+    let result = with_engine_mut(handle, |engine| {
+        engine.set_checkpoint_capacity(capacity as usize);
+        Ok(json!({ "checkpoints": engine.list_checkpoints() }))
+    });
+
+    response_to_ptr(result)
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn gs_softening_radius(handle: u64, tolerance: f64) -> *mut c_char {
+    let result = with_engine(handle, |engine| {
+        let radius = softening_radius(engine.config(), tolerance)?;
+        Ok(json!({ "radius": radius }))
+    });
+
+    response_to_ptr(result)
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn gs_excursions(handle: u64) -> *mut c_char {
+    let result = with_engine(handle, |engine| {
+        Ok(json!({ "excursions": engine.excursion_summary() }))
+    });
     response_to_ptr(result)
 }
 
 #[unsafe(no_mangle)]
 pub extern "C" fn gs_load_scenario(handle: u64, scenario_json: *const c_char) -> *mut c_char {
     let result = with_engine_mut(handle, |engine| {
-        let scenario: Scenario = parse_json_arg(scenario_json, "scenario")?;
-        engine
-            .load_scenario(scenario)
-            .map_err(|error| error.to_string())?;
-        Ok(json!({ "state": engine.get_state() }))
+        let scenario: Value = parse_json_arg(scenario_json, "scenario")?;
+        let scenario = Scenario::from_json(scenario)?;
+        engine.load_scenario(scenario)?;
+        Ok(json!({ "state": state_echo(handle, engine)? }))
     });
 
     response_to_ptr(result)
@@ -134,65 +996,394 @@ pub extern "C" fn gs_snapshot(handle: u64) -> *mut c_char {
 pub extern "C" fn gs_restore_snapshot(handle: u64, snapshot_json: *const c_char) -> *mut c_char {
     let result = with_engine_mut(handle, |engine| {
         let snapshot: Snapshot = parse_json_arg(snapshot_json, "snapshot")?;
-        engine
-            .restore_snapshot(snapshot)
-            .map_err(|error| error.to_string())?;
-        Ok(json!({ "state": engine.get_state() }))
+        engine.restore_snapshot(snapshot)?;
+        Ok(json!({ "state": state_echo(handle, engine)? }))
+    });
+
+    response_to_ptr(result)
+}
+
+/// Writes an owned buffer to `out_ptr`/`out_len`; release it with `gs_bytes_free`.
+#[unsafe(no_mangle)]
+pub extern "C" fn gs_snapshot_binary(
+    handle: u64,
+    out_ptr: *mut *mut u8,
+    out_len: *mut usize,
+) -> *mut c_char {
+    let result = with_engine(handle, |engine| {
+        write_bytes_out(engine.snapshot_binary(), out_ptr, out_len)
+    });
+    response_to_ptr(result)
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn gs_restore_snapshot_binary(
+    handle: u64,
+    bytes_ptr: *const u8,
+    bytes_len: usize,
+) -> *mut c_char {
+    let result = with_engine_mut(handle, |engine| {
+        let bytes = bytes_arg(bytes_ptr, bytes_len)?;
+        engine.restore_snapshot_binary(bytes)?;
+        Ok(json!({ "state": state_echo(handle, engine)? }))
+    });
+
+    response_to_ptr(result)
+}
+
+/// Writes an owned buffer to `out_ptr`/`out_len`; release it with `gs_bytes_free`.
+#[unsafe(no_mangle)]
+pub extern "C" fn gs_save_scenario_binary(
+    handle: u64,
+    out_ptr: *mut *mut u8,
+    out_len: *mut usize,
+) -> *mut c_char {
+    let result = with_engine(handle, |engine| {
+        write_bytes_out(engine.save_scenario_binary(), out_ptr, out_len)
+    });
+    response_to_ptr(result)
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn gs_load_scenario_binary(
+    handle: u64,
+    bytes_ptr: *const u8,
+    bytes_len: usize,
+) -> *mut c_char {
+    let result = with_engine_mut(handle, |engine| {
+        let bytes = bytes_arg(bytes_ptr, bytes_len)?;
+        engine.load_scenario_binary(bytes)?;
+        Ok(json!({ "state": state_echo(handle, engine)? }))
+    });
+
+    response_to_ptr(result)
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn gs_dt_schedule(handle: u64) -> *mut c_char {
+    let result = with_engine(handle, |engine| {
+        Ok(json!({ "dtSchedule": engine.dt_schedule() }))
+    });
+    response_to_ptr(result)
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn gs_replay_dt_schedule(handle: u64, schedule_json: *const c_char) -> *mut c_char {
+    let result = with_engine_mut(handle, |engine| {
+        let schedule: DtSchedule = parse_json_arg(schedule_json, "schedule")?;
+        let queued = schedule.levels.len();
+        engine.replay_dt_schedule(schedule)?;
+        Ok(json!({ "queuedTicks": queued }))
+    });
+
+    response_to_ptr(result)
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn gs_add_zone(handle: u64, zone_json: *const c_char) -> *mut c_char {
+    let result = with_engine_mut(handle, |engine| {
+        let zone: Zone = parse_json_arg(zone_json, "zone")?;
+        engine.add_zone(zone)?;
+        Ok(json!({ "zones": engine.zones().collect::<Vec<_>>() }))
+    });
+
+    response_to_ptr(result)
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn gs_remove_zone(handle: u64, name_json: *const c_char) -> *mut c_char {
+    let result = with_engine_mut(handle, |engine| {
+        let name: String = parse_json_arg(name_json, "name")?;
+        Ok(json!({ "removed": engine.remove_zone(&name) }))
+    });
+
+    response_to_ptr(result)
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn gs_schedule_maneuver(
+    handle: u64,
+    body_id_json: *const c_char,
+    maneuver_json: *const c_char,
+) -> *mut c_char {
+    let result = with_engine_mut(handle, |engine| {
+        let body_id: String = parse_json_arg(body_id_json, "body_id")?;
+        let maneuver: Maneuver = parse_json_arg(maneuver_json, "maneuver")?;
+        let id = engine.schedule_maneuver(&body_id, maneuver)?;
+        Ok(json!({ "maneuverId": id, "maneuvers": engine.maneuvers() }))
     });
 
     response_to_ptr(result)
 }
 
 #[unsafe(no_mangle)]
+pub extern "C" fn gs_list_maneuvers(handle: u64) -> *mut c_char {
+    let result = with_engine(handle, |engine| {
+        Ok(json!({ "maneuvers": engine.maneuvers() }))
+    });
+
+    response_to_ptr(result)
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn gs_cancel_maneuver(handle: u64, maneuver_id: u64) -> *mut c_char {
+    let result = with_engine_mut(handle, |engine| {
+        Ok(json!({ "cancelled": engine.cancel_maneuver(maneuver_id) }))
+    });
+
+    response_to_ptr(result)
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn gs_add_poincare_section(handle: u64, section_json: *const c_char) -> *mut c_char {
+    let result = with_engine_mut(handle, |engine| {
+        let section: PoincareSection = parse_json_arg(section_json, "section")?;
+        engine.add_poincare_section(section)?;
+        Ok(json!({ "sections": engine.poincare_sections().collect::<Vec<_>>() }))
+    });
+
+    response_to_ptr(result)
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn gs_remove_poincare_section(handle: u64, name_json: *const c_char) -> *mut c_char {
+    let result = with_engine_mut(handle, |engine| {
+        let name: String = parse_json_arg(name_json, "name")?;
+        Ok(json!({ "removed": engine.remove_poincare_section(&name) }))
+    });
+
+    response_to_ptr(result)
+}
+
+/// Samples recorded so far by one section; `clear` empties it after reading.
+#[unsafe(no_mangle)]
+pub extern "C" fn gs_poincare_samples(
+    handle: u64,
+    name_json: *const c_char,
+    clear: bool,
+) -> *mut c_char {
+    let result = with_engine_mut(handle, |engine| {
+        let name: String = parse_json_arg(name_json, "name")?;
+        let samples = engine
+            .poincare_samples(&name)
+            .ok_or_else(|| EngineError::InvalidConfig(format!("unknown section '{name}'")))?
+            .cloned()
+            .collect::<Vec<_>>();
+        if clear {
+            engine.clear_poincare_samples(&name);
+        }
+        Ok(json!({ "samples": samples }))
+    });
+
+    response_to_ptr(result)
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn gs_watch_alignment(handle: u64, watch_json: *const c_char) -> *mut c_char {
+    let result = with_engine_mut(handle, |engine| {
+        let watch: AlignmentWatch = parse_json_arg(watch_json, "watch")?;
+        engine.watch_alignment(watch)?;
+        Ok(json!({ "watching": true }))
+    });
+
+    response_to_ptr(result)
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn gs_predict_alignment(
+    handle: u64,
+    watch_json: *const c_char,
+    max_ticks: u32,
+) -> *mut c_char {
+    let result = with_engine(handle, |engine| {
+        let watch: AlignmentWatch = parse_json_arg(watch_json, "watch")?;
+        let alignment = engine.predict_alignment(watch, max_ticks)?;
+        Ok(json!({ "alignment": alignment }))
+    });
+
+    response_to_ptr(result)
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn gs_catalog_add(scenario_json: *const c_char) -> *mut c_char {
+    let result = with_catalog(|catalog| {
+        let scenario: Scenario = parse_json_arg(scenario_json, "scenario")?;
+        let id = catalog.insert(scenario)?;
+        Ok(json!({ "id": id }))
+    });
+
+    response_to_ptr(result)
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn gs_catalog_remove(id: u64) -> *mut c_char {
+    let result = with_catalog(|catalog| Ok(json!({ "removed": catalog.remove(id).is_some() })));
+    response_to_ptr(result)
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn gs_catalog_get(id: u64) -> *mut c_char {
+    let result = with_catalog(|catalog| {
+        let scenario = catalog
+            .get(id)
+            .ok_or_else(|| format!("catalog entry not found: {id}"))?;
+        Ok(json!({ "scenario": scenario }))
+    });
+    response_to_ptr(result)
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn gs_catalog_search(query_json: *const c_char) -> *mut c_char {
+    let result = with_catalog(|catalog| {
+        let query: CatalogQuery = parse_json_arg(query_json, "query")?;
+        Ok(json!({ "page": catalog.search(&query) }))
+    });
+
+    response_to_ptr(result)
+}
+
+/// Counts of FFI strings and byte buffers handed out and freed. Needs the
+/// `ffi-audit` feature; the returned string is allocated after the counts are read.
+#[unsafe(no_mangle)]
+pub extern "C" fn gs_debug_alloc_stats() -> *mut c_char {
+    #[cfg(feature = "ffi-audit")]
+    let result = serde_json::to_value(crate::ffi_audit::stats()).map_err(|error| FfiError {
+        code: ErrorCode::Internal,
+        message: error.to_string(),
+    });
+    #[cfg(not(feature = "ffi-audit"))]
+    let result = Err(EngineError::UnsupportedFeature(
+        "allocation stats require the ffi-audit feature".to_string(),
+    )
+    .into());
+    response_to_ptr(result)
+}
+
+#[unsafe(no_mangle)]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
 pub extern "C" fn gs_string_free(ptr: *mut c_char) {
     if ptr.is_null() {
         return;
     }
 
+    #[cfg(feature = "ffi-audit")]
+    if !crate::ffi_audit::release_string(ptr.cast()) {
+        return;
+    }
+
     // SAFETY: `ptr` was allocated by `CString::into_raw` in this module.
     unsafe {
         let _ = CString::from_raw(ptr);
     }
 }
 
-fn with_engine<F>(handle: u64, action: F) -> std::result::Result<Value, String>
+#[unsafe(no_mangle)]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn gs_bytes_free(ptr: *mut u8, len: usize) {
+    if ptr.is_null() {
+        return;
+    }
+
+    #[cfg(feature = "ffi-audit")]
+    if !crate::ffi_audit::release_buffer(ptr, len) {
+        return;
+    }
+
+    // SAFETY: `ptr`/`len` describe a boxed slice leaked by `write_bytes_out`.
+    unsafe {
+        let _ = Box::from_raw(std::ptr::slice_from_raw_parts_mut(ptr, len));
+    }
+}
+
+fn filter_events(handle: u64, bodies: &[Body], events: &mut Vec<SimulationEvent>) -> FfiResult<()> {
+    let filters = EVENT_FILTERS.lock().map_err(|_| poisoned("event filter"))?;
+    if let Some(filter) = filters.get(&handle) {
+        events.retain(|event| filter.matches(event, bodies));
+    }
+    Ok(())
+}
+
+/// The state a mutating call returns under the handle's response mode.
+fn state_echo(handle: u64, engine: &SimulationEngine) -> FfiResult {
+    let modes = RESPONSE_MODES
+        .lock()
+        .map_err(|_| poisoned("response mode"))?;
+    Ok(match modes.get(&handle).copied().unwrap_or_default() {
+        ResponseMode::Full => serde_json::to_value(engine.get_state())
+            .map_err(|error| format!("failed to serialize state: {error}"))?,
+        ResponseMode::SummaryOnly => Value::Null,
+    })
+}
+
+fn with_engine<F>(handle: u64, action: F) -> FfiResult
 where
-    F: FnOnce(&SimulationEngine) -> std::result::Result<Value, String>,
+    F: FnOnce(&SimulationEngine) -> FfiResult,
 {
-    let engines = ENGINES
-        .lock()
-        .map_err(|_| "engine registry lock poisoned".to_string())?;
-    let engine = engines
-        .get(&handle)
-        .ok_or_else(|| format!("engine handle not found: {handle}"))?;
-    action(engine)
+    let slot = engine_slot(handle)?;
+    let engine = slot.lock().map_err(|_| poisoned("engine"))?;
+    action(engine.as_ref().ok_or_else(|| missing_engine(handle))?)
 }
 
-fn with_engine_mut<F>(handle: u64, action: F) -> std::result::Result<Value, String>
+fn with_engine_mut<F>(handle: u64, action: F) -> FfiResult
 where
-    F: FnOnce(&mut SimulationEngine) -> std::result::Result<Value, String>,
+    F: FnOnce(&mut SimulationEngine) -> FfiResult,
 {
-    let mut engines = ENGINES
+    let slot = engine_slot(handle)?;
+    let mut engine = slot.lock().map_err(|_| poisoned("engine"))?;
+    action(engine.as_mut().ok_or_else(|| missing_engine(handle))?)
+}
+
+fn engine_slot(handle: u64) -> FfiResult<EngineSlot> {
+    let engines = ENGINES.lock().map_err(|_| poisoned("engine registry"))?;
+    engines
+        .get(&handle)
+        .cloned()
+        .ok_or_else(|| missing_engine(handle))
+}
+
+fn missing_engine(handle: u64) -> FfiError {
+    let busy = RUNNERS
         .lock()
-        .map_err(|_| "engine registry lock poisoned".to_string())?;
-    let engine = engines
-        .get_mut(&handle)
-        .ok_or_else(|| format!("engine handle not found: {handle}"))?;
-    action(engine)
+        .is_ok_and(|runners| runners.contains_key(&handle));
+    if busy {
+        return EngineError::Busy(format!(
+            "engine {handle} is stepping in the background; drain it with gs_poll_result"
+        ))
+        .into();
+    }
+    FfiError {
+        code: ErrorCode::EngineNotFound,
+        message: format!("engine handle not found: {handle}"),
+    }
+}
+
+fn poisoned(lock: &str) -> FfiError {
+    FfiError {
+        code: ErrorCode::Internal,
+        message: format!("{lock} lock poisoned"),
+    }
+}
+
+fn with_catalog<F>(action: F) -> FfiResult
+where
+    F: FnOnce(&mut ScenarioCatalog) -> FfiResult,
+{
+    let mut catalog = CATALOG.lock().map_err(|_| poisoned("scenario catalog"))?;
+    action(&mut catalog)
 }
 
-fn parse_json_arg<T>(arg_ptr: *const c_char, name: &str) -> std::result::Result<T, String>
+fn parse_json_arg<T>(arg_ptr: *const c_char, name: &str) -> FfiResult<T>
 where
     T: DeserializeOwned,
 {
     let json_str = c_char_to_string(arg_ptr)?;
     serde_json::from_str::<T>(&json_str)
-        .map_err(|error| format!("failed to parse {name} json: {error}"))
+        .map_err(|error| format!("failed to parse {name} json: {error}").into())
 }
 
-fn c_char_to_string(ptr: *const c_char) -> std::result::Result<String, String> {
+fn c_char_to_string(ptr: *const c_char) -> FfiResult<String> {
     if ptr.is_null() {
-        return Err("received null c-string pointer".to_string());
+        return Err("received null c-string pointer".to_string().into());
     }
 
     // SAFETY: caller guarantees the pointer is valid and NUL-terminated.
@@ -200,20 +1391,50 @@ fn c_char_to_string(ptr: *const c_char) -> std::result::Result<String, String> {
     c_str
         .to_str()
         .map(|value| value.to_string())
-        .map_err(|error| format!("invalid utf-8 in c-string: {error}"))
+        .map_err(|error| format!("invalid utf-8 in c-string: {error}").into())
+}
+
+fn bytes_arg<'a>(ptr: *const u8, len: usize) -> FfiResult<&'a [u8]> {
+    if ptr.is_null() {
+        return Err("received null byte buffer pointer".to_string().into());
+    }
+
+    // SAFETY: caller guarantees `ptr` points to `len` readable bytes for this call.
+    Ok(unsafe { std::slice::from_raw_parts(ptr, len) })
 }
 
-fn response_to_ptr(result: std::result::Result<Value, String>) -> *mut c_char {
+fn write_bytes_out(bytes: Vec<u8>, out_ptr: *mut *mut u8, out_len: *mut usize) -> FfiResult {
+    if out_ptr.is_null() || out_len.is_null() {
+        return Err("received null output pointer".to_string().into());
+    }
+
+    let len = bytes.len();
+    let ptr = Box::into_raw(bytes.into_boxed_slice()).cast::<u8>();
+    #[cfg(feature = "ffi-audit")]
+    crate::ffi_audit::record_buffer(ptr, len);
+    // SAFETY: both output pointers were checked for null and are caller-owned.
+    unsafe {
+        *out_ptr = ptr;
+        *out_len = len;
+    }
+    Ok(json!({ "length": len }))
+}
+
+fn response_to_ptr(result: FfiResult) -> *mut c_char {
     let payload = match result {
         Ok(data) => json!({ "ok": true, "data": data }),
-        Err(error) => json!({ "ok": false, "error": error }),
+        Err(error) => json!({ "ok": false, "error": error.message, "errorCode": error.code }),
     };
 
     let json_string = payload.to_string();
-    match CString::new(json_string) {
-        Ok(c_string) => c_string.into_raw(),
-        Err(_) => CString::new("{\"ok\":false,\"error\":\"response contains interior null\"}")
+    let c_string = CString::new(json_string).unwrap_or_else(|_| {
+        CString::new("{\"ok\":false,\"error\":\"response contains interior null\"}")
             .expect("static fallback response must be valid")
-            .into_raw(),
-    }
+    });
+    #[cfg(feature = "ffi-audit")]
+    let len = c_string.as_bytes_with_nul().len();
+    let ptr = c_string.into_raw();
+    #[cfg(feature = "ffi-audit")]
+    crate::ffi_audit::record_string(ptr.cast_const().cast(), len);
+    ptr
 }
@@ -0,0 +1,98 @@
+//! Ownership tracking for buffers handed across the FFI (`ffi-audit` feature).
+//!
+//! Every string from `response_to_ptr` and every byte buffer from `write_bytes_out` is
+//! registered until the host hands it back, so leaks and double or foreign frees
+//! become visible through `gs_debug_alloc_stats`.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use once_cell::sync::Lazy;
+use serde::Serialize;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AllocStats {
+    pub strings_allocated: u64,
+    pub strings_freed: u64,
+    pub live_strings: u64,
+    pub live_string_bytes: u64,
+    pub buffers_allocated: u64,
+    pub buffers_freed: u64,
+    pub live_buffers: u64,
+    pub live_buffer_bytes: u64,
+    /// Frees of pointers that are not live: double frees or foreign pointers. These
+    /// are refused rather than passed to the allocator.
+    pub invalid_frees: u64,
+}
+
+#[derive(Default)]
+struct Ledger {
+    stats: AllocStats,
+    strings: HashMap<usize, usize>,
+    buffers: HashMap<usize, usize>,
+}
+
+static LEDGER: Lazy<Mutex<Ledger>> = Lazy::new(|| Mutex::new(Ledger::default()));
+
+pub(crate) fn stats() -> AllocStats {
+    ledger().stats
+}
+
+pub(crate) fn record_string(ptr: *const u8, len: usize) {
+    let mut ledger = ledger();
+    ledger.strings.insert(ptr as usize, len);
+    ledger.stats.strings_allocated += 1;
+    ledger.stats.live_strings += 1;
+    ledger.stats.live_string_bytes += len as u64;
+}
+
+/// Returns false when `ptr` is not a live string; the caller must not free it.
+pub(crate) fn release_string(ptr: *const u8) -> bool {
+    let mut ledger = ledger();
+    let Some(len) = ledger.strings.remove(&(ptr as usize)) else {
+        ledger.stats.invalid_frees += 1;
+        return false;
+    };
+    ledger.stats.strings_freed += 1;
+    ledger.stats.live_strings -= 1;
+    ledger.stats.live_string_bytes -= len as u64;
+    true
+}
+
+/// Empty buffers own no allocation and share a dangling pointer, so they are not
+/// tracked.
+pub(crate) fn record_buffer(ptr: *const u8, len: usize) {
+    if len == 0 {
+        return;
+    }
+    let mut ledger = ledger();
+    ledger.buffers.insert(ptr as usize, len);
+    ledger.stats.buffers_allocated += 1;
+    ledger.stats.live_buffers += 1;
+    ledger.stats.live_buffer_bytes += len as u64;
+}
+
+/// Returns false when `ptr`/`len` is not a live buffer; the caller must not free it.
+pub(crate) fn release_buffer(ptr: *const u8, len: usize) -> bool {
+    if len == 0 {
+        return true;
+    }
+    let mut ledger = ledger();
+    if ledger.buffers.get(&(ptr as usize)) != Some(&len) {
+        ledger.stats.invalid_frees += 1;
+        return false;
+    }
+    ledger.buffers.remove(&(ptr as usize));
+    ledger.stats.buffers_freed += 1;
+    ledger.stats.live_buffers -= 1;
+    ledger.stats.live_buffer_bytes -= len as u64;
+    true
+}
+
+fn ledger() -> std::sync::MutexGuard<'static, Ledger> {
+    // The ledger holds plain counters, so a panic elsewhere cannot leave it torn.
+    LEDGER
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
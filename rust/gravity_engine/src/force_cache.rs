@@ -0,0 +1,167 @@
+use crate::config::{EngineConfig, GravitySolver};
+use crate::forces::{softened_inverse_cube, softening_squares};
+use crate::math::Vec2;
+use crate::solver::{
+    SolverRuntimeMode, SolverStats, choose_runtime_mode, compute_accelerations_with_config,
+};
+use crate::types::Body;
+
+/// Gravity-only accelerations from the last evaluation, keyed by the positions they
+/// were computed at. External forces are velocity dependent and never cached. Only
+/// pairwise solves are cached: the incremental corrections are exact pair sums, which
+/// would neither match nor beat a Barnes-Hut tree.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct ForceCache {
+    reference_positions: Vec<Vec2>,
+    masses: Vec<u64>,
+    softening: Vec<Option<u64>>,
+    alive: Vec<bool>,
+    config_key: Option<ConfigKey>,
+    gravity: Vec<Vec2>,
+    mode: Option<SolverRuntimeMode>,
+    pub hits: u64,
+    pub partial_updates: u64,
+}
+
+impl ForceCache {
+    pub(crate) fn gravity(
+        &mut self,
+        bodies: &[Body],
+        positions: &[Vec2],
+        config: &EngineConfig,
+    ) -> (Vec<Vec2>, SolverStats) {
+        // Incremental updates assume every pair interacts through its plain separation.
+        let alive_count = bodies.iter().filter(|body| body.alive).count();
+        let Some(caching) = config.force_caching.as_ref().filter(|_| {
+            config.interaction_groups.is_none()
+                && config.periodic_extent().is_none()
+                && choose_runtime_mode(alive_count, config) == SolverRuntimeMode::Pairwise
+        }) else {
+            return compute_accelerations_with_config(bodies, positions, config);
+        };
+        let Some(mode) = self.mode.filter(|_| self.matches(bodies, config)) else {
+            return self.refresh(bodies, positions, config);
+        };
+
+        let threshold = caching.displacement_threshold;
+        let moved = (0..bodies.len())
+            .filter(|&index| {
+                self.alive[index]
+                    && (positions[index] - self.reference_positions[index]).norm() > threshold
+            })
+            .collect::<Vec<_>>();
+
+        if moved.is_empty() {
+            self.hits += 1;
+            return (self.gravity.clone(), SolverStats { mode });
+        }
+        // A zero threshold means exact reuse only, so any motion forces a full solve.
+        if threshold == 0.0 || moved.len() * 2 > alive_count {
+            return self.refresh(bodies, positions, config);
+        }
+
+        self.partial_updates += 1;
+        self.update_incrementally(bodies, positions, config, &moved);
+        (self.gravity.clone(), SolverStats { mode })
+    }
+
+    fn matches(&self, bodies: &[Body], config: &EngineConfig) -> bool {
+        self.config_key == Some(config_key(config))
+            && self.masses.len() == bodies.len()
+            && bodies.iter().enumerate().all(|(index, body)| {
+                self.masses[index] == body.mass.to_bits()
+                    && self.softening[index] == body.softening.map(f64::to_bits)
+                    && self.alive[index] == body.alive
+            })
+    }
+
+    fn refresh(
+        &mut self,
+        bodies: &[Body],
+        positions: &[Vec2],
+        config: &EngineConfig,
+    ) -> (Vec<Vec2>, SolverStats) {
+        let (gravity, stats) = compute_accelerations_with_config(bodies, positions, config);
+        self.reference_positions = positions.to_vec();
+        self.masses = bodies.iter().map(|body| body.mass.to_bits()).collect();
+        self.softening = bodies
+            .iter()
+            .map(|body| body.softening.map(f64::to_bits))
+            .collect();
+        self.alive = bodies.iter().map(|body| body.alive).collect();
+        self.config_key = Some(config_key(config));
+        self.gravity = gravity.clone();
+        self.mode = Some(stats.mode);
+        (gravity, stats)
+    }
+
+    /// Quiet bodies keep their cached value corrected for the moved sources; moved
+    /// bodies are re-summed directly. Both use the pairwise kernel.
+    fn update_incrementally(
+        &mut self,
+        bodies: &[Body],
+        positions: &[Vec2],
+        config: &EngineConfig,
+        moved: &[usize],
+    ) {
+        let softening = softening_squares(bodies, config.softening_epsilon);
+        let g = config.gravity_constant;
+        let mut is_moved = vec![false; bodies.len()];
+        for &index in moved {
+            is_moved[index] = true;
+        }
+
+        for index in 0..bodies.len() {
+            if !self.alive[index] {
+                continue;
+            }
+            if is_moved[index] {
+                let mut total = Vec2::ZERO;
+                for (source, body) in bodies.iter().enumerate() {
+                    if source != index && body.alive {
+                        total += pair_acceleration(
+                            positions[source] - positions[index],
+                            body.mass,
+                            g,
+                            0.5 * (softening[source] + softening[index]),
+                        );
+                    }
+                }
+                self.gravity[index] = total;
+                continue;
+            }
+            for &source in moved {
+                let mass = bodies[source].mass;
+                let epsilon2 = 0.5 * (softening[source] + softening[index]);
+                self.gravity[index] +=
+                    pair_acceleration(positions[source] - positions[index], mass, g, epsilon2)
+                        - pair_acceleration(
+                            self.reference_positions[source] - self.reference_positions[index],
+                            mass,
+                            g,
+                            epsilon2,
+                        );
+            }
+        }
+
+        for &index in moved {
+            self.reference_positions[index] = positions[index];
+        }
+    }
+}
+
+fn pair_acceleration(delta: Vec2, source_mass: f64, g: f64, epsilon2: f64) -> Vec2 {
+    delta * (g * source_mass * softened_inverse_cube(delta.norm_squared(), epsilon2))
+}
+
+type ConfigKey = (u64, u64, u64, usize, GravitySolver);
+
+fn config_key(config: &EngineConfig) -> ConfigKey {
+    (
+        config.gravity_constant.to_bits(),
+        config.softening_epsilon.to_bits(),
+        config.barnes_hut_theta.to_bits(),
+        config.barnes_hut_threshold,
+        config.gravity_solver,
+    )
+}
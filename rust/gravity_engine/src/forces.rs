@@ -0,0 +1,388 @@
+use std::fmt;
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::EngineConfig;
+use crate::errors::{EngineError, Result};
+use crate::math::Vec2;
+use crate::solver::{SolverStats, compute_accelerations_with_config};
+use crate::types::Body;
+
+/// Plummer-softened attraction between point masses `distance` apart:
+/// `G m1 m2 r / (r^2 + eps^2)^(3/2)`.
+///
+/// Matches Newton's `G m1 m2 / r^2` once `r` is well above `softening_epsilon`, peaks at
+/// `r = eps / sqrt(2)` and falls linearly to zero inside it. Both gravity solvers and the
+/// force cache evaluate this law through the same kernel.
+pub fn force_magnitude(m1: f64, m2: f64, distance: f64, config: &EngineConfig) -> f64 {
+    let epsilon2 = config.softening_epsilon * config.softening_epsilon;
+    config.gravity_constant
+        * m1
+        * m2
+        * distance
+        * softened_inverse_cube(distance * distance, epsilon2)
+}
+
+/// Separation below which the softened force falls more than `tolerance` (a fraction,
+/// e.g. 0.01 for 1%) short of Newton's, for rendering a "softening zone" around bodies.
+/// Depends only on `softening_epsilon`; zero when softening is off.
+pub fn softening_radius(config: &EngineConfig, tolerance: f64) -> Result<f64> {
+    if !(tolerance > 0.0 && tolerance < 1.0) {
+        return Err(EngineError::InvalidConfig(
+            "softening tolerance must be in (0, 1)".to_string(),
+        ));
+    }
+    // F_soft / F_newton = (r^2 / (r^2 + eps^2))^(3/2); solve for the ratio 1 - tolerance.
+    let q = (1.0 - tolerance).powf(2.0 / 3.0);
+    Ok(config.softening_epsilon * (q / (1.0 - q)).sqrt())
+}
+
+/// Squared softening length of `body`: its own `softening` or `epsilon`.
+fn softening_squared(body: &Body, epsilon: f64) -> f64 {
+    let length = body.softening.unwrap_or(epsilon);
+    length * length
+}
+
+pub(crate) fn softening_squares(bodies: &[Body], epsilon: f64) -> Vec<f64> {
+    bodies
+        .iter()
+        .map(|body| softening_squared(body, epsilon))
+        .collect()
+}
+
+/// Pairs soften with the mean of the two squared lengths: symmetric, so forces stay
+/// equal and opposite, and exactly the global value when neither body overrides it.
+pub(crate) fn pair_softening_squared(first: &Body, second: &Body, epsilon: f64) -> f64 {
+    0.5 * (softening_squared(first, epsilon) + softening_squared(second, epsilon))
+}
+
+/// `1 / (r^2 + eps^2)^(3/2)`, or zero when the softened distance vanishes.
+pub(crate) fn softened_inverse_cube(distance_squared: f64, epsilon2: f64) -> f64 {
+    let dist_sq = distance_squared + epsilon2;
+    if dist_sq <= 0.0 {
+        return 0.0;
+    }
+    let inv_dist = dist_sq.sqrt().recip();
+    inv_dist * inv_dist * inv_dist
+}
+
+/// Gravitational acceleration of a body and its first two time derivatives, for
+/// renderers that need curvature (motion blur, camera smoothing) between frames.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BodyDerivatives {
+    pub id: String,
+    pub acceleration: Vec2,
+    pub jerk: Vec2,
+    pub snap: Vec2,
+}
+
+/// Analytic point-mass gravity derivatives of `targets` from the current positions and
+/// velocities, pairwise and with per-pair softening. External forces are excluded.
+///
+/// Per pair with `r`, `v`, `a` the relative position, velocity and acceleration and
+/// `s^2 = r^2 + eps^2`: `A = G m r / s^3`, `J = G m v / s^3 - 3 alpha A` and
+/// `S = G m a / s^3 - 6 alpha J - 3 beta A`, where `alpha = r.v / s^2` and
+/// `beta = (v.v + r.a) / s^2 + alpha^2`.
+pub(crate) fn gravity_derivatives(
+    bodies: &[Body],
+    config: &EngineConfig,
+    targets: &[usize],
+) -> Vec<BodyDerivatives> {
+    let positions = bodies.iter().map(|body| body.position).collect::<Vec<_>>();
+    let softening = softening_squares(bodies, config.softening_epsilon);
+    let g = config.gravity_constant;
+    let pair_terms = |i: usize, j: usize| {
+        let r = positions[j] - positions[i];
+        let v = bodies[j].velocity - bodies[i].velocity;
+        let epsilon2 = 0.5 * (softening[i] + softening[j]);
+        let scale = g * bodies[j].mass * softened_inverse_cube(r.norm_squared(), epsilon2);
+        let dist_sq = r.norm_squared() + epsilon2;
+        let alpha = if dist_sq > 0.0 {
+            r.dot(v) / dist_sq
+        } else {
+            0.0
+        };
+        (r, v, scale, dist_sq, alpha)
+    };
+
+    let mut accelerations = vec![Vec2::ZERO; bodies.len()];
+    for i in 0..bodies.len() {
+        if !bodies[i].alive {
+            continue;
+        }
+        for j in (0..bodies.len()).filter(|&j| j != i && bodies[j].alive) {
+            let (r, _, scale, _, _) = pair_terms(i, j);
+            accelerations[i] += r * scale;
+        }
+    }
+
+    targets
+        .iter()
+        .map(|&i| {
+            let mut jerk = Vec2::ZERO;
+            let mut snap = Vec2::ZERO;
+            for j in (0..bodies.len()).filter(|&j| j != i && bodies[j].alive) {
+                let (r, v, scale, dist_sq, alpha) = pair_terms(i, j);
+                if dist_sq <= 0.0 {
+                    continue;
+                }
+                let a = accelerations[j] - accelerations[i];
+                let beta = (v.norm_squared() + r.dot(a)) / dist_sq + alpha * alpha;
+                let pair_acceleration = r * scale;
+                let pair_jerk = v * scale - pair_acceleration * (3.0 * alpha);
+                jerk += pair_jerk;
+                snap += a * scale - pair_jerk * (6.0 * alpha) - pair_acceleration * (3.0 * beta);
+            }
+            BodyDerivatives {
+                id: bodies[i].id.clone(),
+                acceleration: accelerations[i],
+                jerk,
+                snap,
+            }
+        })
+        .collect()
+}
+
+/// User-supplied physics evaluated at every integrator stage, on top of gravity.
+///
+/// `positions` are the stage positions (not necessarily `bodies[i].position`) and the
+/// result must have one entry per body, including dead ones.
+pub trait ForceProvider: Send + Sync {
+    fn accelerations(&self, bodies: &[Body], positions: &[Vec2]) -> Vec<Vec2>;
+}
+
+/// Registered providers in evaluation order, keyed by name.
+#[derive(Clone, Default)]
+pub(crate) struct ForceProviders(Vec<(String, Arc<dyn ForceProvider>)>);
+
+impl fmt::Debug for ForceProviders {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list()
+            .entries(self.0.iter().map(|(name, _)| name))
+            .finish()
+    }
+}
+
+impl ForceProviders {
+    pub(crate) fn insert(&mut self, name: String, provider: Arc<dyn ForceProvider>) -> bool {
+        if self.0.iter().any(|(existing, _)| *existing == name) {
+            return false;
+        }
+        self.0.push((name, provider));
+        true
+    }
+
+    pub(crate) fn remove(&mut self, name: &str) -> bool {
+        let before = self.0.len();
+        self.0.retain(|(existing, _)| existing != name);
+        self.0.len() != before
+    }
+
+    pub(crate) fn names(&self) -> impl Iterator<Item = &str> {
+        self.0.iter().map(|(name, _)| name.as_str())
+    }
+}
+
+pub(crate) fn evaluate_accelerations(
+    bodies: &[Body],
+    positions: &[Vec2],
+    velocities: &[Vec2],
+    config: &EngineConfig,
+    providers: &ForceProviders,
+) -> (Vec<Vec2>, SolverStats) {
+    let (mut accelerations, stats) = compute_accelerations_with_config(bodies, positions, config);
+    add_external_accelerations(
+        bodies,
+        positions,
+        velocities,
+        config,
+        providers,
+        &mut accelerations,
+    );
+    (accelerations, stats)
+}
+
+/// Everything beyond point-mass gravity, added on top of the solver output.
+pub(crate) fn add_external_accelerations(
+    bodies: &[Body],
+    positions: &[Vec2],
+    velocities: &[Vec2],
+    config: &EngineConfig,
+    providers: &ForceProviders,
+    accelerations: &mut [Vec2],
+) {
+    add_radiation_pressure(bodies, positions, config, accelerations);
+    add_yarkovsky_drift(bodies, velocities, accelerations);
+    add_oblateness(bodies, positions, config, accelerations);
+    for (name, provider) in &providers.0 {
+        let extra = provider.accelerations(bodies, positions);
+        assert_eq!(
+            extra.len(),
+            bodies.len(),
+            "force provider '{name}' returned the wrong number of accelerations"
+        );
+        for (acceleration, delta) in accelerations.iter_mut().zip(extra) {
+            *acceleration += delta;
+        }
+    }
+}
+
+/// Ratio of radiation pressure to gravity exerted by a luminous source on a
+/// receiver with the given area-to-mass parameter (the dust `beta`).
+pub fn radiation_beta(
+    luminosity: f64,
+    area_to_mass: f64,
+    gravity_constant: f64,
+    source_mass: f64,
+) -> f64 {
+    luminosity * area_to_mass / (gravity_constant * source_mass)
+}
+
+/// Area-to-mass parameter that yields `beta` for a receiver of `source`.
+pub fn area_to_mass_for_beta(beta: f64, source: &Body, gravity_constant: f64) -> Option<f64> {
+    let luminosity = source.luminosity.filter(|value| *value > 0.0)?;
+    Some(beta * gravity_constant * source.mass / luminosity)
+}
+
+fn add_radiation_pressure(
+    bodies: &[Body],
+    positions: &[Vec2],
+    config: &EngineConfig,
+    accelerations: &mut [Vec2],
+) {
+    let sources = bodies
+        .iter()
+        .enumerate()
+        .filter_map(|(index, body)| match body.luminosity {
+            Some(luminosity) if body.alive && luminosity > 0.0 => Some((index, luminosity)),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    if sources.is_empty() {
+        return;
+    }
+
+    for (index, body) in bodies.iter().enumerate() {
+        let area_to_mass = match body.area_to_mass {
+            Some(value) if body.alive && value > 0.0 => value,
+            _ => continue,
+        };
+
+        for &(source_index, luminosity) in &sources {
+            if source_index == index {
+                continue;
+            }
+
+            // Softened exactly like gravity so `beta` stays a constant force ratio.
+            let delta = positions[index] - positions[source_index];
+            let epsilon2 =
+                pair_softening_squared(body, &bodies[source_index], config.softening_epsilon);
+            let dist_sq = delta.norm_squared() + epsilon2;
+            if dist_sq <= 0.0 {
+                continue;
+            }
+
+            let inv_dist = dist_sq.sqrt().recip();
+            let inv_dist3 = inv_dist * inv_dist * inv_dist;
+            accelerations[index] += delta * (luminosity * area_to_mass * inv_dist3);
+        }
+    }
+}
+
+fn add_yarkovsky_drift(bodies: &[Body], velocities: &[Vec2], accelerations: &mut [Vec2]) {
+    if !bodies.iter().any(|body| body.yarkovsky.is_some()) {
+        return;
+    }
+
+    let mut heaviest: Option<usize> = None;
+    let mut runner_up: Option<usize> = None;
+    for (index, body) in bodies.iter().enumerate().filter(|(_, body)| body.alive) {
+        if heaviest.is_none_or(|best| body.mass > bodies[best].mass) {
+            runner_up = heaviest;
+            heaviest = Some(index);
+        } else if runner_up.is_none_or(|second| body.mass > bodies[second].mass) {
+            runner_up = Some(index);
+        }
+    }
+
+    for (index, body) in bodies.iter().enumerate() {
+        let magnitude = match body.yarkovsky {
+            Some(value) if body.alive && value != 0.0 => value,
+            _ => continue,
+        };
+        let primary = if heaviest == Some(index) {
+            runner_up
+        } else {
+            heaviest
+        };
+        let Some(primary) = primary else {
+            continue;
+        };
+
+        // Along-track is measured against the primary so the drift changes the orbit, not the frame.
+        let along_track = (velocities[index] - velocities[primary]).normalized_or(Vec2::ZERO);
+        accelerations[index] += along_track * magnitude;
+    }
+}
+
+fn add_oblateness(
+    bodies: &[Body],
+    positions: &[Vec2],
+    config: &EngineConfig,
+    accelerations: &mut [Vec2],
+) {
+    for (source_index, source) in bodies.iter().enumerate() {
+        let Some(oblateness) = source.oblateness.as_ref().filter(|_| source.alive) else {
+            continue;
+        };
+        let influence_radius = sphere_of_influence(bodies, positions, source_index);
+        let gm = config.gravity_constant * source.mass;
+        let coefficient = 0.5 * gm * oblateness.j2 * oblateness.equatorial_radius.powi(2);
+        // In-plane projection of the spin axis; its dot with a position is the height above the equator.
+        let axis = Vec2::new(-oblateness.node_angle.sin(), oblateness.node_angle.cos())
+            * oblateness.obliquity.sin();
+
+        for (index, body) in bodies.iter().enumerate() {
+            if index == source_index || !body.alive {
+                continue;
+            }
+
+            let offset = positions[index] - positions[source_index];
+            let r2 = offset.norm_squared();
+            let r = r2.sqrt();
+            if r < oblateness.equatorial_radius || r > influence_radius {
+                continue;
+            }
+
+            let height = offset.dot(axis);
+            let inv_r5 = (r2 * r2 * r).recip();
+            let acceleration = (axis * (-6.0 * height)
+                + offset * (15.0 * height * height / r2 - 3.0))
+                * (coefficient * inv_r5);
+
+            accelerations[index] += acceleration;
+            accelerations[source_index] -= acceleration * (body.mass / source.mass);
+        }
+    }
+}
+
+fn sphere_of_influence(bodies: &[Body], positions: &[Vec2], index: usize) -> f64 {
+    let body = &bodies[index];
+    let parent = bodies
+        .iter()
+        .enumerate()
+        .filter(|(other, candidate)| {
+            *other != index && candidate.alive && candidate.mass > body.mass
+        })
+        .max_by(|(_, a), (_, b)| a.mass.total_cmp(&b.mass));
+
+    match parent {
+        Some((parent_index, parent)) => {
+            let distance = (positions[index] - positions[parent_index]).norm();
+            distance * (body.mass / parent.mass).powf(0.4)
+        }
+        None => f64::INFINITY,
+    }
+}
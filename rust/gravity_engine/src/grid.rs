@@ -0,0 +1,122 @@
+use serde::{Deserialize, Serialize};
+
+use crate::errors::{EngineError, Result};
+use crate::math::Vec2;
+use crate::types::Body;
+
+/// Uniform grid of square cells; cell `(column, row)` is stored at `row * columns + column`.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GridSpec {
+    /// Lower-left corner of the grid.
+    pub origin: Vec2,
+    pub cell_size: f64,
+    pub columns: usize,
+    pub rows: usize,
+}
+
+impl GridSpec {
+    /// Grid of `columns` x `rows` cells centred on `center` covering at least `extent`.
+    pub fn centered(center: Vec2, extent: f64, columns: usize, rows: usize) -> Self {
+        let cell_size = extent / columns.max(rows).max(1) as f64;
+        Self {
+            origin: center - Vec2::new(columns as f64, rows as f64) * (0.5 * cell_size),
+            cell_size,
+            columns,
+            rows,
+        }
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        if !self.origin.is_finite() || !self.cell_size.is_finite() || self.cell_size <= 0.0 {
+            return Err(EngineError::InvalidConfig(
+                "grid origin and cell_size must be finite with cell_size > 0".to_string(),
+            ));
+        }
+        if self.columns == 0 || self.rows == 0 || self.columns * self.rows > 1 << 22 {
+            return Err(EngineError::InvalidConfig(
+                "grid must have between 1 and 4194304 cells".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    pub fn cell_count(&self) -> usize {
+        self.columns * self.rows
+    }
+
+    pub fn cell_index(&self, position: Vec2) -> Option<usize> {
+        let local = (position - self.origin) / self.cell_size;
+        if local.x < 0.0 || local.y < 0.0 {
+            return None;
+        }
+        let (column, row) = (local.x as usize, local.y as usize);
+        (column < self.columns && row < self.rows).then_some(row * self.columns + column)
+    }
+
+    pub fn cell_center(&self, index: usize) -> Vec2 {
+        let (column, row) = (index % self.columns, index / self.columns);
+        self.origin + Vec2::new(column as f64 + 0.5, row as f64 + 0.5) * self.cell_size
+    }
+}
+
+/// Bins alive bodies into grid cells; bodies outside the grid are skipped.
+pub(crate) fn bin_bodies<'a>(
+    bodies: &'a [Body],
+    spec: &'a GridSpec,
+) -> impl Iterator<Item = (usize, &'a Body)> + 'a {
+    bodies
+        .iter()
+        .filter(|body| body.alive)
+        .filter_map(|body| spec.cell_index(body.position).map(|index| (index, body)))
+}
+
+/// Surface mass density per cell.
+pub fn density_grid(bodies: &[Body], spec: &GridSpec) -> Vec<f64> {
+    let mut density = vec![0.0; spec.cell_count()];
+    let area = spec.cell_size * spec.cell_size;
+    for (index, body) in bin_bodies(bodies, spec) {
+        density[index] += body.mass / area;
+    }
+    density
+}
+
+/// Gravitational potential and acceleration at every cell centre of `grid`.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FieldGrid {
+    pub grid: GridSpec,
+    pub potential: Vec<f64>,
+    pub acceleration: Vec<Vec2>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CellKinematics {
+    pub body_count: usize,
+    pub mass: f64,
+    /// Mass-weighted mean velocity.
+    pub mean_velocity: Vec2,
+    /// Mass-weighted RMS speed relative to `mean_velocity` (the local "temperature").
+    pub velocity_dispersion: f64,
+}
+
+pub fn kinematics_grid(bodies: &[Body], spec: &GridSpec) -> Vec<CellKinematics> {
+    let mut cells = vec![CellKinematics::default(); spec.cell_count()];
+    let mut speed_sq_sums = vec![0.0; spec.cell_count()];
+    for (index, body) in bin_bodies(bodies, spec) {
+        let cell = &mut cells[index];
+        cell.body_count += 1;
+        cell.mass += body.mass;
+        cell.mean_velocity += body.velocity * body.mass;
+        speed_sq_sums[index] += body.mass * body.velocity.norm_squared();
+    }
+    for (cell, speed_sq_sum) in cells.iter_mut().zip(speed_sq_sums) {
+        if cell.mass > 0.0 {
+            cell.mean_velocity = cell.mean_velocity / cell.mass;
+            let variance = speed_sq_sum / cell.mass - cell.mean_velocity.norm_squared();
+            cell.velocity_dispersion = variance.max(0.0).sqrt();
+        }
+    }
+    cells
+}
@@ -0,0 +1,173 @@
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+
+use serde::{Deserialize, Serialize};
+
+use crate::engine::SimulationEngine;
+use crate::events::CollisionEvent;
+use crate::math::Vec2;
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BodySample {
+    pub id: String,
+    pub mass: f64,
+    pub radius: f64,
+    pub position: Vec2,
+    pub velocity: Vec2,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HistoryFrame {
+    pub tick: u64,
+    pub sim_time: f64,
+    pub bodies: Vec<BodySample>,
+    /// Collisions logged by the engine since the previous frame.
+    #[serde(default)]
+    pub collisions: Vec<CollisionEvent>,
+}
+
+impl HistoryFrame {
+    pub fn body(&self, id: &str) -> Option<&BodySample> {
+        self.bodies.iter().find(|body| body.id == id)
+    }
+}
+
+/// Bounded recording of engine frames; the oldest frames are dropped first.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct History {
+    capacity: usize,
+    frames: VecDeque<HistoryFrame>,
+}
+
+impl History {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            frames: VecDeque::new(),
+        }
+    }
+
+    /// Records the engine's current bodies along with any collisions still in its
+    /// event log since the previous frame.
+    pub fn record(&mut self, engine: &SimulationEngine) {
+        let state = engine.get_state();
+        let previous_tick = self.frames.back().map(|frame| frame.tick);
+        let collisions = engine
+            .collision_events()
+            .filter(|event| {
+                previous_tick.is_some_and(|tick| event.tick > tick) && event.tick <= state.tick
+            })
+            .cloned()
+            .collect();
+        let bodies = state
+            .bodies
+            .into_iter()
+            .filter(|body| body.alive)
+            .map(|body| BodySample {
+                id: body.id,
+                mass: body.mass,
+                radius: body.radius,
+                position: body.position,
+                velocity: body.velocity,
+            })
+            .collect();
+        self.push(HistoryFrame {
+            tick: state.tick,
+            sim_time: state.sim_time,
+            bodies,
+            collisions,
+        });
+    }
+
+    pub fn push(&mut self, frame: HistoryFrame) {
+        if self.frames.len() == self.capacity {
+            self.frames.pop_front();
+        }
+        self.frames.push_back(frame);
+    }
+
+    pub fn frames(&self) -> impl Iterator<Item = &HistoryFrame> {
+        self.frames.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    pub fn time_span(&self) -> Option<(f64, f64)> {
+        Some((self.frames.front()?.sim_time, self.frames.back()?.sim_time))
+    }
+
+    pub fn collisions(&self) -> impl Iterator<Item = &CollisionEvent> {
+        self.frames.iter().flat_map(|frame| frame.collisions.iter())
+    }
+
+    pub fn trajectory(&self, id: &str) -> Vec<(f64, BodySample)> {
+        self.frames
+            .iter()
+            .filter_map(|frame| Some((frame.sim_time, frame.body(id)?.clone())))
+            .collect()
+    }
+
+    /// Trajectories of several bodies gathered in one pass over the frames.
+    pub(crate) fn trajectories(
+        &self,
+        ids: &BTreeSet<&str>,
+    ) -> BTreeMap<String, Vec<(f64, BodySample)>> {
+        let mut trajectories = BTreeMap::<String, Vec<(f64, BodySample)>>::new();
+        for frame in &self.frames {
+            for body in frame
+                .bodies
+                .iter()
+                .filter(|body| ids.contains(body.id.as_str()))
+            {
+                trajectories
+                    .entry(body.id.clone())
+                    .or_default()
+                    .push((frame.sim_time, body.clone()));
+            }
+        }
+        trajectories
+    }
+
+    /// Linearly interpolated sample of `id` at `sim_time`, clamped to the recorded range.
+    pub fn body_at(&self, id: &str, sim_time: f64) -> Option<BodySample> {
+        sample_trajectory(&self.trajectory(id), sim_time)
+    }
+}
+
+/// Interpolates a time-ordered trajectory at `sim_time`, clamped to its range.
+pub(crate) fn sample_trajectory(
+    trajectory: &[(f64, BodySample)],
+    sim_time: f64,
+) -> Option<BodySample> {
+    let (first_time, first) = trajectory.first()?;
+    if sim_time <= *first_time {
+        return Some(first.clone());
+    }
+    let next = trajectory.partition_point(|(time, _)| *time < sim_time);
+    let Some((t1, after)) = trajectory.get(next) else {
+        return trajectory.last().map(|(_, sample)| sample.clone());
+    };
+
+    let (t0, before) = &trajectory[next - 1];
+    let span = t1 - t0;
+    let alpha = if span > 0.0 {
+        (sim_time - t0) / span
+    } else {
+        1.0
+    };
+    Some(BodySample {
+        id: before.id.clone(),
+        mass: before.mass + (after.mass - before.mass) * alpha,
+        radius: before.radius + (after.radius - before.radius) * alpha,
+        position: before.position + (after.position - before.position) * alpha,
+        velocity: before.velocity + (after.velocity - before.velocity) * alpha,
+    })
+}
@@ -0,0 +1,124 @@
+//! Callbacks around every integrator stage, for extensions such as constraint
+//! projection or thermostats that must see the intermediate state.
+
+use std::fmt;
+use std::sync::Arc;
+
+use crate::config::IntegratorKind;
+use crate::math::Vec2;
+use crate::types::Body;
+
+/// Where in a step a hook is being invoked.
+#[derive(Clone, Copy, Debug)]
+pub struct StageContext<'a> {
+    pub integrator: IntegratorKind,
+    /// Force evaluation index within the step: 0 for semi-implicit Euler, 0..=1 for
+    /// velocity Verlet, 0..=3 for RK4.
+    pub stage: u32,
+    /// Length of the (sub)step being taken.
+    pub dt: f64,
+    pub bodies: &'a [Body],
+}
+
+/// Integrator stage callbacks. All slices have one entry per body, dead ones
+/// included, which hooks should leave alone.
+///
+/// Hooks must be deterministic functions of their inputs: error-controlled dt also
+/// runs them on trial substeps that are then discarded, and replays and rollbacks
+/// expect the same state to produce the same result.
+pub trait StageHook: Send + Sync {
+    /// Before forces are evaluated at this stage's positions and velocities.
+    fn before_forces(
+        &self,
+        _context: &StageContext<'_>,
+        _positions: &mut [Vec2],
+        _velocities: &mut [Vec2],
+    ) {
+    }
+
+    /// After forces (gravity, built-in effects and force providers) are evaluated.
+    fn after_forces(
+        &self,
+        _context: &StageContext<'_>,
+        _positions: &[Vec2],
+        _velocities: &[Vec2],
+        _accelerations: &mut [Vec2],
+    ) {
+    }
+
+    /// With the step's final state, just before it is written back to the bodies.
+    /// `context.stage` is the last stage index.
+    fn before_commit(
+        &self,
+        _context: &StageContext<'_>,
+        _positions: &mut [Vec2],
+        _velocities: &mut [Vec2],
+    ) {
+    }
+}
+
+/// Registered hooks in invocation order, keyed by name.
+#[derive(Clone, Default)]
+pub(crate) struct StageHooks(Vec<(String, Arc<dyn StageHook>)>);
+
+impl fmt::Debug for StageHooks {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list()
+            .entries(self.0.iter().map(|(name, _)| name))
+            .finish()
+    }
+}
+
+impl StageHooks {
+    pub(crate) fn insert(&mut self, name: String, hook: Arc<dyn StageHook>) -> bool {
+        if self.0.iter().any(|(existing, _)| *existing == name) {
+            return false;
+        }
+        self.0.push((name, hook));
+        true
+    }
+
+    pub(crate) fn remove(&mut self, name: &str) -> bool {
+        let before = self.0.len();
+        self.0.retain(|(existing, _)| existing != name);
+        self.0.len() != before
+    }
+
+    pub(crate) fn names(&self) -> impl Iterator<Item = &str> {
+        self.0.iter().map(|(name, _)| name.as_str())
+    }
+
+    pub(crate) fn before_forces(
+        &self,
+        context: &StageContext<'_>,
+        positions: &mut [Vec2],
+        velocities: &mut [Vec2],
+    ) {
+        for (_, hook) in &self.0 {
+            hook.before_forces(context, positions, velocities);
+        }
+    }
+
+    pub(crate) fn after_forces(
+        &self,
+        context: &StageContext<'_>,
+        positions: &[Vec2],
+        velocities: &[Vec2],
+        accelerations: &mut [Vec2],
+    ) {
+        for (_, hook) in &self.0 {
+            hook.after_forces(context, positions, velocities, accelerations);
+        }
+    }
+
+    pub(crate) fn before_commit(
+        &self,
+        context: &StageContext<'_>,
+        positions: &mut [Vec2],
+        velocities: &mut [Vec2],
+    ) {
+        for (_, hook) in &self.0 {
+            hook.before_commit(context, positions, velocities);
+        }
+    }
+}
@@ -1,32 +1,537 @@
+use std::f64::consts::TAU;
+
+use crate::boundary::separation;
 use crate::config::{DtPolicy, EngineConfig, IntegratorKind};
 use crate::errors::{EngineError, Result};
-use crate::solver::{SolverRuntimeMode, compute_accelerations, compute_accelerations_with_config};
+use crate::force_cache::ForceCache;
+use crate::forces::{
+    ForceProviders, add_external_accelerations, evaluate_accelerations, gravity_derivatives,
+    softened_inverse_cube, softening_squares,
+};
+use crate::hooks::{StageContext, StageHooks};
+use crate::math::Vec2;
+use crate::orbits::propagate_kepler;
+use crate::solver::{SolverRuntimeMode, SolverStats};
 use crate::types::Body;
 
+/// Registered user extensions consulted while integrating.
+#[derive(Clone, Copy)]
+pub(crate) struct StepExtensions<'a> {
+    pub providers: &'a ForceProviders,
+    pub hooks: &'a StageHooks,
+    /// Per-body maneuver acceleration, constant over the tick; empty when none burn.
+    pub thrust: &'a [Vec2],
+}
+
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub(crate) struct IntegratorStepStats {
     pub used_barnes_hut: bool,
     pub dt_used: f64,
+    /// The tick was split into `2^substep_level` equal substeps.
+    pub substep_level: u8,
 }
 
+/// Advances one tick. `forced_level` replays a previously chosen substep level
+/// instead of running the error estimate (only meaningful for `ErrorControlled`).
 pub(crate) fn integrate_step(
     bodies: &mut [Body],
     config: &EngineConfig,
+    forced_level: Option<u8>,
+    cache: &mut ForceCache,
+    extensions: StepExtensions<'_>,
 ) -> Result<IntegratorStepStats> {
-    let dt = effective_dt(bodies, config);
-    let used_barnes_hut = match config.integrator {
-        IntegratorKind::SemiImplicitEuler => semi_implicit_euler_step(bodies, config, dt)?,
-        IntegratorKind::VelocityVerlet => velocity_verlet_step(bodies, config, dt)?,
-        IntegratorKind::Rk4 => rk4_step(bodies, config, dt)?,
+    if matches!(config.dt_policy, DtPolicy::BlockTimesteps) {
+        return block_timestep_step(bodies, config, extensions);
+    }
+    if let Some(central) = central_body_index(bodies, config) {
+        let dt = effective_dt(bodies, config);
+        central_body_step(bodies, config, central, dt, extensions)?;
+        return Ok(IntegratorStepStats {
+            used_barnes_hut: false,
+            dt_used: dt,
+            substep_level: 0,
+        });
+    }
+    if !matches!(config.dt_policy, DtPolicy::ErrorControlled) {
+        let dt = effective_dt(bodies, config);
+        return Ok(IntegratorStepStats {
+            used_barnes_hut: advance(bodies, config, dt, cache, extensions)?,
+            dt_used: dt,
+            substep_level: 0,
+        });
+    }
+
+    let (substep_level, mut used_barnes_hut) = match forced_level {
+        Some(level) => (level, false),
+        None => choose_substep_level(bodies, config, extensions),
     };
+    let substeps = 1_u32 << substep_level;
+    let h = config.dt / f64::from(substeps);
+    for _ in 0..substeps {
+        used_barnes_hut |= advance(bodies, config, h, cache, extensions)?;
+    }
 
     Ok(IntegratorStepStats {
         used_barnes_hut,
-        dt_used: dt,
+        dt_used: config.dt,
+        substep_level,
+    })
+}
+
+/// Turns every alive body through `angular_velocity * dt`. Spin is torque-free
+/// between collisions, so this is exact.
+pub(crate) fn advance_spin(bodies: &mut [Body], dt: f64) {
+    for body in bodies.iter_mut().filter(|body| body.alive) {
+        if body.angular_velocity != 0.0 {
+            body.angle = (body.angle + body.angular_velocity * dt).rem_euclid(TAU);
+        }
+    }
+}
+
+fn advance(
+    bodies: &mut [Body],
+    config: &EngineConfig,
+    dt: f64,
+    cache: &mut ForceCache,
+    extensions: StepExtensions<'_>,
+) -> Result<bool> {
+    match config.integrator {
+        IntegratorKind::SemiImplicitEuler => {
+            semi_implicit_euler_step(bodies, config, dt, extensions)
+        }
+        IntegratorKind::VelocityVerlet => {
+            velocity_verlet_step(bodies, config, dt, cache, extensions)
+        }
+        IntegratorKind::Rk4 => rk4_step(bodies, config, dt, extensions),
+    }
+}
+
+/// Step doubling: compares one substep of `h` against two of `h / 2` and refines
+/// until the relative position error is within `dt_tolerance`. Trials use a scratch
+/// force cache so replaying a recorded level sees the same cache state.
+fn choose_substep_level(
+    bodies: &[Body],
+    config: &EngineConfig,
+    extensions: StepExtensions<'_>,
+) -> (u8, bool) {
+    let mut used_barnes_hut = false;
+    for level in 0..config.max_substep_level {
+        let h = config.dt / f64::from(1_u32 << level);
+        let mut full = bodies.to_vec();
+        let mut halves = bodies.to_vec();
+        let trial = advance(&mut full, config, h, &mut ForceCache::default(), extensions).and_then(
+            |full_bh| {
+                let mut scratch = ForceCache::default();
+                let first = advance(&mut halves, config, 0.5 * h, &mut scratch, extensions)?;
+                let second = advance(&mut halves, config, 0.5 * h, &mut scratch, extensions)?;
+                Ok(full_bh || first || second)
+            },
+        );
+        let Ok(trial_bh) = trial else {
+            continue;
+        };
+        used_barnes_hut |= trial_bh;
+        if step_error(bodies, &full, &halves) <= config.dt_tolerance {
+            return (level, used_barnes_hut);
+        }
+    }
+    (config.max_substep_level, used_barnes_hut)
+}
+
+/// Power-of-two timestep level of every body for the next tick: body `i` steps by
+/// `dt / 2^level`, with `dt / 2^level <= block_timestep_eta * |a| / |da/dt|`, capped
+/// at `max_substep_level`. Dead, fixed and unaccelerated bodies sit at level 0.
+pub(crate) fn block_levels(bodies: &[Body], config: &EngineConfig) -> Vec<u8> {
+    let mut levels = vec![0; bodies.len()];
+    let targets = (0..bodies.len())
+        .filter(|&index| bodies[index].alive && !bodies[index].fixed)
+        .collect::<Vec<_>>();
+    for (&index, derivatives) in targets
+        .iter()
+        .zip(gravity_derivatives(bodies, config, &targets))
+    {
+        let jerk = derivatives.jerk.norm();
+        if jerk == 0.0 {
+            continue;
+        }
+        let step = config.block_timestep_eta * derivatives.acceleration.norm() / jerk;
+        let level = (config.dt / step).log2().ceil();
+        levels[index] = if level.is_nan() || level <= 0.0 {
+            0
+        } else {
+            level.min(f64::from(config.max_substep_level)) as u8
+        };
+    }
+    levels
+}
+
+/// Hierarchical kick-drift-kick leapfrog over `2^L` fine substeps, `L` being the
+/// deepest level in use. Every body drifts each fine substep but is only kicked at the
+/// ends of its own step, so a body at level `k` evaluates forces `2^k` times per tick.
+/// All bodies are synchronized at the end of the tick, where levels are reassigned.
+///
+/// Gravity is always summed pairwise for the kicked bodies only; the configured
+/// integrator, gravity solver and force cache are bypassed, and of the stage hooks
+/// only `before_commit` runs.
+fn block_timestep_step(
+    bodies: &mut [Body],
+    config: &EngineConfig,
+    extensions: StepExtensions<'_>,
+) -> Result<IntegratorStepStats> {
+    let levels = block_levels(bodies, config);
+    let deepest = levels.iter().copied().max().unwrap_or(0);
+    let substeps = 1_u32 << deepest;
+    let h = config.dt / f64::from(substeps);
+    let stride = |index: usize| 1_u32 << (deepest - levels[index]);
+    let moving = |body: &Body| body.alive && !body.fixed;
+
+    let mut positions = bodies.iter().map(|body| body.position).collect::<Vec<_>>();
+    let mut velocities = bodies.iter().map(|body| body.velocity).collect::<Vec<_>>();
+    let everyone = (0..bodies.len())
+        .filter(|&index| moving(&bodies[index]))
+        .collect::<Vec<_>>();
+    let mut accelerations = vec![Vec2::ZERO; bodies.len()];
+    block_accelerations(
+        bodies,
+        &positions,
+        &velocities,
+        config,
+        extensions,
+        &everyone,
+        &mut accelerations,
+    );
+
+    for substep in 0..substeps {
+        for &index in &everyone {
+            if substep % stride(index) == 0 {
+                let half = 0.5 * h * f64::from(stride(index));
+                velocities[index] += accelerations[index] * half;
+            }
+        }
+        for &index in &everyone {
+            positions[index] += velocities[index] * h;
+        }
+        let finishing = everyone
+            .iter()
+            .copied()
+            .filter(|&index| (substep + 1) % stride(index) == 0)
+            .collect::<Vec<_>>();
+        block_accelerations(
+            bodies,
+            &positions,
+            &velocities,
+            config,
+            extensions,
+            &finishing,
+            &mut accelerations,
+        );
+        for &index in &finishing {
+            let half = 0.5 * h * f64::from(stride(index));
+            velocities[index] += accelerations[index] * half;
+        }
+    }
+    commit(
+        bodies, config, config.dt, 0, extensions, positions, velocities,
+    )?;
+
+    Ok(IntegratorStepStats {
+        used_barnes_hut: false,
+        dt_used: config.dt,
+        substep_level: deepest,
     })
 }
 
-fn effective_dt(bodies: &[Body], config: &EngineConfig) -> f64 {
+/// Overwrites the accelerations of `targets`: pairwise gravity (respecting interaction
+/// groups and periodic images) plus external forces.
+fn block_accelerations(
+    bodies: &[Body],
+    positions: &[Vec2],
+    velocities: &[Vec2],
+    config: &EngineConfig,
+    extensions: StepExtensions<'_>,
+    targets: &[usize],
+    accelerations: &mut [Vec2],
+) {
+    if targets.is_empty() {
+        return;
+    }
+    let softening = softening_squares(bodies, config.softening_epsilon);
+    let period = config.periodic_extent();
+    let mut external = vec![Vec2::ZERO; bodies.len()];
+    add_external_accelerations(
+        bodies,
+        positions,
+        velocities,
+        config,
+        extensions.providers,
+        &mut external,
+    );
+    for (acceleration, thrust) in external.iter_mut().zip(extensions.thrust) {
+        *acceleration += *thrust;
+    }
+    for &target in targets {
+        let mut acceleration = external[target];
+        for (source, body) in bodies.iter().enumerate() {
+            if source == target || !body.alive {
+                continue;
+            }
+            if let Some(groups) = &config.interaction_groups
+                && !groups.attracts(body.interaction_group(), bodies[target].interaction_group())
+            {
+                continue;
+            }
+            let delta = separation(positions[target], positions[source], period);
+            let epsilon2 = 0.5 * (softening[source] + softening[target]);
+            acceleration += delta
+                * (config.gravity_constant
+                    * body.mass
+                    * softened_inverse_cube(delta.norm_squared(), epsilon2));
+        }
+        accelerations[target] = acceleration;
+    }
+}
+
+fn central_body_index(bodies: &[Body], config: &EngineConfig) -> Option<usize> {
+    let id = config.central_body.as_ref()?;
+    bodies.iter().position(|body| body.alive && body.id == *id)
+}
+
+/// Kick-drift-kick in coordinates relative to the central body. Each drift follows
+/// every perturber's exact conic about the central body (`mu = G (M + m)`, unsoftened);
+/// the kicks carry perturber-perturber gravity, the indirect term from the central
+/// body's own acceleration, and external forces. The central body is recovered from
+/// the barycentre, which only external forces move. A fixed central body neither
+/// recoils nor adds its perturbers' mass to `mu`; fixed perturbers act as external
+/// attractors. Stage hooks other than `before_commit` are skipped.
+fn central_body_step(
+    bodies: &mut [Body],
+    config: &EngineConfig,
+    central: usize,
+    dt: f64,
+    extensions: StepExtensions<'_>,
+) -> Result<()> {
+    let frame = CentralFrame::new(bodies, config, central);
+    let mut offsets = bodies
+        .iter()
+        .map(|body| body.position - bodies[central].position)
+        .collect::<Vec<_>>();
+    let mut relative = bodies
+        .iter()
+        .map(|body| body.velocity - bodies[central].velocity)
+        .collect::<Vec<_>>();
+    let mut barycentre = frame.barycentre();
+
+    frame.kick(
+        0.5 * dt,
+        &offsets,
+        &mut relative,
+        &mut barycentre,
+        extensions,
+    );
+    for &index in &frame.moving {
+        let mu = frame.mu(index);
+        let Some((offset, velocity)) = propagate_kepler(offsets[index], relative[index], mu, dt)
+        else {
+            return Err(EngineError::NumericalInstability(format!(
+                "body '{}' has no Kepler orbit about the central body",
+                bodies[index].id
+            )));
+        };
+        offsets[index] = offset;
+        relative[index] = velocity;
+    }
+    barycentre.0 += barycentre.1 * dt;
+    frame.kick(
+        0.5 * dt,
+        &offsets,
+        &mut relative,
+        &mut barycentre,
+        extensions,
+    );
+
+    let (positions, velocities) = frame.inertial(&offsets, &relative, barycentre);
+    commit(bodies, config, dt, 0, extensions, positions, velocities)
+}
+
+struct CentralFrame<'a> {
+    bodies: &'a [Body],
+    config: &'a EngineConfig,
+    central: usize,
+    /// Alive, unfixed bodies other than the central one.
+    moving: Vec<usize>,
+    recoil: bool,
+    total_mass: f64,
+    softening: Vec<f64>,
+}
+
+impl<'a> CentralFrame<'a> {
+    fn new(bodies: &'a [Body], config: &'a EngineConfig, central: usize) -> Self {
+        let moving = (0..bodies.len())
+            .filter(|&index| index != central && bodies[index].alive && !bodies[index].fixed)
+            .collect::<Vec<_>>();
+        let total_mass =
+            bodies[central].mass + moving.iter().map(|&index| bodies[index].mass).sum::<f64>();
+        Self {
+            bodies,
+            config,
+            central,
+            moving,
+            recoil: !bodies[central].fixed,
+            total_mass,
+            softening: softening_squares(bodies, config.softening_epsilon),
+        }
+    }
+
+    fn mu(&self, index: usize) -> f64 {
+        let recoil_mass = if self.recoil {
+            self.bodies[index].mass
+        } else {
+            0.0
+        };
+        self.config.gravity_constant * (self.bodies[self.central].mass + recoil_mass)
+    }
+
+    /// Position and velocity of the centre of mass of the central and moving bodies.
+    fn barycentre(&self) -> (Vec2, Vec2) {
+        let members = self.moving.iter().chain([&self.central]);
+        let (mut position, mut velocity) = (Vec2::ZERO, Vec2::ZERO);
+        for &index in members {
+            position += self.bodies[index].position * self.bodies[index].mass;
+            velocity += self.bodies[index].velocity * self.bodies[index].mass;
+        }
+        (position / self.total_mass, velocity / self.total_mass)
+    }
+
+    fn inertial(
+        &self,
+        offsets: &[Vec2],
+        relative: &[Vec2],
+        barycentre: (Vec2, Vec2),
+    ) -> (Vec<Vec2>, Vec<Vec2>) {
+        let central = &self.bodies[self.central];
+        let (origin, origin_velocity) = if self.recoil {
+            let mut origin = barycentre;
+            for &index in &self.moving {
+                let share = self.bodies[index].mass / self.total_mass;
+                origin.0 -= offsets[index] * share;
+                origin.1 -= relative[index] * share;
+            }
+            origin
+        } else {
+            (central.position, central.velocity)
+        };
+        let mut positions = self
+            .bodies
+            .iter()
+            .map(|body| body.position)
+            .collect::<Vec<_>>();
+        let mut velocities = self
+            .bodies
+            .iter()
+            .map(|body| body.velocity)
+            .collect::<Vec<_>>();
+        for &index in &self.moving {
+            positions[index] = origin + offsets[index];
+            velocities[index] = origin_velocity + relative[index];
+        }
+        positions[self.central] = origin;
+        velocities[self.central] = origin_velocity;
+        (positions, velocities)
+    }
+
+    fn pull(&self, positions: &[Vec2], target: usize, source: usize, softened: bool) -> Vec2 {
+        let delta = positions[source] - positions[target];
+        let epsilon2 = if softened {
+            0.5 * (self.softening[source] + self.softening[target])
+        } else {
+            0.0
+        };
+        delta
+            * (self.config.gravity_constant
+                * self.bodies[source].mass
+                * softened_inverse_cube(delta.norm_squared(), epsilon2))
+    }
+
+    fn kick(
+        &self,
+        h: f64,
+        offsets: &[Vec2],
+        relative: &mut [Vec2],
+        barycentre: &mut (Vec2, Vec2),
+        extensions: StepExtensions<'_>,
+    ) {
+        let (positions, velocities) = self.inertial(offsets, relative, *barycentre);
+        let mut external = vec![Vec2::ZERO; self.bodies.len()];
+        add_external_accelerations(
+            self.bodies,
+            &positions,
+            &velocities,
+            self.config,
+            extensions.providers,
+            &mut external,
+        );
+        for (acceleration, thrust) in external.iter_mut().zip(extensions.thrust) {
+            *acceleration += *thrust;
+        }
+        let attracted = self
+            .moving
+            .iter()
+            .copied()
+            .chain(self.recoil.then_some(self.central))
+            .collect::<Vec<_>>();
+        for (source, body) in self.bodies.iter().enumerate() {
+            if source == self.central || !body.alive || !body.fixed {
+                continue;
+            }
+            for &target in &attracted {
+                external[target] += self.pull(&positions, target, source, true);
+            }
+        }
+
+        // The central body's acceleration, which every relative velocity must shed.
+        let mut indirect = Vec2::ZERO;
+        if self.recoil {
+            indirect = external[self.central];
+            for &index in &self.moving {
+                indirect += self.pull(&positions, self.central, index, false);
+            }
+        }
+        for &index in &self.moving {
+            let mut acceleration = external[index] - indirect;
+            if self.recoil {
+                // Already part of this body's own conic.
+                acceleration += self.pull(&positions, self.central, index, false);
+            }
+            for &other in self.moving.iter().filter(|&&other| other != index) {
+                acceleration += self.pull(&positions, index, other, true);
+            }
+            relative[index] += acceleration * h;
+        }
+        if self.recoil {
+            let net_force = attracted
+                .iter()
+                .map(|&index| external[index] * self.bodies[index].mass)
+                .sum::<Vec2>();
+            barycentre.1 += net_force * (h / self.total_mass);
+        }
+    }
+}
+
+fn step_error(start: &[Body], full: &[Body], halves: &[Body]) -> f64 {
+    let mut worst = 0.0_f64;
+    for ((origin, coarse), fine) in start.iter().zip(full).zip(halves) {
+        if !origin.alive {
+            continue;
+        }
+        // The floor keeps rounding noise on near-stationary bodies from forcing refinement.
+        let floor = f64::EPSILON.sqrt() * (origin.position.norm() + 1.0);
+        let displacement = (fine.position - origin.position).norm().max(floor);
+        worst = worst.max((fine.position - coarse.position).norm() / displacement);
+    }
+    worst
+}
+
+pub(crate) fn effective_dt(bodies: &[Body], config: &EngineConfig) -> f64 {
     if !matches!(config.dt_policy, DtPolicy::Adaptive) {
         return config.dt;
     }
@@ -60,93 +565,191 @@ fn effective_dt(bodies: &[Body], config: &EngineConfig) -> f64 {
     suggested.clamp(config.dt * 0.05, config.dt)
 }
 
-fn semi_implicit_euler_step(bodies: &mut [Body], config: &EngineConfig, dt: f64) -> Result<bool> {
-    let (accelerations, stats) = compute_accelerations(bodies, config);
+fn semi_implicit_euler_step(
+    bodies: &mut [Body],
+    config: &EngineConfig,
+    dt: f64,
+    extensions: StepExtensions<'_>,
+) -> Result<bool> {
+    let mut positions = bodies.iter().map(|body| body.position).collect::<Vec<_>>();
+    let mut velocities = bodies.iter().map(|body| body.velocity).collect::<Vec<_>>();
+    let (accelerations, stats) = run_stage(
+        &stage_context(bodies, config, dt, 0),
+        extensions,
+        &mut positions,
+        &mut velocities,
+        |p, v| evaluate_accelerations(bodies, p, v, config, extensions.providers),
+    );
 
-    for (index, body) in bodies.iter_mut().enumerate() {
+    for (index, body) in bodies.iter().enumerate() {
         if !body.alive {
             continue;
         }
-        body.velocity += accelerations[index] * dt;
-        body.position += body.velocity * dt;
-        ensure_finite_body(body)?;
+        velocities[index] += accelerations[index] * dt;
+        positions[index] += velocities[index] * dt;
     }
+    commit(bodies, config, dt, 0, extensions, positions, velocities)?;
 
     Ok(matches!(stats.mode, SolverRuntimeMode::BarnesHut))
 }
 
-fn velocity_verlet_step(bodies: &mut [Body], config: &EngineConfig, dt: f64) -> Result<bool> {
-    let original_positions = bodies.iter().map(|body| body.position).collect::<Vec<_>>();
-    let (accelerations_0, stats_0) =
-        compute_accelerations_with_config(bodies, &original_positions, config);
+fn velocity_verlet_step(
+    bodies: &mut [Body],
+    config: &EngineConfig,
+    dt: f64,
+    cache: &mut ForceCache,
+    extensions: StepExtensions<'_>,
+) -> Result<bool> {
+    let mut original_positions = bodies.iter().map(|body| body.position).collect::<Vec<_>>();
+    let mut original_velocities = bodies.iter().map(|body| body.velocity).collect::<Vec<_>>();
+    // The second-stage gravity of the previous tick is evaluated at exactly these
+    // positions, so the cache usually turns this stage into a copy.
+    let (accelerations_0, stats_0) = run_stage(
+        &stage_context(bodies, config, dt, 0),
+        extensions,
+        &mut original_positions,
+        &mut original_velocities,
+        |p, v| {
+            let (mut accelerations, stats) = cache.gravity(bodies, p, config);
+            add_external_accelerations(
+                bodies,
+                p,
+                v,
+                config,
+                extensions.providers,
+                &mut accelerations,
+            );
+            (accelerations, stats)
+        },
+    );
 
     let mut predicted_positions = original_positions.clone();
+    let mut predicted_velocities = original_velocities.clone();
     for (index, body) in bodies.iter().enumerate() {
         if !body.alive {
             continue;
         }
-        predicted_positions[index] =
-            body.position + body.velocity * dt + accelerations_0[index] * (0.5 * dt * dt);
+        predicted_positions[index] = original_positions[index]
+            + original_velocities[index] * dt
+            + accelerations_0[index] * (0.5 * dt * dt);
+        predicted_velocities[index] = original_velocities[index] + accelerations_0[index] * dt;
     }
 
-    let (accelerations_1, stats_1) =
-        compute_accelerations_with_config(bodies, &predicted_positions, config);
+    let (accelerations_1, stats_1) = run_stage(
+        &stage_context(bodies, config, dt, 1),
+        extensions,
+        &mut predicted_positions,
+        &mut predicted_velocities,
+        |p, v| {
+            let (mut accelerations, stats) = cache.gravity(bodies, p, config);
+            add_external_accelerations(
+                bodies,
+                p,
+                v,
+                config,
+                extensions.providers,
+                &mut accelerations,
+            );
+            (accelerations, stats)
+        },
+    );
 
-    for (index, body) in bodies.iter_mut().enumerate() {
+    let mut velocities = original_velocities;
+    for (index, body) in bodies.iter().enumerate() {
         if !body.alive {
             continue;
         }
-        body.position = predicted_positions[index];
-        body.velocity += (accelerations_0[index] + accelerations_1[index]) * (0.5 * dt);
-        ensure_finite_body(body)?;
+        velocities[index] += (accelerations_0[index] + accelerations_1[index]) * (0.5 * dt);
     }
+    commit(
+        bodies,
+        config,
+        dt,
+        1,
+        extensions,
+        predicted_positions,
+        velocities,
+    )?;
 
     Ok(matches!(stats_0.mode, SolverRuntimeMode::BarnesHut)
         || matches!(stats_1.mode, SolverRuntimeMode::BarnesHut))
 }
 
-fn rk4_step(bodies: &mut [Body], config: &EngineConfig, dt: f64) -> Result<bool> {
+fn rk4_step(
+    bodies: &mut [Body],
+    config: &EngineConfig,
+    dt: f64,
+    extensions: StepExtensions<'_>,
+) -> Result<bool> {
     let count = bodies.len();
-    let p0 = bodies.iter().map(|body| body.position).collect::<Vec<_>>();
-    let v0 = bodies.iter().map(|body| body.velocity).collect::<Vec<_>>();
+    let evaluate =
+        |p: &[Vec2], v: &[Vec2]| evaluate_accelerations(bodies, p, v, config, extensions.providers);
+    let mut p0 = bodies.iter().map(|body| body.position).collect::<Vec<_>>();
+    let mut v0 = bodies.iter().map(|body| body.velocity).collect::<Vec<_>>();
 
-    let (a1, stats_1) = compute_accelerations_with_config(bodies, &p0, config);
+    let (a1, stats_1) = run_stage(
+        &stage_context(bodies, config, dt, 0),
+        extensions,
+        &mut p0,
+        &mut v0,
+        evaluate,
+    );
     let k1p = v0.clone();
     let k1v = a1;
 
-    let p2 = (0..count)
+    let mut p2 = (0..count)
         .map(|i| p0[i] + k1p[i] * (0.5 * dt))
         .collect::<Vec<_>>();
-    let v2 = (0..count)
+    let mut v2 = (0..count)
         .map(|i| v0[i] + k1v[i] * (0.5 * dt))
         .collect::<Vec<_>>();
-    let (k2v, stats_2) = compute_accelerations_with_config(bodies, &p2, config);
+    let (k2v, stats_2) = run_stage(
+        &stage_context(bodies, config, dt, 1),
+        extensions,
+        &mut p2,
+        &mut v2,
+        evaluate,
+    );
     let k2p = v2;
 
-    let p3 = (0..count)
+    let mut p3 = (0..count)
         .map(|i| p0[i] + k2p[i] * (0.5 * dt))
         .collect::<Vec<_>>();
-    let v3 = (0..count)
+    let mut v3 = (0..count)
         .map(|i| v0[i] + k2v[i] * (0.5 * dt))
         .collect::<Vec<_>>();
-    let (k3v, stats_3) = compute_accelerations_with_config(bodies, &p3, config);
+    let (k3v, stats_3) = run_stage(
+        &stage_context(bodies, config, dt, 2),
+        extensions,
+        &mut p3,
+        &mut v3,
+        evaluate,
+    );
     let k3p = v3;
 
-    let p4 = (0..count).map(|i| p0[i] + k3p[i] * dt).collect::<Vec<_>>();
-    let v4 = (0..count).map(|i| v0[i] + k3v[i] * dt).collect::<Vec<_>>();
-    let (k4v, stats_4) = compute_accelerations_with_config(bodies, &p4, config);
+    let mut p4 = (0..count).map(|i| p0[i] + k3p[i] * dt).collect::<Vec<_>>();
+    let mut v4 = (0..count).map(|i| v0[i] + k3v[i] * dt).collect::<Vec<_>>();
+    let (k4v, stats_4) = run_stage(
+        &stage_context(bodies, config, dt, 3),
+        extensions,
+        &mut p4,
+        &mut v4,
+        evaluate,
+    );
     let k4p = v4;
 
+    let mut positions = p0;
+    let mut velocities = v0;
     for i in 0..count {
         if !bodies[i].alive {
             continue;
         }
         let dp = (k1p[i] + k2p[i] * 2.0 + k3p[i] * 2.0 + k4p[i]) * (dt / 6.0);
         let dv = (k1v[i] + k2v[i] * 2.0 + k3v[i] * 2.0 + k4v[i]) * (dt / 6.0);
-        bodies[i].position += dp;
-        bodies[i].velocity += dv;
-        ensure_finite_body(&bodies[i])?;
+        positions[i] += dp;
+        velocities[i] += dv;
     }
+    commit(bodies, config, dt, 3, extensions, positions, velocities)?;
 
     Ok(matches!(stats_1.mode, SolverRuntimeMode::BarnesHut)
         || matches!(stats_2.mode, SolverRuntimeMode::BarnesHut)
@@ -154,8 +757,80 @@ fn rk4_step(bodies: &mut [Body], config: &EngineConfig, dt: f64) -> Result<bool>
         || matches!(stats_4.mode, SolverRuntimeMode::BarnesHut))
 }
 
+fn stage_context<'a>(
+    bodies: &'a [Body],
+    config: &EngineConfig,
+    dt: f64,
+    stage: u32,
+) -> StageContext<'a> {
+    StageContext {
+        integrator: config.integrator,
+        stage,
+        dt,
+        bodies,
+    }
+}
+
+/// One force evaluation wrapped in the `before_forces` / `after_forces` hooks. Fixed
+/// bodies are held at their committed position and feel no acceleration.
+fn run_stage(
+    context: &StageContext<'_>,
+    extensions: StepExtensions<'_>,
+    positions: &mut [Vec2],
+    velocities: &mut [Vec2],
+    evaluate: impl FnOnce(&[Vec2], &[Vec2]) -> (Vec<Vec2>, SolverStats),
+) -> (Vec<Vec2>, SolverStats) {
+    for (index, body) in context.bodies.iter().enumerate() {
+        if body.fixed {
+            positions[index] = body.position;
+        }
+    }
+    extensions
+        .hooks
+        .before_forces(context, positions, velocities);
+    let (mut accelerations, stats) = evaluate(positions, velocities);
+    for (acceleration, thrust) in accelerations.iter_mut().zip(extensions.thrust) {
+        *acceleration += *thrust;
+    }
+    for (acceleration, body) in accelerations.iter_mut().zip(context.bodies) {
+        if body.fixed {
+            *acceleration = Vec2::ZERO;
+        }
+    }
+    extensions
+        .hooks
+        .after_forces(context, positions, velocities, &mut accelerations);
+    (accelerations, stats)
+}
+
+/// Runs the `before_commit` hooks and writes the new state of alive, unfixed bodies.
+fn commit(
+    bodies: &mut [Body],
+    config: &EngineConfig,
+    dt: f64,
+    last_stage: u32,
+    extensions: StepExtensions<'_>,
+    mut positions: Vec<Vec2>,
+    mut velocities: Vec<Vec2>,
+) -> Result<()> {
+    extensions.hooks.before_commit(
+        &stage_context(bodies, config, dt, last_stage),
+        &mut positions,
+        &mut velocities,
+    );
+    for ((body, position), velocity) in bodies.iter_mut().zip(positions).zip(velocities) {
+        if !body.alive || body.fixed {
+            continue;
+        }
+        body.position = position;
+        body.velocity = velocity;
+        ensure_finite_body(body)?;
+    }
+    Ok(())
+}
+
 fn ensure_finite_body(body: &Body) -> Result<()> {
-    if !body.position.is_finite() || !body.velocity.is_finite() {
+    if !body.is_finite() {
         return Err(EngineError::NumericalInstability(format!(
             "body '{}' produced non-finite state",
             body.id
@@ -0,0 +1,76 @@
+//! Ordered record of the commands that changed an engine since its state was last
+//! set from outside, so an interactive session can be replayed exactly.
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::EngineConfig;
+use crate::postmortem::HistoryFrame;
+use crate::types::{BodyEdit, Snapshot, TimeMarker};
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "command", rename_all = "camelCase")]
+pub enum JournalCommand {
+    /// Consecutive `step` calls are folded into one entry.
+    Step {
+        ticks: u32,
+    },
+    ApplyEdit {
+        edit: Box<BodyEdit>,
+    },
+    ApplyEdits {
+        edits: Vec<BodyEdit>,
+    },
+    /// Also recorded for `update_config`, with the patched config.
+    SetConfig {
+        config: Box<EngineConfig>,
+    },
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JournalEntry {
+    /// Engine tick when the command was issued.
+    pub tick: u64,
+    #[serde(flatten)]
+    pub command: JournalCommand,
+}
+
+/// Starts from `config` and `start`, taken when the engine was created or its state
+/// was last replaced (scenario load, snapshot or checkpoint restore, `step_back`).
+/// Zones, force providers, hooks and maneuvers are not part of the journal.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Journal {
+    pub config: EngineConfig,
+    pub start: Snapshot,
+    pub entries: Vec<JournalEntry>,
+}
+
+/// The engine's side of the journal; the start state only becomes a `Snapshot` on
+/// export.
+#[derive(Clone, Debug)]
+pub(crate) struct JournalRecorder {
+    pub(crate) config: EngineConfig,
+    pub(crate) start: HistoryFrame,
+    pub(crate) markers: Vec<TimeMarker>,
+    pub(crate) entries: Vec<JournalEntry>,
+}
+
+impl JournalRecorder {
+    pub(crate) fn record(&mut self, tick: u64, command: JournalCommand) {
+        if let (
+            JournalCommand::Step { ticks },
+            Some(JournalEntry {
+                tick: last_tick,
+                command: JournalCommand::Step { ticks: last_ticks },
+            }),
+        ) = (&command, self.entries.last_mut())
+            && *last_tick + u64::from(*last_ticks) == tick
+            && let Some(total) = last_ticks.checked_add(*ticks)
+        {
+            *last_ticks = total;
+            return;
+        }
+        self.entries.push(JournalEntry { tick, command });
+    }
+}
@@ -0,0 +1,133 @@
+//! Circular restricted three-body geometry of a pair of bodies: the five Lagrange
+//! points and the effective potential in the frame co-rotating with the pair.
+
+use serde::{Deserialize, Serialize};
+
+use crate::errors::{EngineError, Result};
+use crate::grid::GridSpec;
+use crate::math::Vec2;
+use crate::types::Body;
+
+/// Positions in simulation coordinates. L4 leads the secondary along its orbit and
+/// L5 trails it.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LagrangePoints {
+    pub l1: Vec2,
+    pub l2: Vec2,
+    pub l3: Vec2,
+    pub l4: Vec2,
+    pub l5: Vec2,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LagrangeMap {
+    pub primary_id: String,
+    pub secondary_id: String,
+    pub barycenter: Vec2,
+    /// Rate of the co-rotating frame, counter-clockwise positive.
+    pub angular_rate: f64,
+    pub points: LagrangePoints,
+    /// Effective potential at every cell centre of the requested grid, laid out as in
+    /// [`GridSpec`]; empty when no grid was requested.
+    pub potential: Vec<f64>,
+}
+
+/// The pair's current separation and relative velocity define the frame, as for
+/// [`crate::diagnostics::jacobi_constants`]; points are exact only for circular
+/// primaries. The potential `-G m1 / r1 - G m2 / r2 - w^2 r^2 / 2` is softened by
+/// `softening` so cells on a primary stay finite.
+pub fn lagrange_map(
+    primary: &Body,
+    secondary: &Body,
+    gravity_constant: f64,
+    softening: f64,
+    grid: Option<&GridSpec>,
+) -> Result<LagrangeMap> {
+    let pair_mass = primary.mass + secondary.mass;
+    let separation = secondary.position - primary.position;
+    let distance = separation.norm();
+    if primary.mass <= 0.0 || secondary.mass <= 0.0 || distance <= 0.0 {
+        return Err(EngineError::InvalidConfig(
+            "lagrange points need two separated bodies with positive mass".to_string(),
+        ));
+    }
+    let mu = secondary.mass / pair_mass;
+    let barycenter =
+        (primary.position * primary.mass + secondary.position * secondary.mass) / pair_mass;
+    let angular_rate =
+        separation.cross(secondary.velocity - primary.velocity) / (distance * distance);
+
+    // Normalised frame: barycentre at the origin, primary at -mu, secondary at 1 - mu.
+    let axis = separation / distance;
+    let leading = if angular_rate < 0.0 {
+        -axis.perp()
+    } else {
+        axis.perp()
+    };
+    let place = |x: f64, y: f64| barycenter + (axis * x + leading * y) * distance;
+    let height = 0.75_f64.sqrt();
+    let points = LagrangePoints {
+        l1: place(collinear_point(mu, -mu, 1.0 - mu), 0.0),
+        l2: place(collinear_point(mu, 1.0 - mu, 2.0), 0.0),
+        l3: place(collinear_point(mu, -2.0, -mu), 0.0),
+        l4: place(0.5 - mu, height),
+        l5: place(0.5 - mu, -height),
+    };
+
+    let potential = match grid {
+        Some(spec) => {
+            spec.validate()?;
+            let softening_sq = softening * softening;
+            let pull = |body: &Body, point: Vec2| {
+                gravity_constant * body.mass
+                    / (point.distance_squared(body.position) + softening_sq).sqrt()
+            };
+            (0..spec.cell_count())
+                .map(|index| {
+                    let point = spec.cell_center(index);
+                    -pull(primary, point)
+                        - pull(secondary, point)
+                        - 0.5 * angular_rate * angular_rate * point.distance_squared(barycenter)
+                })
+                .collect()
+        }
+        None => Vec::new(),
+    };
+
+    Ok(LagrangeMap {
+        primary_id: primary.id.clone(),
+        secondary_id: secondary.id.clone(),
+        barycenter,
+        angular_rate,
+        points,
+        potential,
+    })
+}
+
+/// Root of the collinear equilibrium condition strictly between `low` and `high`.
+/// The condition increases monotonically between the primaries' singularities, so
+/// bisection always converges.
+fn collinear_point(mu: f64, mut low: f64, mut high: f64) -> f64 {
+    let balance = |x: f64| {
+        let (to_primary, to_secondary) = (x + mu, x - 1.0 + mu);
+        x - (1.0 - mu) * to_primary / to_primary.abs().powi(3)
+            - mu * to_secondary / to_secondary.abs().powi(3)
+    };
+    loop {
+        let mid = 0.5 * (low + high);
+        if mid <= low || mid >= high {
+            return mid;
+        }
+        let value = balance(mid);
+        if value == 0.0 {
+            return mid;
+        }
+        if value < 0.0 {
+            low = mid;
+        } else {
+            high = mid;
+        }
+    }
+}
@@ -1,18 +1,127 @@
+pub mod accuracy;
+pub mod alignment;
+pub mod analysis;
+#[cfg(feature = "bench-utils")]
+pub mod bench_utils;
+pub mod binary;
+mod boundary;
+pub mod camera;
+pub mod catalog;
+pub mod checkpoint;
+pub mod clock;
+mod coarsening;
 pub mod collision;
+pub mod comparison;
 pub mod config;
+pub mod diagnostics;
 pub mod engine;
+pub mod engine3d;
+pub mod epochs;
 pub mod errors;
+pub mod events;
+pub mod excursions;
+pub mod export;
 pub mod ffi;
+#[cfg(feature = "ffi-audit")]
+mod ffi_audit;
+mod force_cache;
+pub mod forces;
+pub mod grid;
+pub mod history;
+pub mod hooks;
 pub mod integrator;
+pub mod journal;
+pub mod lagrange;
+pub mod lyapunov;
+pub mod maneuvers;
 pub mod math;
+pub mod netcode;
+pub mod octree;
+pub mod orbits;
+mod perf;
+pub mod playlist;
+pub mod poincare;
+pub mod postmortem;
+pub mod query;
+pub mod random;
+pub mod runner;
+#[cfg(feature = "schema")]
+pub mod schema;
+pub mod search;
+mod slots;
+pub mod softbody;
 pub mod solver;
+pub mod stopping;
+pub mod stress;
+pub mod tidal;
 pub mod types;
+pub mod units;
+pub mod zones;
 
-pub use config::{CollisionMode, DtPolicy, EngineConfig, GravitySolver, IntegratorKind};
+pub use accuracy::{AccuracyReport, BodyAccuracy, LevelError, accuracy_report};
+pub use alignment::AlignmentWatch;
+pub use analysis::{
+    CollisionRateEstimate, CollisionRateQuery, FlybyQuery, FlybyReport, analyze_flybys,
+    estimate_collision_rate,
+};
+pub use catalog::{CatalogEntry, CatalogPage, CatalogQuery, ScenarioCatalog};
+pub use checkpoint::{CheckpointInfo, RewindMethod};
+pub use clock::SimClock;
+pub use comparison::{ComparisonReport, Divergence, MultiEngineRunner, VariantReport};
+pub use config::{
+    AngularMomentumGuard, BinaryDetection, BoundaryMode, CoarseGraining, CollisionMode,
+    ConfigChange, ConfigDiff, ConfigPatch, DtPolicy, EnergyWatchdog, EngineConfig, EscapePolicy,
+    ExcursionTracking, ForceCaching, ForceErrorSampling, GravitySolver, GroupRule,
+    InstabilityCapture, IntegratorKind, InteractionGroups, ThetaTarget, ThetaTuning,
+    TidalDisruption, WorldBoundary,
+};
+pub use diagnostics::{
+    Diagnostics, ForceErrorStats, GroupDiagnostics, JacobiSample, MassBin, MassDistribution,
+    MassHistogramOptions,
+};
 pub use engine::SimulationEngine;
-pub use errors::{EngineError, Result};
-pub use math::Vec2;
+pub use engine3d::{Body3, SimulationEngine3, SimulationState3};
+pub use epochs::{Epoch, validate_epochs};
+pub use errors::{EngineError, ErrorCode, Result};
+pub use events::{
+    AggregationEvent, AlignmentEvent, BinaryEvent, BoundaryEvent, CollisionEvent, CollisionKind,
+    EpochEvent, EscapeEvent, EventFilter, EventLog, EventOverflow, ExcursionEvent, ImpactReport,
+    MergeCause, MergeRecord, PlaylistEvent, PushOutcome, SimulationEvent, TidalDisruptionEvent,
+    ZoneEvent,
+};
+pub use excursions::{ExcursionRecord, ExcursionSummary};
+pub use export::{DiagnosticsColumn, DiagnosticsRow, TrajectoryColumn};
+pub use forces::{BodyDerivatives, ForceProvider, force_magnitude, softening_radius};
+pub use grid::{CellKinematics, FieldGrid, GridSpec};
+pub use hooks::{StageContext, StageHook};
+pub use journal::{Journal, JournalCommand, JournalEntry};
+pub use lagrange::{LagrangeMap, LagrangePoints, lagrange_map};
+pub use lyapunov::{LyapunovEstimate, LyapunovQuery};
+pub use maneuvers::{Maneuver, ScheduledManeuver, Thrust};
+pub use math::{Transform2, Vec2, Vec3};
+pub use netcode::{
+    ReplayCheckpoint, ReplayMismatch, ReplayVerification, RollbackReport, RollbackSession,
+    verify_replay,
+};
+pub use orbits::{
+    OrbitPlacement, OrbitalElements, orbit_state, orbital_elements, propagate_kepler,
+};
+pub use perf::TickCostEstimate;
+pub use playlist::{Playlist, PlaylistEntry, PlaylistRunner, PlaylistTransition};
+pub use poincare::{PoincareSample, PoincareSection};
+pub use postmortem::{BodyStateSample, InstabilityReport};
+pub use query::{BodyQuery, BodyQueryResult};
+pub use random::{CloudShape, CloudSpec, PerturbSpec, Xoshiro256, generate_cloud};
+pub use runner::{EngineRunner, RunnerCommand, RunnerOutput, RunnerReply};
+pub use search::{SearchCandidate, StabilityScore, StableSearch, search_stable};
+pub use softbody::{SoftBody, SoftBodyShape, SoftBodySpec, Spring, SpringNetwork, build_soft_body};
+pub use stopping::{RunOutcome, StopCondition};
+pub use stress::{OperationLatency, StressReport, StressWorkload, run_stress};
+pub use tidal::roche_limit;
 pub use types::{
-    Body, BodyEdit, BodyMetadata, BodyUpdate, Scenario, ScenarioMetadata, SimulationState,
-    Snapshot, StepSummary,
+    Body, BodyEdit, BodyMetadata, BodyUpdate, DtSchedule, Frame, Oblateness, RemovedBodies,
+    Scenario, ScenarioMetadata, SimulationState, Snapshot, StepSummary, TimeMarker,
+    TrajectorySample,
 };
+pub use units::{Dimension, PhysicalConstants, UnitSystem};
+pub use zones::{Region, Zone};
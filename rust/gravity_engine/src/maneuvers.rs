@@ -0,0 +1,121 @@
+//! Scheduled thrust on individual bodies: finite burns at a constant acceleration,
+//! or a total velocity change spread over a burn (or applied at once).
+
+use serde::{Deserialize, Serialize};
+
+use crate::errors::{EngineError, Result};
+use crate::math::Vec2;
+use crate::types::Body;
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub enum Thrust {
+    /// Held for the whole burn.
+    Acceleration(Vec2),
+    /// Total velocity change, delivered evenly over the burn.
+    DeltaV(Vec2),
+}
+
+/// A burn starting at `start_time` (simulation time) and lasting `duration`. A
+/// `deltaV` burn with zero duration is impulsive and lands in the tick containing
+/// `start_time`.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct Maneuver {
+    pub start_time: f64,
+    #[serde(default)]
+    pub duration: f64,
+    #[serde(flatten)]
+    pub thrust: Thrust,
+}
+
+impl Maneuver {
+    pub fn validate(&self) -> Result<()> {
+        let vector = match self.thrust {
+            Thrust::Acceleration(vector) | Thrust::DeltaV(vector) => vector,
+        };
+        if !(self.start_time.is_finite()
+            && self.duration.is_finite()
+            && self.duration >= 0.0
+            && vector.is_finite())
+        {
+            return Err(EngineError::InvalidConfig(
+                "maneuver needs a finite start time, vector and duration >= 0".to_string(),
+            ));
+        }
+        if matches!(self.thrust, Thrust::Acceleration(_)) && self.duration == 0.0 {
+            return Err(EngineError::InvalidConfig(
+                "acceleration maneuvers need a duration > 0".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    pub fn end_time(&self) -> f64 {
+        self.start_time + self.duration
+    }
+
+    /// True once every tick that could still deliver thrust lies before `sim_time`.
+    pub fn finished(&self, sim_time: f64) -> bool {
+        if self.duration == 0.0 {
+            self.start_time < sim_time
+        } else {
+            self.end_time() <= sim_time
+        }
+    }
+
+    /// Velocity change delivered between `from` and `to`.
+    fn delta_v_between(&self, from: f64, to: f64) -> Vec2 {
+        if self.duration == 0.0 {
+            let lands = (from..to).contains(&self.start_time);
+            return match self.thrust {
+                Thrust::DeltaV(delta_v) if lands => delta_v,
+                _ => Vec2::ZERO,
+            };
+        }
+        let overlap = (to.min(self.end_time()) - from.max(self.start_time)).max(0.0);
+        match self.thrust {
+            Thrust::Acceleration(acceleration) => acceleration * overlap,
+            Thrust::DeltaV(delta_v) => delta_v * (overlap / self.duration),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScheduledManeuver {
+    pub id: u64,
+    pub body_id: String,
+    pub maneuver: Maneuver,
+}
+
+/// Thrust of every body over the tick `[sim_time, sim_time + dt)`, as the constant
+/// acceleration that delivers the same velocity change. Empty when nothing burns.
+pub(crate) fn tick_thrust(
+    scheduled: &[ScheduledManeuver],
+    bodies: &[Body],
+    sim_time: f64,
+    dt: f64,
+) -> Vec<Vec2> {
+    if scheduled.is_empty() || dt <= 0.0 {
+        return Vec::new();
+    }
+    let mut thrust = vec![Vec2::ZERO; bodies.len()];
+    let mut burning = false;
+    for entry in scheduled {
+        let delta_v = entry.maneuver.delta_v_between(sim_time, sim_time + dt);
+        if delta_v == Vec2::ZERO {
+            continue;
+        }
+        if let Some(index) = bodies
+            .iter()
+            .position(|body| body.alive && body.id == entry.body_id)
+        {
+            thrust[index] += delta_v / dt;
+            burning = true;
+        }
+    }
+    if burning { thrust } else { Vec::new() }
+}
@@ -1,7 +1,11 @@
 use serde::{Deserialize, Serialize};
-use std::ops::{Add, AddAssign, Div, Mul, Sub, SubAssign};
+use std::iter::Sum;
+
+use crate::types::Body;
+use std::ops::{Add, AddAssign, Div, Mul, Neg, Sub, SubAssign};
 
 #[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct Vec2 {
     pub x: f64,
     pub y: f64,
@@ -38,6 +42,45 @@ impl Vec2 {
     pub fn is_finite(self) -> bool {
         self.x.is_finite() && self.y.is_finite()
     }
+
+    /// Unit vector at `angle` radians from the +x axis.
+    pub fn from_angle(angle: f64) -> Self {
+        let (sin, cos) = angle.sin_cos();
+        Self::new(cos, sin)
+    }
+
+    /// Angle from the +x axis in `(-pi, pi]`.
+    pub fn angle(self) -> f64 {
+        self.y.atan2(self.x)
+    }
+
+    /// Counter-clockwise rotation by `angle` radians.
+    pub fn rotate(self, angle: f64) -> Self {
+        let (sin, cos) = angle.sin_cos();
+        Self::new(self.x * cos - self.y * sin, self.x * sin + self.y * cos)
+    }
+
+    /// The vector rotated a quarter turn counter-clockwise.
+    pub fn perp(self) -> Self {
+        Self::new(-self.y, self.x)
+    }
+
+    /// z component of the 3D cross product; positive when `other` is counter-clockwise.
+    pub fn cross(self, other: Self) -> f64 {
+        self.x * other.y - self.y * other.x
+    }
+
+    pub fn lerp(self, other: Self, t: f64) -> Self {
+        self + (other - self) * t
+    }
+
+    pub fn distance(self, other: Self) -> f64 {
+        (other - self).norm()
+    }
+
+    pub fn distance_squared(self, other: Self) -> f64 {
+        (other - self).norm_squared()
+    }
 }
 
 impl Add for Vec2 {
@@ -78,6 +121,14 @@ impl Mul<f64> for Vec2 {
     }
 }
 
+impl Mul<Vec2> for f64 {
+    type Output = Vec2;
+
+    fn mul(self, rhs: Vec2) -> Self::Output {
+        rhs * self
+    }
+}
+
 impl Div<f64> for Vec2 {
     type Output = Self;
 
@@ -85,3 +136,243 @@ impl Div<f64> for Vec2 {
         Self::new(self.x / rhs, self.y / rhs)
     }
 }
+
+impl Neg for Vec2 {
+    type Output = Self;
+
+    fn neg(self) -> Self::Output {
+        Self::new(-self.x, -self.y)
+    }
+}
+
+impl Sum for Vec2 {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self::ZERO, Add::add)
+    }
+}
+
+impl<'a> Sum<&'a Vec2> for Vec2 {
+    fn sum<I: Iterator<Item = &'a Self>>(iter: I) -> Self {
+        iter.copied().sum()
+    }
+}
+
+/// Similarity transform with a Galilean boost: positions are scaled, rotated and then
+/// translated; velocities are rotated and boosted but not scaled.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct Transform2 {
+    /// Counter-clockwise rotation in radians.
+    #[serde(default)]
+    pub rotation: f64,
+    #[serde(default = "unit_scale")]
+    pub scale: f64,
+    #[serde(default)]
+    pub translation: Vec2,
+    #[serde(default)]
+    pub velocity_boost: Vec2,
+}
+
+fn unit_scale() -> f64 {
+    1.0
+}
+
+impl Default for Transform2 {
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}
+
+impl Transform2 {
+    pub const IDENTITY: Self = Self {
+        rotation: 0.0,
+        scale: 1.0,
+        translation: Vec2::ZERO,
+        velocity_boost: Vec2::ZERO,
+    };
+
+    pub fn translation(offset: Vec2) -> Self {
+        Self {
+            translation: offset,
+            ..Self::IDENTITY
+        }
+    }
+
+    pub fn rotation(angle: f64) -> Self {
+        Self {
+            rotation: angle,
+            ..Self::IDENTITY
+        }
+    }
+
+    pub fn scaling(scale: f64) -> Self {
+        Self {
+            scale,
+            ..Self::IDENTITY
+        }
+    }
+
+    pub fn boost(velocity: Vec2) -> Self {
+        Self {
+            velocity_boost: velocity,
+            ..Self::IDENTITY
+        }
+    }
+
+    pub fn is_valid(&self) -> bool {
+        self.rotation.is_finite()
+            && self.scale.is_finite()
+            && self.scale > 0.0
+            && self.translation.is_finite()
+            && self.velocity_boost.is_finite()
+    }
+
+    pub fn apply_point(&self, point: Vec2) -> Vec2 {
+        self.apply_vector(point) + self.translation
+    }
+
+    /// Rotates and scales a displacement; translation does not apply.
+    pub fn apply_vector(&self, vector: Vec2) -> Vec2 {
+        vector.rotate(self.rotation) * self.scale
+    }
+
+    pub fn apply_velocity(&self, velocity: Vec2) -> Vec2 {
+        velocity.rotate(self.rotation) + self.velocity_boost
+    }
+
+    /// Moves the body's position and velocity and turns its orientation; mass and
+    /// radius are left unchanged.
+    pub fn apply_to_body(&self, body: &mut Body) {
+        body.position = self.apply_point(body.position);
+        body.velocity = self.apply_velocity(body.velocity);
+        if self.rotation != 0.0 {
+            body.angle = (body.angle + self.rotation).rem_euclid(std::f64::consts::TAU);
+        }
+    }
+
+    /// `self` followed by `next`.
+    pub fn then(&self, next: &Self) -> Self {
+        Self {
+            rotation: self.rotation + next.rotation,
+            scale: self.scale * next.scale,
+            translation: next.apply_point(self.translation),
+            velocity_boost: next.apply_velocity(self.velocity_boost),
+        }
+    }
+
+    pub fn inverse(&self) -> Self {
+        Self {
+            rotation: -self.rotation,
+            scale: self.scale.recip(),
+            translation: -self.translation.rotate(-self.rotation) / self.scale,
+            velocity_boost: -self.velocity_boost.rotate(-self.rotation),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Vec3 {
+    pub x: f64,
+    pub y: f64,
+    pub z: f64,
+}
+
+impl Vec3 {
+    pub const ZERO: Self = Self {
+        x: 0.0,
+        y: 0.0,
+        z: 0.0,
+    };
+
+    pub const fn new(x: f64, y: f64, z: f64) -> Self {
+        Self { x, y, z }
+    }
+
+    pub fn dot(self, other: Self) -> f64 {
+        self.x * other.x + self.y * other.y + self.z * other.z
+    }
+
+    pub fn cross(self, other: Self) -> Self {
+        Self::new(
+            self.y * other.z - self.z * other.y,
+            self.z * other.x - self.x * other.z,
+            self.x * other.y - self.y * other.x,
+        )
+    }
+
+    pub fn norm_squared(self) -> f64 {
+        self.dot(self)
+    }
+
+    pub fn norm(self) -> f64 {
+        self.norm_squared().sqrt()
+    }
+
+    pub fn normalized_or(self, fallback: Self) -> Self {
+        let length = self.norm();
+        if length > 0.0 {
+            self / length
+        } else {
+            fallback
+        }
+    }
+
+    pub fn is_finite(self) -> bool {
+        self.x.is_finite() && self.y.is_finite() && self.z.is_finite()
+    }
+}
+
+impl From<Vec2> for Vec3 {
+    fn from(value: Vec2) -> Self {
+        Self::new(value.x, value.y, 0.0)
+    }
+}
+
+impl Add for Vec3 {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        Self::new(self.x + rhs.x, self.y + rhs.y, self.z + rhs.z)
+    }
+}
+
+impl AddAssign for Vec3 {
+    fn add_assign(&mut self, rhs: Self) {
+        self.x += rhs.x;
+        self.y += rhs.y;
+        self.z += rhs.z;
+    }
+}
+
+impl Sub for Vec3 {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        Self::new(self.x - rhs.x, self.y - rhs.y, self.z - rhs.z)
+    }
+}
+
+impl SubAssign for Vec3 {
+    fn sub_assign(&mut self, rhs: Self) {
+        self.x -= rhs.x;
+        self.y -= rhs.y;
+        self.z -= rhs.z;
+    }
+}
+
+impl Mul<f64> for Vec3 {
+    type Output = Self;
+
+    fn mul(self, rhs: f64) -> Self::Output {
+        Self::new(self.x * rhs, self.y * rhs, self.z * rhs)
+    }
+}
+
+impl Div<f64> for Vec3 {
+    type Output = Self;
+
+    fn div(self, rhs: f64) -> Self::Output {
+        Self::new(self.x / rhs, self.y / rhs, self.z / rhs)
+    }
+}
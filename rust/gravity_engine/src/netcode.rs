@@ -0,0 +1,248 @@
+use std::collections::{BTreeMap, VecDeque};
+
+use serde::{Deserialize, Serialize};
+
+use crate::engine::SimulationEngine;
+use crate::errors::{EngineError, Result};
+use crate::types::{BodyEdit, Scenario, Snapshot, StepSummary};
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RollbackReport {
+    pub authoritative_tick: u64,
+    /// False when the local prediction already matched the authoritative state.
+    pub mispredicted: bool,
+    pub resimulated_ticks: u64,
+}
+
+/// Client-side prediction for deterministic lockstep-style multiplayer.
+///
+/// The session records the predicted state at the start of every tick and the local
+/// inputs scheduled per tick. When a late authoritative snapshot arrives it is compared
+/// with the prediction for that tick; on a mismatch the engine is rewound to it and the
+/// queued inputs are replayed up to the present tick.
+///
+/// Inputs scheduled for tick `t` are applied after the state at `t` is recorded, so an
+/// authoritative snapshot for tick `t` must not yet include them.
+#[derive(Clone, Debug)]
+pub struct RollbackSession {
+    engine: SimulationEngine,
+    capacity: usize,
+    predicted: VecDeque<Snapshot>,
+    authoritative: VecDeque<Snapshot>,
+    inputs: BTreeMap<u64, Vec<BodyEdit>>,
+}
+
+impl RollbackSession {
+    pub fn new(engine: SimulationEngine, capacity: usize) -> Result<Self> {
+        if !engine.config().deterministic {
+            return Err(EngineError::InvalidConfig(
+                "rollback sessions require deterministic mode".to_string(),
+            ));
+        }
+        if capacity == 0 {
+            return Err(EngineError::InvalidConfig(
+                "rollback capacity must be >= 1".to_string(),
+            ));
+        }
+        Ok(Self {
+            engine,
+            capacity,
+            predicted: VecDeque::new(),
+            authoritative: VecDeque::new(),
+            inputs: BTreeMap::new(),
+        })
+    }
+
+    pub fn engine(&self) -> &SimulationEngine {
+        &self.engine
+    }
+
+    pub fn current_tick(&self) -> u64 {
+        self.engine.tick()
+    }
+
+    pub fn authoritative_snapshots(&self) -> impl Iterator<Item = &Snapshot> {
+        self.authoritative.iter()
+    }
+
+    /// Schedules a local edit. Inputs for a tick that was already simulated rewind
+    /// to the recorded prediction for that tick and resimulate.
+    pub fn submit_input(&mut self, tick: u64, edit: BodyEdit) -> Result<u64> {
+        let current = self.engine.tick();
+        if tick >= current {
+            self.inputs.entry(tick).or_default().push(edit);
+            return Ok(0);
+        }
+
+        let Some(base) = self
+            .predicted
+            .iter()
+            .find(|snapshot| snapshot.tick == tick)
+            .cloned()
+        else {
+            return Err(EngineError::InvalidConfig(format!(
+                "input for tick {tick} is older than the rollback window"
+            )));
+        };
+        self.inputs.entry(tick).or_default().push(edit);
+        self.rewind_and_replay(base, current)
+    }
+
+    pub fn advance(&mut self, ticks: u32) -> Result<StepSummary> {
+        let mut summary = StepSummary::default();
+        for _ in 0..ticks {
+            summary.absorb(self.advance_one()?);
+        }
+        Ok(summary)
+    }
+
+    pub fn reconcile(&mut self, authoritative: Snapshot) -> Result<RollbackReport> {
+        let current = self.engine.tick();
+        let config_hash = self.engine.config().stable_hash();
+        if authoritative.config_hash != config_hash {
+            return Err(EngineError::InvalidConfig(format!(
+                "authoritative config hash {} does not match local {}",
+                authoritative.config_hash, config_hash
+            )));
+        }
+        let tick = authoritative.tick;
+        self.authoritative.push_back(authoritative.clone());
+        while self.authoritative.len() > self.capacity {
+            self.authoritative.pop_front();
+        }
+
+        let report = if tick > current {
+            self.engine.restore_snapshot(authoritative)?;
+            self.predicted.clear();
+            RollbackReport {
+                authoritative_tick: tick,
+                mispredicted: true,
+                resimulated_ticks: 0,
+            }
+        } else {
+            let predicted = self.predicted.iter().find(|snapshot| snapshot.tick == tick);
+            let matched = match predicted {
+                Some(snapshot) => {
+                    snapshot.sim_time == authoritative.sim_time
+                        && snapshot.bodies == authoritative.bodies
+                }
+                None if tick == current => self.engine.snapshot().bodies == authoritative.bodies,
+                None => false,
+            };
+            let resimulated_ticks = if matched {
+                0
+            } else {
+                self.rewind_and_replay(authoritative, current)?
+            };
+            RollbackReport {
+                authoritative_tick: tick,
+                mispredicted: !matched,
+                resimulated_ticks,
+            }
+        };
+
+        // Inputs before the newest confirmed tick can never be replayed again.
+        self.inputs = self.inputs.split_off(&tick);
+        self.predicted.retain(|snapshot| snapshot.tick >= tick);
+        Ok(report)
+    }
+
+    fn advance_one(&mut self) -> Result<StepSummary> {
+        let tick = self.engine.tick();
+        self.predicted.push_back(self.engine.snapshot());
+        while self.predicted.len() > self.capacity {
+            self.predicted.pop_front();
+        }
+        if let Some(edits) = self.inputs.get(&tick) {
+            for edit in edits.clone() {
+                self.engine.apply_edit(edit)?;
+            }
+        }
+        self.engine.step(1)
+    }
+
+    fn rewind_and_replay(&mut self, base: Snapshot, target_tick: u64) -> Result<u64> {
+        let base_tick = base.tick;
+        self.engine.restore_snapshot(base)?;
+        self.predicted.retain(|snapshot| snapshot.tick < base_tick);
+        while self.engine.tick() < target_tick {
+            self.advance_one()?;
+        }
+        Ok(target_tick - base_tick)
+    }
+}
+
+/// Expected [`SimulationEngine::state_hash`] once the engine reaches `tick`.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReplayCheckpoint {
+    pub tick: u64,
+    pub hash: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReplayVerification {
+    /// Checkpoints that matched before the run ended or diverged.
+    pub verified: usize,
+    pub mismatch: Option<ReplayMismatch>,
+}
+
+/// First checkpoint whose hash differed.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReplayMismatch {
+    pub tick: u64,
+    pub expected: String,
+    pub actual: String,
+}
+
+impl ReplayVerification {
+    pub fn passed(&self) -> bool {
+        self.mismatch.is_none()
+    }
+}
+
+/// Loads `scenario` into a fresh engine, steps it through every checkpoint tick in
+/// order and compares state hashes, stopping at the first mismatch. Checkpoint ticks
+/// must be strictly increasing.
+pub fn verify_replay(
+    scenario: Scenario,
+    expected: &[ReplayCheckpoint],
+) -> Result<ReplayVerification> {
+    if expected.windows(2).any(|pair| pair[1].tick <= pair[0].tick) {
+        return Err(EngineError::InvalidConfig(
+            "replay checkpoints must have strictly increasing ticks".to_string(),
+        ));
+    }
+    let mut engine = SimulationEngine::initialize(scenario.engine_config.clone())?;
+    engine.load_scenario(scenario)?;
+    let mut verification = ReplayVerification {
+        verified: 0,
+        mismatch: None,
+    };
+    for checkpoint in expected {
+        while engine.tick() < checkpoint.tick {
+            let remaining = checkpoint.tick - engine.tick();
+            let summary = engine.step(u32::try_from(remaining).unwrap_or(u32::MAX))?;
+            if summary.ticks_applied == 0 {
+                return Err(EngineError::InvalidConfig(format!(
+                    "replay stalled before tick {}",
+                    checkpoint.tick
+                )));
+            }
+        }
+        let actual = engine.state_hash();
+        if actual != checkpoint.hash {
+            verification.mismatch = Some(ReplayMismatch {
+                tick: checkpoint.tick,
+                expected: checkpoint.hash.clone(),
+                actual,
+            });
+            break;
+        }
+        verification.verified += 1;
+    }
+    Ok(verification)
+}
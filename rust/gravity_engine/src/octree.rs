@@ -0,0 +1,267 @@
+use crate::config::EngineConfig;
+use crate::engine3d::Body3;
+use crate::forces::softened_inverse_cube;
+use crate::math::Vec3;
+use crate::solver::{SolverRuntimeMode, choose_runtime_mode};
+
+pub(crate) fn compute_accelerations_3d(
+    bodies: &[Body3],
+    positions: &[Vec3],
+    config: &EngineConfig,
+) -> (Vec<Vec3>, SolverRuntimeMode) {
+    let alive_count = bodies.iter().filter(|body| body.alive).count();
+    let mode = choose_runtime_mode(alive_count, config);
+    let accelerations = match mode {
+        SolverRuntimeMode::Pairwise => pairwise_accelerations(
+            bodies,
+            positions,
+            config.gravity_constant,
+            config.softening_epsilon,
+        ),
+        SolverRuntimeMode::BarnesHut => barnes_hut_accelerations(
+            bodies,
+            positions,
+            config.gravity_constant,
+            config.softening_epsilon,
+            config.barnes_hut_theta,
+        ),
+    };
+    (accelerations, mode)
+}
+
+fn pairwise_accelerations(
+    bodies: &[Body3],
+    positions: &[Vec3],
+    gravity_constant: f64,
+    softening_epsilon: f64,
+) -> Vec<Vec3> {
+    let count = bodies.len();
+    let mut accelerations = vec![Vec3::ZERO; count];
+    let epsilon2 = softening_epsilon * softening_epsilon;
+
+    for i in 0..count {
+        if !bodies[i].alive {
+            continue;
+        }
+        for j in (i + 1)..count {
+            if !bodies[j].alive {
+                continue;
+            }
+
+            let delta = positions[j] - positions[i];
+            let scale = gravity_constant * softened_inverse_cube(delta.norm_squared(), epsilon2);
+            accelerations[i] += delta * (scale * bodies[j].mass);
+            accelerations[j] -= delta * (scale * bodies[i].mass);
+        }
+    }
+
+    accelerations
+}
+
+fn barnes_hut_accelerations(
+    bodies: &[Body3],
+    positions: &[Vec3],
+    gravity_constant: f64,
+    softening_epsilon: f64,
+    theta: f64,
+) -> Vec<Vec3> {
+    let mut accelerations = vec![Vec3::ZERO; bodies.len()];
+    let alive_indices = bodies
+        .iter()
+        .enumerate()
+        .filter_map(|(index, body)| body.alive.then_some(index))
+        .collect::<Vec<_>>();
+    if alive_indices.len() < 2 {
+        return accelerations;
+    }
+
+    let masses = bodies.iter().map(|body| body.mass).collect::<Vec<_>>();
+    let root = build_octree(positions, &alive_indices, &masses);
+    let epsilon2 = softening_epsilon * softening_epsilon;
+
+    for &index in &alive_indices {
+        let mut acceleration = Vec3::ZERO;
+        root.accumulate(
+            index,
+            positions[index],
+            gravity_constant,
+            epsilon2,
+            theta,
+            &mut acceleration,
+        );
+        accelerations[index] = acceleration;
+    }
+
+    accelerations
+}
+
+fn build_octree(positions: &[Vec3], alive_indices: &[usize], masses: &[f64]) -> OctNode {
+    let mut min = Vec3::new(f64::INFINITY, f64::INFINITY, f64::INFINITY);
+    let mut max = Vec3::new(-f64::INFINITY, -f64::INFINITY, -f64::INFINITY);
+    for &index in alive_indices {
+        let position = positions[index];
+        min = Vec3::new(
+            min.x.min(position.x),
+            min.y.min(position.y),
+            min.z.min(position.z),
+        );
+        max = Vec3::new(
+            max.x.max(position.x),
+            max.y.max(position.y),
+            max.z.max(position.z),
+        );
+    }
+
+    let extent = max - min;
+    let span = extent.x.max(extent.y).max(extent.z).max(1e-6);
+    let half_size = 0.5 * span + 1e-6;
+    let mut root = OctNode::new((min + max) * 0.5, half_size);
+    let min_half = (half_size * 1e-6).max(1e-9);
+
+    for &index in alive_indices {
+        root.insert(index, positions, masses, min_half);
+    }
+    root
+}
+
+#[derive(Clone, Debug)]
+struct OctNode {
+    center: Vec3,
+    half_size: f64,
+    mass: f64,
+    com: Vec3,
+    count: usize,
+    body_index: Option<usize>,
+    children: [Option<Box<OctNode>>; 8],
+}
+
+impl OctNode {
+    fn new(center: Vec3, half_size: f64) -> Self {
+        Self {
+            center,
+            half_size,
+            mass: 0.0,
+            com: Vec3::ZERO,
+            count: 0,
+            body_index: None,
+            children: Default::default(),
+        }
+    }
+
+    fn is_leaf(&self) -> bool {
+        self.children.iter().all(|child| child.is_none())
+    }
+
+    fn insert(&mut self, index: usize, positions: &[Vec3], masses: &[f64], min_half: f64) {
+        let position = positions[index];
+        let mass = masses[index];
+
+        if self.count == 0 {
+            self.count = 1;
+            self.mass = mass;
+            self.com = position;
+            self.body_index = Some(index);
+            return;
+        }
+
+        let next_mass = self.mass + mass;
+        if next_mass > 0.0 {
+            self.com = (self.com * self.mass + position * mass) / next_mass;
+        }
+        self.mass = next_mass;
+        self.count += 1;
+
+        if self.is_leaf() {
+            let Some(existing_index) = self.body_index.take() else {
+                // Aggregated leaf already stores multiple bodies and cannot subdivide further.
+                return;
+            };
+            let same_spot = (positions[existing_index] - position).norm_squared() <= 1e-18;
+            if self.half_size <= min_half || same_spot {
+                return;
+            }
+
+            let child_half = self.half_size * 0.5;
+            for (octant, child) in self.children.iter_mut().enumerate() {
+                let offset = Vec3::new(
+                    if octant & 1 == 0 {
+                        -child_half
+                    } else {
+                        child_half
+                    },
+                    if octant & 2 == 0 {
+                        -child_half
+                    } else {
+                        child_half
+                    },
+                    if octant & 4 == 0 {
+                        -child_half
+                    } else {
+                        child_half
+                    },
+                );
+                *child = Some(Box::new(OctNode::new(self.center + offset, child_half)));
+            }
+            self.insert_into_child(existing_index, positions, masses, min_half);
+        }
+
+        self.insert_into_child(index, positions, masses, min_half);
+    }
+
+    fn insert_into_child(
+        &mut self,
+        index: usize,
+        positions: &[Vec3],
+        masses: &[f64],
+        min_half: f64,
+    ) {
+        let position = positions[index];
+        let octant = usize::from(position.x >= self.center.x)
+            + 2 * usize::from(position.y >= self.center.y)
+            + 4 * usize::from(position.z >= self.center.z);
+        if let Some(child) = self.children[octant].as_mut() {
+            child.insert(index, positions, masses, min_half);
+        }
+    }
+
+    fn accumulate(
+        &self,
+        body_index: usize,
+        body_position: Vec3,
+        gravity_constant: f64,
+        epsilon2: f64,
+        theta: f64,
+        out_acceleration: &mut Vec3,
+    ) {
+        if self.count == 0 || self.mass <= 0.0 {
+            return;
+        }
+        if self.count == 1 && self.body_index == Some(body_index) {
+            return;
+        }
+
+        let delta = self.com - body_position;
+        let raw_dist_sq = delta.norm_squared();
+        let dist_sq = raw_dist_sq + epsilon2;
+        if dist_sq <= 0.0 {
+            return;
+        }
+
+        if self.is_leaf() || (self.half_size * 2.0 / dist_sq.sqrt()) < theta {
+            *out_acceleration += delta
+                * (gravity_constant * self.mass * softened_inverse_cube(raw_dist_sq, epsilon2));
+            return;
+        }
+
+        for child in self.children.iter().flatten() {
+            child.accumulate(
+                body_index,
+                body_position,
+                gravity_constant,
+                epsilon2,
+                theta,
+                out_acceleration,
+            );
+        }
+    }
+}
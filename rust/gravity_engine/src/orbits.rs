@@ -0,0 +1,259 @@
+//! Two-body orbital elements relative to a chosen primary, and the inverse for
+//! placing bodies on a desired orbit.
+//!
+//! Both directions use `mu = G * (m_primary + m_body)`, so a body placed from
+//! elements reads back the same elements.
+
+use std::f64::consts::TAU;
+
+use serde::{Deserialize, Serialize};
+
+use crate::errors::{EngineError, Result};
+use crate::math::Vec2;
+use crate::types::Body;
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OrbitalElements {
+    /// Negative for hyperbolic orbits, infinite for parabolic ones.
+    pub semi_major_axis: f64,
+    pub eccentricity: f64,
+    /// Direction of periapsis in the simulation plane, in radians. For circular
+    /// orbits this is the body's current direction.
+    pub argument_of_periapsis: f64,
+    /// Angle from periapsis to the body in the direction of motion, in radians.
+    pub true_anomaly: f64,
+    pub periapsis: f64,
+    /// `None` for unbound orbits.
+    pub apoapsis: Option<f64>,
+    /// `None` for unbound orbits.
+    pub period: Option<f64>,
+    pub clockwise: bool,
+}
+
+/// Target orbit for `Body::from_orbital_elements`.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OrbitPlacement {
+    /// Positive with `eccentricity < 1`, negative with `eccentricity > 1`.
+    pub semi_major_axis: f64,
+    pub eccentricity: f64,
+    pub true_anomaly: f64,
+    #[serde(default)]
+    pub argument_of_periapsis: f64,
+    #[serde(default)]
+    pub clockwise: bool,
+}
+
+/// Elements of `body` about `primary`; `None` when they coincide or `mu` is not
+/// positive.
+pub fn orbital_elements(
+    primary: &Body,
+    body: &Body,
+    gravity_constant: f64,
+) -> Option<OrbitalElements> {
+    let offset = body.position - primary.position;
+    let relative_velocity = body.velocity - primary.velocity;
+    let mu = gravity_constant * (primary.mass + body.mass);
+    let distance = offset.norm();
+    if distance <= 0.0 || mu <= 0.0 {
+        return None;
+    }
+
+    let angular_momentum = offset.cross(relative_velocity);
+    let eccentricity_vector = (offset * (relative_velocity.norm_squared() - mu / distance)
+        - relative_velocity * offset.dot(relative_velocity))
+        / mu;
+    let eccentricity = eccentricity_vector.norm();
+    let specific_energy = 0.5 * relative_velocity.norm_squared() - mu / distance;
+    let semi_latus_rectum = angular_momentum * angular_momentum / mu;
+    let clockwise = angular_momentum < 0.0;
+
+    let (argument_of_periapsis, true_anomaly) = if eccentricity > 1e-12 {
+        let anomaly = eccentricity_vector
+            .cross(offset)
+            .atan2(eccentricity_vector.dot(offset));
+        (
+            eccentricity_vector.angle(),
+            if clockwise { -anomaly } else { anomaly },
+        )
+    } else {
+        (offset.angle(), 0.0)
+    };
+    let bound = specific_energy < 0.0;
+    let semi_major_axis = -mu / (2.0 * specific_energy);
+
+    Some(OrbitalElements {
+        semi_major_axis,
+        eccentricity,
+        argument_of_periapsis,
+        true_anomaly,
+        periapsis: semi_latus_rectum / (1.0 + eccentricity),
+        apoapsis: (bound && eccentricity < 1.0).then(|| semi_latus_rectum / (1.0 - eccentricity)),
+        period: bound.then(|| TAU * (semi_major_axis.powi(3) / mu).sqrt()),
+        clockwise,
+    })
+}
+
+/// Position and velocity relative to the primary for a body of `body_mass` on
+/// `placement`.
+pub fn orbit_state(
+    primary_mass: f64,
+    body_mass: f64,
+    placement: &OrbitPlacement,
+    gravity_constant: f64,
+) -> Result<(Vec2, Vec2)> {
+    let OrbitPlacement {
+        semi_major_axis: a,
+        eccentricity: e,
+        true_anomaly,
+        argument_of_periapsis,
+        clockwise,
+    } = *placement;
+    let shape_ok = e.is_finite()
+        && a.is_finite()
+        && ((0.0..1.0).contains(&e) && a > 0.0 || e > 1.0 && a < 0.0);
+    if !shape_ok {
+        return Err(EngineError::InvalidConfig(
+            "orbit needs 0 <= e < 1 with a > 0, or e > 1 with a < 0".to_string(),
+        ));
+    }
+    let mu = gravity_constant * (primary_mass + body_mass);
+    if !mu.is_finite() || mu <= 0.0 {
+        return Err(EngineError::InvalidConfig(
+            "orbit needs a positive gravitational parameter".to_string(),
+        ));
+    }
+    let semi_latus_rectum = a * (1.0 - e * e);
+    let denominator = 1.0 + e * true_anomaly.cos();
+    if denominator <= 0.0 {
+        return Err(EngineError::InvalidConfig(
+            "true anomaly lies beyond the hyperbola's asymptotes".to_string(),
+        ));
+    }
+
+    let (sin, cos) = true_anomaly.sin_cos();
+    let radius = semi_latus_rectum / denominator;
+    let speed_scale = (mu / semi_latus_rectum).sqrt();
+    let mut position = Vec2::new(cos, sin) * radius;
+    let mut velocity = Vec2::new(-sin, e + cos) * speed_scale;
+    if clockwise {
+        position.y = -position.y;
+        velocity.y = -velocity.y;
+    }
+    Ok((
+        position.rotate(argument_of_periapsis),
+        velocity.rotate(argument_of_periapsis),
+    ))
+}
+
+impl Body {
+    /// A body on `placement` about `primary`, moving with the primary.
+    pub fn from_orbital_elements(
+        id: impl Into<String>,
+        mass: f64,
+        radius: f64,
+        primary: &Body,
+        placement: &OrbitPlacement,
+        gravity_constant: f64,
+    ) -> Result<Self> {
+        let (offset, relative_velocity) =
+            orbit_state(primary.mass, mass, placement, gravity_constant)?;
+        let body = Body::new(
+            id,
+            mass,
+            radius,
+            primary.position + offset,
+            primary.velocity + relative_velocity,
+        );
+        body.validate()?;
+        Ok(body)
+    }
+}
+
+/// Advances a two-body relative state by `dt` along its exact conic, using universal
+/// variables so elliptic, parabolic and hyperbolic orbits are handled alike. `None`
+/// when the bodies coincide, `mu` is not positive, or the solve fails to converge.
+pub fn propagate_kepler(position: Vec2, velocity: Vec2, mu: f64, dt: f64) -> Option<(Vec2, Vec2)> {
+    let r0 = position.norm();
+    if r0 <= 0.0 || mu <= 0.0 || !(position.is_finite() && velocity.is_finite()) {
+        return None;
+    }
+    if dt == 0.0 {
+        return Some((position, velocity));
+    }
+    let sqrt_mu = mu.sqrt();
+    let radial = position.dot(velocity) / sqrt_mu;
+    // Reciprocal semi-major axis: positive when bound.
+    let alpha = 2.0 / r0 - velocity.norm_squared() / mu;
+    // Whole periods contribute nothing, and dropping them keeps the solve well scaled.
+    let dt = if alpha > 0.0 {
+        dt % (TAU / (sqrt_mu * alpha.powf(1.5)))
+    } else {
+        dt
+    };
+
+    // F(x) = r0 vr0 / sqrt(mu) x^2 C + (1 - alpha r0) x^3 S + r0 x - sqrt(mu) dt, solved
+    // with Laguerre's method, which converges from almost any start.
+    let target = sqrt_mu * dt;
+    let mut chi = if alpha > 0.0 {
+        sqrt_mu * alpha * dt
+    } else {
+        sqrt_mu * dt / r0
+    };
+    let order: f64 = 5.0;
+    let mut converged = false;
+    for _ in 0..64 {
+        let z = alpha * chi * chi;
+        let (c, s) = stumpff(z);
+        let value =
+            radial * chi * chi * c + (1.0 - alpha * r0) * chi.powi(3) * s + r0 * chi - target;
+        let slope = radial * chi * (1.0 - z * s) + (1.0 - alpha * r0) * chi * chi * c + r0;
+        let curvature = radial * (1.0 - z * c) + (1.0 - alpha * r0) * chi * (1.0 - z * s);
+        let discriminant = ((order - 1.0).powi(2) * slope * slope
+            - order * (order - 1.0) * value * curvature)
+            .abs()
+            .sqrt();
+        let denominator = slope + slope.signum() * discriminant;
+        if denominator == 0.0 || !denominator.is_finite() {
+            return None;
+        }
+        let delta = order * value / denominator;
+        chi -= delta;
+        if delta.abs() <= 1e-12 * chi.abs().max(f64::MIN_POSITIVE) {
+            converged = true;
+            break;
+        }
+    }
+    if !converged || !chi.is_finite() {
+        return None;
+    }
+
+    let z = alpha * chi * chi;
+    let (c, s) = stumpff(z);
+    let f = 1.0 - chi * chi / r0 * c;
+    let g = dt - chi.powi(3) / sqrt_mu * s;
+    let new_position = position * f + velocity * g;
+    let r = new_position.norm();
+    let f_dot = sqrt_mu / (r * r0) * (z * chi * s - chi);
+    let g_dot = 1.0 - chi * chi / r * c;
+    let new_velocity = position * f_dot + velocity * g_dot;
+    (new_position.is_finite() && new_velocity.is_finite()).then_some((new_position, new_velocity))
+}
+
+/// Stumpff functions `C(z)` and `S(z)`, with series near zero to avoid cancellation.
+fn stumpff(z: f64) -> (f64, f64) {
+    if z.abs() < 1e-3 {
+        return (
+            0.5 - z / 24.0 + z * z / 720.0,
+            1.0 / 6.0 - z / 120.0 + z * z / 5040.0,
+        );
+    }
+    if z > 0.0 {
+        let root = z.sqrt();
+        ((1.0 - root.cos()) / z, (root - root.sin()) / (z * root))
+    } else {
+        let root = (-z).sqrt();
+        ((root.cosh() - 1.0) / -z, (root.sinh() - root) / (-z * root))
+    }
+}
@@ -0,0 +1,73 @@
+use serde::{Deserialize, Serialize};
+
+use crate::solver::SolverRuntimeMode;
+
+/// Priors used until a solver mode has been measured, in microseconds per unit of work.
+const PAIRWISE_PRIOR: f64 = 0.005;
+const BARNES_HUT_PRIOR: f64 = 0.05;
+/// Weight of the newest measurement in the running calibration.
+const SMOOTHING: f64 = 0.2;
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TickCostEstimate {
+    pub micros_per_tick: f64,
+    pub body_count: usize,
+    /// `pairwise` or `barnesHut`, as the solver would run for `body_count` bodies.
+    pub solver_mode: String,
+    /// False while the estimate still relies on the built-in prior for this mode.
+    pub calibrated: bool,
+    pub samples: u64,
+}
+
+/// Running cost coefficients per solver mode, fitted against `n^2` work for pairwise
+/// and `n log2 n` for Barnes-Hut.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct TickCostModel {
+    pairwise: Option<(f64, u64)>,
+    barnes_hut: Option<(f64, u64)>,
+}
+
+impl TickCostModel {
+    pub fn record(&mut self, mode: SolverRuntimeMode, body_count: usize, micros_per_tick: f64) {
+        let work = work_units(mode, body_count);
+        if work <= 0.0 || !micros_per_tick.is_finite() {
+            return;
+        }
+        let measured = micros_per_tick / work;
+        let slot = match mode {
+            SolverRuntimeMode::Pairwise => &mut self.pairwise,
+            SolverRuntimeMode::BarnesHut => &mut self.barnes_hut,
+        };
+        *slot = Some(match *slot {
+            Some((coefficient, samples)) => (
+                coefficient + SMOOTHING * (measured - coefficient),
+                samples + 1,
+            ),
+            None => (measured, 1),
+        });
+    }
+
+    pub fn estimate(&self, mode: SolverRuntimeMode, body_count: usize) -> TickCostEstimate {
+        let (slot, prior, name) = match mode {
+            SolverRuntimeMode::Pairwise => (self.pairwise, PAIRWISE_PRIOR, "pairwise"),
+            SolverRuntimeMode::BarnesHut => (self.barnes_hut, BARNES_HUT_PRIOR, "barnesHut"),
+        };
+        let (coefficient, samples) = slot.unwrap_or((prior, 0));
+        TickCostEstimate {
+            micros_per_tick: coefficient * work_units(mode, body_count),
+            body_count,
+            solver_mode: name.to_string(),
+            calibrated: samples > 0,
+            samples,
+        }
+    }
+}
+
+fn work_units(mode: SolverRuntimeMode, body_count: usize) -> f64 {
+    let n = body_count as f64;
+    match mode {
+        SolverRuntimeMode::Pairwise => n * n,
+        SolverRuntimeMode::BarnesHut => n * n.max(2.0).log2(),
+    }
+}
@@ -0,0 +1,171 @@
+//! Unattended cycling through a list of scenarios.
+
+use serde::{Deserialize, Serialize};
+
+use crate::engine::SimulationEngine;
+use crate::errors::{EngineError, Result};
+use crate::events::{PlaylistEvent, SimulationEvent};
+use crate::types::{BodyEdit, Scenario, StepSummary};
+
+/// How the engine state carries into an entry when the playlist reaches it.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub enum PlaylistTransition {
+    /// Load the entry's scenario from scratch; tick and time restart at zero.
+    #[default]
+    Reset,
+    /// Keep the current bodies and timeline; only the entry's config is applied.
+    CarryBodies,
+    /// Keep the current bodies and add the entry's; incoming ids that are already
+    /// taken get an `@<entry index>` suffix.
+    Merge,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct PlaylistEntry {
+    pub scenario: Scenario,
+    pub ticks: u32,
+    /// Ignored for the first entry on the initial pass, which always loads fresh.
+    #[serde(default)]
+    pub transition: PlaylistTransition,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct Playlist {
+    pub entries: Vec<PlaylistEntry>,
+    /// Start over from the first entry after the last instead of finishing.
+    #[serde(default)]
+    pub looping: bool,
+}
+
+impl Playlist {
+    pub fn validate(&self) -> Result<()> {
+        if self.entries.is_empty() {
+            return Err(EngineError::InvalidConfig(
+                "playlist must contain at least one entry".to_string(),
+            ));
+        }
+        if let Some(index) = self.entries.iter().position(|entry| entry.ticks == 0) {
+            return Err(EngineError::InvalidConfig(format!(
+                "playlist entry {index} must run for at least one tick"
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Drives an engine through a playlist, switching entries when their tick budget is
+/// spent and emitting a `playlistAdvanced` event at every switch.
+#[derive(Clone, Debug)]
+pub struct PlaylistRunner {
+    engine: SimulationEngine,
+    playlist: Playlist,
+    index: usize,
+    ticks_in_entry: u32,
+    finished: bool,
+}
+
+impl PlaylistRunner {
+    pub fn new(playlist: Playlist) -> Result<Self> {
+        playlist.validate()?;
+        let first = &playlist.entries[0].scenario;
+        let mut engine = SimulationEngine::initialize(first.engine_config.clone())?;
+        engine.load_scenario(first.clone())?;
+        Ok(Self {
+            engine,
+            playlist,
+            index: 0,
+            ticks_in_entry: 0,
+            finished: false,
+        })
+    }
+
+    pub fn engine(&self) -> &SimulationEngine {
+        &self.engine
+    }
+
+    pub fn current_index(&self) -> usize {
+        self.index
+    }
+
+    /// True once the last entry of a non-looping playlist has used its budget.
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    /// Runs up to `ticks` ticks across as many entries as needed. Stops early when
+    /// the playlist finishes or a `pause_on_events` event fires.
+    pub fn advance(&mut self, ticks: u32) -> Result<StepSummary> {
+        let mut summary = StepSummary::default();
+        let mut remaining = ticks;
+        while remaining > 0 && !self.finished {
+            let budget = self.playlist.entries[self.index].ticks - self.ticks_in_entry;
+            let step = self.engine.step(remaining.min(budget))?;
+            let applied = step.ticks_applied;
+            let paused = step.stop_reason.is_some();
+            summary.absorb(step);
+            self.ticks_in_entry += applied;
+            remaining -= applied;
+
+            if self.ticks_in_entry == self.playlist.entries[self.index].ticks {
+                self.transition(&mut summary)?;
+            }
+            if paused || applied == 0 {
+                break;
+            }
+        }
+        Ok(summary)
+    }
+
+    fn transition(&mut self, summary: &mut StepSummary) -> Result<()> {
+        let next = self.index + 1;
+        let next = if next < self.playlist.entries.len() {
+            next
+        } else if self.playlist.looping {
+            0
+        } else {
+            self.finished = true;
+            return Ok(());
+        };
+
+        let entry = &self.playlist.entries[next];
+        let scenario = entry.scenario.clone();
+        match entry.transition {
+            PlaylistTransition::Reset => self.engine.load_scenario(scenario)?,
+            PlaylistTransition::CarryBodies => self.engine.set_config(scenario.engine_config)?,
+            PlaylistTransition::Merge => {
+                scenario.verify_checksum()?;
+                self.engine.set_config(scenario.engine_config)?;
+                for mut body in scenario.bodies {
+                    while self
+                        .engine
+                        .bodies()
+                        .iter()
+                        .any(|existing| existing.id == body.id)
+                    {
+                        body.id = format!("{}@{next}", body.id);
+                    }
+                    self.engine.apply_edit(BodyEdit::Create(body))?;
+                }
+            }
+        }
+
+        let event = SimulationEvent::PlaylistAdvanced(PlaylistEvent {
+            tick: self.engine.tick(),
+            sim_time: self.engine.sim_time(),
+            from_index: self.index,
+            to_index: next,
+            scenario_name: entry.scenario.metadata.name.clone(),
+            transition: entry.transition,
+        });
+        self.engine.record_event(summary, event);
+        self.index = next;
+        self.ticks_in_entry = 0;
+        Ok(())
+    }
+}
@@ -0,0 +1,187 @@
+//! Surfaces of section: every time a watched body crosses a line in a chosen
+//! direction, its state along the line is recorded for phase-space plots.
+
+use std::collections::{BTreeMap, VecDeque};
+
+use serde::{Deserialize, Serialize};
+
+use crate::errors::{EngineError, Result};
+use crate::math::Vec2;
+use crate::types::Body;
+
+/// The line through `point` perpendicular to `normal`, crossed in the direction of
+/// `normal`. The defaults give the classic `y = 0` section with `vy > 0`, sampling
+/// `(x, vx)`.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PoincareSection {
+    pub name: String,
+    /// Watched bodies; empty watches every body.
+    #[serde(default)]
+    pub body_ids: Vec<String>,
+    #[serde(default)]
+    pub point: Vec2,
+    #[serde(default = "default_normal")]
+    pub normal: Vec2,
+    /// Measure positions and velocities relative to this body instead of the
+    /// simulation frame.
+    #[serde(default)]
+    pub relative_to: Option<String>,
+    /// Oldest samples are dropped beyond this many.
+    #[serde(default = "default_max_samples")]
+    pub max_samples: usize,
+}
+
+fn default_normal() -> Vec2 {
+    Vec2::new(0.0, 1.0)
+}
+
+fn default_max_samples() -> usize {
+    10_000
+}
+
+impl PoincareSection {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            body_ids: Vec::new(),
+            point: Vec2::ZERO,
+            normal: default_normal(),
+            relative_to: None,
+            max_samples: default_max_samples(),
+        }
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        if self.name.trim().is_empty() {
+            return Err(EngineError::InvalidConfig(
+                "section name must not be empty".to_string(),
+            ));
+        }
+        if !self.point.is_finite() || !self.normal.is_finite() || self.normal == Vec2::ZERO {
+            return Err(EngineError::InvalidConfig(
+                "section point and normal must be finite with a nonzero normal".to_string(),
+            ));
+        }
+        if self.max_samples == 0 {
+            return Err(EngineError::InvalidConfig(
+                "section max_samples must be at least 1".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Unit vector along the line, a quarter turn clockwise from `normal`.
+    fn tangent(&self) -> Vec2 {
+        let normal = self.normal.normalized_or(default_normal());
+        Vec2::new(normal.y, -normal.x)
+    }
+}
+
+/// One crossing, interpolated linearly between the ticks either side of it.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PoincareSample {
+    pub body_id: String,
+    /// Tick at which the crossing was detected.
+    pub tick: u64,
+    pub sim_time: f64,
+    /// Position along the line from `point`.
+    pub coordinate: f64,
+    /// Velocity component along the line.
+    pub tangent_velocity: f64,
+    /// Velocity component along `normal`; always positive.
+    pub normal_velocity: f64,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct SectionState {
+    sim_time: f64,
+    position: Vec2,
+    velocity: Vec2,
+}
+
+#[derive(Clone, Debug)]
+pub(crate) struct PoincareTracker {
+    pub section: PoincareSection,
+    pub samples: VecDeque<PoincareSample>,
+    previous: BTreeMap<String, SectionState>,
+}
+
+impl PoincareTracker {
+    pub(crate) fn new(section: PoincareSection, bodies: &[Body], sim_time: f64) -> Self {
+        let mut tracker = Self {
+            section,
+            samples: VecDeque::new(),
+            previous: BTreeMap::new(),
+        };
+        tracker.previous = tracker.states(bodies, sim_time);
+        tracker
+    }
+
+    /// Forgets the last positions so a discontinuity (a new scenario) is not read as
+    /// a crossing.
+    pub(crate) fn reseed(&mut self, bodies: &[Body], sim_time: f64) {
+        self.previous = self.states(bodies, sim_time);
+    }
+
+    pub(crate) fn update(&mut self, bodies: &[Body], tick: u64, sim_time: f64) {
+        let current = self.states(bodies, sim_time);
+        let normal = self.section.normal.normalized_or(default_normal());
+        let tangent = self.section.tangent();
+        for (id, now) in &current {
+            let Some(before) = self.previous.get(id) else {
+                continue;
+            };
+            let (was, is) = (
+                (before.position - self.section.point).dot(normal),
+                (now.position - self.section.point).dot(normal),
+            );
+            if !(was < 0.0 && is >= 0.0) {
+                continue;
+            }
+            let alpha = was / (was - is);
+            let position = before.position.lerp(now.position, alpha);
+            let velocity = before.velocity.lerp(now.velocity, alpha);
+            if self.samples.len() == self.section.max_samples {
+                self.samples.pop_front();
+            }
+            self.samples.push_back(PoincareSample {
+                body_id: id.clone(),
+                tick,
+                sim_time: before.sim_time + (now.sim_time - before.sim_time) * alpha,
+                coordinate: (position - self.section.point).dot(tangent),
+                tangent_velocity: velocity.dot(tangent),
+                normal_velocity: velocity.dot(normal),
+            });
+        }
+        self.previous = current;
+    }
+
+    fn states(&self, bodies: &[Body], sim_time: f64) -> BTreeMap<String, SectionState> {
+        let origin = match &self.section.relative_to {
+            Some(id) => match bodies.iter().find(|body| body.alive && body.id == *id) {
+                Some(body) => (body.position, body.velocity),
+                None => return BTreeMap::new(),
+            },
+            None => (Vec2::ZERO, Vec2::ZERO),
+        };
+        bodies
+            .iter()
+            .filter(|body| {
+                body.alive
+                    && self.section.relative_to.as_ref() != Some(&body.id)
+                    && (self.section.body_ids.is_empty()
+                        || self.section.body_ids.contains(&body.id))
+            })
+            .map(|body| {
+                let state = SectionState {
+                    sim_time,
+                    position: body.position - origin.0,
+                    velocity: body.velocity - origin.1,
+                };
+                (body.id.clone(), state)
+            })
+            .collect()
+    }
+}
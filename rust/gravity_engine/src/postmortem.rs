@@ -0,0 +1,78 @@
+//! Post-mortem capture when a step blows up with non-finite state.
+
+use std::collections::VecDeque;
+
+use serde::{Deserialize, Serialize};
+
+use crate::clock::SimClock;
+use crate::math::Vec2;
+use crate::types::{Body, Snapshot};
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BodyStateSample {
+    pub tick: u64,
+    pub sim_time: f64,
+    pub position: Vec2,
+    pub velocity: Vec2,
+}
+
+/// Reproducible artifact for a `NumericalInstability`: restoring `last_good` with
+/// the same config and stepping one tick reproduces the failure.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InstabilityReport {
+    pub message: String,
+    /// First body found with non-finite state, if the failure left one behind.
+    pub body_id: Option<String>,
+    /// State at the start of the tick that failed.
+    pub last_good: Snapshot,
+    /// The offending body over the recorded ticks, oldest first.
+    pub history: Vec<BodyStateSample>,
+}
+
+#[derive(Clone, Debug)]
+pub(crate) struct HistoryFrame {
+    pub(crate) tick: u64,
+    pub(crate) sim_time: f64,
+    pub(crate) clock: Option<SimClock>,
+    pub(crate) bodies: Vec<Body>,
+}
+
+/// Pre-tick states of the last few ticks, newest last.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct StateHistory {
+    frames: VecDeque<HistoryFrame>,
+}
+
+impl StateHistory {
+    pub(crate) fn record(&mut self, frame: HistoryFrame, capacity: usize) {
+        while self.frames.len() >= capacity.max(1) {
+            self.frames.pop_front();
+        }
+        self.frames.push_back(frame);
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.frames.clear();
+    }
+
+    pub(crate) fn latest(&self) -> Option<&HistoryFrame> {
+        self.frames.back()
+    }
+
+    pub(crate) fn body_history(&self, id: &str) -> Vec<BodyStateSample> {
+        self.frames
+            .iter()
+            .filter_map(|frame| {
+                let body = frame.bodies.iter().find(|body| body.id == id)?;
+                Some(BodyStateSample {
+                    tick: frame.tick,
+                    sim_time: frame.sim_time,
+                    position: body.position,
+                    velocity: body.velocity,
+                })
+            })
+            .collect()
+    }
+}
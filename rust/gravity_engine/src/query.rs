@@ -0,0 +1,96 @@
+//! Server-side body filtering so hosts don't pull the whole state to find a few bodies.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::errors::{EngineError, Result};
+use crate::types::Body;
+use crate::zones::Region;
+
+/// Every populated field must match. Results keep engine order.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct BodyQuery {
+    /// Bodies must carry every listed tag; an empty value matches any value.
+    pub tags: BTreeMap<String, String>,
+    /// Inclusive mass bounds.
+    pub min_mass: Option<f64>,
+    pub max_mass: Option<f64>,
+    pub region: Option<Region>,
+    pub alive: Option<bool>,
+    /// Return full bodies instead of ids.
+    pub include_bodies: bool,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum BodyQueryResult {
+    Ids(Vec<String>),
+    Bodies(Vec<Body>),
+}
+
+impl BodyQueryResult {
+    pub fn len(&self) -> usize {
+        match self {
+            Self::Ids(ids) => ids.len(),
+            Self::Bodies(bodies) => bodies.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl BodyQuery {
+    pub fn validate(&self) -> Result<()> {
+        let bounds = [self.min_mass, self.max_mass];
+        if bounds.iter().flatten().any(|mass| !mass.is_finite()) {
+            return Err(EngineError::InvalidConfig(
+                "query mass bounds must be finite".to_string(),
+            ));
+        }
+        if let (Some(min), Some(max)) = (self.min_mass, self.max_mass)
+            && min > max
+        {
+            return Err(EngineError::InvalidConfig(
+                "query min_mass must be <= max_mass".to_string(),
+            ));
+        }
+        if let Some(region) = &self.region {
+            region.validate()?;
+        }
+        Ok(())
+    }
+
+    pub fn matches(&self, body: &Body) -> bool {
+        if self.alive.is_some_and(|alive| body.alive != alive)
+            || self.min_mass.is_some_and(|min| body.mass < min)
+            || self.max_mass.is_some_and(|max| body.mass > max)
+        {
+            return false;
+        }
+        if let Some(region) = &self.region
+            && !region.contains(body.position)
+        {
+            return false;
+        }
+        self.tags.iter().all(|(key, value)| {
+            body.metadata
+                .as_ref()
+                .and_then(|metadata| metadata.tags.get(key))
+                .is_some_and(|tag| value.is_empty() || tag == value)
+        })
+    }
+
+    pub(crate) fn run(&self, bodies: &[Body]) -> Result<BodyQueryResult> {
+        self.validate()?;
+        let matches = bodies.iter().filter(|body| self.matches(body));
+        Ok(if self.include_bodies {
+            BodyQueryResult::Bodies(matches.cloned().collect())
+        } else {
+            BodyQueryResult::Ids(matches.map(|body| body.id.clone()).collect())
+        })
+    }
+}
@@ -0,0 +1,206 @@
+//! Seeded random numbers for reproducible scenario generation.
+//!
+//! The generator is xoshiro256** (Blackman & Vigna) seeded through splitmix64, so a
+//! seed yields the same `u64` stream on every platform. Floating-point helpers that
+//! use `ln`/`sin`/`cos` inherit the platform libm, which is exact on all mainstream
+//! targets in practice but not guaranteed by IEEE 754.
+
+use std::f64::consts::TAU;
+
+use serde::{Deserialize, Serialize};
+
+use crate::errors::{EngineError, Result};
+use crate::math::Vec2;
+use crate::types::Body;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Xoshiro256 {
+    state: [u64; 4],
+}
+
+impl Xoshiro256 {
+    pub fn new(seed: u64) -> Self {
+        let mut splitmix = seed;
+        let mut next = || {
+            splitmix = splitmix.wrapping_add(0x9e37_79b9_7f4a_7c15);
+            let mut z = splitmix;
+            z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+            z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+            z ^ (z >> 31)
+        };
+        Self {
+            state: [next(), next(), next(), next()],
+        }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        let result = self.state[1].wrapping_mul(5).rotate_left(7).wrapping_mul(9);
+        let t = self.state[1] << 17;
+        self.state[2] ^= self.state[0];
+        self.state[3] ^= self.state[1];
+        self.state[1] ^= self.state[2];
+        self.state[0] ^= self.state[3];
+        self.state[2] ^= t;
+        self.state[3] = self.state[3].rotate_left(45);
+        result
+    }
+
+    /// Uniform in `[0, 1)` with 53 bits of precision.
+    pub fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 * (1.0 / (1_u64 << 53) as f64)
+    }
+
+    pub fn range(&mut self, low: f64, high: f64) -> f64 {
+        low + (high - low) * self.next_f64()
+    }
+
+    /// Standard normal sample (Box-Muller, one value per call).
+    pub fn gaussian(&mut self) -> f64 {
+        let u1 = 1.0 - self.next_f64();
+        let u2 = self.next_f64();
+        (-2.0 * u1.ln()).sqrt() * (TAU * u2).cos()
+    }
+}
+
+/// Seeded Gaussian noise added to positions and velocities.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct PerturbSpec {
+    #[serde(default)]
+    pub position_sigma: f64,
+    #[serde(default)]
+    pub velocity_sigma: f64,
+    pub seed: u64,
+}
+
+impl PerturbSpec {
+    pub fn validate(&self) -> Result<()> {
+        let valid = |sigma: f64| sigma.is_finite() && sigma >= 0.0;
+        if !valid(self.position_sigma) || !valid(self.velocity_sigma) {
+            return Err(EngineError::InvalidBody(
+                "perturbation sigmas must be finite and >= 0".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Draws four samples per body (position x/y, then velocity x/y) in slice order, so
+    /// the result depends only on the seed and the bodies' order.
+    pub fn apply<'a>(&self, bodies: impl IntoIterator<Item = &'a mut Body>) {
+        let mut rng = Xoshiro256::new(self.seed);
+        for body in bodies {
+            body.position += Vec2::new(rng.gaussian(), rng.gaussian()) * self.position_sigma;
+            body.velocity += Vec2::new(rng.gaussian(), rng.gaussian()) * self.velocity_sigma;
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "shape", rename_all = "camelCase")]
+pub enum CloudShape {
+    UniformDisk {
+        radius: f64,
+    },
+    GaussianBlob {
+        sigma: f64,
+    },
+    /// Uniform in area between the two radii.
+    Ring {
+        inner_radius: f64,
+        outer_radius: f64,
+    },
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CloudSpec {
+    /// Bodies are named `{id_prefix}{index}`.
+    pub id_prefix: String,
+    pub count: usize,
+    pub shape: CloudShape,
+    #[serde(default)]
+    pub center: Vec2,
+    pub body_mass: f64,
+    pub body_radius: f64,
+    #[serde(default)]
+    pub bulk_velocity: Vec2,
+    /// When set, each body also gets the counter-clockwise circular speed
+    /// `sqrt(central_gm / r)` about `center`.
+    #[serde(default)]
+    pub central_gm: Option<f64>,
+    #[serde(default)]
+    pub origin_group: Option<u32>,
+}
+
+impl CloudSpec {
+    pub fn validate(&self) -> Result<()> {
+        let shape_ok = match self.shape {
+            CloudShape::UniformDisk { radius } => radius.is_finite() && radius > 0.0,
+            CloudShape::GaussianBlob { sigma } => sigma.is_finite() && sigma > 0.0,
+            CloudShape::Ring {
+                inner_radius,
+                outer_radius,
+            } => inner_radius >= 0.0 && outer_radius.is_finite() && outer_radius > inner_radius,
+        };
+        if !shape_ok {
+            return Err(EngineError::InvalidConfig(
+                "cloud shape dimensions must be finite and positive".to_string(),
+            ));
+        }
+        if self
+            .central_gm
+            .is_some_and(|gm| !gm.is_finite() || gm <= 0.0)
+        {
+            return Err(EngineError::InvalidConfig(
+                "cloud central_gm must be finite and > 0".to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+pub fn generate_cloud(spec: &CloudSpec, rng: &mut Xoshiro256) -> Result<Vec<Body>> {
+    spec.validate()?;
+    let mut bodies = Vec::with_capacity(spec.count);
+    for index in 0..spec.count {
+        let offset = sample_offset(&spec.shape, rng);
+        let mut velocity = spec.bulk_velocity;
+        if let Some(gm) = spec.central_gm {
+            let distance = offset.norm();
+            if distance > 0.0 {
+                velocity += offset.perp() / distance * (gm / distance).sqrt();
+            }
+        }
+        let body = Body {
+            origin_group: spec.origin_group,
+            ..Body::new(
+                format!("{}{index}", spec.id_prefix),
+                spec.body_mass,
+                spec.body_radius,
+                spec.center + offset,
+                velocity,
+            )
+        };
+        body.validate()?;
+        bodies.push(body);
+    }
+    Ok(bodies)
+}
+
+fn sample_offset(shape: &CloudShape, rng: &mut Xoshiro256) -> Vec2 {
+    match *shape {
+        CloudShape::UniformDisk { radius } => {
+            Vec2::from_angle(rng.range(0.0, TAU)) * (radius * rng.next_f64().sqrt())
+        }
+        CloudShape::GaussianBlob { sigma } => Vec2::new(rng.gaussian(), rng.gaussian()) * sigma,
+        CloudShape::Ring {
+            inner_radius,
+            outer_radius,
+        } => {
+            let inner_sq = inner_radius * inner_radius;
+            let radius = rng.range(inner_sq, outer_radius * outer_radius).sqrt();
+            Vec2::from_angle(rng.range(0.0, TAU)) * radius
+        }
+    }
+}
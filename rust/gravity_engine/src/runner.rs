@@ -0,0 +1,199 @@
+//! Background stepping: a worker thread owns the engine and works through queued
+//! commands so hosts can keep drawing frames while large systems step.
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread::{self, JoinHandle};
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::EngineConfig;
+use crate::engine::SimulationEngine;
+use crate::errors::{EngineError, Result};
+use crate::types::{BodyEdit, SimulationState, Snapshot, StepSummary};
+
+/// Edits and config changes submitted during a step batch wait behind it, so they
+/// always land between ticks.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "command", rename_all = "camelCase")]
+pub enum RunnerCommand {
+    Step { ticks: u32 },
+    ApplyEdit { edit: Box<BodyEdit> },
+    SetConfig { config: Box<EngineConfig> },
+    Snapshot,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+#[allow(clippy::large_enum_variant)]
+pub enum RunnerOutput {
+    Stepped {
+        summary: StepSummary,
+        state: SimulationState,
+    },
+    Edited {
+        state: SimulationState,
+    },
+    Snapshot {
+        snapshot: Snapshot,
+    },
+}
+
+#[derive(Debug, PartialEq)]
+pub struct RunnerReply {
+    /// The id `submit` returned for the command.
+    pub job: u64,
+    pub result: Result<RunnerOutput>,
+}
+
+/// Owns a `SimulationEngine` on a worker thread. Commands run in submission order;
+/// replies arrive on `try_recv`/`recv`, or go to the callback given to
+/// `spawn_with_callback`. Dropping the runner finishes the queued commands first.
+pub struct EngineRunner {
+    commands: Option<Sender<(u64, RunnerCommand)>>,
+    replies: Receiver<RunnerReply>,
+    worker: Option<JoinHandle<SimulationEngine>>,
+    pending: Arc<AtomicUsize>,
+    next_job: u64,
+}
+
+impl EngineRunner {
+    pub fn spawn(engine: SimulationEngine) -> Self {
+        let (reply_sender, replies) = mpsc::channel();
+        Self::start(engine, replies, false, move |reply| {
+            // The runner may already be gone; nobody is left to read the reply.
+            let _ = reply_sender.send(reply);
+        })
+    }
+
+    /// Delivers each reply to `callback` on the worker thread instead of queueing it.
+    pub fn spawn_with_callback<F>(engine: SimulationEngine, callback: F) -> Self
+    where
+        F: FnMut(RunnerReply) + Send + 'static,
+    {
+        let (_, replies) = mpsc::channel();
+        Self::start(engine, replies, true, callback)
+    }
+
+    /// `settle_on_worker` counts a job done once delivered rather than once received.
+    fn start<F>(
+        engine: SimulationEngine,
+        replies: Receiver<RunnerReply>,
+        settle_on_worker: bool,
+        mut deliver: F,
+    ) -> Self
+    where
+        F: FnMut(RunnerReply) + Send + 'static,
+    {
+        let (commands, inbox) = mpsc::channel::<(u64, RunnerCommand)>();
+        let pending = Arc::new(AtomicUsize::new(0));
+        let worker_pending = Arc::clone(&pending);
+        let worker = thread::spawn(move || {
+            let mut engine = engine;
+            for (job, command) in inbox {
+                let result = execute(&mut engine, command);
+                deliver(RunnerReply { job, result });
+                if settle_on_worker {
+                    worker_pending.fetch_sub(1, Ordering::AcqRel);
+                }
+            }
+            engine
+        });
+        Self {
+            commands: Some(commands),
+            replies,
+            worker: Some(worker),
+            pending,
+            next_job: 1,
+        }
+    }
+
+    /// Queues `command` and returns its job id.
+    pub fn submit(&mut self, command: RunnerCommand) -> Result<u64> {
+        let job = self.next_job;
+        let commands = self
+            .commands
+            .as_ref()
+            .ok_or_else(|| EngineError::WorkerFailed("runner is shut down".to_string()))?;
+        self.pending.fetch_add(1, Ordering::AcqRel);
+        if commands.send((job, command)).is_err() {
+            self.pending.fetch_sub(1, Ordering::AcqRel);
+            return Err(EngineError::WorkerFailed(
+                "worker thread has stopped".to_string(),
+            ));
+        }
+        self.next_job += 1;
+        Ok(job)
+    }
+
+    /// Commands whose reply has not been received yet, or with a callback, not yet
+    /// delivered.
+    pub fn pending(&self) -> usize {
+        self.pending.load(Ordering::Acquire)
+    }
+
+    pub fn try_recv(&self) -> Option<RunnerReply> {
+        let reply = self.replies.try_recv().ok()?;
+        self.pending.fetch_sub(1, Ordering::AcqRel);
+        Some(reply)
+    }
+
+    /// Blocks until the next reply; `None` once nothing is left to wait for.
+    pub fn recv(&self) -> Option<RunnerReply> {
+        if self.pending() == 0 {
+            return None;
+        }
+        let reply = self.replies.recv().ok()?;
+        self.pending.fetch_sub(1, Ordering::AcqRel);
+        Some(reply)
+    }
+
+    /// Finishes the queued commands and hands the engine back.
+    pub fn shutdown(mut self) -> Result<SimulationEngine> {
+        self.commands = None;
+        let worker = self
+            .worker
+            .take()
+            .ok_or_else(|| EngineError::WorkerFailed("runner is shut down".to_string()))?;
+        worker
+            .join()
+            .map_err(|_| EngineError::WorkerFailed("worker thread panicked".to_string()))
+    }
+}
+
+impl Drop for EngineRunner {
+    fn drop(&mut self) {
+        self.commands = None;
+        if let Some(worker) = self.worker.take() {
+            let _ = worker.join();
+        }
+    }
+}
+
+fn execute(engine: &mut SimulationEngine, command: RunnerCommand) -> Result<RunnerOutput> {
+    match command {
+        RunnerCommand::Step { ticks } => {
+            let summary = engine.step(ticks)?;
+            Ok(RunnerOutput::Stepped {
+                summary,
+                state: engine.get_state(),
+            })
+        }
+        RunnerCommand::ApplyEdit { edit } => {
+            engine.apply_edit(*edit)?;
+            Ok(RunnerOutput::Edited {
+                state: engine.get_state(),
+            })
+        }
+        RunnerCommand::SetConfig { config } => {
+            engine.set_config(*config)?;
+            Ok(RunnerOutput::Edited {
+                state: engine.get_state(),
+            })
+        }
+        RunnerCommand::Snapshot => Ok(RunnerOutput::Snapshot {
+            snapshot: engine.snapshot(),
+        }),
+    }
+}
@@ -0,0 +1,34 @@
+use std::collections::BTreeMap;
+
+use serde_json::Value;
+
+use crate::config::EngineConfig;
+use crate::errors::{EngineError, Result};
+use crate::types::{BodyEdit, Scenario, Snapshot};
+
+/// Names accepted by [`json_schema`], matching the camelCase used on the wire.
+pub const SCHEMA_NAMES: [&str; 4] = ["engineConfig", "scenario", "snapshot", "bodyEdit"];
+
+pub fn json_schema(name: &str) -> Result<Value> {
+    let schema = match name {
+        "engineConfig" => schemars::schema_for!(EngineConfig),
+        "scenario" => schemars::schema_for!(Scenario),
+        "snapshot" => schemars::schema_for!(Snapshot),
+        "bodyEdit" => schemars::schema_for!(BodyEdit),
+        other => {
+            return Err(EngineError::UnsupportedFeature(format!(
+                "no schema named '{other}' (expected one of {})",
+                SCHEMA_NAMES.join(", ")
+            )));
+        }
+    };
+    serde_json::to_value(schema)
+        .map_err(|error| EngineError::SchemaValidationFailed(error.to_string()))
+}
+
+pub fn all_json_schemas() -> BTreeMap<&'static str, Value> {
+    SCHEMA_NAMES
+        .iter()
+        .map(|name| (*name, json_schema(name).expect("known schema name")))
+        .collect()
+}
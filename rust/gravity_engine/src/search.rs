@@ -0,0 +1,268 @@
+//! Random-restart search for initial conditions that stay together.
+//!
+//! Each candidate draws its own seed from the search seed, samples bodies inside
+//! the bounding radius on roughly circular, co-rotating orbits about the centre of
+//! mass, then runs a short trial. Candidates are ranked stable-first, then by
+//! merges, escapes and relative energy drift, so the best ones can be loaded as
+//! scenarios directly.
+
+use std::f64::consts::TAU;
+use std::ops::ControlFlow;
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::EngineConfig;
+use crate::engine::SimulationEngine;
+use crate::errors::{EngineError, Result};
+use crate::math::Vec2;
+use crate::random::Xoshiro256;
+use crate::types::{Body, Scenario};
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StableSearch {
+    pub seed: u64,
+    pub candidates: u32,
+    pub body_count: usize,
+    pub total_mass: f64,
+    /// Bodies start inside this radius and must stay inside it to count as stable.
+    pub bounding_radius: f64,
+    #[serde(default = "default_trial_ticks")]
+    pub trial_ticks: u32,
+    /// Number of ranked candidates returned.
+    #[serde(default = "default_keep")]
+    pub keep: usize,
+    /// Largest `|E_end - E_start| / |E_start|` a stable candidate may show.
+    #[serde(default = "default_max_energy_drift")]
+    pub max_energy_drift: f64,
+    /// Masses are drawn uniformly from `[1 - spread, 1 + spread]` before scaling to
+    /// `total_mass`.
+    #[serde(default)]
+    pub mass_spread: f64,
+}
+
+fn default_trial_ticks() -> u32 {
+    2000
+}
+
+fn default_keep() -> usize {
+    5
+}
+
+fn default_max_energy_drift() -> f64 {
+    1e-3
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StabilityScore {
+    pub stable: bool,
+    pub merges: u64,
+    /// Bodies outside the bounding radius at the end of the trial.
+    pub escaped: usize,
+    pub energy_drift: f64,
+    /// Farthest any body strayed from the centre of mass during the trial.
+    pub max_extent: f64,
+    /// Ticks run before the trial ended; short when the integration blew up.
+    pub ticks_survived: u32,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchCandidate {
+    /// Seed that regenerates this candidate's initial conditions.
+    pub seed: u64,
+    pub score: StabilityScore,
+    /// Initial conditions, ready for `load_scenario`.
+    pub scenario: Scenario,
+}
+
+impl StableSearch {
+    pub fn validate(&self) -> Result<()> {
+        if self.candidates == 0 || self.keep == 0 || self.trial_ticks == 0 {
+            return Err(EngineError::InvalidConfig(
+                "search candidates, keep and trial_ticks must be > 0".to_string(),
+            ));
+        }
+        if self.body_count < 2 {
+            return Err(EngineError::InvalidConfig(
+                "search needs at least 2 bodies".to_string(),
+            ));
+        }
+        let positive = |value: f64| value.is_finite() && value > 0.0;
+        if !positive(self.total_mass)
+            || !positive(self.bounding_radius)
+            || !positive(self.max_energy_drift)
+        {
+            return Err(EngineError::InvalidConfig(
+                "search total_mass, bounding_radius and max_energy_drift must be finite and > 0"
+                    .to_string(),
+            ));
+        }
+        if !(0.0..1.0).contains(&self.mass_spread) {
+            return Err(EngineError::InvalidConfig(
+                "search mass_spread must be in [0, 1)".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Initial conditions for one candidate, centred on the origin with zero net
+    /// momentum.
+    pub fn sample(&self, seed: u64, gravity_constant: f64) -> Result<Vec<Body>> {
+        self.validate()?;
+        let mut rng = Xoshiro256::new(seed);
+        let weights = (0..self.body_count)
+            .map(|_| rng.range(1.0 - self.mass_spread, 1.0 + self.mass_spread))
+            .collect::<Vec<_>>();
+        let weight_sum = weights.iter().sum::<f64>();
+        let masses = weights
+            .iter()
+            .map(|weight| self.total_mass * weight / weight_sum)
+            .collect::<Vec<_>>();
+        // Leave room to breathe so bodies start well inside the boundary.
+        let start_radius = 0.5 * self.bounding_radius;
+        let mut bodies = masses
+            .iter()
+            .enumerate()
+            .map(|(index, &mass)| {
+                let offset = Vec2::from_angle(rng.range(0.0, TAU))
+                    * (start_radius * rng.range(0.05, 1.0).sqrt());
+                let radius = 1e-3 * self.bounding_radius;
+                Body::new(format!("body{index}"), mass, radius, offset, Vec2::ZERO)
+            })
+            .collect::<Vec<_>>();
+
+        let center = bodies
+            .iter()
+            .map(|body| body.position * body.mass)
+            .sum::<Vec2>()
+            / self.total_mass;
+        let positions = bodies
+            .iter()
+            .map(|body| body.position - center)
+            .collect::<Vec<_>>();
+        for (body, position) in bodies.iter_mut().zip(&positions) {
+            let distance = position.norm();
+            let enclosed = positions
+                .iter()
+                .zip(&masses)
+                .filter(|(other, _)| other.norm() < distance)
+                .map(|(_, mass)| mass)
+                .sum::<f64>()
+                .max(body.mass);
+            let circular = (gravity_constant * enclosed / distance.max(f64::EPSILON)).sqrt();
+            body.position = *position;
+            body.velocity =
+                position.perp() / distance.max(f64::EPSILON) * (circular * rng.range(0.8, 1.1));
+        }
+        let drift = bodies
+            .iter()
+            .map(|body| body.velocity * body.mass)
+            .sum::<Vec2>()
+            / self.total_mass;
+        for body in &mut bodies {
+            body.velocity -= drift;
+            body.validate()?;
+        }
+        Ok(bodies)
+    }
+}
+
+/// Runs every candidate under `config` and returns the best `keep`, best first.
+pub fn search_stable(config: &EngineConfig, search: &StableSearch) -> Result<Vec<SearchCandidate>> {
+    search.validate()?;
+    config.validate()?;
+    let mut seeds = Xoshiro256::new(search.seed);
+    let mut ranked = Vec::with_capacity(search.candidates as usize);
+    for _ in 0..search.candidates {
+        let seed = seeds.next_u64();
+        let bodies = search.sample(seed, config.gravity_constant)?;
+        let mut scenario =
+            SimulationEngine::with_bodies(config.clone(), bodies.clone())?.save_scenario();
+        scenario.metadata.name = format!("Stable search {seed:016x}");
+        scenario.metadata.tags = vec!["generated".to_string()];
+        scenario.checksum = Some(scenario.compute_checksum());
+        ranked.push(SearchCandidate {
+            seed,
+            score: trial(config, bodies, search)?,
+            scenario,
+        });
+    }
+    ranked.sort_by(|a, b| {
+        let (a, b) = (&a.score, &b.score);
+        b.stable
+            .cmp(&a.stable)
+            .then(a.merges.cmp(&b.merges))
+            .then(a.escaped.cmp(&b.escaped))
+            .then(b.ticks_survived.cmp(&a.ticks_survived))
+            .then(a.energy_drift.total_cmp(&b.energy_drift))
+    });
+    ranked.truncate(search.keep);
+    Ok(ranked)
+}
+
+fn trial(
+    config: &EngineConfig,
+    bodies: Vec<Body>,
+    search: &StableSearch,
+) -> Result<StabilityScore> {
+    let mut engine = SimulationEngine::with_bodies(config.clone(), bodies)?;
+    let start_energy = engine.diagnostics().total_energy;
+    let mut max_extent = 0.0_f64;
+    let mut merges = 0;
+    let outcome = engine.step_with_observer(search.trial_ticks, |_, bodies, summary| {
+        merges = summary.merged_events;
+        let center = center_of_mass(bodies);
+        for body in bodies.iter().filter(|body| body.alive) {
+            max_extent = max_extent.max((body.position - center).norm());
+        }
+        ControlFlow::Continue(())
+    });
+    let ticks_survived = engine.tick() as u32;
+    let blew_up = matches!(outcome, Err(EngineError::NumericalInstability(_)));
+    if let Err(error) = outcome
+        && !blew_up
+    {
+        return Err(error);
+    }
+
+    let center = center_of_mass(engine.bodies());
+    let escaped = engine
+        .bodies()
+        .iter()
+        .filter(|body| body.alive && (body.position - center).norm() > search.bounding_radius)
+        .count();
+    let energy_drift = if blew_up {
+        f64::INFINITY
+    } else {
+        let end_energy = engine.diagnostics().total_energy;
+        (end_energy - start_energy).abs() / start_energy.abs().max(f64::MIN_POSITIVE)
+    };
+    Ok(StabilityScore {
+        stable: !blew_up
+            && merges == 0
+            && escaped == 0
+            && max_extent <= search.bounding_radius
+            && energy_drift <= search.max_energy_drift,
+        merges,
+        escaped,
+        energy_drift,
+        max_extent,
+        ticks_survived,
+    })
+}
+
+fn center_of_mass(bodies: &[Body]) -> Vec2 {
+    let (moment, mass) = bodies
+        .iter()
+        .filter(|body| body.alive)
+        .fold((Vec2::ZERO, 0.0), |(moment, mass), body| {
+            (moment + body.position * body.mass, mass + body.mass)
+        });
+    if mass > 0.0 {
+        moment / mass
+    } else {
+        Vec2::ZERO
+    }
+}
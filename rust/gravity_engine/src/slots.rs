@@ -0,0 +1,61 @@
+//! Stable per-body slots that survive merges and deletions, for hosts that keep their
+//! own arrays parallel to the body list.
+
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+
+use crate::types::{Body, RemovedBodies};
+
+/// Slots are assigned on first sight and freed on removal; a freed slot is reused,
+/// lowest first, by the next new body.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct BodySlots {
+    slots: BTreeMap<String, usize>,
+    free: BTreeSet<usize>,
+    next: usize,
+}
+
+impl BodySlots {
+    pub(crate) fn from_bodies(bodies: &[Body]) -> Self {
+        let mut slots = Self::default();
+        slots.sync(bodies);
+        slots
+    }
+
+    pub(crate) fn map(&self) -> &BTreeMap<String, usize> {
+        &self.slots
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.slots.len()
+    }
+
+    /// Frees the slots of ids no longer in `bodies`, then assigns slots to new ones.
+    pub(crate) fn sync(&mut self, bodies: &[Body]) -> RemovedBodies {
+        let present = bodies
+            .iter()
+            .map(|body| body.id.as_str())
+            .collect::<HashSet<_>>();
+        let mut removed = self
+            .slots
+            .iter()
+            .filter(|(id, _)| !present.contains(id.as_str()))
+            .map(|(id, &slot)| (slot, id.clone()))
+            .collect::<Vec<_>>();
+        removed.sort_unstable();
+        for (slot, id) in &removed {
+            self.slots.remove(id);
+            self.free.insert(*slot);
+        }
+        for body in bodies {
+            if !self.slots.contains_key(&body.id) {
+                let slot = self.free.pop_first().unwrap_or_else(|| {
+                    self.next += 1;
+                    self.next - 1
+                });
+                self.slots.insert(body.id.clone(), slot);
+            }
+        }
+        let (slots, ids) = removed.into_iter().unzip();
+        RemovedBodies { ids, slots }
+    }
+}
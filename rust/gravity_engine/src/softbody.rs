@@ -0,0 +1,212 @@
+//! Extended objects built from point masses joined by springs, for demonstrating
+//! tidal deformation.
+
+use std::collections::HashMap;
+use std::f64::consts::TAU;
+
+use serde::{Deserialize, Serialize};
+
+use crate::errors::{EngineError, Result};
+use crate::forces::ForceProvider;
+use crate::math::Vec2;
+use crate::types::Body;
+
+/// Hookean link between two bodies, by id.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Spring {
+    pub first: String,
+    pub second: String,
+    pub rest_length: f64,
+    /// Force per unit extension.
+    pub stiffness: f64,
+}
+
+/// Springs evaluated as a force provider. Springs whose ends are missing or dead
+/// (e.g. after a merge) exert nothing.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SpringNetwork {
+    pub springs: Vec<Spring>,
+}
+
+impl ForceProvider for SpringNetwork {
+    fn accelerations(&self, bodies: &[Body], positions: &[Vec2]) -> Vec<Vec2> {
+        let index_of = bodies
+            .iter()
+            .enumerate()
+            .filter(|(_, body)| body.alive)
+            .map(|(index, body)| (body.id.as_str(), index))
+            .collect::<HashMap<_, _>>();
+        let mut accelerations = vec![Vec2::ZERO; bodies.len()];
+        for spring in &self.springs {
+            let (Some(&i), Some(&j)) = (
+                index_of.get(spring.first.as_str()),
+                index_of.get(spring.second.as_str()),
+            ) else {
+                continue;
+            };
+            let delta = positions[j] - positions[i];
+            let length = delta.norm();
+            if length <= 0.0 {
+                continue;
+            }
+            let force = delta * (spring.stiffness * (length - spring.rest_length) / length);
+            accelerations[i] += force / bodies[i].mass;
+            accelerations[j] -= force / bodies[j].mass;
+        }
+        accelerations
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "shape", rename_all = "camelCase")]
+pub enum SoftBodyShape {
+    /// Evenly spaced on a circle, linked to nearest and next-nearest neighbours so
+    /// the ring resists bending.
+    Ring { count: usize, radius: f64 },
+    /// Square grid linked along edges and both diagonals.
+    Lattice {
+        columns: usize,
+        rows: usize,
+        spacing: f64,
+    },
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SoftBodySpec {
+    /// Particles are named `{id_prefix}{index}`.
+    pub id_prefix: String,
+    pub shape: SoftBodyShape,
+    #[serde(default)]
+    pub center: Vec2,
+    #[serde(default)]
+    pub velocity: Vec2,
+    /// Split evenly between the particles.
+    pub total_mass: f64,
+    pub particle_radius: f64,
+    pub stiffness: f64,
+    /// Tag shared by every particle; `group_diagnostics` reports the aggregate,
+    /// including its ellipticity.
+    pub origin_group: u32,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SoftBody {
+    pub bodies: Vec<Body>,
+    pub springs: SpringNetwork,
+}
+
+impl SoftBodySpec {
+    pub fn validate(&self) -> Result<()> {
+        let shape_ok = match self.shape {
+            SoftBodyShape::Ring { count, radius } => {
+                count >= 3 && radius.is_finite() && radius > 0.0
+            }
+            SoftBodyShape::Lattice {
+                columns,
+                rows,
+                spacing,
+            } => columns >= 2 && rows >= 2 && spacing.is_finite() && spacing > 0.0,
+        };
+        if !shape_ok {
+            return Err(EngineError::InvalidConfig(
+                "soft body needs a ring of at least 3 or a lattice of at least 2x2 with positive size"
+                    .to_string(),
+            ));
+        }
+        if !self.stiffness.is_finite() || self.stiffness <= 0.0 {
+            return Err(EngineError::InvalidConfig(
+                "soft body stiffness must be finite and > 0".to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+pub fn build_soft_body(spec: &SoftBodySpec) -> Result<SoftBody> {
+    spec.validate()?;
+    let (offsets, links) = match spec.shape {
+        SoftBodyShape::Ring { count, radius } => {
+            let offsets = (0..count)
+                .map(|index| Vec2::from_angle(TAU * index as f64 / count as f64) * radius)
+                .collect::<Vec<_>>();
+            let mut links = (0..count)
+                .map(|index| (index, (index + 1) % count))
+                .collect::<Vec<_>>();
+            // A triangle has no next-nearest links and a square has only its two
+            // diagonals.
+            let skips = match count {
+                3 => 0,
+                4 => 2,
+                _ => count,
+            };
+            links.extend((0..skips).map(|index| (index, (index + 2) % count)));
+            (offsets, links)
+        }
+        SoftBodyShape::Lattice {
+            columns,
+            rows,
+            spacing,
+        } => {
+            let middle = Vec2::new((columns - 1) as f64, (rows - 1) as f64) * (0.5 * spacing);
+            let offsets = (0..rows)
+                .flat_map(|row| {
+                    (0..columns)
+                        .map(move |column| Vec2::new(column as f64, row as f64) * spacing - middle)
+                })
+                .collect::<Vec<_>>();
+            let at = |column: usize, row: usize| row * columns + column;
+            let mut links = Vec::new();
+            for row in 0..rows {
+                for column in 0..columns {
+                    if column + 1 < columns {
+                        links.push((at(column, row), at(column + 1, row)));
+                    }
+                    if row + 1 < rows {
+                        links.push((at(column, row), at(column, row + 1)));
+                    }
+                    if column + 1 < columns && row + 1 < rows {
+                        links.push((at(column, row), at(column + 1, row + 1)));
+                        links.push((at(column + 1, row), at(column, row + 1)));
+                    }
+                }
+            }
+            (offsets, links)
+        }
+    };
+
+    let particle_mass = spec.total_mass / offsets.len() as f64;
+    let bodies = offsets
+        .iter()
+        .enumerate()
+        .map(|(index, offset)| {
+            let body = Body {
+                origin_group: Some(spec.origin_group),
+                ..Body::new(
+                    format!("{}{index}", spec.id_prefix),
+                    particle_mass,
+                    spec.particle_radius,
+                    spec.center + *offset,
+                    spec.velocity,
+                )
+            };
+            body.validate().map(|_| body)
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let springs = links
+        .into_iter()
+        .map(|(i, j)| Spring {
+            first: bodies[i].id.clone(),
+            second: bodies[j].id.clone(),
+            rest_length: (offsets[j] - offsets[i]).norm(),
+            stiffness: spec.stiffness,
+        })
+        .collect();
+    Ok(SoftBody {
+        bodies,
+        springs: SpringNetwork { springs },
+    })
+}
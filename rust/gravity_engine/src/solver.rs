@@ -1,6 +1,11 @@
-use crate::config::{EngineConfig, GravitySolver};
+use std::collections::BTreeMap;
+
+use crate::boundary::separation;
+use crate::config::{EngineConfig, GravitySolver, InteractionGroups};
+use crate::forces::{softened_inverse_cube, softening_squares};
 use crate::math::Vec2;
 use crate::types::Body;
+use crate::zones::Region;
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub(crate) enum SolverRuntimeMode {
@@ -13,14 +18,6 @@ pub(crate) struct SolverStats {
     pub mode: SolverRuntimeMode,
 }
 
-pub(crate) fn compute_accelerations(
-    bodies: &[Body],
-    config: &EngineConfig,
-) -> (Vec<Vec2>, SolverStats) {
-    let positions = bodies.iter().map(|body| body.position).collect::<Vec<_>>();
-    compute_accelerations_with_config(bodies, &positions, config)
-}
-
 pub(crate) fn compute_accelerations_with_config(
     bodies: &[Body],
     positions: &[Vec2],
@@ -28,14 +25,33 @@ pub(crate) fn compute_accelerations_with_config(
 ) -> (Vec<Vec2>, SolverStats) {
     let alive_count = bodies.iter().filter(|body| body.alive).count();
     let mode = choose_runtime_mode(alive_count, config);
+    if let Some(groups) = &config.interaction_groups {
+        return (
+            grouped_accelerations_from_positions(bodies, positions, config, groups, mode),
+            SolverStats { mode },
+        );
+    }
 
     match mode {
+        #[cfg(feature = "simd")]
+        SolverRuntimeMode::Pairwise if uses_simd_kernel(config) => (
+            pairwise_simd_accelerations_from_positions(
+                bodies,
+                positions,
+                config.gravity_constant,
+                config.softening_epsilon,
+            ),
+            SolverStats {
+                mode: SolverRuntimeMode::Pairwise,
+            },
+        ),
         SolverRuntimeMode::Pairwise => (
             pairwise_accelerations_from_positions(
                 bodies,
                 positions,
                 config.gravity_constant,
                 config.softening_epsilon,
+                config.periodic_extent(),
             ),
             SolverStats {
                 mode: SolverRuntimeMode::Pairwise,
@@ -56,9 +72,13 @@ pub(crate) fn compute_accelerations_with_config(
     }
 }
 
-fn choose_runtime_mode(alive_count: usize, config: &EngineConfig) -> SolverRuntimeMode {
+/// Periodic boundaries always sum pairwise, since the tree has no periodic images.
+pub(crate) fn choose_runtime_mode(alive_count: usize, config: &EngineConfig) -> SolverRuntimeMode {
+    if config.periodic_extent().is_some() {
+        return SolverRuntimeMode::Pairwise;
+    }
     match config.gravity_solver {
-        GravitySolver::Pairwise => SolverRuntimeMode::Pairwise,
+        GravitySolver::Pairwise | GravitySolver::PairwiseSimd => SolverRuntimeMode::Pairwise,
         GravitySolver::BarnesHut => {
             if alive_count >= 2 {
                 SolverRuntimeMode::BarnesHut
@@ -81,10 +101,11 @@ fn pairwise_accelerations_from_positions(
     positions: &[Vec2],
     gravity_constant: f64,
     softening_epsilon: f64,
+    period: Option<Vec2>,
 ) -> Vec<Vec2> {
     let count = bodies.len();
     let mut accelerations = vec![Vec2::ZERO; count];
-    let epsilon2 = softening_epsilon * softening_epsilon;
+    let softening = softening_squares(bodies, softening_epsilon);
 
     for i in 0..count {
         if !bodies[i].alive {
@@ -95,15 +116,9 @@ fn pairwise_accelerations_from_positions(
                 continue;
             }
 
-            let delta = positions[j] - positions[i];
-            let dist_sq = delta.norm_squared() + epsilon2;
-            if dist_sq <= 0.0 {
-                continue;
-            }
-
-            let inv_dist = dist_sq.sqrt().recip();
-            let inv_dist3 = inv_dist * inv_dist * inv_dist;
-            let scale = gravity_constant * inv_dist3;
+            let delta = separation(positions[i], positions[j], period);
+            let epsilon2 = 0.5 * (softening[i] + softening[j]);
+            let scale = gravity_constant * softened_inverse_cube(delta.norm_squared(), epsilon2);
 
             accelerations[i] += delta * (scale * bodies[j].mass);
             accelerations[j] -= delta * (scale * bodies[i].mass);
@@ -113,6 +128,168 @@ fn pairwise_accelerations_from_positions(
     accelerations
 }
 
+/// Gravity restricted by the interaction matrix, summed source group by source group.
+/// Groups that attract nobody are never visited as sources, so massless tracers cost
+/// O(tracers x massive bodies) instead of O(n^2). The SIMD kernel is not used here.
+fn grouped_accelerations_from_positions(
+    bodies: &[Body],
+    positions: &[Vec2],
+    config: &EngineConfig,
+    groups: &InteractionGroups,
+    mode: SolverRuntimeMode,
+) -> Vec<Vec2> {
+    let mut members: BTreeMap<u32, Vec<usize>> = BTreeMap::new();
+    for (index, body) in bodies.iter().enumerate() {
+        if body.alive {
+            members
+                .entry(body.interaction_group())
+                .or_default()
+                .push(index);
+        }
+    }
+
+    let mut accelerations = vec![Vec2::ZERO; bodies.len()];
+    let masses = bodies.iter().map(|body| body.mass).collect::<Vec<_>>();
+    let softening = softening_squares(bodies, config.softening_epsilon);
+    let gravity_constant = config.gravity_constant;
+    let period = config.periodic_extent();
+    let mut stack = Vec::new();
+    for (&source, sources) in &members {
+        let targets = members
+            .iter()
+            .filter(|(target, _)| groups.attracts(source, **target))
+            .flat_map(|(_, indices)| indices.iter().copied())
+            .collect::<Vec<_>>();
+        if targets.is_empty() {
+            continue;
+        }
+
+        match mode {
+            SolverRuntimeMode::BarnesHut => {
+                let Some(tree) = QuadTree::build(positions, sources, &masses, &softening) else {
+                    continue;
+                };
+                for target in targets {
+                    accelerations[target] += tree.acceleration_at(
+                        target,
+                        positions[target],
+                        gravity_constant,
+                        softening[target],
+                        config.barnes_hut_theta,
+                        &mut stack,
+                    );
+                }
+            }
+            SolverRuntimeMode::Pairwise => {
+                for target in targets {
+                    for &index in sources.iter().filter(|&&index| index != target) {
+                        let delta = separation(positions[target], positions[index], period);
+                        let epsilon2 = 0.5 * (softening[index] + softening[target]);
+                        accelerations[target] += delta
+                            * (gravity_constant
+                                * masses[index]
+                                * softened_inverse_cube(delta.norm_squared(), epsilon2));
+                    }
+                }
+            }
+        }
+    }
+    accelerations
+}
+
+#[cfg(feature = "simd")]
+fn uses_simd_kernel(config: &EngineConfig) -> bool {
+    matches!(
+        config.gravity_solver,
+        GravitySolver::PairwiseSimd | GravitySolver::Auto
+    ) && config.periodic_extent().is_none()
+}
+
+#[cfg(feature = "simd")]
+const SIMD_LANES: usize = 4;
+/// Smallest squared separation the vectorized kernel resolves; its cube root inverse
+/// still fits in an f64.
+#[cfg(feature = "simd")]
+const SIMD_MIN_DIST_SQ: f64 = 1e-200;
+
+/// Full (non-symmetric) pairwise sum over structure-of-arrays lanes, written so the
+/// lane loop compiles to packed arithmetic on stable Rust.
+#[cfg(feature = "simd")]
+fn pairwise_simd_accelerations_from_positions(
+    bodies: &[Body],
+    positions: &[Vec2],
+    gravity_constant: f64,
+    softening_epsilon: f64,
+) -> Vec<Vec2> {
+    let mut accelerations = vec![Vec2::ZERO; bodies.len()];
+    let alive_indices = bodies
+        .iter()
+        .enumerate()
+        .filter_map(|(index, body)| body.alive.then_some(index))
+        .collect::<Vec<_>>();
+    if alive_indices.len() < 2 {
+        return accelerations;
+    }
+
+    // Padding lanes carry zero mass, so they contribute nothing.
+    let padded = alive_indices.len().div_ceil(SIMD_LANES) * SIMD_LANES;
+    let mut xs = vec![0.0; padded];
+    let mut ys = vec![0.0; padded];
+    let mut masses = vec![0.0; padded];
+    let mut softening = vec![0.0; padded];
+    let squares = softening_squares(bodies, softening_epsilon);
+    for (slot, &index) in alive_indices.iter().enumerate() {
+        xs[slot] = positions[index].x;
+        ys[slot] = positions[index].y;
+        masses[slot] = bodies[index].mass;
+        softening[slot] = squares[index];
+    }
+    let mut ax = vec![0.0; alive_indices.len()];
+    let mut ay = vec![0.0; alive_indices.len()];
+    simd_lane_sums(&xs, &ys, &masses, &softening, &mut ax, &mut ay);
+
+    for (slot, &index) in alive_indices.iter().enumerate() {
+        accelerations[index] = Vec2::new(ax[slot], ay[slot]) * gravity_constant;
+    }
+    accelerations
+}
+
+#[cfg(feature = "simd")]
+fn simd_lane_sums(
+    xs: &[f64],
+    ys: &[f64],
+    masses: &[f64],
+    softening: &[f64],
+    ax: &mut [f64],
+    ay: &mut [f64],
+) {
+    for slot in 0..ax.len() {
+        let (px, py, ps) = (xs[slot], ys[slot], softening[slot]);
+        let mut sum_x = [0.0; SIMD_LANES];
+        let mut sum_y = [0.0; SIMD_LANES];
+        for (((cx, cy), cm), cs) in xs
+            .chunks_exact(SIMD_LANES)
+            .zip(ys.chunks_exact(SIMD_LANES))
+            .zip(masses.chunks_exact(SIMD_LANES))
+            .zip(softening.chunks_exact(SIMD_LANES))
+        {
+            for lane in 0..SIMD_LANES {
+                let dx = cx[lane] - px;
+                let dy = cy[lane] - py;
+                // A floor instead of a branch keeps the lanes branch-free; with softening
+                // off it also keeps the body's own lane (dx = dy = 0) finite and zero.
+                let dist_sq = (dx * dx + dy * dy + 0.5 * (ps + cs[lane])).max(SIMD_MIN_DIST_SQ);
+                let inv = dist_sq.sqrt().recip();
+                let scale = cm[lane] * inv * inv * inv;
+                sum_x[lane] += dx * scale;
+                sum_y[lane] += dy * scale;
+            }
+        }
+        ax[slot] = sum_x.iter().sum();
+        ay[slot] = sum_y.iter().sum();
+    }
+}
+
 fn barnes_hut_accelerations_from_positions(
     bodies: &[Body],
     positions: &[Vec2],
@@ -134,105 +311,138 @@ fn barnes_hut_accelerations_from_positions(
     }
 
     let masses = bodies.iter().map(|body| body.mass).collect::<Vec<_>>();
-    let Some(root) = build_quadtree(positions, &alive_indices, &masses) else {
+    let softening = softening_squares(bodies, softening_epsilon);
+    let Some(tree) = QuadTree::build(positions, &alive_indices, &masses, &softening) else {
         return accelerations;
     };
 
-    let epsilon2 = softening_epsilon * softening_epsilon;
+    let mut stack = Vec::new();
 
     for &index in &alive_indices {
-        let mut acceleration = Vec2::ZERO;
-        accumulate_force_from_node(
-            &root,
+        accelerations[index] = tree.acceleration_at(
             index,
             positions[index],
             gravity_constant,
-            epsilon2,
+            softening[index],
             theta,
-            &mut acceleration,
+            &mut stack,
         );
-        accelerations[index] = acceleration;
     }
 
     accelerations
 }
 
-fn build_quadtree(positions: &[Vec2], alive_indices: &[usize], masses: &[f64]) -> Option<QuadNode> {
-    if alive_indices.is_empty() {
-        return None;
-    }
-
-    let mut min_x = f64::INFINITY;
-    let mut max_x = -f64::INFINITY;
-    let mut min_y = f64::INFINITY;
-    let mut max_y = -f64::INFINITY;
-
-    for &index in alive_indices {
-        let position = positions[index];
-        min_x = min_x.min(position.x);
-        max_x = max_x.max(position.x);
-        min_y = min_y.min(position.y);
-        max_y = max_y.max(position.y);
-    }
-
-    let span = (max_x - min_x).abs().max((max_y - min_y).abs()).max(1e-6);
-    let half_size = 0.5 * span + 1e-6;
-    let center = Vec2::new(0.5 * (min_x + max_x), 0.5 * (min_y + max_y));
-
-    let mut root = QuadNode::new(center, half_size);
-    let min_half = (half_size * 1e-6).max(1e-9);
-
-    for &index in alive_indices {
-        root.insert(index, positions, masses, min_half);
-    }
-
-    Some(root)
-}
-
-fn accumulate_force_from_node(
-    node: &QuadNode,
-    body_index: usize,
-    body_position: Vec2,
-    gravity_constant: f64,
-    epsilon2: f64,
+/// Relative acceleration error of a Barnes-Hut walk at `theta` against the exact
+/// softened sum, for each sampled body that feels a nonzero exact force.
+pub(crate) fn barnes_hut_relative_errors(
+    bodies: &[Body],
+    config: &EngineConfig,
     theta: f64,
-    out_acceleration: &mut Vec2,
-) {
-    if node.count == 0 || node.mass <= 0.0 {
-        return;
-    }
-
-    if node.count == 1 && node.body_index == Some(body_index) {
-        return;
-    }
-
-    let delta = node.com - body_position;
-    let dist_sq = delta.norm_squared() + epsilon2;
-    if dist_sq <= 0.0 {
-        return;
-    }
+    sample: &[usize],
+) -> Vec<f64> {
+    let alive_indices = bodies
+        .iter()
+        .enumerate()
+        .filter_map(|(index, body)| body.alive.then_some(index))
+        .collect::<Vec<_>>();
+    let positions = bodies.iter().map(|body| body.position).collect::<Vec<_>>();
+    let masses = bodies.iter().map(|body| body.mass).collect::<Vec<_>>();
+    let softening = softening_squares(bodies, config.softening_epsilon);
+    let Some(tree) = QuadTree::build(&positions, &alive_indices, &masses, &softening) else {
+        return Vec::new();
+    };
+    let mut stack = Vec::new();
+    sample
+        .iter()
+        .filter_map(|&index| {
+            let exact = alive_indices
+                .iter()
+                .filter(|&&other| other != index)
+                .map(|&other| {
+                    let delta = positions[other] - positions[index];
+                    let epsilon2 = 0.5 * (softening[index] + softening[other]);
+                    delta
+                        * (config.gravity_constant
+                            * masses[other]
+                            * softened_inverse_cube(delta.norm_squared(), epsilon2))
+                })
+                .sum::<Vec2>();
+            let approximate = tree.acceleration_at(
+                index,
+                positions[index],
+                config.gravity_constant,
+                softening[index],
+                theta,
+                &mut stack,
+            );
+            let scale = exact.norm();
+            (scale > 0.0).then(|| (approximate - exact).norm() / scale)
+        })
+        .collect()
+}
 
-    let distance = dist_sq.sqrt();
-    let size = node.half_size * 2.0;
+/// Softened potential and acceleration a massless probe would feel at each of
+/// `points`, from a Barnes-Hut walk at the configured theta. The probe softens with
+/// the global `softening_epsilon`.
+pub(crate) fn sample_field(
+    bodies: &[Body],
+    config: &EngineConfig,
+    points: &[Vec2],
+) -> Vec<(f64, Vec2)> {
+    let alive_indices = bodies
+        .iter()
+        .enumerate()
+        .filter_map(|(index, body)| body.alive.then_some(index))
+        .collect::<Vec<_>>();
+    let positions = bodies.iter().map(|body| body.position).collect::<Vec<_>>();
+    let masses = bodies.iter().map(|body| body.mass).collect::<Vec<_>>();
+    let softening = softening_squares(bodies, config.softening_epsilon);
+    let Some(tree) = QuadTree::build(&positions, &alive_indices, &masses, &softening) else {
+        return vec![(0.0, Vec2::ZERO); points.len()];
+    };
+    let probe_softening = config.softening_epsilon * config.softening_epsilon;
+    let mut stack = Vec::new();
+    points
+        .iter()
+        .map(|&point| {
+            tree.field_at(
+                point,
+                config.gravity_constant,
+                probe_softening,
+                config.barnes_hut_theta,
+                &mut stack,
+            )
+        })
+        .collect()
+}
 
-    if node.is_leaf() || (size / distance) < theta {
-        let inv_dist = distance.recip();
-        let inv_dist3 = inv_dist * inv_dist * inv_dist;
-        *out_acceleration += delta * (gravity_constant * node.mass * inv_dist3);
-        return;
-    }
+/// Indices of the alive bodies inside `region`, in engine order, found by walking a
+/// quadtree so only cells overlapping the region are inspected.
+pub(crate) fn bodies_in_region(bodies: &[Body], region: &Region) -> Vec<usize> {
+    let alive_indices = bodies
+        .iter()
+        .enumerate()
+        .filter_map(|(index, body)| body.alive.then_some(index))
+        .collect::<Vec<_>>();
+    let positions = bodies.iter().map(|body| body.position).collect::<Vec<_>>();
+    let masses = bodies.iter().map(|body| body.mass).collect::<Vec<_>>();
+    let softening = vec![0.0; bodies.len()];
+    let Some(tree) = QuadTree::build(&positions, &alive_indices, &masses, &softening) else {
+        return Vec::new();
+    };
+    let mut found = tree.indices_in(region, &positions);
+    found.sort_unstable();
+    found
+}
 
-    for child in node.children.iter().flatten() {
-        accumulate_force_from_node(
-            child,
-            body_index,
-            body_position,
-            gravity_constant,
-            epsilon2,
-            theta,
-            out_acceleration,
-        );
-    }
+/// Quadtree stored as a flat arena; the four children of a node are contiguous and
+/// addressed by the index of the first one, so building allocates only the node vector.
+#[derive(Clone, Debug)]
+struct QuadTree {
+    nodes: Vec<QuadNode>,
+    /// `(node, body)` pairs for leaves that hold several bodies because they could not
+    /// subdivide further; only region queries need them.
+    crowded: Vec<(usize, usize)>,
 }
 
 #[derive(Clone, Debug)]
@@ -243,93 +453,280 @@ struct QuadNode {
     com: Vec2,
     count: usize,
     body_index: Option<usize>,
-    children: [Option<Box<QuadNode>>; 4],
+    first_child: Option<usize>,
+    /// Largest squared softening among the members.
+    softening: f64,
 }
 
-impl QuadNode {
-    fn new(center: Vec2, half_size: f64) -> Self {
-        Self {
-            center,
-            half_size,
-            mass: 0.0,
-            com: Vec2::ZERO,
-            count: 0,
-            body_index: None,
-            children: Default::default(),
-        }
-    }
-
-    fn is_leaf(&self) -> bool {
-        self.children.iter().all(|child| child.is_none())
-    }
-
-    fn insert(&mut self, index: usize, positions: &[Vec2], masses: &[f64], min_half: f64) {
-        let position = positions[index];
-        let mass = masses[index];
+impl QuadTree {
+    const ROOT: usize = 0;
 
-        if self.count == 0 {
-            self.count = 1;
-            self.mass = mass;
-            self.com = position;
-            self.body_index = Some(index);
-            return;
+    fn build(
+        positions: &[Vec2],
+        alive_indices: &[usize],
+        masses: &[f64],
+        softening: &[f64],
+    ) -> Option<Self> {
+        if alive_indices.is_empty() {
+            return None;
         }
 
-        let previous_mass = self.mass;
-        let next_mass = previous_mass + mass;
-        if next_mass > 0.0 {
-            self.com = (self.com * previous_mass + position * mass) / next_mass;
+        let mut min_x = f64::INFINITY;
+        let mut max_x = -f64::INFINITY;
+        let mut min_y = f64::INFINITY;
+        let mut max_y = -f64::INFINITY;
+
+        for &index in alive_indices {
+            let position = positions[index];
+            min_x = min_x.min(position.x);
+            max_x = max_x.max(position.x);
+            min_y = min_y.min(position.y);
+            max_y = max_y.max(position.y);
         }
-        self.mass = next_mass;
-        self.count += 1;
-
-        if self.is_leaf() {
-            if let Some(existing_index) = self.body_index.take() {
-                let same_spot = (positions[existing_index] - position).norm_squared() <= 1e-18;
-                if self.half_size <= min_half || same_spot {
-                    self.body_index = None;
-                    return;
-                }
 
-                self.ensure_children();
-                self.insert_into_child(existing_index, positions, masses, min_half);
-                self.insert_into_child(index, positions, masses, min_half);
-                return;
-            }
+        let span = (max_x - min_x).abs().max((max_y - min_y).abs()).max(1e-6);
+        let half_size = 0.5 * span + 1e-6;
+        let center = Vec2::new(0.5 * (min_x + max_x), 0.5 * (min_y + max_y));
 
-            // Aggregated leaf already stores multiple bodies and cannot subdivide further.
-            return;
+        let mut tree = Self {
+            nodes: Vec::with_capacity(alive_indices.len() * 2 + 1),
+            crowded: Vec::new(),
+        };
+        tree.nodes.push(QuadNode::new(center, half_size));
+        let min_half = (half_size * 1e-6).max(1e-9);
+
+        for &index in alive_indices {
+            tree.insert(index, positions, masses, softening, min_half);
         }
 
-        self.insert_into_child(index, positions, masses, min_half);
+        Some(tree)
     }
 
-    fn insert_into_child(
+    fn insert(
         &mut self,
         index: usize,
         positions: &[Vec2],
         masses: &[f64],
+        softening: &[f64],
         min_half: f64,
     ) {
-        if self.is_leaf() {
-            self.ensure_children();
+        let position = positions[index];
+        let mass = masses[index];
+        let mut node_id = Self::ROOT;
+
+        loop {
+            let node = &mut self.nodes[node_id];
+            if node.count == 0 {
+                node.set_single(index, position, mass, softening[index]);
+                return;
+            }
+            node.softening = node.softening.max(softening[index]);
+
+            let previous_mass = node.mass;
+            let next_mass = previous_mass + mass;
+            if next_mass > 0.0 {
+                node.com = (node.com * previous_mass + position * mass) / next_mass;
+            }
+            node.mass = next_mass;
+            node.count += 1;
+
+            if let Some(first_child) = node.first_child {
+                node_id = first_child + node.child_index(position);
+                continue;
+            }
+
+            // Aggregated leaf already stores multiple bodies and cannot subdivide further.
+            let Some(existing_index) = node.body_index.take() else {
+                self.crowded.push((node_id, index));
+                return;
+            };
+            let same_spot = (positions[existing_index] - position).norm_squared() <= 1e-18;
+            if node.half_size <= min_half || same_spot {
+                self.crowded
+                    .extend([(node_id, existing_index), (node_id, index)]);
+                return;
+            }
+
+            let (center, child_half) = (node.center, node.half_size * 0.5);
+            let existing_child = node.child_index(positions[existing_index]);
+            let new_child = node.child_index(position);
+            let first_child = self.nodes.len();
+            self.nodes[node_id].first_child = Some(first_child);
+            self.nodes.extend(
+                (0..4).map(|child| {
+                    QuadNode::new(child_center(center, child_half, child), child_half)
+                }),
+            );
+            self.nodes[first_child + existing_child].set_single(
+                existing_index,
+                positions[existing_index],
+                masses[existing_index],
+                softening[existing_index],
+            );
+            node_id = first_child + new_child;
+        }
+    }
+
+    fn indices_in(&self, region: &Region, positions: &[Vec2]) -> Vec<usize> {
+        let mut found = Vec::new();
+        let mut stack = vec![Self::ROOT];
+        while let Some(node_id) = stack.pop() {
+            let node = &self.nodes[node_id];
+            if node.count == 0 || !node.overlaps(region) {
+                continue;
+            }
+            match (node.first_child, node.body_index) {
+                (Some(first_child), _) => stack.extend(first_child..first_child + 4),
+                (None, Some(index)) => found.push(index),
+                (None, None) => found.extend(
+                    self.crowded
+                        .iter()
+                        .filter(|(crowded_node, _)| *crowded_node == node_id)
+                        .map(|(_, index)| *index),
+                ),
+            }
+        }
+        found.retain(|&index| region.contains(positions[index]));
+        found
+    }
+
+    /// Depth-first walk with an explicit stack, visiting children in quadrant order.
+    fn acceleration_at(
+        &self,
+        body_index: usize,
+        body_position: Vec2,
+        gravity_constant: f64,
+        body_softening: f64,
+        theta: f64,
+        stack: &mut Vec<usize>,
+    ) -> Vec2 {
+        let mut acceleration = Vec2::ZERO;
+        stack.clear();
+        stack.push(Self::ROOT);
+
+        while let Some(node_id) = stack.pop() {
+            let node = &self.nodes[node_id];
+            if node.count == 0 || node.mass <= 0.0 {
+                continue;
+            }
+
+            if node.count == 1 && node.body_index == Some(body_index) {
+                continue;
+            }
+
+            let delta = node.com - body_position;
+            let raw_dist_sq = delta.norm_squared();
+            let epsilon2 = 0.5 * (body_softening + node.softening);
+            let dist_sq = raw_dist_sq + epsilon2;
+            if dist_sq <= 0.0 {
+                continue;
+            }
+
+            let size = node.half_size * 2.0;
+            match node.first_child {
+                Some(first_child) if (size / dist_sq.sqrt()) >= theta => {
+                    stack.extend((first_child..first_child + 4).rev());
+                }
+                _ => {
+                    acceleration += delta
+                        * (gravity_constant
+                            * node.mass
+                            * softened_inverse_cube(raw_dist_sq, epsilon2));
+                }
+            }
         }
 
-        let child_index = self.child_index(positions[index]);
-        if let Some(child) = self.children[child_index].as_mut() {
-            child.insert(index, positions, masses, min_half);
+        acceleration
+    }
+
+    /// Potential and acceleration at a point that is not itself a body, with the same
+    /// opening criterion as `acceleration_at`.
+    fn field_at(
+        &self,
+        point: Vec2,
+        gravity_constant: f64,
+        probe_softening: f64,
+        theta: f64,
+        stack: &mut Vec<usize>,
+    ) -> (f64, Vec2) {
+        let (mut potential, mut acceleration) = (0.0, Vec2::ZERO);
+        stack.clear();
+        stack.push(Self::ROOT);
+
+        while let Some(node_id) = stack.pop() {
+            let node = &self.nodes[node_id];
+            if node.count == 0 || node.mass <= 0.0 {
+                continue;
+            }
+
+            let delta = node.com - point;
+            let raw_dist_sq = delta.norm_squared();
+            let epsilon2 = 0.5 * (probe_softening + node.softening);
+            let dist_sq = raw_dist_sq + epsilon2;
+            if dist_sq <= 0.0 {
+                continue;
+            }
+
+            let size = node.half_size * 2.0;
+            match node.first_child {
+                Some(first_child) if (size / dist_sq.sqrt()) >= theta => {
+                    stack.extend((first_child..first_child + 4).rev());
+                }
+                _ => {
+                    potential -= gravity_constant * node.mass / dist_sq.sqrt();
+                    acceleration += delta
+                        * (gravity_constant
+                            * node.mass
+                            * softened_inverse_cube(raw_dist_sq, epsilon2));
+                }
+            }
         }
+
+        (potential, acceleration)
     }
+}
 
-    fn ensure_children(&mut self) {
-        if !self.is_leaf() {
-            return;
+impl QuadNode {
+    fn new(center: Vec2, half_size: f64) -> Self {
+        Self {
+            center,
+            half_size,
+            mass: 0.0,
+            com: Vec2::ZERO,
+            count: 0,
+            body_index: None,
+            first_child: None,
+            softening: 0.0,
         }
+    }
+
+    fn set_single(&mut self, index: usize, position: Vec2, mass: f64, softening: f64) {
+        self.count = 1;
+        self.mass = mass;
+        self.com = position;
+        self.body_index = Some(index);
+        self.softening = softening;
+    }
 
-        let child_half = self.half_size * 0.5;
-        for index in 0..4 {
-            let center = child_center(self.center, child_half, index);
-            self.children[index] = Some(Box::new(QuadNode::new(center, child_half)));
+    fn overlaps(&self, region: &Region) -> bool {
+        let (min, max) = (
+            self.center - Vec2::new(self.half_size, self.half_size),
+            self.center + Vec2::new(self.half_size, self.half_size),
+        );
+        match region {
+            Region::Circle { center, radius } => {
+                let nearest = Vec2::new(center.x.clamp(min.x, max.x), center.y.clamp(min.y, max.y));
+                (nearest - *center).norm_squared() <= radius * radius
+            }
+            Region::Rect {
+                min: region_min,
+                max: region_max,
+            } => {
+                min.x <= region_max.x
+                    && max.x >= region_min.x
+                    && min.y <= region_max.y
+                    && max.y >= region_min.y
+            }
         }
     }
 
@@ -341,7 +738,7 @@ impl QuadNode {
 }
 
 fn child_center(center: Vec2, child_half: f64, index: usize) -> Vec2 {
-    let x_offset = if index % 2 == 0 {
+    let x_offset = if index.is_multiple_of(2) {
         -child_half
     } else {
         child_half
@@ -0,0 +1,141 @@
+use serde::{Deserialize, Serialize};
+
+use crate::errors::{EngineError, Result};
+use crate::math::Vec2;
+use crate::types::{Body, StepSummary};
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum StopCondition {
+    /// Simulation time reaches `sim_time`.
+    SimTime {
+        sim_time: f64,
+    },
+    /// `ticks` ticks have run since the call started.
+    Ticks {
+        ticks: u64,
+    },
+    FirstCollision,
+    /// The body is farther than `radius` from `center` (the origin by default).
+    BodyBeyond {
+        body_id: String,
+        radius: f64,
+        #[serde(default)]
+        center: Option<Vec2>,
+    },
+    /// Two bodies come within `distance` of each other.
+    Separation {
+        first_id: String,
+        second_id: String,
+        distance: f64,
+    },
+    Any {
+        conditions: Vec<StopCondition>,
+    },
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RunOutcome {
+    pub summary: StepSummary,
+    /// Kind of the condition that fired, the pausing event kind if `pause_on_events`
+    /// ended the run, or `None` when `max_ticks` ran out first.
+    pub stop_reason: Option<String>,
+}
+
+impl StopCondition {
+    pub fn validate(&self, bodies: &[Body]) -> Result<()> {
+        let require_body = |id: &String| {
+            if bodies.iter().any(|body| body.id == *id) {
+                Ok(())
+            } else {
+                Err(EngineError::BodyNotFound(id.clone()))
+            }
+        };
+        match self {
+            StopCondition::SimTime { sim_time } if !sim_time.is_finite() => Err(
+                EngineError::InvalidConfig("stop sim_time must be finite".to_string()),
+            ),
+            StopCondition::BodyBeyond {
+                body_id, radius, ..
+            } => {
+                if !radius.is_finite() || *radius <= 0.0 {
+                    return Err(EngineError::InvalidConfig(
+                        "stop radius must be finite and > 0".to_string(),
+                    ));
+                }
+                require_body(body_id)
+            }
+            StopCondition::Separation {
+                first_id,
+                second_id,
+                distance,
+            } => {
+                if !distance.is_finite() || *distance < 0.0 {
+                    return Err(EngineError::InvalidConfig(
+                        "stop separation must be finite and >= 0".to_string(),
+                    ));
+                }
+                require_body(first_id)?;
+                require_body(second_id)
+            }
+            StopCondition::Any { conditions } => conditions
+                .iter()
+                .try_for_each(|condition| condition.validate(bodies)),
+            _ => Ok(()),
+        }
+    }
+
+    /// Returns the kind of the first satisfied condition.
+    pub(crate) fn check(
+        &self,
+        bodies: &[Body],
+        sim_time: f64,
+        ticks_run: u64,
+        last_step: Option<&StepSummary>,
+    ) -> Option<&'static str> {
+        let position = |id: &str| {
+            bodies
+                .iter()
+                .find(|body| body.alive && body.id == id)
+                .map(|body| body.position)
+        };
+        let met = match self {
+            StopCondition::SimTime { sim_time: target } => sim_time >= *target,
+            StopCondition::Ticks { ticks } => ticks_run >= *ticks,
+            StopCondition::FirstCollision => {
+                last_step.is_some_and(|summary| summary.collision_events > 0)
+            }
+            StopCondition::BodyBeyond {
+                body_id,
+                radius,
+                center,
+            } => position(body_id)
+                .is_some_and(|position| (position - center.unwrap_or(Vec2::ZERO)).norm() > *radius),
+            StopCondition::Separation {
+                first_id,
+                second_id,
+                distance,
+            } => position(first_id)
+                .zip(position(second_id))
+                .is_some_and(|(a, b)| (b - a).norm() <= *distance),
+            StopCondition::Any { conditions } => {
+                return conditions
+                    .iter()
+                    .find_map(|condition| condition.check(bodies, sim_time, ticks_run, last_step));
+            }
+        };
+        met.then(|| self.kind())
+    }
+
+    pub fn kind(&self) -> &'static str {
+        match self {
+            StopCondition::SimTime { .. } => "simTime",
+            StopCondition::Ticks { .. } => "ticks",
+            StopCondition::FirstCollision => "firstCollision",
+            StopCondition::BodyBeyond { .. } => "bodyBeyond",
+            StopCondition::Separation { .. } => "separation",
+            StopCondition::Any { .. } => "any",
+        }
+    }
+}
@@ -0,0 +1,203 @@
+//! Load testing with deterministic synthetic edit streams.
+//!
+//! A workload runs in rounds: spawn bodies, delete earlier spawns, optionally flip the
+//! gravity solver, then step a batch of ticks. Each operation is timed on its own so
+//! regressions on the interaction path show up separately from pure stepping. The
+//! edit stream depends only on the seed; the latencies are wall-clock.
+
+use std::time::Instant;
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::GravitySolver;
+use crate::engine::SimulationEngine;
+use crate::errors::{EngineError, Result};
+use crate::math::Vec2;
+use crate::random::Xoshiro256;
+use crate::types::{Body, BodyEdit};
+
+const SPAWN_ID_PREFIX: &str = "stress-";
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct StressWorkload {
+    pub seed: u64,
+    pub rounds: u32,
+    #[serde(default)]
+    pub spawns_per_round: u32,
+    /// Deletes pick among earlier spawns only, so the loaded scenario is left intact.
+    #[serde(default)]
+    pub deletes_per_round: u32,
+    /// Toggle between pairwise and Barnes-Hut every this many rounds; 0 never flips.
+    #[serde(default)]
+    pub config_flip_every: u32,
+    #[serde(default = "default_step_batch")]
+    pub step_batch: u32,
+    /// Spawns land uniformly in a square of this half-width around the origin.
+    #[serde(default = "default_spawn_extent")]
+    pub spawn_extent: f64,
+    #[serde(default = "default_spawn_mass")]
+    pub spawn_mass: f64,
+}
+
+fn default_step_batch() -> u32 {
+    1
+}
+
+fn default_spawn_extent() -> f64 {
+    10.0
+}
+
+fn default_spawn_mass() -> f64 {
+    1e-3
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OperationLatency {
+    pub operation: String,
+    pub count: usize,
+    pub p50_micros: f64,
+    pub p90_micros: f64,
+    pub p99_micros: f64,
+    pub max_micros: f64,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StressReport {
+    pub rounds: u32,
+    pub ticks: u64,
+    pub final_body_count: usize,
+    /// One entry per operation that ran at least once, in the order
+    /// spawn, delete, configFlip, step.
+    pub operations: Vec<OperationLatency>,
+}
+
+impl StressWorkload {
+    pub fn validate(&self) -> Result<()> {
+        if self.step_batch == 0 {
+            return Err(EngineError::InvalidConfig(
+                "stress step_batch must be > 0".to_string(),
+            ));
+        }
+        if !self.spawn_extent.is_finite() || self.spawn_extent <= 0.0 {
+            return Err(EngineError::InvalidConfig(
+                "stress spawn_extent must be finite and > 0".to_string(),
+            ));
+        }
+        if !self.spawn_mass.is_finite() || self.spawn_mass <= 0.0 {
+            return Err(EngineError::InvalidConfig(
+                "stress spawn_mass must be finite and > 0".to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+pub fn run_stress(
+    engine: &mut SimulationEngine,
+    workload: &StressWorkload,
+) -> Result<StressReport> {
+    workload.validate()?;
+    let mut rng = Xoshiro256::new(workload.seed);
+    let mut samples: [Vec<f64>; 4] = Default::default();
+    let mut next_spawn = 0_u64;
+    let start_tick = engine.tick();
+
+    for round in 1..=workload.rounds {
+        for _ in 0..workload.spawns_per_round {
+            let body = spawn_body(&mut rng, &mut next_spawn, engine, workload);
+            timed(&mut samples[0], || {
+                engine.apply_edit(BodyEdit::Create(body))
+            })?;
+        }
+
+        for _ in 0..workload.deletes_per_round {
+            let spawned = engine
+                .bodies()
+                .iter()
+                .filter(|body| body.id.starts_with(SPAWN_ID_PREFIX))
+                .map(|body| body.id.clone())
+                .collect::<Vec<_>>();
+            if spawned.is_empty() {
+                break;
+            }
+            let id = spawned[(rng.next_u64() % spawned.len() as u64) as usize].clone();
+            timed(&mut samples[1], || {
+                engine.apply_edit(BodyEdit::Delete { id })
+            })?;
+        }
+
+        if workload.config_flip_every > 0 && round.is_multiple_of(workload.config_flip_every) {
+            let mut config = engine.config().clone();
+            config.gravity_solver = match config.gravity_solver {
+                GravitySolver::BarnesHut => GravitySolver::Pairwise,
+                _ => GravitySolver::BarnesHut,
+            };
+            timed(&mut samples[2], || engine.set_config(config))?;
+        }
+
+        timed(&mut samples[3], || {
+            engine.step(workload.step_batch).map(|_| ())
+        })?;
+    }
+
+    let operations = ["spawn", "delete", "configFlip", "step"]
+        .into_iter()
+        .zip(samples)
+        .filter(|(_, micros)| !micros.is_empty())
+        .map(|(operation, micros)| latency(operation, micros))
+        .collect();
+    Ok(StressReport {
+        rounds: workload.rounds,
+        ticks: engine.tick() - start_tick,
+        final_body_count: engine.bodies().len(),
+        operations,
+    })
+}
+
+fn spawn_body(
+    rng: &mut Xoshiro256,
+    next_spawn: &mut u64,
+    engine: &SimulationEngine,
+    workload: &StressWorkload,
+) -> Body {
+    // Skip ids a previous run already left in the engine.
+    let id = loop {
+        *next_spawn += 1;
+        let id = format!("{SPAWN_ID_PREFIX}{next_spawn}");
+        if engine.bodies().iter().all(|body| body.id != id) {
+            break id;
+        }
+    };
+    let extent = workload.spawn_extent;
+    let position = Vec2::new(rng.range(-extent, extent), rng.range(-extent, extent));
+    let velocity = Vec2::new(rng.range(-1.0, 1.0), rng.range(-1.0, 1.0)) * 1e-3;
+    Body::new(id, workload.spawn_mass, 1e-3, position, velocity)
+}
+
+fn timed(samples: &mut Vec<f64>, operation: impl FnOnce() -> Result<()>) -> Result<()> {
+    let start = Instant::now();
+    operation()?;
+    samples.push(start.elapsed().as_secs_f64() * 1e6);
+    Ok(())
+}
+
+fn latency(operation: &str, mut micros: Vec<f64>) -> OperationLatency {
+    micros.sort_by(f64::total_cmp);
+    // Nearest-rank percentile.
+    let percentile = |p: f64| {
+        let rank = (p * micros.len() as f64).ceil() as usize;
+        micros[rank.clamp(1, micros.len()) - 1]
+    };
+    OperationLatency {
+        operation: operation.to_string(),
+        count: micros.len(),
+        p50_micros: percentile(0.5),
+        p90_micros: percentile(0.9),
+        p99_micros: percentile(0.99),
+        max_micros: micros[micros.len() - 1],
+    }
+}
@@ -0,0 +1,97 @@
+//! Tidal disruption: small bodies that pass inside the Roche limit of a much heavier
+//! one, optionally broken into fragments.
+
+use std::collections::HashSet;
+
+use crate::config::TidalDisruption;
+use crate::math::Vec2;
+use crate::types::Body;
+
+/// Fluid-body Roche limit `coefficient * R * (rho_M / rho_m)^(1/3)` with densities
+/// taken as `mass / radius^3`, which reduces to `coefficient * r * (M / m)^(1/3)` in
+/// the satellite's radius `r` and mass `m`.
+pub fn roche_limit(
+    primary_mass: f64,
+    satellite_mass: f64,
+    satellite_radius: f64,
+    coefficient: f64,
+) -> f64 {
+    coefficient * satellite_radius * (primary_mass / satellite_mass).cbrt()
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct RocheCrossing {
+    pub index: usize,
+    pub primary: usize,
+    pub distance: f64,
+    pub roche_limit: f64,
+}
+
+/// Every alive, unfixed body inside the Roche limit of a primary at least
+/// `min_mass_ratio` times heavier, paired with the primary it is deepest inside.
+pub(crate) fn find_roche_crossings(
+    bodies: &[Body],
+    policy: &TidalDisruption,
+) -> Vec<RocheCrossing> {
+    let mut crossings = Vec::new();
+    for (index, body) in bodies.iter().enumerate() {
+        if !body.alive || body.fixed || body.radius <= 0.0 {
+            continue;
+        }
+        let mut deepest: Option<RocheCrossing> = None;
+        for (primary, other) in bodies.iter().enumerate() {
+            if primary == index || !other.alive || other.mass < policy.min_mass_ratio * body.mass {
+                continue;
+            }
+            let distance = (body.position - other.position).norm();
+            let limit = roche_limit(other.mass, body.mass, body.radius, policy.roche_coefficient);
+            if distance >= limit {
+                continue;
+            }
+            if deepest.is_none_or(|known| distance / limit < known.distance / known.roche_limit) {
+                deepest = Some(RocheCrossing {
+                    index,
+                    primary,
+                    distance,
+                    roche_limit: limit,
+                });
+            }
+        }
+        crossings.extend(deepest);
+    }
+    crossings
+}
+
+/// Splits `body` into `count` equal pieces of the same density strung along the line
+/// to `primary`, just clear of each other. Mass, momentum and the centre of mass are
+/// preserved; ids are `"{id}#{k}"`, skipping any already in `taken`.
+pub(crate) fn fragment(
+    body: &Body,
+    primary: &Body,
+    count: u32,
+    taken: &mut HashSet<String>,
+) -> Vec<Body> {
+    let pieces = f64::from(count);
+    let radius = body.radius / pieces.cbrt();
+    let axis = (body.position - primary.position).normalized_or(Vec2::new(1.0, 0.0));
+    let mut suffix = 0;
+    (0..count)
+        .map(|k| {
+            let id = loop {
+                suffix += 1;
+                let id = format!("{}#{suffix}", body.id);
+                if taken.insert(id.clone()) {
+                    break id;
+                }
+            };
+            let offset = (f64::from(k) - 0.5 * (pieces - 1.0)) * 2.2 * radius;
+            Body {
+                id,
+                mass: body.mass / pieces,
+                radius,
+                position: body.position + axis * offset,
+                ..body.clone()
+            }
+        })
+        .collect()
+}
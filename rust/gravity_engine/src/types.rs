@@ -1,19 +1,51 @@
+use std::collections::BTreeMap;
+
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 
+use crate::binary::content_checksum;
+use crate::clock::SimClock;
 use crate::config::EngineConfig;
+use crate::diagnostics::{Diagnostics, ForceErrorStats};
+use crate::epochs::Epoch;
 use crate::errors::{EngineError, Result};
-use crate::math::Vec2;
+use crate::events::{MergeRecord, SimulationEvent};
+use crate::math::{Transform2, Vec2};
+use crate::random::PerturbSpec;
+use crate::units::UnitSystem;
 
-#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct BodyMetadata {
     pub label: Option<String>,
     pub kind: Option<String>,
     pub color: Option<String>,
+    /// Merges this body has survived, see `EngineConfig::count_impacts`.
+    #[serde(default)]
+    pub impacts_absorbed: u32,
+    /// Free-form key/value tags, matched by `BodyQuery`.
+    #[serde(default)]
+    pub tags: BTreeMap<String, String>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct Oblateness {
+    pub j2: f64,
+    pub equatorial_radius: f64,
+    /// Tilt of the equator against the simulation plane, in radians.
+    #[serde(default)]
+    pub obliquity: f64,
+    /// In-plane direction of the equator's line of nodes, in radians.
+    #[serde(default)]
+    pub node_angle: f64,
 }
 
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct Body {
     pub id: String,
     pub mass: f64,
@@ -22,6 +54,38 @@ pub struct Body {
     pub velocity: Vec2,
     pub alive: bool,
     pub metadata: Option<BodyMetadata>,
+    #[serde(default)]
+    pub luminosity: Option<f64>,
+    #[serde(default)]
+    pub area_to_mass: Option<f64>,
+    #[serde(default)]
+    pub yarkovsky: Option<f64>,
+    #[serde(default)]
+    pub oblateness: Option<Oblateness>,
+    #[serde(default)]
+    pub origin_group: Option<u32>,
+    /// Non-collidable bodies (e.g. tracer swarms) are skipped by collision detection.
+    #[serde(default = "default_collidable")]
+    pub collidable: bool,
+    /// Anchored bodies exert gravity but are never moved by integration or collisions.
+    #[serde(default)]
+    pub fixed: bool,
+    /// Interaction group, see `EngineConfig::interaction_groups`; `None` is group 0.
+    #[serde(default)]
+    pub group: Option<u32>,
+    /// Overrides `EngineConfig::softening_epsilon` for pairs involving this body.
+    #[serde(default)]
+    pub softening: Option<f64>,
+    /// Orientation in radians, kept in `[0, 2pi)`.
+    #[serde(default)]
+    pub angle: f64,
+    /// Spin rate in radians per unit time, counter-clockwise positive.
+    #[serde(default)]
+    pub angular_velocity: f64,
+}
+
+fn default_collidable() -> bool {
+    true
 }
 
 impl Body {
@@ -40,9 +104,33 @@ impl Body {
             velocity,
             alive: true,
             metadata: None,
+            luminosity: None,
+            area_to_mass: None,
+            yarkovsky: None,
+            oblateness: None,
+            origin_group: None,
+            collidable: true,
+            fixed: false,
+            group: None,
+            softening: None,
+            angle: 0.0,
+            angular_velocity: 0.0,
         }
     }
 
+    /// Moment of inertia about the centre, treating the body as a uniform disk.
+    pub fn moment_of_inertia(&self) -> f64 {
+        0.5 * self.mass * self.radius * self.radius
+    }
+
+    pub fn is_finite(&self) -> bool {
+        self.position.is_finite() && self.velocity.is_finite()
+    }
+
+    pub fn interaction_group(&self) -> u32 {
+        self.group.unwrap_or(0)
+    }
+
     pub fn validate(&self) -> Result<()> {
         if self.id.trim().is_empty() {
             return Err(EngineError::InvalidBody("id must not be empty".to_string()));
@@ -71,12 +159,64 @@ impl Body {
                 self.id
             )));
         }
+        if !self.angle.is_finite() || !self.angular_velocity.is_finite() {
+            return Err(EngineError::InvalidBody(format!(
+                "body '{}' angle and angular_velocity must be finite",
+                self.id
+            )));
+        }
+        if let Some(softening) = self.softening
+            && (!softening.is_finite() || softening < 0.0)
+        {
+            return Err(EngineError::InvalidBody(format!(
+                "body '{}' softening must be finite and >= 0",
+                self.id
+            )));
+        }
+        if let Some(luminosity) = self.luminosity
+            && (!luminosity.is_finite() || luminosity < 0.0)
+        {
+            return Err(EngineError::InvalidBody(format!(
+                "body '{}' luminosity must be finite and >= 0",
+                self.id
+            )));
+        }
+        if let Some(area_to_mass) = self.area_to_mass
+            && (!area_to_mass.is_finite() || area_to_mass < 0.0)
+        {
+            return Err(EngineError::InvalidBody(format!(
+                "body '{}' area_to_mass must be finite and >= 0",
+                self.id
+            )));
+        }
+        if let Some(yarkovsky) = self.yarkovsky
+            && !yarkovsky.is_finite()
+        {
+            return Err(EngineError::InvalidBody(format!(
+                "body '{}' yarkovsky must be finite",
+                self.id
+            )));
+        }
+        if let Some(oblateness) = &self.oblateness {
+            let valid = oblateness.j2.is_finite()
+                && oblateness.equatorial_radius.is_finite()
+                && oblateness.equatorial_radius > 0.0
+                && oblateness.obliquity.is_finite()
+                && oblateness.node_angle.is_finite();
+            if !valid {
+                return Err(EngineError::InvalidBody(format!(
+                    "body '{}' oblateness needs finite j2/angles and equatorial_radius > 0",
+                    self.id
+                )));
+            }
+        }
         Ok(())
     }
 }
 
 #[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct BodyUpdate {
     pub id: String,
     pub mass: Option<f64>,
@@ -85,14 +225,106 @@ pub struct BodyUpdate {
     pub velocity: Option<Vec2>,
     pub alive: Option<bool>,
     pub metadata: Option<BodyMetadata>,
+    #[serde(default)]
+    pub collidable: Option<bool>,
+    #[serde(default)]
+    pub fixed: Option<bool>,
+    #[serde(default)]
+    pub group: Option<u32>,
+    #[serde(default)]
+    pub softening: Option<f64>,
+    #[serde(default)]
+    pub angle: Option<f64>,
+    #[serde(default)]
+    pub angular_velocity: Option<f64>,
 }
 
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub enum BodyEdit {
     Create(Body),
     Update(BodyUpdate),
-    Delete { id: String },
+    Delete {
+        id: String,
+    },
+    /// Applies `transform` to the listed bodies, or to every body when `ids` is empty.
+    Transform {
+        #[serde(default)]
+        ids: Vec<String>,
+        transform: Transform2,
+    },
+    /// Seeded Gaussian kick to the listed bodies, or to every body when `ids` is empty.
+    Perturb {
+        #[serde(default)]
+        ids: Vec<String>,
+        spec: PerturbSpec,
+    },
+    /// Shifts every body into `frame` (see `SimulationEngine::recenter`).
+    Recenter {
+        frame: Frame,
+    },
+}
+
+/// Reference frame for `SimulationEngine::recenter`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub enum Frame {
+    /// Mass-weighted mean position and velocity of the alive bodies.
+    CenterOfMass,
+    /// Rest frame of one body.
+    Body(String),
+}
+
+impl Frame {
+    /// Position and velocity of the frame's origin, or `None` when the body is missing
+    /// or dead, or no alive body has mass.
+    pub(crate) fn origin(&self, bodies: &[Body]) -> Option<(Vec2, Vec2)> {
+        match self {
+            Self::CenterOfMass => {
+                let (mut mass, mut moment, mut momentum) = (0.0, Vec2::ZERO, Vec2::ZERO);
+                for body in bodies.iter().filter(|body| body.alive) {
+                    mass += body.mass;
+                    moment += body.position * body.mass;
+                    momentum += body.velocity * body.mass;
+                }
+                (mass > 0.0).then(|| (moment / mass, momentum / mass))
+            }
+            Self::Body(id) => bodies
+                .iter()
+                .find(|body| body.alive && body.id == *id)
+                .map(|body| (body.position, body.velocity)),
+        }
+    }
+}
+
+/// Substep levels chosen by error-controlled dt, one per tick starting at `start_tick`.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DtSchedule {
+    pub start_tick: u64,
+    pub levels: Vec<u8>,
+}
+
+/// Bodies that left the simulation, with the stable slots they held (see
+/// `SimulationEngine::body_index_map`). `ids` and `slots` are parallel.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RemovedBodies {
+    pub ids: Vec<String>,
+    pub slots: Vec<usize>,
+}
+
+impl RemovedBodies {
+    pub fn is_empty(&self) -> bool {
+        self.ids.is_empty()
+    }
+
+    pub(crate) fn extend(&mut self, other: RemovedBodies) {
+        self.ids.extend(other.ids);
+        self.slots.extend(other.slots);
+    }
 }
 
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
@@ -116,6 +348,103 @@ pub struct StepSummary {
     pub max_body_count: usize,
     #[serde(default)]
     pub last_solver_mode: String,
+    #[serde(default)]
+    pub events: Vec<SimulationEvent>,
+    #[serde(default)]
+    pub diagnostics: Option<Diagnostics>,
+    /// Integrator substeps taken; equals `ticks_applied` unless dt is error-controlled.
+    #[serde(default)]
+    pub substeps: u64,
+    /// Force evaluations served entirely from the cache.
+    #[serde(default)]
+    pub force_cache_hits: u64,
+    /// Force evaluations that only re-summed bodies past the displacement threshold.
+    #[serde(default)]
+    pub force_cache_partial_updates: u64,
+    /// Kind of the `pause_on_events` event that ended the call early, or "paused"
+    /// when the engine is paused.
+    #[serde(default)]
+    pub stop_reason: Option<String>,
+    /// Events the engine's log evicted or refused because it was full. `events` is
+    /// bounded the same way.
+    #[serde(default)]
+    pub events_dropped: u64,
+    /// Events the log folded into a newer one of the same kind.
+    #[serde(default)]
+    pub events_coalesced: u64,
+    /// Bodies absorbed into super-particles by coarse-graining.
+    #[serde(default)]
+    pub aggregated_bodies: u64,
+    /// Relative angular momentum drift at the last `angular_momentum_guard` check,
+    /// before any correction.
+    #[serde(default)]
+    pub angular_momentum_drift: Option<f64>,
+    /// Angular momentum the guard added back, summed over the call.
+    #[serde(default)]
+    pub angular_momentum_correction: f64,
+    /// Latest `force_error_sampling` result in the call.
+    #[serde(default)]
+    pub force_error: Option<ForceErrorStats>,
+    /// Relative total-energy drift at the last `energy_watchdog` check.
+    #[serde(default)]
+    pub energy_drift: Option<f64>,
+    /// `barnes_hut_theta` at the end of the call while `theta_tuning` is on.
+    #[serde(default)]
+    pub barnes_hut_theta: Option<f64>,
+    /// Bodies merged away or coarse-grained during the call.
+    #[serde(default)]
+    pub removed_bodies: RemovedBodies,
+    /// Merge genealogy entries recorded during the call.
+    #[serde(default)]
+    pub merges: Vec<MergeRecord>,
+}
+
+impl StepSummary {
+    /// Folds a later summary into this one, as if both ran in a single `step` call.
+    pub fn absorb(&mut self, next: StepSummary) {
+        self.ticks_applied += next.ticks_applied;
+        self.final_tick = next.final_tick;
+        self.sim_time = next.sim_time;
+        self.collision_events += next.collision_events;
+        self.merged_events += next.merged_events;
+        self.warnings.extend(next.warnings);
+        self.pairwise_ticks += next.pairwise_ticks;
+        self.barnes_hut_ticks += next.barnes_hut_ticks;
+        self.step_wall_time_micros += next.step_wall_time_micros;
+        if self.ticks_applied > 0 {
+            self.average_tick_micros = self.step_wall_time_micros / u64::from(self.ticks_applied);
+        }
+        self.max_body_count = self.max_body_count.max(next.max_body_count);
+        self.last_solver_mode = next.last_solver_mode;
+        self.events.extend(next.events);
+        if next.diagnostics.is_some() {
+            self.diagnostics = next.diagnostics;
+        }
+        self.substeps += next.substeps;
+        self.force_cache_hits += next.force_cache_hits;
+        self.force_cache_partial_updates += next.force_cache_partial_updates;
+        if next.stop_reason.is_some() {
+            self.stop_reason = next.stop_reason;
+        }
+        self.events_dropped += next.events_dropped;
+        self.events_coalesced += next.events_coalesced;
+        self.aggregated_bodies += next.aggregated_bodies;
+        if next.angular_momentum_drift.is_some() {
+            self.angular_momentum_drift = next.angular_momentum_drift;
+        }
+        self.angular_momentum_correction += next.angular_momentum_correction;
+        if next.force_error.is_some() {
+            self.force_error = next.force_error;
+        }
+        if next.barnes_hut_theta.is_some() {
+            self.barnes_hut_theta = next.barnes_hut_theta;
+        }
+        if next.energy_drift.is_some() {
+            self.energy_drift = next.energy_drift;
+        }
+        self.removed_bodies.extend(next.removed_bodies);
+        self.merges.extend(next.merges);
+    }
 }
 
 impl Default for StepSummary {
@@ -133,6 +462,22 @@ impl Default for StepSummary {
             average_tick_micros: 0,
             max_body_count: 0,
             last_solver_mode: "pairwise".to_string(),
+            events: Vec::new(),
+            diagnostics: None,
+            substeps: 0,
+            force_cache_hits: 0,
+            force_cache_partial_updates: 0,
+            stop_reason: None,
+            events_dropped: 0,
+            events_coalesced: 0,
+            aggregated_bodies: 0,
+            angular_momentum_drift: None,
+            angular_momentum_correction: 0.0,
+            force_error: None,
+            energy_drift: None,
+            barnes_hut_theta: None,
+            removed_bodies: RemovedBodies::default(),
+            merges: Vec::new(),
         }
     }
 }
@@ -148,6 +493,7 @@ pub struct SimulationState {
 
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct ScenarioMetadata {
     pub name: String,
     pub description: Option<String>,
@@ -158,15 +504,26 @@ pub struct ScenarioMetadata {
 
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct Scenario {
     pub schema_version: String,
     pub metadata: ScenarioMetadata,
     pub engine_config: EngineConfig,
     pub bodies: Vec<Body>,
+    /// Staged physics; `engine_config` is the base the overlays apply to.
+    #[serde(default)]
+    pub epochs: Vec<Epoch>,
+    /// Units the numbers are stored in; see [`Scenario::from_json`].
+    #[serde(default)]
+    pub units: Option<UnitSystem>,
+    /// Content hash stamped on save; files without one load unverified.
+    #[serde(default)]
+    pub checksum: Option<String>,
 }
 
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct Snapshot {
     pub schema_version: String,
     pub created_at: String,
@@ -174,6 +531,110 @@ pub struct Snapshot {
     pub sim_time: f64,
     pub config_hash: String,
     pub bodies: Vec<Body>,
+    /// Exact quantized time, present when the engine runs with a `time_quantum`.
+    #[serde(default)]
+    pub clock: Option<SimClock>,
+    #[serde(default)]
+    pub markers: Vec<TimeMarker>,
+    /// Content hash stamped on save; files without one load unverified.
+    #[serde(default)]
+    pub checksum: Option<String>,
+}
+
+/// Host annotation pinned to the tick it was added at.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct TimeMarker {
+    pub name: String,
+    pub tick: u64,
+    pub sim_time: f64,
+}
+
+/// One body's state at a sampled tick of [`crate::SimulationEngine::predict`].
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TrajectorySample {
+    pub tick: u64,
+    pub sim_time: f64,
+    pub body_id: String,
+    pub position: Vec2,
+    pub velocity: Vec2,
+}
+
+impl Scenario {
+    pub fn compute_checksum(&self) -> String {
+        let content = (
+            &self.schema_version,
+            &self.metadata,
+            &self.engine_config,
+            &self.bodies,
+        );
+        // Scenarios without epochs or units keep the checksum they had before those
+        // existed.
+        match (self.epochs.is_empty(), &self.units) {
+            (true, None) => content_checksum(&content),
+            (false, None) => content_checksum(&(content, &self.epochs)),
+            (_, Some(units)) => content_checksum(&(content, &self.epochs, units)),
+        }
+    }
+
+    /// Parses scenario JSON, first converting quantity strings such as `"1 Msun"` into
+    /// the units declared by its `units` block.
+    pub fn from_json(mut value: Value) -> Result<Self> {
+        if let Some(units) = value.get("units").filter(|units| !units.is_null()) {
+            let units = UnitSystem::deserialize(units)
+                .map_err(|error| EngineError::SchemaValidationFailed(error.to_string()))?;
+            units.validate()?;
+            units.resolve_scenario(&mut value)?;
+        }
+        Self::deserialize(value)
+            .map_err(|error| EngineError::SchemaValidationFailed(error.to_string()))
+    }
+
+    pub fn verify_checksum(&self) -> Result<()> {
+        verify_checksum("scenario", self.checksum.as_deref(), || {
+            self.compute_checksum()
+        })
+    }
+}
+
+impl Snapshot {
+    pub fn compute_checksum(&self) -> String {
+        content_checksum(&(
+            &self.schema_version,
+            &self.created_at,
+            self.tick,
+            self.sim_time,
+            &self.config_hash,
+            &self.bodies,
+            &self.clock,
+            &self.markers,
+        ))
+    }
+
+    pub fn verify_checksum(&self) -> Result<()> {
+        verify_checksum("snapshot", self.checksum.as_deref(), || {
+            self.compute_checksum()
+        })
+    }
+}
+
+fn verify_checksum(
+    name: &str,
+    stored: Option<&str>,
+    compute: impl FnOnce() -> String,
+) -> Result<()> {
+    let Some(stored) = stored else {
+        return Ok(());
+    };
+    let actual = compute();
+    if stored != actual {
+        return Err(EngineError::ChecksumMismatch(format!(
+            "{name} checksum {stored} does not match content {actual}"
+        )));
+    }
+    Ok(())
 }
 
 // Intentionally stable so deterministic replays can compare snapshots byte-for-byte.
@@ -0,0 +1,311 @@
+//! Human-friendly quantities in scenario files.
+//!
+//! A scenario's `units` block names the units its plain numbers are stored in and the
+//! constants those units were defined with. Where the block is present, numeric fields
+//! may instead be strings such as `"1 Msun"`, `"5.2 au"` or `"29.8 km/s"`, converted on
+//! load; `"gravityConstant": "G"` expands to `G` in the stored units.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::errors::{EngineError, Result};
+
+const SPEED_OF_LIGHT: f64 = 299_792_458.0;
+
+/// SI values of the astronomical constants unit strings refer to. Apps with different
+/// conventions (e.g. a 365-day year) override them per scenario.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct PhysicalConstants {
+    /// Metres.
+    pub au: f64,
+    /// Seconds; the default is the Julian year.
+    pub year: f64,
+    /// Kilograms.
+    pub solar_mass: f64,
+    pub earth_mass: f64,
+    pub jupiter_mass: f64,
+    /// `m^3 kg^-1 s^-2`.
+    pub gravitational_constant: f64,
+}
+
+impl Default for PhysicalConstants {
+    fn default() -> Self {
+        Self {
+            au: 1.495_978_707e11,
+            year: 365.25 * 86_400.0,
+            solar_mass: 1.988_47e30,
+            earth_mass: 5.972_2e24,
+            jupiter_mass: 1.898_13e27,
+            gravitational_constant: 6.674_30e-11,
+        }
+    }
+}
+
+/// Units of the plain numbers in a scenario, written as unit strings (`"au"`, `"Msun"`).
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct UnitSystem {
+    #[serde(default = "default_length_unit")]
+    pub length: String,
+    #[serde(default = "default_mass_unit")]
+    pub mass: String,
+    #[serde(default = "default_time_unit")]
+    pub time: String,
+    #[serde(default)]
+    pub constants: PhysicalConstants,
+}
+
+fn default_length_unit() -> String {
+    "m".to_string()
+}
+
+fn default_mass_unit() -> String {
+    "kg".to_string()
+}
+
+fn default_time_unit() -> String {
+    "s".to_string()
+}
+
+impl Default for UnitSystem {
+    fn default() -> Self {
+        Self {
+            length: default_length_unit(),
+            mass: default_mass_unit(),
+            time: default_time_unit(),
+            constants: PhysicalConstants::default(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Dimension {
+    Length,
+    Mass,
+    Time,
+    Velocity,
+}
+
+impl Dimension {
+    /// Exponents of length, mass and time.
+    fn exponents(self) -> [i32; 3] {
+        match self {
+            Self::Length => [1, 0, 0],
+            Self::Mass => [0, 1, 0],
+            Self::Time => [0, 0, 1],
+            Self::Velocity => [1, 0, -1],
+        }
+    }
+}
+
+/// A unit expression reduced to its SI factor and dimension exponents.
+#[derive(Clone, Copy, Debug)]
+struct Unit {
+    si: f64,
+    exponents: [i32; 3],
+}
+
+impl UnitSystem {
+    pub fn validate(&self) -> Result<()> {
+        let constants = &self.constants;
+        if [
+            constants.au,
+            constants.year,
+            constants.solar_mass,
+            constants.earth_mass,
+            constants.jupiter_mass,
+            constants.gravitational_constant,
+        ]
+        .iter()
+        .any(|value| !value.is_finite() || *value <= 0.0)
+        {
+            return Err(EngineError::InvalidConfig(
+                "unit constants must be finite and positive".to_string(),
+            ));
+        }
+        for (text, dimension) in [
+            (&self.length, Dimension::Length),
+            (&self.mass, Dimension::Mass),
+            (&self.time, Dimension::Time),
+        ] {
+            self.checked_unit(text, dimension)?;
+        }
+        Ok(())
+    }
+
+    /// `text` (`"<number> <unit>"`, or a bare number already in these units) converted
+    /// to these units. Fails unless the unit has dimension `expected`.
+    pub fn parse_quantity(&self, text: &str, expected: Dimension) -> Result<f64> {
+        let text = text.trim();
+        let (number, unit) = text
+            .split_once(char::is_whitespace)
+            .map_or((text, ""), |(number, unit)| (number, unit.trim()));
+        let value = number
+            .parse::<f64>()
+            .ok()
+            .filter(|value| value.is_finite())
+            .ok_or_else(|| {
+                EngineError::InvalidConfig(format!("'{text}' does not start with a number"))
+            })?;
+        if unit.is_empty() {
+            return Ok(value);
+        }
+        let unit = self.checked_unit(unit, expected)?;
+        Ok(value * unit.si / self.internal_si(expected.exponents())?)
+    }
+
+    /// `G` expressed in these units.
+    pub fn gravity_constant(&self) -> Result<f64> {
+        Ok(self.constants.gravitational_constant / self.internal_si([3, -1, -2])?)
+    }
+
+    /// Replaces quantity strings in a scenario's JSON with numbers in these units.
+    pub(crate) fn resolve_scenario(&self, scenario: &mut Value) -> Result<()> {
+        if let Some(config) = scenario.get_mut("engineConfig") {
+            if config.get("gravityConstant").and_then(Value::as_str) == Some("G") {
+                config["gravityConstant"] = Value::from(self.gravity_constant()?);
+            }
+            for (field, dimension) in [
+                ("dt", Dimension::Time),
+                ("softeningEpsilon", Dimension::Length),
+                ("timeQuantum", Dimension::Time),
+            ] {
+                self.resolve_field(config, field, dimension)?;
+            }
+        }
+        let bodies = scenario
+            .get_mut("bodies")
+            .and_then(Value::as_array_mut)
+            .into_iter()
+            .flatten();
+        for body in bodies {
+            for (field, dimension) in [
+                ("mass", Dimension::Mass),
+                ("radius", Dimension::Length),
+                ("softening", Dimension::Length),
+            ] {
+                self.resolve_field(body, field, dimension)?;
+            }
+            for (field, dimension) in [
+                ("position", Dimension::Length),
+                ("velocity", Dimension::Velocity),
+            ] {
+                if let Some(vector) = body.get_mut(field) {
+                    self.resolve_field(vector, "x", dimension)?;
+                    self.resolve_field(vector, "y", dimension)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn resolve_field(&self, object: &mut Value, field: &str, dimension: Dimension) -> Result<()> {
+        let Some(value) = object.get_mut(field) else {
+            return Ok(());
+        };
+        if let Some(text) = value.as_str() {
+            let quantity = self
+                .parse_quantity(text, dimension)
+                .map_err(|error| match error {
+                    EngineError::InvalidConfig(message) => {
+                        EngineError::InvalidConfig(format!("{field}: {message}"))
+                    }
+                    other => other,
+                })?;
+            *value = Value::from(quantity);
+        }
+        Ok(())
+    }
+
+    fn checked_unit(&self, text: &str, expected: Dimension) -> Result<Unit> {
+        let unit = self.parse_unit(text)?;
+        if unit.exponents != expected.exponents() {
+            return Err(EngineError::InvalidConfig(format!(
+                "unit '{text}' is not a {expected:?} unit"
+            )));
+        }
+        Ok(unit)
+    }
+
+    /// SI size of one internal unit of the given dimension.
+    fn internal_si(&self, exponents: [i32; 3]) -> Result<f64> {
+        let bases = [
+            self.parse_unit(&self.length)?.si,
+            self.parse_unit(&self.mass)?.si,
+            self.parse_unit(&self.time)?.si,
+        ];
+        Ok(bases
+            .iter()
+            .zip(exponents)
+            .map(|(base, exponent)| base.powi(exponent))
+            .product())
+    }
+
+    /// Products and quotients of named units with optional integer powers, e.g.
+    /// `km/s`, `m/s^2` or `Msun*au^-1`.
+    fn parse_unit(&self, text: &str) -> Result<Unit> {
+        let mut unit = Unit {
+            si: 1.0,
+            exponents: [0; 3],
+        };
+        let mut sign = 1;
+        let mut rest = text.trim();
+        loop {
+            let end = rest.find(['*', '/']).unwrap_or(rest.len());
+            let (name, power) = match rest[..end].trim().split_once('^') {
+                Some((name, power)) => (
+                    name.trim(),
+                    power.trim().parse::<i32>().map_err(|_| {
+                        EngineError::InvalidConfig(format!("bad power in unit '{text}'"))
+                    })?,
+                ),
+                None => (rest[..end].trim(), 1),
+            };
+            let factor = self.named_unit(name)?;
+            let power = sign * power;
+            unit.si *= factor.si.powi(power);
+            for (total, exponent) in unit.exponents.iter_mut().zip(factor.exponents) {
+                *total += exponent * power;
+            }
+            let Some(operator) = rest[end..].chars().next() else {
+                return Ok(unit);
+            };
+            sign = if operator == '/' { -1 } else { 1 };
+            rest = &rest[end + 1..];
+        }
+    }
+
+    fn named_unit(&self, name: &str) -> Result<Unit> {
+        let constants = &self.constants;
+        let (si, exponents) = match name {
+            "m" => (1.0, [1, 0, 0]),
+            "cm" => (1e-2, [1, 0, 0]),
+            "km" => (1e3, [1, 0, 0]),
+            "au" | "AU" => (constants.au, [1, 0, 0]),
+            "ly" => (SPEED_OF_LIGHT * constants.year, [1, 0, 0]),
+            "pc" => (constants.au * 648_000.0 / std::f64::consts::PI, [1, 0, 0]),
+            "Rsun" => (6.957e8, [1, 0, 0]),
+            "Rearth" => (6.378_1e6, [1, 0, 0]),
+            "kg" => (1.0, [0, 1, 0]),
+            "g" => (1e-3, [0, 1, 0]),
+            "Msun" => (constants.solar_mass, [0, 1, 0]),
+            "Mearth" => (constants.earth_mass, [0, 1, 0]),
+            "Mjup" => (constants.jupiter_mass, [0, 1, 0]),
+            "s" => (1.0, [0, 0, 1]),
+            "min" => (60.0, [0, 0, 1]),
+            "h" => (3_600.0, [0, 0, 1]),
+            "d" | "day" => (86_400.0, [0, 0, 1]),
+            "yr" | "year" => (constants.year, [0, 0, 1]),
+            other => {
+                return Err(EngineError::InvalidConfig(format!(
+                    "unknown unit '{other}'"
+                )));
+            }
+        };
+        Ok(Unit { si, exponents })
+    }
+}
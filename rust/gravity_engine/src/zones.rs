@@ -0,0 +1,115 @@
+use std::collections::BTreeSet;
+use std::f64::consts::PI;
+
+use serde::{Deserialize, Serialize};
+
+use crate::errors::{EngineError, Result};
+use crate::math::Vec2;
+use crate::types::Body;
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "shape", rename_all = "camelCase")]
+pub enum Region {
+    Circle { center: Vec2, radius: f64 },
+    Rect { min: Vec2, max: Vec2 },
+}
+
+impl Region {
+    pub fn contains(&self, point: Vec2) -> bool {
+        match self {
+            Region::Circle { center, radius } => {
+                (point - *center).norm_squared() <= radius * radius
+            }
+            Region::Rect { min, max } => {
+                point.x >= min.x && point.x <= max.x && point.y >= min.y && point.y <= max.y
+            }
+        }
+    }
+
+    pub fn area(&self) -> f64 {
+        match self {
+            Region::Circle { radius, .. } => PI * radius * radius,
+            Region::Rect { min, max } => (max.x - min.x) * (max.y - min.y),
+        }
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        let valid = match self {
+            Region::Circle { center, radius } => {
+                center.is_finite() && radius.is_finite() && *radius > 0.0
+            }
+            Region::Rect { min, max } => {
+                min.is_finite() && max.is_finite() && min.x < max.x && min.y < max.y
+            }
+        };
+        if !valid {
+            return Err(EngineError::InvalidConfig(
+                "region must be finite with positive extent".to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Zone {
+    pub name: String,
+    pub region: Region,
+}
+
+impl Zone {
+    pub fn new(name: impl Into<String>, region: Region) -> Self {
+        Self {
+            name: name.into(),
+            region,
+        }
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        if self.name.trim().is_empty() {
+            return Err(EngineError::InvalidConfig(
+                "zone name must not be empty".to_string(),
+            ));
+        }
+        self.region.validate()
+    }
+}
+
+/// Tracks which bodies are inside a zone. Bodies that disappear (merged or deleted)
+/// are dropped without an exit event.
+#[derive(Clone, Debug)]
+pub(crate) struct ZoneTracker {
+    pub zone: Zone,
+    inside: BTreeSet<String>,
+}
+
+impl ZoneTracker {
+    pub(crate) fn new(zone: Zone, bodies: &[Body]) -> Self {
+        let inside = bodies
+            .iter()
+            .filter(|body| body.alive && zone.region.contains(body.position))
+            .map(|body| body.id.clone())
+            .collect();
+        Self { zone, inside }
+    }
+
+    /// Returns the ids that entered and the ids that left since the last update.
+    pub(crate) fn update(&mut self, bodies: &[Body]) -> (Vec<String>, Vec<String>) {
+        let mut entered = Vec::new();
+        let mut exited = Vec::new();
+        let mut inside = BTreeSet::new();
+        for body in bodies.iter().filter(|body| body.alive) {
+            if self.zone.region.contains(body.position) {
+                if !self.inside.contains(&body.id) {
+                    entered.push(body.id.clone());
+                }
+                inside.insert(body.id.clone());
+            } else if self.inside.contains(&body.id) {
+                exited.push(body.id.clone());
+            }
+        }
+        self.inside = inside;
+        (entered, exited)
+    }
+}
@@ -0,0 +1,86 @@
+use gravity_engine::{
+    Body, CollisionMode, DtPolicy, EngineConfig, EngineError, GravitySolver, IntegratorKind, Vec2,
+    accuracy_report,
+};
+
+fn config(integrator: IntegratorKind) -> EngineConfig {
+    EngineConfig {
+        gravity_constant: 1.0,
+        softening_epsilon: 1e-9,
+        dt: 0.02,
+        dt_policy: DtPolicy::Fixed,
+        integrator,
+        collision_mode: CollisionMode::Ignore,
+        gravity_solver: GravitySolver::Pairwise,
+        ..EngineConfig::default()
+    }
+}
+
+fn eccentric_orbit() -> Vec<Body> {
+    vec![
+        Body::new("sun", 1.0, 0.01, Vec2::ZERO, Vec2::ZERO),
+        Body::new(
+            "comet",
+            1e-6,
+            0.01,
+            Vec2::new(1.0, 0.0),
+            Vec2::new(0.0, 1.2),
+        ),
+    ]
+}
+
+#[test]
+fn observed_order_matches_the_integrator() {
+    let verlet = accuracy_report(
+        &config(IntegratorKind::VelocityVerlet),
+        &eccentric_orbit(),
+        300,
+    )
+    .unwrap();
+    let order = verlet.order.unwrap();
+    assert!((order - 2.0).abs() < 0.2, "{verlet:?}");
+    assert_eq!(
+        verlet
+            .levels
+            .iter()
+            .map(|level| level.ticks)
+            .collect::<Vec<_>>(),
+        [300, 600, 1200]
+    );
+    assert!((verlet.duration - 6.0).abs() < 1e-12);
+    let errors = verlet
+        .levels
+        .iter()
+        .map(|level| level.rms_position_error)
+        .collect::<Vec<_>>();
+    assert!(
+        errors[0] > 3.0 * errors[1] && errors[1] > 3.0 * errors[2],
+        "{errors:?}"
+    );
+    assert_eq!(verlet.bodies.len(), 2);
+    let comet = &verlet.bodies[1];
+    assert_eq!(comet.id, "comet");
+    assert!(comet.position_error > 0.0);
+
+    let rk4 = accuracy_report(&config(IntegratorKind::Rk4), &eccentric_orbit(), 300).unwrap();
+    assert!((rk4.order.unwrap() - 4.0).abs() < 0.4, "{rk4:?}");
+    assert!(rk4.levels[0].rms_position_error < verlet.levels[0].rms_position_error);
+
+    let tolerance = verlet.levels[0].rms_position_error / 100.0;
+    let dt = verlet.dt_for_tolerance(tolerance).unwrap();
+    assert!((dt / (0.02 / 10.0) - 1.0).abs() < 0.2, "{dt}");
+    assert_eq!(verlet.dt_for_tolerance(0.0), None);
+}
+
+#[test]
+fn accuracy_reports_need_a_fixed_step() {
+    let adaptive = EngineConfig {
+        dt_policy: DtPolicy::Adaptive,
+        ..config(IntegratorKind::Rk4)
+    };
+    assert!(matches!(
+        accuracy_report(&adaptive, &eccentric_orbit(), 10),
+        Err(EngineError::InvalidConfig(_))
+    ));
+    assert!(accuracy_report(&config(IntegratorKind::Rk4), &eccentric_orbit(), 0).is_err());
+}
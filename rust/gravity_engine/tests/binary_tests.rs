@@ -0,0 +1,67 @@
+use gravity_engine::binary::{decode_scenario, decode_snapshot};
+use gravity_engine::{Body, BodyMetadata, EngineConfig, Oblateness, SimulationEngine, Vec2};
+
+fn engine() -> SimulationEngine {
+    let mut bodies = (0..500)
+        .map(|index| {
+            let angle = index as f64 * 0.1;
+            Body::new(
+                format!("b{index}"),
+                1e-3,
+                0.01,
+                Vec2::from_angle(angle) * (5.0 + index as f64 * 0.01),
+                Vec2::from_angle(angle).perp() * 0.3,
+            )
+        })
+        .collect::<Vec<_>>();
+    bodies[0].metadata = Some(BodyMetadata {
+        label: Some("Probe".to_string()),
+        kind: None,
+        color: Some("#ff0000".to_string()),
+        ..BodyMetadata::default()
+    });
+    bodies[1].oblateness = Some(Oblateness {
+        j2: 1e-3,
+        equatorial_radius: 0.01,
+        obliquity: 0.2,
+        node_angle: 0.0,
+    });
+    bodies[2].collidable = false;
+    bodies[3].origin_group = Some(9);
+    SimulationEngine::with_bodies(EngineConfig::default(), bodies).unwrap()
+}
+
+#[test]
+fn binary_snapshot_round_trips_and_is_smaller_than_json() {
+    let mut source = engine();
+    source.step(3).unwrap();
+    let bytes = source.snapshot_binary();
+    assert_eq!(decode_snapshot(&bytes).unwrap(), source.snapshot());
+    assert!(bytes.len() * 2 < serde_json::to_vec(&source.snapshot()).unwrap().len());
+
+    let mut target = engine();
+    target.restore_snapshot_binary(&bytes).unwrap();
+    assert_eq!(target.snapshot(), source.snapshot());
+    source.step(5).unwrap();
+    target.step(5).unwrap();
+    assert_eq!(target.get_state().bodies, source.get_state().bodies);
+}
+
+#[test]
+fn binary_scenario_round_trips_and_rejects_foreign_buffers() {
+    let source = engine();
+    let bytes = source.save_scenario_binary();
+    assert_eq!(decode_scenario(&bytes).unwrap(), source.save_scenario());
+
+    let mut target = SimulationEngine::with_bodies(EngineConfig::default(), Vec::new()).unwrap();
+    target.load_scenario_binary(&bytes).unwrap();
+    assert_eq!(target.get_state().bodies, source.get_state().bodies);
+
+    assert!(target.restore_snapshot_binary(&bytes).is_err());
+    assert!(
+        target
+            .load_scenario_binary(&bytes[..bytes.len() / 2])
+            .is_err()
+    );
+    assert!(target.load_scenario_binary(b"").is_err());
+}
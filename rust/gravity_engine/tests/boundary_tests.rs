@@ -0,0 +1,113 @@
+use gravity_engine::{
+    Body, BoundaryMode, CollisionMode, EngineConfig, GravitySolver, IntegratorKind,
+    SimulationEngine, SimulationEvent, Vec2, WorldBoundary,
+};
+
+fn world(mode: BoundaryMode) -> EngineConfig {
+    EngineConfig {
+        gravity_constant: 1.0,
+        softening_epsilon: 1e-6,
+        dt: 0.01,
+        integrator: IntegratorKind::VelocityVerlet,
+        collision_mode: CollisionMode::Ignore,
+        gravity_solver: GravitySolver::Pairwise,
+        boundary: Some(WorldBoundary {
+            min: Vec2::new(0.0, 0.0),
+            max: Vec2::new(10.0, 10.0),
+            mode,
+        }),
+        ..EngineConfig::default()
+    }
+}
+
+fn mover(id: &str, position: Vec2, velocity: Vec2) -> Body {
+    Body::new(id, 1e-12, 0.01, position, velocity)
+}
+
+#[test]
+fn reflective_walls_bounce_bodies_back_inside() {
+    let body = mover("ball", Vec2::new(9.95, 0.02), Vec2::new(10.0, -5.0));
+    let mut engine =
+        SimulationEngine::with_bodies(world(BoundaryMode::Reflect), vec![body]).unwrap();
+    engine.step(1).unwrap();
+    let ball = &engine.bodies()[0];
+    assert!((ball.position.x - 9.95).abs() < 1e-9, "{:?}", ball.position);
+    assert!((ball.position.y - 0.03).abs() < 1e-9, "{:?}", ball.position);
+    assert_eq!(ball.velocity, Vec2::new(-10.0, 5.0));
+}
+
+#[test]
+fn periodic_worlds_wrap_positions_and_attract_through_the_seam() {
+    let body = mover("runner", Vec2::new(9.99, 5.0), Vec2::new(2.0, 0.0));
+    let mut engine =
+        SimulationEngine::with_bodies(world(BoundaryMode::Periodic), vec![body]).unwrap();
+    engine.step(1).unwrap();
+    assert!((engine.bodies()[0].position.x - 0.01).abs() < 1e-9);
+
+    let pair = vec![
+        Body::new("left", 1.0, 0.01, Vec2::new(0.5, 5.0), Vec2::ZERO),
+        Body::new("right", 1.0, 0.01, Vec2::new(9.5, 5.0), Vec2::ZERO),
+    ];
+    for gravity_solver in [GravitySolver::Pairwise, GravitySolver::BarnesHut] {
+        let config = EngineConfig {
+            gravity_solver,
+            ..world(BoundaryMode::Periodic)
+        };
+        let mut engine = SimulationEngine::with_bodies(config, pair.clone()).unwrap();
+        let summary = engine.step(1).unwrap();
+        assert_eq!(summary.barnes_hut_ticks, 0);
+        // The nearest image of "right" is 1 away on the left.
+        let left = &engine.bodies()[0];
+        assert!(left.velocity.x < 0.0);
+        assert!((left.velocity.x + 0.01).abs() < 1e-4, "{:?}", left.velocity);
+    }
+
+    let mut open = SimulationEngine::with_bodies(
+        EngineConfig {
+            boundary: None,
+            ..world(BoundaryMode::Periodic)
+        },
+        pair,
+    )
+    .unwrap();
+    open.step(1).unwrap();
+    assert!(open.bodies()[0].velocity.x > 0.0);
+}
+
+#[test]
+fn absorbing_walls_kill_bodies_and_emit_events() {
+    let bodies = vec![
+        mover("escaper", Vec2::new(0.01, 5.0), Vec2::new(-2.0, 0.0)),
+        mover("stayer", Vec2::new(5.0, 5.0), Vec2::new(1.0, 0.0)),
+    ];
+    let mut engine = SimulationEngine::with_bodies(world(BoundaryMode::Absorb), bodies).unwrap();
+    let summary = engine.step(2).unwrap();
+    assert!(!engine.bodies()[0].alive);
+    assert!(engine.bodies()[1].alive);
+    let absorbed = summary
+        .events
+        .iter()
+        .filter_map(|event| match event {
+            SimulationEvent::BodyAbsorbed(event) => Some((event.tick, event.body_id.as_str())),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(absorbed, [(1, "escaper")]);
+}
+
+#[test]
+fn degenerate_worlds_are_rejected() {
+    let config = EngineConfig {
+        boundary: Some(WorldBoundary {
+            min: Vec2::new(0.0, 0.0),
+            max: Vec2::new(10.0, 0.0),
+            mode: BoundaryMode::Reflect,
+        }),
+        ..world(BoundaryMode::Reflect)
+    };
+    assert!(config.validate().is_err());
+    assert_ne!(
+        world(BoundaryMode::Reflect).stable_hash(),
+        world(BoundaryMode::Periodic).stable_hash()
+    );
+}
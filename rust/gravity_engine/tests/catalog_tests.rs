@@ -0,0 +1,131 @@
+use gravity_engine::{
+    Body, CatalogQuery, EngineConfig, Scenario, ScenarioCatalog, ScenarioMetadata, Vec2,
+};
+
+fn scenario(
+    name: &str,
+    description: &str,
+    author: &str,
+    created_at: &str,
+    tags: &[&str],
+) -> Scenario {
+    Scenario {
+        schema_version: "1.0".to_string(),
+        metadata: ScenarioMetadata {
+            name: name.to_string(),
+            description: Some(description.to_string()),
+            author: Some(author.to_string()),
+            created_at: created_at.to_string(),
+            tags: tags.iter().map(|tag| tag.to_string()).collect(),
+        },
+        engine_config: EngineConfig::default(),
+        bodies: vec![Body::new("sun", 1.0, 1.0, Vec2::ZERO, Vec2::ZERO)],
+        epochs: Vec::new(),
+        units: None,
+        checksum: None,
+    }
+}
+
+fn catalog() -> ScenarioCatalog {
+    let mut catalog = ScenarioCatalog::default();
+    for entry in [
+        scenario(
+            "Inner Planets",
+            "Mercury to Mars",
+            "ada",
+            "2024-01-10T00:00:00Z",
+            &["solar", "classic"],
+        ),
+        scenario(
+            "Figure Eight",
+            "Stable three body choreography",
+            "ben",
+            "2024-03-05T00:00:00Z",
+            &["threeBody"],
+        ),
+        scenario(
+            "Binary Star",
+            "Two suns and a circumbinary planet",
+            "ada",
+            "2024-06-01T00:00:00Z",
+            &["binary", "classic"],
+        ),
+        scenario(
+            "Galaxy Merger",
+            "Two disks colliding",
+            "cy",
+            "2025-02-14T00:00:00Z",
+            &["galaxy"],
+        ),
+    ] {
+        catalog.insert(entry).unwrap();
+    }
+    catalog
+}
+
+#[test]
+fn search_combines_text_tag_author_and_date_filters() {
+    let catalog = catalog();
+    let names = |query: CatalogQuery| {
+        catalog
+            .search(&query)
+            .entries
+            .into_iter()
+            .map(|entry| entry.metadata.name)
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(
+        names(CatalogQuery {
+            text: Some("two SUNS".to_string()),
+            ..CatalogQuery::default()
+        }),
+        vec!["Binary Star"]
+    );
+    assert_eq!(
+        names(CatalogQuery {
+            tags: vec!["Classic".to_string()],
+            author: Some("ada".to_string()),
+            ..CatalogQuery::default()
+        }),
+        vec!["Inner Planets", "Binary Star"]
+    );
+    assert_eq!(
+        names(CatalogQuery {
+            created_after: Some("2024-02-01".to_string()),
+            created_before: Some("2024-12-31".to_string()),
+            ..CatalogQuery::default()
+        }),
+        vec!["Figure Eight", "Binary Star"]
+    );
+    assert!(
+        names(CatalogQuery {
+            text: Some("planet galaxy".to_string()),
+            ..CatalogQuery::default()
+        })
+        .is_empty()
+    );
+}
+
+#[test]
+fn search_pages_results_and_entries_can_be_removed() {
+    let mut catalog = catalog();
+    let page = catalog.search(&CatalogQuery {
+        offset: 1,
+        limit: Some(2),
+        ..CatalogQuery::default()
+    });
+    assert_eq!(page.total, 4);
+    assert_eq!(page.entries.len(), 2);
+    assert_eq!(page.entries[0].metadata.name, "Figure Eight");
+    assert_eq!(page.entries[0].body_count, 1);
+
+    let id = page.entries[0].id;
+    assert!(catalog.get(id).is_some());
+    assert!(catalog.remove(id).is_some());
+    assert_eq!(catalog.search(&CatalogQuery::default()).total, 3);
+
+    let mut future = scenario("Next", "", "ada", "2026-01-01T00:00:00Z", &[]);
+    future.schema_version = "2.0".to_string();
+    assert!(catalog.insert(future).is_err());
+}
@@ -0,0 +1,65 @@
+use gravity_engine::{Body, EngineConfig, EngineError, Scenario, SimulationEngine, Snapshot, Vec2};
+use serde_json::Value;
+
+fn engine() -> SimulationEngine {
+    let bodies = (0..40)
+        .map(|index| {
+            let angle = index as f64 * 0.37;
+            Body::new(
+                format!("b{index}"),
+                1e-3 * (1.0 + index as f64 / 7.0),
+                0.01,
+                Vec2::from_angle(angle) * (3.0 + index as f64 / 3.0),
+                Vec2::from_angle(angle).perp() * 0.45,
+            )
+        })
+        .collect::<Vec<_>>();
+    SimulationEngine::with_bodies(EngineConfig::default(), bodies).unwrap()
+}
+
+#[test]
+fn json_scenario_round_trip_verifies_and_hand_edits_fail_loudly() {
+    let mut source = engine();
+    source.step(7).unwrap();
+    let json = serde_json::to_string(&source.save_scenario()).unwrap();
+
+    let mut target = SimulationEngine::with_bodies(EngineConfig::default(), Vec::new()).unwrap();
+    let scenario: Scenario = serde_json::from_str(&json).unwrap();
+    assert!(scenario.checksum.is_some());
+    target.load_scenario(scenario).unwrap();
+
+    let mut edited: Value = serde_json::from_str(&json).unwrap();
+    edited["bodies"][3]["mass"] = Value::from(5.0);
+    let tampered: Scenario = serde_json::from_value(edited.clone()).unwrap();
+    assert!(matches!(
+        target.load_scenario(tampered),
+        Err(EngineError::ChecksumMismatch(_))
+    ));
+
+    // Files that never carried a checksum still load.
+    edited.as_object_mut().unwrap().remove("checksum");
+    let legacy: Scenario = serde_json::from_value(edited).unwrap();
+    target.load_scenario(legacy).unwrap();
+}
+
+#[test]
+fn tampered_snapshot_is_rejected_and_state_is_untouched() {
+    let mut source = engine();
+    source.step(5).unwrap();
+    let mut target = engine();
+    let before = target.get_state();
+
+    let mut snapshot = source.snapshot();
+    snapshot.bodies.truncate(snapshot.bodies.len() - 1);
+    assert!(matches!(
+        target.restore_snapshot(snapshot),
+        Err(EngineError::ChecksumMismatch(_))
+    ));
+    assert_eq!(target.get_state(), before);
+
+    let json = serde_json::to_string(&source.snapshot()).unwrap();
+    let snapshot: Snapshot = serde_json::from_str(&json).unwrap();
+    assert_eq!(snapshot.checksum, Some(snapshot.compute_checksum()));
+    target.restore_snapshot(snapshot).unwrap();
+    assert_eq!(target.get_state().bodies, source.get_state().bodies);
+}
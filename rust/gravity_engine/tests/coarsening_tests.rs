@@ -0,0 +1,116 @@
+use gravity_engine::{
+    Body, CoarseGraining, CollisionMode, EngineConfig, GravitySolver, SimulationEngine,
+    SimulationEvent, Vec2,
+};
+
+fn base_config() -> EngineConfig {
+    EngineConfig {
+        gravity_constant: 1.0,
+        softening_epsilon: 1e-3,
+        dt: 0.001,
+        collision_mode: CollisionMode::Ignore,
+        gravity_solver: GravitySolver::Pairwise,
+        ..EngineConfig::default()
+    }
+}
+
+fn disc_with_far_debris() -> Vec<Body> {
+    let mut bodies = vec![
+        Body::new("star", 100.0, 0.5, Vec2::ZERO, Vec2::ZERO),
+        Body::new("near", 0.01, 0.01, Vec2::new(2.0, 0.0), Vec2::new(0.0, 7.0)),
+        Body::new("giant", 5.0, 0.2, Vec2::new(52.0, 1.0), Vec2::ZERO),
+    ];
+    for index in 0..12 {
+        let offset = Vec2::new((index % 4) as f64, (index / 4) as f64) * 1.5;
+        bodies.push(Body::new(
+            format!("dust{index}"),
+            0.01 + 0.001 * index as f64,
+            0.01,
+            Vec2::new(51.0, 0.5) + offset,
+            Vec2::new(0.1 * index as f64, -0.2),
+        ));
+    }
+    bodies
+}
+
+#[test]
+fn far_light_bodies_merge_per_cell_preserving_mass_momentum_and_com() {
+    let coarse = CoarseGraining {
+        cell_size: 10.0,
+        max_body_mass: 0.1,
+        focus: Vec2::ZERO,
+        focus_radius: 10.0,
+        interval_ticks: 1,
+        min_members: 2,
+    };
+    let config = EngineConfig {
+        coarse_graining: Some(coarse),
+        ..base_config()
+    };
+    let mut coarse_engine = SimulationEngine::with_bodies(config, disc_with_far_debris()).unwrap();
+    let mut fine_engine =
+        SimulationEngine::with_bodies(base_config(), disc_with_far_debris()).unwrap();
+
+    let summary = coarse_engine.step(1).unwrap();
+    fine_engine.step(1).unwrap();
+    assert_eq!(summary.aggregated_bodies, 11);
+    assert_eq!(coarse_engine.bodies().len(), 4);
+    let ids = coarse_engine
+        .bodies()
+        .iter()
+        .map(|body| body.id.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(ids, ["star", "near", "giant", "dust11"]);
+
+    let coarse = coarse_engine.diagnostics();
+    let fine = fine_engine.diagnostics();
+    assert!((coarse.total_mass - fine.total_mass).abs() < 1e-12);
+    assert!((coarse.linear_momentum - fine.linear_momentum).norm() < 1e-12);
+    assert!((coarse.center_of_mass - fine.center_of_mass).norm() < 1e-12);
+
+    let aggregated = summary
+        .events
+        .iter()
+        .filter_map(|event| match event {
+            SimulationEvent::Aggregated(event) => Some(event),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(aggregated.len(), 1);
+    let event = aggregated[0];
+    assert_eq!(event.body_id, "dust11");
+    assert_eq!(event.absorbed_ids.len(), 11);
+    assert_eq!(event.member_masses.len(), 12);
+    assert!((event.member_masses.iter().sum::<f64>() - event.mass).abs() < 1e-12);
+    assert_eq!(coarse_engine.bodies()[3].mass, event.mass);
+}
+
+#[test]
+fn coarse_graining_waits_for_its_interval_and_validates() {
+    let config = EngineConfig {
+        coarse_graining: Some(CoarseGraining {
+            cell_size: 10.0,
+            max_body_mass: 0.1,
+            focus: Vec2::ZERO,
+            focus_radius: 10.0,
+            interval_ticks: 4,
+            min_members: 20,
+        }),
+        ..base_config()
+    };
+    let mut engine = SimulationEngine::with_bodies(config.clone(), disc_with_far_debris()).unwrap();
+    assert_eq!(engine.step(8).unwrap().aggregated_bodies, 0);
+
+    let mut config = config;
+    if let Some(coarse) = config.coarse_graining.as_mut() {
+        coarse.min_members = 2;
+    }
+    engine.set_config(config.clone()).unwrap();
+    assert_eq!(engine.step(3).unwrap().aggregated_bodies, 0);
+    assert_eq!(engine.step(1).unwrap().aggregated_bodies, 11);
+
+    if let Some(coarse) = config.coarse_graining.as_mut() {
+        coarse.cell_size = 0.0;
+    }
+    assert!(config.validate().is_err());
+}
@@ -0,0 +1,107 @@
+use gravity_engine::{
+    Body, CollisionMode, DtPolicy, EngineConfig, EngineError, GravitySolver, IntegratorKind,
+    MultiEngineRunner, Vec2,
+};
+
+fn config(integrator: IntegratorKind) -> EngineConfig {
+    EngineConfig {
+        gravity_constant: 1.0,
+        softening_epsilon: 1e-6,
+        dt: 0.05,
+        dt_policy: DtPolicy::Fixed,
+        integrator,
+        collision_mode: CollisionMode::Ignore,
+        gravity_solver: GravitySolver::Pairwise,
+        ..EngineConfig::default()
+    }
+}
+
+fn orbit() -> Vec<Body> {
+    vec![
+        Body::new("sun", 1.0, 0.01, Vec2::ZERO, Vec2::ZERO),
+        Body::new(
+            "planet",
+            1e-6,
+            0.01,
+            Vec2::new(1.0, 0.0),
+            Vec2::new(0.0, 1.0),
+        ),
+    ]
+}
+
+#[test]
+fn integrators_are_compared_in_lockstep() {
+    let variants = vec![
+        ("rk4".to_string(), config(IntegratorKind::Rk4)),
+        ("verlet".to_string(), config(IntegratorKind::VelocityVerlet)),
+        (
+            "euler".to_string(),
+            config(IntegratorKind::SemiImplicitEuler),
+        ),
+        ("rk4-again".to_string(), config(IntegratorKind::Rk4)),
+    ];
+    let mut runner = MultiEngineRunner::new(orbit(), variants).unwrap();
+    assert_eq!(runner.labels(), ["rk4", "verlet", "euler", "rk4-again"]);
+    let start = runner.report();
+    assert!(
+        start
+            .variants
+            .iter()
+            .all(|variant| variant.energy_drift == 0.0)
+    );
+    assert!(
+        start
+            .divergence
+            .iter()
+            .all(|divergence| divergence.max_position_delta == 0.0)
+    );
+
+    let report = runner.step(200).unwrap();
+    assert!(report.variants.iter().all(|variant| variant.tick == 200));
+    let drift = |label: &str| {
+        report
+            .variants
+            .iter()
+            .find(|variant| variant.label == label)
+            .unwrap()
+            .energy_drift
+            .abs()
+    };
+    assert!(drift("rk4") < drift("verlet"));
+    assert!(drift("verlet") < drift("euler"), "{report:?}");
+
+    let labels = report
+        .divergence
+        .iter()
+        .map(|divergence| divergence.label.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(labels, ["verlet", "euler", "rk4-again"]);
+    assert_eq!(report.divergence[2].max_position_delta, 0.0);
+    assert_eq!(report.divergence[2].compared_bodies, 2);
+    assert!(report.divergence[1].max_position_delta > report.divergence[0].max_position_delta);
+    let euler = &report.divergence[1];
+    assert!(euler.rms_position_delta <= euler.max_position_delta);
+    assert_eq!(runner.engine("euler").unwrap().tick(), 200);
+    assert!(runner.engine("leapfrog").is_none());
+}
+
+#[test]
+fn variants_need_unique_labels_and_valid_configs() {
+    let duplicate = vec![
+        ("a".to_string(), config(IntegratorKind::Rk4)),
+        ("a".to_string(), config(IntegratorKind::Rk4)),
+    ];
+    assert!(matches!(
+        MultiEngineRunner::new(orbit(), duplicate),
+        Err(EngineError::InvalidConfig(_))
+    ));
+    assert!(MultiEngineRunner::new(orbit(), Vec::new()).is_err());
+    let bad = vec![(
+        "bad".to_string(),
+        EngineConfig {
+            dt: -1.0,
+            ..config(IntegratorKind::Rk4)
+        },
+    )];
+    assert!(MultiEngineRunner::new(orbit(), bad).is_err());
+}
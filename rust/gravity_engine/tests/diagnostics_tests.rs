@@ -0,0 +1,659 @@
+use gravity_engine::analysis::sample_kepler_orbit;
+use gravity_engine::{
+    AngularMomentumGuard, BinaryDetection, Body, BodyEdit, BodyQuery, BodyUpdate, CollisionMode,
+    CollisionRateQuery, EnergyWatchdog, EngineConfig, EngineError, GravitySolver, GridSpec,
+    IntegratorKind, MassHistogramOptions, Region, SimulationEngine, SimulationEvent, Vec2,
+};
+
+fn base_config() -> EngineConfig {
+    EngineConfig {
+        gravity_constant: 1.0,
+        softening_epsilon: 1e-6,
+        dt: 0.001,
+        integrator: IntegratorKind::VelocityVerlet,
+        collision_mode: CollisionMode::Ignore,
+        gravity_solver: GravitySolver::Pairwise,
+        ..EngineConfig::default()
+    }
+}
+
+fn tagged(body: Body, group: u32) -> Body {
+    Body {
+        origin_group: Some(group),
+        ..body
+    }
+}
+
+#[test]
+fn group_diagnostics_report_bound_core_and_unbound_spray() {
+    let bodies = vec![
+        tagged(
+            Body::new(
+                "core_a",
+                50.0,
+                0.1,
+                Vec2::new(-1.0, 0.0),
+                Vec2::new(0.0, -1.0),
+            ),
+            1,
+        ),
+        tagged(
+            Body::new(
+                "core_b",
+                50.0,
+                0.1,
+                Vec2::new(1.0, 0.0),
+                Vec2::new(0.0, 1.0),
+            ),
+            1,
+        ),
+        tagged(
+            Body::new(
+                "spray_a",
+                1.0,
+                0.1,
+                Vec2::new(40.0, 0.0),
+                Vec2::new(30.0, 0.0),
+            ),
+            2,
+        ),
+        tagged(
+            Body::new(
+                "spray_b",
+                1.0,
+                0.1,
+                Vec2::new(60.0, 0.0),
+                Vec2::new(-30.0, 0.0),
+            ),
+            2,
+        ),
+        Body::new("untagged", 1.0, 0.1, Vec2::new(0.0, 80.0), Vec2::ZERO),
+    ];
+    let engine = SimulationEngine::with_bodies(base_config(), bodies).unwrap();
+
+    let groups = engine.group_diagnostics();
+    assert_eq!(groups.len(), 2);
+
+    let core = &groups[0];
+    assert_eq!(core.origin_group, 1);
+    assert_eq!(core.body_count, 2);
+    assert_eq!(core.bound_fraction, 1.0);
+    assert!(core.center_of_mass.norm() < 1e-12);
+    assert!((core.velocity_dispersion - 1.0).abs() < 1e-12);
+
+    let spray = &groups[1];
+    assert_eq!(spray.origin_group, 2);
+    assert_eq!(spray.bound_fraction, 0.0);
+    assert!((spray.center_of_mass - Vec2::new(50.0, 0.0)).norm() < 1e-12);
+}
+
+#[test]
+fn merges_keep_the_dominant_origin_group() {
+    let config = EngineConfig {
+        collision_mode: CollisionMode::InelasticMerge,
+        ..base_config()
+    };
+    let bodies = vec![
+        tagged(Body::new("small", 1.0, 1.0, Vec2::ZERO, Vec2::ZERO), 7),
+        tagged(
+            Body::new("big", 9.0, 1.0, Vec2::new(0.5, 0.0), Vec2::ZERO),
+            3,
+        ),
+    ];
+    let mut engine = SimulationEngine::with_bodies(config, bodies).unwrap();
+    engine.step(1).unwrap();
+
+    assert_eq!(engine.bodies().len(), 1);
+    assert_eq!(engine.bodies()[0].origin_group, Some(3));
+    assert_eq!(engine.group_diagnostics()[0].origin_group, 3);
+}
+
+#[test]
+fn binary_catalog_tracks_formation_and_disruption() {
+    let config = EngineConfig {
+        binary_detection: Some(BinaryDetection { interval_ticks: 10 }),
+        ..base_config()
+    };
+    let bodies = vec![
+        Body::new("a", 10.0, 0.01, Vec2::new(-0.5, 0.0), Vec2::new(0.0, -1.0)),
+        Body::new("b", 5.0, 0.01, Vec2::new(0.5, 0.0), Vec2::new(0.0, 2.0)),
+        Body::new("c", 1.0, 0.01, Vec2::new(30.0, 0.0), Vec2::new(0.0, 5.0)),
+        Body::new("d", 1.0, 0.01, Vec2::new(32.0, 0.0), Vec2::new(0.0, -5.0)),
+    ];
+    let mut engine = SimulationEngine::with_bodies(config, bodies).unwrap();
+
+    let summary = engine.step(10).unwrap();
+    let binaries = engine.binaries();
+    assert_eq!(binaries.len(), 1);
+    assert_eq!(binaries[0].primary_id, "a");
+    assert_eq!(binaries[0].secondary_id, "b");
+    assert_eq!(binaries[0].formed_tick, 10);
+    assert!(binaries[0].binding_energy < 0.0);
+    assert!(binaries[0].elements.eccentricity < 1.0);
+    assert!(
+        summary.events.iter().any(
+            |event| matches!(event, SimulationEvent::BinaryFormed(e) if e.secondary_id == "b")
+        )
+    );
+
+    engine.step(10).unwrap();
+    assert_eq!(engine.binaries()[0].formed_tick, 10);
+
+    engine
+        .apply_edit(BodyEdit::Update(BodyUpdate {
+            id: "b".to_string(),
+            velocity: Some(Vec2::new(0.0, 50.0)),
+            ..BodyUpdate::default()
+        }))
+        .unwrap();
+    let summary = engine.step(10).unwrap();
+    assert!(engine.binaries().is_empty());
+    assert!(
+        summary
+            .events
+            .iter()
+            .any(|event| matches!(event, SimulationEvent::BinaryDisrupted(_)))
+    );
+}
+
+#[test]
+fn diagnostics_report_conserved_quantities() {
+    let config = EngineConfig {
+        include_diagnostics: true,
+        ..base_config()
+    };
+    let bodies = vec![
+        Body::new("a", 4.0, 0.05, Vec2::new(-2.0, 0.0), Vec2::new(0.0, 0.3)),
+        Body::new("b", 2.0, 0.05, Vec2::new(2.0, 0.0), Vec2::new(0.0, -0.6)),
+    ];
+    let mut engine = SimulationEngine::with_bodies(config, bodies).unwrap();
+
+    let before = engine.diagnostics();
+    assert!((before.kinetic_energy - (0.5 * 4.0 * 0.09 + 0.5 * 2.0 * 0.36)).abs() < 1e-12);
+    assert!((before.potential_energy + 8.0 / 4.0).abs() < 1e-9);
+    assert!(before.linear_momentum.norm() < 1e-12);
+    assert!((before.angular_momentum - (-2.0 * 1.2 + 2.0 * -1.2)).abs() < 1e-12);
+    assert_eq!(before.total_mass, 6.0);
+
+    let summary = engine.step(2000).unwrap();
+    let after = summary.diagnostics.expect("diagnostics were enabled");
+    assert_eq!(after, engine.diagnostics());
+    assert!((after.total_energy - before.total_energy).abs() < 1e-6);
+    assert!((after.angular_momentum - before.angular_momentum).abs() < 1e-9);
+    assert!((after.center_of_mass - before.center_of_mass).norm() < 1e-9);
+
+    let mut quiet = SimulationEngine::with_bodies(base_config(), vec![]).unwrap();
+    assert!(quiet.step(1).unwrap().diagnostics.is_none());
+}
+
+#[test]
+fn mass_distribution_bins_and_summarises_alive_bodies() {
+    let mut bodies = [1.0, 2.0, 4.0, 8.0, 100.0]
+        .iter()
+        .enumerate()
+        .map(|(i, &mass)| {
+            Body::new(
+                format!("b{i}"),
+                mass,
+                0.1,
+                Vec2::new(10.0 * i as f64, 0.0),
+                Vec2::ZERO,
+            )
+        })
+        .collect::<Vec<_>>();
+    bodies[4].alive = false;
+    let engine = SimulationEngine::with_bodies(base_config(), bodies).unwrap();
+
+    let linear = engine
+        .mass_distribution(&MassHistogramOptions {
+            bins: 7,
+            logarithmic: false,
+        })
+        .unwrap();
+    assert_eq!(linear.body_count, 4);
+    assert_eq!(linear.total_mass, 15.0);
+    assert_eq!((linear.min_mass, linear.max_mass), (1.0, 8.0));
+    assert_eq!(linear.median_mass, 3.0);
+    assert!((linear.largest_fraction - 8.0 / 15.0).abs() < 1e-12);
+    let counts = linear.bins.iter().map(|bin| bin.count).collect::<Vec<_>>();
+    assert_eq!(counts, vec![1, 1, 0, 1, 0, 0, 1]);
+
+    let log = engine
+        .mass_distribution(&MassHistogramOptions {
+            bins: 3,
+            logarithmic: true,
+        })
+        .unwrap();
+    assert_eq!(log.bins.iter().map(|bin| bin.count).sum::<usize>(), 4);
+    assert!(log.bins[2].count >= 1);
+    assert!((log.bins[1].lower - 2.0).abs() < 1e-9);
+    assert_eq!(log.bins.iter().map(|bin| bin.mass).sum::<f64>(), 15.0);
+
+    assert!(
+        engine
+            .mass_distribution(&MassHistogramOptions {
+                bins: 0,
+                logarithmic: false
+            })
+            .is_err()
+    );
+}
+
+#[test]
+fn kinematics_grid_reports_per_cell_dispersion() {
+    let bodies = vec![
+        Body::new("a", 1.0, 0.01, Vec2::new(0.5, 0.5), Vec2::new(1.0, 0.0)),
+        Body::new("b", 1.0, 0.01, Vec2::new(0.6, 0.4), Vec2::new(-1.0, 0.0)),
+        Body::new("c", 2.0, 0.01, Vec2::new(1.5, 0.5), Vec2::new(0.0, 3.0)),
+        Body::new("outside", 9.0, 0.01, Vec2::new(-5.0, 0.0), Vec2::ZERO),
+    ];
+    let engine = SimulationEngine::with_bodies(base_config(), bodies).unwrap();
+    let spec = GridSpec::centered(Vec2::new(1.0, 1.0), 2.0, 2, 2);
+    assert_eq!(spec.origin, Vec2::ZERO);
+
+    let cells = engine.kinematics_grid(&spec).unwrap();
+    assert_eq!(cells.len(), 4);
+    assert_eq!(cells[0].body_count, 2);
+    assert!(cells[0].mean_velocity.norm() < 1e-12);
+    assert!((cells[0].velocity_dispersion - 1.0).abs() < 1e-12);
+    assert_eq!(cells[1].body_count, 1);
+    assert_eq!(cells[1].mean_velocity, Vec2::new(0.0, 3.0));
+    assert_eq!(cells[1].velocity_dispersion, 0.0);
+    assert_eq!(cells[2].body_count + cells[3].body_count, 0);
+
+    let density = engine.density_grid(&spec).unwrap();
+    assert_eq!(density, vec![2.0, 2.0, 0.0, 0.0]);
+    assert_eq!(spec.cell_center(3), Vec2::new(1.5, 1.5));
+}
+
+#[test]
+fn field_samples_track_the_direct_sum_and_fill_a_flat_buffer() {
+    use std::ffi::CString;
+
+    use gravity_engine::ffi::{gs_dispose, gs_initialize, gs_sample_field, gs_string_free};
+
+    let bodies = (0..150)
+        .map(|index| {
+            let angle = index as f64 * 2.399;
+            let position = Vec2::from_angle(angle) * (0.5 + 0.02 * index as f64);
+            Body::new(
+                format!("b{index}"),
+                1.0 + (index % 3) as f64,
+                0.01,
+                position,
+                Vec2::ZERO,
+            )
+        })
+        .collect::<Vec<_>>();
+    let config = EngineConfig {
+        softening_epsilon: 0.05,
+        barnes_hut_theta: 0.4,
+        ..base_config()
+    };
+    let engine = SimulationEngine::with_bodies(config.clone(), bodies.clone()).unwrap();
+    let grid = GridSpec::centered(Vec2::new(0.3, -0.2), 8.0, 6, 4);
+    let field = engine.sample_field(grid.clone()).unwrap();
+    assert_eq!((field.potential.len(), field.acceleration.len()), (24, 24));
+    for index in 0..grid.cell_count() {
+        let point = grid.cell_center(index);
+        let (mut potential, mut acceleration) = (0.0, Vec2::ZERO);
+        for body in &bodies {
+            let delta = body.position - point;
+            let dist_sq = delta.norm_squared() + 0.05 * 0.05;
+            potential -= body.mass / dist_sq.sqrt();
+            acceleration += delta * (body.mass / (dist_sq * dist_sq.sqrt()));
+        }
+        assert!((field.potential[index] - potential).abs() < 0.01 * potential.abs());
+        assert!((field.acceleration[index] - acceleration).norm() < 0.02 * acceleration.norm());
+    }
+    assert!(
+        engine
+            .sample_field(GridSpec {
+                columns: 0,
+                ..grid.clone()
+            })
+            .is_err()
+    );
+
+    let config = CString::new(serde_json::to_string(&config).unwrap()).unwrap();
+    let bodies = CString::new(serde_json::to_string(&bodies).unwrap()).unwrap();
+    let response = gs_initialize(config.as_ptr(), bodies.as_ptr());
+    let text = unsafe { std::ffi::CStr::from_ptr(response) }
+        .to_str()
+        .unwrap()
+        .to_owned();
+    gs_string_free(response);
+    let handle = serde_json::from_str::<serde_json::Value>(&text).unwrap()["data"]["handle"]
+        .as_u64()
+        .unwrap();
+    let grid_json = CString::new(serde_json::to_string(&grid).unwrap()).unwrap();
+    let mut buffer = vec![0.0; 72];
+    assert_eq!(
+        gs_sample_field(handle, grid_json.as_ptr(), buffer.as_mut_ptr(), 71),
+        -2
+    );
+    assert_eq!(
+        gs_sample_field(handle, grid_json.as_ptr(), buffer.as_mut_ptr(), 72),
+        24
+    );
+    assert_eq!(buffer[3 * 5], field.potential[5]);
+    assert_eq!(buffer[3 * 5 + 2], field.acceleration[5].y);
+    gs_string_free(gs_dispose(handle));
+}
+
+#[test]
+fn kepler_orbit_sampling_follows_the_osculating_conic() {
+    let star = Body::new("star", 1.0, 0.1, Vec2::ZERO, Vec2::ZERO);
+    let planet = Body::new(
+        "planet",
+        1e-9,
+        0.01,
+        Vec2::new(1.0, 0.0),
+        Vec2::new(0.0, 1.2),
+    );
+    let points = sample_kepler_orbit(&star, &planet, 64, 1.0);
+    assert_eq!(points.len(), 64);
+    assert!(points[0].distance(planet.position) < 1e-9);
+    // a = 1 / (2 - 1.44), apoapsis = 2a - periapsis.
+    let apoapsis = 2.0 / 0.56 - 1.0;
+    assert!((points[32].norm() - apoapsis).abs() < 1e-6);
+    assert!(points[1].y > 0.0);
+
+    let escaping = Body {
+        velocity: Vec2::new(0.0, -2.0),
+        ..planet.clone()
+    };
+    let hyperbola = sample_kepler_orbit(&star, &escaping, 33, 1.0);
+    assert!(hyperbola[16].distance(planet.position) < 1e-9);
+    assert!(hyperbola[0].y > 0.0 && hyperbola[32].y < 0.0);
+    assert!(hyperbola.iter().all(|point| point.norm() <= 10.0 + 1e-9));
+
+    let radial = Body {
+        velocity: Vec2::new(0.5, 0.0),
+        ..planet
+    };
+    assert!(sample_kepler_orbit(&star, &radial, 16, 1.0).is_empty());
+}
+
+#[test]
+fn jacobi_constant_is_conserved_for_a_test_particle() {
+    let (m1, m2, separation) = (1.0 - 1e-3, 1e-3, 1.0);
+    let omega = 1.0_f64;
+    let primary_x = -m2 * separation;
+    let secondary_x = m1 * separation;
+    let particle = Vec2::new(0.0, 0.7);
+    let bodies = vec![
+        Body::new(
+            "sun",
+            m1,
+            0.01,
+            Vec2::new(primary_x, 0.0),
+            Vec2::new(0.0, omega * primary_x),
+        ),
+        Body::new(
+            "jupiter",
+            m2,
+            0.001,
+            Vec2::new(secondary_x, 0.0),
+            Vec2::new(0.0, omega * secondary_x),
+        ),
+        Body::new("asteroid", 1e-12, 0.0001, particle, particle.perp() * -1.1),
+    ];
+    let config = EngineConfig {
+        softening_epsilon: 0.0,
+        ..base_config()
+    };
+    let mut engine = SimulationEngine::with_bodies(config, bodies).unwrap();
+    let particle_energy = |engine: &SimulationEngine| {
+        let bodies = engine.bodies();
+        0.5 * bodies[2].velocity.norm_squared()
+            - bodies[..2]
+                .iter()
+                .map(|body| body.mass / body.position.distance(bodies[2].position))
+                .sum::<f64>()
+    };
+    let initial_energy = particle_energy(&engine);
+    let initial = engine.jacobi_constants("sun", "jupiter").unwrap();
+    assert_eq!(initial.len(), 1);
+    assert_eq!(initial[0].body_id, "asteroid");
+
+    engine.step(3000).unwrap();
+    let later = engine.jacobi_constants("sun", "jupiter").unwrap();
+    let drift = (later[0].jacobi_constant - initial[0].jacobi_constant).abs();
+    assert!(
+        drift < 1e-5 * initial[0].jacobi_constant.abs(),
+        "drift {drift}"
+    );
+    assert!((particle_energy(&engine) - initial_energy).abs() > 100.0 * drift);
+
+    assert!(engine.jacobi_constants("sun", "sun").is_err());
+    assert!(engine.jacobi_constants("sun", "pluto").is_err());
+}
+
+#[test]
+fn lagrange_points_match_the_earth_moon_system() {
+    let mu = 0.01215;
+    let pair = |omega: f64| {
+        vec![
+            Body::new(
+                "earth",
+                1.0 - mu,
+                0.01,
+                Vec2::new(-mu, 0.0),
+                Vec2::new(0.0, -omega * mu),
+            ),
+            Body::new(
+                "moon",
+                mu,
+                0.001,
+                Vec2::new(1.0 - mu, 0.0),
+                Vec2::new(0.0, omega * (1.0 - mu)),
+            ),
+        ]
+    };
+    let engine = SimulationEngine::with_bodies(base_config(), pair(1.0)).unwrap();
+    let l4 = Vec2::new(0.5 - mu, 0.75_f64.sqrt());
+    let grid = GridSpec::centered(l4, 1e-3, 1, 1);
+    let map = engine.lagrange_map("earth", "moon", Some(&grid)).unwrap();
+    let points = map.points;
+    assert!((map.angular_rate - 1.0).abs() < 1e-12);
+    assert!(map.barycenter.norm() < 1e-12);
+    for (point, x) in [
+        (points.l1, 0.836915),
+        (points.l2, 1.155682),
+        (points.l3, -1.005063),
+    ] {
+        assert!(
+            (point.x - x).abs() < 1e-5 && point.y == 0.0,
+            "{point:?} vs {x}"
+        );
+    }
+    assert!(points.l4.distance(l4) < 1e-12 && points.l5.distance(Vec2::new(l4.x, -l4.y)) < 1e-12);
+    // Both primaries are a unit distance from L4.
+    let expected = -1.0 - 0.5 * (1.0 - mu + mu * mu);
+    assert!(
+        (map.potential[0] - expected).abs() < 1e-9,
+        "{:?}",
+        map.potential
+    );
+
+    let retrograde = SimulationEngine::with_bodies(base_config(), pair(-1.0)).unwrap();
+    let map = retrograde.lagrange_map("earth", "moon", None).unwrap();
+    assert!(map.potential.is_empty());
+    assert!(map.points.l4.y < 0.0 && map.points.l5.y > 0.0);
+    assert!(engine.lagrange_map("earth", "earth", None).is_err());
+    assert!(matches!(
+        engine.lagrange_map("earth", "mars", None),
+        Err(EngineError::BodyNotFound(_))
+    ));
+}
+
+#[test]
+fn angular_momentum_guard_reports_and_corrects_drift() {
+    let bodies = vec![
+        Body::new("sun", 1.0, 0.01, Vec2::ZERO, Vec2::ZERO),
+        Body::new(
+            "inner",
+            0.01,
+            0.01,
+            Vec2::new(1.0, 0.0),
+            Vec2::new(0.0, 1.2),
+        ),
+        Body::new(
+            "outer",
+            0.01,
+            0.01,
+            Vec2::new(-2.0, 0.0),
+            Vec2::new(0.0, -0.6),
+        ),
+    ];
+    let guarded = |correct: bool| EngineConfig {
+        integrator: IntegratorKind::Rk4,
+        dt: 0.05,
+        angular_momentum_guard: Some(AngularMomentumGuard {
+            interval_ticks: 10,
+            tolerance: 1e-12,
+            correct,
+        }),
+        ..base_config()
+    };
+
+    let mut watched = SimulationEngine::with_bodies(guarded(false), bodies.clone()).unwrap();
+    let initial = watched.diagnostics();
+    let summary = watched.step(400).unwrap();
+    let drift = summary.angular_momentum_drift.unwrap();
+    assert!(drift.abs() > 1e-12);
+    let measured = (watched.diagnostics().angular_momentum - initial.angular_momentum)
+        / initial.angular_momentum.abs();
+    assert!((drift - measured).abs() < 1e-15);
+    assert!(
+        summary
+            .warnings
+            .iter()
+            .any(|warning| warning.contains("angular momentum"))
+    );
+    assert_eq!(summary.angular_momentum_correction, 0.0);
+
+    let mut corrected = SimulationEngine::with_bodies(guarded(true), bodies).unwrap();
+    let summary = corrected.step(400).unwrap();
+    assert!(summary.angular_momentum_correction != 0.0);
+    assert!(summary.warnings.is_empty());
+    let after = corrected.diagnostics();
+    let residual =
+        (after.angular_momentum - initial.angular_momentum) / initial.angular_momentum.abs();
+    assert!(residual.abs() < 1e-12, "residual {residual}");
+    assert!((after.linear_momentum - initial.linear_momentum).norm() < 1e-12);
+    let energy_shift = (after.total_energy - watched.diagnostics().total_energy).abs();
+    assert!(energy_shift < 1e-6 * initial.total_energy.abs());
+}
+
+#[test]
+fn collision_rate_applies_gravitational_focusing() {
+    let body = |id: &str, mass: f64, position: Vec2, velocity: Vec2| {
+        Body::new(id, mass, 0.1, position, velocity)
+    };
+    let mut bodies = vec![
+        body("big0", 1.0, Vec2::new(2.0, 2.0), Vec2::new(1.0, 0.0)),
+        body("big1", 1.0, Vec2::new(8.0, 2.0), Vec2::new(-1.0, 0.0)),
+        body("big-outside", 1.0, Vec2::new(20.0, 2.0), Vec2::ZERO),
+    ];
+    for index in 0..3 {
+        bodies.push(body(
+            &format!("small{index}"),
+            0.5,
+            Vec2::new(2.0 + 2.0 * index as f64, 7.0),
+            Vec2::new(0.0, 2.0),
+        ));
+    }
+    let engine = SimulationEngine::with_bodies(base_config(), bodies).unwrap();
+    let heavy = BodyQuery {
+        min_mass: Some(0.9),
+        ..BodyQuery::default()
+    };
+    let light = BodyQuery {
+        max_mass: Some(0.6),
+        ..BodyQuery::default()
+    };
+    let region = Region::Rect {
+        min: Vec2::ZERO,
+        max: Vec2::new(10.0, 10.0),
+    };
+
+    let estimate = engine
+        .collision_rate(&CollisionRateQuery {
+            first: heavy.clone(),
+            second: light,
+            region: region.clone(),
+        })
+        .unwrap();
+    assert_eq!((estimate.first_count, estimate.second_count), (2, 3));
+    assert!((estimate.relative_speed - 5.0_f64.sqrt()).abs() < 1e-12);
+    assert!((estimate.escape_speed - 15.0_f64.sqrt()).abs() < 1e-12);
+    assert!((estimate.focusing_factor - 4.0).abs() < 1e-12);
+    assert!((estimate.geometric_rate - 6.0 / 100.0 * 0.4 * 5.0_f64.sqrt()).abs() < 1e-12);
+    assert!((estimate.expected_rate - 2.0 * estimate.geometric_rate).abs() < 1e-12);
+
+    let within = engine
+        .collision_rate(&CollisionRateQuery {
+            first: heavy.clone(),
+            second: heavy,
+            region,
+        })
+        .unwrap();
+    assert!((within.relative_speed - 2.0_f64.sqrt()).abs() < 1e-12);
+    assert!((within.geometric_rate - 1.0 / 100.0 * 0.4 * 2.0_f64.sqrt()).abs() < 1e-12);
+}
+
+#[test]
+fn energy_watchdog_warns_and_fails_on_drift() {
+    let bodies = vec![
+        Body::new("sun", 1.0, 0.01, Vec2::ZERO, Vec2::ZERO),
+        Body::new(
+            "comet",
+            1e-3,
+            0.01,
+            Vec2::new(1.0, 0.0),
+            Vec2::new(0.0, 0.4),
+        ),
+    ];
+    let watched = |dt: f64, hard_limit: Option<f64>| EngineConfig {
+        integrator: IntegratorKind::SemiImplicitEuler,
+        dt,
+        energy_watchdog: Some(EnergyWatchdog {
+            interval_ticks: 10,
+            warn_drift: 1e-3,
+            hard_limit,
+        }),
+        ..base_config()
+    };
+
+    let mut fine = SimulationEngine::with_bodies(watched(1e-4, None), bodies.clone()).unwrap();
+    let summary = fine.step(100).unwrap();
+    assert!(summary.energy_drift.unwrap().abs() < 1e-3);
+    assert!(summary.warnings.is_empty());
+
+    let mut coarse = SimulationEngine::with_bodies(watched(0.05, None), bodies.clone()).unwrap();
+    let initial = coarse.diagnostics().total_energy;
+    let summary = coarse.step(100).unwrap();
+    let drift = summary.energy_drift.unwrap();
+    let measured = (coarse.diagnostics().total_energy - initial) / initial.abs();
+    assert!((drift - measured).abs() < 1e-12);
+    assert!(
+        summary
+            .warnings
+            .iter()
+            .any(|warning| warning.contains("total energy"))
+    );
+
+    let mut failing =
+        SimulationEngine::with_bodies(watched(0.05, Some(drift.abs() / 2.0)), bodies).unwrap();
+    assert!(matches!(
+        failing.step(100),
+        Err(EngineError::NumericalInstability(_))
+    ));
+
+    assert!(
+        SimulationEngine::with_bodies(watched(0.05, Some(1e-4)), Vec::new()).is_err(),
+        "hard limit below warn drift"
+    );
+}
@@ -0,0 +1,193 @@
+use gravity_engine::{
+    Body3, BoundaryMode, CoarseGraining, CollisionMode, EngineConfig, EngineError, EscapePolicy,
+    Frame, GravitySolver, GroupRule, IntegratorKind, InteractionGroups, SimulationEngine3,
+    TidalDisruption, Vec2, Vec3, WorldBoundary,
+};
+
+fn base_config() -> EngineConfig {
+    EngineConfig {
+        gravity_constant: 1.0,
+        softening_epsilon: 1e-6,
+        dt: 0.001,
+        integrator: IntegratorKind::VelocityVerlet,
+        collision_mode: CollisionMode::Ignore,
+        gravity_solver: GravitySolver::Pairwise,
+        ..EngineConfig::default()
+    }
+}
+
+fn angular_momentum(bodies: &[Body3]) -> Vec3 {
+    bodies.iter().fold(Vec3::ZERO, |acc, body| {
+        acc + body.position.cross(body.velocity * body.mass)
+    })
+}
+
+fn momentum(bodies: &[Body3]) -> Vec3 {
+    bodies
+        .iter()
+        .fold(Vec3::ZERO, |acc, body| acc + body.velocity * body.mass)
+}
+
+#[test]
+fn inclined_orbit_leaves_the_plane_and_keeps_its_orbit_normal() {
+    let inclination: f64 = 0.5;
+    let radius = 10.0;
+    let speed = (1000.0_f64 / radius).sqrt();
+    let bodies = vec![
+        Body3::new("star", 1000.0, 1.0, Vec3::ZERO, Vec3::ZERO),
+        Body3::new(
+            "planet",
+            1e-6,
+            0.1,
+            Vec3::new(radius, 0.0, 0.0),
+            Vec3::new(0.0, speed * inclination.cos(), speed * inclination.sin()),
+        ),
+    ];
+    let l0 = angular_momentum(&bodies);
+
+    let mut engine = SimulationEngine3::with_bodies(base_config(), bodies).unwrap();
+    engine.step(500).unwrap();
+
+    let planet = &engine.bodies()[1];
+    assert!(planet.position.z > 1.0, "z = {}", planet.position.z);
+    assert!(((planet.position - engine.bodies()[0].position).norm() - radius).abs() < 1e-3);
+
+    let l1 = angular_momentum(engine.bodies());
+    assert!((l1 - l0).norm() < 1e-9 * l0.norm().max(1.0));
+}
+
+#[test]
+fn octree_barnes_hut_tracks_pairwise_in_a_cluster() {
+    let mut bodies = Vec::new();
+    for i in 0..150 {
+        let t = i as f64;
+        let position = Vec3::new(
+            20.0 * (t * 0.37).sin(),
+            20.0 * (t * 0.71).cos(),
+            10.0 * (t * 0.13).sin(),
+        );
+        let velocity = Vec3::new(-position.y, position.x, 0.0) * 0.05;
+        bodies.push(Body3::new(format!("b{i}"), 0.5, 0.1, position, velocity));
+    }
+
+    let mut pairwise = SimulationEngine3::with_bodies(base_config(), bodies.clone()).unwrap();
+    let mut barnes_hut = SimulationEngine3::with_bodies(
+        EngineConfig {
+            gravity_solver: GravitySolver::BarnesHut,
+            ..base_config()
+        },
+        bodies,
+    )
+    .unwrap();
+
+    pairwise.step(100).unwrap();
+    let summary = barnes_hut.step(100).unwrap();
+    assert_eq!(summary.barnes_hut_ticks, 100);
+
+    let max_deviation = pairwise
+        .bodies()
+        .iter()
+        .zip(barnes_hut.bodies())
+        .map(|(a, b)| (a.position - b.position).norm())
+        .fold(0.0, f64::max);
+    assert!(max_deviation < 1e-3, "max deviation {max_deviation}");
+}
+
+#[test]
+fn merge_in_3d_conserves_mass_and_momentum() {
+    let config = EngineConfig {
+        collision_mode: CollisionMode::InelasticMerge,
+        ..base_config()
+    };
+    let bodies = vec![
+        Body3::new("a", 2.0, 1.0, Vec3::ZERO, Vec3::new(1.0, 0.0, 0.5)),
+        Body3::new(
+            "b",
+            3.0,
+            1.0,
+            Vec3::new(0.2, 0.3, 0.4),
+            Vec3::new(-0.5, 0.2, 0.0),
+        ),
+    ];
+    let p0 = momentum(&bodies);
+
+    let mut engine = SimulationEngine3::with_bodies(config, bodies).unwrap();
+    let summary = engine.step(1).unwrap();
+
+    assert_eq!(summary.merged_events, 1);
+    assert_eq!(engine.bodies().len(), 1);
+    assert!((engine.bodies()[0].mass - 5.0).abs() < 1e-12);
+    assert!((engine.bodies()[0].radius - 2.0_f64.cbrt()).abs() < 1e-12);
+    assert!((momentum(engine.bodies()) - p0).norm() < 1e-10);
+}
+
+#[test]
+fn planar_only_options_are_rejected_by_the_3d_engine() {
+    let configs = [
+        EngineConfig {
+            boundary: Some(WorldBoundary {
+                min: Vec2::new(-10.0, -10.0),
+                max: Vec2::new(10.0, 10.0),
+                mode: BoundaryMode::Reflect,
+            }),
+            ..base_config()
+        },
+        EngineConfig {
+            central_body: Some("star".to_string()),
+            ..base_config()
+        },
+        EngineConfig {
+            remove_escapers: Some(EscapePolicy {
+                radius: 100.0,
+                min_speed: 0.0,
+            }),
+            ..base_config()
+        },
+        EngineConfig {
+            tidal_disruption: Some(TidalDisruption::default()),
+            ..base_config()
+        },
+        EngineConfig {
+            coarse_graining: Some(CoarseGraining {
+                cell_size: 1.0,
+                max_body_mass: 1e-6,
+                focus: Vec2::ZERO,
+                focus_radius: 10.0,
+                interval_ticks: 5,
+                min_members: 2,
+            }),
+            ..base_config()
+        },
+        EngineConfig {
+            interaction_groups: Some(InteractionGroups {
+                rules: vec![GroupRule {
+                    source: Some(1),
+                    target: Some(2),
+                    gravity: Some(false),
+                    collisions: None,
+                }],
+            }),
+            ..base_config()
+        },
+        EngineConfig {
+            collision_friction: 0.2,
+            ..base_config()
+        },
+        EngineConfig {
+            recenter: Some(Frame::CenterOfMass),
+            ..base_config()
+        },
+        EngineConfig {
+            pause_on_events: vec!["collision".to_string()],
+            ..base_config()
+        },
+    ];
+
+    for config in configs {
+        let error = SimulationEngine3::with_bodies(config.clone(), Vec::new()).unwrap_err();
+        assert!(
+            matches!(error, EngineError::UnsupportedFeature(_)),
+            "{config:?} gave {error:?}"
+        );
+    }
+}
@@ -1,6 +1,11 @@
+use std::ops::ControlFlow;
+
 use gravity_engine::{
-    Body, CollisionMode, DtPolicy, EngineConfig, GravitySolver, IntegratorKind, SimulationEngine,
-    Vec2,
+    AngularMomentumGuard, Body, BodyEdit, BodyUpdate, BoundaryMode, CoarseGraining, CollisionMode,
+    ConfigPatch, DtPolicy, EngineConfig, EngineError, EscapePolicy, ForceCaching,
+    ForceErrorSampling, Frame, GravitySolver, IntegratorKind, LyapunovQuery, Maneuver, MergeCause,
+    RewindMethod, SimulationEngine, StopCondition, ThetaTarget, ThetaTuning, Thrust,
+    TidalDisruption, Vec2, WorldBoundary,
 };
 
 fn base_config() -> EngineConfig {
@@ -15,6 +20,7 @@ fn base_config() -> EngineConfig {
         gravity_solver: GravitySolver::Pairwise,
         barnes_hut_theta: 0.6,
         barnes_hut_threshold: 256,
+        ..EngineConfig::default()
     }
 }
 
@@ -218,6 +224,144 @@ fn deterministic_replay_produces_identical_snapshots() {
     assert_eq!(engine_a.snapshot(), engine_b.snapshot());
 }
 
+fn eccentric_orbit() -> Vec<Body> {
+    let periapsis_speed = 19.0_f64.sqrt();
+    vec![
+        Body::new("star", 1.0, 0.01, Vec2::ZERO, Vec2::ZERO),
+        Body::new(
+            "comet",
+            1e-9,
+            0.001,
+            Vec2::new(0.1, 0.0),
+            Vec2::new(0.0, periapsis_speed),
+        ),
+    ]
+}
+
+#[test]
+fn error_controlled_dt_refines_near_periapsis() {
+    let fixed = EngineConfig {
+        dt: 0.02,
+        ..base_config()
+    };
+    let controlled = EngineConfig {
+        dt_policy: DtPolicy::ErrorControlled,
+        dt_tolerance: 1e-5,
+        ..fixed.clone()
+    };
+
+    let e0 = total_energy(&eccentric_orbit(), 1.0);
+    let mut fixed_engine = SimulationEngine::with_bodies(fixed, eccentric_orbit()).unwrap();
+    let mut controlled_engine =
+        SimulationEngine::with_bodies(controlled, eccentric_orbit()).unwrap();
+
+    let fixed_summary = fixed_engine.step(630).unwrap();
+    let controlled_summary = controlled_engine.step(630).unwrap();
+
+    assert_eq!(fixed_summary.substeps, 630);
+    assert!(controlled_summary.substeps > 630);
+    approx_eq(controlled_summary.sim_time, fixed_summary.sim_time, 1e-9);
+
+    let fixed_drift = (total_energy(fixed_engine.bodies(), 1.0) - e0).abs();
+    let controlled_drift = (total_energy(controlled_engine.bodies(), 1.0) - e0).abs();
+    assert!(
+        controlled_drift < fixed_drift * 0.01,
+        "controlled drift {controlled_drift} vs fixed drift {fixed_drift}"
+    );
+}
+
+#[test]
+fn error_controlled_dt_replays_recorded_schedule() {
+    let config = EngineConfig {
+        dt: 0.02,
+        dt_policy: DtPolicy::ErrorControlled,
+        dt_tolerance: 1e-5,
+        ..base_config()
+    };
+
+    let mut recorded = SimulationEngine::with_bodies(config.clone(), eccentric_orbit()).unwrap();
+    recorded.step(300).unwrap();
+    let schedule = recorded.dt_schedule().clone();
+    assert_eq!(schedule.start_tick, 0);
+    assert_eq!(schedule.levels.len(), 300);
+
+    // A looser tolerance would pick different levels; the replay must override it.
+    let loose = EngineConfig {
+        dt_tolerance: 1e-2,
+        ..config
+    };
+    let mut replayed = SimulationEngine::with_bodies(loose, eccentric_orbit()).unwrap();
+    replayed.replay_dt_schedule(schedule.clone()).unwrap();
+    replayed.step(300).unwrap();
+
+    assert_eq!(replayed.bodies(), recorded.bodies());
+    assert_eq!(replayed.dt_schedule(), &schedule);
+    assert!(replayed.replay_dt_schedule(schedule).is_err());
+}
+
+/// A tight binary orbiting a star, with a wide planet further out.
+fn binary_in_wide_system() -> Vec<Body> {
+    let binary_speed = (1.0_f64 / 3.0).sqrt();
+    vec![
+        Body::new("star", 1.0, 0.001, Vec2::ZERO, Vec2::ZERO),
+        Body::new(
+            "binary-a",
+            0.01,
+            0.001,
+            Vec2::new(2.99, 0.0),
+            Vec2::new(0.0, binary_speed + 0.5),
+        ),
+        Body::new(
+            "binary-b",
+            0.01,
+            0.001,
+            Vec2::new(3.01, 0.0),
+            Vec2::new(0.0, binary_speed - 0.5),
+        ),
+        Body::new(
+            "planet",
+            0.001,
+            0.001,
+            Vec2::new(-8.0, 0.0),
+            Vec2::new(0.0, -(1.0_f64 / 8.0).sqrt()),
+        ),
+    ]
+}
+
+#[test]
+fn block_timesteps_refine_only_the_tight_binary() {
+    let block = EngineConfig {
+        dt: 0.05,
+        dt_policy: DtPolicy::BlockTimesteps,
+        max_substep_level: 10,
+        ..base_config()
+    };
+    let reference = EngineConfig {
+        dt: 0.05 / 256.0,
+        ..base_config()
+    };
+    let mut engine = SimulationEngine::with_bodies(block, binary_in_wide_system()).unwrap();
+    let levels = engine.timestep_levels();
+    assert!(levels[1] >= 5 && levels[1] == levels[2], "{levels:?}");
+    assert!(levels[3] < levels[1], "{levels:?}");
+    assert!(levels[1] <= 10);
+
+    let e0 = total_energy(engine.bodies(), 1.0);
+    let summary = engine.step(100).unwrap();
+    assert_eq!(summary.ticks_applied, 100);
+    assert!(summary.substeps >= 100 << levels[1]);
+    approx_eq(summary.sim_time, 5.0, 1e-9);
+    let drift = ((total_energy(engine.bodies(), 1.0) - e0) / e0).abs();
+    assert!(drift < 1e-5, "relative energy drift {drift}");
+
+    let mut fine = SimulationEngine::with_bodies(reference, binary_in_wide_system()).unwrap();
+    fine.step(25_600).unwrap();
+    for (block_body, fine_body) in engine.bodies().iter().zip(fine.bodies()) {
+        let error = (block_body.position - fine_body.position).norm();
+        assert!(error < 2e-3, "{} off by {error}", block_body.id);
+    }
+}
+
 #[test]
 fn inelastic_merge_conserves_mass_and_momentum() {
     let config = EngineConfig {
@@ -247,6 +391,282 @@ fn inelastic_merge_conserves_mass_and_momentum() {
     approx_eq(p0.y, p1.y, 1e-10);
 }
 
+#[test]
+fn spins_integrate_and_merges_conserve_total_angular_momentum() {
+    let spinner = Body {
+        angular_velocity: 2.0,
+        ..Body::new("spinner", 1.0, 0.1, Vec2::new(50.0, 0.0), Vec2::ZERO)
+    };
+    let mut engine = SimulationEngine::with_bodies(base_config(), vec![spinner]).unwrap();
+    engine.step(4000).unwrap();
+    approx_eq(engine.bodies()[0].angle, 8.0 - std::f64::consts::TAU, 1e-9);
+
+    // An off-centre hit: the orbital angular momentum of the pair becomes spin.
+    let config = EngineConfig {
+        collision_mode: CollisionMode::InelasticMerge,
+        ..base_config()
+    };
+    let bodies = vec![
+        Body {
+            angular_velocity: -0.5,
+            ..Body::new("a", 2.0, 1.0, Vec2::new(0.0, 0.0), Vec2::new(1.0, 0.0))
+        },
+        Body::new("b", 3.0, 1.0, Vec2::new(1.0, 1.2), Vec2::new(-0.5, 0.0)),
+    ];
+    let mut engine = SimulationEngine::with_bodies(config, bodies).unwrap();
+    let before = engine.diagnostics().angular_momentum;
+    assert_eq!(engine.step(1).unwrap().merged_events, 1);
+    // (2 * 0.648 + 3 * 0.288 - 0.5) about the centre of mass, over I = 0.5 * 5 * 2.
+    approx_eq(engine.bodies()[0].angular_velocity, 0.332, 1e-3);
+    approx_eq(engine.diagnostics().angular_momentum, before, 1e-9);
+}
+
+#[test]
+fn collision_friction_trades_sliding_for_spin() {
+    let bodies = vec![
+        Body::new("a", 1.0, 0.5, Vec2::new(0.0, 0.0), Vec2::new(1.0, 0.0)),
+        Body::new("b", 1.0, 0.5, Vec2::new(0.6, 0.8), Vec2::new(-1.0, 0.0)),
+    ];
+    let config = |friction: f64| EngineConfig {
+        gravity_constant: 1e-9,
+        collision_mode: CollisionMode::Elastic,
+        collision_friction: friction,
+        ..base_config()
+    };
+
+    let mut smooth = SimulationEngine::with_bodies(config(0.0), bodies.clone()).unwrap();
+    smooth.step(1).unwrap();
+    assert!(
+        smooth
+            .bodies()
+            .iter()
+            .all(|body| body.angular_velocity == 0.0)
+    );
+
+    let mut rough = SimulationEngine::with_bodies(config(0.5), bodies).unwrap();
+    let before = rough.diagnostics();
+    assert_eq!(rough.step(1).unwrap().collision_events, 1);
+    let (a, b) = (&rough.bodies()[0], &rough.bodies()[1]);
+    assert!(a.angular_velocity != 0.0);
+    approx_eq(a.angular_velocity, b.angular_velocity, 1e-12);
+    let after = rough.diagnostics();
+    assert!(after.kinetic_energy < before.kinetic_energy);
+    approx_eq(after.angular_momentum, before.angular_momentum, 1e-2);
+    assert!(
+        EngineConfig {
+            collision_friction: -1.0,
+            ..base_config()
+        }
+        .validate()
+        .is_err()
+    );
+}
+
+#[test]
+fn body_slots_survive_merges_and_deletions() {
+    let config = EngineConfig {
+        collision_mode: CollisionMode::InelasticMerge,
+        ..base_config()
+    };
+    let bodies = vec![
+        Body::new("a", 2.0, 1.0, Vec2::new(0.0, 0.0), Vec2::new(1.0, 0.0)),
+        Body::new("b", 3.0, 1.0, Vec2::new(0.5, 0.0), Vec2::new(-0.5, 0.0)),
+        Body::new("c", 1.0, 0.1, Vec2::new(100.0, 0.0), Vec2::ZERO),
+        Body::new("d", 1.0, 0.1, Vec2::new(-100.0, 0.0), Vec2::ZERO),
+    ];
+    let mut engine = SimulationEngine::with_bodies(config, bodies).unwrap();
+    let slot = |engine: &SimulationEngine, id: &str| engine.body_index_map().get(id).copied();
+    assert_eq!(
+        ["a", "b", "c", "d"].map(|id| slot(&engine, id)),
+        [Some(0), Some(1), Some(2), Some(3)]
+    );
+
+    let summary = engine.step(1).unwrap();
+    assert_eq!(summary.merged_events, 1);
+    let removed = &summary.removed_bodies;
+    assert_eq!(removed.ids.len(), 1);
+    let (gone, freed) = (removed.ids[0].clone(), removed.slots[0]);
+    assert!(gone == "a" || gone == "b");
+    assert_eq!(slot(&engine, &gone), None);
+    assert_eq!([slot(&engine, "c"), slot(&engine, "d")], [Some(2), Some(3)]);
+    assert_eq!(engine.body_index_map().len(), engine.bodies().len());
+
+    engine
+        .apply_edit(BodyEdit::Delete {
+            id: "c".to_string(),
+        })
+        .unwrap();
+    assert_eq!(slot(&engine, "c"), None);
+    assert_eq!(slot(&engine, "d"), Some(3));
+    engine
+        .apply_edit(BodyEdit::Create(Body::new(
+            "e",
+            1.0,
+            0.1,
+            Vec2::new(0.0, 100.0),
+            Vec2::ZERO,
+        )))
+        .unwrap();
+    assert_eq!(slot(&engine, "e"), Some(freed.min(2)));
+    assert!(engine.step(1).unwrap().removed_bodies.is_empty());
+}
+
+#[test]
+fn batched_edits_apply_atomically() {
+    let bodies = vec![Body::new("a", 1.0, 0.1, Vec2::ZERO, Vec2::ZERO)];
+    let mut engine = SimulationEngine::with_bodies(base_config(), bodies).unwrap();
+    let create =
+        |id: &str, x: f64| BodyEdit::Create(Body::new(id, 1.0, 0.1, Vec2::new(x, 0.0), Vec2::ZERO));
+
+    let error = engine
+        .apply_edits(vec![
+            create("b", 1.0),
+            BodyEdit::Delete {
+                id: "a".to_string(),
+            },
+            create("b", 2.0),
+        ])
+        .unwrap_err();
+    assert!(matches!(error, EngineError::DuplicateBodyId(_)), "{error}");
+    let ids = |engine: &SimulationEngine| {
+        engine
+            .bodies()
+            .iter()
+            .map(|body| body.id.clone())
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(ids(&engine), ["a"]);
+    assert_eq!(engine.body_index_map().len(), 1);
+
+    let edits = (0..500)
+        .map(|index| create(&format!("drawn-{index}"), 2.0 + index as f64))
+        .collect();
+    engine.apply_edits(edits).unwrap();
+    assert_eq!(engine.bodies().len(), 501);
+    assert_eq!(engine.body_index_map()["drawn-499"], 500);
+}
+
+#[test]
+fn merge_history_traces_accretion_chains() {
+    let config = EngineConfig {
+        collision_mode: CollisionMode::InelasticMerge,
+        ..base_config()
+    };
+    let bodies = vec![
+        Body::new("a", 2.0, 1.0, Vec2::new(0.0, 0.0), Vec2::new(1.0, 0.0)),
+        Body::new("b", 3.0, 1.0, Vec2::new(0.5, 0.0), Vec2::new(-0.5, 0.0)),
+    ];
+    let mut engine = SimulationEngine::with_bodies(config, bodies).unwrap();
+    let summary = engine.step(1).unwrap();
+    assert_eq!(summary.merges.len(), 1);
+    let first = summary.merges[0].clone();
+    assert_eq!(first.cause, MergeCause::Collision);
+    assert_eq!(first.tick, 1);
+    approx_eq(first.mass, 5.0, 1e-12);
+    let mut members = [first.survivor.as_str(), first.absorbed[0].as_str()];
+    members.sort_unstable();
+    assert_eq!(members, ["a", "b"]);
+    let snapshot = engine.snapshot();
+
+    let survivor = engine.bodies()[0].position;
+    engine
+        .apply_edit(BodyEdit::Create(Body::new(
+            "c",
+            1.0,
+            1.0,
+            survivor,
+            Vec2::ZERO,
+        )))
+        .unwrap();
+    engine.step(1).unwrap();
+    let history = engine.merge_history();
+    assert_eq!(history.len(), 2);
+    assert_eq!(history[0], first);
+    assert!(history[1].survivor == first.survivor || history[1].absorbed[0] == first.survivor);
+    approx_eq(history[1].mass, 6.0, 1e-12);
+
+    engine.restore_snapshot(snapshot).unwrap();
+    assert_eq!(engine.merge_history(), [first]);
+}
+
+#[test]
+fn non_collidable_bodies_pass_through_each_other() {
+    let config = EngineConfig {
+        collision_mode: CollisionMode::InelasticMerge,
+        ..base_config()
+    };
+
+    let tracer = Body {
+        collidable: false,
+        ..Body::new("tracer", 1.0, 1.0, Vec2::new(0.5, 0.0), Vec2::ZERO)
+    };
+    let bodies = vec![
+        Body::new("a", 2.0, 1.0, Vec2::new(0.0, 0.0), Vec2::ZERO),
+        tracer,
+        Body::new("b", 3.0, 1.0, Vec2::new(1.0, 0.0), Vec2::ZERO),
+    ];
+
+    let mut engine = SimulationEngine::with_bodies(config, bodies).unwrap();
+    let summary = engine.step(1).unwrap();
+
+    assert_eq!(summary.collision_events, 1);
+    assert_eq!(summary.merged_events, 1);
+    let ids = engine
+        .bodies()
+        .iter()
+        .map(|body| body.id.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(ids, vec!["a", "tracer"]);
+}
+
+#[test]
+fn fixed_bodies_pull_but_never_move() {
+    let config = EngineConfig {
+        collision_mode: CollisionMode::InelasticMerge,
+        ..base_config()
+    };
+    let star = Body {
+        fixed: true,
+        ..Body::new("star", 1.0, 0.05, Vec2::ZERO, Vec2::new(0.3, 0.0))
+    };
+    let bodies = vec![
+        star,
+        Body::new(
+            "planet",
+            1.0,
+            0.01,
+            Vec2::new(1.0, 0.0),
+            Vec2::new(0.0, 1.0),
+        ),
+    ];
+
+    let mut engine = SimulationEngine::with_bodies(config, bodies).unwrap();
+    engine.step(500).unwrap();
+    assert_eq!(engine.bodies()[0].position, Vec2::ZERO);
+    // An equal-mass partner would have drifted; only the star's pull bends this one.
+    let planet = &engine.bodies()[1];
+    approx_eq(planet.position.norm(), 1.0, 1e-3);
+
+    let impactor = Body::new(
+        "rock",
+        4.0,
+        0.05,
+        Vec2::new(0.08, 0.0),
+        Vec2::new(-1.0, 0.0),
+    );
+    engine.apply_edit(BodyEdit::Create(impactor)).unwrap();
+    let summary = engine.step(1).unwrap();
+    assert_eq!(summary.merged_events, 1);
+    let star = engine
+        .bodies()
+        .iter()
+        .find(|body| body.id == "star")
+        .unwrap();
+    assert!(star.fixed);
+    assert_eq!(star.position, Vec2::ZERO);
+    approx_eq(star.mass, 5.0, 1e-12);
+}
+
 #[test]
 fn auto_solver_switches_between_pairwise_and_barnes_hut() {
     let bodies = vec![
@@ -350,3 +770,1277 @@ fn escape_velocity_threshold_matches_energy_sign() {
     assert!(specific_energy_below < 0.0);
     assert!(specific_energy_above > 0.0);
 }
+
+fn quiet_cluster_with_fast_binary() -> Vec<Body> {
+    let mut bodies = vec![
+        Body::new("a", 1.0, 0.01, Vec2::new(-0.05, 0.0), Vec2::new(0.0, -2.2)),
+        Body::new("b", 1.0, 0.01, Vec2::new(0.05, 0.0), Vec2::new(0.0, 2.2)),
+    ];
+    for i in 0..30 {
+        let angle = i as f64 * 0.21;
+        let radius = 40.0 + (i % 5) as f64;
+        bodies.push(Body::new(
+            format!("dust{i}"),
+            1e-3,
+            0.01,
+            Vec2::new(radius * angle.cos(), radius * angle.sin()),
+            Vec2::ZERO,
+        ));
+    }
+    bodies
+}
+
+#[test]
+fn exact_force_caching_matches_uncached_verlet() {
+    let cached = EngineConfig {
+        force_caching: Some(ForceCaching {
+            displacement_threshold: 0.0,
+        }),
+        ..base_config()
+    };
+    let mut plain =
+        SimulationEngine::with_bodies(base_config(), quiet_cluster_with_fast_binary()).unwrap();
+    let mut reuse =
+        SimulationEngine::with_bodies(cached, quiet_cluster_with_fast_binary()).unwrap();
+
+    plain.step(200).unwrap();
+    let summary = reuse.step(200).unwrap();
+
+    assert_eq!(plain.bodies(), reuse.bodies());
+    assert_eq!(summary.force_cache_hits, 199);
+    assert_eq!(summary.force_cache_partial_updates, 0);
+}
+
+#[test]
+fn threshold_force_caching_only_resums_moving_bodies() {
+    let cached = EngineConfig {
+        force_caching: Some(ForceCaching {
+            displacement_threshold: 1e-3,
+        }),
+        ..base_config()
+    };
+    let mut plain =
+        SimulationEngine::with_bodies(base_config(), quiet_cluster_with_fast_binary()).unwrap();
+    let mut reuse =
+        SimulationEngine::with_bodies(cached, quiet_cluster_with_fast_binary()).unwrap();
+
+    plain.step(500).unwrap();
+    let summary = reuse.step(500).unwrap();
+
+    assert!(summary.force_cache_partial_updates > 400);
+    for (expected, actual) in plain.bodies().iter().zip(reuse.bodies()) {
+        assert!(
+            (expected.position - actual.position).norm() < 1e-6,
+            "{} drifted",
+            expected.id
+        );
+    }
+}
+
+#[test]
+fn force_caching_is_bypassed_by_barnes_hut_solves() {
+    let tree = EngineConfig {
+        gravity_solver: GravitySolver::BarnesHut,
+        ..base_config()
+    };
+    let cached = EngineConfig {
+        force_caching: Some(ForceCaching {
+            displacement_threshold: 1e-3,
+        }),
+        ..tree.clone()
+    };
+    let mut plain = SimulationEngine::with_bodies(tree, quiet_cluster_with_fast_binary()).unwrap();
+    let mut reuse =
+        SimulationEngine::with_bodies(cached, quiet_cluster_with_fast_binary()).unwrap();
+
+    plain.step(100).unwrap();
+    let summary = reuse.step(100).unwrap();
+
+    assert_eq!(plain.bodies(), reuse.bodies());
+    assert_eq!(summary.force_cache_hits, 0);
+    assert_eq!(summary.force_cache_partial_updates, 0);
+}
+
+#[test]
+fn force_caching_threshold_changes_the_config_hash() {
+    let caching = |displacement_threshold| EngineConfig {
+        force_caching: Some(ForceCaching {
+            displacement_threshold,
+        }),
+        ..base_config()
+    };
+
+    assert_ne!(base_config().stable_hash(), caching(0.0).stable_hash());
+    assert_ne!(caching(0.0).stable_hash(), caching(1e-3).stable_hash());
+}
+
+#[test]
+fn run_until_stops_on_the_first_satisfied_condition() {
+    let bodies = vec![
+        Body::new("a", 1.0, 0.1, Vec2::new(-1.0, 0.0), Vec2::ZERO),
+        Body::new("b", 1.0, 0.1, Vec2::new(1.0, 0.0), Vec2::ZERO),
+    ];
+    let mut engine = SimulationEngine::with_bodies(base_config(), bodies.clone()).unwrap();
+
+    let close = StopCondition::Separation {
+        first_id: "a".to_string(),
+        second_id: "b".to_string(),
+        distance: 1.0,
+    };
+    let outcome = engine.run_until(&close, 100_000).unwrap();
+    assert_eq!(outcome.stop_reason.as_deref(), Some("separation"));
+    let gap = (engine.bodies()[1].position - engine.bodies()[0].position).norm();
+    assert!(gap <= 1.0 && gap > 0.99);
+    assert_eq!(u64::from(outcome.summary.ticks_applied), engine.tick());
+
+    // Already satisfied: no ticks are taken.
+    let again = engine.run_until(&close, 10).unwrap();
+    assert_eq!(again.summary.ticks_applied, 0);
+
+    let capped = StopCondition::Any {
+        conditions: vec![
+            StopCondition::FirstCollision,
+            StopCondition::Ticks { ticks: 25 },
+        ],
+    };
+    let outcome = engine.run_until(&capped, 1000).unwrap();
+    assert_eq!(outcome.stop_reason.as_deref(), Some("ticks"));
+    assert_eq!(outcome.summary.ticks_applied, 25);
+
+    let config = EngineConfig {
+        collision_mode: CollisionMode::Elastic,
+        ..base_config()
+    };
+    let mut colliding = SimulationEngine::with_bodies(config, bodies).unwrap();
+    let outcome = colliding
+        .run_until(&StopCondition::FirstCollision, 100_000)
+        .unwrap();
+    assert_eq!(outcome.stop_reason.as_deref(), Some("firstCollision"));
+    assert_eq!(outcome.summary.collision_events, 1);
+
+    let timed_out = colliding
+        .run_until(&StopCondition::SimTime { sim_time: 1e9 }, 3)
+        .unwrap();
+    assert_eq!(timed_out.stop_reason, None);
+    assert_eq!(timed_out.summary.ticks_applied, 3);
+
+    let missing = StopCondition::BodyBeyond {
+        body_id: "ghost".to_string(),
+        radius: 1.0,
+        center: None,
+    };
+    assert!(colliding.run_until(&missing, 1).is_err());
+}
+
+#[test]
+fn step_observer_sees_every_tick_and_can_stop_early() {
+    let bodies = vec![
+        Body::new("a", 1.0, 0.1, Vec2::new(-1.0, 0.0), Vec2::ZERO),
+        Body::new("b", 1.0, 0.1, Vec2::new(1.0, 0.0), Vec2::ZERO),
+    ];
+    let mut engine = SimulationEngine::with_bodies(base_config(), bodies).unwrap();
+
+    let mut seen = Vec::new();
+    let summary = engine
+        .step_with_observer(10, |tick, bodies, summary| {
+            assert_eq!(bodies.len(), 2);
+            assert_eq!(u64::from(summary.ticks_applied), tick);
+            seen.push((tick, bodies[0].position.x));
+            ControlFlow::Continue(())
+        })
+        .unwrap();
+    assert_eq!(summary.ticks_applied, 10);
+    assert_eq!(
+        seen.iter().map(|(tick, _)| *tick).collect::<Vec<_>>(),
+        (1..=10).collect::<Vec<_>>()
+    );
+    assert!(seen.windows(2).all(|pair| pair[1].1 > pair[0].1));
+
+    let summary = engine
+        .step_with_observer(100, |tick, _, _| {
+            if tick >= 14 {
+                ControlFlow::Break(())
+            } else {
+                ControlFlow::Continue(())
+            }
+        })
+        .unwrap();
+    assert_eq!(summary.ticks_applied, 4);
+    assert_eq!(engine.tick(), 14);
+}
+
+#[test]
+fn step_budget_runs_at_least_one_tick_and_respects_the_cap() {
+    use std::time::Duration;
+
+    let bodies = vec![
+        Body::new("a", 1.0, 0.1, Vec2::new(-1.0, 0.0), Vec2::ZERO),
+        Body::new("b", 1.0, 0.1, Vec2::new(1.0, 0.0), Vec2::ZERO),
+    ];
+    let mut engine = SimulationEngine::with_bodies(base_config(), bodies).unwrap();
+
+    let summary = engine.step_budget(Duration::ZERO, 50).unwrap();
+    assert_eq!(summary.ticks_applied, 1);
+    let summary = engine.step_budget(Duration::from_secs(60), 25).unwrap();
+    assert_eq!(summary.ticks_applied, 25);
+    assert_eq!(engine.tick(), 26);
+    let summary = engine.step_budget(Duration::from_secs(60), 0).unwrap();
+    assert_eq!(summary.ticks_applied, 0);
+}
+
+#[test]
+fn ffi_step_observed_samples_every_nth_tick() {
+    use std::ffi::{CStr, CString};
+    use std::os::raw::c_void;
+
+    use gravity_engine::ffi::{gs_dispose, gs_initialize, gs_step_observed, gs_string_free};
+
+    extern "C" fn record(tick: u64, positions: *const f64, count: usize, data: *mut c_void) -> i32 {
+        let samples = unsafe { &mut *(data as *mut Vec<(u64, f64)>) };
+        let positions = unsafe { std::slice::from_raw_parts(positions, count * 2) };
+        samples.push((tick, positions[0]));
+        i32::from(tick >= 20)
+    }
+
+    let take = |ptr: *mut std::os::raw::c_char| {
+        let text = unsafe { CStr::from_ptr(ptr) }.to_str().unwrap().to_string();
+        gs_string_free(ptr);
+        serde_json::from_str::<serde_json::Value>(&text).unwrap()
+    };
+    let config = CString::new(serde_json::to_string(&base_config()).unwrap()).unwrap();
+    let bodies = serde_json::to_string(&vec![
+        Body::new("a", 1.0, 0.1, Vec2::new(-1.0, 0.0), Vec2::ZERO),
+        Body::new("b", 1.0, 0.1, Vec2::new(1.0, 0.0), Vec2::ZERO),
+    ])
+    .unwrap();
+    let bodies = CString::new(bodies).unwrap();
+    let handle = take(gs_initialize(config.as_ptr(), bodies.as_ptr()))["data"]["handle"]
+        .as_u64()
+        .unwrap();
+
+    let mut samples: Vec<(u64, f64)> = Vec::new();
+    let data = &mut samples as *mut Vec<(u64, f64)> as *mut c_void;
+    let response = take(gs_step_observed(handle, 7, 3, Some(record), data));
+    assert_eq!(response["data"]["summary"]["ticksApplied"], 7);
+    assert_eq!(
+        samples.iter().map(|(tick, _)| *tick).collect::<Vec<_>>(),
+        [3, 6, 7]
+    );
+    assert!(samples.windows(2).all(|pair| pair[1].1 > pair[0].1));
+
+    let response = take(gs_step_observed(handle, 100, 1, Some(record), data));
+    assert_eq!(response["data"]["summary"]["ticksApplied"], 13);
+    assert_eq!(samples.last().unwrap().0, 20);
+    assert_eq!(
+        take(gs_step_observed(handle, 1, 1, None, data))["ok"],
+        false
+    );
+
+    take(gs_dispose(handle));
+}
+
+#[test]
+fn ffi_summary_only_mode_drops_the_state_echo() {
+    use std::ffi::{CStr, CString};
+
+    use gravity_engine::ffi::{
+        gs_apply_edits, gs_dispose, gs_get_state, gs_initialize, gs_set_response_mode, gs_step,
+        gs_string_free,
+    };
+
+    let take = |ptr: *mut std::os::raw::c_char| {
+        let text = unsafe { CStr::from_ptr(ptr) }.to_str().unwrap().to_string();
+        gs_string_free(ptr);
+        serde_json::from_str::<serde_json::Value>(&text).unwrap()
+    };
+    let config = CString::new(serde_json::to_string(&base_config()).unwrap()).unwrap();
+    let bodies =
+        serde_json::to_string(&vec![Body::new("a", 1.0, 0.1, Vec2::ZERO, Vec2::ZERO)]).unwrap();
+    let bodies = CString::new(bodies).unwrap();
+    let handle = take(gs_initialize(config.as_ptr(), bodies.as_ptr()))["data"]["handle"]
+        .as_u64()
+        .unwrap();
+    assert!(take(gs_step(handle, 1))["data"]["state"].is_object());
+
+    let mode = |text: &str| {
+        let text = CString::new(text).unwrap();
+        take(gs_set_response_mode(handle, text.as_ptr()))
+    };
+    assert_eq!(
+        mode("\"summaryOnly\"")["data"]["responseMode"],
+        "summaryOnly"
+    );
+    assert_eq!(mode("\"terse\"")["ok"], false);
+    let step = take(gs_step(handle, 2));
+    assert_eq!(step["data"]["summary"]["ticksApplied"], 2);
+    assert!(step["data"]["state"].is_null());
+    let edits = serde_json::to_string(&vec![BodyEdit::Create(Body::new(
+        "b",
+        1.0,
+        0.1,
+        Vec2::new(5.0, 0.0),
+        Vec2::ZERO,
+    ))])
+    .unwrap();
+    let edits = CString::new(edits).unwrap();
+    let response = take(gs_apply_edits(handle, edits.as_ptr()));
+    assert_eq!(response["ok"], true);
+    assert!(response["data"]["state"].is_null());
+    let state = take(gs_get_state(handle));
+    assert_eq!(
+        state["data"]["state"]["bodies"].as_array().unwrap().len(),
+        2
+    );
+
+    mode("\"full\"");
+    assert!(take(gs_step(handle, 1))["data"]["state"].is_object());
+    take(gs_dispose(handle));
+}
+
+#[test]
+fn restitution_scales_the_rebound_speed() {
+    let config = EngineConfig {
+        collision_mode: CollisionMode::Elastic,
+        restitution: 0.5,
+        ..base_config()
+    };
+    let bodies = vec![
+        Body::new("a", 2e-6, 0.1, Vec2::new(-0.15, 0.0), Vec2::new(1.0, 0.0)),
+        Body::new("b", 1e-6, 0.1, Vec2::new(0.15, 0.0), Vec2::new(-1.0, 0.0)),
+    ];
+    let p0 = total_momentum(&bodies);
+    let mut engine = SimulationEngine::with_bodies(config.clone(), bodies).unwrap();
+    let summary = engine.step(100).unwrap();
+
+    assert_eq!(summary.collision_events, 1);
+    let [a, b] = engine.bodies() else {
+        unreachable!()
+    };
+    approx_eq((b.velocity - a.velocity).x, 1.0, 1e-4);
+    let p1 = total_momentum(engine.bodies());
+    approx_eq(p0.x, p1.x, 1e-15);
+
+    let invalid = EngineConfig {
+        restitution: 1.5,
+        ..config
+    };
+    assert!(invalid.validate().is_err());
+}
+
+#[test]
+fn tick_cost_estimate_calibrates_from_measured_steps() {
+    let bodies = (0..64)
+        .map(|index| {
+            Body::new(
+                format!("b{index}"),
+                1.0,
+                0.01,
+                Vec2::new(index as f64, (index * 7 % 13) as f64),
+                Vec2::ZERO,
+            )
+        })
+        .collect::<Vec<_>>();
+    let config = EngineConfig {
+        collision_mode: CollisionMode::Ignore,
+        gravity_solver: GravitySolver::Pairwise,
+        ..base_config()
+    };
+    let mut engine = SimulationEngine::with_bodies(config, bodies).unwrap();
+
+    let prior = engine.estimate_tick_cost();
+    assert!(!prior.calibrated);
+    assert_eq!(
+        (prior.body_count, prior.solver_mode.as_str()),
+        (64, "pairwise")
+    );
+    assert!(prior.micros_per_tick > 0.0);
+
+    engine.step(20).unwrap();
+    engine.step(20).unwrap();
+    let estimate = engine.estimate_tick_cost();
+    assert!(estimate.calibrated);
+    assert_eq!(estimate.samples, 2);
+    assert!(estimate.micros_per_tick.is_finite() && estimate.micros_per_tick > 0.0);
+
+    for index in 32..64 {
+        engine
+            .apply_edit(BodyEdit::Delete {
+                id: format!("b{index}"),
+            })
+            .unwrap();
+    }
+    let smaller = engine.estimate_tick_cost();
+    assert!((smaller.micros_per_tick * 4.0 - estimate.micros_per_tick).abs() < 1e-9);
+}
+
+#[test]
+fn barnes_hut_with_tiny_theta_matches_pairwise_on_deep_clusters() {
+    // Stays above the tree's minimum cell size, below which bodies share one leaf.
+    let mut bodies = (0..16)
+        .map(|level| {
+            let offset = 0.5_f64.powi(level);
+            Body::new(
+                format!("nested{level}"),
+                1.0,
+                1e-12,
+                Vec2::new(offset, offset * 0.5),
+                Vec2::ZERO,
+            )
+        })
+        .collect::<Vec<_>>();
+    bodies.push(Body::new(
+        "twin_a",
+        1.0,
+        1e-12,
+        Vec2::new(-3.0, 2.0),
+        Vec2::ZERO,
+    ));
+    bodies.push(Body::new(
+        "twin_b",
+        1.0,
+        1e-12,
+        Vec2::new(-3.0, 2.0),
+        Vec2::ZERO,
+    ));
+
+    let run = |gravity_solver| {
+        let config = EngineConfig {
+            gravity_solver,
+            barnes_hut_theta: 1e-9,
+            integrator: IntegratorKind::SemiImplicitEuler,
+            softening_epsilon: 1e-3,
+            ..base_config()
+        };
+        let mut engine = SimulationEngine::with_bodies(config, bodies.clone()).unwrap();
+        engine.step(1).unwrap();
+        engine.bodies().to_vec()
+    };
+    let pairwise = run(GravitySolver::Pairwise);
+    let barnes_hut = run(GravitySolver::BarnesHut);
+    for (exact, approx) in pairwise.iter().zip(&barnes_hut) {
+        let error = (exact.velocity - approx.velocity).norm();
+        assert!(
+            error <= 1e-12 * exact.velocity.norm().max(1e-12),
+            "{}",
+            exact.id
+        );
+    }
+}
+
+#[cfg(feature = "simd")]
+#[test]
+fn simd_pairwise_matches_scalar_pairwise() {
+    let bodies = (0..37)
+        .map(|index| {
+            let angle = index as f64 * 0.7;
+            Body::new(
+                format!("b{index}"),
+                1.0 + index as f64 * 0.1,
+                0.01,
+                Vec2::new(angle.cos(), angle.sin()) * (1.0 + index as f64 * 0.3),
+                Vec2::new(-angle.sin(), angle.cos()),
+            )
+        })
+        .collect::<Vec<_>>();
+    let run = |gravity_solver| {
+        let config = EngineConfig {
+            gravity_solver,
+            softening_epsilon: 0.0,
+            ..base_config()
+        };
+        let mut engine = SimulationEngine::with_bodies(config, bodies.clone()).unwrap();
+        engine.step(50).unwrap();
+        engine.bodies().to_vec()
+    };
+    let scalar = run(GravitySolver::Pairwise);
+    let simd = run(GravitySolver::PairwiseSimd);
+    for (exact, vectorized) in scalar.iter().zip(&simd) {
+        assert!((exact.position - vectorized.position).norm() < 1e-10);
+    }
+}
+
+#[cfg(not(feature = "simd"))]
+#[test]
+fn simd_pairwise_requires_the_feature() {
+    let config = EngineConfig {
+        gravity_solver: GravitySolver::PairwiseSimd,
+        ..base_config()
+    };
+    assert!(SimulationEngine::with_bodies(config, Vec::new()).is_err());
+}
+
+#[test]
+fn quantized_time_agrees_across_dt_and_survives_snapshots() {
+    let run = |dt: f64, ticks: u32, time_quantum: Option<f64>| {
+        let config = EngineConfig {
+            dt,
+            time_quantum,
+            ..base_config()
+        };
+        let mut engine = SimulationEngine::with_bodies(config, Vec::new()).unwrap();
+        engine.step(ticks).unwrap();
+        engine
+    };
+    assert_ne!(run(1e-3, 1000, None).sim_time(), 1.0);
+
+    let fine = run(1e-3, 1000, Some(1e-3));
+    let coarse = run(2e-3, 500, Some(1e-3));
+    assert_eq!(fine.sim_time(), coarse.sim_time());
+    assert_eq!(fine.clock().unwrap().quanta, 1000);
+    assert_eq!(fine.clock().unwrap().residual, 0.0);
+
+    let mut odd = run(0.7e-3, 3, Some(1e-3));
+    let clock = odd.clock().unwrap();
+    assert_eq!(clock.quanta, 2);
+    assert!((clock.residual - 0.1e-3).abs() < 1e-15);
+    let snapshot = odd.snapshot();
+    odd.step(5).unwrap();
+    odd.restore_snapshot(snapshot).unwrap();
+    assert_eq!(odd.clock(), Some(clock));
+}
+
+#[test]
+fn checkpoints_restore_state_and_config_and_evict_oldest() {
+    let bodies = vec![
+        Body::new("sun", 1.0, 0.01, Vec2::ZERO, Vec2::ZERO),
+        Body::new(
+            "planet",
+            1e-3,
+            0.01,
+            Vec2::new(1.0, 0.0),
+            Vec2::new(0.0, 1.0),
+        ),
+    ];
+    let mut engine = SimulationEngine::with_bodies(base_config(), bodies).unwrap();
+    engine.step(10).unwrap();
+    engine.create_checkpoint("before_flyby").unwrap();
+    let saved = engine.get_state();
+
+    engine.step(25).unwrap();
+    engine
+        .set_config(EngineConfig {
+            dt: 0.002,
+            ..base_config()
+        })
+        .unwrap();
+    engine.create_checkpoint("after").unwrap();
+    engine.restore_checkpoint("before_flyby").unwrap();
+    assert_eq!(engine.get_state(), saved);
+
+    let names = |engine: &SimulationEngine| {
+        engine
+            .list_checkpoints()
+            .into_iter()
+            .map(|info| info.name)
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(names(&engine), ["before_flyby", "after"]);
+    engine.set_checkpoint_capacity(1);
+    assert_eq!(names(&engine), ["after"]);
+    assert!(matches!(
+        engine.restore_checkpoint("before_flyby"),
+        Err(EngineError::CheckpointNotFound(_))
+    ));
+    assert!(engine.create_checkpoint(" ").is_err());
+}
+
+#[test]
+fn markers_follow_snapshots_checkpoints_and_rewinds() {
+    let bodies = vec![
+        Body::new("sun", 1.0, 0.01, Vec2::ZERO, Vec2::ZERO),
+        Body::new(
+            "planet",
+            1e-3,
+            0.01,
+            Vec2::new(1.0, 0.0),
+            Vec2::new(0.0, 1.0),
+        ),
+    ];
+    let mut engine = SimulationEngine::with_bodies(base_config(), bodies).unwrap();
+    engine.step(10).unwrap();
+    let marker = engine.add_marker("tweaked dt here").unwrap().clone();
+    assert_eq!(marker.tick, 10);
+    approx_eq(marker.sim_time, 0.01, 1e-12);
+    assert!(engine.add_marker("  ").is_err());
+    engine.create_checkpoint("ten").unwrap();
+    let snapshot = engine.snapshot();
+
+    engine.step(20).unwrap();
+    engine.add_marker("flyby").unwrap();
+    engine.step(20).unwrap();
+    engine.add_marker("tweaked dt here").unwrap();
+    assert_eq!(engine.markers().len(), 3);
+    assert_eq!(
+        engine
+            .markers_between(0, 30)
+            .map(|marker| marker.name.as_str())
+            .collect::<Vec<_>>(),
+        ["tweaked dt here", "flyby"]
+    );
+
+    assert_eq!(engine.step_back(15).unwrap(), RewindMethod::Reversed);
+    assert_eq!(engine.markers().len(), 2);
+
+    engine.restore_snapshot(snapshot.clone()).unwrap();
+    assert_eq!(engine.markers(), std::slice::from_ref(&marker));
+    engine.step(5).unwrap();
+    engine.add_marker("replayed").unwrap();
+    engine.restore_checkpoint("ten").unwrap();
+    assert_eq!(engine.markers(), [marker]);
+
+    engine.step(5).unwrap();
+    engine.add_marker("again").unwrap();
+    assert_eq!(engine.remove_markers("tweaked dt here"), 1);
+    assert_eq!(engine.markers()[0].name, "again");
+    let restored = SimulationEngine::initialize(base_config())
+        .and_then(|mut other| {
+            other.restore_snapshot_binary(&engine.snapshot_binary())?;
+            Ok(other)
+        })
+        .unwrap();
+    assert_eq!(restored.markers(), engine.markers());
+}
+
+#[test]
+fn step_back_reverses_verlet_and_replays_from_checkpoints_otherwise() {
+    let bodies = vec![
+        Body::new("sun", 1.0, 0.01, Vec2::ZERO, Vec2::ZERO),
+        Body::new(
+            "planet",
+            1e-3,
+            0.01,
+            Vec2::new(1.0, 0.0),
+            Vec2::new(0.0, 1.0),
+        ),
+        Body::new("moon", 1e-6, 0.01, Vec2::new(1.1, 0.0), Vec2::new(0.0, 1.3)),
+    ];
+    let mut spinning = bodies.clone();
+    spinning[1].angular_velocity = 3.0;
+    let mut engine = SimulationEngine::with_bodies(base_config(), spinning).unwrap();
+    engine.step(40).unwrap();
+    let earlier = engine.get_state();
+    engine.step(60).unwrap();
+    assert_eq!(engine.step_back(60).unwrap(), RewindMethod::Reversed);
+    assert_eq!(engine.tick(), 40);
+    for (rewound, original) in engine.bodies().iter().zip(&earlier.bodies) {
+        assert!((rewound.position - original.position).norm() < 1e-10);
+        assert!((rewound.velocity - original.velocity).norm() < 1e-10);
+        assert!((rewound.angle - original.angle).abs() < 1e-10);
+    }
+    assert!(earlier.bodies[1].angle > 0.1);
+
+    let config = EngineConfig {
+        integrator: IntegratorKind::Rk4,
+        ..base_config()
+    };
+    let mut engine = SimulationEngine::with_bodies(config, bodies).unwrap();
+    engine.step(10).unwrap();
+    engine.create_checkpoint("start").unwrap();
+    engine.step(30).unwrap();
+    let earlier = engine.get_state();
+    engine.step(20).unwrap();
+    assert_eq!(engine.step_back(20).unwrap(), RewindMethod::Replayed);
+    assert_eq!(engine.get_state(), earlier);
+    assert!(engine.step_back(45).is_err());
+    assert!(engine.step_back(1000).is_err());
+}
+
+#[test]
+fn step_back_across_a_burn_replays_it_from_a_checkpoint() {
+    let bodies = vec![
+        Body::new("sun", 1.0, 0.01, Vec2::ZERO, Vec2::ZERO),
+        Body::new("ship", 1e-6, 0.01, Vec2::new(1.0, 0.0), Vec2::new(0.0, 1.0)),
+    ];
+    let mut engine = SimulationEngine::with_bodies(base_config(), bodies).unwrap();
+    engine.create_checkpoint("start").unwrap();
+    engine
+        .schedule_maneuver(
+            "ship",
+            Maneuver {
+                start_time: 0.0205,
+                duration: 0.01,
+                thrust: Thrust::Acceleration(Vec2::new(5.0, 0.0)),
+            },
+        )
+        .unwrap();
+    engine.step(10).unwrap();
+    let before_burn = engine.get_state();
+    engine.step(40).unwrap();
+    assert!(engine.maneuvers().is_empty());
+
+    // The burn is over and pruned, yet the rewind must not integrate back through it.
+    assert_eq!(engine.step_back(40).unwrap(), RewindMethod::Replayed);
+    assert_eq!(engine.get_state(), before_burn);
+    assert_eq!(engine.maneuvers().len(), 1);
+    engine.step(40).unwrap();
+    let after_burn = engine.get_state();
+    engine.step(10).unwrap();
+    assert_eq!(engine.step_back(10).unwrap(), RewindMethod::Reversed);
+    for (rewound, original) in engine.bodies().iter().zip(&after_burn.bodies) {
+        assert!((rewound.position - original.position).norm() < 1e-10);
+    }
+
+    engine.delete_checkpoint("start");
+    assert!(engine.step_back(30).is_err());
+}
+
+/// Verlet with fixed dt and no collisions, which would otherwise reverse, must replay
+/// from the checkpoint when `config` edits bodies after integrating.
+fn assert_step_back_replays(config: EngineConfig) {
+    let bodies = vec![
+        Body::new("sun", 1.0, 0.01, Vec2::ZERO, Vec2::ZERO),
+        Body::new(
+            "planet",
+            1e-3,
+            0.01,
+            Vec2::new(1.0, 0.0),
+            Vec2::new(0.0, 1.0),
+        ),
+    ];
+    let mut engine = SimulationEngine::with_bodies(config, bodies).unwrap();
+    engine.create_checkpoint("start").unwrap();
+    engine.step(10).unwrap();
+    let earlier = engine.get_state();
+    engine.step(10).unwrap();
+    assert_eq!(engine.step_back(10).unwrap(), RewindMethod::Replayed);
+    assert_eq!(engine.get_state(), earlier);
+}
+
+#[test]
+fn step_back_replays_with_a_boundary() {
+    for mode in [
+        BoundaryMode::Reflect,
+        BoundaryMode::Absorb,
+        BoundaryMode::Periodic,
+    ] {
+        assert_step_back_replays(EngineConfig {
+            boundary: Some(WorldBoundary {
+                min: Vec2::new(-5.0, -5.0),
+                max: Vec2::new(5.0, 5.0),
+                mode,
+            }),
+            ..base_config()
+        });
+    }
+}
+
+#[test]
+fn step_back_replays_when_removing_escapers() {
+    assert_step_back_replays(EngineConfig {
+        remove_escapers: Some(EscapePolicy {
+            radius: 100.0,
+            min_speed: 0.0,
+        }),
+        ..base_config()
+    });
+}
+
+#[test]
+fn step_back_replays_with_tidal_disruption() {
+    assert_step_back_replays(EngineConfig {
+        tidal_disruption: Some(TidalDisruption::default()),
+        ..base_config()
+    });
+}
+
+#[test]
+fn step_back_replays_with_coarse_graining() {
+    assert_step_back_replays(EngineConfig {
+        coarse_graining: Some(CoarseGraining {
+            cell_size: 1.0,
+            max_body_mass: 1e-6,
+            focus: Vec2::ZERO,
+            focus_radius: 10.0,
+            interval_ticks: 5,
+            min_members: 2,
+        }),
+        ..base_config()
+    });
+}
+
+#[test]
+fn step_back_replays_with_a_correcting_angular_momentum_guard() {
+    assert_step_back_replays(EngineConfig {
+        angular_momentum_guard: Some(AngularMomentumGuard {
+            interval_ticks: 1,
+            tolerance: 0.0,
+            correct: true,
+        }),
+        ..base_config()
+    });
+}
+
+#[test]
+fn step_back_replays_when_recentering_every_tick() {
+    assert_step_back_replays(EngineConfig {
+        recenter: Some(Frame::Body("planet".to_string())),
+        ..base_config()
+    });
+}
+
+#[test]
+fn update_config_patches_fields_and_reports_diff() {
+    let bodies = vec![
+        Body::new("a", 1.0, 0.01, Vec2::new(-0.5, 0.0), Vec2::new(0.0, -0.5)),
+        Body::new("b", 1.0, 0.01, Vec2::new(0.5, 0.0), Vec2::new(0.0, 0.5)),
+    ];
+    let mut engine = SimulationEngine::with_bodies(base_config(), bodies).unwrap();
+
+    let diff = engine
+        .update_config(&ConfigPatch::default().set("includeDiagnostics", true))
+        .unwrap();
+    assert_eq!(diff.changes.len(), 1);
+    assert!(!diff.changes[0].affects_replay);
+    assert!(diff.warnings.is_empty());
+    assert!(engine.config().include_diagnostics);
+
+    let unchanged = engine
+        .update_config(&ConfigPatch::default().set("dt", 0.001))
+        .unwrap();
+    assert!(unchanged.is_empty());
+
+    engine.step(10).unwrap();
+    let diff = engine
+        .update_config(
+            &ConfigPatch::default()
+                .set("integrator", IntegratorKind::Rk4)
+                .set("dt", 0.002),
+        )
+        .unwrap();
+    assert!(diff.changed("integrator") && diff.changed("dt"));
+    assert_eq!(diff.changes[1].before, serde_json::json!("velocityVerlet"));
+    assert_eq!(diff.warnings.len(), 2);
+    assert!(diff.warnings[1].contains("integrator"));
+    assert_eq!(engine.config().integrator, IntegratorKind::Rk4);
+    assert_eq!(engine.config().dt, 0.002);
+    assert!(engine.config().include_diagnostics);
+
+    let before = engine.config().clone();
+    for patch in [
+        ConfigPatch::default().set("noSuchField", 1),
+        ConfigPatch::default().set("dt", -1.0),
+        ConfigPatch::default().set("integrator", "leapfrog"),
+    ] {
+        assert!(matches!(
+            engine.update_config(&patch),
+            Err(EngineError::InvalidConfig(_))
+        ));
+    }
+    assert_eq!(engine.config(), &before);
+}
+
+#[test]
+fn theta_tuning_moves_theta_towards_its_target_within_bounds() {
+    let bodies = (0..300)
+        .map(|index| {
+            let angle = index as f64 * 2.399;
+            let position = Vec2::from_angle(angle) * (1.0 + index as f64).sqrt();
+            Body::new(format!("b{index}"), 1.0, 0.01, position, Vec2::ZERO)
+        })
+        .collect::<Vec<_>>();
+    // Wall-clock targets make runs diverge, so they need non-deterministic mode.
+    let tuned = |target: ThetaTarget| EngineConfig {
+        deterministic: !matches!(target, ThetaTarget::TickMicros { .. }),
+        gravity_solver: GravitySolver::BarnesHut,
+        barnes_hut_theta: 1.0,
+        theta_tuning: Some(ThetaTuning {
+            target,
+            interval_ticks: 2,
+            min_theta: 0.5,
+            max_theta: 1.2,
+        }),
+        ..base_config()
+    };
+
+    let mut accurate = SimulationEngine::with_bodies(
+        tuned(ThetaTarget::ForceError {
+            rms: 1e-4,
+            sample_size: 32,
+        }),
+        bodies.clone(),
+    )
+    .unwrap();
+    let summary = accurate.step(2).unwrap();
+    let first = summary.barnes_hut_theta.unwrap();
+    assert!((first - 1.0 / 1.1).abs() < 1e-12, "{first}");
+    let summary = accurate.step(40).unwrap();
+    assert_eq!(summary.barnes_hut_theta, Some(0.5));
+    assert_eq!(accurate.config().barnes_hut_theta, 0.5);
+
+    // No tick can finish within a microsecond, so theta widens to its cap.
+    let mut fast =
+        SimulationEngine::with_bodies(tuned(ThetaTarget::TickMicros { micros: 1 }), bodies.clone())
+            .unwrap();
+    assert_eq!(fast.step(10).unwrap().barnes_hut_theta, Some(1.2));
+    let mut slow = SimulationEngine::with_bodies(
+        tuned(ThetaTarget::TickMicros { micros: 60_000_000 }),
+        bodies.clone(),
+    )
+    .unwrap();
+    assert_eq!(slow.step(20).unwrap().barnes_hut_theta, Some(0.5));
+
+    let untuned = SimulationEngine::with_bodies(base_config(), bodies.clone())
+        .unwrap()
+        .step(2)
+        .unwrap();
+    assert_eq!(untuned.barnes_hut_theta, None);
+    let inverted = EngineConfig {
+        theta_tuning: Some(ThetaTuning {
+            min_theta: 1.0,
+            max_theta: 0.5,
+            ..tuned(ThetaTarget::TickMicros { micros: 1 })
+                .theta_tuning
+                .unwrap()
+        }),
+        ..base_config()
+    };
+    let Err(EngineError::InvalidConfig(message)) = inverted.validate() else {
+        panic!("inverted theta range must be rejected");
+    };
+    assert_eq!(
+        message,
+        "theta_tuning needs interval_ticks >= 1, a positive target and 0 < min_theta <= \
+         max_theta <= 2"
+    );
+    let deterministic = EngineConfig {
+        deterministic: true,
+        ..tuned(ThetaTarget::TickMicros { micros: 1 })
+    };
+    assert!(matches!(
+        deterministic.validate(),
+        Err(EngineError::InvalidConfig(message)) if message.contains("deterministic mode")
+    ));
+}
+
+#[test]
+fn barnes_hut_error_shrinks_with_theta_and_is_sampled_periodically() {
+    let bodies = (0..300)
+        .map(|index| {
+            let angle = index as f64 * 2.399;
+            let position = Vec2::from_angle(angle) * (1.0 + index as f64).sqrt();
+            Body::new(format!("b{index}"), 1.0, 0.01, position, Vec2::ZERO)
+        })
+        .collect::<Vec<_>>();
+    let config = EngineConfig {
+        force_error_sampling: Some(ForceErrorSampling {
+            interval_ticks: 5,
+            sample_size: 40,
+            ..ForceErrorSampling::default()
+        }),
+        ..base_config()
+    };
+    let mut engine = SimulationEngine::with_bodies(config, bodies).unwrap();
+
+    let at = |theta: f64| ForceErrorSampling {
+        sample_size: 1000,
+        theta: Some(theta),
+        ..ForceErrorSampling::default()
+    };
+    let coarse = engine.barnes_hut_error(&at(1.2)).unwrap();
+    let fine = engine.barnes_hut_error(&at(0.3)).unwrap();
+    assert_eq!(coarse.sampled, 300);
+    assert!(fine.mean < coarse.mean && fine.p95 < coarse.p95);
+    assert!(coarse.mean <= coarse.p95 && coarse.p95 <= coarse.max);
+    assert!(coarse.mean <= coarse.rms && coarse.rms <= coarse.max);
+    assert!(fine.rms < coarse.rms);
+    assert!(fine.max < 0.05, "{fine:?}");
+    assert_eq!(engine.barnes_hut_error(&at(0.3)).unwrap(), fine);
+
+    let summary = engine.step(12).unwrap();
+    let sampled = summary.force_error.unwrap();
+    assert_eq!((sampled.sampled, sampled.theta), (40, 0.6));
+    assert!(matches!(
+        engine.barnes_hut_error(&ForceErrorSampling {
+            theta: Some(3.0),
+            ..ForceErrorSampling::default()
+        }),
+        Err(EngineError::InvalidConfig(_))
+    ));
+}
+
+#[test]
+fn maneuvers_deliver_their_delta_v_and_are_dropped_when_done() {
+    let bodies = vec![
+        Body::new("ship", 1.0, 0.01, Vec2::ZERO, Vec2::ZERO),
+        Body::new("far", 1e-9, 0.01, Vec2::new(1e6, 0.0), Vec2::ZERO),
+    ];
+    let mut engine = SimulationEngine::with_bodies(base_config(), bodies).unwrap();
+    let kick = engine
+        .schedule_maneuver(
+            "ship",
+            Maneuver {
+                start_time: 0.0105,
+                duration: 0.0,
+                thrust: Thrust::DeltaV(Vec2::new(1.0, 0.0)),
+            },
+        )
+        .unwrap();
+    let burn = engine
+        .schedule_maneuver(
+            "ship",
+            Maneuver {
+                start_time: 0.005,
+                duration: 0.01,
+                thrust: Thrust::Acceleration(Vec2::new(0.0, 2.0)),
+            },
+        )
+        .unwrap();
+    assert_ne!(kick, burn);
+    assert_eq!(engine.maneuvers().len(), 2);
+
+    engine.step(10).unwrap();
+    let ship = &engine.bodies()[0];
+    approx_eq(ship.velocity.x, 0.0, 1e-12);
+    approx_eq(ship.velocity.y, 0.01, 1e-9);
+
+    engine.step(10).unwrap();
+    let ship = &engine.bodies()[0];
+    approx_eq(ship.velocity.x, 1.0, 1e-9);
+    approx_eq(ship.velocity.y, 0.02, 1e-9);
+    assert!(engine.maneuvers().is_empty());
+
+    let pending = engine
+        .schedule_maneuver(
+            "ship",
+            Maneuver {
+                start_time: 1.0,
+                duration: 0.5,
+                thrust: Thrust::DeltaV(Vec2::new(0.0, -1.0)),
+            },
+        )
+        .unwrap();
+    assert!(engine.cancel_maneuver(pending));
+    assert!(!engine.cancel_maneuver(pending));
+    engine.step(5).unwrap();
+    approx_eq(engine.bodies()[0].velocity.y, 0.02, 1e-9);
+
+    let mut schedule =
+        |body_id: &str, maneuver: Maneuver| engine.schedule_maneuver(body_id, maneuver);
+    assert!(matches!(
+        schedule(
+            "ship",
+            Maneuver {
+                start_time: 1.0,
+                duration: 0.0,
+                thrust: Thrust::Acceleration(Vec2::new(1.0, 0.0)),
+            }
+        ),
+        Err(EngineError::InvalidConfig(_))
+    ));
+    assert!(matches!(
+        schedule(
+            "ship",
+            Maneuver {
+                start_time: 0.0,
+                duration: 0.001,
+                thrust: Thrust::DeltaV(Vec2::new(1.0, 0.0)),
+            }
+        ),
+        Err(EngineError::InvalidConfig(_))
+    ));
+    assert!(matches!(
+        schedule(
+            "ghost",
+            Maneuver {
+                start_time: 1.0,
+                duration: 0.0,
+                thrust: Thrust::DeltaV(Vec2::new(1.0, 0.0)),
+            }
+        ),
+        Err(EngineError::BodyNotFound(_))
+    ));
+}
+
+#[test]
+fn predict_previews_edits_without_touching_the_engine() {
+    let bodies = vec![
+        Body::new("sun", 1.0, 0.05, Vec2::ZERO, Vec2::ZERO),
+        Body::new(
+            "planet",
+            1e-6,
+            0.01,
+            Vec2::new(1.0, 0.0),
+            Vec2::new(0.0, 1.0),
+        ),
+    ];
+    let mut engine = SimulationEngine::with_bodies(base_config(), bodies).unwrap();
+    let before = engine.get_state();
+
+    let samples = engine.predict(Vec::new(), 25, 10).unwrap();
+    assert_eq!(engine.get_state(), before);
+    let ticks = samples.iter().map(|sample| sample.tick).collect::<Vec<_>>();
+    assert_eq!(ticks, [0, 0, 10, 10, 20, 20, 25, 25]);
+    engine.step(25).unwrap();
+    let planet = samples.last().unwrap();
+    assert_eq!(planet.body_id, "planet");
+    assert_eq!(planet.position, engine.bodies()[1].position);
+    assert_eq!(planet.sim_time, engine.get_state().sim_time);
+
+    let boosted = engine
+        .predict(
+            vec![BodyEdit::Update(BodyUpdate {
+                id: "planet".to_string(),
+                velocity: Some(Vec2::new(0.0, 1.2)),
+                ..BodyUpdate::default()
+            })],
+            10,
+            10,
+        )
+        .unwrap();
+    assert_eq!(boosted[1].velocity, Vec2::new(0.0, 1.2));
+    assert!(
+        boosted[3].position.norm()
+            > engine.predict(Vec::new(), 10, 10).unwrap()[3]
+                .position
+                .norm()
+    );
+    assert!(engine.bodies()[1].velocity.y < 1.1);
+    assert!(matches!(
+        engine.predict(Vec::new(), 10, 0),
+        Err(EngineError::InvalidConfig(_))
+    ));
+}
+
+#[test]
+fn predict_mid_run_matches_stepping_through_a_scheduled_burn() {
+    let config = EngineConfig {
+        time_quantum: Some(0.0005),
+        ..base_config()
+    };
+    let bodies = vec![
+        Body::new("sun", 1.0, 0.05, Vec2::ZERO, Vec2::ZERO),
+        Body::new(
+            "planet",
+            1e-6,
+            0.01,
+            Vec2::new(1.0, 0.0),
+            Vec2::new(0.0, 1.0),
+        ),
+    ];
+    let mut engine = SimulationEngine::with_bodies(config, bodies).unwrap();
+    engine.step(7).unwrap();
+    engine
+        .schedule_maneuver(
+            "planet",
+            Maneuver {
+                start_time: 0.0105,
+                duration: 0.005,
+                thrust: Thrust::Acceleration(Vec2::new(0.0, 3.0)),
+            },
+        )
+        .unwrap();
+
+    let samples = engine.predict(Vec::new(), 20, 20).unwrap();
+    engine.step(20).unwrap();
+    let planet = samples.last().unwrap();
+    assert_eq!(planet.tick, 27);
+    assert_eq!(planet.position, engine.bodies()[1].position);
+    assert_eq!(planet.velocity, engine.bodies()[1].velocity);
+}
+
+#[test]
+fn lyapunov_exponent_depends_only_on_the_current_bodies() {
+    let bodies = vec![
+        Body::new("sun", 1.0, 0.01, Vec2::ZERO, Vec2::ZERO),
+        Body::new("a", 1e-6, 0.01, Vec2::new(1.0, 0.0), Vec2::new(0.0, 1.0)),
+    ];
+    let mut engine = SimulationEngine::with_bodies(base_config(), bodies).unwrap();
+    engine.create_checkpoint("start").unwrap();
+    engine.step(50).unwrap();
+    let before = engine.get_state();
+    let fresh = SimulationEngine::with_bodies(base_config(), engine.bodies().to_vec()).unwrap();
+    let query = LyapunovQuery {
+        body_id: "a".to_string(),
+        ticks: 200,
+        perturbation: 1e-8,
+        renormalize_every: 25,
+    };
+
+    let estimate = engine.lyapunov_exponent(&query).unwrap();
+    let expected = fresh.lyapunov_exponent(&query).unwrap();
+    assert_eq!(estimate.renormalizations, expected.renormalizations);
+    approx_eq(estimate.exponent, expected.exponent, 1e-12);
+    approx_eq(estimate.elapsed_time, expected.elapsed_time, 1e-12);
+    assert_eq!(engine.get_state(), before);
+}
+
+#[test]
+fn lyapunov_exponent_separates_chaotic_from_regular_motion() {
+    let config = EngineConfig {
+        softening_epsilon: 1e-3,
+        dt: 0.002,
+        ..base_config()
+    };
+    let sun = Body::new("sun", 1.0, 0.01, Vec2::ZERO, Vec2::ZERO);
+    let kepler = SimulationEngine::with_bodies(
+        config.clone(),
+        vec![
+            sun.clone(),
+            Body::new("a", 1e-6, 0.01, Vec2::new(1.0, 0.0), Vec2::new(0.0, 1.0)),
+        ],
+    )
+    .unwrap();
+    // A test particle whose orbit crosses the path of a Jupiter-like planet.
+    let crossing = SimulationEngine::with_bodies(
+        config,
+        vec![
+            Body {
+                mass: 0.99,
+                position: Vec2::new(-0.01, 0.0),
+                velocity: Vec2::new(0.0, -0.01),
+                ..sun
+            },
+            Body::new(
+                "jupiter",
+                0.01,
+                0.01,
+                Vec2::new(0.99, 0.0),
+                Vec2::new(0.0, 0.99),
+            ),
+            Body::new(
+                "a",
+                1e-9,
+                0.01,
+                Vec2::new(0.0, 1.3),
+                Vec2::new(-1.3_f64.sqrt().recip(), 0.0),
+            ),
+        ],
+    )
+    .unwrap();
+    let query = LyapunovQuery {
+        body_id: "a".to_string(),
+        ticks: 100_000,
+        perturbation: 1e-8,
+        renormalize_every: 25,
+    };
+    let regular = kepler.lyapunov_exponent(&query).unwrap();
+    let chaotic = crossing.lyapunov_exponent(&query).unwrap();
+    assert!(regular.complete && chaotic.complete);
+    assert_eq!(regular.renormalizations, 4000);
+    assert_eq!(regular.history.len(), 4000);
+    assert!((regular.elapsed_time - 200.0).abs() < 1e-9);
+    // Shear makes a regular orbit's separation grow linearly, so its estimate decays.
+    assert!(regular.exponent < 0.5 * regular.history[999]);
+    assert!(
+        chaotic.exponent > 5.0 * regular.exponent,
+        "{} vs {}",
+        chaotic.exponent,
+        regular.exponent
+    );
+    assert_eq!(kepler.get_state().tick, 0);
+    assert!(matches!(
+        kepler.lyapunov_exponent(&LyapunovQuery {
+            body_id: "comet".to_string(),
+            ..query.clone()
+        }),
+        Err(EngineError::BodyNotFound(_))
+    ));
+    assert!(
+        kepler
+            .lyapunov_exponent(&LyapunovQuery {
+                renormalize_every: 0,
+                ..query
+            })
+            .is_err()
+    );
+}
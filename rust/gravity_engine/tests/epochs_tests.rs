@@ -0,0 +1,148 @@
+use gravity_engine::{
+    Body, CollisionMode, ConfigPatch, EngineConfig, EngineError, Epoch, IntegratorKind,
+    RewindMethod, SimulationEngine, SimulationEvent, Vec2,
+};
+
+fn base_config() -> EngineConfig {
+    EngineConfig {
+        gravity_constant: 1.0,
+        dt: 0.01,
+        ..EngineConfig::default()
+    }
+}
+
+fn staged_engine() -> SimulationEngine {
+    let bodies = vec![
+        Body::new("sun", 1.0, 0.01, Vec2::ZERO, Vec2::ZERO),
+        Body::new(
+            "planet",
+            1e-3,
+            0.01,
+            Vec2::new(1.0, 0.0),
+            Vec2::new(0.0, 1.0),
+        ),
+    ];
+    let mut scenario = SimulationEngine::with_bodies(base_config(), bodies)
+        .unwrap()
+        .save_scenario();
+    scenario.epochs = vec![
+        Epoch {
+            name: "gas disc".to_string(),
+            start_tick: 0,
+            end_tick: Some(50),
+            overlay: ConfigPatch::default().set("dt", 0.02),
+        },
+        Epoch {
+            name: "late".to_string(),
+            start_tick: 100,
+            end_tick: None,
+            overlay: ConfigPatch::default().set("integrator", IntegratorKind::Rk4),
+        },
+    ];
+    scenario.checksum = Some(scenario.compute_checksum());
+    let mut engine = SimulationEngine::initialize(base_config()).unwrap();
+    engine.load_scenario(scenario).unwrap();
+    engine
+}
+
+#[test]
+fn epochs_switch_config_with_events_at_transitions() {
+    let mut engine = staged_engine();
+    engine.step(20).unwrap();
+    assert_eq!(engine.active_epoch().unwrap().name, "gas disc");
+    assert_eq!(engine.config().dt, 0.02);
+    let early = engine.snapshot();
+
+    let summary = engine.step(130).unwrap();
+    assert!(summary.stop_reason.is_none());
+    assert!((engine.sim_time() - (50.0 * 0.02 + 100.0 * 0.01)).abs() < 1e-12);
+    assert_eq!(engine.config().integrator, IntegratorKind::Rk4);
+    assert_eq!(engine.config().dt, 0.01);
+    let transitions = engine
+        .drain_events()
+        .into_iter()
+        .filter_map(|event| match event {
+            SimulationEvent::EpochChanged(event) => Some((event.tick, event.from, event.to)),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        transitions,
+        [
+            (0, None, Some("gas disc".to_string())),
+            (50, Some("gas disc".to_string()), None),
+            (100, None, Some("late".to_string())),
+        ]
+    );
+
+    // The base config saves alongside the epochs, so a reload replays identically.
+    let scenario = engine.save_scenario();
+    assert_eq!(scenario.engine_config, base_config());
+    let mut reloaded = SimulationEngine::initialize(base_config()).unwrap();
+    reloaded
+        .load_scenario_binary(&engine.save_scenario_binary())
+        .unwrap();
+    assert_eq!(reloaded.epochs(), scenario.epochs.as_slice());
+
+    engine.restore_snapshot(early).unwrap();
+    assert_eq!(engine.active_epoch().unwrap().name, "gas disc");
+    assert_eq!(engine.config().dt, 0.02);
+    assert_eq!(engine.config().integrator, IntegratorKind::VelocityVerlet);
+
+    // Host config changes replace the base; the epoch overlay still applies.
+    engine
+        .set_config(EngineConfig {
+            softening_epsilon: 0.1,
+            ..base_config()
+        })
+        .unwrap();
+    assert_eq!(engine.config().dt, 0.02);
+    assert_eq!(engine.config().softening_epsilon, 0.1);
+}
+
+#[test]
+fn step_back_across_an_epoch_boundary_replays_with_each_epochs_config() {
+    let mut engine = staged_engine();
+    engine
+        .set_config(EngineConfig {
+            collision_mode: CollisionMode::Ignore,
+            ..base_config()
+        })
+        .unwrap();
+    engine.create_checkpoint("start").unwrap();
+    engine.step(30).unwrap();
+    let earlier = engine.get_state();
+    engine.step(30).unwrap();
+    assert_eq!(engine.step_back(5).unwrap(), RewindMethod::Reversed);
+    assert_eq!(engine.step_back(25).unwrap(), RewindMethod::Replayed);
+    assert_eq!(engine.get_state(), earlier);
+    assert_eq!(engine.active_epoch().unwrap().name, "gas disc");
+}
+
+#[test]
+fn load_scenario_rejects_overlapping_or_invalid_epochs() {
+    let mut scenario = staged_engine().save_scenario();
+    let epoch = |name: &str, start_tick, end_tick, overlay| Epoch {
+        name: name.to_string(),
+        start_tick,
+        end_tick,
+        overlay,
+    };
+    for epochs in [
+        vec![
+            epoch("a", 0, Some(60), ConfigPatch::default()),
+            epoch("b", 50, None, ConfigPatch::default()),
+        ],
+        vec![epoch("a", 10, Some(10), ConfigPatch::default())],
+        vec![epoch("a", 0, None, ConfigPatch::default().set("drag", 1.0))],
+    ] {
+        scenario.epochs = epochs;
+        scenario.checksum = Some(scenario.compute_checksum());
+        let mut engine = SimulationEngine::initialize(base_config()).unwrap();
+        assert!(matches!(
+            engine.load_scenario(scenario.clone()),
+            Err(EngineError::InvalidConfig(_))
+        ));
+        assert!(engine.epochs().is_empty());
+    }
+}
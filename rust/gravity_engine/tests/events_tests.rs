@@ -0,0 +1,630 @@
+use gravity_engine::alignment::separation_angle;
+use gravity_engine::{
+    AlignmentWatch, Body, CollisionKind, CollisionMode, EngineConfig, EngineError, EscapePolicy,
+    EventFilter, EventOverflow, ExcursionTracking, GravitySolver, Region, SimulationEngine,
+    SimulationEvent, TidalDisruption, Vec2, Zone, roche_limit,
+};
+
+fn base_config() -> EngineConfig {
+    EngineConfig {
+        gravity_constant: 1.0,
+        softening_epsilon: 1e-6,
+        dt: 0.001,
+        collision_mode: CollisionMode::Ignore,
+        gravity_solver: GravitySolver::Pairwise,
+        ..EngineConfig::default()
+    }
+}
+
+fn solar_system() -> Vec<Body> {
+    let inner_speed = (1000.0_f64 / 5.0).sqrt();
+    let outer_speed = (1000.0_f64 / 12.0).sqrt();
+    vec![
+        Body::new("sun", 1000.0, 1.0, Vec2::ZERO, Vec2::ZERO),
+        Body::new(
+            "inner",
+            1e-6,
+            0.1,
+            Vec2::new(0.0, 5.0),
+            Vec2::new(-inner_speed, 0.0),
+        ),
+        Body::new(
+            "outer",
+            1e-6,
+            0.1,
+            Vec2::new(12.0, 0.0),
+            Vec2::new(0.0, outer_speed),
+        ),
+    ]
+}
+
+#[test]
+fn separation_angle_distinguishes_conjunction_and_opposition() {
+    let angle = separation_angle(Vec2::new(2.0, 0.0), Vec2::ZERO, Vec2::new(5.0, 0.0)).unwrap();
+    assert!(angle.abs() < 1e-12);
+    let angle = separation_angle(Vec2::new(-2.0, 0.0), Vec2::ZERO, Vec2::new(5.0, 0.0)).unwrap();
+    assert!((angle - std::f64::consts::PI).abs() < 1e-12);
+    assert!(separation_angle(Vec2::ZERO, Vec2::ZERO, Vec2::new(1.0, 0.0)).is_none());
+}
+
+#[test]
+fn alignment_event_is_emitted_and_matches_prediction() {
+    let watch = AlignmentWatch::new("inner", "sun", "outer", 0.01);
+    let mut engine = SimulationEngine::with_bodies(base_config(), solar_system()).unwrap();
+
+    let predicted = engine
+        .predict_alignment(watch.clone(), 5000)
+        .unwrap()
+        .expect("planets should line up within the horizon");
+    assert_eq!(engine.get_state().tick, 0);
+
+    engine.watch_alignment(watch).unwrap();
+    let summary = engine.step(predicted.tick as u32).unwrap();
+
+    let events = summary
+        .events
+        .iter()
+        .filter(|event| matches!(event, SimulationEvent::Alignment(_)))
+        .collect::<Vec<_>>();
+    assert_eq!(events.len(), 1);
+    let SimulationEvent::Alignment(alignment) = events[0] else {
+        unreachable!("filtered to alignment events");
+    };
+    assert_eq!(alignment, &predicted);
+    assert!(alignment.alignment_error <= 0.01);
+    assert_eq!(alignment.body_ids, vec!["inner", "sun", "outer"]);
+    assert_eq!(engine.events().count(), 1);
+    assert_eq!(engine.drain_events().len(), 1);
+    assert_eq!(engine.events().count(), 0);
+}
+
+#[test]
+fn invalid_alignment_watch_is_rejected() {
+    let mut engine = SimulationEngine::with_bodies(base_config(), solar_system()).unwrap();
+    assert!(
+        engine
+            .watch_alignment(AlignmentWatch::new("sun", "sun", "outer", 0.01))
+            .is_err()
+    );
+    assert!(
+        engine
+            .watch_alignment(AlignmentWatch::new("inner", "sun", "outer", 0.0))
+            .is_err()
+    );
+}
+
+#[test]
+fn excursions_log_exit_and_return_with_duration() {
+    let config = EngineConfig {
+        dt: 0.01,
+        excursion_tracking: Some(ExcursionTracking { system_radius: 5.0 }),
+        ..base_config()
+    };
+    let bodies = vec![
+        Body::new("star", 1000.0, 1.0, Vec2::ZERO, Vec2::ZERO),
+        Body::new(
+            "ejecta",
+            1e-6,
+            0.01,
+            Vec2::new(2.0, 0.0),
+            Vec2::new(0.0, 28.0),
+        ),
+    ];
+    let mut engine = SimulationEngine::with_bodies(config, bodies).unwrap();
+
+    let mut exited = Vec::new();
+    let mut returned = Vec::new();
+    for _ in 0..200 {
+        for event in engine.step(1).unwrap().events {
+            match event {
+                SimulationEvent::BodyExited(event) => exited.push(event),
+                SimulationEvent::BodyReturned(event) => returned.push(event),
+                _ => {}
+            }
+        }
+    }
+
+    assert_eq!(exited.len(), 1);
+    assert_eq!(returned.len(), 1);
+    assert_eq!(exited[0].body_id, "ejecta");
+    assert!(exited[0].distance > 5.0 && returned[0].distance <= 5.0);
+    let duration = returned[0].duration.unwrap();
+    assert!((duration - (returned[0].sim_time - exited[0].sim_time)).abs() < 1e-12);
+
+    let summary = engine.excursion_summary();
+    assert_eq!(summary.total_exits, 1);
+    assert_eq!(summary.total_returns, 1);
+    assert_eq!(summary.currently_outside, 0);
+    let record = summary
+        .bodies
+        .iter()
+        .find(|record| record.body_id == "ejecta")
+        .unwrap();
+    assert_eq!(record.longest_excursion, duration);
+}
+
+#[test]
+fn zones_report_enter_and_exit_per_body() {
+    let bodies = vec![
+        Body::new(
+            "comet",
+            1e-9,
+            0.01,
+            Vec2::new(-1.0, 0.0),
+            Vec2::new(10.0, 0.0),
+        ),
+        Body::new("parked", 1e-9, 0.01, Vec2::new(5.0, 5.0), Vec2::ZERO),
+    ];
+    let mut engine = SimulationEngine::with_bodies(base_config(), bodies).unwrap();
+    engine
+        .add_zone(Zone::new(
+            "goal",
+            Region::Rect {
+                min: Vec2::new(0.0, -1.0),
+                max: Vec2::new(0.5, 1.0),
+            },
+        ))
+        .unwrap();
+    engine
+        .add_zone(Zone::new(
+            "lot",
+            Region::Circle {
+                center: Vec2::new(5.0, 5.0),
+                radius: 1.0,
+            },
+        ))
+        .unwrap();
+    assert!(
+        engine
+            .add_zone(Zone::new(
+                "goal",
+                Region::Circle {
+                    center: Vec2::ZERO,
+                    radius: 1.0
+                }
+            ))
+            .is_err()
+    );
+
+    let events = engine.step(200).unwrap().events;
+    let zone_events = events
+        .iter()
+        .filter_map(|event| match event {
+            SimulationEvent::ZoneEntered(e) => Some(("enter", e)),
+            SimulationEvent::ZoneExited(e) => Some(("exit", e)),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+
+    assert_eq!(zone_events.len(), 2);
+    let (kind, entered) = zone_events[0];
+    assert_eq!((kind, entered.zone.as_str()), ("enter", "goal"));
+    assert_eq!(entered.body_id, "comet");
+    assert!((100..=101).contains(&entered.tick));
+    let (kind, exited) = zone_events[1];
+    assert_eq!(kind, "exit");
+    assert!((150..=151).contains(&exited.tick));
+
+    assert!(engine.remove_zone("goal"));
+    assert!(!engine.remove_zone("goal"));
+}
+
+#[test]
+fn collisions_are_logged_with_ids_and_merge_target() {
+    let config = EngineConfig {
+        collision_mode: CollisionMode::InelasticMerge,
+        ..base_config()
+    };
+    let bodies = vec![
+        Body::new("big", 5.0, 0.5, Vec2::ZERO, Vec2::ZERO),
+        Body::new("small", 1.0, 0.5, Vec2::new(0.8, 0.0), Vec2::ZERO),
+        Body::new("far", 1.0, 0.5, Vec2::new(50.0, 0.0), Vec2::ZERO),
+    ];
+    let mut engine = SimulationEngine::with_bodies(config, bodies).unwrap();
+    let summary = engine.step(3).unwrap();
+
+    assert_eq!(summary.collision_events, 1);
+    let logged = engine.collision_events().collect::<Vec<_>>();
+    assert_eq!(logged.len(), 1);
+    assert_eq!(logged[0].tick, 1);
+    assert_eq!(
+        (logged[0].body_a.as_str(), logged[0].body_b.as_str()),
+        ("big", "small")
+    );
+    assert_eq!(logged[0].kind, CollisionKind::Merge);
+    assert_eq!(logged[0].merged_into.as_deref(), Some("big"));
+
+    let json = serde_json::to_value(&summary.events[0]).unwrap();
+    assert_eq!(json["type"], "collision");
+    assert_eq!(json["kind"], "merge");
+    assert_eq!(json["mergedInto"], "big");
+}
+
+#[test]
+fn merges_report_impact_energy_speed_and_angle() {
+    let config = EngineConfig {
+        collision_mode: CollisionMode::InelasticMerge,
+        count_impacts: true,
+        ..base_config()
+    };
+    let bodies = vec![
+        Body::new("a", 1.0, 0.2, Vec2::new(-0.15, 0.0), Vec2::new(10.0, 0.0)),
+        Body::new("b", 1.0, 0.2, Vec2::new(0.15, 0.0), Vec2::new(-10.0, 0.0)),
+        Body::new("c", 2.0, 0.2, Vec2::new(0.0, 1.0), Vec2::ZERO),
+        Body::new("d", 2.0, 0.2, Vec2::new(0.25, 1.25), Vec2::new(0.0, -3.0)),
+    ];
+    let mut engine = SimulationEngine::with_bodies(config, bodies).unwrap();
+    engine.step(1).unwrap();
+
+    let logged = engine.collision_events().collect::<Vec<_>>();
+    assert_eq!(logged.len(), 2);
+    let head_on = logged[0].impact.unwrap();
+    // One tick of mutual attraction speeds them up slightly.
+    assert!((head_on.speed - 20.0).abs() < 0.1);
+    assert!((head_on.energy - 100.0).abs() < 1.0);
+    assert!(head_on.angle < 1e-2);
+    // Approaching along -y with the centres offset at 45 degrees.
+    let oblique = logged[1].impact.unwrap();
+    assert!((oblique.angle - std::f64::consts::FRAC_PI_4).abs() < 1e-2);
+    assert!((oblique.energy - 0.5 * 1.0 * 9.0).abs() < 0.1);
+
+    let json = serde_json::to_value(logged[0]).unwrap();
+    assert!(json["impact"]["energy"].is_number());
+    for body in engine.bodies() {
+        assert_eq!(body.metadata.as_ref().unwrap().impacts_absorbed, 1);
+    }
+
+    let mut survivor = engine.bodies()[0].clone();
+    survivor.id = "e".to_string();
+    survivor.position = engine.bodies()[1].position;
+    engine
+        .apply_edit(gravity_engine::BodyEdit::Create(survivor))
+        .unwrap();
+    engine.step(1).unwrap();
+    assert_eq!(
+        engine.bodies()[1]
+            .metadata
+            .as_ref()
+            .unwrap()
+            .impacts_absorbed,
+        3
+    );
+}
+
+fn merge_storm(overflow: EventOverflow) -> SimulationEngine {
+    let config = EngineConfig {
+        collision_mode: CollisionMode::InelasticMerge,
+        ..base_config()
+    };
+    let bodies = (0..20)
+        .flat_map(|pair| {
+            let x = 10.0 * pair as f64;
+            [
+                Body::new(format!("a{pair}"), 1.0, 0.5, Vec2::new(x, 0.0), Vec2::ZERO),
+                Body::new(format!("b{pair}"), 1.0, 0.5, Vec2::new(x, 0.5), Vec2::ZERO),
+            ]
+        })
+        .collect();
+    let mut engine = SimulationEngine::with_bodies(config, bodies).unwrap();
+    engine.set_event_log_capacity(5);
+    engine.set_event_overflow(overflow);
+    engine
+}
+
+#[test]
+fn full_event_log_applies_its_overflow_policy() {
+    let mut engine = merge_storm(EventOverflow::DropOldest);
+    let summary = engine.step(1).unwrap();
+    assert_eq!(summary.merged_events, 20);
+    assert_eq!((summary.events_dropped, summary.events_coalesced), (15, 0));
+    assert_eq!(summary.events.len(), 5);
+    let survivors = engine
+        .collision_events()
+        .map(|event| event.body_a.clone())
+        .collect::<Vec<_>>();
+    assert_eq!(survivors, ["a15", "a16", "a17", "a18", "a19"]);
+
+    let mut engine = merge_storm(EventOverflow::CoalesceByKind);
+    let summary = engine.step(1).unwrap();
+    assert_eq!((summary.events_dropped, summary.events_coalesced), (0, 15));
+    let survivors = engine
+        .collision_events()
+        .map(|event| event.body_a.clone())
+        .collect::<Vec<_>>();
+    assert_eq!(survivors, ["a0", "a1", "a2", "a3", "a19"]);
+
+    let mut engine = merge_storm(EventOverflow::Error);
+    assert!(matches!(
+        engine.step(1),
+        Err(EngineError::EventQueueFull(_))
+    ));
+    assert_eq!(engine.drain_events().len(), 5);
+    engine.step(1).unwrap();
+}
+
+#[test]
+fn event_filters_select_by_kind_body_and_region() {
+    let mut engine = merge_storm(EventOverflow::DropOldest);
+    engine.set_event_log_capacity(64);
+    let events = engine.step(1).unwrap().events;
+    let count = |filter: &EventFilter| {
+        events
+            .iter()
+            .filter(|event| filter.matches(event, engine.bodies()))
+            .count()
+    };
+
+    assert_eq!(count(&EventFilter::default()), 20);
+    let zones_only = EventFilter {
+        kinds: vec!["zoneEntered".to_string()],
+        ..EventFilter::default()
+    };
+    assert_eq!(count(&zones_only), 0);
+    let by_body = EventFilter {
+        body_ids: vec!["b3".to_string(), "a7".to_string(), "ghost".to_string()],
+        ..EventFilter::default()
+    };
+    assert_eq!(count(&by_body), 2);
+    let nearby = EventFilter {
+        region: Some(Region::Rect {
+            min: Vec2::new(-1.0, -1.0),
+            max: Vec2::new(25.0, 1.0),
+        }),
+        ..EventFilter::default()
+    };
+    assert_eq!(count(&nearby), 3);
+
+    let unknown = EventFilter {
+        kinds: vec!["explosion".to_string()],
+        ..EventFilter::default()
+    };
+    assert!(unknown.validate().is_err());
+}
+
+#[test]
+fn ffi_event_filter_applies_per_handle() {
+    use std::ffi::{CStr, CString};
+
+    use gravity_engine::ffi::{
+        gs_dispose, gs_initialize, gs_set_event_filter, gs_step, gs_string_free,
+    };
+
+    let take = |ptr: *mut std::os::raw::c_char| {
+        let text = unsafe { CStr::from_ptr(ptr) }.to_str().unwrap().to_string();
+        gs_string_free(ptr);
+        serde_json::from_str::<serde_json::Value>(&text).unwrap()
+    };
+    let config = CString::new(
+        serde_json::to_string(&EngineConfig {
+            collision_mode: CollisionMode::InelasticMerge,
+            ..base_config()
+        })
+        .unwrap(),
+    )
+    .unwrap();
+    let bodies = serde_json::to_string(&vec![
+        Body::new("a", 1.0, 0.5, Vec2::ZERO, Vec2::ZERO),
+        Body::new("b", 1.0, 0.5, Vec2::new(0.5, 0.0), Vec2::ZERO),
+    ])
+    .unwrap();
+    let bodies = CString::new(bodies).unwrap();
+    let handles = [0, 1].map(|_| {
+        take(gs_initialize(config.as_ptr(), bodies.as_ptr()))["data"]["handle"]
+            .as_u64()
+            .unwrap()
+    });
+
+    let filter = CString::new(r#"{"bodyIds":["nobody"]}"#).unwrap();
+    let response = take(gs_set_event_filter(handles[0], filter.as_ptr()));
+    assert_eq!(response["ok"], true);
+    let bad = CString::new(r#"{"kinds":["explosion"]}"#).unwrap();
+    assert_eq!(
+        take(gs_set_event_filter(handles[0], bad.as_ptr()))["ok"],
+        false
+    );
+
+    let filtered = take(gs_step(handles[0], 1));
+    let unfiltered = take(gs_step(handles[1], 1));
+    assert_eq!(
+        filtered["data"]["summary"]["events"]
+            .as_array()
+            .unwrap()
+            .len(),
+        0
+    );
+    assert_eq!(filtered["data"]["summary"]["collisionEvents"], 1);
+    assert_eq!(
+        unfiltered["data"]["collisions"].as_array().unwrap().len(),
+        1
+    );
+
+    for handle in handles {
+        take(gs_dispose(handle));
+    }
+}
+
+#[test]
+fn step_pauses_after_the_tick_that_emits_a_pause_event() {
+    let config = EngineConfig {
+        collision_mode: CollisionMode::InelasticMerge,
+        pause_on_events: vec!["collision".to_string()],
+        ..base_config()
+    };
+    let bodies = vec![
+        Body::new("left", 1.0, 0.1, Vec2::new(-1.0, 0.0), Vec2::new(10.0, 0.0)),
+        Body::new(
+            "right",
+            1.0,
+            0.1,
+            Vec2::new(1.0, 0.0),
+            Vec2::new(-10.0, 0.0),
+        ),
+    ];
+    let mut engine = SimulationEngine::with_bodies(config, bodies).unwrap();
+
+    let summary = engine.step(1000).unwrap();
+    assert_eq!(summary.stop_reason.as_deref(), Some("collision"));
+    assert!(summary.ticks_applied < 1000);
+    assert_eq!(summary.final_tick, u64::from(summary.ticks_applied));
+    assert!(matches!(
+        summary.events.last(),
+        Some(SimulationEvent::Collision(_))
+    ));
+
+    let resumed = engine.step(10).unwrap();
+    assert_eq!(resumed.ticks_applied, 10);
+    assert_eq!(resumed.stop_reason, None);
+
+    let invalid = EngineConfig {
+        pause_on_events: vec!["impact".to_string()],
+        ..base_config()
+    };
+    assert!(invalid.validate().is_err());
+}
+
+#[test]
+fn unbound_bodies_beyond_the_radius_are_removed_as_escapers() {
+    let bodies = vec![
+        Body::new("sun", 1000.0, 1.0, Vec2::ZERO, Vec2::ZERO),
+        // Escape speed at distance 20 is 10.
+        Body::new(
+            "bound",
+            1e-6,
+            0.1,
+            Vec2::new(20.0, 0.0),
+            Vec2::new(8.0, 0.0),
+        ),
+        Body::new(
+            "ejected",
+            1e-6,
+            0.1,
+            Vec2::new(0.0, 20.0),
+            Vec2::new(0.0, 15.0),
+        ),
+        Body::new(
+            "infalling",
+            1e-6,
+            0.1,
+            Vec2::new(-20.0, 0.0),
+            Vec2::new(15.0, 0.0),
+        ),
+        Body::new(
+            "fast",
+            1e-6,
+            0.1,
+            Vec2::new(0.0, -5.0),
+            Vec2::new(0.0, -30.0),
+        ),
+    ];
+    let config = |min_speed: f64| EngineConfig {
+        remove_escapers: Some(EscapePolicy {
+            radius: 10.0,
+            min_speed,
+        }),
+        ..base_config()
+    };
+
+    let mut engine = SimulationEngine::with_bodies(config(0.0), bodies.clone()).unwrap();
+    let summary = engine.step(1).unwrap();
+    let alive = engine
+        .bodies()
+        .iter()
+        .filter(|body| body.alive)
+        .map(|body| body.id.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(alive, ["sun", "bound", "infalling", "fast"]);
+    let escaped = summary
+        .events
+        .iter()
+        .filter_map(|event| match event {
+            SimulationEvent::BodyEscaped(event) => Some(event),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(escaped.len(), 1);
+    assert_eq!(escaped[0].body_id, "ejected");
+    assert!((escaped[0].distance - 20.015).abs() < 1e-2);
+
+    let mut strict = SimulationEngine::with_bodies(config(20.0), bodies).unwrap();
+    strict.step(1).unwrap();
+    assert!(strict.bodies().iter().all(|body| body.alive));
+    assert!(
+        EngineConfig {
+            remove_escapers: Some(EscapePolicy {
+                radius: 0.0,
+                min_speed: 0.0
+            }),
+            ..base_config()
+        }
+        .validate()
+        .is_err()
+    );
+}
+
+#[test]
+fn roche_limit_crossings_are_flagged_once_or_fragment_the_body() {
+    // Apoapsis 5, periapsis 1.5: one pass through the comet's 2.44 Roche limit.
+    let apoapsis_speed = (1000.0_f64 * (2.0 / 5.0 - 1.0 / 3.25)).sqrt();
+    let bodies = vec![
+        Body::new("sun", 1000.0, 1.0, Vec2::ZERO, Vec2::ZERO),
+        Body::new(
+            "comet",
+            1e-3,
+            0.01,
+            Vec2::new(5.0, 0.0),
+            Vec2::new(0.0, apoapsis_speed),
+        ),
+    ];
+    assert!((roche_limit(1000.0, 1e-3, 0.01, 2.44) - 2.44).abs() < 1e-9);
+    let config = |fragments: u32| EngineConfig {
+        tidal_disruption: Some(TidalDisruption {
+            fragments,
+            ..TidalDisruption::default()
+        }),
+        ..base_config()
+    };
+    let disruptions = |summary: &gravity_engine::StepSummary| {
+        summary
+            .events
+            .iter()
+            .filter_map(|event| match event {
+                SimulationEvent::TidalDisruption(event) => Some(event.clone()),
+                _ => None,
+            })
+            .collect::<Vec<_>>()
+    };
+
+    let mut flagged = SimulationEngine::with_bodies(config(0), bodies.clone()).unwrap();
+    let events = disruptions(&flagged.step(1200).unwrap());
+    assert_eq!(events.len(), 1);
+    assert_eq!(
+        (events[0].body_id.as_str(), events[0].primary_id.as_str()),
+        ("comet", "sun")
+    );
+    assert!(events[0].distance < events[0].roche_limit);
+    assert!(events[0].fragment_ids.is_empty());
+    assert_eq!(flagged.bodies().len(), 2);
+
+    let mut shattered = SimulationEngine::with_bodies(config(4), bodies).unwrap();
+    let summary = shattered.step(1200).unwrap();
+    let events = disruptions(&summary);
+    assert_eq!(events.len(), 1);
+    assert_eq!(
+        events[0].fragment_ids,
+        ["comet#1", "comet#2", "comet#3", "comet#4"]
+    );
+    let ids = shattered
+        .bodies()
+        .iter()
+        .map(|body| body.id.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(ids, ["sun", "comet#1", "comet#2", "comet#3", "comet#4"]);
+    let fragment_mass = shattered.bodies()[1..]
+        .iter()
+        .map(|body| body.mass)
+        .sum::<f64>();
+    assert!((fragment_mass - 1e-3).abs() < 1e-15);
+    assert_eq!(summary.removed_bodies.ids, ["comet"]);
+
+    assert!(config(1).validate().is_err());
+}
@@ -0,0 +1,113 @@
+use gravity_engine::export::{write_diagnostics_csv, write_trajectory_csv};
+use gravity_engine::history::History;
+use gravity_engine::{
+    Body, CollisionMode, DiagnosticsColumn, DiagnosticsRow, EngineConfig, GravitySolver,
+    SimulationEngine, TrajectoryColumn, Vec2,
+};
+
+fn base_config() -> EngineConfig {
+    EngineConfig {
+        gravity_constant: 1.0,
+        softening_epsilon: 1e-6,
+        dt: 0.001,
+        collision_mode: CollisionMode::Ignore,
+        gravity_solver: GravitySolver::Pairwise,
+        ..EngineConfig::default()
+    }
+}
+
+fn recorded_run() -> (History, Vec<DiagnosticsRow>) {
+    let bodies = vec![
+        Body::new("star", 10.0, 0.1, Vec2::ZERO, Vec2::ZERO),
+        Body::new(
+            "planet, b",
+            1e-3,
+            0.01,
+            Vec2::new(1.0, 0.0),
+            Vec2::new(0.0, 3.0),
+        ),
+    ];
+    let mut engine = SimulationEngine::with_bodies(base_config(), bodies).unwrap();
+    let mut history = History::new(16);
+    let mut rows = Vec::new();
+    for _ in 0..3 {
+        engine.step(5).unwrap();
+        history.record(&engine);
+        rows.push(DiagnosticsRow::capture(&engine));
+    }
+    (history, rows)
+}
+
+#[test]
+fn trajectory_csv_has_selected_columns_and_round_trips_floats() {
+    let (history, _) = recorded_run();
+    let mut out = Vec::new();
+    write_trajectory_csv(
+        &history,
+        &[
+            TrajectoryColumn::Tick,
+            TrajectoryColumn::Id,
+            TrajectoryColumn::X,
+        ],
+        &mut out,
+    )
+    .unwrap();
+    let text = String::from_utf8(out).unwrap();
+    let lines = text.lines().collect::<Vec<_>>();
+    assert_eq!(lines[0], "tick,id,x");
+    assert_eq!(lines.len(), 1 + 3 * 2);
+    assert!(lines[1].starts_with("5,star,"));
+
+    let planet = lines[2];
+    assert!(planet.starts_with("5,\"planet, b\","));
+    let x = planet.rsplit(',').next().unwrap().parse::<f64>().unwrap();
+    let frame = history.frames().next().unwrap();
+    assert_eq!(x, frame.body("planet, b").unwrap().position.x);
+
+    assert!(write_trajectory_csv(&history, &[], Vec::new()).is_err());
+}
+
+#[test]
+fn diagnostics_csv_writes_one_row_per_capture() {
+    let (_, rows) = recorded_run();
+    let mut out = Vec::new();
+    write_diagnostics_csv(&rows, &DiagnosticsColumn::ALL, &mut out).unwrap();
+    let text = String::from_utf8(out).unwrap();
+    let lines = text.lines().collect::<Vec<_>>();
+    assert_eq!(lines.len(), 4);
+    assert!(lines[0].starts_with("tick,sim_time,kinetic_energy,"));
+    assert!(lines[0].ends_with(",total_mass"));
+    let last = lines[3].split(',').collect::<Vec<_>>();
+    assert_eq!(last.len(), DiagnosticsColumn::ALL.len());
+    assert_eq!(last[0], "15");
+    assert_eq!(
+        last[4].parse::<f64>().unwrap(),
+        rows[2].diagnostics.total_energy
+    );
+}
+
+#[cfg(feature = "parquet")]
+#[test]
+fn parquet_export_reads_back_row_for_row() {
+    use gravity_engine::export::write_trajectory_parquet;
+    use parquet::file::reader::{FileReader, SerializedFileReader};
+    use parquet::record::RowAccessor;
+
+    let (history, _) = recorded_run();
+    let path = std::env::temp_dir().join(format!("trajectory-{}.parquet", std::process::id()));
+    let file = std::fs::File::create(&path).unwrap();
+    write_trajectory_parquet(&history, &TrajectoryColumn::ALL, file).unwrap();
+
+    let reader = SerializedFileReader::new(std::fs::File::open(&path).unwrap()).unwrap();
+    assert_eq!(reader.metadata().file_metadata().num_rows(), 6);
+    let rows = reader
+        .get_row_iter(None)
+        .unwrap()
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    let expected = history.frames().last().unwrap().body("planet, b").unwrap();
+    assert_eq!(rows[5].get_long(0).unwrap(), 15);
+    assert_eq!(rows[5].get_string(2).unwrap(), "planet, b");
+    assert_eq!(rows[5].get_double(5).unwrap(), expected.position.x);
+    std::fs::remove_file(path).unwrap();
+}
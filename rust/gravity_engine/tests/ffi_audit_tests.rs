@@ -0,0 +1,59 @@
+use std::ffi::CStr;
+
+use gravity_engine::ffi::{gs_debug_alloc_stats, gs_string_free};
+use serde_json::Value;
+
+fn take_response(ptr: *mut std::os::raw::c_char) -> Value {
+    let text = unsafe { CStr::from_ptr(ptr) }.to_str().unwrap().to_string();
+    gs_string_free(ptr);
+    serde_json::from_str(&text).unwrap()
+}
+
+#[cfg(feature = "ffi-audit")]
+#[test]
+fn audit_tracks_strings_and_buffers_until_freed() {
+    use std::ffi::CString;
+
+    use gravity_engine::EngineConfig;
+    use gravity_engine::ffi::{
+        gs_bytes_free, gs_dispose, gs_initialize, gs_snapshot_binary, gs_step,
+    };
+
+    let stats = || take_response(gs_debug_alloc_stats())["data"].clone();
+    let before = stats();
+
+    let config = CString::new(serde_json::to_string(&EngineConfig::default()).unwrap()).unwrap();
+    let bodies = CString::new("[]").unwrap();
+    let created = take_response(gs_initialize(config.as_ptr(), bodies.as_ptr()));
+    let handle = created["data"]["handle"].as_u64().unwrap();
+
+    let leaked = gs_step(handle, 1);
+    let mut buffer = std::ptr::null_mut();
+    let mut len = 0;
+    take_response(gs_snapshot_binary(handle, &mut buffer, &mut len));
+
+    let during = stats();
+    let delta =
+        |after: &Value, key: &str| after[key].as_u64().unwrap() - before[key].as_u64().unwrap();
+    assert_eq!(delta(&during, "liveStrings"), 1);
+    assert_eq!(delta(&during, "liveBuffers"), 1);
+    assert_eq!(delta(&during, "liveBufferBytes"), len as u64);
+
+    gs_string_free(leaked);
+    gs_string_free(leaked);
+    gs_bytes_free(buffer, len);
+    take_response(gs_dispose(handle));
+
+    let after = stats();
+    assert_eq!(after["liveStrings"], before["liveStrings"]);
+    assert_eq!(after["liveBuffers"], before["liveBuffers"]);
+    assert_eq!(delta(&after, "invalidFrees"), 1);
+    assert_eq!(delta(&after, "buffersFreed"), 1);
+}
+
+#[cfg(not(feature = "ffi-audit"))]
+#[test]
+fn alloc_stats_require_the_audit_feature() {
+    let response = take_response(gs_debug_alloc_stats());
+    assert_eq!(response["ok"], false);
+}
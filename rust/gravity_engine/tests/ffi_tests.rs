@@ -0,0 +1,239 @@
+use std::ffi::{CStr, CString};
+
+use gravity_engine::ffi::{
+    gs_apply_edit, gs_body_count, gs_dispose, gs_get_positions, gs_get_state, gs_initialize,
+    gs_step, gs_step_delta, gs_string_free, gs_update_config,
+};
+use gravity_engine::{Body, BodyEdit, EngineConfig, EngineError, ErrorCode, SimulationState, Vec2};
+use serde_json::Value;
+
+fn take_response(ptr: *mut std::os::raw::c_char) -> Value {
+    let text = unsafe { CStr::from_ptr(ptr) }.to_str().unwrap().to_string();
+    gs_string_free(ptr);
+    serde_json::from_str(&text).unwrap()
+}
+
+fn json_arg(value: &impl serde::Serialize) -> CString {
+    CString::new(serde_json::to_string(value).unwrap()).unwrap()
+}
+
+#[test]
+fn failed_calls_carry_a_machine_readable_error_code() {
+    let config = json_arg(&EngineConfig::default());
+    let bodies = json_arg(&vec![Body::new("a", 1.0, 0.1, Vec2::ZERO, Vec2::ZERO)]);
+    let handle = take_response(gs_initialize(config.as_ptr(), bodies.as_ptr()))["data"]["handle"]
+        .as_u64()
+        .unwrap();
+    let code = |response: Value| {
+        assert_eq!(response["ok"], false);
+        assert!(response["error"].is_string());
+        response["errorCode"].clone()
+    };
+
+    let duplicate = json_arg(&BodyEdit::Create(Body::new(
+        "a",
+        1.0,
+        0.1,
+        Vec2::new(1.0, 0.0),
+        Vec2::ZERO,
+    )));
+    assert_eq!(
+        code(take_response(gs_apply_edit(handle, duplicate.as_ptr()))),
+        "DuplicateBodyId"
+    );
+    let missing = json_arg(&BodyEdit::Delete {
+        id: "ghost".to_string(),
+    });
+    assert_eq!(
+        code(take_response(gs_apply_edit(handle, missing.as_ptr()))),
+        "BodyNotFound"
+    );
+    let patch = CString::new(r#"{"dt": -1.0}"#).unwrap();
+    assert_eq!(
+        code(take_response(gs_update_config(handle, patch.as_ptr()))),
+        "InvalidConfig"
+    );
+    let garbage = CString::new("{").unwrap();
+    assert_eq!(
+        code(take_response(gs_apply_edit(handle, garbage.as_ptr()))),
+        "InvalidArgument"
+    );
+    assert_eq!(
+        code(take_response(gs_apply_edit(handle, std::ptr::null()))),
+        "InvalidArgument"
+    );
+    assert_eq!(code(take_response(gs_step(u64::MAX, 1))), "EngineNotFound");
+    assert_eq!(take_response(gs_step(handle, 1))["errorCode"], Value::Null);
+    take_response(gs_dispose(handle));
+
+    assert_eq!(
+        EngineError::NumericalInstability(String::new()).code(),
+        ErrorCode::NumericalInstability
+    );
+    assert_eq!(ErrorCode::DuplicateBodyId as u32, 3);
+}
+
+#[test]
+fn c_header_declares_every_export_and_error_code() {
+    let header = include_str!("../include/gravity_engine.h");
+    let source = include_str!("../src/ffi.rs");
+    let exports = source
+        .split("pub extern \"C\" fn ")
+        .skip(1)
+        .map(|rest| &rest[..rest.find('(').unwrap()])
+        .collect::<Vec<_>>();
+    assert!(exports.len() > 50);
+    for name in exports {
+        assert!(
+            header.contains(&format!("{name}(")),
+            "{name} missing from header"
+        );
+    }
+    for code in [
+        ErrorCode::InvalidConfig,
+        ErrorCode::WorkerFailed,
+        ErrorCode::EngineNotFound,
+        ErrorCode::Internal,
+    ] {
+        let name = serde_json::to_value(code).unwrap();
+        let line = format!("ErrorCode_{} = {},", name.as_str().unwrap(), code as u32);
+        assert!(header.contains(&line), "{line}");
+    }
+}
+
+fn initialize(bodies: &[Body]) -> u64 {
+    let config = json_arg(&EngineConfig {
+        gravity_constant: 1.0,
+        dt: 0.01,
+        ..EngineConfig::default()
+    });
+    let bodies = json_arg(&bodies);
+    take_response(gs_initialize(config.as_ptr(), bodies.as_ptr()))["data"]["handle"]
+        .as_u64()
+        .unwrap()
+}
+
+fn state(handle: u64) -> SimulationState {
+    serde_json::from_value(take_response(gs_get_state(handle))["data"]["state"].clone()).unwrap()
+}
+
+#[test]
+fn get_positions_fills_caller_buffers_in_engine_order() {
+    let handle = initialize(&[
+        Body::new("a", 1.0, 0.1, Vec2::new(-1.0, 2.0), Vec2::ZERO),
+        Body::new("b", 2.0, 0.1, Vec2::new(3.0, -4.0), Vec2::new(0.0, 1.0)),
+    ]);
+    take_response(gs_step(handle, 5));
+    assert_eq!(gs_body_count(handle), 2);
+    assert_eq!(gs_body_count(u64::MAX), -1);
+
+    let mut buffer = [0.0; 5];
+    assert_eq!(
+        gs_get_positions(handle, buffer.as_mut_ptr(), buffer.len()),
+        2
+    );
+    let expected = state(handle)
+        .bodies
+        .iter()
+        .flat_map(|body| [body.position.x, body.position.y])
+        .collect::<Vec<_>>();
+    assert_eq!(&buffer[..4], expected.as_slice());
+    assert_eq!(buffer[4], 0.0);
+
+    let mut short = [7.0; 3];
+    assert_eq!(
+        gs_get_positions(handle, short.as_mut_ptr(), short.len()),
+        -2
+    );
+    assert_eq!(short, [7.0; 3]);
+    assert_eq!(gs_get_positions(handle, std::ptr::null_mut(), 4), -1);
+    assert_eq!(gs_get_positions(u64::MAX, buffer.as_mut_ptr(), 4), -1);
+
+    take_response(gs_dispose(handle));
+    assert_eq!(gs_body_count(handle), -1);
+}
+
+#[test]
+fn step_delta_returns_flat_arrays_that_match_the_state() {
+    let handle = initialize(&[
+        Body::new("a", 1.0, 0.5, Vec2::new(-0.2, 0.0), Vec2::ZERO),
+        Body::new("b", 1.0, 0.5, Vec2::new(0.2, 0.0), Vec2::ZERO),
+        Body::new("c", 1e-3, 0.1, Vec2::new(10.0, 0.0), Vec2::new(0.0, 0.3)),
+    ]);
+
+    let delta = take_response(gs_step_delta(handle, 3))["data"].clone();
+    let state = state(handle);
+    assert_eq!(delta["tick"], 3);
+    // `a` and `b` merged on the first tick; merges drop the absorbed body.
+    assert_eq!(state.bodies.len(), 2);
+    let flat = |values: fn(&Body) -> Vec2| {
+        state
+            .bodies
+            .iter()
+            .flat_map(|body| [values(body).x, values(body).y])
+            .collect::<Vec<_>>()
+    };
+    let floats = |key: &str| {
+        delta[key]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|value| value.as_f64().unwrap())
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(floats("positions").len(), 2 * state.bodies.len());
+    assert_eq!(floats("positions"), flat(|body| body.position));
+    assert_eq!(floats("velocities"), flat(|body| body.velocity));
+    let alive = state
+        .bodies
+        .iter()
+        .map(|body| Value::Bool(body.alive))
+        .collect::<Vec<_>>();
+    assert_eq!(delta["alive"].as_array().unwrap(), &alive);
+    take_response(gs_dispose(handle));
+}
+
+#[test]
+fn separate_handles_step_concurrently() {
+    use std::os::raw::c_void;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::time::{Duration, Instant};
+
+    use gravity_engine::ffi::gs_step_observed;
+
+    // Parks the first handle's step until the main thread has stepped the second one.
+    extern "C" fn wait_for_other(_: u64, _: *const f64, _: usize, data: *mut c_void) -> i32 {
+        let released = unsafe { &*(data as *const AtomicBool) };
+        let deadline = Instant::now() + Duration::from_secs(10);
+        while !released.load(Ordering::Acquire) && Instant::now() < deadline {
+            std::thread::sleep(Duration::from_millis(1));
+        }
+        i32::from(!released.load(Ordering::Acquire))
+    }
+
+    let config = json_arg(&EngineConfig::default());
+    let bodies = json_arg(&vec![Body::new("a", 1.0, 0.1, Vec2::ZERO, Vec2::ZERO)]);
+    let create = || {
+        take_response(gs_initialize(config.as_ptr(), bodies.as_ptr()))["data"]["handle"]
+            .as_u64()
+            .unwrap()
+    };
+    let (parked, free) = (create(), create());
+
+    static RELEASED: AtomicBool = AtomicBool::new(false);
+    let worker = std::thread::spawn(move || {
+        let data = &RELEASED as *const AtomicBool as *mut c_void;
+        take_response(gs_step_observed(parked, 1, 1, Some(wait_for_other), data))
+    });
+    std::thread::sleep(Duration::from_millis(20));
+    let started = Instant::now();
+    let stepped = take_response(gs_step(free, 3));
+    assert_eq!(stepped["data"]["summary"]["ticksApplied"], 3);
+    assert!(started.elapsed() < Duration::from_secs(5));
+    RELEASED.store(true, Ordering::Release);
+
+    let parked_response = worker.join().unwrap();
+    assert_eq!(parked_response["ok"], true);
+    take_response(gs_dispose(parked));
+    take_response(gs_dispose(free));
+}
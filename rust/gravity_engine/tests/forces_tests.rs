@@ -0,0 +1,579 @@
+use std::sync::{Arc, Mutex};
+
+use gravity_engine::forces::{area_to_mass_for_beta, radiation_beta};
+use gravity_engine::{
+    Body, CollisionMode, EngineConfig, EngineError, ForceProvider, GravitySolver,
+    InstabilityCapture, IntegratorKind, Oblateness, SimulationEngine, StageContext, StageHook,
+    Vec2, force_magnitude, softening_radius,
+};
+
+fn base_config() -> EngineConfig {
+    EngineConfig {
+        gravity_constant: 1.0,
+        softening_epsilon: 1e-6,
+        dt: 0.001,
+        integrator: IntegratorKind::VelocityVerlet,
+        collision_mode: CollisionMode::Ignore,
+        gravity_solver: GravitySolver::Pairwise,
+        ..EngineConfig::default()
+    }
+}
+
+fn star() -> Body {
+    Body {
+        luminosity: Some(50.0),
+        ..Body::new("star", 1000.0, 1.0, Vec2::ZERO, Vec2::ZERO)
+    }
+}
+
+fn dust(beta: f64, star: &Body, g: f64) -> Body {
+    Body {
+        area_to_mass: area_to_mass_for_beta(beta, star, g),
+        ..Body::new("dust", 1e-9, 0.01, Vec2::new(10.0, 0.0), Vec2::ZERO)
+    }
+}
+
+#[test]
+fn beta_helpers_round_trip() {
+    let star = star();
+    let area_to_mass = area_to_mass_for_beta(0.3, &star, 1.0).unwrap();
+    let beta = radiation_beta(star.luminosity.unwrap(), area_to_mass, 1.0, star.mass);
+    assert!((beta - 0.3).abs() < 1e-12);
+    assert_eq!(
+        area_to_mass_for_beta(
+            0.3,
+            &Body::new("dark", 1.0, 1.0, Vec2::ZERO, Vec2::ZERO),
+            1.0
+        ),
+        None
+    );
+}
+
+#[test]
+fn unit_beta_dust_feels_no_net_force() {
+    let config = base_config();
+    let star = star();
+    let dust = dust(1.0, &star, config.gravity_constant);
+
+    let mut engine = SimulationEngine::with_bodies(config, vec![star, dust]).unwrap();
+    engine.step(2000).unwrap();
+
+    let dust = &engine.bodies()[1];
+    assert!(dust.velocity.norm() < 1e-9, "velocity {:?}", dust.velocity);
+    assert!((dust.position.x - 10.0).abs() < 1e-9);
+}
+
+#[test]
+fn radiation_pressure_is_consistent_across_integrators() {
+    for integrator in [
+        IntegratorKind::SemiImplicitEuler,
+        IntegratorKind::VelocityVerlet,
+        IntegratorKind::Rk4,
+    ] {
+        let config = EngineConfig {
+            integrator,
+            ..base_config()
+        };
+        let star = star();
+        let weak = dust(0.5, &star, config.gravity_constant);
+        let sail = Body {
+            id: "sail".to_string(),
+            position: Vec2::new(-10.0, 0.0),
+            ..dust(1.5, &star, config.gravity_constant)
+        };
+
+        let mut engine = SimulationEngine::with_bodies(config, vec![star, weak, sail]).unwrap();
+        engine.step(1000).unwrap();
+
+        let bodies = engine.bodies();
+        assert!(
+            bodies[1].position.x < 10.0,
+            "{integrator:?} weak dust should fall"
+        );
+        assert!(
+            bodies[2].position.x < -10.0,
+            "{integrator:?} sail should be pushed out"
+        );
+    }
+}
+
+#[test]
+fn negative_luminosity_is_rejected() {
+    let body = Body {
+        luminosity: Some(-1.0),
+        ..Body::new("bad", 1.0, 1.0, Vec2::ZERO, Vec2::ZERO)
+    };
+    assert!(body.validate().is_err());
+}
+
+fn semi_major_axis(body: &Body, primary: &Body, g: f64) -> f64 {
+    let mu = g * (primary.mass + body.mass);
+    let r = (body.position - primary.position).norm();
+    let v2 = (body.velocity - primary.velocity).norm_squared();
+    1.0 / (2.0 / r - v2 / mu)
+}
+
+#[test]
+fn yarkovsky_drift_moves_semi_major_axis_in_its_sign_direction() {
+    let config = base_config();
+    let g = config.gravity_constant;
+    let star = Body::new("star", 1000.0, 1.0, Vec2::ZERO, Vec2::ZERO);
+    let radius = 10.0;
+    let speed = (g * star.mass / radius).sqrt();
+    let duration = 20.0;
+    let ticks = (duration / config.dt) as u32;
+
+    for magnitude in [1e-3, -1e-3] {
+        let asteroid = Body {
+            yarkovsky: Some(magnitude),
+            ..Body::new(
+                "asteroid",
+                1e-6,
+                0.01,
+                Vec2::new(radius, 0.0),
+                Vec2::new(0.0, speed),
+            )
+        };
+        let a0 = semi_major_axis(&asteroid, &star, g);
+
+        let mut engine =
+            SimulationEngine::with_bodies(config.clone(), vec![star.clone(), asteroid]).unwrap();
+        engine.step(ticks).unwrap();
+        let bodies = engine.bodies();
+        let a1 = semi_major_axis(&bodies[1], &bodies[0], g);
+
+        // Gauss' equation for a purely along-track perturbation on a circular orbit.
+        let expected = 2.0 * magnitude * (a0.powi(3) / (g * star.mass)).sqrt() * duration;
+        let drift = a1 - a0;
+        assert!(drift.signum() == magnitude.signum(), "drift {drift}");
+        assert!(
+            (drift - expected).abs() < 0.1 * expected.abs(),
+            "drift {drift} expected {expected}"
+        );
+    }
+}
+
+#[test]
+fn j2_precession_matches_first_order_rate() {
+    let config = base_config();
+    let g = config.gravity_constant;
+    let j2 = 0.02;
+    let equatorial_radius = 1.0;
+    let planet = Body {
+        oblateness: Some(Oblateness {
+            j2,
+            equatorial_radius,
+            obliquity: 0.0,
+            node_angle: 0.0,
+        }),
+        ..Body::new("planet", 1000.0, equatorial_radius, Vec2::ZERO, Vec2::ZERO)
+    };
+
+    let a: f64 = 3.0;
+    let e: f64 = 0.2;
+    let mu = g * planet.mass;
+    let periapsis = a * (1.0 - e);
+    let periapsis_speed = (mu * (1.0 + e) / periapsis).sqrt();
+    let moon = Body::new(
+        "moon",
+        1e-9,
+        0.01,
+        Vec2::new(periapsis, 0.0),
+        Vec2::new(0.0, periapsis_speed),
+    );
+
+    // Fit the periapsis longitude over many orbits to average out short-period J2 terms.
+    let mut engine = SimulationEngine::with_bodies(config.clone(), vec![planet, moon]).unwrap();
+    let mut samples = Vec::new();
+    let mut unwrapped = 0.0_f64;
+    for _ in 0..400 {
+        engine.step(100).unwrap();
+        let bodies = engine.bodies();
+        let r = bodies[1].position - bodies[0].position;
+        let v = bodies[1].velocity - bodies[0].velocity;
+        let eccentricity = (r * (v.norm_squared() - mu / r.norm()) - v * r.dot(v)) / mu;
+        let angle = eccentricity.y.atan2(eccentricity.x);
+        let wraps = ((unwrapped - angle) / std::f64::consts::TAU).round();
+        unwrapped = angle + wraps * std::f64::consts::TAU;
+        samples.push((engine.get_state().sim_time, unwrapped));
+    }
+    let n = samples.len() as f64;
+    let mean_t = samples.iter().map(|(t, _)| t).sum::<f64>() / n;
+    let mean_w = samples.iter().map(|(_, w)| w).sum::<f64>() / n;
+    let measured = samples
+        .iter()
+        .map(|(t, w)| (t - mean_t) * (w - mean_w))
+        .sum::<f64>()
+        / samples
+            .iter()
+            .map(|(t, _)| (t - mean_t).powi(2))
+            .sum::<f64>();
+
+    let mean_motion = (mu / a.powi(3)).sqrt();
+    let semi_latus = a * (1.0 - e * e);
+    let expected = 1.5 * mean_motion * j2 * (equatorial_radius / semi_latus).powi(2);
+    assert!(
+        (measured - expected).abs() < 0.05 * expected,
+        "measured {measured} expected {expected}"
+    );
+}
+
+/// Harmonic trap pulling every body towards the origin.
+struct Spring {
+    stiffness: f64,
+}
+
+impl ForceProvider for Spring {
+    fn accelerations(&self, _bodies: &[Body], positions: &[Vec2]) -> Vec<Vec2> {
+        positions
+            .iter()
+            .map(|position| *position * -self.stiffness)
+            .collect()
+    }
+}
+
+#[test]
+fn registered_force_providers_act_at_every_stage() {
+    for integrator in [IntegratorKind::VelocityVerlet, IntegratorKind::Rk4] {
+        let config = EngineConfig {
+            integrator,
+            ..base_config()
+        };
+        let bodies = vec![Body::new("bob", 1.0, 0.1, Vec2::new(1.0, 0.0), Vec2::ZERO)];
+        let mut engine = SimulationEngine::with_bodies(config, bodies).unwrap();
+        engine
+            .add_force_provider("spring", Arc::new(Spring { stiffness: 4.0 }))
+            .unwrap();
+        assert!(
+            engine
+                .add_force_provider("spring", Arc::new(Spring { stiffness: 1.0 }))
+                .is_err()
+        );
+
+        // Half a period of omega = 2 lands the bob on the opposite side.
+        let ticks = (std::f64::consts::FRAC_PI_2 / 0.001).round() as u32;
+        engine.step(ticks).unwrap();
+        let position = engine.bodies()[0].position;
+        assert!(
+            (position.x + 1.0).abs() < 1e-3,
+            "{integrator:?}: {position:?}"
+        );
+
+        assert!(engine.remove_force_provider("spring"));
+        assert_eq!(engine.force_provider_names().count(), 0);
+    }
+}
+
+#[test]
+fn force_magnitude_matches_the_simulated_pair_force() {
+    let config = EngineConfig {
+        gravity_constant: 2.0,
+        softening_epsilon: 0.5,
+        dt: 1e-3,
+        integrator: IntegratorKind::SemiImplicitEuler,
+        collision_mode: CollisionMode::Ignore,
+        gravity_solver: GravitySolver::Pairwise,
+        ..EngineConfig::default()
+    };
+    let newton = |r: f64| 2.0 * 3.0 * 5.0 / (r * r);
+    assert!((force_magnitude(3.0, 5.0, 100.0, &config) / newton(100.0) - 1.0).abs() < 1e-4);
+    assert_eq!(force_magnitude(3.0, 5.0, 0.0, &config), 0.0);
+    let peak = 0.5 / 2.0_f64.sqrt();
+    assert!(
+        force_magnitude(3.0, 5.0, peak, &config) > force_magnitude(3.0, 5.0, peak * 0.9, &config)
+    );
+    assert!(
+        force_magnitude(3.0, 5.0, peak, &config) > force_magnitude(3.0, 5.0, peak * 1.1, &config)
+    );
+
+    for distance in [0.1, 0.5, 2.0] {
+        let bodies = vec![
+            Body::new("a", 3.0, 0.01, Vec2::ZERO, Vec2::ZERO),
+            Body::new("b", 5.0, 0.01, Vec2::new(distance, 0.0), Vec2::ZERO),
+        ];
+        let mut engine = SimulationEngine::with_bodies(config.clone(), bodies).unwrap();
+        engine.step(1).unwrap();
+        let simulated = engine.bodies()[0].velocity.x * 3.0 / config.dt;
+        let expected = force_magnitude(3.0, 5.0, distance, &config);
+        assert!((simulated / expected - 1.0).abs() < 1e-12);
+    }
+}
+
+#[test]
+fn softening_radius_marks_where_the_force_deviates_by_the_tolerance() {
+    let config = EngineConfig {
+        gravity_constant: 1.0,
+        softening_epsilon: 0.2,
+        ..EngineConfig::default()
+    };
+    let radius = softening_radius(&config, 0.01).unwrap();
+    let ratio = |r: f64| force_magnitude(1.0, 1.0, r, &config) * r * r;
+    assert!((ratio(radius) - 0.99).abs() < 1e-12);
+    assert!(ratio(radius * 0.9) < 0.99);
+    assert!(ratio(radius * 1.1) > 0.99);
+    assert!(softening_radius(&config, 0.05).unwrap() < radius);
+
+    let unsoftened = EngineConfig {
+        softening_epsilon: 0.0,
+        ..config.clone()
+    };
+    assert_eq!(softening_radius(&unsoftened, 0.01).unwrap(), 0.0);
+    assert!(softening_radius(&config, 0.0).is_err());
+    assert!(softening_radius(&config, 1.0).is_err());
+}
+
+/// Counts hook calls per stage index.
+#[derive(Default)]
+struct StageCounter {
+    before_forces: Mutex<Vec<u32>>,
+    after_forces: Mutex<Vec<u32>>,
+    commits: Mutex<Vec<u32>>,
+}
+
+impl StageHook for StageCounter {
+    fn before_forces(&self, context: &StageContext<'_>, _: &mut [Vec2], _: &mut [Vec2]) {
+        self.before_forces.lock().unwrap().push(context.stage);
+    }
+
+    fn after_forces(&self, context: &StageContext<'_>, _: &[Vec2], _: &[Vec2], _: &mut [Vec2]) {
+        self.after_forces.lock().unwrap().push(context.stage);
+    }
+
+    fn before_commit(&self, context: &StageContext<'_>, _: &mut [Vec2], _: &mut [Vec2]) {
+        self.commits.lock().unwrap().push(context.stage);
+    }
+}
+
+/// Constraint projection keeping every body on the unit circle.
+struct UnitCircle;
+
+impl StageHook for UnitCircle {
+    fn before_commit(
+        &self,
+        _context: &StageContext<'_>,
+        positions: &mut [Vec2],
+        velocities: &mut [Vec2],
+    ) {
+        for (position, velocity) in positions.iter_mut().zip(velocities.iter_mut()) {
+            *position = *position / position.norm();
+            let radial = *position * velocity.dot(*position);
+            *velocity -= radial;
+        }
+    }
+}
+
+#[test]
+fn stage_hooks_run_around_every_stage_and_can_project_constraints() {
+    for (integrator, stages) in [
+        (IntegratorKind::SemiImplicitEuler, vec![0]),
+        (IntegratorKind::VelocityVerlet, vec![0, 1]),
+        (IntegratorKind::Rk4, vec![0, 1, 2, 3]),
+    ] {
+        let config = EngineConfig {
+            integrator,
+            ..base_config()
+        };
+        let bodies = vec![Body::new(
+            "bead",
+            1.0,
+            0.1,
+            Vec2::new(1.0, 0.0),
+            Vec2::new(0.3, 1.0),
+        )];
+        let mut engine = SimulationEngine::with_bodies(config, bodies).unwrap();
+        let counter = Arc::new(StageCounter::default());
+        engine.add_stage_hook("counter", counter.clone()).unwrap();
+        engine
+            .add_stage_hook("circle", Arc::new(UnitCircle))
+            .unwrap();
+        assert!(
+            engine
+                .add_stage_hook("circle", Arc::new(UnitCircle))
+                .is_err()
+        );
+        assert_eq!(
+            engine.stage_hook_names().collect::<Vec<_>>(),
+            ["counter", "circle"]
+        );
+
+        engine.step(2).unwrap();
+        let expected = [stages.clone(), stages.clone()].concat();
+        assert_eq!(*counter.before_forces.lock().unwrap(), expected);
+        assert_eq!(*counter.after_forces.lock().unwrap(), expected);
+        let last = *stages.last().unwrap();
+        assert_eq!(*counter.commits.lock().unwrap(), [last, last]);
+
+        engine.step(200).unwrap();
+        let bead = &engine.bodies()[0];
+        assert!((bead.position.norm() - 1.0).abs() < 1e-12);
+        assert!(bead.velocity.dot(bead.position).abs() < 1e-12);
+        assert!(engine.remove_stage_hook("circle"));
+    }
+}
+
+/// Poisons any body that crosses `x = 1`.
+struct Tripwire;
+
+impl ForceProvider for Tripwire {
+    fn accelerations(&self, _bodies: &[Body], positions: &[Vec2]) -> Vec<Vec2> {
+        positions
+            .iter()
+            .map(|position| {
+                if position.x > 1.0 {
+                    Vec2::new(f64::NAN, 0.0)
+                } else {
+                    Vec2::ZERO
+                }
+            })
+            .collect()
+    }
+}
+
+#[test]
+fn instability_leaves_a_reproducible_post_mortem() {
+    let config = EngineConfig {
+        dt: 0.1,
+        instability_capture: Some(InstabilityCapture { history_ticks: 4 }),
+        ..base_config()
+    };
+    let bodies = vec![
+        Body::new("probe", 1e-9, 0.01, Vec2::ZERO, Vec2::new(1.0, 0.0)),
+        Body::new("anchor", 1e-9, 0.01, Vec2::new(0.0, 100.0), Vec2::ZERO),
+    ];
+    let mut engine = SimulationEngine::with_bodies(config.clone(), bodies).unwrap();
+    engine
+        .add_force_provider("tripwire", Arc::new(Tripwire))
+        .unwrap();
+    engine.step(5).unwrap();
+    assert!(engine.last_instability().is_none());
+
+    let error = engine.step(20).unwrap_err();
+    assert!(matches!(error, EngineError::NumericalInstability(_)));
+    let report = engine.last_instability().unwrap().clone();
+    assert_eq!(report.body_id.as_deref(), Some("probe"));
+    assert_eq!(report.history.len(), 4);
+    let last = report.history.last().unwrap();
+    assert_eq!(last.tick, report.last_good.tick);
+    assert!(last.position.x <= 1.0 + 1e-9);
+    assert!(
+        report
+            .history
+            .windows(2)
+            .all(|pair| pair[1].tick == pair[0].tick + 1)
+    );
+
+    let mut replay = SimulationEngine::initialize(config).unwrap();
+    replay
+        .add_force_provider("tripwire", Arc::new(Tripwire))
+        .unwrap();
+    replay.restore_snapshot(report.last_good).unwrap();
+    assert!(matches!(
+        replay.step(1),
+        Err(EngineError::NumericalInstability(_))
+    ));
+}
+
+#[test]
+fn per_body_softening_overrides_the_global_length_symmetrically() {
+    let config = EngineConfig {
+        softening_epsilon: 0.1,
+        ..base_config()
+    };
+    let pair = |softening: [Option<f64>; 2]| {
+        let bodies = vec![
+            Body {
+                softening: softening[0],
+                ..Body::new("a", 3.0, 0.01, Vec2::ZERO, Vec2::ZERO)
+            },
+            Body {
+                softening: softening[1],
+                ..Body::new("b", 5.0, 0.01, Vec2::new(0.3, 0.0), Vec2::ZERO)
+            },
+        ];
+        let mut engine = SimulationEngine::with_bodies(config.clone(), bodies).unwrap();
+        engine.step(1).unwrap();
+        let bodies = engine.bodies();
+        (
+            bodies[0].velocity * bodies[0].mass,
+            bodies[1].velocity * bodies[1].mass,
+        )
+    };
+
+    let global = pair([None, None]);
+    assert_eq!(pair([Some(0.1), Some(0.1)]), global);
+
+    let (first, second) = pair([Some(0.5), None]);
+    assert!((first + second).norm() < 1e-15);
+    assert!(first.x < global.0.x);
+    assert_eq!(pair([None, Some(0.5)]), (first, second));
+
+    let mut solvers = Vec::new();
+    for gravity_solver in [GravitySolver::Pairwise, GravitySolver::BarnesHut] {
+        let bodies = (0..12)
+            .map(|index| {
+                let angle = index as f64 * 0.7;
+                Body {
+                    softening: (index % 3 == 0).then_some(0.4),
+                    ..Body::new(
+                        format!("b{index}"),
+                        1.0 + index as f64,
+                        0.01,
+                        Vec2::new(angle.cos(), angle.sin()) * (1.0 + index as f64 * 0.2),
+                        Vec2::ZERO,
+                    )
+                }
+            })
+            .collect();
+        let config = EngineConfig {
+            gravity_solver,
+            barnes_hut_theta: 1e-6,
+            barnes_hut_threshold: 1,
+            ..config.clone()
+        };
+        let mut engine = SimulationEngine::with_bodies(config, bodies).unwrap();
+        engine.step(1).unwrap();
+        solvers.push(engine.bodies().to_vec());
+    }
+    for (pairwise, tree) in solvers[0].iter().zip(&solvers[1]) {
+        assert!((pairwise.velocity - tree.velocity).norm() < 1e-12);
+    }
+
+    let invalid = Body {
+        softening: Some(-1.0),
+        ..Body::new("bad", 1.0, 0.01, Vec2::ZERO, Vec2::ZERO)
+    };
+    assert!(matches!(
+        SimulationEngine::with_bodies(config, vec![invalid]),
+        Err(EngineError::InvalidBody(_))
+    ));
+}
+
+#[test]
+fn body_derivatives_match_uniform_circular_motion() {
+    let config = EngineConfig {
+        softening_epsilon: 0.0,
+        ..base_config()
+    };
+    // Equal masses 2 apart: each circles the origin at radius 1 with omega = 0.5.
+    let bodies = vec![
+        Body::new("a", 1.0, 0.01, Vec2::new(1.0, 0.0), Vec2::new(0.0, 0.5)),
+        Body::new("b", 1.0, 0.01, Vec2::new(-1.0, 0.0), Vec2::new(0.0, -0.5)),
+    ];
+    let engine = SimulationEngine::with_bodies(config, bodies).unwrap();
+
+    let derivatives = engine.body_derivatives(&["a".to_string()]).unwrap();
+    assert_eq!(derivatives.len(), 1);
+    let a = &derivatives[0];
+    assert_eq!(a.id, "a");
+    assert!((a.acceleration - Vec2::new(-0.25, 0.0)).norm() < 1e-12);
+    assert!((a.jerk - Vec2::new(0.0, -0.125)).norm() < 1e-12);
+    assert!((a.snap - a.acceleration * -0.25).norm() < 1e-12);
+
+    let all = engine.body_derivatives(&[]).unwrap();
+    assert_eq!(all.len(), 2);
+    assert!((all[0].jerk + all[1].jerk).norm() < 1e-12);
+    assert!(matches!(
+        engine.body_derivatives(&["missing".to_string()]),
+        Err(EngineError::BodyNotFound(_))
+    ));
+}
@@ -0,0 +1,199 @@
+use gravity_engine::camera::{TourOptions, TourStop, grand_tour};
+use gravity_engine::history::History;
+use gravity_engine::{
+    Body, CollisionKind, CollisionMode, EngineConfig, FlybyQuery, GravitySolver, SimulationEngine,
+    Vec2, analyze_flybys,
+};
+
+fn recorded_history() -> History {
+    let config = EngineConfig {
+        gravity_constant: 1.0,
+        dt: 0.01,
+        collision_mode: CollisionMode::Ignore,
+        gravity_solver: GravitySolver::Pairwise,
+        ..EngineConfig::default()
+    };
+    let bodies = vec![
+        Body::new("sun", 1000.0, 2.0, Vec2::ZERO, Vec2::ZERO),
+        Body::new(
+            "planet",
+            1.0,
+            0.5,
+            Vec2::new(10.0, 0.0),
+            Vec2::new(0.0, 10.0),
+        ),
+    ];
+    let mut engine = SimulationEngine::with_bodies(config, bodies).unwrap();
+    let mut history = History::new(64);
+    history.record(&engine);
+    for _ in 0..100 {
+        engine.step(10).unwrap();
+        history.record(&engine);
+    }
+    history
+}
+
+#[test]
+fn history_is_bounded_and_interpolates() {
+    let history = recorded_history();
+    assert_eq!(history.len(), 64);
+
+    let (start, end) = history.time_span().unwrap();
+    assert!(start > 0.0 && end > start);
+
+    let frames = history.frames().collect::<Vec<_>>();
+    let midpoint = 0.5 * (frames[0].sim_time + frames[1].sim_time);
+    let sample = history.body_at("planet", midpoint).unwrap();
+    let expected = (frames[0].body("planet").unwrap().position
+        + frames[1].body("planet").unwrap().position)
+        * 0.5;
+    assert!((sample.position - expected).norm() < 1e-12);
+    assert!(history.body_at("missing", midpoint).is_none());
+}
+
+#[test]
+fn grand_tour_visits_stops_in_time_order() {
+    let history = recorded_history();
+    let (start, end) = history.time_span().unwrap();
+    let event_time = start + 0.9 * (end - start);
+    let stops = vec![
+        TourStop::Body {
+            id: "planet".to_string(),
+        },
+        TourStop::Location {
+            sim_time: event_time,
+            position: Vec2::new(-5.0, 5.0),
+            view_radius: 3.0,
+        },
+        TourStop::Body {
+            id: "sun".to_string(),
+        },
+    ];
+
+    let path = grand_tour(&history, &stops, &TourOptions::default());
+    assert!(!path.is_empty());
+    assert!(
+        path.windows(2)
+            .all(|pair| pair[0].sim_time <= pair[1].sim_time)
+    );
+    assert!(path.first().unwrap().sim_time >= start);
+    assert!(path.last().unwrap().sim_time <= end);
+
+    let first = path.first().unwrap();
+    let planet = history.body_at("planet", first.sim_time).unwrap();
+    assert!((first.position - planet.position).norm() < 1e-12);
+
+    let last = path.last().unwrap();
+    assert_eq!(last.position, Vec2::new(-5.0, 5.0));
+    assert_eq!(last.view_radius, 3.0);
+
+    assert!(grand_tour(&History::new(4), &stops, &TourOptions::default()).is_empty());
+}
+
+#[test]
+fn grand_tour_stops_at_recorded_merges() {
+    let config = EngineConfig {
+        gravity_constant: 1.0,
+        dt: 0.01,
+        collision_mode: CollisionMode::InelasticMerge,
+        gravity_solver: GravitySolver::Pairwise,
+        ..EngineConfig::default()
+    };
+    let bodies = vec![
+        Body::new("left", 1.0, 0.5, Vec2::new(-5.0, 0.0), Vec2::new(5.0, 0.0)),
+        Body::new("right", 1.0, 0.5, Vec2::new(5.0, 0.0), Vec2::new(-5.0, 0.0)),
+    ];
+    let mut engine = SimulationEngine::with_bodies(config, bodies).unwrap();
+    let mut history = History::new(256);
+    history.record(&engine);
+    for _ in 0..200 {
+        engine.step(1).unwrap();
+        history.record(&engine);
+    }
+
+    let merges = history.collisions().collect::<Vec<_>>();
+    assert_eq!(merges.len(), 1);
+    assert_eq!(merges[0].kind, CollisionKind::Merge);
+
+    let stops = vec![TourStop::Collisions { merges_only: true }];
+    let options = TourOptions::default();
+    let path = grand_tour(&history, &stops, &options);
+    assert_eq!(path.len(), 2);
+    assert!(path[0].sim_time <= merges[0].sim_time && merges[0].sim_time <= path[1].sim_time);
+    assert!(path[0].position.norm() < 0.5, "{:?}", path[0].position);
+    assert!(path[0].view_radius >= options.body_padding * 0.5);
+}
+
+#[test]
+fn flybys_report_periapsis_speeds_and_deflection() {
+    let config = EngineConfig {
+        gravity_constant: 1.0,
+        dt: 0.005,
+        collision_mode: CollisionMode::Ignore,
+        gravity_solver: GravitySolver::Pairwise,
+        ..EngineConfig::default()
+    };
+    let (position, velocity) = (Vec2::new(-300.0, 10.0), Vec2::new(10.5, 0.0));
+    let mut planet = Body::new("planet", 1000.0, 1.0, Vec2::ZERO, Vec2::ZERO);
+    planet.fixed = true;
+    let probe = Body::new("probe", 1e-3, 0.01, position, velocity);
+    let mut engine = SimulationEngine::with_bodies(config, vec![planet, probe]).unwrap();
+    let mut history = History::new(20_000);
+    history.record(&engine);
+    for _ in 0..3000 {
+        engine.step(4).unwrap();
+        history.record(&engine);
+    }
+
+    let query = FlybyQuery {
+        body_id: "probe".to_string(),
+        max_distance: 250.0,
+        min_mass_ratio: 10.0,
+    };
+    let reports = analyze_flybys(&history, &query).unwrap();
+    assert_eq!(reports.len(), 1, "{reports:?}");
+    let flyby = &reports[0];
+    assert_eq!(flyby.primary_id, "planet");
+    assert!(flyby.complete);
+
+    let mu = 1000.0;
+    let h = position.cross(velocity).abs();
+    let energy = 0.5 * velocity.norm_squared() - mu / position.norm();
+    let eccentricity = (1.0 + 2.0 * energy * h * h / (mu * mu)).sqrt();
+    let periapsis = h * h / (mu * (1.0 + eccentricity));
+    assert!(
+        (flyby.periapsis_distance - periapsis).abs() < 0.01 * periapsis,
+        "{} vs {periapsis}",
+        flyby.periapsis_distance
+    );
+    assert!((flyby.speed_in - flyby.speed_out).abs() < 0.01 * flyby.speed_in);
+    // Passing above the planet bends the path clockwise; the asymptotic turn differs
+    // from the turn at 250 by a few hundredths of a radian.
+    let asymptotic = -2.0 * (1.0 / eccentricity).asin();
+    assert!(
+        (flyby.deflection_angle - asymptotic).abs() < 0.08,
+        "{} vs {asymptotic}",
+        flyby.deflection_angle
+    );
+    assert!(flyby.periapsis_time > 20.0 && flyby.periapsis_time < 40.0);
+
+    let reports = analyze_flybys(
+        &history,
+        &FlybyQuery {
+            min_mass_ratio: 1e9,
+            ..query.clone()
+        },
+    )
+    .unwrap();
+    assert!(reports.is_empty());
+    assert!(
+        analyze_flybys(
+            &history,
+            &FlybyQuery {
+                max_distance: 0.0,
+                ..query
+            }
+        )
+        .is_err()
+    );
+}
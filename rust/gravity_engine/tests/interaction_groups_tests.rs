@@ -0,0 +1,112 @@
+use gravity_engine::{
+    Body, CollisionMode, EngineConfig, GravitySolver, GroupRule, InteractionGroups,
+    SimulationEngine, Vec2,
+};
+
+const TRACERS: u32 = 1;
+
+fn tracer_rules() -> InteractionGroups {
+    InteractionGroups {
+        rules: vec![GroupRule {
+            source: Some(TRACERS),
+            target: None,
+            gravity: Some(false),
+            collisions: Some(false),
+        }],
+    }
+}
+
+fn config(solver: GravitySolver) -> EngineConfig {
+    EngineConfig {
+        gravity_constant: 1.0,
+        softening_epsilon: 1e-3,
+        dt: 0.001,
+        gravity_solver: solver,
+        collision_mode: CollisionMode::InelasticMerge,
+        interaction_groups: Some(tracer_rules()),
+        ..EngineConfig::default()
+    }
+}
+
+fn massive() -> Vec<Body> {
+    vec![
+        Body::new("star", 1.0, 0.05, Vec2::ZERO, Vec2::ZERO),
+        Body::new(
+            "planet",
+            1e-3,
+            0.01,
+            Vec2::new(1.0, 0.0),
+            Vec2::new(0.0, 1.0),
+        ),
+    ]
+}
+
+fn tracers(count: usize) -> Vec<Body> {
+    (0..count)
+        .map(|index| Body {
+            group: Some(TRACERS),
+            // Heavy enough to matter if they did pull; the first sits inside the star.
+            ..Body::new(
+                format!("tracer-{index}"),
+                0.5,
+                0.01,
+                Vec2::new(0.0, 0.01 + 0.25 * index as f64),
+                Vec2::ZERO,
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn rules_resolve_with_later_entries_winning() {
+    let mut groups = tracer_rules();
+    assert!(!groups.attracts(TRACERS, 0));
+    assert!(groups.attracts(0, TRACERS));
+    assert!(!groups.collides(0, TRACERS));
+    assert!(groups.collides(0, 2));
+
+    groups.rules.push(GroupRule {
+        source: Some(TRACERS),
+        target: Some(TRACERS),
+        gravity: Some(true),
+        collisions: None,
+    });
+    assert!(groups.attracts(TRACERS, TRACERS));
+    assert!(!groups.attracts(TRACERS, 0));
+    assert!(!groups.collides(TRACERS, TRACERS));
+}
+
+#[test]
+fn tracers_feel_gravity_without_pulling_or_colliding() {
+    let mut alone =
+        SimulationEngine::with_bodies(config(GravitySolver::Pairwise), massive()).unwrap();
+    let mut bodies = massive();
+    bodies.extend(tracers(4));
+    let mut with_tracers =
+        SimulationEngine::with_bodies(config(GravitySolver::Pairwise), bodies).unwrap();
+
+    let summary = with_tracers.step(200).unwrap();
+    alone.step(200).unwrap();
+
+    assert_eq!(summary.merged_events, 0);
+    assert_eq!(with_tracers.bodies().len(), 6);
+    assert_eq!(&with_tracers.bodies()[..2], alone.bodies());
+    // Falling towards the star.
+    assert!(with_tracers.bodies()[3].velocity.y < -0.1);
+}
+
+#[test]
+fn grouped_barnes_hut_tracks_grouped_pairwise() {
+    let mut bodies = massive();
+    bodies.extend(tracers(20));
+    let mut pairwise =
+        SimulationEngine::with_bodies(config(GravitySolver::Pairwise), bodies.clone()).unwrap();
+    let mut barnes_hut =
+        SimulationEngine::with_bodies(config(GravitySolver::BarnesHut), bodies).unwrap();
+    pairwise.step(100).unwrap();
+    barnes_hut.step(100).unwrap();
+
+    for (exact, approximate) in pairwise.bodies().iter().zip(barnes_hut.bodies()) {
+        assert!((exact.position - approximate.position).norm() < 1e-4);
+    }
+}
@@ -0,0 +1,157 @@
+use std::f64::consts::{FRAC_PI_2, PI};
+
+use gravity_engine::{
+    Body, BodyEdit, BoundaryMode, CollisionMode, EngineConfig, EngineError, Frame,
+    SimulationEngine, Transform2, Vec2, WorldBoundary,
+};
+
+fn assert_close(a: Vec2, b: Vec2) {
+    assert!(a.distance(b) < 1e-12, "{a:?} != {b:?}");
+}
+
+#[test]
+fn vec2_rotation_helpers_agree() {
+    let v = Vec2::new(3.0, 4.0);
+    assert_close(v.rotate(FRAC_PI_2), v.perp());
+    assert_close(v.rotate(PI), -v);
+    assert_close(Vec2::from_angle(v.angle()) * v.norm(), v);
+    assert!((Vec2::new(-1.0, 0.0).angle() - PI).abs() < 1e-12);
+
+    assert_eq!(Vec2::new(1.0, 0.0).cross(Vec2::new(0.0, 1.0)), 1.0);
+    assert_eq!(v.cross(v * 2.0), 0.0);
+    assert_eq!(v.perp().dot(v), 0.0);
+}
+
+#[test]
+fn vec2_arithmetic_helpers() {
+    let a = Vec2::new(1.0, 2.0);
+    let b = Vec2::new(5.0, -2.0);
+    assert_eq!(a.lerp(b, 0.25), Vec2::new(2.0, 1.0));
+    assert_eq!(a.distance(b), 32.0_f64.sqrt());
+    assert_eq!(a.distance_squared(b), 32.0);
+    assert_eq!(2.0 * a, a * 2.0);
+
+    let points = [a, b, Vec2::new(0.0, 3.0)];
+    assert_eq!(points.iter().sum::<Vec2>(), Vec2::new(6.0, 3.0));
+    assert_eq!(points.into_iter().sum::<Vec2>(), Vec2::new(6.0, 3.0));
+}
+
+#[test]
+fn transform_compose_and_inverse_round_trip() {
+    let first = Transform2 {
+        rotation: 0.7,
+        scale: 2.0,
+        translation: Vec2::new(1.0, -3.0),
+        velocity_boost: Vec2::new(0.5, 0.25),
+    };
+    let second = Transform2::rotation(-1.2).then(&Transform2::translation(Vec2::new(4.0, 0.0)));
+    let composed = first.then(&second);
+
+    let point = Vec2::new(-2.0, 5.0);
+    let velocity = Vec2::new(0.3, -0.1);
+    assert_close(
+        composed.apply_point(point),
+        second.apply_point(first.apply_point(point)),
+    );
+    assert_close(
+        composed.apply_velocity(velocity),
+        second.apply_velocity(first.apply_velocity(velocity)),
+    );
+
+    let round_trip = composed.then(&composed.inverse());
+    assert_close(round_trip.apply_point(point), point);
+    assert_close(round_trip.apply_velocity(velocity), velocity);
+}
+
+#[test]
+fn transform_edit_moves_selected_bodies_atomically() {
+    let bodies = vec![
+        Body::new("a", 1.0, 0.1, Vec2::new(1.0, 0.0), Vec2::new(0.0, 1.0)),
+        Body::new("b", 1.0, 0.1, Vec2::new(-1.0, 0.0), Vec2::ZERO),
+    ];
+    let mut engine = SimulationEngine::with_bodies(EngineConfig::default(), bodies).unwrap();
+    let transform = Transform2 {
+        rotation: FRAC_PI_2,
+        translation: Vec2::new(10.0, 0.0),
+        velocity_boost: Vec2::new(0.0, 2.0),
+        ..Transform2::IDENTITY
+    };
+
+    engine
+        .apply_edit(BodyEdit::Transform {
+            ids: vec!["a".to_string()],
+            transform,
+        })
+        .unwrap();
+    assert_close(engine.bodies()[0].position, Vec2::new(10.0, 1.0));
+    assert_close(engine.bodies()[0].velocity, Vec2::new(-1.0, 2.0));
+    assert_eq!(engine.bodies()[1].position, Vec2::new(-1.0, 0.0));
+
+    let missing = engine.apply_edit(BodyEdit::Transform {
+        ids: vec!["b".to_string(), "ghost".to_string()],
+        transform,
+    });
+    assert!(missing.is_err());
+    assert_eq!(engine.bodies()[1].position, Vec2::new(-1.0, 0.0));
+}
+
+#[test]
+fn recenter_moves_bodies_into_the_chosen_frame() {
+    let bodies = vec![
+        Body::new("sun", 3.0, 0.1, Vec2::new(1.0, 1.0), Vec2::new(0.5, 0.0)),
+        Body::new("planet", 1.0, 0.1, Vec2::new(5.0, 1.0), Vec2::new(0.5, 2.0)),
+    ];
+    let config = EngineConfig {
+        gravity_constant: 1.0,
+        dt: 0.01,
+        collision_mode: CollisionMode::Ignore,
+        ..EngineConfig::default()
+    };
+    let mut engine = SimulationEngine::with_bodies(config.clone(), bodies.clone()).unwrap();
+
+    engine.recenter(&Frame::CenterOfMass).unwrap();
+    assert_close(engine.bodies()[0].position, Vec2::new(-1.0, 0.0));
+    assert_close(engine.bodies()[1].position, Vec2::new(3.0, 0.0));
+    assert_close(engine.bodies()[0].velocity, Vec2::new(0.0, -0.5));
+    assert_close(engine.bodies()[1].velocity, Vec2::new(0.0, 1.5));
+
+    engine.recenter(&Frame::Body("planet".to_string())).unwrap();
+    assert_close(engine.bodies()[1].position, Vec2::ZERO);
+    assert_close(engine.bodies()[1].velocity, Vec2::ZERO);
+    assert!(matches!(
+        engine.recenter(&Frame::Body("ghost".to_string())),
+        Err(EngineError::BodyNotFound(_))
+    ));
+    assert_close(engine.bodies()[0].position, Vec2::new(-4.0, 0.0));
+
+    // Every tick: the drifting pair stays on the origin instead of wandering off.
+    let locked = EngineConfig {
+        recenter: Some(Frame::CenterOfMass),
+        ..config.clone()
+    };
+    let mut drifting = SimulationEngine::with_bodies(config.clone(), bodies.clone()).unwrap();
+    let mut centred = SimulationEngine::with_bodies(locked, bodies).unwrap();
+    drifting.step(1000).unwrap();
+    centred.step(1000).unwrap();
+    let center = |engine: &SimulationEngine| {
+        engine
+            .bodies()
+            .iter()
+            .map(|body| body.position * body.mass)
+            .sum::<Vec2>()
+            / 4.0
+    };
+    assert!(center(&drifting).norm() > 5.0);
+    assert!(center(&centred).norm() < 1e-9);
+
+    let walled = EngineConfig {
+        recenter: Some(Frame::CenterOfMass),
+        boundary: Some(WorldBoundary {
+            min: Vec2::new(-10.0, -10.0),
+            max: Vec2::new(10.0, 10.0),
+            mode: BoundaryMode::Reflect,
+        }),
+        ..config
+    };
+    assert!(walled.validate().is_err());
+}
@@ -0,0 +1,203 @@
+use gravity_engine::{
+    Body, BodyEdit, BodyUpdate, CollisionMode, EngineConfig, GravitySolver, Journal,
+    JournalCommand, PerturbSpec, ReplayCheckpoint, RollbackSession, SimulationEngine, Vec2,
+    verify_replay,
+};
+
+fn base_config() -> EngineConfig {
+    EngineConfig {
+        gravity_constant: 1.0,
+        softening_epsilon: 1e-6,
+        dt: 0.001,
+        collision_mode: CollisionMode::Ignore,
+        gravity_solver: GravitySolver::Pairwise,
+        ..EngineConfig::default()
+    }
+}
+
+fn arena() -> Vec<Body> {
+    vec![
+        Body::new("star", 100.0, 0.5, Vec2::ZERO, Vec2::ZERO),
+        Body::new("ship_a", 1.0, 0.1, Vec2::new(5.0, 0.0), Vec2::new(0.0, 4.5)),
+        Body::new(
+            "ship_b",
+            1.0,
+            0.1,
+            Vec2::new(-6.0, 0.0),
+            Vec2::new(0.0, -4.0),
+        ),
+    ]
+}
+
+fn thrust(id: &str, velocity: Vec2) -> BodyEdit {
+    BodyEdit::Update(BodyUpdate {
+        id: id.to_string(),
+        velocity: Some(velocity),
+        ..BodyUpdate::default()
+    })
+}
+
+/// Runs the authoritative timeline tick by tick, applying each input before its tick.
+fn run_server(inputs: &[(u64, BodyEdit)], ticks: u64) -> Vec<SimulationEngine> {
+    let mut server = SimulationEngine::with_bodies(base_config(), arena()).unwrap();
+    let mut states = vec![server.clone()];
+    for tick in 0..ticks {
+        for (_, edit) in inputs.iter().filter(|(at, _)| *at == tick) {
+            server.apply_edit(edit.clone()).unwrap();
+        }
+        server.step(1).unwrap();
+        states.push(server.clone());
+    }
+    states
+}
+
+#[test]
+fn late_authoritative_state_triggers_rollback_and_replay() {
+    let remote = (10, thrust("ship_b", Vec2::new(1.0, -4.0)));
+    let local = (25, thrust("ship_a", Vec2::new(-1.0, 4.5)));
+    let server = run_server(&[remote, local.clone()], 30);
+
+    let engine = SimulationEngine::with_bodies(base_config(), arena()).unwrap();
+    let mut session = RollbackSession::new(engine, 64).unwrap();
+    session.submit_input(local.0, local.1).unwrap();
+    session.advance(30).unwrap();
+    assert_ne!(session.engine().bodies(), server[30].bodies());
+
+    let report = session.reconcile(server[20].snapshot()).unwrap();
+    assert!(report.mispredicted);
+    assert_eq!(report.resimulated_ticks, 10);
+    assert_eq!(session.current_tick(), 30);
+    assert_eq!(session.engine().bodies(), server[30].bodies());
+}
+
+#[test]
+fn matching_prediction_skips_resimulation() {
+    let local = (5, thrust("ship_a", Vec2::new(0.0, 5.0)));
+    let server = run_server(std::slice::from_ref(&local), 20);
+
+    let engine = SimulationEngine::with_bodies(base_config(), arena()).unwrap();
+    let mut session = RollbackSession::new(engine, 64).unwrap();
+    session.submit_input(local.0, local.1.clone()).unwrap();
+    session.advance(20).unwrap();
+
+    let report = session.reconcile(server[12].snapshot()).unwrap();
+    assert!(!report.mispredicted);
+    assert_eq!(report.resimulated_ticks, 0);
+    assert_eq!(session.engine().bodies(), server[20].bodies());
+
+    // A local input for an already simulated tick rewinds to the recorded prediction.
+    let late = thrust("ship_b", Vec2::new(0.5, -4.0));
+    let replayed = session.submit_input(15, late.clone()).unwrap();
+    assert_eq!(replayed, 5);
+    let server = run_server(&[local, (15, late)], 20);
+    assert_eq!(session.engine().bodies(), server[20].bodies());
+    assert!(session.submit_input(3, thrust("star", Vec2::ZERO)).is_err());
+}
+
+#[test]
+fn rollback_requires_deterministic_mode() {
+    let config = EngineConfig {
+        deterministic: false,
+        ..base_config()
+    };
+    let engine = SimulationEngine::with_bodies(config, arena()).unwrap();
+    assert!(RollbackSession::new(engine, 8).is_err());
+}
+
+#[test]
+fn state_hashes_verify_a_replayed_scenario() {
+    let mut engine = SimulationEngine::with_bodies(base_config(), arena()).unwrap();
+    let scenario = engine.save_scenario();
+    let mut checkpoints = vec![ReplayCheckpoint {
+        tick: 0,
+        hash: engine.state_hash(),
+    }];
+    for _ in 0..3 {
+        engine.step(40).unwrap();
+        checkpoints.push(ReplayCheckpoint {
+            tick: engine.get_state().tick,
+            hash: engine.state_hash(),
+        });
+    }
+    assert_eq!(checkpoints[3].hash.len(), 16);
+    assert_ne!(checkpoints[2].hash, checkpoints[3].hash);
+
+    let verification = verify_replay(scenario.clone(), &checkpoints).unwrap();
+    assert!(verification.passed());
+    assert_eq!(verification.verified, 4);
+
+    let mut nudged = SimulationEngine::with_bodies(base_config(), arena()).unwrap();
+    nudged.step(80).unwrap();
+    nudged
+        .apply_edit(thrust("ship_a", Vec2::new(0.0, 4.5)))
+        .unwrap();
+    assert_ne!(nudged.state_hash(), checkpoints[2].hash);
+    let mut tampered = checkpoints.clone();
+    tampered[2].hash = nudged.state_hash();
+    let verification = verify_replay(scenario.clone(), &tampered).unwrap();
+    assert_eq!(verification.verified, 2);
+    let mismatch = verification.mismatch.unwrap();
+    assert_eq!(mismatch.tick, 80);
+    assert_eq!(mismatch.actual, checkpoints[2].hash);
+
+    checkpoints.swap(1, 2);
+    assert!(verify_replay(scenario, &checkpoints).is_err());
+}
+
+#[test]
+fn exported_journal_replays_an_interactive_session() {
+    let mut engine = SimulationEngine::with_bodies(base_config(), arena()).unwrap();
+    engine.step(10).unwrap();
+    engine.step(5).unwrap();
+    engine
+        .apply_edit(thrust("ship_a", Vec2::new(0.5, 4.0)))
+        .unwrap();
+    engine
+        .perturb(
+            &["ship_b".to_string()],
+            &PerturbSpec {
+                position_sigma: 0.0,
+                velocity_sigma: 0.01,
+                seed: 7,
+            },
+        )
+        .unwrap();
+    engine
+        .set_config(EngineConfig {
+            dt: 0.002,
+            ..base_config()
+        })
+        .unwrap();
+    assert!(engine.apply_edit(thrust("missing", Vec2::ZERO)).is_err());
+    engine.step(20).unwrap();
+
+    let journal = engine.export_journal();
+    let commands: Vec<_> = journal
+        .entries
+        .iter()
+        .map(|entry| (entry.tick, &entry.command))
+        .collect();
+    assert_eq!(commands.len(), 5);
+    assert_eq!(commands[0], (0, &JournalCommand::Step { ticks: 15 }));
+    assert!(matches!(commands[2].1, JournalCommand::ApplyEdit { .. }));
+    assert!(matches!(commands[3].1, JournalCommand::SetConfig { .. }));
+    assert_eq!(commands[4], (15, &JournalCommand::Step { ticks: 20 }));
+
+    let text = serde_json::to_string(&journal).unwrap();
+    let parsed: Journal = serde_json::from_str(&text).unwrap();
+    assert_eq!(parsed, journal);
+    let replayed = SimulationEngine::replay_journal(&parsed).unwrap();
+    assert_eq!(replayed.tick(), 35);
+    assert_eq!(replayed.state_hash(), engine.state_hash());
+
+    // Replacing the state starts a new journal from it.
+    let snapshot = engine.snapshot();
+    engine.restore_snapshot(snapshot.clone()).unwrap();
+    let restarted = engine.export_journal();
+    assert!(restarted.entries.is_empty());
+    assert_eq!(restarted.start, snapshot);
+
+    let mut tampered = journal;
+    tampered.entries[4].tick = 16;
+    assert!(SimulationEngine::replay_journal(&tampered).is_err());
+}
@@ -0,0 +1,307 @@
+use std::f64::consts::{FRAC_PI_2, FRAC_PI_3, PI, TAU};
+
+use gravity_engine::{
+    Body, CollisionMode, DtPolicy, EngineConfig, GravitySolver, IntegratorKind, OrbitPlacement,
+    PoincareSection, SimulationEngine, Vec2, orbit_state, orbital_elements, propagate_kepler,
+};
+
+fn star() -> Body {
+    Body::new("star", 10.0, 0.1, Vec2::new(3.0, -2.0), Vec2::new(0.5, 0.0))
+}
+
+fn approx(a: f64, b: f64, tol: f64) {
+    assert!((a - b).abs() <= tol, "expected {b}, got {a}");
+}
+
+#[test]
+fn circular_orbit_matches_hand_computed_speed() {
+    let placement = OrbitPlacement {
+        semi_major_axis: 4.0,
+        eccentricity: 0.0,
+        true_anomaly: 0.0,
+        argument_of_periapsis: FRAC_PI_2,
+        clockwise: false,
+    };
+    let primary = star();
+    let body = Body::from_orbital_elements("moon", 1e-6, 0.01, &primary, &placement, 1.0).unwrap();
+    let offset = body.position - primary.position;
+    approx(offset.x, 0.0, 1e-12);
+    approx(offset.y, 4.0, 1e-12);
+    let relative = body.velocity - primary.velocity;
+    approx(relative.x, -(10.000001_f64 / 4.0).sqrt(), 1e-12);
+    approx(relative.y, 0.0, 1e-12);
+
+    let elements = orbital_elements(&primary, &body, 1.0).unwrap();
+    approx(elements.eccentricity, 0.0, 1e-9);
+    approx(elements.periapsis, 4.0, 1e-9);
+    approx(elements.apoapsis.unwrap(), 4.0, 1e-9);
+    approx(
+        elements.period.unwrap(),
+        TAU * (64.0_f64 / 10.000001).sqrt(),
+        1e-9,
+    );
+}
+
+#[test]
+fn elements_round_trip_through_placement() {
+    let primary = star();
+    for (a, e, anomaly, clockwise) in [
+        (2.0, 0.6, FRAC_PI_3, false),
+        (5.0, 0.1, -2.5, true),
+        (-3.0, 1.8, 0.4, false),
+        (-1.0, 3.0, -1.0, true),
+    ] {
+        let placement = OrbitPlacement {
+            semi_major_axis: a,
+            eccentricity: e,
+            true_anomaly: anomaly,
+            argument_of_periapsis: 0.7,
+            clockwise,
+        };
+        let body = Body::from_orbital_elements("b", 0.5, 0.01, &primary, &placement, 2.0).unwrap();
+        let elements = orbital_elements(&primary, &body, 2.0).unwrap();
+        approx(elements.semi_major_axis, a, 1e-9);
+        approx(elements.eccentricity, e, 1e-9);
+        approx(elements.true_anomaly, anomaly, 1e-9);
+        approx(elements.argument_of_periapsis, 0.7, 1e-9);
+        assert_eq!(elements.clockwise, clockwise);
+        approx(elements.periapsis, a * (1.0 - e), 1e-9);
+        assert_eq!(elements.period.is_some(), e < 1.0);
+    }
+}
+
+#[test]
+fn placed_orbit_reaches_apoapsis_after_half_a_period() {
+    let config = EngineConfig {
+        gravity_constant: 1.0,
+        softening_epsilon: 0.0,
+        dt: 1e-4,
+        ..EngineConfig::default()
+    };
+    let primary = Body::new("star", 1.0, 0.01, Vec2::ZERO, Vec2::ZERO);
+    let placement = OrbitPlacement {
+        semi_major_axis: 1.0,
+        eccentricity: 0.5,
+        true_anomaly: 0.0,
+        argument_of_periapsis: 0.0,
+        clockwise: false,
+    };
+    let planet =
+        Body::from_orbital_elements("planet", 1e-9, 0.01, &primary, &placement, 1.0).unwrap();
+    let mut engine = SimulationEngine::with_bodies(config, vec![primary, planet]).unwrap();
+    // Half of the period 2 pi.
+    engine.step((PI / 1e-4).round() as u32).unwrap();
+    let position = engine.bodies()[1].position;
+    approx(position.x, -1.5, 1e-3);
+    approx(position.y, 0.0, 1e-3);
+}
+
+#[test]
+fn invalid_orbits_are_rejected() {
+    let primary = star();
+    for (a, e, anomaly) in [
+        (-1.0, 0.5, 0.0),
+        (1.0, 1.5, 0.0),
+        (1.0, 1.0, 0.0),
+        (-1.0, 2.0, 2.5),
+    ] {
+        let placement = OrbitPlacement {
+            semi_major_axis: a,
+            eccentricity: e,
+            true_anomaly: anomaly,
+            argument_of_periapsis: 0.0,
+            clockwise: false,
+        };
+        assert!(Body::from_orbital_elements("b", 1.0, 0.01, &primary, &placement, 1.0).is_err());
+    }
+    assert!(orbital_elements(&primary, &primary, 1.0).is_none());
+}
+
+#[test]
+fn kepler_propagation_follows_elliptic_and_hyperbolic_conics() {
+    let mu = 2.0;
+    let ellipse = OrbitPlacement {
+        semi_major_axis: 3.0,
+        eccentricity: 0.7,
+        true_anomaly: 0.0,
+        argument_of_periapsis: FRAC_PI_3,
+        clockwise: true,
+    };
+    let (position, velocity) = orbit_state(mu, 0.0, &ellipse, 1.0).unwrap();
+    let period = TAU * (27.0 / mu).sqrt();
+    let (apo, apo_velocity) = propagate_kepler(position, velocity, mu, 2.5 * period).unwrap();
+    approx(apo.norm(), 3.0 * 1.7, 1e-9);
+    approx(apo.dot(position), -apo.norm() * position.norm(), 1e-8);
+    approx(
+        apo_velocity.norm(),
+        (mu * (2.0 / 5.1 - 1.0 / 3.0)).sqrt(),
+        1e-9,
+    );
+    let (back, back_velocity) = propagate_kepler(apo, apo_velocity, mu, -0.5 * period).unwrap();
+    approx((back - position).norm(), 0.0, 1e-9);
+    approx((back_velocity - velocity).norm(), 0.0, 1e-9);
+
+    let hyperbola = OrbitPlacement {
+        semi_major_axis: -1.5,
+        eccentricity: 2.2,
+        true_anomaly: -1.5,
+        argument_of_periapsis: 0.4,
+        clockwise: false,
+    };
+    let (position, velocity) = orbit_state(mu, 0.0, &hyperbola, 1.0).unwrap();
+    let invariants = |p: Vec2, v: Vec2| (0.5 * v.norm_squared() - mu / p.norm(), p.cross(v));
+    let (later, later_velocity) = propagate_kepler(position, velocity, mu, 100.0).unwrap();
+    let (energy, momentum) = invariants(position, velocity);
+    let (later_energy, later_momentum) = invariants(later, later_velocity);
+    approx(later_energy, energy, 1e-10);
+    approx(later_momentum, momentum, 1e-10);
+    assert!(later.norm() > 100.0);
+
+    assert!(propagate_kepler(Vec2::ZERO, velocity, mu, 1.0).is_none());
+}
+
+#[test]
+fn central_body_mode_beats_plain_verlet_at_the_same_dt() {
+    let sun = Body::new("sun", 1.0, 0.01, Vec2::ZERO, Vec2::ZERO);
+    let planet = |id: &str, mass: f64, a: f64, anomaly: f64| {
+        let placement = OrbitPlacement {
+            semi_major_axis: a,
+            eccentricity: 0.3,
+            true_anomaly: anomaly,
+            argument_of_periapsis: 0.0,
+            clockwise: false,
+        };
+        Body::from_orbital_elements(id, mass, 0.001, &sun, &placement, 1.0).unwrap()
+    };
+    let bodies = vec![
+        sun.clone(),
+        planet("inner", 1e-3, 1.0, 0.0),
+        planet("outer", 3e-4, 2.5, 2.0),
+    ];
+    let config = |dt: f64, central: bool| EngineConfig {
+        gravity_constant: 1.0,
+        softening_epsilon: 0.0,
+        dt,
+        dt_policy: DtPolicy::Fixed,
+        integrator: IntegratorKind::VelocityVerlet,
+        collision_mode: CollisionMode::Ignore,
+        gravity_solver: GravitySolver::Pairwise,
+        central_body: central.then(|| "sun".to_string()),
+        ..EngineConfig::default()
+    };
+    let run = |dt: f64, ticks: u32, central: bool| {
+        let mut engine =
+            SimulationEngine::with_bodies(config(dt, central), bodies.clone()).unwrap();
+        engine.step(ticks).unwrap();
+        engine.bodies().to_vec()
+    };
+    let error = |state: &[Body], reference: &[Body]| {
+        state
+            .iter()
+            .zip(reference)
+            .map(|(body, exact)| (body.position - exact.position).norm())
+            .fold(0.0, f64::max)
+    };
+
+    let reference = run(0.05 / 64.0, 64 * 400, false);
+    let central = run(0.05, 400, true);
+    let plain = run(0.05, 400, false);
+    let central_error = error(&central, &reference);
+    let plain_error = error(&plain, &reference);
+    assert!(
+        central_error * 1000.0 < plain_error,
+        "central {central_error} vs plain {plain_error}"
+    );
+
+    let momentum = |state: &[Body]| {
+        state
+            .iter()
+            .map(|body| body.velocity * body.mass)
+            .sum::<Vec2>()
+    };
+    assert!((momentum(&central) - momentum(&bodies)).norm() < 1e-14);
+
+    assert!(
+        EngineConfig {
+            central_body: Some(" ".to_string()),
+            ..EngineConfig::default()
+        }
+        .validate()
+        .is_err()
+    );
+    assert!(
+        EngineConfig {
+            dt_policy: DtPolicy::BlockTimesteps,
+            ..config(0.05, true)
+        }
+        .validate()
+        .is_err()
+    );
+}
+
+#[test]
+fn poincare_sections_sample_each_upward_crossing() {
+    let drift = Vec2::new(0.0, 0.5);
+    let start = Vec2::from_angle(0.1);
+    let bodies = vec![
+        Body::new("sun", 1.0, 0.05, Vec2::ZERO, drift),
+        Body::new("planet", 1e-9, 0.01, start, start.perp() + drift),
+    ];
+    let config = EngineConfig {
+        gravity_constant: 1.0,
+        softening_epsilon: 0.0,
+        dt: 0.001,
+        integrator: IntegratorKind::VelocityVerlet,
+        collision_mode: CollisionMode::Ignore,
+        gravity_solver: GravitySolver::Pairwise,
+        ..EngineConfig::default()
+    };
+    let mut engine = SimulationEngine::with_bodies(config, bodies).unwrap();
+    engine
+        .add_poincare_section(PoincareSection {
+            body_ids: vec!["planet".to_string()],
+            relative_to: Some("sun".to_string()),
+            ..PoincareSection::new("heliocentric")
+        })
+        .unwrap();
+    engine
+        .add_poincare_section(PoincareSection {
+            max_samples: 2,
+            ..PoincareSection::new("lab")
+        })
+        .unwrap();
+    assert!(
+        engine
+            .add_poincare_section(PoincareSection::new("lab"))
+            .is_err()
+    );
+    assert!(
+        engine
+            .add_poincare_section(PoincareSection {
+                normal: Vec2::ZERO,
+                ..PoincareSection::new("flat")
+            })
+            .is_err()
+    );
+
+    engine.step((3.0 * TAU / 0.001) as u32).unwrap();
+    let samples = engine
+        .poincare_samples("heliocentric")
+        .unwrap()
+        .collect::<Vec<_>>();
+    assert_eq!(samples.len(), 3);
+    for (orbit, sample) in samples.iter().enumerate() {
+        assert_eq!(sample.body_id, "planet");
+        approx(sample.coordinate, 1.0, 1e-6);
+        approx(sample.tangent_velocity, 0.0, 1e-6);
+        approx(sample.normal_velocity, 1.0, 1e-6);
+        approx(sample.sim_time, TAU * (orbit + 1) as f64 - 0.1, 1e-5);
+    }
+    // The drifting pair leaves the lab-frame line behind after the first passes.
+    assert!(engine.poincare_samples("lab").unwrap().count() <= 2);
+    assert!(engine.clear_poincare_samples("heliocentric"));
+    assert_eq!(engine.poincare_samples("heliocentric").unwrap().count(), 0);
+    assert!(engine.remove_poincare_section("lab"));
+    assert!(engine.poincare_samples("lab").is_none());
+    assert_eq!(engine.poincare_sections().count(), 1);
+}
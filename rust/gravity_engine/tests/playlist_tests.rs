@@ -0,0 +1,144 @@
+use gravity_engine::{
+    Body, CollisionMode, EngineConfig, Playlist, PlaylistEntry, PlaylistRunner, PlaylistTransition,
+    Scenario, SimulationEngine, SimulationEvent, Vec2,
+};
+
+fn scenario(name: &str, dt: f64, ids: &[&str]) -> Scenario {
+    let config = EngineConfig {
+        gravity_constant: 1.0,
+        dt,
+        collision_mode: CollisionMode::Ignore,
+        ..EngineConfig::default()
+    };
+    let bodies = ids
+        .iter()
+        .enumerate()
+        .map(|(index, id)| {
+            let radius = 1.0 + index as f64;
+            Body::new(*id, 1e-3, 0.01, Vec2::new(radius, 0.0), Vec2::new(0.0, 0.5))
+        })
+        .collect();
+    let mut scenario = SimulationEngine::with_bodies(config, bodies)
+        .unwrap()
+        .save_scenario();
+    scenario.metadata.name = name.to_string();
+    scenario.checksum = Some(scenario.compute_checksum());
+    scenario
+}
+
+fn entry(scenario: Scenario, ticks: u32, transition: PlaylistTransition) -> PlaylistEntry {
+    PlaylistEntry {
+        scenario,
+        ticks,
+        transition,
+    }
+}
+
+fn transitions(events: &[SimulationEvent]) -> Vec<(usize, usize, String)> {
+    events
+        .iter()
+        .filter_map(|event| match event {
+            SimulationEvent::PlaylistAdvanced(event) => Some((
+                event.from_index,
+                event.to_index,
+                event.scenario_name.clone(),
+            )),
+            _ => None,
+        })
+        .collect()
+}
+
+#[test]
+fn playlist_runs_each_entry_for_its_budget_then_finishes() {
+    let playlist = Playlist {
+        entries: vec![
+            entry(
+                scenario("intro", 0.01, &["a", "b"]),
+                5,
+                PlaylistTransition::Reset,
+            ),
+            entry(
+                scenario("finale", 0.01, &["c"]),
+                3,
+                PlaylistTransition::Reset,
+            ),
+        ],
+        looping: false,
+    };
+    let mut runner = PlaylistRunner::new(playlist).unwrap();
+    let summary = runner.advance(100).unwrap();
+
+    assert_eq!(summary.ticks_applied, 8);
+    assert!(runner.is_finished());
+    assert_eq!(transitions(&summary.events), [(0, 1, "finale".to_string())]);
+    assert_eq!(runner.engine().tick(), 3);
+    let ids = runner
+        .engine()
+        .bodies()
+        .iter()
+        .map(|body| body.id.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(ids, ["c"]);
+    assert_eq!(runner.advance(10).unwrap().ticks_applied, 0);
+}
+
+#[test]
+fn looping_playlist_carries_and_merges_bodies_across_entries() {
+    let playlist = Playlist {
+        entries: vec![
+            entry(
+                scenario("first", 0.01, &["sun", "a"]),
+                4,
+                PlaylistTransition::Reset,
+            ),
+            entry(
+                scenario("merge", 0.01, &["sun", "m"]),
+                4,
+                PlaylistTransition::Merge,
+            ),
+            entry(
+                scenario("carry", 0.02, &["ignored"]),
+                4,
+                PlaylistTransition::CarryBodies,
+            ),
+        ],
+        looping: true,
+    };
+    let mut runner = PlaylistRunner::new(playlist).unwrap();
+
+    let mut events = Vec::new();
+    for _ in 0..3 {
+        events.extend(runner.advance(3).unwrap().events);
+    }
+    // Nine ticks: four in "first", four in "merge", one in "carry".
+    assert_eq!(runner.current_index(), 2);
+    assert_eq!(runner.engine().tick(), 9);
+    assert_eq!(runner.engine().config().dt, 0.02);
+    let mut ids = runner
+        .engine()
+        .bodies()
+        .iter()
+        .map(|body| body.id.clone())
+        .collect::<Vec<_>>();
+    ids.sort();
+    assert_eq!(ids, ["a", "m", "sun", "sun@1"]);
+
+    events.extend(runner.advance(3).unwrap().events);
+    assert!(!runner.is_finished());
+    assert_eq!(runner.current_index(), 0);
+    assert_eq!(runner.engine().tick(), 0);
+    assert_eq!(
+        transitions(&events),
+        [
+            (0, 1, "merge".to_string()),
+            (1, 2, "carry".to_string()),
+            (2, 0, "first".to_string()),
+        ]
+    );
+
+    let invalid = Playlist {
+        entries: Vec::new(),
+        looping: false,
+    };
+    assert!(PlaylistRunner::new(invalid).is_err());
+}
@@ -0,0 +1,144 @@
+use gravity_engine::{
+    Body, BodyEdit, BodyMetadata, BodyQuery, BodyQueryResult, BodyUpdate, EngineConfig,
+    EngineError, Region, SimulationEngine, Vec2,
+};
+
+fn tagged(id: &str, mass: f64, position: Vec2, tags: &[(&str, &str)]) -> Body {
+    let mut body = Body::new(id, mass, 0.01, position, Vec2::ZERO);
+    body.metadata = Some(BodyMetadata {
+        tags: tags
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect(),
+        ..BodyMetadata::default()
+    });
+    body
+}
+
+fn ids(result: BodyQueryResult) -> Vec<String> {
+    match result {
+        BodyQueryResult::Ids(ids) => ids,
+        BodyQueryResult::Bodies(bodies) => bodies.into_iter().map(|body| body.id).collect(),
+    }
+}
+
+#[test]
+fn query_bodies_filters_by_tag_mass_region_and_alive() {
+    let bodies = vec![
+        tagged("sun", 10.0, Vec2::ZERO, &[("kind", "star")]),
+        tagged(
+            "earth",
+            1.0,
+            Vec2::new(5.0, 0.0),
+            &[("kind", "planet"), ("home", "")],
+        ),
+        tagged("mars", 0.5, Vec2::new(-8.0, 1.0), &[("kind", "planet")]),
+        Body::new("rock", 0.01, 0.01, Vec2::new(0.0, 3.0), Vec2::ZERO),
+    ];
+    let mut engine = SimulationEngine::with_bodies(EngineConfig::default(), bodies).unwrap();
+
+    let planet = |value: &str| BodyQuery {
+        tags: [("kind".to_string(), value.to_string())].into(),
+        ..BodyQuery::default()
+    };
+    assert_eq!(
+        ids(engine.query_bodies(&planet("planet")).unwrap()),
+        ["earth", "mars"]
+    );
+    assert_eq!(ids(engine.query_bodies(&planet("")).unwrap()).len(), 3);
+
+    let light = BodyQuery {
+        max_mass: Some(1.0),
+        region: Some(Region::Circle {
+            center: Vec2::ZERO,
+            radius: 6.0,
+        }),
+        include_bodies: true,
+        ..BodyQuery::default()
+    };
+    let BodyQueryResult::Bodies(found) = engine.query_bodies(&light).unwrap() else {
+        panic!("expected full bodies");
+    };
+    assert_eq!(
+        found
+            .iter()
+            .map(|body| body.id.as_str())
+            .collect::<Vec<_>>(),
+        ["earth", "rock"]
+    );
+
+    engine
+        .apply_edit(BodyEdit::Update(BodyUpdate {
+            id: "mars".to_string(),
+            alive: Some(false),
+            ..BodyUpdate::default()
+        }))
+        .unwrap();
+    let alive_planets = BodyQuery {
+        alive: Some(true),
+        ..planet("planet")
+    };
+    assert_eq!(ids(engine.query_bodies(&alive_planets).unwrap()), ["earth"]);
+
+    let inverted = BodyQuery {
+        min_mass: Some(2.0),
+        max_mass: Some(1.0),
+        ..BodyQuery::default()
+    };
+    assert!(matches!(
+        engine.query_bodies(&inverted),
+        Err(EngineError::InvalidConfig(_))
+    ));
+}
+
+#[test]
+fn state_in_region_matches_a_linear_scan() {
+    let mut bodies = (0..500)
+        .map(|index| {
+            let angle = index as f64 * 2.399;
+            let position = Vec2::from_angle(angle) * (index as f64).sqrt();
+            Body::new(format!("b{index}"), 1.0, 0.01, position, Vec2::ZERO)
+        })
+        .collect::<Vec<_>>();
+    // Coincident bodies end up sharing a quadtree leaf.
+    for index in 0..3 {
+        bodies.push(Body::new(
+            format!("stack{index}"),
+            1.0,
+            0.01,
+            Vec2::new(2.0, 2.0),
+            Vec2::ZERO,
+        ));
+    }
+    bodies[7].alive = false;
+    let engine = SimulationEngine::with_bodies(EngineConfig::default(), bodies).unwrap();
+
+    for region in [
+        Region::Circle {
+            center: Vec2::new(1.5, 1.5),
+            radius: 4.0,
+        },
+        Region::Rect {
+            min: Vec2::new(-3.0, -20.0),
+            max: Vec2::new(2.5, 2.0),
+        },
+    ] {
+        let expected = engine
+            .bodies()
+            .iter()
+            .filter(|body| body.alive && region.contains(body.position))
+            .collect::<Vec<_>>();
+        let state = engine.state_in_region(&region).unwrap();
+        assert_eq!(state.bodies.iter().collect::<Vec<_>>(), expected);
+        assert!(state.bodies.iter().any(|body| body.id == "stack2"));
+        assert!(state.bodies.len() < 100);
+    }
+    let empty = Region::Circle {
+        center: Vec2::ZERO,
+        radius: 0.0,
+    };
+    assert!(matches!(
+        engine.state_in_region(&empty),
+        Err(EngineError::InvalidConfig(_))
+    ));
+}
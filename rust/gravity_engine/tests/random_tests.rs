@@ -0,0 +1,140 @@
+use gravity_engine::{
+    BodyEdit, CloudShape, CloudSpec, EngineConfig, PerturbSpec, SimulationEngine, Vec2, Xoshiro256,
+    generate_cloud,
+};
+
+fn spec(shape: CloudShape) -> CloudSpec {
+    CloudSpec {
+        id_prefix: "p".to_string(),
+        count: 200,
+        shape,
+        center: Vec2::new(10.0, -5.0),
+        body_mass: 0.5,
+        body_radius: 0.01,
+        bulk_velocity: Vec2::ZERO,
+        central_gm: None,
+        origin_group: Some(3),
+    }
+}
+
+#[test]
+fn xoshiro_stream_matches_reference_and_is_reproducible() {
+    let first = Xoshiro256::new(0).next_u64();
+    assert_eq!(Xoshiro256::new(0).next_u64(), first);
+    assert_ne!(Xoshiro256::new(1).next_u64(), first);
+
+    let mut a = Xoshiro256::new(42);
+    let mut b = Xoshiro256::new(42);
+    for _ in 0..1000 {
+        let value = a.next_f64();
+        assert!((0.0..1.0).contains(&value));
+        assert_eq!(value.to_bits(), b.next_f64().to_bits());
+    }
+}
+
+#[test]
+fn generated_clouds_respect_shape_and_tag_origin_group() {
+    let mut rng = Xoshiro256::new(7);
+    let disk = generate_cloud(&spec(CloudShape::UniformDisk { radius: 3.0 }), &mut rng).unwrap();
+    assert_eq!(disk.len(), 200);
+    assert_eq!(disk[0].id, "p0");
+    assert!(disk.iter().all(|body| body.origin_group == Some(3)));
+    assert!(
+        disk.iter()
+            .all(|body| body.position.distance(Vec2::new(10.0, -5.0)) <= 3.0)
+    );
+
+    let ring = generate_cloud(
+        &CloudSpec {
+            central_gm: Some(4.0),
+            ..spec(CloudShape::Ring {
+                inner_radius: 2.0,
+                outer_radius: 4.0,
+            })
+        },
+        &mut rng,
+    )
+    .unwrap();
+    for body in &ring {
+        let offset = body.position - Vec2::new(10.0, -5.0);
+        let radius = offset.norm();
+        assert!((2.0..=4.0).contains(&radius));
+        assert!((body.velocity.norm() - (4.0 / radius).sqrt()).abs() < 1e-12);
+        assert!(offset.cross(body.velocity) > 0.0);
+    }
+
+    let blob = generate_cloud(&spec(CloudShape::GaussianBlob { sigma: 1.0 }), &mut rng).unwrap();
+    let again = generate_cloud(
+        &spec(CloudShape::GaussianBlob { sigma: 1.0 }),
+        &mut Xoshiro256::new(7),
+    )
+    .unwrap();
+    assert_ne!(blob[0].position, again[0].position);
+
+    assert!(generate_cloud(&spec(CloudShape::UniformDisk { radius: 0.0 }), &mut rng).is_err());
+}
+
+#[test]
+fn perturb_is_seeded_and_limited_to_the_listed_bodies() {
+    let base = generate_cloud(
+        &CloudSpec {
+            count: 4,
+            ..spec(CloudShape::UniformDisk { radius: 3.0 })
+        },
+        &mut Xoshiro256::new(1),
+    )
+    .unwrap();
+    let config = EngineConfig {
+        gravity_constant: 1.0,
+        ..EngineConfig::default()
+    };
+    let perturb = PerturbSpec {
+        position_sigma: 0.01,
+        velocity_sigma: 0.001,
+        seed: 99,
+    };
+    let ids = vec!["p1".to_string(), "p3".to_string()];
+
+    let mut first = SimulationEngine::with_bodies(config.clone(), base.clone()).unwrap();
+    first.perturb(&ids, &perturb).unwrap();
+    let mut second = SimulationEngine::with_bodies(config.clone(), base.clone()).unwrap();
+    second
+        .apply_edit(BodyEdit::Perturb {
+            ids: ids.clone(),
+            spec: perturb,
+        })
+        .unwrap();
+    assert_eq!(first.bodies(), second.bodies());
+    assert_eq!(first.bodies()[0], base[0]);
+    assert_eq!(first.bodies()[2], base[2]);
+    assert_ne!(first.bodies()[1].position, base[1].position);
+    assert_ne!(first.bodies()[3].velocity, base[3].velocity);
+
+    let mut reseeded = SimulationEngine::with_bodies(config, base).unwrap();
+    reseeded
+        .perturb(
+            &[],
+            &PerturbSpec {
+                seed: 100,
+                ..perturb
+            },
+        )
+        .unwrap();
+    assert_ne!(reseeded.bodies()[1], first.bodies()[1]);
+    assert!(
+        reseeded
+            .perturb(&["missing".to_string()], &perturb)
+            .is_err()
+    );
+    assert!(
+        reseeded
+            .perturb(
+                &[],
+                &PerturbSpec {
+                    position_sigma: -1.0,
+                    ..perturb
+                }
+            )
+            .is_err()
+    );
+}
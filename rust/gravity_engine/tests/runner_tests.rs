@@ -0,0 +1,171 @@
+use std::ffi::{CStr, CString};
+use std::sync::mpsc;
+use std::time::Duration;
+
+use gravity_engine::ffi::{
+    gs_apply_edit, gs_dispose, gs_initialize, gs_poll_result, gs_set_paused, gs_step,
+    gs_step_async, gs_string_free,
+};
+use gravity_engine::{
+    Body, BodyEdit, CollisionMode, EngineConfig, EngineError, EngineRunner, GravitySolver,
+    RunnerCommand, RunnerOutput, SimulationEngine, Vec2,
+};
+
+fn base_config() -> EngineConfig {
+    EngineConfig {
+        gravity_constant: 1.0,
+        softening_epsilon: 1e-6,
+        dt: 0.001,
+        collision_mode: CollisionMode::Ignore,
+        gravity_solver: GravitySolver::Pairwise,
+        ..EngineConfig::default()
+    }
+}
+
+fn pair() -> Vec<Body> {
+    vec![
+        Body::new("a", 1.0, 0.1, Vec2::new(-1.0, 0.0), Vec2::new(0.0, -0.5)),
+        Body::new("b", 1.0, 0.1, Vec2::new(1.0, 0.0), Vec2::new(0.0, 0.5)),
+    ]
+}
+
+#[test]
+fn runner_matches_synchronous_stepping_in_submission_order() {
+    let mut reference = SimulationEngine::with_bodies(base_config(), pair()).unwrap();
+    reference.step(30).unwrap();
+
+    let engine = SimulationEngine::with_bodies(base_config(), pair()).unwrap();
+    let mut runner = EngineRunner::spawn(engine);
+    let jobs = [
+        runner.submit(RunnerCommand::Step { ticks: 10 }).unwrap(),
+        runner.submit(RunnerCommand::Step { ticks: 20 }).unwrap(),
+        runner
+            .submit(RunnerCommand::ApplyEdit {
+                edit: Box::new(BodyEdit::Delete {
+                    id: "ghost".to_string(),
+                }),
+            })
+            .unwrap(),
+        runner.submit(RunnerCommand::Snapshot).unwrap(),
+    ];
+
+    let replies = (0..jobs.len())
+        .map(|_| runner.recv().unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        replies.iter().map(|reply| reply.job).collect::<Vec<_>>(),
+        jobs
+    );
+    assert!(matches!(
+        &replies[1].result,
+        Ok(RunnerOutput::Stepped { state, .. }) if state.bodies == reference.bodies()
+    ));
+    assert!(matches!(
+        replies[2].result,
+        Err(EngineError::BodyNotFound(_))
+    ));
+    assert!(matches!(
+        &replies[3].result,
+        Ok(RunnerOutput::Snapshot { snapshot }) if snapshot.tick == 30
+    ));
+    assert_eq!(runner.pending(), 0);
+    assert!(runner.recv().is_none());
+
+    let engine = runner.shutdown().unwrap();
+    assert_eq!(engine.bodies(), reference.bodies());
+}
+
+#[test]
+fn runner_callback_receives_replies_on_the_worker() {
+    let (sender, receiver) = mpsc::channel();
+    let engine = SimulationEngine::with_bodies(base_config(), pair()).unwrap();
+    let mut runner = EngineRunner::spawn_with_callback(engine, move |reply| {
+        sender.send(reply.job).unwrap();
+    });
+    let job = runner.submit(RunnerCommand::Step { ticks: 5 }).unwrap();
+
+    assert_eq!(receiver.recv_timeout(Duration::from_secs(5)), Ok(job));
+    assert!(runner.try_recv().is_none());
+    assert_eq!(runner.shutdown().unwrap().tick(), 5);
+}
+
+#[test]
+fn ffi_async_step_hands_the_engine_back_once_drained() {
+    let take = |ptr: *mut std::os::raw::c_char| {
+        let text = unsafe { CStr::from_ptr(ptr) }.to_str().unwrap().to_string();
+        gs_string_free(ptr);
+        serde_json::from_str::<serde_json::Value>(&text).unwrap()
+    };
+    let config = CString::new(serde_json::to_string(&base_config()).unwrap()).unwrap();
+    let bodies = CString::new(serde_json::to_string(&pair()).unwrap()).unwrap();
+    let handle = take(gs_initialize(config.as_ptr(), bodies.as_ptr()))["data"]["handle"]
+        .as_u64()
+        .unwrap();
+
+    let first = take(gs_step_async(handle, 40));
+    assert_eq!(first["ok"], true);
+    take(gs_step_async(handle, 2));
+    assert_eq!(take(gs_step(handle, 1))["ok"], false);
+    let edit = CString::new(
+        serde_json::to_string(&BodyEdit::Delete {
+            id: "a".to_string(),
+        })
+        .unwrap(),
+    )
+    .unwrap();
+    let rejected = take(gs_apply_edit(handle, edit.as_ptr()));
+    assert_eq!(rejected["errorCode"], "Busy");
+    assert_eq!(take(gs_set_paused(handle, true))["errorCode"], "Busy");
+
+    let mut finished = Vec::new();
+    while finished.len() < 2 {
+        let polled = take(gs_poll_result(handle));
+        assert_eq!(polled["ok"], true);
+        if polled["data"]["job"].is_null() {
+            std::thread::yield_now();
+            continue;
+        }
+        finished.push(polled["data"].clone());
+    }
+    assert_eq!(finished[0]["job"], first["data"]["job"]);
+    assert_eq!(finished[0]["summary"]["ticksApplied"], 40);
+    assert_eq!(finished[1]["pending"], 0);
+    assert_eq!(finished[1]["state"]["tick"], 42);
+
+    assert_eq!(take(gs_poll_result(handle))["ok"], false);
+    assert_eq!(take(gs_step(handle, 1))["data"]["state"]["tick"], 43);
+    take(gs_dispose(handle));
+}
+
+#[test]
+fn paused_engines_take_edits_but_not_ticks() {
+    let mut engine = SimulationEngine::with_bodies(base_config(), pair()).unwrap();
+    engine.set_paused(true);
+    let summary = engine.step(10).unwrap();
+    assert_eq!(summary.ticks_applied, 0);
+    assert_eq!(summary.stop_reason.as_deref(), Some("paused"));
+    engine
+        .apply_edit(BodyEdit::Delete {
+            id: "b".to_string(),
+        })
+        .unwrap();
+    assert_eq!(engine.tick(), 0);
+    assert_eq!(engine.export_journal().entries.len(), 1);
+
+    engine.set_paused(false);
+    let mut runner = EngineRunner::spawn(engine);
+    runner.submit(RunnerCommand::Step { ticks: 30 }).unwrap();
+    let config = Box::new(EngineConfig {
+        dt: 0.002,
+        ..base_config()
+    });
+    let queued = runner.submit(RunnerCommand::SetConfig { config }).unwrap();
+    let replies: Vec<_> = std::iter::from_fn(|| runner.recv()).collect();
+    assert_eq!(replies[1].job, queued);
+    let Ok(RunnerOutput::Edited { state }) = &replies[1].result else {
+        panic!("config change failed: {:?}", replies[1].result);
+    };
+    // The config change waited for the whole batch.
+    assert_eq!(state.tick, 30);
+    assert_eq!(runner.shutdown().unwrap().config().dt, 0.002);
+}
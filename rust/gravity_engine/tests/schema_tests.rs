@@ -0,0 +1,17 @@
+#![cfg(feature = "schema")]
+
+use gravity_engine::schema::{SCHEMA_NAMES, all_json_schemas, json_schema};
+
+#[test]
+fn schemas_use_wire_field_names() {
+    let config = json_schema("engineConfig").unwrap();
+    let properties = config["properties"].as_object().unwrap();
+    assert!(properties.contains_key("gravityConstant"));
+    assert!(properties.contains_key("barnesHutTheta"));
+
+    let edit = serde_json::to_string(&json_schema("bodyEdit").unwrap()).unwrap();
+    assert!(edit.contains("\"create\"") && edit.contains("\"delete\""));
+
+    assert_eq!(all_json_schemas().len(), SCHEMA_NAMES.len());
+    assert!(json_schema("unknown").is_err());
+}
@@ -0,0 +1,77 @@
+use gravity_engine::{
+    CollisionMode, EngineConfig, GravitySolver, SimulationEngine, StableSearch, search_stable,
+};
+
+fn base_config() -> EngineConfig {
+    EngineConfig {
+        gravity_constant: 1.0,
+        softening_epsilon: 1e-3,
+        dt: 0.001,
+        collision_mode: CollisionMode::InelasticMerge,
+        gravity_solver: GravitySolver::Pairwise,
+        ..EngineConfig::default()
+    }
+}
+
+fn search() -> StableSearch {
+    StableSearch {
+        seed: 11,
+        candidates: 12,
+        body_count: 3,
+        total_mass: 1.0,
+        bounding_radius: 2.0,
+        trial_ticks: 400,
+        keep: 4,
+        max_energy_drift: 1e-3,
+        mass_spread: 0.5,
+    }
+}
+
+#[test]
+fn search_ranks_stable_candidates_first_and_is_reproducible() {
+    let config = base_config();
+    let best = search_stable(&config, &search()).unwrap();
+    assert_eq!(best.len(), 4);
+    assert!(best[0].score.stable);
+    for pair in best.windows(2) {
+        assert!(pair[0].score.stable >= pair[1].score.stable);
+    }
+    for candidate in best.iter().filter(|candidate| candidate.score.stable) {
+        assert_eq!(candidate.score.merges, 0);
+        assert_eq!(candidate.score.escaped, 0);
+        assert!(candidate.score.energy_drift <= 1e-3);
+        assert!(candidate.score.max_extent <= 2.0);
+    }
+    assert_eq!(search_stable(&config, &search()).unwrap(), best);
+
+    let scenario = best[0].scenario.clone();
+    scenario.verify_checksum().unwrap();
+    assert_eq!(
+        search()
+            .sample(best[0].seed, config.gravity_constant)
+            .unwrap(),
+        scenario.bodies
+    );
+    let total_mass = scenario.bodies.iter().map(|body| body.mass).sum::<f64>();
+    assert!((total_mass - 1.0).abs() < 1e-12);
+
+    let mut engine = SimulationEngine::initialize(config).unwrap();
+    engine.load_scenario(scenario).unwrap();
+    let momentum = engine.diagnostics().linear_momentum;
+    assert!(momentum.norm() < 1e-12);
+}
+
+#[test]
+fn search_rejects_degenerate_requests() {
+    let config = base_config();
+    let single = StableSearch {
+        body_count: 1,
+        ..search()
+    };
+    assert!(search_stable(&config, &single).is_err());
+    let spread = StableSearch {
+        mass_spread: 1.0,
+        ..search()
+    };
+    assert!(search_stable(&config, &spread).is_err());
+}
@@ -0,0 +1,123 @@
+use gravity_engine::{
+    Body, EngineConfig, SimulationEngine, SoftBodyShape, SoftBodySpec, Vec2, build_soft_body,
+};
+
+const STAR_MASS: f64 = 50.0;
+
+fn ring_spec(center: Vec2) -> SoftBodySpec {
+    SoftBodySpec {
+        id_prefix: "blob-".to_string(),
+        shape: SoftBodyShape::Ring {
+            count: 12,
+            radius: 0.5,
+        },
+        center,
+        velocity: Vec2::ZERO,
+        total_mass: 1e-3,
+        particle_radius: 0.01,
+        stiffness: 1e-3,
+        origin_group: 7,
+    }
+}
+
+fn ellipticity_after(star_mass: Option<f64>, ticks: u32) -> f64 {
+    let config = EngineConfig {
+        gravity_constant: 1.0,
+        softening_epsilon: 1e-3,
+        dt: 0.001,
+        ..EngineConfig::default()
+    };
+    let bodies = star_mass
+        .map(|mass| {
+            vec![Body {
+                fixed: true,
+                ..Body::new("star", mass, 0.1, Vec2::ZERO, Vec2::ZERO)
+            }]
+        })
+        .unwrap_or_default();
+    let mut engine = SimulationEngine::with_bodies(config, bodies).unwrap();
+    engine
+        .add_soft_body(
+            "blob",
+            &SoftBodySpec {
+                // Circular about the star when it is present.
+                velocity: Vec2::new(0.0, (STAR_MASS / 4.0).sqrt()),
+                ..ring_spec(Vec2::new(4.0, 0.0))
+            },
+        )
+        .unwrap();
+    engine.step(ticks).unwrap();
+    let groups = engine.group_diagnostics();
+    assert_eq!(groups.len(), 1);
+    assert_eq!(groups[0].body_count, 12);
+    groups[0].ellipticity
+}
+
+#[test]
+fn ring_and_lattice_are_linked_at_rest() {
+    let ring = build_soft_body(&ring_spec(Vec2::ZERO)).unwrap();
+    assert_eq!(ring.bodies.len(), 12);
+    assert_eq!(ring.springs.springs.len(), 24);
+    assert!(ring.bodies.iter().all(|body| body.origin_group == Some(7)));
+
+    let lattice = build_soft_body(&SoftBodySpec {
+        shape: SoftBodyShape::Lattice {
+            columns: 3,
+            rows: 2,
+            spacing: 0.2,
+        },
+        ..ring_spec(Vec2::ZERO)
+    })
+    .unwrap();
+    // 3 + 4 edges and 2 crossed cells.
+    assert_eq!(lattice.springs.springs.len(), 11);
+    for spring in &lattice.springs.springs {
+        let position = |id: &str| {
+            lattice
+                .bodies
+                .iter()
+                .find(|body| body.id == id)
+                .unwrap()
+                .position
+        };
+        let length = (position(&spring.second) - position(&spring.first)).norm();
+        assert!((length - spring.rest_length).abs() < 1e-12);
+    }
+
+    let degenerate = SoftBodySpec {
+        shape: SoftBodyShape::Ring {
+            count: 2,
+            radius: 0.5,
+        },
+        ..ring_spec(Vec2::ZERO)
+    };
+    assert!(build_soft_body(&degenerate).is_err());
+}
+
+#[test]
+fn tides_stretch_a_soft_ring() {
+    let isolated = ellipticity_after(None, 1500);
+    let tidal = ellipticity_after(Some(STAR_MASS), 1500);
+    assert!(isolated < 1e-6, "isolated ring deformed: {isolated}");
+    assert!(tidal > 0.05, "tidal ellipticity {tidal}");
+}
+
+#[test]
+fn soft_body_ids_and_provider_names_must_be_free() {
+    let mut engine = SimulationEngine::initialize(EngineConfig::default()).unwrap();
+    engine
+        .add_soft_body("blob", &ring_spec(Vec2::ZERO))
+        .unwrap();
+    assert!(
+        engine
+            .add_soft_body("blob", &ring_spec(Vec2::ZERO))
+            .is_err()
+    );
+    assert!(
+        engine
+            .add_soft_body("other", &ring_spec(Vec2::ZERO))
+            .is_err()
+    );
+    assert_eq!(engine.bodies().len(), 12);
+    assert!(engine.remove_force_provider("blob"));
+}
@@ -0,0 +1,74 @@
+use gravity_engine::{
+    Body, CollisionMode, EngineConfig, GravitySolver, SimulationEngine, StressWorkload, Vec2,
+    run_stress,
+};
+
+fn engine() -> SimulationEngine {
+    let config = EngineConfig {
+        gravity_constant: 1.0,
+        dt: 1e-3,
+        collision_mode: CollisionMode::Ignore,
+        gravity_solver: GravitySolver::Pairwise,
+        ..EngineConfig::default()
+    };
+    let sun = Body::new("sun", 1.0, 0.1, Vec2::ZERO, Vec2::ZERO);
+    SimulationEngine::with_bodies(config, vec![sun]).unwrap()
+}
+
+fn workload() -> StressWorkload {
+    StressWorkload {
+        seed: 17,
+        rounds: 20,
+        spawns_per_round: 3,
+        deletes_per_round: 2,
+        config_flip_every: 5,
+        step_batch: 2,
+        spawn_extent: 10.0,
+        spawn_mass: 1e-3,
+    }
+}
+
+#[test]
+fn stress_reports_each_operation_and_keeps_the_scenario() {
+    let mut engine = engine();
+    let report = run_stress(&mut engine, &workload()).unwrap();
+
+    let counts = report
+        .operations
+        .iter()
+        .map(|latency| (latency.operation.as_str(), latency.count))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        counts,
+        [
+            ("spawn", 60),
+            ("delete", 40),
+            ("configFlip", 4),
+            ("step", 20)
+        ]
+    );
+    for latency in &report.operations {
+        assert!(latency.p50_micros <= latency.p90_micros);
+        assert!(latency.p90_micros <= latency.p99_micros);
+        assert!(latency.p99_micros <= latency.max_micros);
+    }
+    assert_eq!(report.ticks, 40);
+    assert_eq!(report.final_body_count, 21);
+    assert!(engine.bodies().iter().any(|body| body.id == "sun"));
+    assert_eq!(engine.config().gravity_solver, GravitySolver::Pairwise);
+}
+
+#[test]
+fn stress_edit_stream_is_deterministic() {
+    let mut first = engine();
+    let mut second = engine();
+    run_stress(&mut first, &workload()).unwrap();
+    run_stress(&mut second, &workload()).unwrap();
+    assert_eq!(first.bodies(), second.bodies());
+
+    let invalid = StressWorkload {
+        step_batch: 0,
+        ..workload()
+    };
+    assert!(run_stress(&mut first, &invalid).is_err());
+}
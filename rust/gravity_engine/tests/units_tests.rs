@@ -0,0 +1,118 @@
+use std::f64::consts::PI;
+
+use gravity_engine::{
+    Body, Dimension, EngineConfig, EngineError, PhysicalConstants, Scenario, SimulationEngine,
+    UnitSystem, Vec2,
+};
+use serde_json::{Value, json};
+
+fn solar_units() -> UnitSystem {
+    UnitSystem {
+        length: "au".to_string(),
+        mass: "Msun".to_string(),
+        time: "yr".to_string(),
+        constants: PhysicalConstants::default(),
+    }
+}
+
+fn earth_scenario_json(units: &UnitSystem) -> Value {
+    let bodies = vec![
+        Body::new("sun", 1.0, 0.01, Vec2::ZERO, Vec2::ZERO),
+        Body::new("earth", 1.0, 0.01, Vec2::ZERO, Vec2::ZERO),
+    ];
+    let engine = SimulationEngine::with_bodies(EngineConfig::default(), bodies).unwrap();
+    let mut scenario = serde_json::to_value(engine.save_scenario()).unwrap();
+    scenario["checksum"] = Value::Null;
+    scenario["units"] = serde_json::to_value(units).unwrap();
+    scenario["engineConfig"]["gravityConstant"] = json!("G");
+    scenario["engineConfig"]["dt"] = json!("1 d");
+    scenario["bodies"][0]["mass"] = json!("1 Msun");
+    scenario["bodies"][0]["radius"] = json!("1 Rsun");
+    scenario["bodies"][1]["mass"] = json!("1 Mearth");
+    scenario["bodies"][1]["position"]["x"] = json!("1 au");
+    scenario["bodies"][1]["velocity"]["y"] = json!("29.78 km/s");
+    scenario
+}
+
+#[test]
+fn quantities_convert_into_the_declared_units() {
+    let units = solar_units();
+    assert_eq!(units.parse_quantity("2.5", Dimension::Length).unwrap(), 2.5);
+    assert!((units.parse_quantity("1 pc", Dimension::Length).unwrap() - 206_264.806).abs() < 1e-3);
+    assert!(
+        (units
+            .parse_quantity("1047.6 Mjup", Dimension::Mass)
+            .unwrap()
+            - 1.0)
+            .abs()
+            < 1e-3
+    );
+    assert!((units.parse_quantity("365.25 d", Dimension::Time).unwrap() - 1.0).abs() < 1e-12);
+    assert!((units.gravity_constant().unwrap() / (4.0 * PI * PI) - 1.0).abs() < 1e-4);
+
+    let si = UnitSystem::default();
+    assert_eq!(
+        si.parse_quantity("3 km/s", Dimension::Velocity).unwrap(),
+        3000.0
+    );
+    assert_eq!(
+        si.parse_quantity("3 km*s^-1", Dimension::Velocity).unwrap(),
+        3000.0
+    );
+    assert_eq!(si.gravity_constant().unwrap(), 6.674_30e-11);
+
+    for (text, dimension) in [
+        ("1 au", Dimension::Mass),
+        ("1 furlong", Dimension::Length),
+        ("au", Dimension::Length),
+        ("1 m/s^x", Dimension::Velocity),
+    ] {
+        assert!(
+            matches!(
+                si.parse_quantity(text, dimension),
+                Err(EngineError::InvalidConfig(_))
+            ),
+            "{text}"
+        );
+    }
+}
+
+#[test]
+fn scenario_quantity_strings_resolve_on_load_and_units_round_trip() {
+    let units = solar_units();
+    let scenario = Scenario::from_json(earth_scenario_json(&units)).unwrap();
+    let config = &scenario.engine_config;
+    assert!((config.gravity_constant / (4.0 * PI * PI) - 1.0).abs() < 1e-4);
+    assert!((config.dt - 1.0 / 365.25).abs() < 1e-15);
+    let earth = &scenario.bodies[1];
+    assert_eq!(earth.position, Vec2::new(1.0, 0.0));
+    assert!((earth.mass - 3.003e-6).abs() < 1e-8);
+    // Circular speed at 1 au is 2 pi au/yr.
+    assert!((earth.velocity.y / (2.0 * PI) - 1.0).abs() < 1e-3);
+
+    let mut engine = SimulationEngine::initialize(EngineConfig::default()).unwrap();
+    engine.load_scenario(scenario).unwrap();
+    assert_eq!(engine.units(), Some(&units));
+    let saved = engine.save_scenario();
+    assert_eq!(saved.units.as_ref(), Some(&units));
+    saved.verify_checksum().unwrap();
+    let mut tampered = saved.clone();
+    tampered.units.as_mut().unwrap().length = "km".to_string();
+    assert!(tampered.verify_checksum().is_err());
+
+    // A shorter year changes what "1 d" means in years.
+    let calendar = UnitSystem {
+        constants: PhysicalConstants {
+            year: 365.0 * 86_400.0,
+            ..PhysicalConstants::default()
+        },
+        ..units
+    };
+    let scenario = Scenario::from_json(earth_scenario_json(&calendar)).unwrap();
+    assert!((scenario.engine_config.dt - 1.0 / 365.0).abs() < 1e-15);
+
+    let mut bad = earth_scenario_json(&calendar);
+    bad["bodies"][1]["mass"] = json!("1 km");
+    let error = Scenario::from_json(bad).unwrap_err();
+    assert!(error.to_string().contains("mass"), "{error}");
+}
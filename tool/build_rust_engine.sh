@@ -69,6 +69,10 @@ abi_folder_for_target() {
 }
 
 pushd "$CRATE_DIR" >/dev/null
+if command -v cbindgen >/dev/null 2>&1; then
+  cbindgen --config cbindgen.toml --output include/gravity_engine.h
+  echo "Generated: $CRATE_DIR/include/gravity_engine.h"
+fi
 for target in "${TARGETS[@]}"; do
   abi_folder="$(abi_folder_for_target "$target")"
   if [[ -z "$abi_folder" ]]; then